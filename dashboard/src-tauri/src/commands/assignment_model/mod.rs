@@ -16,12 +16,14 @@ pub mod auto_safe;
 pub mod config;
 pub mod context;
 pub mod folder_scan;
+pub mod rules;
 pub mod scoring;
 pub mod training;
 
 pub use auto_safe::*;
 pub use config::*;
 pub use folder_scan::*;
+pub use rules::*;
 pub use scoring::*;
 pub use training::*;
 
@@ -0,0 +1,733 @@
+//! Saved assignment rules (m32).
+//!
+//! A rule reads "app X with a title / path matching regex Y in date range Z →
+//! project P". Sessions are matched through their own app and date, and —
+//! when a pattern is set — through the file activities overlapping them
+//! (file name, path, window title, detected path, title history).
+//!
+//! Applying a rule by hand goes through the regular manual assignment path
+//! (journal, audit, manual override) with feedback source `assignment_rule`.
+//! Rules kept active also run over unassigned sessions before the AI model;
+//! like the deterministic layer they only fill gaps and never touch sessions
+//! with a manual override (source `assignment_rule_auto`).
+
+use regex::{Regex, RegexBuilder};
+use rusqlite::{Connection, ToSql};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle};
+
+use crate::commands::assignment_model::{
+    auto_safe::assign_file_activities_for_window, config::is_project_active,
+    scoring::check_manual_override,
+};
+use crate::commands::helpers::run_db_blocking;
+use crate::commands::mutation_journal::Recorder;
+use crate::commands::sessions::{assign_session_to_project_tx, track_session_assignment};
+use crate::commands::sql_fragments::ACTIVE_SESSION_FILTER_S;
+
+const RULE_SOURCE: &str = "assignment_rule";
+const RULE_AUTO_SOURCE: &str = "assignment_rule_auto";
+/// Sessions listed in a preview; the totals always cover every match.
+const PREVIEW_LIST_LIMIT: usize = 500;
+const PATTERN_SIZE_LIMIT: usize = 1 << 20;
+
+const RULE_COLUMNS: &str = "r.id, r.name, r.app_id, a.executable_name, r.pattern, r.date_from, \
+     r.date_to, r.project_id, p.name, r.keep_active, r.last_applied_at, r.created_at, r.updated_at";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssignmentRuleInput {
+    pub name: String,
+    /// `None` = any app.
+    pub app_id: Option<i64>,
+    /// Case-insensitive regex over titles and paths; `None` = no condition.
+    pub pattern: Option<String>,
+    /// Inclusive `YYYY-MM-DD` bounds.
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub project_id: i64,
+    #[serde(default)]
+    pub keep_active: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssignmentRule {
+    pub id: i64,
+    pub name: String,
+    pub app_id: Option<i64>,
+    pub app_name: Option<String>,
+    pub pattern: Option<String>,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub project_id: i64,
+    pub project_name: Option<String>,
+    pub keep_active: bool,
+    pub last_applied_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssignmentRuleMatch {
+    pub session_id: i64,
+    pub app_id: i64,
+    pub app_name: String,
+    pub date: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub duration_seconds: i64,
+    pub project_id: Option<i64>,
+    pub project_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignmentRulePreview {
+    pub session_count: i64,
+    pub total_seconds: i64,
+    /// Sessions currently assigned elsewhere — applying the rule moves them.
+    pub reassigned_count: i64,
+    pub sessions: Vec<AssignmentRuleMatch>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct AssignmentRuleApplyResult {
+    pub rules_applied: i64,
+    pub sessions_assigned: i64,
+    pub seconds_assigned: i64,
+}
+
+fn normalize_date(value: Option<String>, label: &str) -> Result<Option<String>, String> {
+    let Some(value) = value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    else {
+        return Ok(None);
+    };
+    chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid {} date: {}", label, value))?;
+    Ok(Some(value))
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("Invalid rule pattern: {}", e))
+}
+
+/// Trims and validates `input`; the target project must be active.
+fn normalize_input(
+    conn: &Connection,
+    input: AssignmentRuleInput,
+) -> Result<AssignmentRuleInput, String> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Rule name cannot be empty".to_string());
+    }
+    let pattern = input
+        .pattern
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    if let Some(pattern) = &pattern {
+        compile_pattern(pattern)?;
+    }
+    let date_from = normalize_date(input.date_from, "start")?;
+    let date_to = normalize_date(input.date_to, "end")?;
+    if let (Some(from), Some(to)) = (&date_from, &date_to) {
+        if from > to {
+            return Err("Rule date range is reversed".to_string());
+        }
+    }
+    if let Some(app_id) = input.app_id {
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM applications WHERE id = ?1",
+                [app_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if !exists {
+            return Err(format!("Application {} not found", app_id));
+        }
+    }
+    if !is_project_active(conn, input.project_id) {
+        return Err("Target project does not exist, is frozen or excluded".to_string());
+    }
+    Ok(AssignmentRuleInput {
+        name,
+        app_id: input.app_id,
+        pattern,
+        date_from,
+        date_to,
+        project_id: input.project_id,
+        keep_active: input.keep_active,
+    })
+}
+
+fn row_to_rule(row: &rusqlite::Row<'_>) -> rusqlite::Result<AssignmentRule> {
+    Ok(AssignmentRule {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        app_id: row.get(2)?,
+        app_name: row.get(3)?,
+        pattern: row.get(4)?,
+        date_from: row.get(5)?,
+        date_to: row.get(6)?,
+        project_id: row.get(7)?,
+        project_name: row.get(8)?,
+        keep_active: row.get::<_, i64>(9)? != 0,
+        last_applied_at: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+    })
+}
+
+fn query_rules(
+    conn: &Connection,
+    sql_where: &str,
+    params: &[&dyn ToSql],
+) -> Result<Vec<AssignmentRule>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}
+             FROM assignment_rules r
+             LEFT JOIN applications a ON a.id = r.app_id
+             LEFT JOIN projects p ON p.id = r.project_id
+             WHERE {}
+             ORDER BY r.id",
+            RULE_COLUMNS, sql_where
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params, row_to_rule)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read assignment rule row: {}", e))
+}
+
+fn load_rule(conn: &Connection, rule_id: i64) -> Result<AssignmentRule, String> {
+    query_rules(conn, "r.id = ?1", &[&rule_id])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Assignment rule {} not found", rule_id))
+}
+
+impl From<&AssignmentRule> for AssignmentRuleInput {
+    fn from(rule: &AssignmentRule) -> Self {
+        Self {
+            name: rule.name.clone(),
+            app_id: rule.app_id,
+            pattern: rule.pattern.clone(),
+            date_from: rule.date_from.clone(),
+            date_to: rule.date_to.clone(),
+            project_id: rule.project_id,
+            keep_active: rule.keep_active,
+        }
+    }
+}
+
+pub fn create_rule_sync(
+    conn: &Connection,
+    input: AssignmentRuleInput,
+) -> Result<AssignmentRule, String> {
+    let rule = normalize_input(conn, input)?;
+    conn.execute(
+        "INSERT INTO assignment_rules (name, app_id, pattern, date_from, date_to, project_id, keep_active)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            rule.name,
+            rule.app_id,
+            rule.pattern,
+            rule.date_from,
+            rule.date_to,
+            rule.project_id,
+            rule.keep_active as i64,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    load_rule(conn, conn.last_insert_rowid())
+}
+
+pub fn update_rule_sync(
+    conn: &Connection,
+    rule_id: i64,
+    input: AssignmentRuleInput,
+) -> Result<AssignmentRule, String> {
+    let rule = normalize_input(conn, input)?;
+    let updated = conn
+        .execute(
+            "UPDATE assignment_rules
+             SET name = ?1, app_id = ?2, pattern = ?3, date_from = ?4, date_to = ?5,
+                 project_id = ?6, keep_active = ?7, updated_at = datetime('now')
+             WHERE id = ?8",
+            rusqlite::params![
+                rule.name,
+                rule.app_id,
+                rule.pattern,
+                rule.date_from,
+                rule.date_to,
+                rule.project_id,
+                rule.keep_active as i64,
+                rule_id,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Assignment rule {} not found", rule_id));
+    }
+    load_rule(conn, rule_id)
+}
+
+/// Visible sessions the rule would assign, oldest first. Sessions already on
+/// the target project are left out; `only_unassigned` also skips sessions
+/// assigned to another project.
+pub fn matching_sessions(
+    conn: &Connection,
+    rule: &AssignmentRuleInput,
+    only_unassigned: bool,
+) -> Result<Vec<AssignmentRuleMatch>, String> {
+    let mut sql = format!(
+        "SELECT s.id, s.app_id, COALESCE(NULLIF(a.display_name, ''), a.executable_name),
+                s.date, s.start_time, s.end_time, s.duration_seconds, s.project_id, p.name
+         FROM sessions s
+         JOIN applications a ON a.id = s.app_id
+         LEFT JOIN projects p ON p.id = s.project_id
+         WHERE {ACTIVE_SESSION_FILTER_S}
+           AND (s.project_id IS NULL OR s.project_id != ?1)",
+    );
+    let mut params: Vec<Box<dyn ToSql>> = vec![Box::new(rule.project_id)];
+    if only_unassigned {
+        sql.push_str(" AND s.project_id IS NULL");
+    }
+    if let Some(app_id) = rule.app_id {
+        params.push(Box::new(app_id));
+        sql.push_str(&format!(" AND s.app_id = ?{}", params.len()));
+    }
+    if let Some(from) = &rule.date_from {
+        params.push(Box::new(from.clone()));
+        sql.push_str(&format!(" AND s.date >= ?{}", params.len()));
+    }
+    if let Some(to) = &rule.date_to {
+        params.push(Box::new(to.clone()));
+        sql.push_str(&format!(" AND s.date <= ?{}", params.len()));
+    }
+    sql.push_str(" ORDER BY s.start_time, s.id");
+
+    let candidates = {
+        let params_ref: Vec<&dyn ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params_ref.as_slice(), |row| {
+                Ok(AssignmentRuleMatch {
+                    session_id: row.get(0)?,
+                    app_id: row.get(1)?,
+                    app_name: row.get(2)?,
+                    date: row.get(3)?,
+                    start_time: row.get(4)?,
+                    end_time: row.get(5)?,
+                    duration_seconds: row.get(6)?,
+                    project_id: row.get(7)?,
+                    project_name: row.get(8)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read rule candidate row: {}", e))?
+    };
+
+    let Some(pattern) = &rule.pattern else {
+        return Ok(candidates);
+    };
+    let regex = compile_pattern(pattern)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT file_name, file_path, window_title, detected_path, title_history
+             FROM file_activities
+             WHERE app_id = ?1
+               AND date = ?2
+               AND last_seen > ?3
+               AND first_seen < ?4",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut matched = Vec::new();
+    for session in candidates {
+        let mut rows = stmt
+            .query(rusqlite::params![
+                session.app_id,
+                session.date,
+                session.start_time,
+                session.end_time
+            ])
+            .map_err(|e| e.to_string())?;
+        let mut hit = false;
+        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+            for idx in 0..5 {
+                let text: Option<String> = row.get(idx).map_err(|e| e.to_string())?;
+                if text.is_some_and(|t| regex.is_match(&t)) {
+                    hit = true;
+                    break;
+                }
+            }
+            if hit {
+                break;
+            }
+        }
+        if hit {
+            matched.push(session);
+        }
+    }
+    Ok(matched)
+}
+
+pub fn preview_rule_sync(
+    conn: &Connection,
+    input: AssignmentRuleInput,
+    only_unassigned: bool,
+) -> Result<AssignmentRulePreview, String> {
+    let rule = normalize_input(conn, input)?;
+    let mut sessions = matching_sessions(conn, &rule, only_unassigned)?;
+    let session_count = sessions.len() as i64;
+    let total_seconds = sessions.iter().map(|s| s.duration_seconds).sum();
+    let reassigned_count = sessions.iter().filter(|s| s.project_id.is_some()).count() as i64;
+    let truncated = sessions.len() > PREVIEW_LIST_LIMIT;
+    sessions.truncate(PREVIEW_LIST_LIMIT);
+    Ok(AssignmentRulePreview {
+        session_count,
+        total_seconds,
+        reassigned_count,
+        sessions,
+        truncated,
+    })
+}
+
+/// Assigns every match of the rule as a manual, undoable bulk assignment.
+pub fn apply_rule_sync(
+    conn: &mut Connection,
+    rule_id: i64,
+    only_unassigned: bool,
+) -> Result<AssignmentRuleApplyResult, String> {
+    let rule = load_rule(conn, rule_id)?;
+    if !is_project_active(conn, rule.project_id) {
+        return Err("Target project does not exist, is frozen or excluded".to_string());
+    }
+    let matches = matching_sessions(conn, &AssignmentRuleInput::from(&rule), only_unassigned)?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new(
+        "apply_assignment_rule",
+        format!(
+            "{}: {} sessions → {}",
+            rule.name,
+            matches.len(),
+            rule.project_name.as_deref().unwrap_or("?")
+        ),
+    );
+    for session in &matches {
+        track_session_assignment(&mut journal, &tx, session.session_id)?;
+    }
+    for session in &matches {
+        assign_session_to_project_tx(
+            &tx,
+            session.session_id,
+            Some(rule.project_id),
+            Some(RULE_SOURCE),
+        )?;
+    }
+    tx.execute(
+        "UPDATE assignment_rules SET last_applied_at = datetime('now') WHERE id = ?1",
+        [rule_id],
+    )
+    .map_err(|e| e.to_string())?;
+    journal.finish(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(AssignmentRuleApplyResult {
+        rules_applied: 1,
+        sessions_assigned: matches.len() as i64,
+        seconds_assigned: matches.iter().map(|s| s.duration_seconds).sum(),
+    })
+}
+
+/// Runs the `keep_active` rules over unassigned sessions, in rule order —
+/// the first matching rule wins. Sessions with a manual override are left to
+/// the override.
+pub fn apply_active_rules_sync(conn: &mut Connection) -> Result<AssignmentRuleApplyResult, String> {
+    let rules = query_rules(conn, "r.keep_active = 1", &[])?;
+    let mut result = AssignmentRuleApplyResult::default();
+    if rules.is_empty() {
+        return Ok(result);
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for rule in &rules {
+        if !is_project_active(&tx, rule.project_id) {
+            continue;
+        }
+        let mut assigned = 0;
+        for session in matching_sessions(&tx, &AssignmentRuleInput::from(rule), true)? {
+            if check_manual_override(&tx, session.session_id).is_some() {
+                continue;
+            }
+            let updated = tx
+                .execute(
+                    "UPDATE sessions SET project_id = ?1 WHERE id = ?2 AND project_id IS NULL",
+                    rusqlite::params![rule.project_id, session.session_id],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                continue;
+            }
+            assign_file_activities_for_window(
+                &tx,
+                rule.project_id,
+                session.app_id,
+                &session.date,
+                &session.start_time,
+                &session.end_time,
+            )?;
+            tx.execute(
+                "INSERT INTO assignment_feedback (
+                    suggestion_id, session_id, app_id, from_project_id, to_project_id, source, created_at
+                 ) VALUES (NULL, ?1, ?2, NULL, ?3, ?4, datetime('now'))",
+                rusqlite::params![session.session_id, session.app_id, rule.project_id, RULE_AUTO_SOURCE],
+            )
+            .map_err(|e| e.to_string())?;
+            assigned += 1;
+            result.seconds_assigned += session.duration_seconds;
+        }
+        if assigned > 0 {
+            tx.execute(
+                "UPDATE assignment_rules SET last_applied_at = datetime('now') WHERE id = ?1",
+                [rule.id],
+            )
+            .map_err(|e| e.to_string())?;
+            result.rules_applied += 1;
+            result.sessions_assigned += assigned;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if result.sessions_assigned > 0 {
+        log::info!(
+            "Assignment rules: {} rules assigned {} sessions",
+            result.rules_applied,
+            result.sessions_assigned
+        );
+    }
+    Ok(result)
+}
+
+#[command]
+pub async fn get_assignment_rules(app: AppHandle) -> Result<Vec<AssignmentRule>, String> {
+    run_db_blocking(app, move |conn| query_rules(conn, "1 = 1", &[])).await
+}
+
+#[command]
+pub async fn create_assignment_rule(
+    app: AppHandle,
+    rule: AssignmentRuleInput,
+) -> Result<AssignmentRule, String> {
+    run_db_blocking(app, move |conn| create_rule_sync(conn, rule)).await
+}
+
+#[command]
+pub async fn update_assignment_rule(
+    app: AppHandle,
+    rule_id: i64,
+    rule: AssignmentRuleInput,
+) -> Result<AssignmentRule, String> {
+    run_db_blocking(app, move |conn| update_rule_sync(conn, rule_id, rule)).await
+}
+
+#[command]
+pub async fn delete_assignment_rule(app: AppHandle, rule_id: i64) -> Result<(), String> {
+    run_db_blocking(app, move |conn| {
+        conn.execute("DELETE FROM assignment_rules WHERE id = ?1", [rule_id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    })
+    .await
+}
+
+/// Preview of an unsaved (or edited) rule, so it can be tuned before saving.
+#[command]
+pub async fn preview_assignment_rule(
+    app: AppHandle,
+    rule: AssignmentRuleInput,
+    only_unassigned: Option<bool>,
+) -> Result<AssignmentRulePreview, String> {
+    run_db_blocking(app, move |conn| {
+        preview_rule_sync(conn, rule, only_unassigned.unwrap_or(false))
+    })
+    .await
+}
+
+#[command]
+pub async fn apply_assignment_rule(
+    app: AppHandle,
+    rule_id: i64,
+    only_unassigned: Option<bool>,
+) -> Result<AssignmentRuleApplyResult, String> {
+    run_db_blocking(app, move |conn| {
+        apply_rule_sync(conn, rule_id, only_unassigned.unwrap_or(false))
+    })
+    .await
+}
+
+#[command]
+pub async fn apply_active_assignment_rules(
+    app: AppHandle,
+) -> Result<AssignmentRuleApplyResult, String> {
+    run_db_blocking(app, apply_active_rules_sync).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        conn.execute_batch(include_str!("../../../resources/sql/schema.sql"))
+            .expect("schema");
+        crate::db_migrations::run_migrations(&conn).expect("migrations");
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, created_at)
+             VALUES (1, 'Alpha', datetime('now')), (2, 'Beta', datetime('now'));
+             INSERT INTO applications (id, executable_name, display_name)
+             VALUES (1, 'code.exe', 'Code'), (2, 'chrome.exe', 'Chrome');
+             INSERT INTO sessions (id, app_id, start_time, end_time, duration_seconds, date, project_id)
+             VALUES
+               (10, 1, '2026-03-02T09:00:00', '2026-03-02T10:00:00', 3600, '2026-03-02', NULL),
+               (11, 1, '2026-03-03T09:00:00', '2026-03-03T09:30:00', 1800, '2026-03-03', 2),
+               (12, 1, '2026-03-04T09:00:00', '2026-03-04T09:30:00', 1800, '2026-03-04', NULL),
+               (13, 2, '2026-03-02T11:00:00', '2026-03-02T11:20:00', 1200, '2026-03-02', NULL),
+               (14, 1, '2026-04-01T09:00:00', '2026-04-01T09:10:00', 600, '2026-04-01', NULL);
+             INSERT INTO file_activities (app_id, date, file_name, file_path, total_seconds, first_seen, last_seen, window_title)
+             VALUES
+               (1, '2026-03-02', 'main.rs', 'C:/work/alpha/src/main.rs', 3600,
+                '2026-03-02T09:00:00', '2026-03-02T10:00:00', 'main.rs - alpha'),
+               (1, '2026-03-03', 'lib.rs', 'C:/work/ALPHA/src/lib.rs', 1800,
+                '2026-03-03T09:00:00', '2026-03-03T09:30:00', 'lib.rs'),
+               (1, '2026-03-04', 'notes.md', 'C:/work/beta/notes.md', 1800,
+                '2026-03-04T09:00:00', '2026-03-04T09:30:00', 'notes.md - beta'),
+               (1, '2026-04-01', 'main.rs', 'C:/work/alpha/src/main.rs', 600,
+                '2026-04-01T09:00:00', '2026-04-01T09:10:00', 'main.rs - alpha');",
+        )
+        .expect("seed");
+        conn
+    }
+
+    fn alpha_rule(keep_active: bool) -> AssignmentRuleInput {
+        AssignmentRuleInput {
+            name: " Alpha repo ".to_string(),
+            app_id: Some(1),
+            pattern: Some(r"[/\\]alpha[/\\]".to_string()),
+            date_from: Some("2026-03-01".to_string()),
+            date_to: Some("2026-03-31".to_string()),
+            project_id: 1,
+            keep_active,
+        }
+    }
+
+    fn feedback_sources(conn: &Connection) -> Vec<(i64, String)> {
+        let mut stmt = conn
+            .prepare("SELECT session_id, source FROM assignment_feedback ORDER BY session_id")
+            .unwrap();
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        rows.collect::<Result<Vec<_>, _>>().unwrap()
+    }
+
+    #[test]
+    fn preview_matches_app_pattern_and_dates_then_apply_records_rule_feedback() {
+        let mut conn = test_conn();
+
+        let preview = preview_rule_sync(&conn, alpha_rule(false), false).unwrap();
+        let ids: Vec<i64> = preview.sessions.iter().map(|s| s.session_id).collect();
+        // Case-insensitive path match; other app, other path and April are out.
+        assert_eq!(ids, [10, 11]);
+        assert_eq!((preview.session_count, preview.total_seconds), (2, 5400));
+        assert_eq!(preview.reassigned_count, 1);
+        let unassigned = preview_rule_sync(&conn, alpha_rule(false), true).unwrap();
+        assert_eq!(unassigned.session_count, 1);
+
+        let mut bad = alpha_rule(false);
+        bad.pattern = Some("(unclosed".to_string());
+        assert!(preview_rule_sync(&conn, bad, false).is_err());
+
+        let rule = create_rule_sync(&conn, alpha_rule(false)).unwrap();
+        assert_eq!(rule.name, "Alpha repo");
+        let result = apply_rule_sync(&mut conn, rule.id, false).unwrap();
+        assert_eq!(
+            (result.sessions_assigned, result.seconds_assigned),
+            (2, 5400)
+        );
+
+        let projects: Vec<Option<i64>> = [10, 11, 12]
+            .iter()
+            .map(|id| {
+                conn.query_row(
+                    "SELECT project_id FROM sessions WHERE id = ?1",
+                    [id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+            })
+            .collect();
+        assert_eq!(projects, [Some(1), Some(1), None]);
+        assert_eq!(
+            feedback_sources(&conn),
+            [(10, RULE_SOURCE.to_string()), (11, RULE_SOURCE.to_string())]
+        );
+        assert!(load_rule(&conn, rule.id).unwrap().last_applied_at.is_some());
+        // Undoable like any manual bulk assignment.
+        let undo = crate::commands::mutation_journal::undo_last_action_in_conn(&mut conn).unwrap();
+        assert!(undo.is_some());
+        let restored: Option<i64> = conn
+            .query_row("SELECT project_id FROM sessions WHERE id = 11", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(restored, Some(2));
+    }
+
+    #[test]
+    fn active_rules_fill_only_unassigned_sessions_without_manual_override() {
+        let mut conn = test_conn();
+        let mut open_ended = alpha_rule(true);
+        open_ended.date_to = None;
+        create_rule_sync(&conn, open_ended).unwrap();
+        create_rule_sync(&conn, alpha_rule(false)).unwrap();
+        conn.execute(
+            "INSERT INTO session_manual_overrides (session_id, executable_name, start_time, end_time, project_name, updated_at)
+             VALUES (14, 'code.exe', '2026-04-01T09:00:00', '2026-04-01T09:10:00', 'Beta', datetime('now'))",
+            [],
+        )
+        .unwrap();
+
+        let result = apply_active_rules_sync(&mut conn).unwrap();
+        assert_eq!((result.rules_applied, result.sessions_assigned), (1, 1));
+        assert_eq!(
+            feedback_sources(&conn),
+            [(10, RULE_AUTO_SOURCE.to_string())]
+        );
+        let activity_project: Option<i64> = conn
+            .query_row(
+                "SELECT project_id FROM file_activities WHERE date = '2026-03-02'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(activity_project, Some(1));
+
+        // Nothing left to fill on the next cycle.
+        assert_eq!(
+            apply_active_rules_sync(&mut conn)
+                .unwrap()
+                .sessions_assigned,
+            0
+        );
+    }
+}
@@ -222,7 +222,7 @@ pub fn retrain_model_sync(conn: &mut rusqlite::Connection) -> Result<i64, String
                      WHERE af.session_id = s.id
                      ORDER BY af.created_at DESC, af.id DESC
                      LIMIT 1
-                   ), '') NOT IN ('auto_accept', 'deterministic_rule')
+                   ), '') NOT IN ('auto_accept', 'deterministic_rule', 'assignment_rule_auto')
              GROUP BY s.app_id, s.project_id
              HAVING cnt > 0",
             rusqlite::params![&training_horizon_modifier, decay_rate],
@@ -256,7 +256,7 @@ pub fn retrain_model_sync(conn: &mut rusqlite::Connection) -> Result<i64, String
                      WHERE af.session_id = s.id
                      ORDER BY af.created_at DESC, af.id DESC
                      LIMIT 1
-                   ), '') NOT IN ('auto_accept', 'deterministic_rule')
+                   ), '') NOT IN ('auto_accept', 'deterministic_rule', 'assignment_rule_auto')
              GROUP BY s.app_id, hour_bucket, weekday, s.project_id
              HAVING cnt > 0",
             rusqlite::params![&training_horizon_modifier, decay_rate],
@@ -401,7 +401,7 @@ pub fn retrain_model_sync(conn: &mut rusqlite::Connection) -> Result<i64, String
                          WHERE af.session_id = s.id
                          ORDER BY af.created_at DESC, af.id DESC
                          LIMIT 1
-                       ), '') NOT IN ('auto_accept', 'deterministic_rule')",
+                       ), '') NOT IN ('auto_accept', 'deterministic_rule', 'assignment_rule_auto')",
             )?;
             let mut file_rows = file_stmt.query(rusqlite::params![&training_horizon_modifier])?;
             while let Some(row) = file_rows.next()? {
@@ -275,6 +275,13 @@ pub async fn clients_update(
                     rusqlite::params![name, old],
                 )
                 .map_err(|e| e.to_string())?;
+                // Sync keys clients by name: for peers a rename is "old name
+                // deleted + new name created", so tombstone the old name.
+                conn.execute(
+                    "INSERT INTO tombstones (table_name, record_id, sync_key) VALUES ('clients', ?1, ?2)",
+                    rusqlite::params![id, old],
+                )
+                .map_err(|e| e.to_string())?;
             }
         }
         load_client_by_id(conn, id)
@@ -623,6 +630,10 @@ pub async fn get_clients_summary(
             .iter()
             .map(|r| (r.project_id, (r.seconds, r.estimated_value)))
             .collect();
+        let billable_by_project: HashMap<i64, (i64, i64)> = estimate_rows
+            .iter()
+            .map(|r| (r.project_id, (r.billable_seconds, r.non_billable_seconds)))
+            .collect();
 
         // Seed buckets from ALL PM clients (so every client appears, even with no
         // tracked time yet). Falls back to the local clients table when PM is off.
@@ -758,6 +769,10 @@ pub async fn get_clients_summary(
                 .cloned()
                 .unwrap_or((project_name_raw, project_color_raw));
 
+            let (billable_seconds, non_billable_seconds) = billable_by_project
+                .get(&project_id)
+                .copied()
+                .unwrap_or((seconds, 0));
             let bucket = buckets
                 .entry(client_name.clone())
                 .or_insert_with(|| empty_summary(client_name.clone(), "#38bdf8".to_string()));
@@ -769,11 +784,15 @@ pub async fn get_clients_summary(
                 status: status.clone(),
                 seconds,
                 value,
+                billable_seconds,
+                non_billable_seconds,
                 daily_seconds: daily_by_project.get(&project_id).cloned().unwrap_or_default(),
             });
             bucket.project_count += 1;
             bucket.total_seconds += seconds;
             bucket.total_value += value;
+            bucket.billable_seconds += billable_seconds;
+            bucket.non_billable_seconds += non_billable_seconds;
             // Real project status (frozen_at/excluded_at derived) drives buckets:
             // active → active_value, frozen → done_value, archived → paid_value.
             // (Excluded projects are filtered out, so 'archived' is unused here.)
@@ -813,6 +832,8 @@ fn empty_summary(client_name: String, color: String) -> ClientSummary {
         project_count: 0,
         total_seconds: 0,
         total_value: 0.0,
+        billable_seconds: 0,
+        non_billable_seconds: 0,
         active_value: 0.0,
         done_value: 0.0,
         paid_value: 0.0,
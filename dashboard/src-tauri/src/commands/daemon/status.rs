@@ -59,6 +59,7 @@ pub async fn get_background_diagnostics(app: AppHandle) -> Result<BackgroundDiag
         min_duration: Some(min_duration),
         include_files: None,
         include_ai_suggestions: None,
+        tag_ids: None,
         limit: None,
         offset: None,
     };
@@ -70,6 +71,7 @@ pub async fn get_background_diagnostics(app: AppHandle) -> Result<BackgroundDiag
         min_duration: Some(min_duration),
         include_files: None,
         include_ai_suggestions: None,
+        tag_ids: None,
         limit: None,
         offset: None,
     };
@@ -44,3 +44,11 @@ pub(crate) fn load_range(start: &str, end: &str) -> Result<BTreeMap<String, Dail
     let conn = open_store()?;
     super::daily_store::load_range_snapshots(&conn, start, end)
 }
+
+pub(crate) fn load_tracker_spans(
+    start: &str,
+    end: &str,
+) -> Result<Vec<super::daily_store::TrackerSpan>, String> {
+    let conn = open_store()?;
+    super::daily_store::load_tracker_spans(&conn, start, end)
+}
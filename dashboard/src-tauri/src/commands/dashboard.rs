@@ -10,11 +10,14 @@ use super::sql_fragments::{
     ensure_session_project_cache, ACTIVE_SESSION_FILTER, ACTIVE_SESSION_FILTER_S,
     SESSION_PROJECT_CTE,
 };
+use super::tags::tag_time_breakdown;
 use super::types::{
     AppWithStats, DashboardData, DashboardStats, DateRange, ProjectTimeRow,
     StackedSeriesMeta, TimelinePoint, TopApp, TopProject,
 };
 
+const TOP_TAGS_LIMIT: usize = 10;
+
 fn build_dashboard_stats(
     conn: &rusqlite::Connection,
     date_range: &DateRange,
@@ -107,6 +110,9 @@ fn build_dashboard_stats(
             seconds: seconds.round() as i64,
         });
 
+    let mut top_tags = tag_time_breakdown(conn, date_range)?;
+    top_tags.truncate(TOP_TAGS_LIMIT);
+
     Ok(DashboardStats {
         total_seconds,
         app_count,
@@ -115,6 +121,7 @@ fn build_dashboard_stats(
         top_apps,
         top_project,
         daily_seconds: grand_daily,
+        top_tags,
     })
 }
 
@@ -485,7 +492,8 @@ pub async fn get_applications(
                                 MAX(s.end_time) as last_used,
                                 p.name as project_name,
                                 p.color as project_color,
-                                a.color as app_color
+                                a.color as app_color,
+                                a.device_local
                          FROM applications a
                          LEFT JOIN sessions s
                            ON s.app_id = a.id
@@ -507,7 +515,8 @@ pub async fn get_applications(
                                 MAX(s.end_time) as last_used,
                                 p.name as project_name,
                                 p.color as project_color,
-                                a.color as app_color
+                                a.color as app_color,
+                                a.device_local
                          FROM applications a
                          LEFT JOIN sessions s ON s.app_id = a.id AND {ACTIVE_SESSION_FILTER_S}
                          LEFT JOIN projects p ON p.id = a.project_id
@@ -534,6 +543,7 @@ pub async fn get_applications(
                     project_name: row.get(7)?,
                     project_color: row.get(8)?,
                     color: row.get(9)?,
+                    device_local: row.get::<_, i64>(10)? != 0,
                     daily_seconds: Vec::new(),
                 })
             })
@@ -631,6 +641,32 @@ pub async fn update_app_color(app: AppHandle, id: i64, color: String) -> Result<
     .await
 }
 
+/// Selective sync: a device-local application and its sessions are left out
+/// of every sync export. Clearing the flag re-stamps its sessions so the next
+/// delta sync carries them.
+#[tauri::command]
+pub async fn set_app_device_local(app: AppHandle, id: i64, device_local: bool) -> Result<(), String> {
+    run_db_blocking(app, move |conn| {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let changed = tx
+            .execute(
+                "UPDATE applications SET device_local = ?2 WHERE id = ?1 AND device_local <> ?2",
+                rusqlite::params![id, device_local as i64],
+            )
+            .map_err(|e| e.to_string())?;
+        if changed > 0 && !device_local {
+            tx.execute(
+                "UPDATE sessions SET updated_at = datetime('now') WHERE app_id = ?1",
+                [id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(())
+    })
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::{build_top_project_rows, query_dashboard_counters, query_project_counts};
@@ -650,7 +686,8 @@ mod tests {
                 project_id INTEGER,
                 is_hidden INTEGER DEFAULT 0,
                 rate_multiplier REAL NOT NULL DEFAULT 1.0,
-                comment TEXT
+                comment TEXT,
+                billable INTEGER
             );
             CREATE TABLE file_activities (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -668,7 +705,8 @@ mod tests {
                 color TEXT NOT NULL DEFAULT '#64748b',
                 excluded_at TEXT,
                 frozen_at TEXT,
-                merged_into TEXT
+                merged_into TEXT,
+                billable INTEGER NOT NULL DEFAULT 1
             );
             CREATE TABLE manual_sessions (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -679,7 +717,8 @@ mod tests {
                 end_time TEXT NOT NULL DEFAULT '',
                 duration_seconds INTEGER NOT NULL,
                 date TEXT NOT NULL,
-                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                billable INTEGER
             );
             CREATE TABLE session_project_cache (
                 session_id INTEGER PRIMARY KEY,
@@ -58,6 +58,15 @@ pub(crate) fn parse_datetime_ms_opt(value: &str) -> Option<i64> {
     parse_datetime_fixed(value).map(|dt| dt.timestamp_millis())
 }
 
+/// Unix seconds as a naive local `YYYY-MM-DDTHH:MM:SS` — the manual-session format.
+pub(crate) fn format_local_naive(timestamp: i64) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,9 @@
 use super::helpers::build_table_hashes;
-use super::types::{ApplicationRow, AssignmentAutoRunRow, AssignmentFeedbackRow, ManualSession, Project, SessionRow, Tombstone};
+use super::types::{
+    ApplicationRow, AssignmentAutoRunRow, AssignmentFeedbackRow, ClientRow, EstimateSettingRow,
+    FileActivityExportRow, ManualSession, Project, SessionRow, TagRow, Tombstone,
+};
+use super::tags::{load_tag_rows, parse_tag_names, tags_json_sql, TagTarget};
 use crate::db;
 use serde::{Deserialize, Serialize};
 
@@ -35,6 +39,14 @@ pub struct DeltaData {
     pub assignment_feedback: Vec<AssignmentFeedbackRow>,
     #[serde(default)]
     pub assignment_auto_runs: Vec<AssignmentAutoRunRow>,
+    #[serde(default)]
+    pub clients: Vec<ClientRow>,
+    #[serde(default)]
+    pub estimate_settings: Vec<EstimateSettingRow>,
+    #[serde(default)]
+    pub file_activities: Vec<FileActivityExportRow>,
+    #[serde(default)]
+    pub tags: Vec<TagRow>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -76,7 +88,7 @@ pub fn build_delta_archive(
 
     // Projects (all — needed as lookup table for project_id resolution)
     let mut stmt = conn
-        .prepare("SELECT id, name, color, hourly_rate, created_at, excluded_at, assigned_folder_path, is_imported, frozen_at, merged_into, merged_at, updated_at
+        .prepare("SELECT id, name, color, hourly_rate, created_at, excluded_at, assigned_folder_path, is_imported, frozen_at, merged_into, merged_at, updated_at, client_name, billable
                   FROM projects")
         .map_err(|e| e.to_string())?;
 
@@ -92,9 +104,11 @@ pub fn build_delta_archive(
                 assigned_folder_path: row.get(6)?,
                 is_imported: row.get(7)?,
                 frozen_at: row.get(8)?,
-                merged_into: row.get(9)?,
-                merged_at: row.get(10)?,
+                merged_into: Some(row.get(9)?),
+                merged_at: Some(row.get(10)?),
                 updated_at: row.get(11)?,
+                client_name: Some(row.get(12)?),
+                billable: Some(row.get::<_, i64>(13)? != 0),
             })
         })
         .map_err(|e| e.to_string())?
@@ -124,9 +138,12 @@ pub fn build_delta_archive(
 
     // Sessions
     let mut stmt = conn
-        .prepare("SELECT id, app_id, project_id, start_time, end_time, duration_seconds, date
-                  , COALESCE(rate_multiplier, 1.0), comment, is_hidden, updated_at, project_name
-                  FROM sessions WHERE updated_at > ?1")
+        .prepare(&format!(
+            "SELECT s.id, s.app_id, s.project_id, s.start_time, s.end_time, s.duration_seconds, s.date
+                  , COALESCE(s.rate_multiplier, 1.0), s.comment, s.is_hidden, s.updated_at, s.project_name, {}, s.billable
+                  FROM sessions s WHERE s.updated_at > ?1",
+            tags_json_sql(TagTarget::Session, "s")
+        ))
         .map_err(|e| e.to_string())?;
 
     let sessions: Vec<SessionRow> = stmt
@@ -144,6 +161,8 @@ pub fn build_delta_archive(
                 is_hidden: row.get::<_, i64>(9)? != 0,
                 updated_at: row.get(10)?,
                 project_name: row.get(11)?,
+                tags: Some(parse_tag_names(row.get(12)?)),
+                billable: Some(row.get::<_, Option<i64>>(13)?.map(|b| b != 0)),
             })
         })
         .map_err(|e| e.to_string())?
@@ -152,9 +171,12 @@ pub fn build_delta_archive(
 
     // Manual Sessions
     let mut stmt = conn
-        .prepare("SELECT id, title, session_type, project_id, app_id, start_time, end_time,
-                         duration_seconds, date, created_at, updated_at
-                  FROM manual_sessions WHERE updated_at > ?1")
+        .prepare(&format!(
+            "SELECT ms.id, ms.title, ms.session_type, ms.project_id, ms.app_id, ms.start_time, ms.end_time,
+                         ms.duration_seconds, ms.date, ms.created_at, ms.updated_at, {}, ms.billable
+                  FROM manual_sessions ms WHERE ms.updated_at > ?1",
+            tags_json_sql(TagTarget::ManualSession, "ms")
+        ))
         .map_err(|e| e.to_string())?;
     
     let manual_sessions: Vec<ManualSession> = stmt
@@ -171,6 +193,8 @@ pub fn build_delta_archive(
                 date: row.get(8)?,
                 created_at: row.get(9)?,
                 updated_at: row.get(10)?,
+                tags: Some(parse_tag_names(row.get(11)?)),
+                billable: Some(row.get::<_, Option<i64>>(12)?.map(|b| b != 0)),
             })
         })
         .map_err(|e| e.to_string())?
@@ -243,10 +267,47 @@ pub fn build_delta_archive(
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
+    // File Activities (delta by day — rows have no updated_at and keep growing
+    // until their day ends, so every row dated on/after the `since` day is sent;
+    // the importer dedupes on (app, date, file_path) and merges activity_spans)
+    let mut stmt = conn
+        .prepare("SELECT app_id, date, file_name, file_path, total_seconds, first_seen, last_seen,
+                         project_id, window_title, detected_path, title_history, activity_type, activity_spans
+                  FROM file_activities WHERE date >= substr(?1, 1, 10)")
+        .map_err(|e| e.to_string())?;
+
+    let file_activities: Vec<FileActivityExportRow> = stmt
+        .query_map([since.as_str()], |row| {
+            Ok(FileActivityExportRow {
+                app_id: row.get(0)?,
+                date: row.get(1)?,
+                file_name: row.get(2)?,
+                file_path: row.get(3)?,
+                total_seconds: row.get(4)?,
+                first_seen: row.get(5)?,
+                last_seen: row.get(6)?,
+                project_id: row.get(7)?,
+                window_title: row.get(8)?,
+                detected_path: row.get(9)?,
+                title_history: row.get(10)?,
+                activity_type: row.get(11)?,
+                activity_spans: row.get(12)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // Clients, estimate settings & tags (all — tiny tables, LWW per row on import)
+    let clients = load_client_rows(&conn)?;
+    let estimate_settings = load_estimate_setting_rows(&conn)?;
+    let tags = load_tag_rows(&conn)?;
+
     log::info!(
-        "Delta export (since={}): projects={}, apps={}, sessions={}, manual={}, tombstones={}, feedback={}, auto_runs={}",
+        "Delta export (since={}): projects={}, apps={}, sessions={}, manual={}, tombstones={}, feedback={}, auto_runs={}, clients={}, estimate_settings={}, file_activities={}, tags={}",
         since, projects.len(), applications.len(), sessions.len(), manual_sessions.len(), tombstones.len(),
-        assignment_feedback.len(), assignment_auto_runs.len()
+        assignment_feedback.len(), assignment_auto_runs.len(), clients.len(), estimate_settings.len(),
+        file_activities.len(), tags.len()
     );
 
     let default_name = format!(
@@ -269,12 +330,65 @@ pub fn build_delta_archive(
             tombstones,
             assignment_feedback,
             assignment_auto_runs,
+            clients,
+            estimate_settings,
+            file_activities,
+            tags,
         },
     };
 
     Ok((archive, default_name))
 }
 
+pub(crate) fn load_client_rows(conn: &rusqlite::Connection) -> Result<Vec<ClientRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name, contact, address, tax_id, currency, default_hourly_rate, color,
+                    archived_at, created_at, updated_at
+             FROM clients ORDER BY name",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ClientRow {
+                name: row.get(0)?,
+                contact: row.get(1)?,
+                address: row.get(2)?,
+                tax_id: row.get(3)?,
+                currency: row.get(4)?,
+                default_hourly_rate: row.get(5)?,
+                color: row.get(6)?,
+                archived_at: row.get(7)?,
+                created_at: row.get(8)?,
+                updated_at: row.get(9)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+pub(crate) fn load_estimate_setting_rows(
+    conn: &rusqlite::Connection,
+) -> Result<Vec<EstimateSettingRow>, String> {
+    let mut stmt = conn
+        .prepare("SELECT key, value, updated_at FROM estimate_settings ORDER BY key")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(EstimateSettingRow {
+                key: row.get(0)?,
+                value: row.get(1)?,
+                updated_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
 /// Convert ISO 8601 timestamps (e.g. "2026-03-29T10:00:00Z" or "2026-03-29T10:00:00+02:00")
 /// to SQLite datetime format in UTC ("2026-03-29 08:00:00") for correct lexicographic comparison.
 pub fn normalize_datetime_for_sqlite_pub(s: &str) -> String {
@@ -3,17 +3,18 @@ use std::collections::HashMap;
 use rusqlite::OptionalExtension;
 use tauri::AppHandle;
 
-use super::analysis::{
-    compute_project_activity_unique, daily_seconds_by_series, project_series_key,
-};
+use super::analysis::{daily_seconds_by_series, project_series_key};
 use super::helpers::run_db_blocking;
-use super::sql_fragments::{ensure_session_project_cache, SESSION_PROJECT_CTE};
-use super::time_algorithm::daily_buckets_by_series;
+use super::rounding::{load_rounding_settings, round_project_total, scale_value_to_rounded};
+use super::sql_fragments::{
+    ensure_session_project_cache, SESSION_BILLABLE_SQL, SESSION_PROJECT_CTE,
+};
+use super::time_algorithm::{compute_project_activity_with_billable, daily_buckets_by_series};
 use super::types::{DateRange, EstimateDay, EstimateProjectRow, EstimateSettings, EstimateSummary};
 
 const DEFAULT_GLOBAL_HOURLY_RATE: f64 = 100.0;
 const MAX_HOURLY_RATE: f64 = 100000.0;
-type ProjectMetaRow = (i64, String, String, Option<f64>, Option<String>);
+type ProjectMetaRow = (i64, String, String, Option<f64>, Option<String>, bool);
 type ProjectMetaById = HashMap<i64, ProjectMetaRow>;
 
 fn sanitize_rate(rate: f64) -> Option<f64> {
@@ -58,7 +59,9 @@ pub(crate) fn get_global_hourly_rate(conn: &rusqlite::Connection) -> Result<f64,
 
 fn query_project_meta(conn: &rusqlite::Connection) -> Result<ProjectMetaById, String> {
     let mut stmt = conn
-        .prepare_cached("SELECT id, name, color, hourly_rate, client_name FROM projects")
+        .prepare_cached(
+            "SELECT id, name, color, hourly_rate, client_name, COALESCE(billable, 1) FROM projects",
+        )
         .map_err(|e| e.to_string())?;
     let rows = stmt
         .query_map([], |row| {
@@ -69,6 +72,7 @@ fn query_project_meta(conn: &rusqlite::Connection) -> Result<ProjectMetaById, St
                 row.get::<_, String>(2)?,
                 rate.and_then(sanitize_rate),
                 row.get::<_, Option<String>>(4)?,
+                row.get::<_, i64>(5)? != 0,
             ))
         })
         .map_err(|e| e.to_string())?;
@@ -136,12 +140,14 @@ fn query_project_multiplier_extra_seconds(
     date_range: &DateRange,
 ) -> Result<HashMap<String, MultiplierInfo>, String> {
     // Merged-aware: see query_project_session_counts — children map to parent.
+    // Boost only adds value to billable sessions.
     let sql = format!(
         "{SESSION_PROJECT_CTE},
          combined AS (
              SELECT COALESCE(parent.id, sp.project_id) as project_id,
                     CASE
                         WHEN sp.safe_rate_multiplier <= 1.0 THEN 0.0
+                        WHEN {SESSION_BILLABLE_SQL} = 0 THEN 0.0
                         ELSE (sp.duration_seconds * (sp.safe_rate_multiplier - 1.0))
                     END as extra_seconds
              FROM session_projects sp
@@ -197,8 +203,8 @@ pub(crate) fn build_estimate_rows(
     ensure_session_project_cache(conn, &date_range.start, &date_range.end)?;
 
     let global_hourly_rate = get_global_hourly_rate(conn)?;
-    let (bucket_project_seconds, totals, series_meta_by_key, _, _) =
-        compute_project_activity_unique(
+    let ((bucket_project_seconds, totals, series_meta_by_key, _, _), billable_by_series) =
+        compute_project_activity_with_billable(
             conn,
             date_range,
             false,
@@ -221,6 +227,8 @@ pub(crate) fn build_estimate_rows(
     let multiplier_extra_seconds_by_project =
         query_project_multiplier_extra_seconds(conn, date_range)?;
 
+    let rounding = load_rounding_settings();
+
     let mut rows: Vec<EstimateProjectRow> = Vec::new();
     for (series_key, seconds_f64) in totals {
         let Some(project_id) = series_meta_by_key
@@ -230,8 +238,14 @@ pub(crate) fn build_estimate_rows(
             continue;
         };
 
-        let Some((project_id, mapped_name, project_color, project_hourly_rate, client_name)) =
-            project_meta.get(&project_id)
+        let Some((
+            project_id,
+            mapped_name,
+            project_color,
+            project_hourly_rate,
+            client_name,
+            project_billable,
+        )) = project_meta.get(&project_id)
         else {
             log::warn!(
                 "Could not resolve project metadata for project_id={} while building estimates",
@@ -242,15 +256,24 @@ pub(crate) fn build_estimate_rows(
 
         let seconds = seconds_f64.round() as i64;
         let hours = seconds_f64 / 3600.0;
+        // Only billable time has value; the rest is reported next to it.
+        let billable_f64 = billable_by_series
+            .get(&series_key)
+            .copied()
+            .unwrap_or(0.0)
+            .min(seconds_f64);
+        let billable_seconds = billable_f64.round() as i64;
         let effective_hourly_rate = project_hourly_rate
             .filter(|r| r.is_finite() && *r > 0.0)
             .unwrap_or(global_hourly_rate);
         let mult_info = multiplier_extra_seconds_by_project.get(&series_key);
         let extra_secs = mult_info.map(|m| m.extra_seconds).unwrap_or(0.0);
-        let weighted_hours = hours + (extra_secs / 3600.0);
+        let weighted_hours = (billable_f64 + extra_secs) / 3600.0;
         let estimated_value = weighted_hours * effective_hourly_rate;
         let session_count = session_counts.get(&series_key).copied().unwrap_or(0);
         let multiplied_session_count = mult_info.map(|m| m.session_count).unwrap_or(0);
+        let daily_seconds = daily_by_series.remove(&series_key).unwrap_or_default();
+        let rounded_seconds = round_project_total(seconds, &daily_seconds, &rounding);
 
         rows.push(EstimateProjectRow {
             project_id: *project_id,
@@ -258,6 +281,9 @@ pub(crate) fn build_estimate_rows(
             project_color: project_color.clone(),
             seconds,
             hours,
+            billable_seconds,
+            non_billable_seconds: (seconds - billable_seconds).max(0),
+            billable: *project_billable,
             weighted_hours,
             project_hourly_rate: *project_hourly_rate,
             effective_hourly_rate,
@@ -265,7 +291,7 @@ pub(crate) fn build_estimate_rows(
             session_count,
             multiplied_session_count,
             multiplier_extra_seconds: extra_secs,
-            daily_seconds: daily_by_series.remove(&series_key).unwrap_or_default(),
+            daily_seconds,
             client_name: client_name.clone(),
             days: daily_buckets
                 .remove(&series_key)
@@ -273,6 +299,8 @@ pub(crate) fn build_estimate_rows(
                 .into_iter()
                 .map(|(date, seconds)| EstimateDay { date, seconds })
                 .collect(),
+            rounded_seconds,
+            rounded_value: scale_value_to_rounded(estimated_value, seconds, rounded_seconds),
         });
     }
 
@@ -341,6 +369,28 @@ pub async fn update_project_hourly_rate(
     .await
 }
 
+/// Project default for billable time (m34); sessions may override it.
+#[tauri::command]
+pub async fn update_project_billable(
+    app: AppHandle,
+    project_id: i64,
+    billable: bool,
+) -> Result<(), String> {
+    run_db_blocking(app, move |conn| {
+        let updated = conn
+            .execute(
+                "UPDATE projects SET billable = ?2, updated_at = datetime('now') WHERE id = ?1",
+                rusqlite::params![project_id, billable as i64],
+            )
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err("Project not found".to_string());
+        }
+        Ok(())
+    })
+    .await
+}
+
 #[tauri::command]
 pub async fn get_project_estimates(
     app: AppHandle,
@@ -359,19 +409,27 @@ pub async fn get_estimates_summary(
 
         let total_seconds = rows.iter().map(|r| r.seconds).sum::<i64>();
         let total_hours = total_seconds as f64 / 3600.0;
+        let billable_seconds = rows.iter().map(|r| r.billable_seconds).sum::<i64>();
+        let non_billable_seconds = rows.iter().map(|r| r.non_billable_seconds).sum::<i64>();
         let total_value = rows.iter().map(|r| r.estimated_value).sum::<f64>();
         let projects_count = rows.len() as i64;
         let overrides_count = rows
             .iter()
             .filter(|r| r.project_hourly_rate.is_some())
             .count() as i64;
+        let rounded_seconds = rows.iter().map(|r| r.rounded_seconds).sum::<i64>();
+        let rounded_value = rows.iter().map(|r| r.rounded_value).sum::<f64>();
 
         Ok(EstimateSummary {
             total_seconds,
             total_hours,
+            billable_seconds,
+            non_billable_seconds,
             total_value,
             projects_count,
             overrides_count,
+            rounded_seconds,
+            rounded_value,
         })
     })
     .await
@@ -394,7 +452,8 @@ mod tests {
                 excluded_at TEXT,
                 frozen_at TEXT,
                 merged_into TEXT,
-                merged_at TEXT
+                merged_at TEXT,
+                billable INTEGER NOT NULL DEFAULT 1
             );
             CREATE TABLE sessions (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -406,12 +465,14 @@ mod tests {
                 rate_multiplier REAL NOT NULL DEFAULT 1.0,
                 project_id INTEGER,
                 is_hidden INTEGER DEFAULT 0,
-                comment TEXT
+                comment TEXT,
+                billable INTEGER
             );
             CREATE TABLE file_activities (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 app_id INTEGER NOT NULL,
                 date TEXT NOT NULL,
+                file_name TEXT NOT NULL DEFAULT '',
                 project_id INTEGER,
                 first_seen TEXT NOT NULL,
                 last_seen TEXT NOT NULL
@@ -423,7 +484,8 @@ mod tests {
                 start_time TEXT NOT NULL,
                 end_time TEXT NOT NULL,
                 duration_seconds INTEGER NOT NULL,
-                date TEXT NOT NULL
+                date TEXT NOT NULL,
+                billable INTEGER
             );
             CREATE TABLE estimate_settings (
                 key TEXT PRIMARY KEY,
@@ -597,6 +659,55 @@ mod tests {
         assert!((row.multiplier_extra_seconds - 3600.0).abs() < 0.0001);
     }
 
+    #[test]
+    fn estimate_rows_value_only_billable_time() {
+        let conn = setup_conn();
+        conn.execute(
+            "INSERT INTO estimate_settings (key, value, updated_at) VALUES (?1, ?2, datetime('now'))",
+            rusqlite::params!["global_hourly_rate", "100"],
+        )
+        .expect("insert setting");
+        conn.execute(
+            "INSERT INTO projects (id, name, color, billable) VALUES (1, 'Client', '#111111', 1),
+                                                                  (2, 'Internal', '#222222', 0)",
+            [],
+        )
+        .expect("insert projects");
+        // Client: 1h inherited billable, 1h boosted but marked non-billable.
+        // Internal: 1h inherited non-billable, 1h overridden to billable.
+        conn.execute_batch(
+            "INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, project_id, rate_multiplier, billable)
+             VALUES (1, '2026-01-03T09:00:00', '2026-01-03T10:00:00', 3600, '2026-01-03', 1, 1.0, NULL),
+                    (2, '2026-01-03T10:00:00', '2026-01-03T11:00:00', 3600, '2026-01-03', 1, 2.0, 0),
+                    (3, '2026-01-03T12:00:00', '2026-01-03T13:00:00', 3600, '2026-01-03', 2, 1.0, NULL),
+                    (4, '2026-01-03T13:00:00', '2026-01-03T14:00:00', 3600, '2026-01-03', 2, 1.0, 1);",
+        )
+        .expect("insert sessions");
+
+        let rows = build_estimate_rows(
+            &conn,
+            &DateRange {
+                start: "2026-01-03".to_string(),
+                end: "2026-01-03".to_string(),
+            },
+        )
+        .expect("estimate rows");
+
+        let client = rows.iter().find(|r| r.project_id == 1).expect("client row");
+        assert_eq!(client.seconds, 7200);
+        assert_eq!(client.billable_seconds, 3600);
+        assert_eq!(client.non_billable_seconds, 3600);
+        assert!(client.billable);
+        assert!((client.multiplier_extra_seconds - 0.0).abs() < 0.0001);
+        assert!((client.estimated_value - 100.0).abs() < 0.0001);
+
+        let internal = rows.iter().find(|r| r.project_id == 2).expect("internal row");
+        assert_eq!(internal.billable_seconds, 3600);
+        assert_eq!(internal.non_billable_seconds, 3600);
+        assert!(!internal.billable);
+        assert!((internal.estimated_value - 100.0).abs() < 0.0001);
+    }
+
     #[test]
     fn estimate_rows_split_seconds_per_day() {
         let conn = setup_conn();
@@ -1,5 +1,7 @@
 use super::daily_store_bridge;
 use super::helpers::{run_app_blocking, timeflow_data_dir};
+use super::rounding::{load_rounding_settings, round_dated_sessions};
+use super::tags::{load_tag_rows, parse_tag_names, tags_json_sql, TagTarget};
 use super::types::{
     AppDailyData, ApplicationRow, DailyData, DateRange, ExportArchive, ExportData, ExportMetadata,
     FileActivityExportRow, ManualSession, Project, SessionRow,
@@ -73,9 +75,9 @@ fn build_export_archive(
 
         // 2. Fetch Projects
         let project_query = if project_id.is_some() {
-            "SELECT id, name, color, hourly_rate, created_at, excluded_at, assigned_folder_path, is_imported, frozen_at, merged_into, merged_at, updated_at FROM projects WHERE id = ?1"
+            "SELECT id, name, color, hourly_rate, created_at, excluded_at, assigned_folder_path, is_imported, frozen_at, merged_into, merged_at, updated_at, client_name, billable FROM projects WHERE id = ?1"
         } else {
-            "SELECT id, name, color, hourly_rate, created_at, excluded_at, assigned_folder_path, is_imported, frozen_at, merged_into, merged_at, updated_at FROM projects"
+            "SELECT id, name, color, hourly_rate, created_at, excluded_at, assigned_folder_path, is_imported, frozen_at, merged_into, merged_at, updated_at, client_name, billable FROM projects"
         };
 
         let mut stmt = conn.prepare(project_query).map_err(|e| e.to_string())?;
@@ -91,9 +93,11 @@ fn build_export_archive(
                     assigned_folder_path: row.get(6)?,
                     is_imported: row.get(7)?,
                     frozen_at: row.get(8)?,
-                    merged_into: row.get(9)?,
-                    merged_at: row.get(10)?,
+                    merged_into: Some(row.get(9)?),
+                    merged_at: Some(row.get(10)?),
                     updated_at: row.get(11)?,
+                    client_name: Some(row.get(12)?),
+                    billable: Some(row.get::<_, i64>(13)? != 0),
                 })
             })
             .map_err(|e| e.to_string())?
@@ -111,9 +115,11 @@ fn build_export_archive(
                     assigned_folder_path: row.get(6)?,
                     is_imported: row.get(7)?,
                     frozen_at: row.get(8)?,
-                    merged_into: row.get(9)?,
-                    merged_at: row.get(10)?,
+                    merged_into: Some(row.get(9)?),
+                    merged_at: Some(row.get(10)?),
                     updated_at: row.get(11)?,
+                    client_name: Some(row.get(12)?),
+                    billable: Some(row.get::<_, i64>(13)? != 0),
                 })
             })
             .map_err(|e| e.to_string())?
@@ -198,14 +204,16 @@ fn build_export_archive(
         let mut sessions = Vec::new();
         if !app_ids.is_empty() {
             let mut stmt = conn
-                .prepare(
+                .prepare(&format!(
                     "SELECT s.id, s.app_id, s.project_id, s.start_time, s.end_time, s.duration_seconds, s.date
                         , COALESCE(s.rate_multiplier, 1.0), s.comment, s.is_hidden
                         , COALESCE(s.project_name, (SELECT p.name FROM projects p WHERE p.id = s.project_id))
+                        , {}, s.billable
                  FROM sessions s
                  INNER JOIN _export_app_ids e ON e.id = s.app_id
                  WHERE s.date >= ?1 AND s.date <= ?2",
-                )
+                    tags_json_sql(TagTarget::Session, "s")
+                ))
                 .map_err(|e| e.to_string())?;
             let s_rows = stmt
                 .query_map([&start, &end], |row| {
@@ -222,6 +230,8 @@ fn build_export_archive(
                         is_hidden: row.get::<_, i64>(9)? != 0,
                         updated_at: None,
                         project_name: row.get(10)?,
+                        tags: Some(parse_tag_names(row.get(11)?)),
+                        billable: Some(row.get::<_, Option<i64>>(12)?.map(|b| b != 0)),
                     })
                 })
                 .map_err(|e| e.to_string())?;
@@ -235,13 +245,14 @@ fn build_export_archive(
         let mut manual_sessions = Vec::new();
         if !project_ids.is_empty() {
             let mut stmt = conn
-            .prepare(
+            .prepare(&format!(
                 "SELECT ms.id, ms.title, ms.session_type, ms.project_id, ms.app_id, ms.start_time, ms.end_time,
-                        ms.duration_seconds, ms.date, ms.created_at, ms.updated_at
+                        ms.duration_seconds, ms.date, ms.created_at, ms.updated_at, {}, ms.billable
                  FROM manual_sessions ms
                  INNER JOIN _export_project_ids e ON e.id = ms.project_id
                  WHERE ms.date >= ?1 AND ms.date <= ?2",
-            )
+                tags_json_sql(TagTarget::ManualSession, "ms")
+            ))
             .map_err(|e| e.to_string())?;
             let ms_rows = stmt
                 .query_map([&start, &end], |row| {
@@ -257,6 +268,8 @@ fn build_export_archive(
                         date: row.get(8)?,
                         created_at: row.get(9)?,
                         updated_at: row.get(10)?,
+                        tags: Some(parse_tag_names(row.get(11)?)),
+                        billable: Some(row.get::<_, Option<i64>>(12)?.map(|b| b != 0)),
                     })
                 })
                 .map_err(|e| e.to_string())?;
@@ -337,6 +350,51 @@ fn build_export_archive(
             }
         }
 
+        // 5.6 Clients & estimate settings. A single-project export carries only
+        // that project's client and no global settings.
+        let mut clients = super::delta_export::load_client_rows(&conn)?;
+        let estimate_settings = if project_id.is_some() {
+            clients.retain(|c| projects.iter().any(|p| p.client_name.as_ref().and_then(|n| n.as_deref()) == Some(c.name.as_str())));
+            Vec::new()
+        } else {
+            super::delta_export::load_estimate_setting_rows(&conn)?
+        };
+
+        // 5.65 Tags (m33). A single-project export carries only the tags its
+        // sessions use.
+        let mut tags = load_tag_rows(&conn)?;
+        if project_id.is_some() {
+            let used: std::collections::HashSet<&String> = sessions
+                .iter()
+                .filter_map(|s| s.tags.as_ref())
+                .chain(manual_sessions.iter().filter_map(|m| m.tags.as_ref()))
+                .flatten()
+                .collect();
+            tags.retain(|t| used.contains(&t.name));
+        }
+
+        // 5.7 Audit trail of the exported sessions (m31) — same scope as step 4.
+        let mut session_audit_log = Vec::new();
+        if !app_ids.is_empty() {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {}
+                     FROM session_audit_log l
+                     INNER JOIN sessions s ON s.id = l.session_id
+                     INNER JOIN _export_app_ids e ON e.id = s.app_id
+                     WHERE s.date >= ?1 AND s.date <= ?2
+                     ORDER BY l.id",
+                    super::session_audit::ENTRY_COLUMNS
+                ))
+                .map_err(|e| e.to_string())?;
+            let a_rows = stmt
+                .query_map([&start, &end], super::session_audit::row_to_entry)
+                .map_err(|e| e.to_string())?;
+            for a in a_rows {
+                session_audit_log.push(a.map_err(|e| e.to_string())?);
+            }
+        }
+
         // 6. Fetch Daily Files from the SQLite daily store.
         let demo_mode = db::is_demo_mode_enabled(app)?;
         let mut daily_files = if demo_mode {
@@ -377,6 +435,20 @@ fn build_export_archive(
                 .iter()
                 .map(|s| s.duration_seconds)
                 .sum::<i64>();
+        let dated_durations: Vec<(String, i64)> = sessions
+            .iter()
+            .map(|s| (s.date.clone(), s.duration_seconds))
+            .chain(manual_sessions.iter().map(|s| {
+                let date = if s.date.is_empty() {
+                    s.start_time.chars().take(10).collect()
+                } else {
+                    s.date.clone()
+                };
+                (date, s.duration_seconds)
+            }))
+            .collect();
+        let rounded_total_seconds =
+            round_dated_sessions(&dated_durations, &load_rounding_settings());
 
         let project_name = if let Some(pid) = project_id {
             projects
@@ -408,6 +480,7 @@ fn build_export_archive(
                 project_name,
                 total_sessions,
                 total_seconds,
+                rounded_total_seconds,
             },
             data: ExportData {
                 projects,
@@ -419,6 +492,10 @@ fn build_export_archive(
                 assignment_feedback: Vec::new(),
                 assignment_auto_runs: Vec::new(),
                 file_activities,
+                clients,
+                estimate_settings,
+                tags,
+                session_audit_log,
             },
         };
         (archive, default_name)
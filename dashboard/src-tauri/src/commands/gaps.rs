@@ -0,0 +1,580 @@
+// Untracked-gap detection.
+//
+// A gap is a stretch of the user's working hours covered by neither a tracked
+// session nor a manual session. The daemon records when it was running, idle
+// and asleep in the daily store (`tracker_spans`), so each gap is classified
+// by whichever of those dominates it. Fill suggestions come from the projects
+// of the sessions on either side; accepted fills become manual sessions.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, Local, LocalResult, NaiveDate, NaiveTime, TimeZone};
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+use super::daily_store::{
+    TrackerSpan, TRACKER_SPAN_IDLE, TRACKER_SPAN_RUNNING, TRACKER_SPAN_SLEEP,
+};
+use super::daily_store_bridge;
+use super::datetime::{format_local_naive, parse_datetime_fixed};
+use super::helpers::run_db_blocking;
+use super::manual_sessions::insert_manual_session;
+use super::sql_fragments::ensure_session_project_cache;
+use super::types::{
+    CreateManualSessionInput, DateRange, GapFillCandidate, GapFillInput, ManualSession,
+    UntrackedGap, UntrackedGapReport, WorkingHours,
+};
+
+const DEFAULT_MIN_GAP_MINUTES: i64 = 15;
+const GAP_FILL_SESSION_TYPE: &str = "other";
+const GAP_FILL_DEFAULT_TITLE: &str = "Untracked time";
+
+const GAP_KIND_SLEEP: &str = "sleep";
+const GAP_KIND_DAEMON_OFF: &str = "daemon_off";
+const GAP_KIND_IDLE: &str = "idle";
+const GAP_KIND_PAUSE: &str = "pause";
+const GAP_KIND_UNKNOWN: &str = "unknown";
+
+/// Tracked or manual time, as unix seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct BusyInterval {
+    start: i64,
+    end: i64,
+    project_id: Option<i64>,
+}
+
+/// Daemon history as merged unix-second intervals per span kind.
+#[derive(Default, Debug)]
+struct TrackerHistory {
+    running: Vec<(i64, i64)>,
+    idle: Vec<(i64, i64)>,
+    sleep: Vec<(i64, i64)>,
+}
+
+impl TrackerHistory {
+    fn from_spans(spans: &[TrackerSpan]) -> Self {
+        let mut history = TrackerHistory::default();
+        for span in spans {
+            let (Some(start), Some(end)) = (
+                parse_datetime_fixed(&span.start),
+                parse_datetime_fixed(&span.end),
+            ) else {
+                continue;
+            };
+            let interval = (start.timestamp(), end.timestamp());
+            match span.kind.as_str() {
+                TRACKER_SPAN_RUNNING => history.running.push(interval),
+                TRACKER_SPAN_IDLE => history.idle.push(interval),
+                TRACKER_SPAN_SLEEP => history.sleep.push(interval),
+                _ => {}
+            }
+        }
+        history.running = merge_intervals(history.running);
+        history.idle = merge_intervals(history.idle);
+        history.sleep = merge_intervals(history.sleep);
+        history
+    }
+
+    /// Whether the daemon left any trace within `window` — without one a
+    /// day predates span recording and gaps cannot be classified.
+    fn covers(&self, window: (i64, i64)) -> bool {
+        [&self.running, &self.idle, &self.sleep]
+            .iter()
+            .any(|intervals| overlap_seconds(window, intervals) > 0)
+    }
+}
+
+fn merge_intervals(mut intervals: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    intervals.retain(|(start, end)| end > start);
+    intervals.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Seconds of `range` covered by `intervals` (which must be merged).
+fn overlap_seconds(range: (i64, i64), intervals: &[(i64, i64)]) -> i64 {
+    intervals
+        .iter()
+        .map(|(start, end)| (range.1.min(*end) - range.0.max(*start)).max(0))
+        .sum()
+}
+
+/// Parts of `window` not covered by any busy interval, at least `min_secs` long.
+fn subtract_busy(window: (i64, i64), busy: &[BusyInterval], min_secs: i64) -> Vec<(i64, i64)> {
+    let covered = merge_intervals(busy.iter().map(|b| (b.start, b.end)).collect());
+    let mut gaps = Vec::new();
+    let mut cursor = window.0;
+    for (start, end) in covered {
+        if end <= cursor {
+            continue;
+        }
+        if start >= window.1 {
+            break;
+        }
+        if start > cursor {
+            gaps.push((cursor, start));
+        }
+        cursor = cursor.max(end);
+    }
+    if cursor < window.1 {
+        gaps.push((cursor, window.1));
+    }
+    gaps.retain(|(start, end)| end - start >= min_secs.max(1));
+    gaps
+}
+
+/// Dominant cause of a gap. Sleep wins over a stopped daemon, which wins over
+/// idle input; whatever is left while the daemon ran with input is a pause.
+fn classify_gap(gap: (i64, i64), history: &TrackerHistory, has_history: bool) -> &'static str {
+    if !has_history {
+        return GAP_KIND_UNKNOWN;
+    }
+    let duration = gap.1 - gap.0;
+    let sleep = overlap_seconds(gap, &history.sleep);
+    let mut alive = history.running.clone();
+    alive.extend_from_slice(&history.sleep);
+    let daemon_off = duration - overlap_seconds(gap, &merge_intervals(alive));
+    let idle = overlap_seconds(gap, &history.idle).min((duration - sleep - daemon_off).max(0));
+    let pause = (duration - sleep - daemon_off - idle).max(0);
+
+    [
+        (GAP_KIND_SLEEP, sleep),
+        (GAP_KIND_DAEMON_OFF, daemon_off),
+        (GAP_KIND_IDLE, idle),
+        (GAP_KIND_PAUSE, pause),
+    ]
+    .into_iter()
+    .fold((GAP_KIND_PAUSE, 0), |best, (kind, seconds)| {
+        if seconds > best.1 {
+            (kind, seconds)
+        } else {
+            best
+        }
+    })
+    .0
+}
+
+/// Projects of the nearest assigned sessions before and after `gap`, within `day`.
+fn neighbour_projects(
+    gap: (i64, i64),
+    day: (i64, i64),
+    busy: &[BusyInterval],
+) -> (Option<i64>, Option<i64>) {
+    let in_day = |b: &&BusyInterval| b.project_id.is_some() && b.end > day.0 && b.start < day.1;
+    let previous = busy
+        .iter()
+        .filter(in_day)
+        .filter(|b| b.end <= gap.0)
+        .max_by_key(|b| b.end)
+        .and_then(|b| b.project_id);
+    let next = busy
+        .iter()
+        .filter(in_day)
+        .filter(|b| b.start >= gap.1)
+        .min_by_key(|b| b.start)
+        .and_then(|b| b.project_id);
+    (previous, next)
+}
+
+fn parse_working_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid working hours time: {}", value))
+}
+
+fn local_timestamp(date: NaiveDate, time: NaiveTime) -> Option<i64> {
+    match Local.from_local_datetime(&date.and_time(time)) {
+        LocalResult::Single(value) | LocalResult::Ambiguous(value, _) => Some(value.timestamp()),
+        LocalResult::None => None,
+    }
+}
+
+fn load_busy_intervals(
+    conn: &Connection,
+    date_range: &DateRange,
+) -> Result<Vec<BusyInterval>, String> {
+    ensure_session_project_cache(conn, &date_range.start, &date_range.end)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT start_time, end_time, project_id FROM session_project_cache
+             WHERE session_date >= ?1 AND session_date <= ?2
+             UNION ALL
+             SELECT start_time, end_time, project_id FROM manual_sessions
+             WHERE date >= ?1 AND date <= ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([&date_range.start, &date_range.end], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut busy = Vec::new();
+    for row in rows {
+        let (start, end, project_id) = row.map_err(|e| e.to_string())?;
+        let (Some(start), Some(end)) = (parse_datetime_fixed(&start), parse_datetime_fixed(&end))
+        else {
+            continue;
+        };
+        if end > start {
+            busy.push(BusyInterval {
+                start: start.timestamp(),
+                end: end.timestamp(),
+                project_id,
+            });
+        }
+    }
+    busy.sort_by_key(|b| (b.start, b.end));
+    Ok(busy)
+}
+
+/// Active projects by id: (name, color). Excluded and frozen projects are
+/// left out so they are never suggested.
+fn load_active_projects(conn: &Connection) -> Result<HashMap<i64, (String, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, color FROM projects
+             WHERE excluded_at IS NULL AND frozen_at IS NULL",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                (row.get::<_, String>(1)?, row.get::<_, String>(2)?),
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+fn gap_candidates(
+    previous: Option<i64>,
+    next: Option<i64>,
+    projects: &HashMap<i64, (String, String)>,
+) -> Vec<GapFillCandidate> {
+    let sources: Vec<(i64, &str)> = match (previous, next) {
+        (Some(p), Some(n)) if p == n => vec![(p, "both")],
+        _ => previous
+            .map(|p| (p, "previous"))
+            .into_iter()
+            .chain(next.map(|n| (n, "next")))
+            .collect(),
+    };
+    sources
+        .into_iter()
+        .filter_map(|(project_id, source)| {
+            projects
+                .get(&project_id)
+                .map(|(name, color)| GapFillCandidate {
+                    project_id,
+                    project_name: name.clone(),
+                    project_color: color.clone(),
+                    source: source.to_string(),
+                })
+        })
+        .collect()
+}
+
+fn build_gap_report(
+    date_range: &DateRange,
+    working_hours: &WorkingHours,
+    min_gap_secs: i64,
+    now: i64,
+    busy: &[BusyInterval],
+    history: &TrackerHistory,
+    projects: &HashMap<i64, (String, String)>,
+) -> Result<UntrackedGapReport, String> {
+    let start_date = NaiveDate::parse_from_str(&date_range.start, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid start date: {}", e))?;
+    let end_date = NaiveDate::parse_from_str(&date_range.end, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid end date: {}", e))?;
+    let work_start = parse_working_time(&working_hours.start)?;
+    let work_end = parse_working_time(&working_hours.end)?;
+    if work_end <= work_start {
+        return Err("Working hours must end after they start".to_string());
+    }
+
+    let mut report = UntrackedGapReport::default();
+    let mut date = start_date;
+    while date <= end_date {
+        let window = local_timestamp(date, work_start).zip(local_timestamp(date, work_end));
+        let day = local_timestamp(date, NaiveTime::MIN)
+            .zip(local_timestamp(date + Duration::days(1), NaiveTime::MIN));
+        if let (Some((window_start, window_end)), Some(day)) = (window, day) {
+            let window = (window_start, window_end.min(now));
+            if window.1 > window.0 {
+                let has_history = history.covers(window);
+                for gap in subtract_busy(window, busy, min_gap_secs) {
+                    let kind = classify_gap(gap, history, has_history);
+                    let (previous, next) = neighbour_projects(gap, day, busy);
+                    let candidates = gap_candidates(previous, next, projects);
+                    let suggested_project_id = if kind == GAP_KIND_SLEEP {
+                        None
+                    } else {
+                        candidates.first().map(|c| c.project_id)
+                    };
+                    let duration_seconds = gap.1 - gap.0;
+                    report.total_seconds += duration_seconds;
+                    match kind {
+                        GAP_KIND_SLEEP => report.sleep_seconds += duration_seconds,
+                        GAP_KIND_DAEMON_OFF => report.daemon_off_seconds += duration_seconds,
+                        GAP_KIND_IDLE => report.idle_seconds += duration_seconds,
+                        GAP_KIND_PAUSE => report.pause_seconds += duration_seconds,
+                        _ => report.unknown_seconds += duration_seconds,
+                    }
+                    report.gaps.push(UntrackedGap {
+                        date: date.format("%Y-%m-%d").to_string(),
+                        start_time: format_local_naive(gap.0),
+                        end_time: format_local_naive(gap.1),
+                        duration_seconds,
+                        kind: kind.to_string(),
+                        suggested_project_id,
+                        candidates,
+                    });
+                }
+            }
+        }
+        date += Duration::days(1);
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn get_untracked_gaps(
+    app: AppHandle,
+    date_range: DateRange,
+    working_hours: WorkingHours,
+    min_gap_minutes: Option<i64>,
+) -> Result<UntrackedGapReport, String> {
+    let min_gap_secs = min_gap_minutes.unwrap_or(DEFAULT_MIN_GAP_MINUTES).max(1) * 60;
+    let spans = daily_store_bridge::load_tracker_spans(&date_range.start, &date_range.end)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load tracker spans for gap detection: {}", e);
+            Vec::new()
+        });
+    let history = TrackerHistory::from_spans(&spans);
+
+    run_db_blocking(app, move |conn| {
+        let busy = load_busy_intervals(conn, &date_range)?;
+        let projects = load_active_projects(conn)?;
+        build_gap_report(
+            &date_range,
+            &working_hours,
+            min_gap_secs,
+            Local::now().timestamp(),
+            &busy,
+            &history,
+            &projects,
+        )
+    })
+    .await
+}
+
+/// Creates one manual session per accepted fill, all or nothing.
+#[tauri::command]
+pub async fn accept_gap_fills(
+    app: AppHandle,
+    fills: Vec<GapFillInput>,
+) -> Result<Vec<ManualSession>, String> {
+    if fills.is_empty() {
+        return Ok(Vec::new());
+    }
+    run_db_blocking(app, move |conn| {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut created = Vec::with_capacity(fills.len());
+        for fill in fills {
+            let title = fill
+                .title
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| GAP_FILL_DEFAULT_TITLE.to_string());
+            let input = CreateManualSessionInput {
+                title,
+                session_type: GAP_FILL_SESSION_TYPE.to_string(),
+                project_id: fill.project_id,
+                app_id: None,
+                start_time: fill.start_time,
+                end_time: fill.end_time,
+            };
+            created.push(insert_manual_session(&tx, &input)?);
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(created)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn busy(start: i64, end: i64, project_id: Option<i64>) -> BusyInterval {
+        BusyInterval {
+            start,
+            end,
+            project_id,
+        }
+    }
+
+    #[test]
+    fn gaps_are_working_hours_minus_busy_time() {
+        let window = (1_000, 10_000);
+        let sessions = vec![
+            busy(500, 2_000, Some(1)),
+            busy(1_800, 3_000, None),
+            busy(5_000, 5_500, Some(2)),
+            busy(9_950, 12_000, Some(2)),
+        ];
+
+        assert_eq!(
+            subtract_busy(window, &sessions, 60),
+            vec![(3_000, 5_000), (5_500, 9_950)]
+        );
+        assert_eq!(
+            subtract_busy(window, &sessions, 3_000),
+            vec![(5_500, 9_950)]
+        );
+        assert_eq!(subtract_busy(window, &[], 60), vec![window]);
+    }
+
+    #[test]
+    fn gaps_are_classified_by_dominant_tracker_state() {
+        let history = TrackerHistory {
+            running: merge_intervals(vec![(0, 1_000), (1_000, 2_000), (4_000, 9_000)]),
+            idle: vec![(6_000, 8_500)],
+            sleep: vec![(2_000, 3_500)],
+        };
+
+        assert_eq!(classify_gap((2_000, 3_600), &history, true), GAP_KIND_SLEEP);
+        assert_eq!(
+            classify_gap((3_500, 4_200), &history, true),
+            GAP_KIND_DAEMON_OFF
+        );
+        assert_eq!(classify_gap((6_000, 8_000), &history, true), GAP_KIND_IDLE);
+        assert_eq!(classify_gap((4_000, 6_500), &history, true), GAP_KIND_PAUSE);
+        assert_eq!(
+            classify_gap((4_000, 6_500), &history, false),
+            GAP_KIND_UNKNOWN
+        );
+        assert!(history.covers((8_000, 20_000)));
+        assert!(!history.covers((9_000, 20_000)));
+    }
+
+    #[test]
+    fn fill_suggestions_come_from_neighbouring_projects() {
+        let day = (0, 86_400);
+        let sessions = vec![
+            busy(100, 200, Some(1)),
+            busy(250, 300, None),
+            busy(900, 1_000, Some(2)),
+            busy(86_500, 86_600, Some(3)),
+        ];
+        let projects = HashMap::from([
+            (1, ("Alpha".to_string(), "#111111".to_string())),
+            (2, ("Beta".to_string(), "#222222".to_string())),
+        ]);
+
+        let (previous, next) = neighbour_projects((300, 900), day, &sessions);
+        assert_eq!((previous, next), (Some(1), Some(2)));
+        let candidates = gap_candidates(previous, next, &projects);
+        let summary: Vec<(i64, &str)> = candidates
+            .iter()
+            .map(|c| (c.project_id, c.source.as_str()))
+            .collect();
+        assert_eq!(summary, vec![(1, "previous"), (2, "next")]);
+
+        // The next-day session is out of reach; inactive projects are dropped.
+        assert_eq!(
+            neighbour_projects((1_000, 2_000), day, &sessions),
+            (Some(2), None)
+        );
+        assert_eq!(
+            gap_candidates(Some(3), Some(3), &projects),
+            Vec::<GapFillCandidate>::new()
+        );
+        assert_eq!(
+            gap_candidates(Some(2), Some(2), &projects)[0].source,
+            "both"
+        );
+    }
+
+    #[test]
+    fn report_totals_follow_gap_kinds_and_skip_suggestions_for_sleep() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 10).expect("date");
+        let at = |h: u32, m: u32| {
+            local_timestamp(date, NaiveTime::from_hms_opt(h, m, 0).expect("time")).expect("ts")
+        };
+        let sessions = vec![
+            busy(at(9, 0), at(10, 0), Some(1)),
+            busy(at(11, 0), at(12, 0), Some(1)),
+        ];
+        let history = TrackerHistory {
+            running: vec![(at(8, 0), at(10, 15))],
+            idle: Vec::new(),
+            sleep: vec![(at(10, 15), at(11, 0))],
+        };
+        let projects = HashMap::from([(1, ("Alpha".to_string(), "#111111".to_string()))]);
+        let range = DateRange {
+            start: "2026-03-10".to_string(),
+            end: "2026-03-10".to_string(),
+        };
+        let hours = WorkingHours {
+            start: "09:00".to_string(),
+            end: "13:00".to_string(),
+        };
+
+        let report = build_gap_report(
+            &range,
+            &hours,
+            15 * 60,
+            at(12, 30),
+            &sessions,
+            &history,
+            &projects,
+        )
+        .expect("report");
+
+        let kinds: Vec<(&str, i64, Option<i64>)> = report
+            .gaps
+            .iter()
+            .map(|g| (g.kind.as_str(), g.duration_seconds, g.suggested_project_id))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (GAP_KIND_SLEEP, 3_600, None),
+                (GAP_KIND_DAEMON_OFF, 1_800, Some(1)),
+            ]
+        );
+        assert_eq!(report.gaps[0].start_time, "2026-03-10T10:00:00");
+        assert_eq!(report.total_seconds, 5_400);
+        assert_eq!(report.sleep_seconds, 3_600);
+        assert_eq!(report.daemon_off_seconds, 1_800);
+
+        let bad_hours = WorkingHours {
+            start: "17:00".to_string(),
+            end: "09:00".to_string(),
+        };
+        assert!(build_gap_report(
+            &range,
+            &bad_hours,
+            60,
+            at(12, 30),
+            &sessions,
+            &history,
+            &projects
+        )
+        .is_err());
+    }
+}
@@ -1,11 +1,13 @@
+use super::daily_store;
 use super::daily_store_bridge;
 use super::helpers::{run_app_blocking, timeflow_data_dir, validate_import_path};
+use super::tags::{self, TagTarget};
 use super::types::{
-    ExportArchive, FileActivityExportRow, ImportSummary, ImportValidation, SessionConflict,
-    SessionRow,
+    ClientRow, EstimateSettingRow, ExportArchive, FileActivityExportRow, ImportSummary,
+    ImportValidation, SessionConflict, SessionRow,
 };
 use crate::db;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -354,6 +356,44 @@ fn apply_archive_tombstones(
             "manual_sessions" => {
                 apply_manual_session_tombstone(tx, sync_key, &t.deleted_at)?;
             }
+            "clients" => {
+                let local_updated: Option<String> = tx
+                    .query_row(
+                        "SELECT updated_at FROM clients WHERE name = ?1",
+                        [sync_key.as_str()],
+                        |row| row.get(0),
+                    )
+                    .ok();
+                if let Some(ref lu) = local_updated {
+                    let norm_local = super::delta_export::normalize_datetime_for_sqlite_pub(lu);
+                    let norm_deleted =
+                        super::delta_export::normalize_datetime_for_sqlite_pub(&t.deleted_at);
+                    if norm_local > norm_deleted {
+                        continue; // Client edited after deletion — skip
+                    }
+                }
+                // Project links are cleared in import_clients, after the
+                // archive's project rows are merged (see there).
+                tx.execute("DELETE FROM clients WHERE name = ?1", [sync_key.as_str()])
+                    .ok();
+            }
+            "tags" => {
+                let local_updated: Option<String> = tx
+                    .query_row(
+                        "SELECT updated_at FROM tags WHERE name = ?1",
+                        [sync_key.as_str()],
+                        |row| row.get(0),
+                    )
+                    .ok();
+                if let Some(ref lu) = local_updated {
+                    if archive_row_is_newer(lu, &t.deleted_at) {
+                        continue; // Tag edited after deletion — skip
+                    }
+                }
+                // Links go with the tag (ON DELETE CASCADE).
+                tx.execute("DELETE FROM tags WHERE name = ?1", [sync_key.as_str()])
+                    .ok();
+            }
             _ => {}
         }
     }
@@ -486,24 +526,36 @@ fn import_archive_into_tx(
 
             if p.updated_at > local_updated_at {
                 tx.execute(
+                    // hourly_rate is taken as-is: NULL means "rate cleared"
+                    // on the newer side, not "unknown". merged_* and
+                    // client_name follow the daemon merge: a missing key
+                    // (older archive) keeps the local value, an explicit
+                    // null clears it (unmerge / client removed).
                     "UPDATE projects
                      SET color = ?1,
-                         hourly_rate = COALESCE(?2, hourly_rate),
+                         hourly_rate = ?2,
                          frozen_at = COALESCE(?3, frozen_at),
                          excluded_at = COALESCE(?4, excluded_at),
-                         merged_into = COALESCE(?5, merged_into),
-                         merged_at = COALESCE(?6, merged_at),
-                         updated_at = ?7
-                     WHERE id = ?8",
+                         merged_into = CASE WHEN ?11 THEN ?5 ELSE merged_into END,
+                         merged_at = CASE WHEN ?12 THEN ?6 ELSE merged_at END,
+                         client_name = CASE WHEN ?13 THEN ?7 ELSE client_name END,
+                         updated_at = ?8,
+                         billable = COALESCE(?10, billable)
+                     WHERE id = ?9",
                     rusqlite::params![
                         p.color,
                         p.hourly_rate,
                         p.frozen_at,
                         p.excluded_at,
-                        p.merged_into,
-                        p.merged_at,
+                        p.merged_into.clone().flatten(),
+                        p.merged_at.clone().flatten(),
+                        p.client_name.clone().flatten(),
                         p.updated_at,
-                        id
+                        id,
+                        p.billable,
+                        p.merged_into.is_some(),
+                        p.merged_at.is_some(),
+                        p.client_name.is_some()
                     ],
                 )
                 .map_err(|e| e.to_string())?;
@@ -511,8 +563,8 @@ fn import_archive_into_tx(
             id
         } else {
             tx.execute(
-                "INSERT INTO projects (name, color, hourly_rate, created_at, excluded_at, assigned_folder_path, is_imported, frozen_at, merged_into, merged_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, NULL, 1, ?6, ?7, ?8, ?9)",
-                rusqlite::params![p.name, p.color, p.hourly_rate, p.created_at, p.excluded_at, p.frozen_at, p.merged_into, p.merged_at, p.updated_at]
+                "INSERT INTO projects (name, color, hourly_rate, created_at, excluded_at, assigned_folder_path, is_imported, frozen_at, merged_into, merged_at, client_name, updated_at, billable) VALUES (?1, ?2, ?3, ?4, ?5, NULL, 1, ?6, ?7, ?8, ?9, ?10, COALESCE(?11, 1))",
+                rusqlite::params![p.name, p.color, p.hourly_rate, p.created_at, p.excluded_at, p.frozen_at, p.merged_into.clone().flatten(), p.merged_at.clone().flatten(), p.client_name.clone().flatten(), p.updated_at, p.billable]
             ).map_err(|e| e.to_string())?;
             summary.projects_created += 1;
             let new_id = tx.last_insert_rowid();
@@ -522,6 +574,12 @@ fn import_archive_into_tx(
         project_mapping.insert(p.id, id);
     }
 
+    // 1b. Clients, estimate settings & tags (LWW per name / key) — tags
+    // before sessions, which link them by name.
+    import_clients(tx, &archive.data.clients)?;
+    import_estimate_settings(tx, &archive.data.estimate_settings)?;
+    tags::import_tags(tx, &archive.data.tags)?;
+
     // 2. Map and Create Applications
     let mut existing_apps_map: HashMap<String, i64> = HashMap::new();
     let mut existing_apps_display_map: HashMap<String, i64> = HashMap::new();
@@ -628,6 +686,8 @@ fn import_archive_into_tx(
                 is_hidden: s.is_hidden,
                 updated_at: s.updated_at.clone(),
                 project_name: s.project_name.clone(),
+                tags: s.tags.clone(),
+                billable: s.billable,
             };
 
             let merged = merge_or_insert_session(tx, local_app_id, &incoming)?;
@@ -653,13 +713,17 @@ fn import_archive_into_tx(
 
             if let Some((local_id, local_updated_at)) = local_status {
                 if ms.updated_at > local_updated_at {
+                    if let Some(ref names) = ms.tags {
+                        tags::link_tag_names(tx, TagTarget::ManualSession, local_id, names, true)?;
+                    }
                     tx.execute(
                         "UPDATE manual_sessions SET
                             session_type = ?1,
                             end_time = ?2,
                             duration_seconds = ?3,
                             updated_at = ?4,
-                            app_id = ?5
+                            app_id = ?5,
+                            billable = CASE WHEN ?7 THEN ?8 ELSE billable END
                          WHERE id = ?6",
                         rusqlite::params![
                             ms.session_type,
@@ -667,17 +731,23 @@ fn import_archive_into_tx(
                             ms.duration_seconds,
                             ms.updated_at,
                             local_manual_app_id,
-                            local_id
+                            local_id,
+                            ms.billable.is_some(),
+                            ms.billable.flatten()
                         ],
                     )
                     .map_err(|e| e.to_string())?;
                 }
             } else {
                 tx.execute(
-                    "INSERT INTO manual_sessions (title, session_type, project_id, app_id, start_time, end_time, duration_seconds, date, created_at, updated_at)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-                    rusqlite::params![ms.title, ms.session_type, local_pid, local_manual_app_id, ms.start_time, ms.end_time, ms.duration_seconds, ms.date, ms.created_at, ms.updated_at]
+                    "INSERT INTO manual_sessions (title, session_type, project_id, app_id, start_time, end_time, duration_seconds, date, created_at, updated_at, billable)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    rusqlite::params![ms.title, ms.session_type, local_pid, local_manual_app_id, ms.start_time, ms.end_time, ms.duration_seconds, ms.date, ms.created_at, ms.updated_at, ms.billable.flatten()]
                 ).map_err(|e| e.to_string())?;
+                if let Some(ref names) = ms.tags {
+                    let new_id = tx.last_insert_rowid();
+                    tags::link_tag_names(tx, TagTarget::ManualSession, new_id, names, false)?;
+                }
             }
         }
     }
@@ -827,10 +897,116 @@ fn import_archive_into_tx(
     Ok(summary)
 }
 
+fn archive_row_is_newer(remote_updated_at: &str, local_updated_at: &str) -> bool {
+    super::delta_export::normalize_datetime_for_sqlite_pub(remote_updated_at)
+        > super::delta_export::normalize_datetime_for_sqlite_pub(local_updated_at)
+}
+
+/// Importuje klientów z archiwum: klucz `name`, LWW po `updated_at`. Na koniec
+/// odpina projekty od klientów skasowanych (tombstone bez lokalnego wiersza) —
+/// lustro `verify_merge_integrity` demona.
+fn import_clients(tx: &rusqlite::Transaction<'_>, rows: &[ClientRow]) -> Result<(), String> {
+    for c in rows {
+        if c.name.trim().is_empty() {
+            continue;
+        }
+        let local_updated: Option<String> = tx
+            .query_row(
+                "SELECT updated_at FROM clients WHERE name = ?1",
+                [c.name.as_str()],
+                |row| row.get(0),
+            )
+            .ok();
+        match local_updated {
+            Some(ref lu) if !archive_row_is_newer(&c.updated_at, lu) => {}
+            Some(_) => {
+                tx.execute(
+                    "UPDATE clients
+                     SET contact = ?1, address = ?2, tax_id = ?3, currency = ?4,
+                         default_hourly_rate = ?5, color = ?6, archived_at = ?7, updated_at = ?8
+                     WHERE name = ?9",
+                    rusqlite::params![
+                        c.contact,
+                        c.address,
+                        c.tax_id,
+                        c.currency,
+                        c.default_hourly_rate,
+                        c.color,
+                        c.archived_at,
+                        c.updated_at,
+                        c.name
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO clients (name, contact, address, tax_id, currency, default_hourly_rate, color, archived_at, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    rusqlite::params![
+                        c.name,
+                        c.contact,
+                        c.address,
+                        c.tax_id,
+                        c.currency,
+                        c.default_hourly_rate,
+                        c.color,
+                        c.archived_at,
+                        c.created_at,
+                        c.updated_at
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    tx.execute(
+        "UPDATE projects SET client_name = NULL
+         WHERE client_name IS NOT NULL
+           AND client_name NOT IN (SELECT name FROM clients)
+           AND client_name IN (SELECT sync_key FROM tombstones WHERE table_name = 'clients')",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Importuje `estimate_settings` (stawka globalna, algorytm czasu) — LWW per klucz.
+fn import_estimate_settings(
+    tx: &rusqlite::Transaction<'_>,
+    rows: &[EstimateSettingRow],
+) -> Result<(), String> {
+    for row in rows {
+        let local_updated: Option<String> = tx
+            .query_row(
+                "SELECT updated_at FROM estimate_settings WHERE key = ?1",
+                [row.key.as_str()],
+                |r| r.get(0),
+            )
+            .ok();
+        if let Some(ref lu) = local_updated {
+            if !archive_row_is_newer(&row.updated_at, lu) {
+                continue;
+            }
+        }
+        tx.execute(
+            "INSERT INTO estimate_settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            rusqlite::params![row.key, row.value, row.updated_at],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 /// Importuje wiersze `file_activities` z archiwum: app_id/project_id mapowane
 /// na lokalne ID, upsert po UNIQUE(app_id, date, file_path) — total_seconds
-/// bierze MAX, first_seen MIN, last_seen MAX, pola opisowe COALESCE (lokalne
-/// wygrywają), activity_spans zastępowane tylko gdy lokalnie puste ('[]').
+/// bierze MAX, first_seen MIN, last_seen MAX, pola opisowe (projekt, tytuł,
+/// ścieżka, historia tytułów, typ) ze strony z nowszym last_seen, z fallbackiem
+/// na tę, która je ma — jak merge w demonie. activity_spans scalane z
+/// lokalnymi (`merge_activity_spans_json` — semantyka `extend_activity_spans`,
+/// idempotentne).
 /// Wiersze bez zmapowanej aplikacji są pomijane. Zwraca liczbę
 /// wstawionych/scalonych wierszy.
 fn import_file_activities(
@@ -851,12 +1027,27 @@ fn import_file_activities(
                 total_seconds = CASE WHEN excluded.total_seconds > file_activities.total_seconds THEN excluded.total_seconds ELSE file_activities.total_seconds END,
                 first_seen = CASE WHEN excluded.first_seen < file_activities.first_seen THEN excluded.first_seen ELSE file_activities.first_seen END,
                 last_seen = CASE WHEN excluded.last_seen > file_activities.last_seen THEN excluded.last_seen ELSE file_activities.last_seen END,
-                project_id = COALESCE(file_activities.project_id, excluded.project_id),
-                window_title = COALESCE(file_activities.window_title, excluded.window_title),
-                detected_path = COALESCE(file_activities.detected_path, excluded.detected_path),
-                title_history = COALESCE(file_activities.title_history, excluded.title_history),
-                activity_type = COALESCE(file_activities.activity_type, excluded.activity_type),
-                activity_spans = CASE WHEN file_activities.activity_spans = '[]' THEN excluded.activity_spans ELSE file_activities.activity_spans END",
+                project_id = CASE WHEN excluded.last_seen > file_activities.last_seen
+                    THEN COALESCE(excluded.project_id, file_activities.project_id)
+                    ELSE COALESCE(file_activities.project_id, excluded.project_id) END,
+                window_title = CASE WHEN excluded.last_seen > file_activities.last_seen
+                    THEN COALESCE(excluded.window_title, file_activities.window_title)
+                    ELSE COALESCE(file_activities.window_title, excluded.window_title) END,
+                detected_path = CASE WHEN excluded.last_seen > file_activities.last_seen
+                    THEN COALESCE(excluded.detected_path, file_activities.detected_path)
+                    ELSE COALESCE(file_activities.detected_path, excluded.detected_path) END,
+                title_history = CASE WHEN excluded.last_seen > file_activities.last_seen
+                    THEN COALESCE(excluded.title_history, file_activities.title_history)
+                    ELSE COALESCE(file_activities.title_history, excluded.title_history) END,
+                activity_type = CASE WHEN excluded.last_seen > file_activities.last_seen
+                    THEN COALESCE(excluded.activity_type, file_activities.activity_type)
+                    ELSE COALESCE(file_activities.activity_type, excluded.activity_type) END,
+                activity_spans = excluded.activity_spans",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut spans_stmt = tx
+        .prepare_cached(
+            "SELECT activity_spans FROM file_activities WHERE app_id = ?1 AND date = ?2 AND file_path = ?3",
         )
         .map_err(|e| e.to_string())?;
 
@@ -870,6 +1061,10 @@ fn import_file_activities(
         let local_project_id = fa
             .project_id
             .and_then(|old_pid| project_mapping.get(&old_pid).copied());
+        let local_spans: String = spans_stmt
+            .query_row(rusqlite::params![local_app_id, fa.date, fa.file_path], |row| row.get(0))
+            .unwrap_or_else(|_| "[]".to_string());
+        let spans = daily_store::merge_activity_spans_json(&local_spans, &fa.activity_spans);
 
         stmt.execute(rusqlite::params![
             local_app_id,
@@ -884,7 +1079,7 @@ fn import_file_activities(
             fa.detected_path,
             fa.title_history,
             fa.activity_type,
-            fa.activity_spans,
+            spans,
         ])
         .map_err(|e| format!("Failed to import file_activities row: {}", e))?;
         imported += 1;
@@ -993,6 +1188,18 @@ fn merge_or_insert_session(
     // rows may overlap — the wall-clock CTE only counts visible rows.
     let merged_is_hidden = incoming.is_hidden;
     let mut overlap_ids: HashSet<i64> = HashSet::new();
+    // Tags: the incoming list, plus the tags of absorbed rows the archive
+    // does not supersede (row edited here after the archive's version).
+    let mut merged_tags: BTreeSet<String> = incoming
+        .tags
+        .iter()
+        .flatten()
+        .cloned()
+        .collect();
+    // Billable override: the archive's value, unless an absorbed row was
+    // edited here later — then the first local override survives.
+    let mut merged_billable: Option<Option<bool>> = incoming.billable;
+    let mut local_billable_kept = false;
 
     // Expand interval until closure: if merged range touches more sessions,
     // include them too so we end with one normalized interval.
@@ -1000,7 +1207,8 @@ fn merge_or_insert_session(
         let mut stmt = tx
             .prepare(
                 "SELECT id, start_time, end_time, project_id
-                        , COALESCE(rate_multiplier, 1.0), comment, project_name
+                        , COALESCE(rate_multiplier, 1.0), comment, project_name, updated_at
+                        , billable
                  FROM sessions
                  WHERE app_id = ?1 AND date = ?2
                    AND start_time <= ?3
@@ -1027,6 +1235,8 @@ fn merge_or_insert_session(
                         row.get::<_, f64>(4)?,
                         row.get::<_, Option<String>>(5)?,
                         row.get::<_, Option<String>>(6)?,
+                        row.get::<_, Option<String>>(7)?,
+                        row.get::<_, Option<i64>>(8)?,
                     ))
                 },
             )
@@ -1034,9 +1244,34 @@ fn merge_or_insert_session(
 
         let prev_count = overlap_ids.len();
         for row in rows {
-            let (id, start, end, project_id, rate_multiplier, comment, project_name) =
-                row.map_err(|e| e.to_string())?;
-            overlap_ids.insert(id);
+            let (
+                id,
+                start,
+                end,
+                project_id,
+                rate_multiplier,
+                comment,
+                project_name,
+                updated_at,
+                billable,
+            ) = row.map_err(|e| e.to_string())?;
+            if !overlap_ids.insert(id) {
+                continue;
+            }
+            let archive_newer = match (incoming.updated_at.as_deref(), updated_at.as_deref()) {
+                (Some(remote), Some(local)) => archive_row_is_newer(remote, local),
+                _ => false,
+            };
+            if !(incoming.tags.is_some() && archive_newer) {
+                merged_tags.extend(tags::session_tag_names(tx, id)?);
+            }
+            if billable.is_some()
+                && !local_billable_kept
+                && !(incoming.billable.is_some() && archive_newer)
+            {
+                merged_billable = Some(billable.map(|b| b != 0));
+                local_billable_kept = true;
+            }
             merged_start = min_timestamp(&merged_start, &start);
             merged_end = max_timestamp(&merged_end, &end);
             if merged_project_id.is_none() {
@@ -1084,6 +1319,8 @@ fn merge_or_insert_session(
             incoming.comment.as_deref(),
             incoming.is_hidden,
         )?;
+        link_merged_tags(tx, local_app_id, &incoming.start_time, &merged_tags)?;
+        set_merged_billable(tx, local_app_id, &incoming.start_time, merged_billable)?;
         return Ok(false);
     }
 
@@ -1118,10 +1355,53 @@ fn merge_or_insert_session(
         final_comment.as_deref(),
         merged_is_hidden,
     )?;
+    link_merged_tags(tx, local_app_id, &merged_start, &merged_tags)?;
+    set_merged_billable(tx, local_app_id, &merged_start, merged_billable)?;
 
     Ok(true)
 }
 
+/// Writes the merged billable override (`None` = archive without the field,
+/// nothing to write) onto the session upserted at (app_id, start_time).
+fn set_merged_billable(
+    tx: &rusqlite::Transaction<'_>,
+    app_id: i64,
+    start_time: &str,
+    billable: Option<Option<bool>>,
+) -> Result<(), String> {
+    let Some(billable) = billable else {
+        return Ok(());
+    };
+    tx.execute(
+        "UPDATE sessions SET billable = ?3 WHERE app_id = ?1 AND start_time = ?2",
+        rusqlite::params![app_id, start_time, billable.map(i64::from)],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Adds `names` to the session upserted at (app_id, start_time). Adding, not
+/// replacing: on a cross-track collision the row kept its own tags.
+fn link_merged_tags(
+    tx: &rusqlite::Transaction<'_>,
+    app_id: i64,
+    start_time: &str,
+    names: &BTreeSet<String>,
+) -> Result<(), String> {
+    if names.is_empty() {
+        return Ok(());
+    }
+    let session_id: i64 = tx
+        .query_row(
+            "SELECT id FROM sessions WHERE app_id = ?1 AND start_time = ?2",
+            rusqlite::params![app_id, start_time],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let names: Vec<String> = names.iter().cloned().collect();
+    tags::link_tag_names(tx, TagTarget::Session, session_id, &names, false)
+}
+
 /// Single upsert point for session intervals. On an exact (app_id, start_time)
 /// collision — possible across visibility tracks — the interval is widened to
 /// the larger end and visibility wins (counting time someone hid on one
@@ -1367,9 +1647,17 @@ mod tests {
                 rate_multiplier REAL NOT NULL DEFAULT 1.0,
                 comment TEXT,
                 is_hidden INTEGER NOT NULL DEFAULT 0,
-                project_name TEXT
+                project_name TEXT,
+                updated_at TEXT,
+                billable INTEGER
             );
-            CREATE UNIQUE INDEX idx_sessions_app_start ON sessions(app_id, start_time);",
+            CREATE UNIQUE INDEX idx_sessions_app_start ON sessions(app_id, start_time);
+            CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE COLLATE NOCASE);
+            CREATE TABLE session_tags (
+                session_id INTEGER NOT NULL,
+                tag_id INTEGER NOT NULL,
+                PRIMARY KEY (session_id, tag_id)
+            );",
         )
         .expect("create sessions schema");
         conn
@@ -1417,6 +1705,8 @@ mod tests {
             is_hidden: false,
             updated_at: None,
             project_name: None,
+            tags: None,
+            billable: None,
         };
 
         let merged = merge_or_insert_session(&tx, 1, &incoming).expect("merge");
@@ -1451,6 +1741,8 @@ mod tests {
             is_hidden: hidden,
             updated_at: None,
             project_name: None,
+            tags: None,
+            billable: None,
         }
     }
 
@@ -1622,6 +1914,75 @@ mod tests {
         assert_eq!(tombstones, 1, "tombstone triggers must be re-created after clear");
     }
 
+    #[test]
+    fn merged_session_keeps_tags_the_archive_does_not_supersede() {
+        let mut conn = full_schema_conn();
+        conn.execute_batch(
+            "INSERT INTO applications (id, executable_name, display_name) VALUES (1, 'app', 'App');
+             INSERT INTO tags (name, updated_at) VALUES ('local', '2026-01-01 00:00:00'),
+                                                    ('remote', '2026-01-01 00:00:00');
+             INSERT INTO sessions (id, app_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES (1, 1, '2026-01-01T10:00:00', '2026-01-01T11:00:00', 3600, '2026-01-01', '2026-01-02 08:00:00');
+             INSERT INTO session_tags (session_id, tag_id) VALUES (1, 1);",
+        )
+        .expect("seed");
+        let incoming = |updated_at: &str| SessionRow {
+            tags: Some(vec!["remote".to_string(), "unknown".to_string()]),
+            updated_at: Some(updated_at.to_string()),
+            ..incoming_session("2026-01-01T10:30:00", "2026-01-01T11:30:00", 3600, false)
+        };
+        let tags_of_merged = |conn: &rusqlite::Connection| {
+            let id: i64 = conn
+                .query_row("SELECT id FROM sessions WHERE app_id = 1", [], |r| r.get(0))
+                .expect("merged row");
+            tags::session_tag_names(conn, id).expect("tags")
+        };
+
+        // Older archive row: local tags survive next to the incoming ones.
+        let tx = conn.transaction().expect("tx");
+        assert!(merge_or_insert_session(&tx, 1, &incoming("2026-01-02 07:00:00")).expect("merge"));
+        tx.commit().expect("commit");
+        assert_eq!(tags_of_merged(&conn), vec!["local", "remote"]);
+
+        // Newer archive row supersedes the local tag list.
+        conn.execute("UPDATE sessions SET updated_at = '2026-01-02 08:00:00'", [])
+            .expect("reset updated_at");
+        let tx = conn.transaction().expect("tx");
+        assert!(merge_or_insert_session(&tx, 1, &incoming("2026-01-03 09:00:00")).expect("merge"));
+        tx.commit().expect("commit");
+        assert_eq!(tags_of_merged(&conn), vec!["remote"]);
+    }
+
+    #[test]
+    fn merged_session_keeps_a_newer_local_billable_override() {
+        let merged_billable = |archive_updated_at: &str| {
+            let mut conn = setup_sessions_conn();
+            conn.execute(
+                "INSERT INTO sessions (id, app_id, start_time, end_time, duration_seconds, date, updated_at, billable)
+                 VALUES (1, 1, '2026-01-01T10:00:00', '2026-01-01T11:00:00', 3600, '2026-01-01', '2026-01-02 08:00:00', 0)",
+                [],
+            )
+            .expect("seed");
+            let incoming = SessionRow {
+                billable: Some(Some(true)),
+                updated_at: Some(archive_updated_at.to_string()),
+                ..incoming_session("2026-01-01T10:30:00", "2026-01-01T11:30:00", 3600, false)
+            };
+            let tx = conn.transaction().expect("transaction");
+            merge_or_insert_session(&tx, 1, &incoming).expect("merge");
+            tx.commit().expect("commit");
+            conn.query_row("SELECT billable FROM sessions WHERE app_id = 1", [], |r| {
+                r.get::<_, Option<i64>>(0)
+            })
+            .expect("merged row")
+        };
+
+        // Older archive row: the local override survives the merge.
+        assert_eq!(merged_billable("2026-01-01 08:00:00"), Some(0));
+        // Newer archive row: its override wins.
+        assert_eq!(merged_billable("2026-01-03 08:00:00"), Some(1));
+    }
+
     #[test]
     fn session_with_only_project_name_resolves_and_persists_label() {
         let mut conn = full_schema_conn();
@@ -1866,7 +2227,7 @@ mod tests {
             r#"[["2026-01-02T10:00:00+00:00","2026-01-02T10:05:00+00:00"]]"#.to_string();
         // 2) kolizja z pre-seedem: total mniejszy (lokalny MAX wygrywa),
         //    first_seen wcześniejszy (MIN), last_seen późniejszy (MAX),
-        //    window_title nie nadpisuje lokalnego, spans wypełnia puste '[]'
+        //    window_title z nowszej strony (archiwum), spans wypełnia puste '[]'
         let mut colliding = fa_row(10, "2026-01-01", "/x/a.txt");
         colliding.total_seconds = 200;
         colliding.first_seen = "2026-01-01T08:00:00+00:00".to_string();
@@ -1911,11 +2272,66 @@ mod tests {
         assert_eq!(total, 500, "MAX total_seconds (local bigger) wins");
         assert_eq!(first, "2026-01-01T08:00:00+00:00", "MIN first_seen");
         assert_eq!(last, "2026-01-01T12:00:00+00:00", "MAX last_seen");
-        assert_eq!(title, "local title", "local window_title wins (COALESCE)");
+        assert_eq!(title, "remote title", "window_title follows the newer last_seen");
         assert!(
             spans.contains("08:10:00"),
             "empty local spans replaced by incoming, got: {spans}"
         );
+
+
+        // Starszy last_seen — pola opisowe zostają lokalne.
+        let mut stale = fa_row(10, "2026-01-01", "/x/a.txt");
+        stale.last_seen = "2026-01-01T11:00:00+00:00".to_string();
+        stale.window_title = Some("stale title".to_string());
+        let tx = conn.transaction().expect("tx");
+        import_file_activities(&tx, &[stale], &app_mapping, &project_mapping).expect("import");
+        tx.commit().expect("commit");
+        let title: String = conn
+            .query_row(
+                "SELECT window_title FROM file_activities WHERE file_path = '/x/a.txt'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(title, "remote title", "older last_seen keeps local descriptive fields");
+    }
+
+    #[test]
+    fn import_file_activities_merges_spans_with_local() {
+        let mut conn = full_schema_conn();
+        conn.execute_batch(
+            r#"INSERT INTO applications (id, executable_name, display_name) VALUES (1, 'app', 'App');
+             INSERT INTO file_activities (app_id, date, file_name, file_path, total_seconds, first_seen, last_seen, activity_spans)
+             VALUES (1, '2026-01-01', 'a.txt', '/x/a.txt', 600,
+                     '2026-01-01T10:00:00+00:00', '2026-01-01T10:10:00+00:00',
+                     '[["2026-01-01T10:00:00+00:00","2026-01-01T10:10:00+00:00"]]');"#,
+        )
+        .expect("seed");
+        let app_mapping: HashMap<i64, i64> = HashMap::from([(10, 1)]);
+
+        let mut incoming = fa_row(10, "2026-01-01", "/x/a.txt");
+        incoming.activity_spans = r#"[["2026-01-01T10:10:20+00:00","2026-01-01T10:20:00+00:00"],["2026-01-01T12:00:00+00:00","2026-01-01T12:05:00+00:00"]]"#.to_string();
+
+        // Dwukrotny import tego samego wiersza — scalanie jest idempotentne.
+        for _ in 0..2 {
+            let tx = conn.transaction().expect("tx");
+            import_file_activities(&tx, std::slice::from_ref(&incoming), &app_mapping, &HashMap::new())
+                .expect("import");
+            tx.commit().expect("commit");
+        }
+
+        let spans: String = conn
+            .query_row(
+                "SELECT activity_spans FROM file_activities WHERE file_path = '/x/a.txt'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            spans,
+            r#"[["2026-01-01T10:00:00+00:00","2026-01-01T10:20:00+00:00"],["2026-01-01T12:00:00+00:00","2026-01-01T12:05:00+00:00"]]"#,
+            "adjacent spans merged (gap < 30s), distant ones kept"
+        );
     }
 
     #[test]
@@ -1952,6 +2368,10 @@ mod tests {
             tombstones: Vec::new(),
             assignment_feedback: Vec::new(),
             assignment_auto_runs: Vec::new(),
+            clients: Vec::new(),
+            estimate_settings: Vec::new(),
+            tags: Vec::new(),
+            session_audit_log: Vec::new(),
             file_activities: vec![FileActivityExportRow {
                 project_id: Some(7),
                 window_title: Some("Doc — Editor".to_string()),
@@ -1972,4 +2392,125 @@ mod tests {
         assert_eq!(fa.activity_type.as_deref(), Some("document"));
         assert_eq!(fa.activity_spans, "[]");
     }
+
+    #[test]
+    fn import_merges_clients_estimate_settings_and_rate_changes() {
+        let mut conn = full_schema_conn();
+        conn.execute_batch(
+            "INSERT INTO clients (name, contact, updated_at) VALUES ('Acme', 'old@acme', '2026-04-20 10:00:00');
+             INSERT INTO clients (name, updated_at) VALUES ('Gone', '2026-04-20 10:00:00');
+             INSERT INTO projects (id, name, created_at, hourly_rate) VALUES (1, 'Website', '2026-04-01 00:00:00', 90);
+             UPDATE projects SET client_name = 'Gone', updated_at = '2026-04-20 10:00:00' WHERE id = 1;
+             INSERT OR REPLACE INTO estimate_settings (key, value, updated_at) VALUES ('global_hourly_rate', '120', '2026-04-25 10:00:00');",
+        )
+        .expect("seed");
+
+        let json = r##"{
+            "version": "1.1",
+            "exported_at": "2026-04-26T10:00:00+00:00",
+            "machine_id": "peer",
+            "export_type": "all_data",
+            "date_range": {"start": "2000-01-01", "end": "2026-04-26"},
+            "metadata": {"project_id": null, "project_name": null, "total_sessions": 0, "total_seconds": 0},
+            "data": {
+                "projects": [{"id": 5, "name": "Website", "color": "#38bdf8", "hourly_rate": null,
+                              "created_at": "2026-04-01 00:00:00", "excluded_at": null, "frozen_at": null,
+                              "assigned_folder_path": null, "updated_at": "2026-04-21 10:00:00"}],
+                "applications": [],
+                "sessions": [],
+                "manual_sessions": [],
+                "daily_files": {},
+                "tombstones": [{"table_name": "clients", "record_id": 2, "record_uuid": null,
+                                "deleted_at": "2026-04-22 10:00:00", "sync_key": "Gone"}],
+                "clients": [
+                    {"name": "Acme", "contact": "new@acme", "color": "#ef4444", "updated_at": "2026-04-21 10:00:00"},
+                    {"name": "Initech", "default_hourly_rate": 200.0, "updated_at": "2026-04-21 10:00:00"}
+                ],
+                "estimate_settings": [
+                    {"key": "global_hourly_rate", "value": "80", "updated_at": "2026-04-21 10:00:00"},
+                    {"key": "time_algorithm", "value": "sum", "updated_at": "2026-04-21 10:00:00"}
+                ]
+            }
+        }"##;
+        let archive = parse_export_archive(json).expect("parse");
+        let tx = conn.transaction().expect("tx");
+        import_archive_into_tx(&tx, &archive, false, DailyFilesMode::Skip).expect("import");
+        tx.commit().expect("commit");
+
+        let q = |sql: &str| -> Option<String> { conn.query_row(sql, [], |r| r.get(0)).unwrap() };
+        assert_eq!(q("SELECT contact FROM clients WHERE name = 'Acme'").as_deref(), Some("new@acme"));
+        assert_eq!(q("SELECT color FROM clients WHERE name = 'Initech'").as_deref(), Some("#38bdf8"));
+        assert_eq!(q("SELECT CAST(COUNT(*) AS TEXT) FROM clients WHERE name = 'Gone'").as_deref(), Some("0"));
+        assert_eq!(q("SELECT client_name FROM projects WHERE id = 1"), None, "deleted client unlinked");
+        assert_eq!(q("SELECT CAST(hourly_rate AS TEXT) FROM projects WHERE id = 1"), None, "cleared rate propagates");
+        assert_eq!(
+            q("SELECT value FROM estimate_settings WHERE key = 'global_hourly_rate'").as_deref(),
+            Some("120"),
+            "newer local rate wins"
+        );
+        assert_eq!(q("SELECT value FROM estimate_settings WHERE key = 'time_algorithm'").as_deref(), Some("sum"));
+    }
+
+    #[test]
+    fn project_links_keep_local_when_key_is_absent_and_clear_on_null() {
+        let mut conn = full_schema_conn();
+        conn.execute_batch(
+            "INSERT INTO clients (name, updated_at) VALUES ('Acme', '2026-04-20 10:00:00');
+             INSERT INTO projects (id, name, created_at) VALUES (1, 'Parent', '2026-04-01 00:00:00');
+             INSERT INTO projects (id, name, created_at) VALUES (2, 'Website', '2026-04-01 00:00:00');
+             UPDATE projects SET client_name = 'Acme', merged_into = 'Parent', merged_at = '2026-04-19 10:00:00',
+                 updated_at = '2026-04-20 10:00:00' WHERE id = 2;",
+        )
+        .expect("seed");
+        let import = |conn: &mut rusqlite::Connection, project: serde_json::Value| {
+            let archive = serde_json::json!({
+                "version": "1.1",
+                "exported_at": "2026-04-26T10:00:00+00:00",
+                "machine_id": "peer",
+                "export_type": "all_data",
+                "date_range": {"start": "2000-01-01", "end": "2026-04-26"},
+                "metadata": {"project_id": null, "project_name": null, "total_sessions": 0, "total_seconds": 0},
+                "data": {
+                    "projects": [project],
+                    "applications": [],
+                    "sessions": [],
+                    "manual_sessions": [],
+                    "daily_files": {},
+                    "tombstones": []
+                }
+            });
+            let archive = parse_export_archive(&archive.to_string()).expect("parse");
+            let tx = conn.transaction().expect("tx");
+            import_archive_into_tx(&tx, &archive, false, DailyFilesMode::Skip).expect("import");
+            tx.commit().expect("commit");
+        };
+
+        // Older archive without the keys → local links survive a newer row.
+        import(&mut conn, serde_json::json!({
+            "id": 9, "name": "Website", "color": "#38bdf8", "hourly_rate": null,
+            "created_at": "2026-04-01 00:00:00", "excluded_at": null, "frozen_at": null,
+            "assigned_folder_path": null, "updated_at": "2026-04-21 10:00:00"
+        }));
+        let links = |conn: &rusqlite::Connection| -> (Option<String>, Option<String>, Option<String>) {
+            conn.query_row(
+                "SELECT client_name, merged_into, merged_at FROM projects WHERE id = 2",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            links(&conn),
+            (Some("Acme".into()), Some("Parent".into()), Some("2026-04-19 10:00:00".into()))
+        );
+
+        // Explicit nulls → client removed and project unmerged, like the daemon merge.
+        import(&mut conn, serde_json::json!({
+            "id": 9, "name": "Website", "color": "#38bdf8", "hourly_rate": null,
+            "created_at": "2026-04-01 00:00:00", "excluded_at": null, "frozen_at": null,
+            "merged_into": null, "merged_at": null, "client_name": null,
+            "assigned_folder_path": null, "updated_at": "2026-04-22 10:00:00"
+        }));
+        assert_eq!(links(&conn), (None, None, None));
+    }
 }
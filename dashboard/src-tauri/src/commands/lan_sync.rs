@@ -4,7 +4,9 @@
 use super::delta_export::TableHashes;
 use super::helpers::{build_table_hashes, timeflow_data_dir};
 use serde::{Deserialize, Serialize};
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use timeflow_shared::peer_addr;
+use timeflow_shared::sync_protocol::{self, ProtocolInfo};
 use tauri::AppHandle;
 
 /// Write a line to logs/lan_sync.log in the TimeFlow data dir (visible to user).
@@ -36,11 +38,20 @@ pub struct LanPeer {
     pub dashboard_port: u16,
     pub last_seen: String,
     pub dashboard_running: bool,
-    /// Wersja TIMEFLOW po stronie peera — wymagana do weryfikacji
-    /// strict-equality przed LAN sync. Pusty string = peer nie ogłosił wersji
+    /// Wersja TIMEFLOW po stronie peera. Pusty string = peer nie ogłosił wersji
     /// (stare daemony lub ping bez `version`).
     #[serde(default)]
     pub timeflow_version: String,
+    /// Zakres protokołu sync peera; 0 = peer sprzed negocjacji (wtedy sync
+    /// wymaga identycznej wersji).
+    #[serde(default)]
+    pub sync_protocol: u32,
+    #[serde(default)]
+    pub min_sync_protocol: u32,
+    /// Wyliczane przy odczycie (`get_lan_peers`) — czy sync z tym peerem jest
+    /// możliwy. UI używa tego zamiast porównywać wersje.
+    #[serde(default)]
+    pub sync_compatible: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -89,6 +100,42 @@ pub struct SyncProgress {
     pub sync_type: String,
 }
 
+/// Counts reported by the daemon's dry-run merge (mirror of `sync_preview::SyncPreview`).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SyncTableChanges {
+    pub inserted: i64,
+    pub updated: i64,
+    pub deleted: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncProjectTotalChange {
+    pub project: String,
+    pub seconds_before: i64,
+    pub seconds_after: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SyncPreview {
+    #[serde(default)]
+    pub tables: std::collections::BTreeMap<String, SyncTableChanges>,
+    #[serde(default)]
+    pub tombstones_added: i64,
+    #[serde(default)]
+    pub projects_changed: Vec<SyncProjectTotalChange>,
+}
+
+/// Last dry-run (LAN or online). `finished_at == None` while it is still running.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncPreviewReport {
+    pub sync_type: String,
+    pub peer: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub preview: Option<SyncPreview>,
+    pub error: Option<String>,
+}
+
 // ── Commands ──
 
 #[tauri::command]
@@ -99,7 +146,20 @@ pub async fn get_lan_peers() -> Result<Vec<LanPeer>, String> {
     }
     let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
     let file: LanPeersFile = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-    Ok(file.peers)
+    let local_version = crate::VERSION.trim();
+    Ok(file
+        .peers
+        .into_iter()
+        .map(|mut peer| {
+            peer.sync_compatible = sync_protocol::peer_compatible(
+                local_version,
+                &peer.timeflow_version,
+                peer.sync_protocol,
+                peer.min_sync_protocol,
+            );
+            peer
+        })
+        .collect())
 }
 
 /// Insert or update a peer in lan_peers.json (used after manual ping).
@@ -164,6 +224,10 @@ pub struct PingLanPeerResult {
     pub dashboard_port: u16,
     pub role: String,
     pub version: String,
+    #[serde(default)]
+    pub sync_protocol: u32,
+    #[serde(default)]
+    pub min_sync_protocol: u32,
 }
 
 #[tauri::command]
@@ -171,10 +235,11 @@ pub async fn ping_lan_peer(ip: String, port: u16) -> Result<PingLanPeerResult, S
     ensure_private_peer(&ip)?;
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(5))
+        .resolve(PEER_HOST, peer_addr::socket_addr(&ip, port)?)
         .build()
         .map_err(|e| e.to_string())?;
 
-    let url = format!("http://{}:{}/lan/ping", ip, port);
+    let url = peer_url(port, "/lan/ping");
     let resp = client
         .get(&url)
         .send()
@@ -191,6 +256,10 @@ pub async fn ping_lan_peer(ip: String, port: u16) -> Result<PingLanPeerResult, S
         machine_name: String,
         role: String,
         version: String,
+        #[serde(default)]
+        sync_protocol: u32,
+        #[serde(default)]
+        min_sync_protocol: u32,
     }
     let ping: PingResp = resp
         .json()
@@ -204,6 +273,8 @@ pub async fn ping_lan_peer(ip: String, port: u16) -> Result<PingLanPeerResult, S
         dashboard_port: port,
         role: ping.role,
         version: ping.version,
+        sync_protocol: ping.sync_protocol,
+        min_sync_protocol: ping.min_sync_protocol,
     })
 }
 
@@ -212,16 +283,32 @@ fn is_private_lan_ip(ip: Ipv4Addr) -> bool {
     a == 10 || (a == 172 && (16..=31).contains(&b)) || (a == 192 && b == 168)
 }
 
-/// Odrzuca cele inne niż prywatny LAN IPv4 (anty-SSRF dla komend sync osiągalnych
-/// zdalnie przez /rpc). Loopback/link-local/publiczne adresy są odrzucane.
-fn ensure_private_peer(ip: &str) -> Result<Ipv4Addr, String> {
-    let addr: Ipv4Addr = ip.parse().map_err(|_| format!("Invalid peer IP: {ip}"))?;
-    if !is_private_lan_ip(addr) {
+/// Odrzuca cele inne niż prywatny LAN (anty-SSRF dla komend sync osiągalnych
+/// zdalnie przez /rpc). IPv4: 10/8, 172.16/12, 192.168/16. IPv6: unique-local
+/// i link-local — w sieciach tylko-IPv6 link-local (`fe80::1%3`) to zwykły
+/// adres LAN. Loopback/publiczne adresy są odrzucane.
+fn ensure_private_peer(ip: &str) -> Result<IpAddr, String> {
+    let (addr, _scope) = peer_addr::parse_peer_ip(ip)?;
+    let private = match addr {
+        IpAddr::V4(v4) => is_private_lan_ip(v4),
+        IpAddr::V6(v6) => {
+            peer_addr::is_ipv6_unique_local(&v6) || peer_addr::is_ipv6_link_local(&v6)
+        }
+    };
+    if !private {
         return Err(format!("Peer IP {ip} is not a private LAN address"));
     }
     Ok(addr)
 }
 
+/// URL-e reqwest nie przenoszą strefy IPv6, więc zapytania do peera idą na ten
+/// stały host, rozwiązywany w kliencie (`resolve`) na adres peera.
+const PEER_HOST: &str = "timeflow-peer";
+
+fn peer_url(port: u16, path: &str) -> String {
+    format!("http://{PEER_HOST}:{port}{path}")
+}
+
 async fn ping_lan_scan_host(client: reqwest::Client, ip: String) -> Option<PingLanPeerResult> {
     let url = format!("http://{}:47891/lan/ping", ip);
     let resp = match client.get(&url).send().await {
@@ -234,6 +321,10 @@ async fn ping_lan_scan_host(client: reqwest::Client, ip: String) -> Option<PingL
         machine_name: String,
         role: String,
         version: String,
+        #[serde(default)]
+        sync_protocol: u32,
+        #[serde(default)]
+        min_sync_protocol: u32,
     }
     let ping: PingResp = resp.json().await.ok()?;
     Some(PingLanPeerResult {
@@ -243,6 +334,8 @@ async fn ping_lan_scan_host(client: reqwest::Client, ip: String) -> Option<PingL
         dashboard_port: 47891,
         role: ping.role,
         version: ping.version,
+        sync_protocol: ping.sync_protocol,
+        min_sync_protocol: ping.min_sync_protocol,
     })
 }
 
@@ -307,6 +400,9 @@ pub async fn scan_lan_subnet() -> Result<Vec<PingLanPeerResult>, String> {
                     last_seen: chrono::Utc::now().to_rfc3339(),
                     dashboard_running: true,
                     timeflow_version: peer.version.clone(),
+                    sync_protocol: peer.sync_protocol,
+                    min_sync_protocol: peer.min_sync_protocol,
+                    sync_compatible: false,
                 });
                 found.push(peer);
             }
@@ -337,6 +433,24 @@ pub async fn get_lan_sync_progress() -> Result<SyncProgress, String> {
     Ok(progress)
 }
 
+/// Result of the last sync dry-run (`run_lan_sync` / `run_online_sync` with `dry_run`).
+#[tauri::command]
+pub async fn get_sync_preview() -> Result<Option<SyncPreviewReport>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(2))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let resp = client
+        .get("http://127.0.0.1:47891/lan/sync-preview")
+        .send()
+        .await
+        .map_err(|e| format!("Daemon not reachable: {}", e))?;
+
+    let report: Option<SyncPreviewReport> = resp.json().await.map_err(|e| e.to_string())?;
+    Ok(report)
+}
+
 #[tauri::command]
 pub async fn build_table_hashes_only(app: AppHandle) -> Result<TableHashes, String> {
     super::helpers::run_db_blocking(app, |conn| Ok(build_table_hashes(conn))).await
@@ -349,22 +463,25 @@ pub async fn run_lan_sync(
     peer_port: u16,
     _since: String,
     force: Option<bool>,
+    dry_run: Option<bool>,
 ) -> Result<LanSyncResult, String> {
     let force = force.unwrap_or(false);
+    let dry_run = dry_run.unwrap_or(false);
     ensure_private_peer(&peer_ip)?;
     sync_log(&format!(
-        "LAN sync: delegating to daemon for peer {}:{}{}",
+        "LAN sync: delegating to daemon for peer {}:{}{}{}",
         peer_ip,
         peer_port,
-        if force { " [FORCE]" } else { "" }
+        if force { " [FORCE]" } else { "" },
+        if dry_run { " [DRY-RUN]" } else { "" }
     ));
 
     // 1. Ping peer first to get device_id and verify version
     let ip_c = peer_ip.clone();
     let port_c = peer_port;
     let ping_result = tokio::task::spawn_blocking(move || {
-        let client = build_http_client()?;
-        let url = format!("http://{}:{}/lan/ping", ip_c, port_c);
+        let client = build_peer_http_client(peer_addr::socket_addr(&ip_c, port_c)?)?;
+        let url = peer_url(port_c, "/lan/ping");
         let resp = client
             .get(&url)
             .send()
@@ -394,16 +511,27 @@ pub async fn run_lan_sync(
         peer_ip, peer_version, peer_device_id, local_version
     ));
 
-    if peer_version != local_version {
-        let msg = format!(
-            "Version mismatch! Local: v{}, Peer: v{}. Update both machines first.",
-            local_version, peer_version
-        );
-        sync_log(&format!("LAN sync: ABORTED — {}", msg));
-        return Err(msg);
-    }
+    let features = match sync_protocol::negotiate(
+        local_version,
+        Some(peer_version),
+        ProtocolInfo::from_json(&ping_result).as_ref(),
+    ) {
+        Ok(f) => f,
+        Err(msg) => {
+            sync_log(&format!("LAN sync: ABORTED — {}", msg));
+            return Err(msg);
+        }
+    };
 
-    sync_log("LAN sync: versions match — triggering daemon sync...");
+    let missing = features.missing();
+    if missing.is_empty() {
+        sync_log("LAN sync: protocol compatible — triggering daemon sync...");
+    } else {
+        sync_log(&format!(
+            "LAN sync: protocol compatible (peer lacks: {}) — triggering daemon sync...",
+            missing.join(", ")
+        ));
+    }
 
     // 2. Tell local daemon to run the 13-step sync with the peer
     let trigger_body = serde_json::json!({
@@ -411,6 +539,7 @@ pub async fn run_lan_sync(
         "peer_port": peer_port,
         "peer_device_id": peer_device_id,
         "force": force,
+        "dry_run": dry_run,
     });
 
     let _trigger_result = tokio::task::spawn_blocking(move || {
@@ -438,7 +567,7 @@ pub async fn run_lan_sync(
 
     Ok(LanSyncResult {
         ok: true,
-        action: "daemon_sync_started".to_string(),
+        action: if dry_run { "daemon_dry_run_started" } else { "daemon_sync_started" }.to_string(),
         pulled: false,
         pushed: false,
         import_summary: None,
@@ -637,6 +766,14 @@ fn build_http_client() -> Result<reqwest::blocking::Client, String> {
         .map_err(|e| format!("Failed to build HTTP client: {}", e))
 }
 
+fn build_peer_http_client(peer: SocketAddr) -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(120))
+        .resolve(PEER_HOST, peer)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -650,4 +787,13 @@ mod tests {
         assert!(ensure_private_peer("10.0.0.2").is_ok());
         assert!(ensure_private_peer("not-an-ip").is_err());
     }
+
+    #[test]
+    fn ensure_private_peer_accepts_ipv6_lan_addresses() {
+        assert!(ensure_private_peer("fd12:3456::7").is_ok());
+        assert!(ensure_private_peer("fe80::1%3").is_ok());
+        assert!(ensure_private_peer("::1").is_err());
+        assert!(ensure_private_peer("2001:4860:4860::8888").is_err());
+        assert!(ensure_private_peer("fe80::1%eth0").is_err());
+    }
 }
@@ -29,10 +29,14 @@ pub struct LogSettings {
     pub dashboard_level: String,
     #[serde(default = "default_max_size")]
     pub max_log_size_kb: u32,
+    /// Days of sync run history (`sync_runs`) the daemon keeps.
+    #[serde(default = "default_sync_history_retention")]
+    pub sync_history_retention_days: u32,
 }
 
 fn default_level() -> String { "info".to_string() }
 fn default_max_size() -> u32 { 1024 }
+fn default_sync_history_retention() -> u32 { 90 }
 
 impl Default for LogSettings {
     fn default() -> Self {
@@ -42,6 +46,7 @@ impl Default for LogSettings {
             online_sync_level: default_level(),
             dashboard_level: default_level(),
             max_log_size_kb: default_max_size(),
+            sync_history_retention_days: default_sync_history_retention(),
         }
     }
 }
@@ -2,6 +2,7 @@ use tauri::AppHandle;
 
 use super::helpers::run_db_blocking;
 use super::projects::project_id_is_active;
+use super::tags::{parse_tag_names, tag_filter_sql, tags_json_sql, TagTarget};
 use super::types::{
     CreateManualSessionInput, ManualSession, ManualSessionFilters, ManualSessionWithProject,
 };
@@ -31,61 +32,69 @@ fn parse_session_datetimes(start: &str, end: &str) -> Result<(i64, String), Stri
     ))
 }
 
-#[tauri::command]
-pub async fn create_manual_session(
-    app: AppHandle,
-    input: CreateManualSessionInput,
+/// Validates and inserts one manual session; shared by the dialog and bulk gap fills.
+pub(crate) fn insert_manual_session(
+    conn: &rusqlite::Connection,
+    input: &CreateManualSessionInput,
 ) -> Result<ManualSession, String> {
     let (duration_seconds, date) = parse_session_datetimes(&input.start_time, &input.end_time)?;
 
-    run_db_blocking(app, move |conn| {
-        if !project_id_is_active(conn, input.project_id)? {
-            return Err(
-                "Cannot assign manual session to an excluded, frozen, or missing project".to_string(),
-            );
-        }
+    if !project_id_is_active(conn, input.project_id)? {
+        return Err(
+            "Cannot assign manual session to an excluded, frozen, or missing project".to_string(),
+        );
+    }
 
-        conn.execute(
-            "INSERT INTO manual_sessions (title, session_type, project_id, app_id, start_time, end_time, duration_seconds, date)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            rusqlite::params![
-                input.title,
-                input.session_type,
-                input.project_id,
-                input.app_id,
-                input.start_time,
-                input.end_time,
-                duration_seconds,
-                date,
-            ],
-        )
-        .map_err(|e| format!("Failed to create manual session: {}", e))?;
+    conn.execute(
+        "INSERT INTO manual_sessions (title, session_type, project_id, app_id, start_time, end_time, duration_seconds, date)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            input.title,
+            input.session_type,
+            input.project_id,
+            input.app_id,
+            input.start_time,
+            input.end_time,
+            duration_seconds,
+            date,
+        ],
+    )
+    .map_err(|e| format!("Failed to create manual session: {}", e))?;
 
-        let id = conn.last_insert_rowid();
+    let id = conn.last_insert_rowid();
 
-        conn.query_row(
-            "SELECT id, title, session_type, project_id, app_id, start_time, end_time, duration_seconds, date, created_at, updated_at
-             FROM manual_sessions WHERE id = ?1",
-            [id],
-            |row| {
-                Ok(ManualSession {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    session_type: row.get(2)?,
-                    project_id: row.get(3)?,
-                    app_id: row.get(4)?,
-                    start_time: row.get(5)?,
-                    end_time: row.get(6)?,
-                    duration_seconds: row.get(7)?,
-                    date: row.get(8)?,
-                    created_at: row.get(9)?,
-                    updated_at: row.get(10)?,
-                })
-            },
-        )
-        .map_err(|e| format!("Failed to read created session: {}", e))
-    })
-    .await
+    conn.query_row(
+        "SELECT id, title, session_type, project_id, app_id, start_time, end_time, duration_seconds, date, created_at, updated_at
+         FROM manual_sessions WHERE id = ?1",
+        [id],
+        |row| {
+            Ok(ManualSession {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                session_type: row.get(2)?,
+                project_id: row.get(3)?,
+                app_id: row.get(4)?,
+                start_time: row.get(5)?,
+                end_time: row.get(6)?,
+                duration_seconds: row.get(7)?,
+                date: row.get(8)?,
+                created_at: row.get(9)?,
+                updated_at: row.get(10)?,
+                tags: Some(Vec::new()),
+                billable: Some(None),
+            })
+        },
+    )
+    .map_err(|e| format!("Failed to read created session: {}", e))
+}
+
+#[tauri::command]
+pub async fn create_manual_session(
+    app: AppHandle,
+    input: CreateManualSessionInput,
+) -> Result<ManualSession, String> {
+    parse_session_datetimes(&input.start_time, &input.end_time)?;
+    run_db_blocking(app, move |conn| insert_manual_session(conn, &input)).await
 }
 
 #[tauri::command]
@@ -94,12 +103,14 @@ pub async fn get_manual_sessions(
     filters: ManualSessionFilters,
 ) -> Result<Vec<ManualSessionWithProject>, String> {
     run_db_blocking(app, move |conn| {
-        let mut sql = String::from(
+        let mut sql = format!(
             "SELECT ms.id, ms.title, ms.session_type, ms.project_id, ms.app_id, p.name, p.color,
-                    ms.start_time, ms.end_time, ms.duration_seconds, ms.date
+                    ms.start_time, ms.end_time, ms.duration_seconds, ms.date, {},
+                    ms.billable
              FROM manual_sessions ms
              JOIN projects p ON p.id = ms.project_id
              WHERE 1=1",
+            tags_json_sql(TagTarget::ManualSession, "ms")
         );
         let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
 
@@ -115,6 +126,10 @@ pub async fn get_manual_sessions(
             params.push(Box::new(pid));
         }
 
+        if let Some(ref tag_ids) = filters.tag_ids {
+            sql.push_str(&tag_filter_sql(TagTarget::ManualSession, "ms", tag_ids));
+        }
+
         sql.push_str(" ORDER BY ms.start_time ASC");
 
         let param_refs: Vec<&dyn rusqlite::types::ToSql> =
@@ -134,6 +149,8 @@ pub async fn get_manual_sessions(
                     end_time: row.get(8)?,
                     duration_seconds: row.get(9)?,
                     date: row.get(10)?,
+                    tags: parse_tag_names(row.get(11)?),
+                    billable: row.get::<_, Option<i64>>(12)?.map(|b| b != 0),
                 })
             })
             .map_err(|e| e.to_string())?;
@@ -210,3 +227,35 @@ pub async fn delete_manual_sessions(app: AppHandle, ids: Vec<i64>) -> Result<(),
     })
     .await
 }
+
+/// `None` clears the override, so the sessions inherit the project default.
+#[tauri::command]
+pub async fn update_manual_sessions_billable(
+    app: AppHandle,
+    ids: Vec<i64>,
+    billable: Option<bool>,
+) -> Result<(), String> {
+    let ids = sanitize_ids(ids);
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    run_db_blocking(app, move |conn| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start manual session update transaction: {}", e))?;
+
+        for id in ids {
+            tx.execute(
+                "UPDATE manual_sessions SET billable = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![billable.map(i64::from), id],
+            )
+            .map_err(|e| format!("Failed to update manual session: {}", e))?;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit manual session update transaction: {}", e))?;
+        Ok(())
+    })
+    .await
+}
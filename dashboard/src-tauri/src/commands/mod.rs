@@ -10,6 +10,7 @@ mod datetime;
 mod delta_export;
 mod estimates;
 mod export;
+mod gaps;
 pub mod helpers;
 mod import;
 mod import_data;
@@ -17,16 +18,23 @@ mod lan_server;
 mod log_management;
 mod lan_sync;
 mod online_sync;
+mod overlaps;
 mod manual_sessions;
 mod monitored;
+mod mutation_journal;
 mod projects;
 mod report;
+mod rounding;
 mod secure_store;
+mod session_audit;
 mod sessions;
 mod settings;
 mod sql_fragments;
+mod sync_conflicts;
+mod sync_history;
 mod sync_log;
 mod sync_markers;
+mod tags;
 mod time_algorithm;
 mod types;
 mod user_settings;
@@ -47,21 +55,28 @@ pub use database::*;
 pub use delta_export::*;
 pub use estimates::*;
 pub use export::*;
+pub use gaps::*;
 pub use import::*;
 pub use import_data::*;
 pub use lan_server::*;
 pub use lan_sync::*;
 pub use log_management::*;
 pub use online_sync::*;
+pub use overlaps::*;
 pub use manual_sessions::*;
 pub use monitored::*;
+pub use mutation_journal::*;
 pub use projects::*;
 pub use report::*;
 pub use secure_store::*;
+pub use session_audit::*;
 pub use sessions::*;
 pub use settings::*;
+pub use sync_conflicts::*;
+pub use sync_history::*;
 pub use sync_log::*;
 pub use sync_markers::*;
+pub use tags::*;
 pub use time_algorithm::*;
 pub use user_settings::*;
 pub use pm::*;
@@ -0,0 +1,666 @@
+// Undo / redo journal for user actions on sessions and projects (m30).
+//
+// A mutating command wraps its transaction with a `Recorder`: `track` takes
+// before-images of the rows the action may touch (a table + WHERE scope) and
+// `finish` stores the diff against the after-images as one journal entry.
+// `undo_last_action` / `redo_last_action` write the images back — only while
+// the rows still look exactly as the action left them, so later edits and
+// sync merges are never overwritten. Images leave out `updated_at` / `hlc`:
+// a replay is a fresh edit (triggers bump both and mark the session cache
+// dirty), so it syncs like any other change.
+
+use std::collections::{BTreeMap, HashSet};
+
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::helpers::run_db_blocking;
+use super::session_audit;
+
+type Image = serde_json::Map<String, Value>;
+
+/// Tables a journal may write back to — names are interpolated into SQL.
+const JOURNALED_TABLES: [&str; 6] = [
+    "sessions",
+    "file_activities",
+    "session_manual_overrides",
+    "manual_sessions",
+    "projects",
+    "applications",
+];
+const VOLATILE_COLUMNS: [&str; 2] = ["updated_at", "hlc"];
+const MAX_ENTRIES: i64 = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JournalEntry {
+    pub id: i64,
+    /// Command that recorded it, e.g. "assign_sessions_to_project".
+    pub action: String,
+    pub label: String,
+    pub row_count: i64,
+    pub created_at: String,
+    /// Set while the entry is undone (it is then the next redo).
+    pub undone_at: Option<String>,
+}
+
+struct Scope {
+    table: &'static str,
+    where_sql: String,
+    params: Vec<SqlValue>,
+    before: BTreeMap<i64, Image>,
+}
+
+struct RowChange {
+    table: String,
+    row_id: i64,
+    before: Option<Image>,
+    after: Option<Image>,
+}
+
+/// Collects before-images for one action; see the module comment.
+pub(crate) struct Recorder {
+    action: String,
+    label: String,
+    scopes: Vec<Scope>,
+}
+
+impl Recorder {
+    pub(crate) fn new(action: &str, label: impl Into<String>) -> Self {
+        Self {
+            action: action.to_string(),
+            label: label.into(),
+            scopes: Vec::new(),
+        }
+    }
+
+    /// Snapshot of `table` rows matching `where_sql` — call before mutating.
+    /// Rows the action moves out of the scope are still followed by id.
+    pub(crate) fn track(
+        &mut self,
+        conn: &Connection,
+        table: &'static str,
+        where_sql: &str,
+        params: Vec<SqlValue>,
+    ) -> Result<(), String> {
+        check_table(table)?;
+        let before = load_images(conn, table, where_sql, &params)?;
+        self.scopes.push(Scope {
+            table,
+            where_sql: where_sql.to_string(),
+            params,
+            before,
+        });
+        Ok(())
+    }
+
+    /// Stores the entry (unless nothing changed) and drops the redo stack.
+    pub(crate) fn finish(self, conn: &Connection) -> Result<Option<i64>, String> {
+        let mut seen = HashSet::new();
+        let mut changes = Vec::new();
+        for scope in self.scopes {
+            let mut after = load_images(conn, scope.table, &scope.where_sql, &scope.params)?;
+            for id in scope.before.keys() {
+                if !after.contains_key(id) {
+                    if let Some(image) = load_image(conn, scope.table, *id)? {
+                        after.insert(*id, image);
+                    }
+                }
+            }
+            let ids: Vec<i64> = scope.before.keys().chain(after.keys()).copied().collect();
+            for id in ids {
+                if !seen.insert((scope.table, id)) {
+                    continue;
+                }
+                let before = scope.before.get(&id).cloned();
+                let after = after.get(&id).cloned();
+                if before != after {
+                    changes.push(RowChange {
+                        table: scope.table.to_string(),
+                        row_id: id,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+        if changes.is_empty() {
+            return Ok(None);
+        }
+
+        discard_undone(conn)?;
+        conn.execute(
+            "INSERT INTO mutation_journal (action, label, row_count) VALUES (?1, ?2, ?3)",
+            rusqlite::params![self.action, self.label, changes.len() as i64],
+        )
+        .map_err(|e| e.to_string())?;
+        let journal_id = conn.last_insert_rowid();
+        for (seq, change) in changes.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO mutation_journal_rows (journal_id, seq, table_name, row_id, before_json, after_json)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    journal_id,
+                    seq as i64,
+                    change.table,
+                    change.row_id,
+                    change.before.as_ref().map(|i| Value::Object(i.clone()).to_string()),
+                    change.after.as_ref().map(|i| Value::Object(i.clone()).to_string()),
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        prune(conn)?;
+        Ok(Some(journal_id))
+    }
+}
+
+/// `id IN (…)` for a list of ids (integers only, safe to inline).
+pub(crate) fn id_list_sql(ids: &[i64]) -> String {
+    let list: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    format!("id IN ({})", list.join(","))
+}
+
+fn check_table(table: &str) -> Result<(), String> {
+    if JOURNALED_TABLES.contains(&table) {
+        Ok(())
+    } else {
+        Err(format!("Table {} is not journaled", table))
+    }
+}
+
+fn to_json(value: ValueRef<'_>) -> Result<Value, String> {
+    Ok(match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(t) => Value::from(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(_) => return Err("BLOB columns cannot be journaled".to_string()),
+    })
+}
+
+fn to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+fn load_images(
+    conn: &Connection,
+    table: &str,
+    where_sql: &str,
+    params: &[SqlValue],
+) -> Result<BTreeMap<i64, Image>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {} WHERE {}", table, where_sql))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = stmt
+        .query(params_from_iter(params.iter()))
+        .map_err(|e| e.to_string())?;
+    let mut images = BTreeMap::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let mut image = Image::new();
+        for (i, column) in columns.iter().enumerate() {
+            if VOLATILE_COLUMNS.contains(&column.as_str()) {
+                continue;
+            }
+            image.insert(
+                column.clone(),
+                to_json(row.get_ref(i).map_err(|e| e.to_string())?)?,
+            );
+        }
+        let id = image
+            .get("id")
+            .and_then(Value::as_i64)
+            .ok_or_else(|| format!("Table {} has no integer id", table))?;
+        images.insert(id, image);
+    }
+    Ok(images)
+}
+
+fn load_image(conn: &Connection, table: &str, id: i64) -> Result<Option<Image>, String> {
+    Ok(load_images(conn, table, "id = ?1", &[SqlValue::Integer(id)])?.remove(&id))
+}
+
+fn discard_undone(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "DELETE FROM mutation_journal_rows
+         WHERE journal_id IN (SELECT id FROM mutation_journal WHERE undone_at IS NOT NULL);
+         DELETE FROM mutation_journal WHERE undone_at IS NOT NULL;",
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn prune(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM mutation_journal_rows WHERE journal_id IN (
+            SELECT id FROM mutation_journal ORDER BY id DESC LIMIT -1 OFFSET ?1
+         )",
+        [MAX_ENTRIES],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM mutation_journal WHERE id IN (
+            SELECT id FROM mutation_journal ORDER BY id DESC LIMIT -1 OFFSET ?1
+         )",
+        [MAX_ENTRIES],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<JournalEntry> {
+    Ok(JournalEntry {
+        id: row.get(0)?,
+        action: row.get(1)?,
+        label: row.get(2)?,
+        row_count: row.get(3)?,
+        created_at: row.get(4)?,
+        undone_at: row.get(5)?,
+    })
+}
+
+const ENTRY_COLUMNS: &str = "id, action, label, row_count, created_at, undone_at";
+
+fn load_changes(conn: &Connection, journal_id: i64) -> Result<Vec<RowChange>, String> {
+    let parse = |raw: Option<String>| -> Result<Option<Image>, String> {
+        raw.map(|s| match serde_json::from_str(&s) {
+            Ok(Value::Object(image)) => Ok(image),
+            _ => Err("Corrupt journal image".to_string()),
+        })
+        .transpose()
+    };
+    let mut stmt = conn
+        .prepare(
+            "SELECT table_name, row_id, before_json, after_json
+             FROM mutation_journal_rows WHERE journal_id = ?1 ORDER BY seq",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([journal_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut changes = Vec::new();
+    for row in rows {
+        let (table, row_id, before, after) = row.map_err(|e| e.to_string())?;
+        check_table(&table)?;
+        changes.push(RowChange {
+            table,
+            row_id,
+            before: parse(before)?,
+            after: parse(after)?,
+        });
+    }
+    Ok(changes)
+}
+
+/// (image the row must have now, image to write) for the replay direction.
+fn current_and_target(change: &RowChange, undo: bool) -> (Option<&Image>, Option<&Image>) {
+    if undo {
+        (change.after.as_ref(), change.before.as_ref())
+    } else {
+        (change.before.as_ref(), change.after.as_ref())
+    }
+}
+
+/// Writes `target` over rows that must currently equal `current`. Deletes
+/// first, then updates, then inserts, so unique keys freed by one row can be
+/// taken by another (e.g. an INSERT OR REPLACE'd override).
+fn write_images(conn: &Connection, changes: &[RowChange], undo: bool) -> Result<(), String> {
+    let pick = |c| current_and_target(c, undo);
+    let verb = if undo { "undo" } else { "redo" };
+    for change in changes {
+        let (current, _) = pick(change);
+        if load_image(conn, &change.table, change.row_id)?.as_ref() != current {
+            return Err(format!(
+                "{} #{} changed after this action — cannot {} it safely",
+                change.table, change.row_id, verb
+            ));
+        }
+    }
+
+    for change in changes.iter().filter(|c| pick(c).1.is_none()) {
+        // Tombstone triggers fire as for any user delete.
+        conn.execute(
+            &format!("DELETE FROM {} WHERE id = ?1", change.table),
+            [change.row_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    for change in changes {
+        let (current, Some(target)) = pick(change) else {
+            continue;
+        };
+        if change.table == "projects" && target.get("excluded_at") == Some(&Value::Null) {
+            // Same order as unmerge / restore: a blacklisted name would make
+            // trg_projects_blacklist_block_* abort the write.
+            if let Some(name) = target.get("name").and_then(Value::as_str) {
+                conn.execute(
+                    "DELETE FROM project_name_blacklist WHERE name_key = lower(trim(?1))",
+                    [name],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        let columns: Vec<&String> = target.keys().filter(|c| *c != "id").collect();
+        let mut values: Vec<SqlValue> = columns.iter().map(|c| to_sql(&target[*c])).collect();
+        values.push(SqlValue::Integer(change.row_id));
+        if current.is_some() {
+            let set: Vec<String> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| format!("\"{}\" = ?{}", c, i + 1))
+                .collect();
+            conn.execute(
+                &format!(
+                    "UPDATE {} SET {} WHERE id = ?{}",
+                    change.table,
+                    set.join(", "),
+                    values.len()
+                ),
+                params_from_iter(values.iter()),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    for change in changes {
+        let (None, Some(target)) = pick(change) else {
+            continue;
+        };
+        let columns: Vec<&String> = target.keys().collect();
+        let values: Vec<SqlValue> = columns.iter().map(|c| to_sql(&target[*c])).collect();
+        let names: Vec<String> = columns.iter().map(|c| format!("\"{}\"", c)).collect();
+        let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+        conn.execute(
+            &format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                change.table,
+                names.join(", "),
+                placeholders.join(", ")
+            ),
+            params_from_iter(values.iter()),
+        )
+        .map_err(|e| e.to_string())?;
+        // The row is back — its tombstone must not delete it on peers.
+        conn.execute(
+            "DELETE FROM tombstones WHERE table_name = ?1 AND record_id = ?2",
+            rusqlite::params![change.table, change.row_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for change in changes.iter().filter(|c| c.table == "sessions") {
+        let (current, target) = pick(change);
+        session_audit::record_replay(conn, change.row_id, verb, current, target)?;
+    }
+
+    // Apps carry the project for their sessions (Layer 1) — no trigger marks
+    // those days dirty, so do it here.
+    for change in changes.iter().filter(|c| c.table == "applications") {
+        conn.execute(
+            "INSERT INTO session_project_cache_dirty (date, updated_at)
+             SELECT DISTINCT date, CURRENT_TIMESTAMP FROM sessions WHERE app_id = ?1
+             ON CONFLICT(date) DO UPDATE SET updated_at = CURRENT_TIMESTAMP",
+            [change.row_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn load_entry(conn: &Connection, sql_where: &str) -> Result<Option<JournalEntry>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM mutation_journal WHERE {}",
+            ENTRY_COLUMNS, sql_where
+        ),
+        [],
+        row_to_entry,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Reverts the newest action that is not undone yet.
+pub(crate) fn undo_last_action_in_conn(
+    conn: &mut Connection,
+) -> Result<Option<JournalEntry>, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let Some(entry) = load_entry(&tx, "undone_at IS NULL ORDER BY id DESC LIMIT 1")? else {
+        return Ok(None);
+    };
+    let mut changes = load_changes(&tx, entry.id)?;
+    changes.reverse();
+    write_images(&tx, &changes, true)?;
+    tx.execute(
+        "UPDATE mutation_journal SET undone_at = datetime('now') WHERE id = ?1",
+        [entry.id],
+    )
+    .map_err(|e| e.to_string())?;
+    let entry = load_entry(&tx, &format!("id = {}", entry.id))?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(entry)
+}
+
+/// Re-applies the most recently undone action.
+pub(crate) fn redo_last_action_in_conn(
+    conn: &mut Connection,
+) -> Result<Option<JournalEntry>, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let Some(entry) = load_entry(&tx, "undone_at IS NOT NULL ORDER BY id ASC LIMIT 1")? else {
+        return Ok(None);
+    };
+    let changes = load_changes(&tx, entry.id)?;
+    write_images(&tx, &changes, false)?;
+    tx.execute(
+        "UPDATE mutation_journal SET undone_at = NULL WHERE id = ?1",
+        [entry.id],
+    )
+    .map_err(|e| e.to_string())?;
+    let entry = load_entry(&tx, &format!("id = {}", entry.id))?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(entry)
+}
+
+pub(crate) fn query_action_history(
+    conn: &Connection,
+    limit: i64,
+) -> Result<Vec<JournalEntry>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM mutation_journal ORDER BY id DESC LIMIT ?1",
+            ENTRY_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([limit], row_to_entry)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Newest first; undone entries (the redo stack) are included.
+#[tauri::command]
+pub async fn get_action_history(
+    app: AppHandle,
+    limit: Option<i64>,
+) -> Result<Vec<JournalEntry>, String> {
+    let limit = limit.unwrap_or(50).clamp(1, MAX_ENTRIES);
+    run_db_blocking(app, move |conn| query_action_history(conn, limit)).await
+}
+
+/// `None` when there is nothing to undo.
+#[tauri::command]
+pub async fn undo_last_action(app: AppHandle) -> Result<Option<JournalEntry>, String> {
+    run_db_blocking(app, undo_last_action_in_conn).await
+}
+
+/// `None` when there is nothing to redo.
+#[tauri::command]
+pub async fn redo_last_action(app: AppHandle) -> Result<Option<JournalEntry>, String> {
+    run_db_blocking(app, redo_last_action_in_conn).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        conn.execute_batch(include_str!("../../resources/sql/schema.sql"))
+            .expect("schema");
+        crate::db_migrations::run_migrations(&conn).expect("migrations");
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, created_at) VALUES (1, 'Alpha', datetime('now'));
+             INSERT INTO applications (id, executable_name, display_name) VALUES (10, 'code.exe', 'Code');
+             INSERT INTO sessions (id, app_id, start_time, end_time, duration_seconds, date)
+             VALUES (100, 10, '2026-03-02T09:00:00', '2026-03-02T10:00:00', 3600, '2026-03-02'),
+                    (101, 10, '2026-03-02T11:00:00', '2026-03-02T12:00:00', 3600, '2026-03-02');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn project_of(conn: &Connection, id: i64) -> Option<i64> {
+        conn.query_row("SELECT project_id FROM sessions WHERE id = ?1", [id], |r| {
+            r.get(0)
+        })
+        .unwrap()
+    }
+
+    fn record(conn: &mut Connection, sql: &str) -> Option<i64> {
+        let tx = conn.transaction().unwrap();
+        let mut journal = Recorder::new("test", "test action");
+        journal
+            .track(&tx, "sessions", &id_list_sql(&[100, 101]), vec![])
+            .unwrap();
+        tx.execute_batch(sql).unwrap();
+        let id = journal.finish(&tx).unwrap();
+        tx.commit().unwrap();
+        id
+    }
+
+    #[test]
+    fn undo_and_redo_replay_updates_inserts_and_tombstones() {
+        let mut conn = test_conn();
+        record(
+            &mut conn,
+            "UPDATE sessions SET project_id = 1 WHERE id IN (100, 101);",
+        )
+        .expect("entry");
+        assert!(
+            record(&mut conn, "UPDATE sessions SET project_id = 1;").is_none(),
+            "no-op"
+        );
+
+        let tx = conn.transaction().unwrap();
+        let mut journal = Recorder::new("split", "split 100");
+        journal
+            .track(
+                &tx,
+                "sessions",
+                "id = 100 OR split_source_session_id = 100",
+                vec![],
+            )
+            .unwrap();
+        tx.execute_batch(
+            "UPDATE sessions SET end_time = '2026-03-02T09:30:00', split_source_session_id = 100 WHERE id = 100;
+             INSERT INTO sessions (id, app_id, start_time, end_time, duration_seconds, date, split_source_session_id)
+             VALUES (102, 10, '2026-03-02T09:30:00', '2026-03-02T10:00:00', 1800, '2026-03-02', 100);",
+        )
+        .unwrap();
+        journal.finish(&tx).unwrap().expect("split entry");
+        tx.commit().unwrap();
+
+        let undone = undo_last_action_in_conn(&mut conn)
+            .unwrap()
+            .expect("undo split");
+        assert_eq!(undone.action, "split");
+        assert!(undone.undone_at.is_some());
+        let parts: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sessions", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(parts, 2);
+        let tombstones: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM tombstones WHERE table_name = 'sessions' AND record_id = 102",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(tombstones, 1, "undoing an insert is a delete");
+
+        undo_last_action_in_conn(&mut conn)
+            .unwrap()
+            .expect("undo assign");
+        assert_eq!(project_of(&conn, 100), None);
+        assert!(undo_last_action_in_conn(&mut conn).unwrap().is_none());
+
+        redo_last_action_in_conn(&mut conn)
+            .unwrap()
+            .expect("redo assign");
+        assert_eq!(project_of(&conn, 101), Some(1));
+        redo_last_action_in_conn(&mut conn)
+            .unwrap()
+            .expect("redo split");
+        let end: String = conn
+            .query_row("SELECT end_time FROM sessions WHERE id = 102", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(end, "2026-03-02T10:00:00");
+        let tombstones: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM tombstones WHERE record_id = 102",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(tombstones, 0, "re-created row drops its tombstone");
+        assert!(redo_last_action_in_conn(&mut conn).unwrap().is_none());
+    }
+
+    #[test]
+    fn undo_refuses_rows_changed_later_and_new_action_clears_redo() {
+        let mut conn = test_conn();
+        record(
+            &mut conn,
+            "UPDATE sessions SET project_id = 1 WHERE id = 100;",
+        )
+        .unwrap();
+        conn.execute("UPDATE sessions SET comment = 'later' WHERE id = 100", [])
+            .unwrap();
+        let err = undo_last_action_in_conn(&mut conn).unwrap_err();
+        assert!(err.contains("sessions #100"), "{}", err);
+        assert_eq!(project_of(&conn, 100), Some(1), "nothing written");
+
+        conn.execute("UPDATE sessions SET comment = NULL WHERE id = 100", [])
+            .unwrap();
+        undo_last_action_in_conn(&mut conn).unwrap().unwrap();
+        record(
+            &mut conn,
+            "UPDATE sessions SET rate_multiplier = 2 WHERE id = 101;",
+        )
+        .unwrap();
+        assert!(redo_last_action_in_conn(&mut conn).unwrap().is_none());
+        let history = query_action_history(&conn, 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].undone_at.is_none());
+    }
+}
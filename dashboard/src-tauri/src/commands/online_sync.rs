@@ -28,6 +28,39 @@ pub struct OnlineSyncSettings {
     pub encryption_key: String,
     pub sync_interval_minutes: u32,
     pub auto_sync_on_startup: bool,
+    /// Transfer storage backend (daemon `OnlineStorageSettings`). `None` keeps
+    /// whatever is already in the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<OnlineStorageSettings>,
+    /// End-to-end passphrase (keychain only). `None` leaves it unchanged, an
+    /// empty string turns it off. Changing an existing one goes through
+    /// `change_online_sync_passphrase` so the daemon re-wraps its key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+}
+
+/// Mirror of the daemon's `config::OnlineStorageSettings`; `password` (WebDAV
+/// password / S3 secret key) lives in the keychain like the other secrets.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OnlineStorageSettings {
+    #[serde(default)]
+    pub backend: String,
+    #[serde(default)]
+    pub local_path: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub bucket: String,
+    #[serde(default)]
+    pub region: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_style: Option<bool>,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
 }
 
 impl Default for OnlineSyncSettings {
@@ -40,6 +73,8 @@ impl Default for OnlineSyncSettings {
             encryption_key: String::new(),
             sync_interval_minutes: 30,
             auto_sync_on_startup: false,
+            storage: None,
+            passphrase: None,
         }
     }
 }
@@ -51,6 +86,8 @@ impl Default for OnlineSyncSettings {
 /// entries — see src/config.rs::load_online_sync_settings.
 const KC_AUTH: &str = "online.auth_token";
 const KC_ENC: &str = "online.encryption_key";
+const KC_STORAGE: &str = "online.storage_password";
+const KC_PASSPHRASE: &str = "online.passphrase";
 
 /// Get online sync settings. Secrets are hydrated from the OS keychain; the JSON
 /// on disk holds only non-sensitive fields. Legacy JSON with inline secrets is
@@ -65,41 +102,81 @@ pub fn get_online_sync_settings() -> Result<OnlineSyncSettings, String> {
         OnlineSyncSettings::default()
     };
     // Migracja: jeśli JSON nadal trzyma sekrety, przenieś do keychaina i wyczyść plik.
-    let needs_migration =
-        !settings.auth_token.is_empty() || !settings.encryption_key.is_empty();
+    let needs_migration = !settings.auth_token.is_empty()
+        || !settings.encryption_key.is_empty()
+        || settings.storage.as_ref().is_some_and(|s| !s.password.is_empty());
     if needs_migration {
         // save_* zapisze sekrety do keychaina i wyzeruje je w pliku na dysku.
         save_online_sync_settings(settings.clone())?;
     }
     settings.auth_token = timeflow_shared::secret_store::get_secret(KC_AUTH).unwrap_or_default();
     settings.encryption_key = timeflow_shared::secret_store::get_secret(KC_ENC).unwrap_or_default();
+    if let Some(storage) = settings.storage.as_mut() {
+        storage.password = timeflow_shared::secret_store::get_secret(KC_STORAGE).unwrap_or_default();
+    }
+    settings.passphrase = timeflow_shared::secret_store::get_secret(KC_PASSPHRASE);
     Ok(settings)
 }
 
 /// Save online sync settings. Secrets go to the OS keychain; the JSON on disk is
 /// written WITHOUT secrets (the daemon hydrates them from the keychain too).
+/// Daemon-only keys already in the file (sync_mode, group_id, storage when not
+/// sent) are kept.
 #[tauri::command]
 pub fn save_online_sync_settings(settings: OnlineSyncSettings) -> Result<(), String> {
     timeflow_shared::secret_store::set_secret(KC_AUTH, &settings.auth_token)?;
-    timeflow_shared::secret_store::set_secret(KC_ENC, &settings.encryption_key)?;
+    // A replaced key is retired, not lost: packages sealed with it still open.
+    timeflow_shared::sync_keyring::install_key(&settings.encryption_key)?;
+    let mut settings = settings;
+    if let Some(storage) = settings.storage.as_mut() {
+        timeflow_shared::secret_store::set_secret(KC_STORAGE, &storage.password)?;
+        storage.password.clear();
+    }
+    if let Some(passphrase) = settings.passphrase.take() {
+        timeflow_shared::secret_store::set_secret(KC_PASSPHRASE, &passphrase)?;
+    }
     let on_disk = OnlineSyncSettings {
         auth_token: String::new(),
         encryption_key: String::new(),
         ..settings
     };
     let path = timeflow_data_dir()?.join("online_sync_settings.json");
-    let json = serde_json::to_string_pretty(&on_disk).map_err(|e| e.to_string())?;
+    let existing = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok());
+    let merged = merge_settings_json(existing, &on_disk)?;
+    let json = serde_json::to_string_pretty(&merged).map_err(|e| e.to_string())?;
     std::fs::write(&path, json).map_err(|e| e.to_string())
 }
 
-/// Trigger online sync via daemon HTTP endpoint.
+/// Overlays `settings` on the JSON object already on disk.
+fn merge_settings_json(
+    existing: Option<serde_json::Value>,
+    settings: &OnlineSyncSettings,
+) -> Result<serde_json::Value, String> {
+    let mut merged = match existing {
+        Some(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    if let serde_json::Value::Object(fields) =
+        serde_json::to_value(settings).map_err(|e| e.to_string())?
+    {
+        merged.extend(fields);
+    }
+    Ok(serde_json::Value::Object(merged))
+}
+
+/// Trigger online sync via daemon HTTP endpoint. `dry_run` previews the merge
+/// without writing anything — fetch the result with `get_sync_preview`.
 #[tauri::command]
-pub async fn run_online_sync() -> Result<String, String> {
+pub async fn run_online_sync(dry_run: Option<bool>) -> Result<String, String> {
+    let body = serde_json::json!({ "dry_run": dry_run.unwrap_or(false) });
     let result = tokio::task::spawn_blocking(move || {
         let client = build_http_client();
         let url = format!("{}/online/trigger-sync", DAEMON_BASE);
         let resp = client
             .post(&url)
+            .json(&body)
             .send()
             .map_err(|e| format!("Daemon unreachable: {}", e))?;
         let status = resp.status();
@@ -157,3 +234,101 @@ pub async fn cancel_online_sync() -> Result<(), String> {
     .await
     .map_err(|e| format!("Task failed: {}", e))?
 }
+
+/// Change the end-to-end passphrase: the daemon re-wraps its sync key under
+/// the new one (nothing is re-uploaded) and updates the keychain.
+#[tauri::command]
+pub async fn change_online_sync_passphrase(
+    old_passphrase: String,
+    new_passphrase: String,
+) -> Result<(), String> {
+    let body = serde_json::json!({
+        "old_passphrase": old_passphrase,
+        "new_passphrase": new_passphrase,
+    });
+    tokio::task::spawn_blocking(move || {
+        let client = build_http_client();
+        let url = format!("{}/online/change-passphrase", DAEMON_BASE);
+        let resp = client
+            .post(&url)
+            .json(&body)
+            .send()
+            .map_err(|e| format!("Daemon unreachable: {}", e))?;
+        let status = resp.status();
+        let body = resp
+            .text()
+            .map_err(|e| format!("Read response failed: {}", e))?;
+        if !status.is_success() {
+            return Err(format!("Daemon refused: {} — {}", status, body));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Rotate `encryption_key`: the daemon generates a new key, hands it to paired
+/// devices (LAN, and an async key package) and keeps the old one for packages
+/// already in flight. Returns the daemon's rotation report.
+#[tauri::command]
+pub async fn rotate_online_sync_key() -> Result<serde_json::Value, String> {
+    tokio::task::spawn_blocking(move || {
+        let client = build_http_client();
+        let url = format!("{}/online/rotate-key", DAEMON_BASE);
+        let resp = client
+            .post(&url)
+            .send()
+            .map_err(|e| format!("Daemon unreachable: {}", e))?;
+        let status = resp.status();
+        let body = resp
+            .text()
+            .map_err(|e| format!("Read response failed: {}", e))?;
+        if !status.is_success() {
+            return Err(format!("Daemon refused: {} — {}", status, body));
+        }
+        let parsed: serde_json::Value =
+            serde_json::from_str(&body).map_err(|e| format!("Invalid daemon response: {}", e))?;
+        Ok(parsed["report"].clone())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_keeps_daemon_only_keys_already_on_disk() {
+        let existing = serde_json::json!({
+            "enabled": false,
+            "server_url": "https://old.example.com",
+            "sync_mode": "async",
+            "group_id": "team",
+            "storage": { "backend": "local", "local_path": "/mnt/share" },
+        });
+        let settings = OnlineSyncSettings {
+            enabled: true,
+            server_url: "https://sync.example.com".to_string(),
+            ..OnlineSyncSettings::default()
+        };
+
+        let merged = merge_settings_json(Some(existing), &settings).unwrap();
+        assert_eq!(merged["enabled"], true);
+        assert_eq!(merged["server_url"], "https://sync.example.com");
+        assert_eq!(merged["sync_mode"], "async");
+        assert_eq!(merged["group_id"], "team");
+        assert_eq!(merged["storage"]["local_path"], "/mnt/share");
+
+        let with_storage = OnlineSyncSettings {
+            storage: Some(OnlineStorageSettings {
+                backend: "s3".to_string(),
+                ..OnlineStorageSettings::default()
+            }),
+            ..settings
+        };
+        let merged = merge_settings_json(Some(merged), &with_storage).unwrap();
+        assert_eq!(merged["storage"]["backend"], "s3");
+        assert!(merged["storage"].get("path_style").is_none());
+    }
+}
@@ -0,0 +1,448 @@
+// Overlaps between manual and tracked sessions.
+//
+// A manual session logged over a stretch the tracker also recorded used to
+// count on both sides. The user picks an overlap policy (stored next to the
+// time algorithm in `estimate_settings`), and the time-algorithm host applies
+// it to the intervals before any strategy sees them, so every total —
+// dashboard, estimates, reports — resolves overlaps the same way.
+// Tracked sessions of different apps on one project are listed for review
+// too; the strategy already counts such shared time once.
+
+use rusqlite::{Connection, OptionalExtension};
+use tauri::AppHandle;
+
+use super::datetime::{format_local_naive, parse_datetime_fixed};
+use super::helpers::run_db_blocking;
+use super::sql_fragments::{ensure_session_project_cache, ACTIVE_SESSION_FILTER_S};
+use super::time_algorithm::IntervalInput;
+use super::types::{DateRange, SessionOverlap};
+
+const OVERLAP_POLICY_SETTING_KEY: &str = "overlap_policy";
+
+const OVERLAP_KIND_MANUAL_TRACKED: &str = "manual_tracked";
+const OVERLAP_KIND_CROSS_APP: &str = "cross_app";
+
+/// How time covered by both a manual and a tracked session is counted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OverlapPolicy {
+    /// Tracked time under a manual session is dropped.
+    ManualWins,
+    /// Manual time under a tracked session is dropped.
+    TrackedWins,
+    /// Both are kept and left to the time strategy (the default).
+    CountBoth,
+}
+
+impl OverlapPolicy {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            OverlapPolicy::ManualWins => "manual_wins",
+            OverlapPolicy::TrackedWins => "tracked_wins",
+            OverlapPolicy::CountBoth => "count_both",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "manual_wins" => Some(OverlapPolicy::ManualWins),
+            "tracked_wins" => Some(OverlapPolicy::TrackedWins),
+            "count_both" => Some(OverlapPolicy::CountBoth),
+            _ => None,
+        }
+    }
+}
+
+/// The stored policy; unset/unknown (or no settings table) means count both.
+pub(crate) fn active_overlap_policy(conn: &Connection) -> OverlapPolicy {
+    conn.query_row(
+        "SELECT value FROM estimate_settings WHERE key = ?1 LIMIT 1",
+        [OVERLAP_POLICY_SETTING_KEY],
+        |row| row.get::<_, String>(0),
+    )
+    .optional()
+    .ok()
+    .flatten()
+    .and_then(|value| OverlapPolicy::parse(&value))
+    .unwrap_or(OverlapPolicy::CountBoth)
+}
+
+/// Applies `policy` to `intervals`. The winning side is taken from
+/// `reference` when given — a project-filtered load passes the unfiltered
+/// intervals so another project's manual session still wins over its time.
+pub(crate) fn apply_overlap_policy(
+    intervals: Vec<IntervalInput>,
+    reference: Option<&[IntervalInput]>,
+    policy: OverlapPolicy,
+) -> Vec<IntervalInput> {
+    let manual_wins = match policy {
+        OverlapPolicy::ManualWins => true,
+        OverlapPolicy::TrackedWins => false,
+        OverlapPolicy::CountBoth => return intervals,
+    };
+    let mut winners: Vec<_> = reference
+        .unwrap_or(&intervals)
+        .iter()
+        .filter(|interval| interval.is_manual == manual_wins)
+        .map(|interval| (interval.start, interval.end))
+        .collect();
+    winners.sort();
+    let mut merged: Vec<(_, _)> = Vec::with_capacity(winners.len());
+    for (start, end) in winners {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let mut out = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        if interval.is_manual == manual_wins {
+            out.push(interval);
+            continue;
+        }
+        let mut cursor = interval.start;
+        for (start, end) in &merged {
+            if *end <= cursor || *start >= interval.end {
+                continue;
+            }
+            if *start > cursor {
+                out.push(IntervalInput {
+                    start: cursor,
+                    end: *start,
+                    ..interval.clone()
+                });
+            }
+            cursor = cursor.max(*end);
+        }
+        if cursor < interval.end {
+            out.push(IntervalInput {
+                start: cursor,
+                ..interval
+            });
+        }
+    }
+    out
+}
+
+/// A session as seen by overlap detection, times in unix seconds.
+#[derive(Clone, Debug)]
+struct OverlapItem {
+    id: i64,
+    is_manual: bool,
+    app_id: Option<i64>,
+    label: String,
+    project_id: Option<i64>,
+    project_name: Option<String>,
+    start: i64,
+    end: i64,
+}
+
+/// Pairs `(a, b, start, end)` of overlapping items worth reviewing: manual vs
+/// tracked, or two tracked sessions of different apps on the same project.
+/// Items must be sorted by start.
+fn detect_overlaps(items: &[OverlapItem]) -> Vec<(usize, usize, i64, i64)> {
+    let mut pairs = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        open.retain(|&other| items[other].end > item.start);
+        for &other_index in &open {
+            let other = &items[other_index];
+            let relevant = if item.is_manual != other.is_manual {
+                true
+            } else {
+                !item.is_manual
+                    && item.project_id.is_some()
+                    && item.project_id == other.project_id
+                    && item.app_id != other.app_id
+            };
+            let end = item.end.min(other.end);
+            if relevant && end > item.start {
+                pairs.push((other_index, index, item.start, end));
+            }
+        }
+        open.push(index);
+    }
+    pairs
+}
+
+fn load_overlap_items(
+    conn: &Connection,
+    date_range: &DateRange,
+) -> Result<Vec<OverlapItem>, String> {
+    ensure_session_project_cache(conn, &date_range.start, &date_range.end)?;
+    let sql = format!(
+        "SELECT s.id, 0 as is_manual, s.app_id, a.display_name,
+                spc.project_id, p.name, s.start_time, s.end_time
+         FROM session_project_cache spc
+         JOIN sessions s ON s.id = spc.session_id
+         JOIN applications a ON a.id = s.app_id
+         LEFT JOIN projects p ON p.id = spc.project_id
+         WHERE spc.session_date >= ?1 AND spc.session_date <= ?2
+           AND {ACTIVE_SESSION_FILTER_S}
+         UNION ALL
+         SELECT ms.id, 1 as is_manual, NULL, ms.title,
+                ms.project_id, p.name, ms.start_time, ms.end_time
+         FROM manual_sessions ms
+         LEFT JOIN projects p ON p.id = ms.project_id
+         WHERE ms.date >= ?1 AND ms.date <= ?2"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([&date_range.start, &date_range.end], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)? != 0,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut items = Vec::new();
+    for row in rows {
+        let (id, is_manual, app_id, label, project_id, project_name, start, end) =
+            row.map_err(|e| e.to_string())?;
+        let (Some(start), Some(end)) = (parse_datetime_fixed(&start), parse_datetime_fixed(&end))
+        else {
+            continue;
+        };
+        if end > start {
+            items.push(OverlapItem {
+                id,
+                is_manual,
+                app_id,
+                label,
+                project_id,
+                project_name,
+                start: start.timestamp(),
+                end: end.timestamp(),
+            });
+        }
+    }
+    items.sort_by_key(|item| (item.start, item.end));
+    Ok(items)
+}
+
+/// Current overlaps in `date_range`, oldest first. With `project_id`, only
+/// overlaps where either side belongs to that project.
+pub(crate) fn query_session_overlaps(
+    conn: &Connection,
+    date_range: &DateRange,
+    project_id: Option<i64>,
+) -> Result<Vec<SessionOverlap>, String> {
+    let items = load_overlap_items(conn, date_range)?;
+    Ok(detect_overlaps(&items)
+        .into_iter()
+        .filter_map(|(a, b, start, end)| {
+            // The tracked side comes first; manual vs tracked has exactly one.
+            let (first, second) = if items[a].is_manual {
+                (&items[b], &items[a])
+            } else {
+                (&items[a], &items[b])
+            };
+            if project_id.is_some()
+                && first.project_id != project_id
+                && second.project_id != project_id
+            {
+                return None;
+            }
+            Some(SessionOverlap {
+                kind: if second.is_manual {
+                    OVERLAP_KIND_MANUAL_TRACKED
+                } else {
+                    OVERLAP_KIND_CROSS_APP
+                }
+                .to_string(),
+                date: format_local_naive(start)[..10].to_string(),
+                start_time: format_local_naive(start),
+                end_time: format_local_naive(end),
+                overlap_seconds: end - start,
+                session_id: first.id,
+                app_name: first.label.clone(),
+                project_id: first.project_id,
+                project_name: first.project_name.clone(),
+                other_session_id: second.id,
+                other_is_manual: second.is_manual,
+                other_label: second.label.clone(),
+                other_project_id: second.project_id,
+                other_project_name: second.project_name.clone(),
+            })
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_overlap_policy(app: AppHandle) -> Result<String, String> {
+    run_db_blocking(app, |conn| {
+        Ok(active_overlap_policy(conn).as_str().to_string())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_overlap_policy(app: AppHandle, policy: String) -> Result<(), String> {
+    if OverlapPolicy::parse(&policy).is_none() {
+        return Err(format!("Unknown overlap policy: {policy}"));
+    }
+    run_db_blocking(app, move |conn| {
+        conn.execute(
+            "INSERT INTO estimate_settings (key, value, updated_at)
+             VALUES (?1, ?2, datetime('now'))
+             ON CONFLICT(key) DO UPDATE SET
+               value = excluded.value,
+               updated_at = datetime('now')",
+            rusqlite::params![OVERLAP_POLICY_SETTING_KEY, policy],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_session_overlaps(
+    app: AppHandle,
+    date_range: DateRange,
+    project_id: Option<i64>,
+) -> Result<Vec<SessionOverlap>, String> {
+    run_db_blocking(app, move |conn| {
+        query_session_overlaps(conn, &date_range, project_id)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn interval(start_hour: u32, end_hour: u32, project: &str, is_manual: bool) -> IntervalInput {
+        IntervalInput {
+            start: Local
+                .with_ymd_and_hms(2026, 3, 2, start_hour, 0, 0)
+                .unwrap(),
+            end: Local.with_ymd_and_hms(2026, 3, 2, end_hour, 0, 0).unwrap(),
+            project_key: project.to_string(),
+            multiplier: 1.0,
+            is_manual,
+            comment: None,
+            billable: true,
+            background: false,
+        }
+    }
+
+    fn hours(intervals: &[IntervalInput]) -> Vec<(String, u32, u32)> {
+        use chrono::Timelike;
+        intervals
+            .iter()
+            .map(|i| (i.project_key.clone(), i.start.hour(), i.end.hour()))
+            .collect()
+    }
+
+    fn item(
+        id: i64,
+        is_manual: bool,
+        app_id: Option<i64>,
+        project_id: i64,
+        start: i64,
+        end: i64,
+    ) -> OverlapItem {
+        OverlapItem {
+            id,
+            is_manual,
+            app_id,
+            label: format!("item {id}"),
+            project_id: Some(project_id),
+            project_name: None,
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn policies_trim_the_losing_side_of_each_overlap() {
+        let intervals = || {
+            vec![
+                interval(9, 13, "project:1", false),
+                interval(10, 11, "project:2", true),
+                interval(12, 14, "project:2", true),
+            ]
+        };
+
+        assert_eq!(
+            hours(&apply_overlap_policy(
+                intervals(),
+                None,
+                OverlapPolicy::ManualWins
+            )),
+            vec![
+                ("project:1".to_string(), 9, 10),
+                ("project:1".to_string(), 11, 12),
+                ("project:2".to_string(), 10, 11),
+                ("project:2".to_string(), 12, 14),
+            ]
+        );
+        assert_eq!(
+            hours(&apply_overlap_policy(
+                intervals(),
+                None,
+                OverlapPolicy::TrackedWins
+            )),
+            vec![
+                ("project:1".to_string(), 9, 13),
+                ("project:2".to_string(), 13, 14)
+            ]
+        );
+        assert_eq!(
+            hours(&apply_overlap_policy(
+                intervals(),
+                None,
+                OverlapPolicy::CountBoth
+            )),
+            hours(&intervals())
+        );
+
+        // A filtered load still yields to winners outside the filter.
+        let reference = intervals();
+        assert_eq!(
+            hours(&apply_overlap_policy(
+                vec![interval(9, 13, "project:1", false)],
+                Some(&reference),
+                OverlapPolicy::ManualWins,
+            )),
+            vec![
+                ("project:1".to_string(), 9, 10),
+                ("project:1".to_string(), 11, 12)
+            ]
+        );
+    }
+
+    #[test]
+    fn detection_pairs_manual_with_tracked_and_apps_within_a_project() {
+        let items = vec![
+            item(1, false, Some(10), 1, 0, 100),
+            item(2, false, Some(11), 1, 50, 150),
+            item(3, false, Some(12), 2, 60, 70),
+            item(4, true, None, 2, 90, 200),
+            item(5, false, Some(10), 1, 150, 160),
+        ];
+
+        let pairs: Vec<(i64, i64, i64, i64)> = detect_overlaps(&items)
+            .into_iter()
+            .map(|(a, b, start, end)| (items[a].id, items[b].id, start, end))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (1, 2, 50, 100),
+                (1, 4, 90, 100),
+                (2, 4, 90, 150),
+                (4, 5, 150, 160),
+            ]
+        );
+    }
+}
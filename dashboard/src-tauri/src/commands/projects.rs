@@ -6,9 +6,11 @@ use super::analysis::{
     compute_project_activity_unique, compute_project_clock_totals_by_id, daily_seconds_by_series,
     query_activity_date_range,
 };
-use super::time_algorithm::distribute_app_seconds;
+use super::time_algorithm::{compute_project_billable_totals_by_id, distribute_app_seconds};
 use super::helpers::{name_hash, run_db_blocking, LAST_PRUNE_EPOCH_SECS, PRUNE_CACHE_TTL_SECS};
-use super::sql_fragments::{ensure_session_project_cache, SESSION_PROJECT_CTE};
+use super::sql_fragments::{
+    ensure_session_project_cache, SESSION_BILLABLE_SQL, SESSION_PROJECT_CTE,
+};
 use super::types::{
     DateRange, FolderProjectCandidate, FolderSyncResult, Project, ProjectDbStats, ProjectExtraInfo,
     ProjectFolder, ProjectWithStats, TopApp,
@@ -468,7 +470,8 @@ fn query_projects_with_stats(
                 p.assigned_folder_path,
                 p.frozen_at,
                 p.merged_into,
-                p.merged_at
+                p.merged_at,
+                p.device_local
          FROM projects p
          LEFT JOIN applications a ON a.project_id = p.id
          WHERE {}
@@ -507,6 +510,7 @@ fn query_projects_with_stats(
                 frozen_at: row.get(9)?,
                 merged_into: row.get(10)?,
                 merged_at: row.get(11)?,
+                device_local: row.get::<_, i64>(12)? != 0,
                 daily_seconds: all_time_daily_by_id
                     .get(&project_id)
                     .cloned()
@@ -564,6 +568,7 @@ pub(crate) fn query_active_project_with_stats(
         last_manual_activity,
         merged_into,
         merged_at,
+        device_local,
     ): (
         i64,
         String,
@@ -577,6 +582,7 @@ pub(crate) fn query_active_project_with_stats(
         Option<String>,
         Option<String>,
         Option<String>,
+        i64,
     ) = conn
         .query_row(
             "SELECT p.id,
@@ -595,7 +601,8 @@ pub(crate) fn query_active_project_with_stats(
                      FROM manual_sessions ms
                      WHERE ms.project_id = p.id) as last_manual_activity,
                     p.merged_into,
-                    p.merged_at
+                    p.merged_at,
+                    p.device_local
              FROM projects p
              LEFT JOIN applications a ON a.project_id = p.id
              WHERE p.id = ?1
@@ -616,6 +623,7 @@ pub(crate) fn query_active_project_with_stats(
                     row.get(9)?,
                     row.get(10)?,
                     row.get(11)?,
+                    row.get(12)?,
                 ))
             },
         )
@@ -678,6 +686,7 @@ pub(crate) fn query_active_project_with_stats(
         app_count,
         last_activity,
         assigned_folder_path,
+        device_local: device_local != 0,
         daily_seconds,
     })
 }
@@ -763,6 +772,49 @@ pub async fn unfreeze_project(app: AppHandle, id: i64) -> Result<(), String> {
     .await
 }
 
+/// Selective sync: a device-local project (with its apps, sessions, manual
+/// sessions and file activities) is left out of every sync export. Clearing
+/// the flag re-stamps its sessions so the next delta sync carries them.
+pub(crate) fn set_project_device_local_in_conn(
+    conn: &mut rusqlite::Connection,
+    id: i64,
+    device_local: bool,
+) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let changed = tx
+        .execute(
+            "UPDATE projects SET device_local = ?2 WHERE id = ?1 AND device_local <> ?2",
+            rusqlite::params![id, device_local as i64],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed > 0 && !device_local {
+        tx.execute(
+            "UPDATE sessions SET updated_at = datetime('now')
+             WHERE project_id = ?1
+                OR app_id IN (SELECT id FROM applications WHERE project_id = ?1)",
+            [id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE manual_sessions SET updated_at = datetime('now') WHERE project_id = ?1",
+            [id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_project_device_local(
+    app: AppHandle,
+    id: i64,
+    device_local: bool,
+) -> Result<(), String> {
+    run_db_blocking(app, move |conn| set_project_device_local_in_conn(conn, id, device_local))
+        .await
+}
+
 /// Logically merges `source` project into `target`:
 /// marker + auto-exclude + re-point apps. No session rows are touched —
 /// time rolls up at query level (see rolled totals helpers).
@@ -793,6 +845,18 @@ pub(crate) fn merge_project_in_conn(
         )
         .map_err(|_| "Source project must exist and not be merged already".to_string())?;
 
+    let mut journal = super::mutation_journal::Recorder::new(
+        "merge_project",
+        format!("{} → {}", source_name, target_name),
+    );
+    journal.track(
+        &tx,
+        "projects",
+        "id = ?1 OR merged_into = ?2",
+        vec![source_id.into(), source_name.clone().into()],
+    )?;
+    journal.track(&tx, "applications", "project_id = ?1", vec![source_id.into()])?;
+
     // Flatten: children of source become children of target (single-level hierarchy).
     tx.execute(
         "UPDATE projects SET merged_into = ?1, updated_at = datetime('now') WHERE merged_into = ?2",
@@ -818,6 +882,7 @@ pub(crate) fn merge_project_in_conn(
     )
     .map_err(|e| e.to_string())?;
 
+    journal.finish(&tx)?;
     tx.commit().map_err(|e| e.to_string())
 }
 
@@ -1040,9 +1105,11 @@ pub async fn create_project(
             created_at: chrono::Local::now().to_rfc3339(),
             excluded_at: None,
             frozen_at: None,
-            merged_into: None,
-            merged_at: None,
+            merged_into: Some(None),
+            merged_at: Some(None),
             assigned_folder_path: normalized_folder,
+            client_name: Some(None),
+            billable: Some(true),
             is_imported: 0,
             updated_at: chrono::Local::now().to_rfc3339(),
         })
@@ -1458,9 +1525,11 @@ pub async fn create_project_from_folder(
             created_at: chrono::Local::now().to_rfc3339(),
             excluded_at: None,
             frozen_at: None,
-            merged_into: None,
-            merged_at: None,
+            merged_into: Some(None),
+            merged_at: Some(None),
             assigned_folder_path: Some(folder_path),
+            client_name: Some(None),
+            billable: Some(true),
             is_imported: 0,
             updated_at: chrono::Local::now().to_rfc3339(),
         })
@@ -1629,7 +1698,8 @@ pub(crate) fn query_project_extra_info(
                      END
                  )
                  FROM session_projects sp
-                 WHERE {PROJECT_WITH_MERGED_CHILDREN_FILTER}"
+                 WHERE {PROJECT_WITH_MERGED_CHILDREN_FILTER}
+                   AND {SESSION_BILLABLE_SQL} = 1"
             );
             conn.query_row(&sql, rusqlite::params![start, end, p_id], |row| {
                 Ok(row.get::<_, Option<f64>>(0)?.unwrap_or(0.0))
@@ -1646,8 +1716,9 @@ pub(crate) fn query_project_extra_info(
         ensure_session_project_cache(conn, start, end)?;
     }
 
+    // (clock total, billable part) per project — only billable time has value.
     let all_time_totals = if let Some(range) = all_time_range.as_ref() {
-        compute_project_clock_totals_by_id(conn, range, false, true)?
+        compute_project_billable_totals_by_id(conn, range, false, true)?
     } else {
         HashMap::new()
     };
@@ -1658,18 +1729,18 @@ pub(crate) fn query_project_extra_info(
     {
         all_time_totals.clone()
     } else {
-        compute_project_clock_totals_by_id(conn, date_range, false, true)?
+        compute_project_billable_totals_by_id(conn, date_range, false, true)?
     };
 
     let current_value = if let Some((start, end)) = all_time_bounds {
-        let clock_seconds = all_time_totals.get(&id).copied().unwrap_or(0.0);
+        let clock_seconds = all_time_totals.get(&id).map(|t| t.1).unwrap_or(0.0);
         let extra_seconds = get_extra_secs(conn, start, end, id)?;
         ((clock_seconds + extra_seconds) / 3600.0) * effective_rate
     } else {
         0.0
     };
 
-    let period_clock_seconds = period_totals.get(&id).copied().unwrap_or(0.0);
+    let period_clock_seconds = period_totals.get(&id).map(|t| t.1).unwrap_or(0.0);
     let period_extra_seconds = get_extra_secs(conn, &date_range.start, &date_range.end, id)?;
     let period_value = ((period_clock_seconds + period_extra_seconds) / 3600.0) * effective_rate;
 
@@ -1783,7 +1854,7 @@ pub(crate) fn query_project_extra_info(
                 |row| Ok(row.get::<_, i64>(0)? as f64),
             )
             .map_err(|e| e.to_string())?;
-        let clock_total = all_time_totals.get(&id).copied().unwrap_or(0.0);
+        let clock_total = all_time_totals.get(&id).map(|t| t.0).unwrap_or(0.0);
         distribute_app_seconds(&mut apps, clock_total, raw_sum_all);
 
         // Dzienne rozbicie per aplikacja, przeskalowane TYM SAMYM współczynnikiem co
@@ -4,8 +4,12 @@ use super::analysis::query_activity_date_range;
 use super::daemon::load_persisted_session_min_duration;
 use super::helpers::run_db_blocking;
 use super::manual_sessions::get_manual_sessions;
+use super::overlaps::{active_overlap_policy, query_session_overlaps};
 use super::projects::{query_active_project_with_stats, query_project_extra_info};
+use super::rounding::{load_rounding_settings, round_project_total, scale_value_to_rounded};
 use super::sql_fragments::{ensure_session_project_cache, SESSION_PROJECT_CTE};
+use super::tags::{parse_tag_names, summarize_tag_time, tags_json_sql, TagTarget};
+use super::time_algorithm::compute_project_billable_totals_by_id;
 use super::types::{
     DateRange, ManualSessionFilters, ProjectExtraInfo, ProjectReportData, ProjectWithStats,
     SessionWithApp,
@@ -41,6 +45,7 @@ async fn get_report_sessions(
     run_db_blocking(app, move |conn| {
         ensure_session_project_cache(conn, &date_range.start, &date_range.end)?;
 
+        let tags_json = tags_json_sql(TagTarget::Session, "s");
         let sql = format!(
             "{SESSION_PROJECT_CTE}
              SELECT s.id, s.app_id, s.start_time, s.end_time, s.duration_seconds,
@@ -53,7 +58,9 @@ async fn get_report_sessions(
                     s.split_source_session_id,
                     asug_latest.suggested_confidence,
                     asug_latest.suggested_project_id,
-                    p_sug.name
+                    p_sug.name,
+                    {tags_json},
+                    s.billable
              FROM sessions s
              JOIN session_projects sp ON sp.id = s.id
              JOIN applications a ON a.id = s.app_id
@@ -97,6 +104,8 @@ async fn get_report_sessions(
                         suggested_project_id: row.get(15).unwrap_or(None),
                         suggested_project_name: row.get(16).unwrap_or(None),
                         files: Vec::new(),
+                        tags: parse_tag_names(row.get(17)?),
+                        billable: row.get::<_, Option<i64>>(18)?.map(|b| b != 0),
                     })
                 },
             )
@@ -164,6 +173,7 @@ pub async fn get_project_report_data(
                 ManualSessionFilters {
                     date_range: Some(date_range),
                     project_id: Some(project_id),
+                    tag_ids: None,
                 },
             )
             .await;
@@ -194,6 +204,43 @@ pub async fn get_project_report_data(
         manual_sessions.len()
     );
 
+    let tag_items: Vec<(Vec<String>, i64)> = sessions
+        .iter()
+        .map(|s| (s.tags.clone(), s.duration_seconds))
+        .chain(
+            manual_sessions
+                .iter()
+                .map(|m| (m.tags.clone(), m.duration_seconds)),
+        )
+        .collect();
+    let (tag_breakdown, (billable_seconds, non_billable_seconds), overlap_policy, overlaps) =
+        run_db_blocking(app, move |conn| {
+            let tag_breakdown = summarize_tag_time(conn, &tag_items)?;
+            let (total, billable) =
+                compute_project_billable_totals_by_id(conn, &date_range, false, true)?
+                    .get(&project_id)
+                    .copied()
+                    .unwrap_or((0.0, 0.0));
+            let billable_seconds = billable.round() as i64;
+            let non_billable_seconds = (total.round() as i64 - billable_seconds).max(0);
+            let overlap_policy = active_overlap_policy(conn).as_str().to_string();
+            let overlaps = query_session_overlaps(conn, &date_range, Some(project_id))?;
+            Ok((
+                tag_breakdown,
+                (billable_seconds, non_billable_seconds),
+                overlap_policy,
+                overlaps,
+            ))
+        })
+        .await?;
+
+    // Same rule as `computeReportDisplayValues` on the frontend.
+    let rounding = load_rounding_settings();
+    let rounded_total_seconds =
+        round_project_total(project.total_seconds, &project.daily_seconds, &rounding);
+    let rounded_estimate =
+        scale_value_to_rounded(estimate, project.total_seconds, rounded_total_seconds);
+
     log::info!(
         "[report] DONE project_id={} in {:?}",
         project_id,
@@ -206,6 +253,13 @@ pub async fn get_project_report_data(
         estimate,
         sessions,
         manual_sessions,
+        tag_breakdown,
+        billable_seconds,
+        non_billable_seconds,
+        overlap_policy,
+        overlaps,
+        rounded_total_seconds,
+        rounded_estimate,
     })
 }
 
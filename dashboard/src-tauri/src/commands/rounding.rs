@@ -0,0 +1,389 @@
+// Server-side time rounding — the Rust twin of `dashboard/src/lib/rounding.ts`.
+//
+// Raw time stays raw in the database; rounding is applied only to what
+// leaves the backend (estimates, project reports, export metadata), so web
+// UI RPC consumers and exported files show the same numbers as the screen.
+// Settings are the ones the frontend writes through to `user_settings.json`
+// under `timeflow.settings.rounding`. Every function here must keep parity
+// with its TypeScript counterpart — the tests below port `rounding.test.ts`.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use super::user_settings::read_user_setting;
+
+pub(crate) const ROUNDING_SETTINGS_KEY: &str = "timeflow.settings.rounding";
+
+/// `per_day` always rounds to a full hour.
+const FULL_HOUR_MINUTES: i64 = 60;
+/// Allowed intervals in minutes (6 min = 1/10 h), same list as the frontend.
+const ROUNDING_INTERVALS: [i64; 7] = [1, 5, 6, 10, 15, 30, 60];
+const DEFAULT_INTERVAL_MINUTES: i64 = 15;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum RoundingMode {
+    /// Sum raw time first, round the sum once.
+    #[default]
+    Total,
+    /// Round every session, then sum.
+    Session,
+    /// Round every day to a full hour, then sum.
+    Day,
+}
+
+impl RoundingMode {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "per_total" => Some(Self::Total),
+            "per_session" => Some(Self::Session),
+            "per_day" => Some(Self::Day),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum RoundingDirection {
+    /// Ceil — the historical (and default) behaviour.
+    #[default]
+    Up,
+    /// Half up, like `Math.round`.
+    Nearest,
+    Down,
+}
+
+impl RoundingDirection {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "up" => Some(Self::Up),
+            "nearest" => Some(Self::Nearest),
+            "down" => Some(Self::Down),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RoundingSettings {
+    pub enabled: bool,
+    pub interval_minutes: i64,
+    pub mode: RoundingMode,
+    pub direction: RoundingDirection,
+}
+
+impl Default for RoundingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: DEFAULT_INTERVAL_MINUTES,
+            mode: RoundingMode::default(),
+            direction: RoundingDirection::default(),
+        }
+    }
+}
+
+/// JS truthiness, so `enabled` normalizes exactly like `!!parsed.enabled`.
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|v| v != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(_) | Value::Object(_) => true,
+    }
+}
+
+/// Mirrors `normalizeRoundingSettings`: unknown interval/mode/direction fall
+/// back to the defaults, anything that is not an object means "disabled".
+pub(crate) fn normalize_rounding_settings(parsed: &Value) -> RoundingSettings {
+    let defaults = RoundingSettings::default();
+    let Some(obj) = parsed.as_object() else {
+        return defaults;
+    };
+    let interval_minutes = obj
+        .get("intervalMinutes")
+        .and_then(Value::as_f64)
+        .and_then(|v| {
+            ROUNDING_INTERVALS
+                .iter()
+                .copied()
+                .find(|allowed| *allowed as f64 == v)
+        })
+        .unwrap_or(defaults.interval_minutes);
+    let mode = obj
+        .get("mode")
+        .and_then(Value::as_str)
+        .and_then(RoundingMode::parse)
+        .unwrap_or(defaults.mode);
+    let direction = obj
+        .get("direction")
+        .and_then(Value::as_str)
+        .and_then(RoundingDirection::parse)
+        .unwrap_or(defaults.direction);
+    RoundingSettings {
+        enabled: obj.get("enabled").is_some_and(is_truthy),
+        interval_minutes,
+        mode,
+        direction,
+    }
+}
+
+/// The user's rounding settings from the shared settings file (disabled
+/// defaults when the file or key is missing).
+pub(crate) fn load_rounding_settings() -> RoundingSettings {
+    read_user_setting(ROUNDING_SETTINGS_KEY)
+        .map(|value| normalize_rounding_settings(&value))
+        .unwrap_or_default()
+}
+
+/// Rounds one value to a multiple of the interval. Zero, negative and
+/// non-finite input → 0 (empty time is never padded).
+pub(crate) fn round_seconds(
+    seconds: f64,
+    interval_minutes: i64,
+    direction: RoundingDirection,
+) -> i64 {
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return 0;
+    }
+    let step = interval_minutes.max(1) * 60;
+    let steps = seconds / step as f64;
+    let steps = match direction {
+        RoundingDirection::Up => steps.ceil(),
+        RoundingDirection::Nearest => (steps + 0.5).floor(),
+        RoundingDirection::Down => steps.floor(),
+    };
+    steps as i64 * step
+}
+
+/// `per_day` forces a full hour; the other modes use the configured interval.
+pub(crate) fn effective_interval_minutes(settings: &RoundingSettings) -> i64 {
+    match settings.mode {
+        RoundingMode::Day => FULL_HOUR_MINUTES,
+        _ => settings.interval_minutes,
+    }
+}
+
+fn raw_sum(seconds: &[i64]) -> i64 {
+    seconds.iter().filter(|s| **s > 0).sum()
+}
+
+/// Sum of session durations under the chosen variant (raw sum when disabled).
+/// Without a day breakdown `per_day` rounds the whole sum to a full hour.
+pub(crate) fn round_durations(per_session_seconds: &[i64], settings: &RoundingSettings) -> i64 {
+    if !settings.enabled {
+        return raw_sum(per_session_seconds);
+    }
+    if settings.mode == RoundingMode::Session {
+        return per_session_seconds
+            .iter()
+            .map(|s| round_seconds(*s as f64, settings.interval_minutes, settings.direction))
+            .sum();
+    }
+    round_seconds(
+        raw_sum(per_session_seconds) as f64,
+        effective_interval_minutes(settings),
+        settings.direction,
+    )
+}
+
+/// Sum of daily totals; `per_day` rounds every day to a full hour, the other
+/// modes treat the day as the unit of `round_durations`.
+pub(crate) fn round_daily_totals(per_day_seconds: &[i64], settings: &RoundingSettings) -> i64 {
+    if settings.enabled && settings.mode == RoundingMode::Day {
+        return per_day_seconds
+            .iter()
+            .map(|s| round_seconds(*s as f64, FULL_HOUR_MINUTES, settings.direction))
+            .sum();
+    }
+    round_durations(per_day_seconds, settings)
+}
+
+/// Rounds an already aggregated total (unchanged when disabled).
+pub(crate) fn round_aggregate(total_seconds: i64, settings: &RoundingSettings) -> i64 {
+    if !settings.enabled {
+        return total_seconds;
+    }
+    round_seconds(
+        total_seconds as f64,
+        effective_interval_minutes(settings),
+        settings.direction,
+    )
+}
+
+/// Scales a value by the rounded/real time ratio (unchanged for zero time).
+pub(crate) fn scale_value_to_rounded(value: f64, real_seconds: i64, rounded_seconds: i64) -> f64 {
+    if real_seconds <= 0 {
+        return value;
+    }
+    value * (rounded_seconds as f64 / real_seconds as f64)
+}
+
+/// Displayed total of one project row, the rule shared by the estimates table
+/// and the project report: `per_day` with a day breakdown sums full hours per
+/// day, everything else rounds the row total once.
+pub(crate) fn round_project_total(
+    total_seconds: i64,
+    daily_seconds: &[i64],
+    settings: &RoundingSettings,
+) -> i64 {
+    if settings.enabled && settings.mode == RoundingMode::Day && !daily_seconds.is_empty() {
+        return round_daily_totals(daily_seconds, settings);
+    }
+    round_aggregate(total_seconds, settings)
+}
+
+/// Total of individual sessions `(date YYYY-MM-DD, seconds)` where the real
+/// breakdown is known (exports): `per_session` rounds each session, `per_day`
+/// groups them by date first, `per_total` rounds the sum once.
+pub(crate) fn round_dated_sessions(sessions: &[(String, i64)], settings: &RoundingSettings) -> i64 {
+    if settings.mode != RoundingMode::Day {
+        let durations: Vec<i64> = sessions.iter().map(|(_, seconds)| *seconds).collect();
+        return round_durations(&durations, settings);
+    }
+    let mut per_day: BTreeMap<&str, i64> = BTreeMap::new();
+    for (date, seconds) in sessions {
+        *per_day.entry(date.as_str()).or_insert(0) += (*seconds).max(0);
+    }
+    round_daily_totals(&per_day.into_values().collect::<Vec<_>>(), settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn settings(enabled: bool, interval_minutes: i64, mode: RoundingMode) -> RoundingSettings {
+        RoundingSettings {
+            enabled,
+            interval_minutes,
+            mode,
+            direction: RoundingDirection::Up,
+        }
+    }
+
+    #[test]
+    fn round_seconds_matches_frontend_cases() {
+        let up = RoundingDirection::Up;
+        assert_eq!(round_seconds(0.0, 15, up), 0);
+        assert_eq!(round_seconds(-100.0, 15, up), 0);
+        assert_eq!(round_seconds(f64::NAN, 15, up), 0);
+        assert_eq!(round_seconds(1.0, 15, up), 900);
+        assert_eq!(round_seconds(60.0, 15, up), 900);
+        assert_eq!(round_seconds(901.0, 15, up), 1800);
+        assert_eq!(round_seconds(900.0, 15, up), 900);
+        assert_eq!(round_seconds(3600.0, 60, up), 3600);
+        assert_eq!(round_seconds(1.0, 6, up), 360);
+        assert_eq!(round_seconds(361.0, 6, up), 720);
+
+        assert_eq!(round_seconds(1349.0, 15, RoundingDirection::Nearest), 900);
+        assert_eq!(round_seconds(1350.0, 15, RoundingDirection::Nearest), 1800);
+        assert_eq!(round_seconds(1799.0, 15, RoundingDirection::Down), 900);
+        assert_eq!(round_seconds(899.0, 15, RoundingDirection::Down), 0);
+    }
+
+    #[test]
+    fn variants_match_frontend_cases() {
+        let sessions = [720, 720, 720];
+        assert_eq!(
+            round_durations(&sessions, &settings(false, 15, RoundingMode::Total)),
+            2160
+        );
+        assert_eq!(
+            round_durations(&sessions, &settings(true, 15, RoundingMode::Total)),
+            2700
+        );
+        assert_eq!(
+            round_durations(&sessions, &settings(true, 5, RoundingMode::Session)),
+            2700
+        );
+        assert_eq!(
+            round_durations(&[61, 61, 61], &settings(true, 15, RoundingMode::Session)),
+            2700
+        );
+        assert_eq!(
+            round_durations(&sessions, &settings(true, 15, RoundingMode::Day)),
+            3600
+        );
+
+        let days = [600, 3900, 7200];
+        assert_eq!(
+            round_daily_totals(&days, &settings(true, 5, RoundingMode::Day)),
+            5 * 3600
+        );
+        assert_eq!(
+            round_daily_totals(&days, &settings(false, 15, RoundingMode::Day)),
+            11700
+        );
+        assert_eq!(
+            round_daily_totals(&days, &settings(true, 30, RoundingMode::Total)),
+            12600
+        );
+
+        assert_eq!(
+            round_aggregate(2160, &settings(false, 15, RoundingMode::Total)),
+            2160
+        );
+        assert_eq!(
+            round_aggregate(2160, &settings(true, 15, RoundingMode::Total)),
+            2700
+        );
+        assert!((scale_value_to_rounded(100.0, 2160, 2700) - 125.0).abs() < 1e-9);
+        assert_eq!(scale_value_to_rounded(100.0, 0, 900), 100.0);
+
+        // Estimate/report row rule (estimate-report.test.ts).
+        let per_day = settings(true, 60, RoundingMode::Day);
+        assert_eq!(round_project_total(4500, &[600, 3900], &per_day), 10800);
+        let per_total = settings(true, 15, RoundingMode::Total);
+        assert_eq!(round_project_total(4000, &[4000], &per_total), 4500);
+        assert_eq!(round_project_total(5000, &[5000], &per_total), 5400);
+
+        // Exports know the real sessions: 2 × 20 min on one day, 10 min on the next.
+        let dated = [
+            ("2026-01-04".to_string(), 1200),
+            ("2026-01-04".to_string(), 1200),
+            ("2026-01-05".to_string(), 600),
+        ];
+        assert_eq!(round_dated_sessions(&dated, &per_day), 2 * 3600);
+        assert_eq!(round_dated_sessions(&dated, &per_total), 3600);
+        assert_eq!(
+            round_dated_sessions(&dated, &settings(true, 15, RoundingMode::Session)),
+            1800 + 1800 + 900
+        );
+    }
+
+    #[test]
+    fn normalize_falls_back_to_defaults_like_the_frontend() {
+        let n = normalize_rounding_settings(
+            &json!({ "enabled": true, "intervalMinutes": 7, "mode": "bogus" }),
+        );
+        assert_eq!(n.interval_minutes, DEFAULT_INTERVAL_MINUTES);
+        assert_eq!(n.mode, RoundingMode::Total);
+        assert!(n.enabled);
+
+        let n = normalize_rounding_settings(&json!({
+            "enabled": false,
+            "intervalMinutes": 30,
+            "mode": "per_session",
+            "direction": "down"
+        }));
+        assert_eq!(
+            n,
+            RoundingSettings {
+                enabled: false,
+                interval_minutes: 30,
+                mode: RoundingMode::Session,
+                direction: RoundingDirection::Down,
+            }
+        );
+        assert_eq!(
+            effective_interval_minutes(&settings(true, 15, RoundingMode::Day)),
+            FULL_HOUR_MINUTES
+        );
+        assert_eq!(
+            normalize_rounding_settings(&json!("garbage")),
+            RoundingSettings::default()
+        );
+    }
+}
@@ -0,0 +1,357 @@
+// Append-only audit trail of manual session edits (m31).
+//
+// Every command that changes a session by hand calls `record` inside its own
+// transaction, once per changed field: old and new value, the project before
+// and after, the assignment source, the device and the time. Unchanged
+// fields are skipped. Undo / redo of a journaled action is audited too, so
+// the trail always explains the current state of a session. The table
+// rejects UPDATE / DELETE; exports carry the entries of exported sessions.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::helpers::{get_machine_id, run_db_blocking, timeflow_data_dir};
+use super::types::SessionAuditEntry;
+
+pub(crate) const ENTRY_COLUMNS: &str = "l.id, l.session_id, l.project_id, l.previous_project_id, \
+     l.action, l.field, l.old_value, l.new_value, l.source, l.device_id, l.created_at";
+
+/// Session columns compared on undo / redo, with the audited field name.
+const REPLAYED_FIELDS: [(&str, &str); 6] = [
+    ("project_id", "project"),
+    ("rate_multiplier", "rate_multiplier"),
+    ("comment", "comment"),
+    ("is_hidden", "is_hidden"),
+    ("duration_seconds", "duration_seconds"),
+    ("billable", "billable"),
+];
+
+/// One field change, written by `record`.
+pub(crate) struct Change<'a> {
+    action: &'a str,
+    field: &'a str,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    from_project: Option<Option<i64>>,
+    source: Option<&'a str>,
+}
+
+impl<'a> Change<'a> {
+    pub(crate) fn new(
+        action: &'a str,
+        field: &'a str,
+        old_value: Option<String>,
+        new_value: Option<String>,
+    ) -> Self {
+        Self {
+            action,
+            field,
+            old_value,
+            new_value,
+            from_project: None,
+            source: None,
+        }
+    }
+
+    /// Project the session had before the change; defaults to its current one.
+    pub(crate) fn previous_project(mut self, project_id: Option<i64>) -> Self {
+        self.from_project = Some(project_id);
+        self
+    }
+
+    pub(crate) fn source(mut self, source: &'a str) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
+
+/// The id the daemon identifies this device with in LAN / online sync
+/// (`device_id.txt`), or the machine name before the daemon created one.
+pub(crate) fn local_device_id() -> String {
+    timeflow_data_dir()
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join("device_id.txt")).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(get_machine_id)
+}
+
+/// Appends `change` for `session_id`; a no-op when the value did not change.
+/// Call after the session row was written — its project then is the "after".
+pub(crate) fn record(conn: &Connection, session_id: i64, change: Change<'_>) -> Result<(), String> {
+    if change.old_value == change.new_value {
+        return Ok(());
+    }
+    let project_id: Option<i64> = conn
+        .query_row(
+            "SELECT project_id FROM sessions WHERE id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    let previous_project_id = change.from_project.unwrap_or(project_id);
+    conn.execute(
+        "INSERT INTO session_audit_log (
+            session_id, project_id, previous_project_id, action, field,
+            old_value, new_value, source, device_id
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        rusqlite::params![
+            session_id,
+            project_id,
+            previous_project_id,
+            change.action,
+            change.field,
+            change.old_value,
+            change.new_value,
+            change.source,
+            local_device_id(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Audited form of a project reference: its name, `#id` once deleted,
+/// `None` for unassigned.
+pub(crate) fn project_value(conn: &Connection, project_id: Option<i64>) -> Option<String> {
+    let id = project_id?;
+    let name: Option<String> = conn
+        .query_row("SELECT name FROM projects WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })
+        .optional()
+        .ok()
+        .flatten();
+    Some(name.unwrap_or_else(|| format!("#{}", id)))
+}
+
+/// Audited form of a session's time range, e.g. for a split part.
+pub(crate) fn time_range_value(start_time: &str, end_time: &str, duration_seconds: i64) -> String {
+    format!("{} – {} ({}s)", start_time, end_time, duration_seconds)
+}
+
+fn json_value(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(match n.as_i64() {
+            Some(i) => i.to_string(),
+            None => n.as_f64().map(|f| f.to_string()).unwrap_or_default(),
+        }),
+        other => Some(other.to_string()),
+    }
+}
+
+fn image_project(image: &serde_json::Map<String, Value>) -> Option<i64> {
+    image.get("project_id").and_then(Value::as_i64)
+}
+
+fn image_time_range(image: &serde_json::Map<String, Value>) -> Option<String> {
+    let text = |key: &str| image.get(key).and_then(Value::as_str).unwrap_or("");
+    let duration = image
+        .get("duration_seconds")
+        .and_then(Value::as_i64)
+        .unwrap_or(0);
+    Some(time_range_value(
+        text("start_time"),
+        text("end_time"),
+        duration,
+    ))
+}
+
+/// Audits an undo / redo that moved a session row from `current` to
+/// `target` (`None` = no row, e.g. a split part). Runs after the write.
+pub(crate) fn record_replay(
+    conn: &Connection,
+    session_id: i64,
+    action: &str,
+    current: Option<&serde_json::Map<String, Value>>,
+    target: Option<&serde_json::Map<String, Value>>,
+) -> Result<(), String> {
+    let from_project = current.and_then(image_project);
+    match (current, target) {
+        (Some(current), Some(target)) => {
+            for (column, field) in REPLAYED_FIELDS {
+                let (old_value, new_value) = if column == "project_id" {
+                    (
+                        project_value(conn, image_project(current)),
+                        project_value(conn, image_project(target)),
+                    )
+                } else {
+                    (
+                        json_value(current.get(column)),
+                        json_value(target.get(column)),
+                    )
+                };
+                record(
+                    conn,
+                    session_id,
+                    Change::new(action, field, old_value, new_value).previous_project(from_project),
+                )?;
+            }
+            Ok(())
+        }
+        (current, target) => record(
+            conn,
+            session_id,
+            Change::new(
+                action,
+                "session",
+                current.and_then(image_time_range),
+                target.and_then(image_time_range),
+            )
+            .previous_project(from_project),
+        ),
+    }
+}
+
+pub(crate) fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<SessionAuditEntry> {
+    Ok(SessionAuditEntry {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        project_id: row.get(2)?,
+        previous_project_id: row.get(3)?,
+        action: row.get(4)?,
+        field: row.get(5)?,
+        old_value: row.get(6)?,
+        new_value: row.get(7)?,
+        source: row.get(8)?,
+        device_id: row.get(9)?,
+        created_at: row.get(10)?,
+    })
+}
+
+fn query_entries(
+    conn: &Connection,
+    sql_where: &str,
+    params: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<SessionAuditEntry>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM session_audit_log l WHERE {}",
+            ENTRY_COLUMNS, sql_where
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params, row_to_entry)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Oldest first — reads as the session's history.
+pub(crate) fn query_session_audit_log(
+    conn: &Connection,
+    session_id: i64,
+) -> Result<Vec<SessionAuditEntry>, String> {
+    query_entries(conn, "l.session_id = ?1 ORDER BY l.id", &[&session_id])
+}
+
+/// Newest first; includes sessions moved away from the project.
+pub(crate) fn query_project_audit_log(
+    conn: &Connection,
+    project_id: i64,
+    limit: i64,
+) -> Result<Vec<SessionAuditEntry>, String> {
+    query_entries(
+        conn,
+        "l.project_id = ?1 OR l.previous_project_id = ?1 ORDER BY l.id DESC LIMIT ?2",
+        &[&project_id, &limit],
+    )
+}
+
+#[tauri::command]
+pub async fn get_session_audit_log(
+    app: AppHandle,
+    session_id: i64,
+) -> Result<Vec<SessionAuditEntry>, String> {
+    run_db_blocking(app, move |conn| query_session_audit_log(conn, session_id)).await
+}
+
+#[tauri::command]
+pub async fn get_project_audit_log(
+    app: AppHandle,
+    project_id: i64,
+    limit: Option<i64>,
+) -> Result<Vec<SessionAuditEntry>, String> {
+    let limit = limit.unwrap_or(500).clamp(1, 10_000);
+    run_db_blocking(app, move |conn| {
+        query_project_audit_log(conn, project_id, limit)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        conn.execute_batch(include_str!("../../resources/sql/schema.sql"))
+            .expect("schema");
+        crate::db_migrations::run_migrations(&conn).expect("migrations");
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, created_at)
+             VALUES (1, 'Alpha', datetime('now')), (2, 'Beta', datetime('now'));
+             INSERT INTO applications (id, executable_name, display_name) VALUES (1, 'code.exe', 'Code');
+             INSERT INTO sessions (id, app_id, start_time, end_time, duration_seconds, date, project_id)
+             VALUES (100, 1, '2026-03-02T09:00:00', '2026-03-02T10:00:00', 3600, '2026-03-02', 2);",
+        )
+        .expect("seed");
+        conn
+    }
+
+    #[test]
+    fn records_changes_per_session_and_project_and_stays_append_only() {
+        let conn = test_conn();
+        record(
+            &conn,
+            100,
+            Change::new(
+                "assign",
+                "project",
+                project_value(&conn, Some(1)),
+                project_value(&conn, Some(2)),
+            )
+            .previous_project(Some(1))
+            .source("manual_session_assign"),
+        )
+        .unwrap();
+        record(
+            &conn,
+            100,
+            Change::new("comment", "comment", None, Some("call".into())),
+        )
+        .unwrap();
+        // Unchanged values leave no trace.
+        record(
+            &conn,
+            100,
+            Change::new("comment", "comment", Some("x".into()), Some("x".into())),
+        )
+        .unwrap();
+
+        let history = query_session_audit_log(&conn, 100).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].old_value.as_deref(), Some("Alpha"));
+        assert_eq!(history[0].new_value.as_deref(), Some("Beta"));
+        assert_eq!(history[0].source.as_deref(), Some("manual_session_assign"));
+        assert_eq!(
+            (history[1].project_id, history[1].previous_project_id),
+            (Some(2), Some(2))
+        );
+        assert!(!history[0].device_id.is_empty());
+
+        // The old project still sees the session that left it.
+        assert_eq!(query_project_audit_log(&conn, 1, 10).unwrap().len(), 1);
+        assert_eq!(query_project_audit_log(&conn, 2, 10).unwrap().len(), 2);
+
+        assert!(conn
+            .execute("UPDATE session_audit_log SET new_value = 'x'", [])
+            .is_err());
+        assert!(conn.execute("DELETE FROM session_audit_log", []).is_err());
+    }
+}
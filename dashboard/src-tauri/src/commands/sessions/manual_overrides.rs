@@ -1,6 +1,8 @@
 use rusqlite::OptionalExtension;
 use std::collections::HashMap;
 
+use super::super::session_audit::{self, Change};
+
 type ManualOverrideRow = (Option<i64>, String, String, String, Option<String>);
 
 pub(crate) fn upsert_manual_session_override(
@@ -40,6 +42,19 @@ pub(crate) fn upsert_manual_session_override(
         None => None,
     };
 
+    let previous_override: Option<String> = conn
+        .query_row(
+            "SELECT project_name FROM session_manual_overrides
+             WHERE lower(executable_name) = lower(?1)
+               AND start_time = ?2
+               AND end_time = ?3",
+            rusqlite::params![executable_name, start_time, end_time],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+
     let updated_legacy = conn
         .execute(
             "UPDATE session_manual_overrides
@@ -75,7 +90,11 @@ pub(crate) fn upsert_manual_session_override(
         .map_err(|e| e.to_string())?;
     }
 
-    Ok(())
+    session_audit::record(
+        conn,
+        session_id,
+        Change::new("override", "project_name", previous_override, project_name),
+    )
 }
 
 /// Re-applies historical manual overrides from `session_manual_overrides` onto
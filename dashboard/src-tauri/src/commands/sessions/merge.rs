@@ -0,0 +1,402 @@
+use rusqlite::OptionalExtension;
+use tauri::AppHandle;
+
+use super::super::helpers::run_db_blocking;
+use super::super::mutation_journal::{id_list_sql, Recorder};
+use super::super::session_audit::{self, Change};
+use super::super::sql_fragments::ACTIVE_SESSION_FILTER;
+use super::manual_overrides::upsert_manual_session_override;
+use super::split::{parse_iso_datetime, strip_split_markers};
+
+/// Default for `max_gap_minutes`: short interruptions, not separate blocks.
+pub(crate) const DEFAULT_MERGE_GAP_MINUTES: i64 = 5;
+
+const MERGE_FEEDBACK_SOURCE: &str = "manual_session_merge";
+
+#[derive(Clone, Debug)]
+struct MergePart {
+    id: i64,
+    app_id: i64,
+    date: String,
+    start_time: String,
+    end_time: String,
+    start_ms: i64,
+    end_ms: i64,
+    duration_seconds: i64,
+    rate_multiplier: f64,
+    project_id: Option<i64>,
+    comment: Option<String>,
+}
+
+/// What the surviving session becomes.
+#[derive(Clone, Debug, PartialEq)]
+struct MergedValues {
+    duration_seconds: i64,
+    project_id: Option<i64>,
+    rate_multiplier: f64,
+    comment: Option<String>,
+}
+
+fn load_merge_part(conn: &rusqlite::Connection, session_id: i64) -> Result<MergePart, String> {
+    let sql = format!(
+        "SELECT id, app_id, date, start_time, end_time, duration_seconds,
+                COALESCE(rate_multiplier, 1.0), project_id, comment
+         FROM sessions WHERE id = ?1 AND {ACTIVE_SESSION_FILTER}"
+    );
+    let part = conn
+        .query_row(&sql, [session_id], |row| {
+            Ok(MergePart {
+                id: row.get(0)?,
+                app_id: row.get(1)?,
+                date: row.get(2)?,
+                start_time: row.get(3)?,
+                end_time: row.get(4)?,
+                start_ms: 0,
+                end_ms: 0,
+                duration_seconds: row.get(5)?,
+                rate_multiplier: row.get(6)?,
+                project_id: row.get(7)?,
+                comment: row.get(8)?,
+            })
+        })
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Session {} not found", session_id))?;
+    let start_ms = parse_iso_datetime(&part.start_time)
+        .map_err(|e| format!("Invalid start time: {}", e))?
+        .timestamp_millis();
+    let end_ms = parse_iso_datetime(&part.end_time)
+        .map_err(|e| format!("Invalid end time: {}", e))?
+        .timestamp_millis();
+    Ok(MergePart {
+        start_ms,
+        end_ms,
+        ..part
+    })
+}
+
+/// Parts in chronological order — one app, one day, no overlaps and no gap
+/// longer than `max_gap_minutes`.
+fn validate_merge_parts(
+    mut parts: Vec<MergePart>,
+    max_gap_minutes: i64,
+) -> Result<Vec<MergePart>, String> {
+    if parts.len() < 2 {
+        return Err("Select at least two sessions to merge".to_string());
+    }
+    if max_gap_minutes < 0 {
+        return Err("Maximum gap must be >= 0 minutes".to_string());
+    }
+    let (app_id, date) = (parts[0].app_id, parts[0].date.clone());
+    if parts.iter().any(|p| p.app_id != app_id) {
+        return Err("Only sessions of the same application can be merged".to_string());
+    }
+    if parts.iter().any(|p| p.date != date) {
+        return Err("Only sessions from the same day can be merged".to_string());
+    }
+    // Chronologically, not by text — offsets may differ (see rebuild).
+    parts.sort_by_key(|p| (p.start_ms, p.id));
+    let max_gap_ms = max_gap_minutes * 60 * 1000;
+    for pair in parts.windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+        if next.start_ms < prev.end_ms {
+            return Err(format!(
+                "Sessions {} and {} overlap and cannot be merged",
+                prev.id, next.id
+            ));
+        }
+        if next.start_ms - prev.end_ms > max_gap_ms {
+            return Err(format!(
+                "Gap between sessions {} and {} exceeds {} min",
+                prev.id, next.id, max_gap_minutes
+            ));
+        }
+    }
+    Ok(parts)
+}
+
+/// Merge policy:
+/// - duration: sum of the parts — gaps between them stay untracked;
+/// - project: the one holding most of the duration (ties: the earliest part);
+/// - rate multiplier: duration-weighted average, so the value of the time
+///   does not change;
+/// - comment: the parts' comments without "Split N/M" markers, de-duplicated,
+///   joined with " | ".
+fn merged_values(parts: &[MergePart]) -> MergedValues {
+    let duration_seconds: i64 = parts.iter().map(|p| p.duration_seconds.max(0)).sum();
+
+    let mut by_project: Vec<(Option<i64>, i64)> = Vec::new();
+    for part in parts {
+        match by_project.iter_mut().find(|(id, _)| *id == part.project_id) {
+            Some((_, total)) => *total += part.duration_seconds.max(0),
+            None => by_project.push((part.project_id, part.duration_seconds.max(0))),
+        }
+    }
+    let mut project = by_project[0];
+    for candidate in &by_project[1..] {
+        if candidate.1 > project.1 {
+            project = *candidate;
+        }
+    }
+
+    let rate_multiplier = if duration_seconds > 0 {
+        parts
+            .iter()
+            .map(|p| p.rate_multiplier * p.duration_seconds.max(0) as f64)
+            .sum::<f64>()
+            / duration_seconds as f64
+    } else {
+        parts.iter().map(|p| p.rate_multiplier).sum::<f64>() / parts.len() as f64
+    };
+    let rate_multiplier = if (rate_multiplier - 1.0).abs() < 0.000_001 {
+        1.0
+    } else {
+        rate_multiplier
+    };
+
+    let mut comments: Vec<String> = Vec::new();
+    for comment in parts.iter().filter_map(|p| p.comment.as_deref()) {
+        let cleaned = strip_split_markers(comment);
+        if !cleaned.is_empty() && !comments.contains(&cleaned) {
+            comments.push(cleaned);
+        }
+    }
+
+    MergedValues {
+        duration_seconds,
+        project_id: project.0,
+        rate_multiplier,
+        comment: if comments.is_empty() {
+            None
+        } else {
+            Some(comments.join(" | "))
+        },
+    }
+}
+
+/// Joins `session_ids` into the earliest of them and hides the rest (like
+/// `rebuild_sessions`). Returns the id of the surviving session.
+pub(crate) fn execute_session_merge(
+    conn: &mut rusqlite::Connection,
+    session_ids: &[i64],
+    max_gap_minutes: i64,
+) -> Result<i64, String> {
+    let mut ids: Vec<i64> = Vec::with_capacity(session_ids.len());
+    for &id in session_ids {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    let parts = ids
+        .iter()
+        .map(|&id| load_merge_part(conn, id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let parts = validate_merge_parts(parts, max_gap_minutes)?;
+    let merged = merged_values(&parts);
+    let (first, last) = (&parts[0], &parts[parts.len() - 1]);
+    let absorbed: Vec<i64> = parts[1..].iter().map(|p| p.id).collect();
+    let projects_differ = parts.iter().any(|p| p.project_id != merged.project_id);
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new("merge_sessions", format!("{} sessions → 1", parts.len()));
+    journal.track(&tx, "sessions", &id_list_sql(&ids), vec![])?;
+    journal.track(
+        &tx,
+        "file_activities",
+        "app_id = ?1 AND date = ?2",
+        vec![first.app_id.into(), first.date.clone().into()],
+    )?;
+    journal.track(
+        &tx,
+        "session_manual_overrides",
+        "lower(executable_name) = (SELECT lower(executable_name) FROM applications WHERE id = ?1)
+         AND substr(start_time, 1, 10) = ?2",
+        vec![first.app_id.into(), first.date.clone().into()],
+    )?;
+
+    tx.execute(
+        "UPDATE sessions
+         SET end_time = ?1,
+             duration_seconds = ?2,
+             project_id = ?3,
+             rate_multiplier = ?4,
+             comment = ?5
+         WHERE id = ?6",
+        rusqlite::params![
+            last.end_time,
+            merged.duration_seconds,
+            merged.project_id,
+            merged.rate_multiplier,
+            merged.comment,
+            first.id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute(
+        &format!(
+            "UPDATE sessions SET is_hidden = 1 WHERE {}",
+            id_list_sql(&absorbed)
+        ),
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    // Once every part of a split is back, the session can be split again.
+    tx.execute(
+        "UPDATE sessions SET split_source_session_id = NULL
+         WHERE id = ?1
+           AND split_source_session_id = ?1
+           AND NOT EXISTS (
+               SELECT 1 FROM sessions o
+               WHERE o.split_source_session_id = ?1
+                 AND o.id <> ?1
+                 AND (o.is_hidden IS NULL OR o.is_hidden = 0)
+           )",
+        [first.id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Overrides are keyed by (executable, start, end): drop the parts' keys,
+    // then pin the merged span.
+    for part in &parts {
+        tx.execute(
+            "DELETE FROM session_manual_overrides
+             WHERE session_id = ?1
+                OR (lower(executable_name) = (SELECT lower(executable_name) FROM applications WHERE id = ?2)
+                    AND start_time = ?3
+                    AND end_time = ?4)",
+            rusqlite::params![part.id, part.app_id, part.start_time, part.end_time],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    upsert_manual_session_override(&tx, first.id, merged.project_id).map_err(|e| {
+        format!(
+            "Failed to persist manual override for session {}: {}",
+            first.id, e
+        )
+    })?;
+
+    if projects_differ {
+        tx.execute(
+            "UPDATE file_activities
+             SET project_id = ?1
+             WHERE app_id = ?2
+               AND date = ?3
+               AND last_seen > ?4
+               AND first_seen < ?5",
+            rusqlite::params![
+                merged.project_id,
+                first.app_id,
+                first.date,
+                first.start_time,
+                last.end_time
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        let moved: Vec<&MergePart> = parts
+            .iter()
+            .filter(|p| p.project_id != merged.project_id)
+            .collect();
+        for part in &moved {
+            let weight = if merged.duration_seconds > 0 {
+                part.duration_seconds.max(0) as f64 / merged.duration_seconds as f64
+            } else {
+                1.0
+            };
+            tx.execute(
+                "INSERT INTO assignment_feedback (
+                    session_id, app_id, from_project_id, to_project_id, source, weight, created_at
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+                rusqlite::params![
+                    part.id,
+                    part.app_id,
+                    part.project_id,
+                    merged.project_id,
+                    MERGE_FEEDBACK_SOURCE,
+                    weight,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.execute(
+            "INSERT INTO assignment_model_state (key, value, updated_at)
+             VALUES ('feedback_since_train', ?1, datetime('now'))
+             ON CONFLICT(key) DO UPDATE SET
+               value = CAST(COALESCE(NULLIF(assignment_model_state.value, ''), '0') AS INTEGER) + ?1,
+               updated_at = datetime('now')",
+            [moved.len() as i64],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let audit = |field, old_value, new_value| {
+        Change::new("merge", field, old_value, new_value)
+            .previous_project(first.project_id)
+            .source(MERGE_FEEDBACK_SOURCE)
+    };
+    session_audit::record(
+        &tx,
+        first.id,
+        audit(
+            "time_range",
+            Some(session_audit::time_range_value(
+                &first.start_time,
+                &first.end_time,
+                first.duration_seconds,
+            )),
+            Some(session_audit::time_range_value(
+                &first.start_time,
+                &last.end_time,
+                merged.duration_seconds,
+            )),
+        ),
+    )?;
+    session_audit::record(
+        &tx,
+        first.id,
+        audit(
+            "project",
+            session_audit::project_value(&tx, first.project_id),
+            session_audit::project_value(&tx, merged.project_id),
+        ),
+    )?;
+    session_audit::record(
+        &tx,
+        first.id,
+        audit(
+            "rate_multiplier",
+            Some(first.rate_multiplier.to_string()),
+            Some(merged.rate_multiplier.to_string()),
+        ),
+    )?;
+    session_audit::record(
+        &tx,
+        first.id,
+        audit("comment", first.comment.clone(), merged.comment.clone()),
+    )?;
+    for part in &parts[1..] {
+        session_audit::record(
+            &tx,
+            part.id,
+            Change::new("merge", "merged_into", None, Some(format!("#{}", first.id)))
+                .previous_project(part.project_id)
+                .source(MERGE_FEEDBACK_SOURCE),
+        )?;
+    }
+
+    journal.finish(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(first.id)
+}
+
+pub async fn merge_sessions(
+    app: AppHandle,
+    session_ids: Vec<i64>,
+    max_gap_minutes: Option<i64>,
+) -> Result<i64, String> {
+    let max_gap_minutes = max_gap_minutes.unwrap_or(DEFAULT_MERGE_GAP_MINUTES);
+    run_db_blocking(app, move |conn| {
+        execute_session_merge(conn, &session_ids, max_gap_minutes)
+    })
+    .await
+}
@@ -5,6 +5,7 @@ use super::types::{
 };
 
 mod manual_overrides;
+mod merge;
 mod mutations;
 mod query;
 pub(crate) mod rebuild;
@@ -13,6 +14,7 @@ mod split;
 mod tests;
 
 pub(crate) use manual_overrides::apply_manual_session_overrides;
+pub(crate) use mutations::{assign_session_to_project_tx, track_session_assignment};
 
 #[tauri::command]
 pub async fn get_sessions(
@@ -93,6 +95,15 @@ pub async fn update_session_comments(
     mutations::update_session_comments(app, session_ids, comment).await
 }
 
+#[tauri::command]
+pub async fn update_sessions_billable(
+    app: AppHandle,
+    session_ids: Vec<i64>,
+    billable: Option<bool>,
+) -> Result<(), String> {
+    mutations::update_sessions_billable(app, session_ids, billable).await
+}
+
 #[tauri::command]
 pub async fn rebuild_sessions(app: AppHandle, gap_fill_minutes: i64) -> Result<i64, String> {
     rebuild::rebuild_sessions(app, gap_fill_minutes).await
@@ -138,3 +149,14 @@ pub async fn split_session_multi(
 ) -> Result<(), String> {
     split::split_session_multi(app, session_id, splits, not_modified_since).await
 }
+
+/// Joins adjacent sessions of one app into the earliest of them (the inverse
+/// of `split_session_multi`). Returns the id of the merged session.
+#[tauri::command]
+pub async fn merge_sessions(
+    app: AppHandle,
+    session_ids: Vec<i64>,
+    max_gap_minutes: Option<i64>,
+) -> Result<i64, String> {
+    merge::merge_sessions(app, session_ids, max_gap_minutes).await
+}
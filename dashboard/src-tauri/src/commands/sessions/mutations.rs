@@ -4,6 +4,8 @@ use rusqlite::{OptionalExtension, Transaction};
 use tauri::AppHandle;
 
 use super::super::helpers::run_db_blocking;
+use super::super::mutation_journal::{id_list_sql, Recorder};
+use super::super::session_audit::{self, Change};
 use super::manual_overrides::upsert_manual_session_override;
 
 fn sanitize_session_ids(session_ids: Vec<i64>) -> Vec<i64> {
@@ -47,7 +49,38 @@ fn normalize_comment(comment: Option<String>) -> Option<String> {
     })
 }
 
-fn assign_session_to_project_tx(
+/// Everything `assign_session_to_project_tx` writes besides the feedback log:
+/// the session, its overlapping file activities and its manual override.
+pub(crate) fn track_session_assignment(
+    journal: &mut Recorder,
+    tx: &Transaction<'_>,
+    session_id: i64,
+) -> Result<(), String> {
+    journal.track(tx, "sessions", "id = ?1", vec![session_id.into()])?;
+    journal.track(
+        tx,
+        "file_activities",
+        "EXISTS (SELECT 1 FROM sessions s
+                 WHERE s.id = ?1
+                   AND s.app_id = file_activities.app_id
+                   AND s.date = file_activities.date
+                   AND file_activities.last_seen > s.start_time
+                   AND file_activities.first_seen < s.end_time)",
+        vec![session_id.into()],
+    )?;
+    journal.track(
+        tx,
+        "session_manual_overrides",
+        "EXISTS (SELECT 1 FROM sessions s JOIN applications a ON a.id = s.app_id
+                 WHERE s.id = ?1
+                   AND lower(a.executable_name) = lower(session_manual_overrides.executable_name)
+                   AND s.start_time = session_manual_overrides.start_time
+                   AND s.end_time = session_manual_overrides.end_time)",
+        vec![session_id.into()],
+    )
+}
+
+pub(crate) fn assign_session_to_project_tx(
     tx: &Transaction<'_>,
     session_id: i64,
     project_id: Option<i64>,
@@ -97,6 +130,20 @@ fn assign_session_to_project_tx(
         log::debug!("Assignment updated the session row without overlapping file activity.");
     }
 
+    let source = source.unwrap_or("manual_session_assign");
+    session_audit::record(
+        tx,
+        session_id,
+        Change::new(
+            "assign",
+            "project",
+            session_audit::project_value(tx, old_project_id),
+            session_audit::project_value(tx, project_id),
+        )
+        .previous_project(old_project_id)
+        .source(source),
+    )?;
+
     upsert_manual_session_override(tx, session_id, project_id).map_err(|e| {
         format!(
             "Failed to persist manual override for session {}: {}",
@@ -112,7 +159,7 @@ fn assign_session_to_project_tx(
             app_id,
             old_project_id,
             project_id,
-            source.unwrap_or("manual_session_assign")
+            source
         ],
     )
     .map_err(|e| e.to_string())?;
@@ -135,16 +182,16 @@ fn update_session_rate_multiplier_tx(
     session_id: i64,
     multiplier: f64,
 ) -> Result<(), String> {
-    let comment_for_session: Option<Option<String>> = tx
+    let session: Option<(Option<String>, Option<f64>)> = tx
         .query_row(
-            "SELECT comment FROM sessions WHERE id = ?1",
+            "SELECT comment, rate_multiplier FROM sessions WHERE id = ?1",
             [session_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
         .optional()
         .map_err(|e| e.to_string())?;
 
-    let Some(existing_comment) = comment_for_session else {
+    let Some((existing_comment, old_multiplier)) = session else {
         return Err("Session not found".to_string());
     };
 
@@ -167,15 +214,44 @@ fn update_session_rate_multiplier_tx(
     if updated == 0 {
         return Err("Session not found".to_string());
     }
-    Ok(())
+    session_audit::record(
+        tx,
+        session_id,
+        Change::new(
+            "rate_multiplier",
+            "rate_multiplier",
+            Some(old_multiplier.unwrap_or(1.0).to_string()),
+            Some(multiplier.to_string()),
+        ),
+    )
 }
 
 fn delete_session_tx(tx: &Transaction<'_>, session_id: i64) -> Result<(), String> {
+    let was_hidden: Option<Option<i64>> = tx
+        .query_row(
+            "SELECT is_hidden FROM sessions WHERE id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
     tx.execute(
         "UPDATE sessions SET is_hidden = 1 WHERE id = ?1",
         [session_id],
     )
     .map_err(|e| e.to_string())?;
+    if let Some(was_hidden) = was_hidden {
+        session_audit::record(
+            tx,
+            session_id,
+            Change::new(
+                "delete",
+                "is_hidden",
+                Some(was_hidden.unwrap_or(0).to_string()),
+                Some("1".to_string()),
+            ),
+        )?;
+    }
     Ok(())
 }
 
@@ -184,6 +260,15 @@ fn update_session_comment_tx(
     session_id: i64,
     comment: &Option<String>,
 ) -> Result<(), String> {
+    let old_comment: Option<String> = tx
+        .query_row(
+            "SELECT comment FROM sessions WHERE id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
     let updated = tx
         .execute(
             "UPDATE sessions SET comment = ?1 WHERE id = ?2",
@@ -193,7 +278,68 @@ fn update_session_comment_tx(
     if updated == 0 {
         return Err("Session not found".to_string());
     }
-    Ok(())
+    session_audit::record(
+        tx,
+        session_id,
+        Change::new("comment", "comment", old_comment, comment.clone()),
+    )
+}
+
+fn billable_value(billable: Option<i64>) -> Option<String> {
+    billable.map(|b| (b != 0).to_string())
+}
+
+/// `None` clears the override, so the session inherits the project default.
+fn update_session_billable_tx(
+    tx: &Transaction<'_>,
+    session_id: i64,
+    billable: Option<bool>,
+) -> Result<(), String> {
+    let old_billable: Option<i64> = tx
+        .query_row(
+            "SELECT billable FROM sessions WHERE id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    let new_billable = billable.map(i64::from);
+    // The updated_at trigger only watches assignment columns; bump it here so
+    // sync picks the change up.
+    let updated = tx
+        .execute(
+            "UPDATE sessions SET billable = ?1, updated_at = datetime('now') WHERE id = ?2",
+            rusqlite::params![new_billable, session_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err("Session not found".to_string());
+    }
+    session_audit::record(
+        tx,
+        session_id,
+        Change::new(
+            "billable",
+            "billable",
+            billable_value(old_billable),
+            billable_value(new_billable),
+        ),
+    )
+}
+
+/// Journal label for an assignment target.
+fn project_label(tx: &Transaction<'_>, project_id: Option<i64>) -> String {
+    let Some(id) = project_id else {
+        return "unassigned".to_string();
+    };
+    tx.query_row("SELECT name FROM projects WHERE id = ?1", [id], |row| {
+        row.get::<_, String>(0)
+    })
+    .optional()
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| format!("project #{}", id))
 }
 
 pub async fn assign_session_to_project(
@@ -204,7 +350,13 @@ pub async fn assign_session_to_project(
 ) -> Result<(), String> {
     run_db_blocking(app, move |conn| {
         let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut journal = Recorder::new(
+            "assign_session_to_project",
+            format!("1 session → {}", project_label(&tx, project_id)),
+        );
+        track_session_assignment(&mut journal, &tx, session_id)?;
         assign_session_to_project_tx(&tx, session_id, project_id, source.as_deref())?;
+        journal.finish(&tx)?;
         tx.commit().map_err(|e| e.to_string())?;
         Ok(())
     })
@@ -224,9 +376,21 @@ pub async fn assign_sessions_to_project(
 
     run_db_blocking(app, move |conn| {
         let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut journal = Recorder::new(
+            "assign_sessions_to_project",
+            format!(
+                "{} sessions → {}",
+                session_ids.len(),
+                project_label(&tx, project_id)
+            ),
+        );
+        for &session_id in &session_ids {
+            track_session_assignment(&mut journal, &tx, session_id)?;
+        }
         for session_id in session_ids {
             assign_session_to_project_tx(&tx, session_id, project_id, source.as_deref())?;
         }
+        journal.finish(&tx)?;
         tx.commit().map_err(|e| e.to_string())?;
         Ok(())
     })
@@ -242,7 +406,13 @@ pub async fn update_session_rate_multiplier(
 
     run_db_blocking(app, move |conn| {
         let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut journal = Recorder::new(
+            "update_session_rate_multiplier",
+            format!("1 session × {}", normalized),
+        );
+        journal.track(&tx, "sessions", "id = ?1", vec![session_id.into()])?;
         update_session_rate_multiplier_tx(&tx, session_id, normalized)?;
+        journal.finish(&tx)?;
         tx.commit().map_err(|e| e.to_string())?;
         Ok(())
     })
@@ -263,9 +433,15 @@ pub async fn update_session_rate_multipliers(
 
     run_db_blocking(app, move |conn| {
         let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut journal = Recorder::new(
+            "update_session_rate_multipliers",
+            format!("{} sessions × {}", session_ids.len(), normalized),
+        );
+        journal.track(&tx, "sessions", &id_list_sql(&session_ids), vec![])?;
         for session_id in session_ids {
             update_session_rate_multiplier_tx(&tx, session_id, normalized)?;
         }
+        journal.finish(&tx)?;
         tx.commit().map_err(|e| e.to_string())?;
         Ok(())
     })
@@ -275,7 +451,10 @@ pub async fn update_session_rate_multipliers(
 pub async fn delete_session(app: AppHandle, session_id: i64) -> Result<(), String> {
     run_db_blocking(app, move |conn| {
         let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut journal = Recorder::new("delete_session", "1 session");
+        journal.track(&tx, "sessions", "id = ?1", vec![session_id.into()])?;
         delete_session_tx(&tx, session_id)?;
+        journal.finish(&tx)?;
         tx.commit().map_err(|e| e.to_string())?;
         Ok(())
     })
@@ -290,9 +469,13 @@ pub async fn delete_sessions(app: AppHandle, session_ids: Vec<i64>) -> Result<()
 
     run_db_blocking(app, move |conn| {
         let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut journal =
+            Recorder::new("delete_sessions", format!("{} sessions", session_ids.len()));
+        journal.track(&tx, "sessions", &id_list_sql(&session_ids), vec![])?;
         for session_id in session_ids {
             delete_session_tx(&tx, session_id)?;
         }
+        journal.finish(&tx)?;
         tx.commit().map_err(|e| e.to_string())?;
         Ok(())
     })
@@ -335,3 +518,35 @@ pub async fn update_session_comments(
     })
     .await
 }
+
+pub async fn update_sessions_billable(
+    app: AppHandle,
+    session_ids: Vec<i64>,
+    billable: Option<bool>,
+) -> Result<(), String> {
+    let session_ids = sanitize_session_ids(session_ids);
+    if session_ids.is_empty() {
+        return Ok(());
+    }
+
+    run_db_blocking(app, move |conn| {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let label = match billable {
+            Some(true) => "billable",
+            Some(false) => "non-billable",
+            None => "project default",
+        };
+        let mut journal = Recorder::new(
+            "update_sessions_billable",
+            format!("{} sessions → {}", session_ids.len(), label),
+        );
+        journal.track(&tx, "sessions", &id_list_sql(&session_ids), vec![])?;
+        for session_id in session_ids {
+            update_session_billable_tx(&tx, session_id, billable)?;
+        }
+        journal.finish(&tx)?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(())
+    })
+    .await
+}
@@ -7,6 +7,7 @@ use super::super::sql_fragments::{
     ensure_session_project_cache, ensure_session_project_cache_all, ACTIVE_SESSION_FILTER_S,
     SESSION_PROJECT_CTE_ALL_TIME,
 };
+use super::super::tags::{parse_tag_names, tag_filter_sql, tags_json_sql, TagTarget};
 use super::super::types::{FileActivity, SessionFilters, SessionWithApp};
 
 #[derive(Clone)]
@@ -133,6 +134,9 @@ fn apply_session_filters(
             sql.push_str(" AND s.project_id IS NOT NULL");
         }
     }
+    if let Some(ref tag_ids) = filters.tag_ids {
+        sql.push_str(&tag_filter_sql(TagTarget::Session, "s", tag_ids));
+    }
 }
 
 fn apply_limit_offset(
@@ -173,6 +177,7 @@ pub async fn get_sessions(
                 ensure_session_project_cache_all(conn)?;
             }
         }
+        let tags_json = tags_json_sql(TagTarget::Session, "s");
         let mut sql = if project_filter.is_some() {
             format!(
                 "{SESSION_PROJECT_CTE_ALL_TIME}
@@ -184,7 +189,9 @@ pub async fn get_sessions(
                     s.split_source_session_id,
                     asug_latest.suggested_confidence,
                     asug_latest.suggested_project_id,
-                    p_sug.name
+                    p_sug.name,
+                    {tags_json},
+                    s.billable
              FROM sessions s
              JOIN applications a ON a.id = s.app_id
              LEFT JOIN session_projects sp_filter ON sp_filter.id = s.id
@@ -212,7 +219,9 @@ pub async fn get_sessions(
                     s.split_source_session_id,
                     asug_latest.suggested_confidence,
                     asug_latest.suggested_project_id,
-                    p_sug.name
+                    p_sug.name,
+                    {tags_json},
+                    s.billable
              FROM sessions s
              JOIN applications a ON a.id = s.app_id
              LEFT JOIN projects p ON p.id = s.project_id
@@ -261,6 +270,8 @@ pub async fn get_sessions(
                 let hist_confidence: Option<f64> = row.get(14).unwrap_or(None);
                 let hist_suggested_pid: Option<i64> = row.get(15).unwrap_or(None);
                 let hist_suggested_pname: Option<String> = row.get(16).unwrap_or(None);
+                let tags = parse_tag_names(row.get(17)?);
+                let billable: Option<i64> = row.get(18)?;
                 Ok((
                     SessionWithApp {
                         id,
@@ -281,6 +292,8 @@ pub async fn get_sessions(
                         suggested_confidence: hist_confidence,
                         ai_assigned: ai_assigned_flag != 0,
                         comment,
+                        tags,
+                        billable: billable.map(|b| b != 0),
                     },
                     explicit_pid,
                 ))
@@ -7,6 +7,8 @@ use tauri::AppHandle;
 use super::super::assignment_model;
 use super::super::datetime::parse_datetime_fixed;
 use super::super::helpers::run_db_blocking;
+use super::super::mutation_journal::Recorder;
+use super::super::session_audit::{self, Change};
 use super::super::sql_fragments::ACTIVE_SESSION_FILTER;
 use super::super::types::{
     MultiProjectAnalysis, ProjectCandidate, SessionSplittableFlag, SplitPart,
@@ -247,7 +249,7 @@ static SPLIT_MARKER_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(?ix)(?:^|\s*\|\s*|\s+)\(?split\s+\d+/\d+\)?").expect("valid split marker regex")
 });
 
-fn strip_split_markers(input: &str) -> String {
+pub(super) fn strip_split_markers(input: &str) -> String {
     SPLIT_MARKER_RE
         .replace_all(input, " ")
         .split_whitespace()
@@ -271,6 +273,26 @@ pub(crate) fn execute_session_split(
 
     let tx = conn.transaction().map_err(|e| e.to_string())?;
     let n = splits.len();
+    let mut journal = Recorder::new("split_session", format!("1 session → {} parts", n));
+    journal.track(
+        &tx,
+        "sessions",
+        "id = ?1 OR split_source_session_id = ?1",
+        vec![session_id.into()],
+    )?;
+    journal.track(
+        &tx,
+        "file_activities",
+        "app_id = ?1 AND date = ?2",
+        vec![source.app_id.into(), source.date_str.clone().into()],
+    )?;
+    journal.track(
+        &tx,
+        "session_manual_overrides",
+        "lower(executable_name) = (SELECT lower(executable_name) FROM applications WHERE id = ?1)
+         AND substr(start_time, 1, 10) = ?2",
+        vec![source.app_id.into(), source.date_str.clone().into()],
+    )?;
     let mut cursor_ms: i64 = 0;
     let mut cursor_secs: i64 = 0;
     let mut split_segments: Vec<SplitSegmentMutation> = Vec::with_capacity(n);
@@ -309,6 +331,7 @@ pub(crate) fn execute_session_split(
             None => split_marker,
         };
         let feedback_source = format!("manual_session_split_part_{}", i + 1);
+        let part_range = session_audit::time_range_value(&part_start_str, &part_end_str, part_secs);
 
         if i == 0 {
             tx.execute(
@@ -366,6 +389,25 @@ pub(crate) fn execute_session_split(
             });
         }
 
+        if let Some(segment) = split_segments.last() {
+            session_audit::record(
+                &tx,
+                segment.session_id,
+                Change::new(
+                    "split",
+                    "time_range",
+                    Some(session_audit::time_range_value(
+                        &source.start_time,
+                        &source.end_time,
+                        source.duration_seconds,
+                    )),
+                    Some(part_range),
+                )
+                .previous_project(source.orig_project_id)
+                .source(&segment.feedback_source),
+            )?;
+        }
+
         cursor_ms += part_ms;
         cursor_secs += part_secs;
     }
@@ -378,6 +420,7 @@ pub(crate) fn execute_session_split(
         split_segments.as_slice(),
     )?;
 
+    journal.finish(&tx)?;
     tx.commit().map_err(|e| e.to_string())?;
     Ok(())
 }
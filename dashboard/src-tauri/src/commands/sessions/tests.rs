@@ -3,6 +3,7 @@ use crate::commands::sql_fragments::{
 };
 use crate::commands::types::SplitPart;
 
+use super::merge::execute_session_merge;
 use super::split::{
     analyze_session_projects_sync, execute_session_split, load_split_source_session,
     parse_iso_datetime,
@@ -121,6 +122,36 @@ fn setup_conn() -> rusqlite::Connection {
             CREATE TABLE session_project_cache_dirty (
                 date TEXT PRIMARY KEY,
                 updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE TABLE mutation_journal (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                action TEXT NOT NULL,
+                label TEXT NOT NULL DEFAULT '',
+                row_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                undone_at TEXT
+            );
+            CREATE TABLE mutation_journal_rows (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                journal_id INTEGER NOT NULL,
+                seq INTEGER NOT NULL,
+                table_name TEXT NOT NULL,
+                row_id INTEGER NOT NULL,
+                before_json TEXT,
+                after_json TEXT
+            );
+            CREATE TABLE session_audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                project_id INTEGER,
+                previous_project_id INTEGER,
+                action TEXT NOT NULL,
+                field TEXT NOT NULL,
+                old_value TEXT,
+                new_value TEXT,
+                source TEXT,
+                device_id TEXT NOT NULL DEFAULT '',
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
             );",
     )
     .expect("schema");
@@ -522,6 +553,184 @@ fn split_multi_preserves_total_duration_and_writes_feedback_per_part() {
     assert_eq!(activities, vec![Some(10), Some(20), Some(30)]);
 }
 
+fn visible_session_ids(conn: &rusqlite::Connection) -> Vec<i64> {
+    conn.prepare(
+        "SELECT id FROM sessions WHERE is_hidden IS NULL OR is_hidden = 0 ORDER BY start_time",
+    )
+    .expect("prepare visible sessions")
+    .query_map([], |row| row.get::<_, i64>(0))
+    .expect("query visible sessions")
+    .collect::<Result<Vec<_>, _>>()
+    .expect("collect visible sessions")
+}
+
+#[test]
+fn merge_after_split_restores_the_original_session() {
+    let mut conn = setup_conn();
+    conn.execute(
+            "INSERT INTO sessions (id, app_id, start_time, end_time, duration_seconds, date, project_id, rate_multiplier, comment, is_hidden)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL, 1.0, ?7, 0)",
+            rusqlite::params![
+                5_i64,
+                1_i64,
+                "2026-01-01T10:00:00Z",
+                "2026-01-01T12:00:00Z",
+                7200_i64,
+                "2026-01-01",
+                "Long block",
+            ],
+        )
+        .expect("insert source session");
+    conn.execute_batch(
+            "INSERT INTO file_activities (id, app_id, date, file_name, total_seconds, first_seen, last_seen, project_id) VALUES
+                (1, 1, '2026-01-01', 'a.rs', 900, '2026-01-01T10:05:00Z', '2026-01-01T10:20:00Z', NULL),
+                (2, 1, '2026-01-01', 'b.rs', 1200, '2026-01-01T10:40:00Z', '2026-01-01T11:00:00Z', NULL),
+                (3, 1, '2026-01-01', 'c.rs', 1200, '2026-01-01T11:20:00Z', '2026-01-01T11:40:00Z', NULL);",
+        )
+        .expect("insert file activities");
+
+    let source = load_split_source_session(&conn, 5_i64, true).expect("load split source");
+    let splits = vec![
+        SplitPart {
+            project_id: Some(10),
+            ratio: 0.25,
+        },
+        SplitPart {
+            project_id: Some(20),
+            ratio: 0.25,
+        },
+        SplitPart {
+            project_id: Some(30),
+            ratio: 0.5,
+        },
+    ];
+    execute_session_split(&mut conn, 5_i64, &source, splits.as_slice()).expect("run split");
+    let parts = visible_session_ids(&conn);
+    assert_eq!(parts.len(), 3);
+
+    // Any order — the merge sorts chronologically and keeps the earliest row.
+    let reversed: Vec<i64> = parts.iter().rev().copied().collect();
+    let merged_id = execute_session_merge(&mut conn, &reversed, 0).expect("run merge");
+    assert_eq!(merged_id, 5);
+    assert_eq!(visible_session_ids(&conn), vec![5]);
+
+    let (start, end, duration, project, multiplier, comment, split_source): (
+        String,
+        String,
+        i64,
+        Option<i64>,
+        f64,
+        Option<String>,
+        Option<i64>,
+    ) = conn
+        .query_row(
+            "SELECT start_time, end_time, duration_seconds, project_id, rate_multiplier, comment, split_source_session_id
+             FROM sessions WHERE id = 5",
+            [],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                ))
+            },
+        )
+        .expect("read merged session");
+    let ms = |value: &str| {
+        parse_iso_datetime(value)
+            .expect("parse datetime")
+            .timestamp_millis()
+    };
+    assert_eq!(ms(&start), ms("2026-01-01T10:00:00Z"));
+    assert_eq!(ms(&end), ms("2026-01-01T12:00:00Z"));
+    assert_eq!(duration, 7200);
+    // The largest part (Gamma, 50%) wins; split markers are gone.
+    assert_eq!(project, Some(30));
+    assert_eq!(multiplier, 1.0);
+    assert_eq!(comment.as_deref(), Some("Long block"));
+    assert_eq!(split_source, None);
+    load_split_source_session(&conn, 5_i64, true).expect("merged session can be split again");
+
+    let overrides: Vec<(Option<i64>, String, Option<String>)> = conn
+        .prepare("SELECT session_id, end_time, project_name FROM session_manual_overrides")
+        .expect("prepare overrides")
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .expect("query overrides")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("collect overrides");
+    assert_eq!(overrides.len(), 1);
+    assert_eq!(overrides[0].0, Some(5));
+    assert_eq!(ms(&overrides[0].1), ms("2026-01-01T12:00:00Z"));
+    assert_eq!(overrides[0].2.as_deref(), Some("Gamma"));
+
+    let activities: Vec<Option<i64>> = conn
+        .prepare("SELECT project_id FROM file_activities ORDER BY id ASC")
+        .expect("prepare file activity query")
+        .query_map([], |row| row.get::<_, Option<i64>>(0))
+        .expect("query file activity projects")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("collect activity projects");
+    assert_eq!(activities, vec![Some(30), Some(30), Some(30)]);
+
+    let merge_feedback: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM assignment_feedback WHERE source = 'manual_session_merge'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("read merge feedback");
+    assert_eq!(merge_feedback, 2, "parts moved from Alpha and Beta");
+}
+
+#[test]
+fn merge_weights_multipliers_and_rejects_overlaps_gaps_and_other_apps() {
+    let mut conn = setup_conn();
+    conn.execute(
+        "INSERT INTO applications (id, display_name, executable_name) VALUES (2, 'Browser', 'browser.exe')",
+        [],
+    )
+    .expect("insert second app");
+    conn.execute_batch(
+        "INSERT INTO sessions (id, app_id, start_time, end_time, duration_seconds, date, project_id, rate_multiplier, comment, is_hidden) VALUES
+            (1, 1, '2026-01-01T10:00:00Z', '2026-01-01T10:30:00Z', 1800, '2026-01-01', 10, 2.0, 'Hotfix', 0),
+            (2, 1, '2026-01-01T10:33:00Z', '2026-01-01T11:03:00Z', 1800, '2026-01-01', 10, 1.0, NULL, 0),
+            (3, 1, '2026-01-01T11:00:00Z', '2026-01-01T11:30:00Z', 1800, '2026-01-01', 10, 1.0, NULL, 0),
+            (4, 1, '2026-01-01T13:00:00Z', '2026-01-01T13:30:00Z', 1800, '2026-01-01', 10, 1.0, NULL, 0),
+            (5, 2, '2026-01-01T10:30:00Z', '2026-01-01T10:33:00Z', 180, '2026-01-01', 10, 1.0, NULL, 0);",
+    )
+    .expect("seed sessions");
+
+    assert!(execute_session_merge(&mut conn, &[1], 5).is_err());
+    assert!(execute_session_merge(&mut conn, &[1, 5], 5).is_err(), "other app");
+    assert!(execute_session_merge(&mut conn, &[2, 3], 5).is_err(), "overlap");
+    assert!(execute_session_merge(&mut conn, &[1, 2], 2).is_err(), "3 min gap > 2");
+    assert_eq!(visible_session_ids(&conn), vec![1, 5, 2, 3, 4]);
+
+    assert_eq!(execute_session_merge(&mut conn, &[2, 1], 5), Ok(1));
+    let (end, duration, multiplier, comment): (String, i64, f64, Option<String>) = conn
+        .query_row(
+            "SELECT end_time, duration_seconds, rate_multiplier, comment FROM sessions WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .expect("read merged session");
+    assert_eq!(end, "2026-01-01T11:03:00Z");
+    // The 3-minute gap stays untracked; the boosted half keeps its value.
+    assert_eq!(duration, 3600);
+    assert!((multiplier - 1.5).abs() < 1e-9);
+    assert_eq!(comment.as_deref(), Some("Hotfix"));
+    let untouched_feedback: i64 = conn
+        .query_row("SELECT COUNT(*) FROM assignment_feedback", [], |row| {
+            row.get(0)
+        })
+        .expect("read feedback");
+    assert_eq!(untouched_feedback, 0, "same project — nothing to learn");
+}
+
 mod rebuild_tests {
     use super::setup_conn;
     use super::super::rebuild::rebuild_sessions_conn;
@@ -54,6 +54,30 @@ pub const SESSION_PROJECT_CTE_ALL_TIME: &str = session_project_cte!(concat!(
     "1=1"
 ));
 
+/// Effective billable flag (0/1) of a `session_projects` row (alias `sp`):
+/// the session's own override, else its project's default (m34). Read live
+/// from `sessions`, so flipping the flag never needs a cache rebuild.
+pub const SESSION_BILLABLE_SQL: &str = "COALESCE(
+    (SELECT bs.billable FROM sessions bs WHERE bs.id = sp.id),
+    (SELECT bp.billable FROM projects bp WHERE bp.id = sp.project_id),
+    1
+)";
+
+/// Background-only flag (0/1) of a `session_projects` row (alias `sp`): 1 when
+/// every file activity of its app overlapping the session is the daemon's
+/// `(background)` marker, 0 when a foreground file overlaps or nothing is
+/// known (older data keeps counting). `first_seen`/`last_seen` span a file's
+/// whole day, so this errs on the side of foreground.
+pub const SESSION_BACKGROUND_ONLY_SQL: &str = "COALESCE(
+    (SELECT MIN(bfa.file_name = '(background)')
+     FROM file_activities bfa
+     WHERE bfa.app_id = sp.app_id
+       AND bfa.date = sp.session_date
+       AND substr(bfa.first_seen, 1, 19) < substr(sp.end_time, 1, 19)
+       AND substr(bfa.last_seen, 1, 19) > substr(sp.start_time, 1, 19)),
+    0
+)";
+
 fn mark_cache_day_missing_sql(all_time: bool) -> String {
     let where_clause = if all_time {
         format!(
@@ -0,0 +1,621 @@
+// Sync conflict review queue — structured view over `sync_merge_log`.
+// The daemon merge resolves conflicts by timestamp (LWW) and records both
+// sides in `details`; here the user can acknowledge a resolution or flip it.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::helpers::run_db_blocking;
+
+/// Tables whose conflicts can be flipped. Anything else in the log (or a
+/// tampered `details` blob) is rejected before it reaches dynamic SQL.
+const FLIPPABLE_TABLES: [&str; 7] = [
+    "projects",
+    "applications",
+    "sessions",
+    "manual_sessions",
+    "clients",
+    "estimate_settings",
+    "tags",
+];
+
+/// Columns a flip writes back — exactly the ones the sync merge takes from a
+/// peer. Identity, natural keys, `created_at` and machine-specific fields
+/// (`assigned_folder_path`) never change hands. The LWW clocks aren't listed:
+/// `updated_at` is set to now and the trigger stamps a fresh `hlc`, so the
+/// flipped values win on the next sync.
+fn flip_columns(table: &str) -> &'static [&'static str] {
+    match table {
+        "projects" => &[
+            "color",
+            "hourly_rate",
+            "excluded_at",
+            "frozen_at",
+            "merged_into",
+            "merged_at",
+            "client_name",
+            "billable",
+        ],
+        "applications" => &["display_name", "project_id"],
+        "sessions" => &[
+            "end_time",
+            "duration_seconds",
+            "rate_multiplier",
+            "comment",
+            "is_hidden",
+            "project_id",
+            "project_name",
+            "billable",
+        ],
+        "manual_sessions" => &[
+            "session_type",
+            "project_id",
+            "app_id",
+            "end_time",
+            "duration_seconds",
+            "date",
+            "billable",
+        ],
+        "clients" => &[
+            "contact",
+            "address",
+            "tax_id",
+            "currency",
+            "default_hourly_rate",
+            "color",
+            "archived_at",
+        ],
+        "estimate_settings" => &["value"],
+        "tags" => &["color"],
+        _ => &[],
+    }
+}
+
+/// Columns holding local row IDs. The incoming record carries the peer's IDs
+/// (the merge maps them by name), so these are restored from the local
+/// snapshot only.
+const FLIP_LOCAL_ID_COLUMNS: [&str; 2] = ["project_id", "app_id"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SyncConflict {
+    pub id: i64,
+    pub sync_timestamp: String,
+    pub table_name: String,
+    pub record_key: String,
+    pub resolution: String,
+    pub local_updated_at: Option<String>,
+    pub remote_updated_at: Option<String>,
+    /// "local" | "remote" — the side LWW kept.
+    pub winner: String,
+    /// Local row before the merge (all columns); None for entries logged
+    /// before values were captured.
+    pub local_values: Option<serde_json::Value>,
+    /// Record as received from the peer (peer-side IDs).
+    pub incoming_values: Option<serde_json::Value>,
+    pub reviewed_at: Option<String>,
+    pub flipped_at: Option<String>,
+    pub can_flip: bool,
+}
+
+fn parse_details(details: Option<&str>) -> (Option<serde_json::Value>, Option<serde_json::Value>) {
+    let Some(parsed) = details.and_then(|d| serde_json::from_str::<serde_json::Value>(d).ok())
+    else {
+        return (None, None);
+    };
+    let pick = |key: &str| parsed.get(key).filter(|v| v.is_object()).cloned();
+    (pick("local"), pick("incoming"))
+}
+
+fn row_to_conflict(row: &rusqlite::Row<'_>) -> rusqlite::Result<SyncConflict> {
+    let table_name: String = row.get(2)?;
+    let winner: String = row.get(7)?;
+    let details: Option<String> = row.get(8)?;
+    let flipped_at: Option<String> = row.get(10)?;
+    let (local_values, incoming_values) = parse_details(details.as_deref());
+    let can_flip = flipped_at.is_none()
+        && FLIPPABLE_TABLES.contains(&table_name.as_str())
+        && local_values.is_some()
+        && (winner == "remote" || incoming_values.is_some());
+    Ok(SyncConflict {
+        id: row.get(0)?,
+        sync_timestamp: row.get(1)?,
+        table_name,
+        record_key: row.get(3)?,
+        resolution: row.get(4)?,
+        local_updated_at: row.get(5)?,
+        remote_updated_at: row.get(6)?,
+        winner,
+        local_values,
+        incoming_values,
+        reviewed_at: row.get(9)?,
+        flipped_at,
+        can_flip,
+    })
+}
+
+const CONFLICT_COLUMNS: &str = "id, sync_timestamp, table_name, record_key, resolution,
+    local_updated_at, remote_updated_at, winner, details, reviewed_at, flipped_at";
+
+pub(crate) fn load_sync_conflicts(
+    conn: &rusqlite::Connection,
+    include_reviewed: bool,
+    limit: i64,
+) -> Result<Vec<SyncConflict>, String> {
+    let sql = format!(
+        "SELECT {} FROM sync_merge_log
+         WHERE ?1 OR reviewed_at IS NULL
+         ORDER BY sync_timestamp DESC, id DESC
+         LIMIT ?2",
+        CONFLICT_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![include_reviewed, limit], row_to_conflict)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn load_sync_conflict(conn: &rusqlite::Connection, id: i64) -> Result<SyncConflict, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM sync_merge_log WHERE id = ?1",
+            CONFLICT_COLUMNS
+        ),
+        [id],
+        row_to_conflict,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => format!("Sync conflict {} not found", id),
+        other => other.to_string(),
+    })
+}
+
+fn json_to_sql(value: &serde_json::Value) -> rusqlite::types::Value {
+    match value {
+        serde_json::Value::Null => rusqlite::types::Value::Null,
+        serde_json::Value::Bool(b) => rusqlite::types::Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => rusqlite::types::Value::Integer(i),
+            None => rusqlite::types::Value::Real(n.as_f64().unwrap_or(0.0)),
+        },
+        serde_json::Value::String(s) => rusqlite::types::Value::Text(s.clone()),
+        other => rusqlite::types::Value::Text(other.to_string()),
+    }
+}
+
+fn table_columns(conn: &rusqlite::Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_info(?1)")
+        .map_err(|e| e.to_string())?;
+    let columns = stmt
+        .query_map([table], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(columns)
+}
+
+fn same_timestamp(a: &str, b: &str) -> bool {
+    let norm = |s: &str| super::delta_export::normalize_datetime_for_sqlite_pub(s);
+    norm(a) == norm(b)
+}
+
+/// Reverses an LWW decision: the losing side's values are written back as a
+/// regular edit with a fresh `updated_at`, so the next sync propagates them.
+/// Refuses when the record changed after the conflict (the flip would
+/// silently discard that newer edit) or no longer exists.
+pub(crate) fn flip_sync_conflict_in_conn(
+    conn: &mut rusqlite::Connection,
+    id: i64,
+) -> Result<(), String> {
+    let conflict = load_sync_conflict(conn, id)?;
+    if conflict.flipped_at.is_some() {
+        return Err(format!("Sync conflict {} was already flipped", id));
+    }
+    if !conflict.can_flip {
+        return Err(format!(
+            "Sync conflict {} cannot be flipped (no recorded values for '{}')",
+            id, conflict.table_name
+        ));
+    }
+    let table = conflict.table_name.as_str();
+    let local = conflict
+        .local_values
+        .as_ref()
+        .ok_or("missing local values")?;
+    let (values, winner_ts) = if conflict.winner == "remote" {
+        (local, conflict.remote_updated_at.as_deref())
+    } else {
+        (
+            conflict
+                .incoming_values
+                .as_ref()
+                .ok_or("missing incoming values")?,
+            conflict.local_updated_at.as_deref(),
+        )
+    };
+
+    // Locate the row via the local snapshot — IDs in `incoming` are the peer's.
+    let ident_col = match table {
+        "estimate_settings" => "key",
+        "tags" => "name",
+        _ => "id",
+    };
+    let ident = local
+        .get(ident_col)
+        .map(json_to_sql)
+        .ok_or_else(|| format!("Local snapshot has no '{}'", ident_col))?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let current_ts: Option<String> = tx
+        .query_row(
+            &format!("SELECT updated_at FROM {} WHERE {} = ?1", table, ident_col),
+            [&ident],
+            |row| row.get(0),
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                format!(
+                    "Record '{}' no longer exists in {}",
+                    conflict.record_key, table
+                )
+            }
+            other => other.to_string(),
+        })?;
+    if !same_timestamp(current_ts.as_deref().unwrap_or(""), winner_ts.unwrap_or("")) {
+        return Err(format!(
+            "Record '{}' changed after the conflict — flip refused",
+            conflict.record_key
+        ));
+    }
+
+    let table_columns = table_columns(&tx, table)?;
+    let Some(values_obj) = values.as_object() else {
+        return Err("Conflict values are not an object".to_string());
+    };
+    let mut assignments: Vec<String> = Vec::new();
+    let mut params: Vec<rusqlite::types::Value> = Vec::new();
+    let restoring_incoming = conflict.winner != "remote";
+    for &column in flip_columns(table) {
+        if !table_columns.iter().any(|c| c == column)
+            || (restoring_incoming && FLIP_LOCAL_ID_COLUMNS.contains(&column))
+        {
+            continue;
+        }
+        if let Some(value) = values_obj.get(column) {
+            params.push(json_to_sql(value));
+            assignments.push(format!("{} = ?{}", column, params.len()));
+        }
+    }
+    if assignments.is_empty() {
+        return Err("Nothing to restore for this conflict".to_string());
+    }
+
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    params.push(rusqlite::types::Value::Text(now.clone()));
+    assignments.push(format!("updated_at = ?{}", params.len()));
+    params.push(ident);
+    let sql = format!(
+        "UPDATE {} SET {} WHERE {} = ?{}",
+        table,
+        assignments.join(", "),
+        ident_col,
+        params.len()
+    );
+    tx.execute(&sql, rusqlite::params_from_iter(params.iter()))
+        .map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "UPDATE sync_merge_log SET flipped_at = ?1, reviewed_at = COALESCE(reviewed_at, ?1)
+         WHERE id = ?2",
+        rusqlite::params![now, id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    log::info!(
+        "Sync conflict {} flipped: {} '{}' restored to the '{}' side",
+        id,
+        table,
+        conflict.record_key,
+        if conflict.winner == "remote" {
+            "local"
+        } else {
+            "remote"
+        }
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_sync_conflicts(
+    app: AppHandle,
+    include_reviewed: Option<bool>,
+    limit: Option<i64>,
+) -> Result<Vec<SyncConflict>, String> {
+    run_db_blocking(app, move |conn| {
+        load_sync_conflicts(
+            conn,
+            include_reviewed.unwrap_or(false),
+            limit.unwrap_or(200).clamp(1, 5000),
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn mark_sync_conflicts_reviewed(app: AppHandle, ids: Vec<i64>) -> Result<usize, String> {
+    run_db_blocking(app, move |conn| {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut updated = 0usize;
+        for id in ids {
+            updated += conn
+                .execute(
+                    "UPDATE sync_merge_log SET reviewed_at = ?1 WHERE id = ?2 AND reviewed_at IS NULL",
+                    rusqlite::params![now, id],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(updated)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn flip_sync_conflict(app: AppHandle, id: i64) -> Result<SyncConflict, String> {
+    run_db_blocking(app, move |conn| {
+        flip_sync_conflict_in_conn(conn, id)?;
+        load_sync_conflict(conn, id)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_schema_conn() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().expect("in-memory db");
+        conn.execute_batch(include_str!("../../resources/sql/schema.sql"))
+            .expect("schema");
+        crate::db_migrations::run_migrations(&conn).expect("migrations");
+        conn
+    }
+
+    fn log_conflict(
+        conn: &rusqlite::Connection,
+        table: &str,
+        key: &str,
+        local_ts: &str,
+        remote_ts: &str,
+        winner: &str,
+        details: &str,
+    ) -> i64 {
+        conn.execute(
+            "INSERT INTO sync_merge_log (table_name, record_key, local_updated_at, remote_updated_at, winner, details)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![table, key, local_ts, remote_ts, winner, details],
+        )
+        .expect("log conflict");
+        conn.last_insert_rowid()
+    }
+
+    #[test]
+    fn flip_remote_win_restores_local_values_with_fresh_timestamp() {
+        let mut conn = full_schema_conn();
+        // Stan po merge: peer (nowszy) nadpisał kolor i stawkę projektu.
+        conn.execute(
+            "INSERT INTO projects (id, name, color, hourly_rate, created_at, updated_at)
+             VALUES (7, 'Website', '#ff0000', 200, '2026-04-01 00:00:00', '2026-04-21 10:00:00')",
+            [],
+        )
+        .unwrap();
+        let details = serde_json::json!({
+            "local": { "id": 7, "name": "Website", "color": "#00ff00", "hourly_rate": 120.0,
+                       "created_at": "2026-04-01 00:00:00", "updated_at": "2026-04-20 10:00:00",
+                       "hlc": "1776679200000-0000-" },
+            "incoming": { "id": 3, "name": "Website", "color": "#ff0000", "hourly_rate": 200.0,
+                          "updated_at": "2026-04-21 10:00:00", "hlc": "1776765600000-0000-peer" }
+        })
+        .to_string();
+        let id = log_conflict(
+            &conn,
+            "projects",
+            "Website",
+            "2026-04-20 10:00:00",
+            "2026-04-21 10:00:00",
+            "remote",
+            &details,
+        );
+
+        let queue = load_sync_conflicts(&conn, false, 50).expect("queue");
+        assert_eq!(queue.len(), 1);
+        assert!(queue[0].can_flip);
+        assert_eq!(queue[0].local_values.as_ref().unwrap()["color"], "#00ff00");
+
+        flip_sync_conflict_in_conn(&mut conn, id).expect("flip");
+
+        let (color, rate, updated_at): (String, f64, String) = conn
+            .query_row(
+                "SELECT color, hourly_rate, updated_at FROM projects WHERE id = 7",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(color, "#00ff00");
+        assert_eq!(rate, 120.0);
+        assert!(
+            updated_at.as_str() > "2026-04-21 10:00:00",
+            "flip must outvote the peer on next sync"
+        );
+        // The stale snapshot clock is never written back — the flip is stamped
+        // as a fresh local edit.
+        let hlc: String = conn
+            .query_row("SELECT hlc FROM projects WHERE id = 7", [], |r| r.get(0))
+            .unwrap();
+        let hlc = timeflow_shared::sync_hlc::Hlc::parse(&hlc).expect("valid hlc");
+        assert!(hlc > timeflow_shared::sync_hlc::Hlc::parse("1776765600000-0000-peer").unwrap());
+        assert!(!hlc.node.is_empty());
+
+        assert!(
+            load_sync_conflicts(&conn, false, 50).unwrap().is_empty(),
+            "flipped entry leaves the queue"
+        );
+        let err = flip_sync_conflict_in_conn(&mut conn, id).unwrap_err();
+        assert!(err.contains("already flipped"), "{err}");
+    }
+
+    #[test]
+    fn flip_local_win_applies_incoming_setting() {
+        let mut conn = full_schema_conn();
+        conn.execute(
+            "INSERT INTO estimate_settings (key, value, updated_at) VALUES ('time_algorithm', 'max', '2026-04-22 08:00:00')
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            [],
+        )
+        .unwrap();
+        let details = serde_json::json!({
+            "local": { "key": "time_algorithm", "value": "max", "updated_at": "2026-04-22 08:00:00" },
+            "incoming": { "key": "time_algorithm", "value": "sum", "updated_at": "2026-04-21 09:00:00" }
+        })
+        .to_string();
+        let id = log_conflict(
+            &conn,
+            "estimate_settings",
+            "time_algorithm",
+            "2026-04-22 08:00:00",
+            "2026-04-21 09:00:00",
+            "local",
+            &details,
+        );
+
+        flip_sync_conflict_in_conn(&mut conn, id).expect("flip");
+        let value: String = conn
+            .query_row(
+                "SELECT value FROM estimate_settings WHERE key = 'time_algorithm'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(value, "sum");
+    }
+
+    #[test]
+    fn flip_local_win_keeps_machine_specific_project_columns() {
+        let mut conn = full_schema_conn();
+        conn.execute(
+            "INSERT INTO projects (id, name, color, assigned_folder_path, created_at, updated_at)
+             VALUES (4, 'Website', '#00ff00', 'C:\\work\\website', '2026-04-01 00:00:00', '2026-04-22 10:00:00')",
+            [],
+        )
+        .unwrap();
+        let details = serde_json::json!({
+            "local": { "id": 4, "name": "Website", "color": "#00ff00",
+                       "assigned_folder_path": "C:\\work\\website",
+                       "created_at": "2026-04-01 00:00:00", "updated_at": "2026-04-22 10:00:00" },
+            "incoming": { "id": 9, "name": "Website", "color": "#ff0000",
+                          "assigned_folder_path": "/home/peer/website",
+                          "created_at": "2026-03-15 00:00:00", "updated_at": "2026-04-21 10:00:00" }
+        })
+        .to_string();
+        let id = log_conflict(
+            &conn,
+            "projects",
+            "Website",
+            "2026-04-22 10:00:00",
+            "2026-04-21 10:00:00",
+            "local",
+            &details,
+        );
+
+        flip_sync_conflict_in_conn(&mut conn, id).expect("flip");
+        let (color, folder, created_at): (String, String, String) = conn
+            .query_row(
+                "SELECT color, assigned_folder_path, created_at FROM projects WHERE id = 4",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(color, "#ff0000");
+        assert_eq!(folder, "C:\\work\\website");
+        assert_eq!(created_at, "2026-04-01 00:00:00");
+    }
+
+    #[test]
+    fn flip_tag_conflict_restores_color() {
+        let mut conn = full_schema_conn();
+        conn.execute(
+            "INSERT INTO tags (id, name, color, updated_at) VALUES (2, 'Urgent', '#ff0000', '2026-04-21 10:00:00')",
+            [],
+        )
+        .unwrap();
+        let details = serde_json::json!({
+            "local": { "id": 2, "name": "Urgent", "color": "#00ff00", "updated_at": "2026-04-20 10:00:00" },
+            "incoming": { "id": 5, "name": "Urgent", "color": "#ff0000", "updated_at": "2026-04-21 10:00:00" }
+        })
+        .to_string();
+        let id = log_conflict(
+            &conn,
+            "tags",
+            "Urgent",
+            "2026-04-20 10:00:00",
+            "2026-04-21 10:00:00",
+            "remote",
+            &details,
+        );
+
+        assert!(load_sync_conflicts(&conn, false, 50).unwrap()[0].can_flip);
+        flip_sync_conflict_in_conn(&mut conn, id).expect("flip");
+        let color: String = conn
+            .query_row("SELECT color FROM tags WHERE name = 'Urgent'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(color, "#00ff00");
+    }
+
+    #[test]
+    fn flip_refused_when_record_changed_or_values_missing() {
+        let mut conn = full_schema_conn();
+        conn.execute(
+            "INSERT INTO projects (id, name, color, created_at, updated_at)
+             VALUES (1, 'Website', '#ff0000', '2026-04-01 00:00:00', '2026-04-25 10:00:00')",
+            [],
+        )
+        .unwrap();
+        let details = serde_json::json!({
+            "local": { "id": 1, "name": "Website", "color": "#00ff00", "updated_at": "2026-04-20 10:00:00" },
+            "incoming": { "id": 1, "name": "Website", "color": "#ff0000", "updated_at": "2026-04-21 10:00:00" }
+        })
+        .to_string();
+        // Rekord edytowany po konflikcie (25.04 ≠ 21.04) → odmowa.
+        let changed = log_conflict(
+            &conn,
+            "projects",
+            "Website",
+            "2026-04-20 10:00:00",
+            "2026-04-21 10:00:00",
+            "remote",
+            &details,
+        );
+        let err = flip_sync_conflict_in_conn(&mut conn, changed).unwrap_err();
+        assert!(err.contains("changed after the conflict"), "{err}");
+        let color: String = conn
+            .query_row("SELECT color FROM projects WHERE id = 1", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(color, "#ff0000");
+
+        // Stary wpis bez `details` — widoczny w kolejce, ale nie do odwrócenia.
+        conn.execute(
+            "INSERT INTO sync_merge_log (table_name, record_key, winner) VALUES ('projects', 'Website', 'remote')",
+            [],
+        )
+        .unwrap();
+        let legacy = conn.last_insert_rowid();
+        let queue = load_sync_conflicts(&conn, false, 50).unwrap();
+        assert!(!queue.iter().find(|c| c.id == legacy).unwrap().can_flip);
+        assert!(flip_sync_conflict_in_conn(&mut conn, legacy).is_err());
+    }
+}
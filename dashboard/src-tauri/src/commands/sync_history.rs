@@ -0,0 +1,331 @@
+// Sync run history — read side of `sync_runs` (m29). The daemon writes one
+// row per LAN / online run and prunes by `sync_history_retention_days`;
+// here the dashboard lists runs, aggregates them per peer and clears them.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::helpers::run_db_blocking;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SyncRunRecord {
+    pub id: i64,
+    /// "lan" | "online"
+    pub kind: String,
+    /// "master" | "slave" | "" (online run that ended before a role was assigned)
+    pub role: String,
+    pub peer_id: String,
+    /// "delta" | "full" | "none" | "async" | "" (ended before negotiation)
+    pub mode: String,
+    pub dry_run: bool,
+    /// "ok" | "error" | "aborted"
+    pub status: String,
+    pub error: Option<String>,
+    pub step_reached: i64,
+    pub phase: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub duration_ms: i64,
+    pub bytes_sent: i64,
+    pub bytes_received: i64,
+    pub rows_inserted: i64,
+    pub rows_updated: i64,
+    pub rows_deleted: i64,
+    pub tombstones_added: i64,
+    pub conflicts: i64,
+    /// `{table: {inserted, updated, deleted}}` for tables the merge touched.
+    pub table_stats: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SyncPeerStats {
+    pub kind: String,
+    pub peer_id: String,
+    pub runs: i64,
+    pub ok: i64,
+    pub failed: i64,
+    pub aborted: i64,
+    pub avg_duration_ms: i64,
+    pub bytes_sent: i64,
+    pub bytes_received: i64,
+    pub conflicts: i64,
+    pub last_run_at: String,
+    pub last_ok_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+fn row_to_run(row: &rusqlite::Row<'_>) -> rusqlite::Result<SyncRunRecord> {
+    let table_stats: String = row.get(20)?;
+    Ok(SyncRunRecord {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        role: row.get(2)?,
+        peer_id: row.get(3)?,
+        mode: row.get(4)?,
+        dry_run: row.get(5)?,
+        status: row.get(6)?,
+        error: row.get(7)?,
+        step_reached: row.get(8)?,
+        phase: row.get(9)?,
+        started_at: row.get(10)?,
+        finished_at: row.get(11)?,
+        duration_ms: row.get(12)?,
+        bytes_sent: row.get(13)?,
+        bytes_received: row.get(14)?,
+        rows_inserted: row.get(15)?,
+        rows_updated: row.get(16)?,
+        rows_deleted: row.get(17)?,
+        tombstones_added: row.get(18)?,
+        conflicts: row.get(19)?,
+        table_stats: serde_json::from_str(&table_stats).unwrap_or_else(|_| serde_json::json!({})),
+    })
+}
+
+pub(crate) fn load_sync_runs(
+    conn: &rusqlite::Connection,
+    kind: Option<&str>,
+    status: Option<&str>,
+    peer_id: Option<&str>,
+    limit: i64,
+) -> Result<Vec<SyncRunRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, kind, role, peer_id, mode, dry_run, status, error, step_reached, phase,
+                    started_at, finished_at, duration_ms, bytes_sent, bytes_received,
+                    rows_inserted, rows_updated, rows_deleted, tombstones_added, conflicts, table_stats
+             FROM sync_runs
+             WHERE (?1 IS NULL OR kind = ?1)
+               AND (?2 IS NULL OR status = ?2)
+               AND (?3 IS NULL OR peer_id = ?3)
+             ORDER BY started_at DESC, id DESC
+             LIMIT ?4",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![kind, status, peer_id, limit], row_to_run)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Per peer (and sync kind) aggregates over the last `days` days. Dry-runs
+/// are excluded — they say nothing about sync health.
+pub(crate) fn load_sync_run_stats(
+    conn: &rusqlite::Connection,
+    days: i64,
+) -> Result<Vec<SyncPeerStats>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT kind, peer_id, COUNT(*),
+                    SUM(status = 'ok'), SUM(status = 'error'), SUM(status = 'aborted'),
+                    CAST(AVG(duration_ms) AS INTEGER),
+                    SUM(bytes_sent), SUM(bytes_received), SUM(conflicts),
+                    MAX(started_at),
+                    MAX(CASE WHEN status = 'ok' THEN started_at END),
+                    (SELECT r2.error FROM sync_runs r2
+                     WHERE r2.kind = r.kind AND r2.peer_id = r.peer_id AND r2.status = 'error'
+                       AND r2.dry_run = 0 AND r2.started_at >= datetime('now', ?1)
+                     ORDER BY r2.started_at DESC, r2.id DESC LIMIT 1)
+             FROM sync_runs r
+             WHERE dry_run = 0 AND started_at >= datetime('now', ?1)
+             GROUP BY kind, peer_id
+             ORDER BY MAX(started_at) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([format!("-{} days", days)], |row| {
+            Ok(SyncPeerStats {
+                kind: row.get(0)?,
+                peer_id: row.get(1)?,
+                runs: row.get(2)?,
+                ok: row.get(3)?,
+                failed: row.get(4)?,
+                aborted: row.get(5)?,
+                avg_duration_ms: row.get(6)?,
+                bytes_sent: row.get(7)?,
+                bytes_received: row.get(8)?,
+                conflicts: row.get(9)?,
+                last_run_at: row.get(10)?,
+                last_ok_at: row.get(11)?,
+                last_error: row.get(12)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Deletes runs older than `older_than_days`, or all of them when `None`.
+pub(crate) fn clear_sync_runs(
+    conn: &rusqlite::Connection,
+    older_than_days: Option<i64>,
+) -> Result<usize, String> {
+    match older_than_days {
+        Some(days) => conn.execute(
+            "DELETE FROM sync_runs WHERE started_at < datetime('now', ?1)",
+            [format!("-{} days", days)],
+        ),
+        None => conn.execute("DELETE FROM sync_runs", []),
+    }
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_sync_runs(
+    app: AppHandle,
+    kind: Option<String>,
+    status: Option<String>,
+    peer_id: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<SyncRunRecord>, String> {
+    run_db_blocking(app, move |conn| {
+        load_sync_runs(
+            conn,
+            kind.as_deref(),
+            status.as_deref(),
+            peer_id.as_deref(),
+            limit.unwrap_or(200).clamp(1, 5000),
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_sync_run_stats(
+    app: AppHandle,
+    days: Option<i64>,
+) -> Result<Vec<SyncPeerStats>, String> {
+    run_db_blocking(app, move |conn| {
+        load_sync_run_stats(conn, days.unwrap_or(30).clamp(1, 3650))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn clear_sync_history(
+    app: AppHandle,
+    older_than_days: Option<i64>,
+) -> Result<usize, String> {
+    run_db_blocking(app, move |conn| {
+        clear_sync_runs(conn, older_than_days.map(|d| d.max(0)))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_schema_conn() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().expect("in-memory db");
+        conn.execute_batch(include_str!("../../resources/sql/schema.sql"))
+            .expect("schema");
+        crate::db_migrations::run_migrations(&conn).expect("migrations");
+        conn
+    }
+
+    fn insert_run(
+        conn: &rusqlite::Connection,
+        kind: &str,
+        peer: &str,
+        status: &str,
+        error: Option<&str>,
+        age_days: i64,
+        dry_run: bool,
+    ) {
+        conn.execute(
+            "INSERT INTO sync_runs (kind, role, peer_id, mode, dry_run, status, error,
+                                    started_at, finished_at, duration_ms, bytes_sent, bytes_received,
+                                    rows_inserted, conflicts, table_stats)
+             VALUES (?1, 'master', ?2, 'delta', ?3, ?4, ?5,
+                     datetime('now', ?6), datetime('now', ?6), 1000, 10, 20,
+                     3, 1, '{\"sessions\":{\"inserted\":3,\"updated\":0,\"deleted\":0}}')",
+            rusqlite::params![kind, peer, dry_run, status, error, format!("-{} days", age_days)],
+        )
+        .expect("insert run");
+    }
+
+    #[test]
+    fn runs_are_filtered_and_newest_first() {
+        let conn = full_schema_conn();
+        insert_run(&conn, "lan", "dev-a", "ok", None, 3, false);
+        insert_run(
+            &conn,
+            "lan",
+            "dev-a",
+            "error",
+            Some("Preflight failed"),
+            1,
+            false,
+        );
+        insert_run(&conn, "online", "https://sync", "ok", None, 2, false);
+
+        let all = load_sync_runs(&conn, None, None, None, 10).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].status, "error");
+        assert_eq!(all[0].table_stats["sessions"]["inserted"], 3);
+
+        let lan_errors = load_sync_runs(&conn, Some("lan"), Some("error"), None, 10).unwrap();
+        assert_eq!(lan_errors.len(), 1);
+        assert_eq!(lan_errors[0].error.as_deref(), Some("Preflight failed"));
+
+        let online = load_sync_runs(&conn, None, None, Some("https://sync"), 10).unwrap();
+        assert_eq!(online.len(), 1);
+        assert_eq!(online[0].kind, "online");
+    }
+
+    #[test]
+    fn stats_aggregate_per_peer_within_window_without_dry_runs() {
+        let conn = full_schema_conn();
+        insert_run(&conn, "lan", "dev-a", "ok", None, 5, false);
+        insert_run(
+            &conn,
+            "lan",
+            "dev-a",
+            "error",
+            Some("older error"),
+            4,
+            false,
+        );
+        insert_run(
+            &conn,
+            "lan",
+            "dev-a",
+            "error",
+            Some("newest error"),
+            2,
+            false,
+        );
+        insert_run(&conn, "lan", "dev-a", "aborted", None, 1, false);
+        insert_run(&conn, "lan", "dev-a", "ok", None, 1, true);
+        insert_run(&conn, "lan", "dev-a", "ok", None, 60, false);
+
+        let stats = load_sync_run_stats(&conn, 30).unwrap();
+        assert_eq!(stats.len(), 1);
+        let s = &stats[0];
+        assert_eq!((s.runs, s.ok, s.failed, s.aborted), (4, 1, 2, 1));
+        assert_eq!(s.avg_duration_ms, 1000);
+        assert_eq!((s.bytes_sent, s.bytes_received, s.conflicts), (40, 80, 4));
+        assert_eq!(s.last_error.as_deref(), Some("newest error"));
+        assert!(s.last_ok_at.is_some());
+    }
+
+    #[test]
+    fn clear_removes_old_runs_or_everything() {
+        let conn = full_schema_conn();
+        insert_run(&conn, "lan", "dev-a", "ok", None, 40, false);
+        insert_run(&conn, "lan", "dev-a", "ok", None, 1, false);
+
+        assert_eq!(clear_sync_runs(&conn, Some(30)).unwrap(), 1);
+        assert_eq!(
+            load_sync_runs(&conn, None, None, None, 10).unwrap().len(),
+            1
+        );
+        assert_eq!(clear_sync_runs(&conn, None).unwrap(), 1);
+        assert!(load_sync_runs(&conn, None, None, None, 10)
+            .unwrap()
+            .is_empty());
+    }
+}
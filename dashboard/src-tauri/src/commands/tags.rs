@@ -0,0 +1,834 @@
+// Tags on sessions and manual sessions (m33).
+//
+// A tag is a named, coloured label; sessions and manual sessions link to any
+// number of them. Sync identifies tags by name (like clients) and carries the
+// links as a list of tag names on every session row, so each link change
+// bumps the owning row's `updated_at` — that is what ships it to peers.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use rusqlite::{Connection, OptionalExtension, Transaction};
+use tauri::AppHandle;
+
+use super::helpers::run_db_blocking;
+use super::session_audit::{self, Change};
+use super::sql_fragments::ACTIVE_SESSION_FILTER_S;
+use super::types::{default_tag_color, DateRange, Tag, TagRow, TagTime};
+
+const MAX_TAG_NAME_LEN: usize = 64;
+
+const TAG_COLUMNS: &str = "t.id, t.name, t.color,
+    (SELECT COUNT(*) FROM session_tags st WHERE st.tag_id = t.id),
+    (SELECT COUNT(*) FROM manual_session_tags mt WHERE mt.tag_id = t.id),
+    COALESCE(t.created_at, ''), t.updated_at";
+
+/// Which session table a link belongs to.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum TagTarget {
+    Session,
+    ManualSession,
+}
+
+impl TagTarget {
+    fn owner_table(self) -> &'static str {
+        match self {
+            TagTarget::Session => "sessions",
+            TagTarget::ManualSession => "manual_sessions",
+        }
+    }
+
+    fn link_table(self) -> &'static str {
+        match self {
+            TagTarget::Session => "session_tags",
+            TagTarget::ManualSession => "manual_session_tags",
+        }
+    }
+
+    fn owner_column(self) -> &'static str {
+        match self {
+            TagTarget::Session => "session_id",
+            TagTarget::ManualSession => "manual_session_id",
+        }
+    }
+}
+
+fn map_tag_row(row: &rusqlite::Row) -> rusqlite::Result<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        session_count: row.get(3)?,
+        manual_session_count: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+fn load_tag(conn: &Connection, id: i64) -> Result<Tag, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM tags t WHERE t.id = ?1", TAG_COLUMNS),
+        [id],
+        map_tag_row,
+    )
+    .map_err(|e| format!("Tag not found: {}", e))
+}
+
+fn normalize_tag_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag name is required".to_string());
+    }
+    if name.chars().count() > MAX_TAG_NAME_LEN {
+        return Err(format!(
+            "Tag name must be at most {} characters",
+            MAX_TAG_NAME_LEN
+        ));
+    }
+    Ok(name.to_string())
+}
+
+fn sanitize_ids(ids: Vec<i64>) -> Vec<i64> {
+    let mut seen = std::collections::HashSet::new();
+    ids.into_iter()
+        .filter(|id| *id > 0 && seen.insert(*id))
+        .collect()
+}
+
+/// JSON array with the names of the tags linked to `alias.id`, sorted by name.
+pub(crate) fn tags_json_sql(target: TagTarget, alias: &str) -> String {
+    format!(
+        "(SELECT json_group_array(name) FROM (
+            SELECT t.name FROM {link} lt JOIN tags t ON t.id = lt.tag_id
+            WHERE lt.{owner} = {alias}.id
+            ORDER BY t.name COLLATE NOCASE
+        ))",
+        link = target.link_table(),
+        owner = target.owner_column(),
+        alias = alias,
+    )
+}
+
+/// Reads a `tags_json_sql` column.
+pub(crate) fn parse_tag_names(json: Option<String>) -> Vec<String> {
+    json.and_then(|j| serde_json::from_str(&j).ok())
+        .unwrap_or_default()
+}
+
+/// ` AND EXISTS (…)` keeping `alias` rows that have ANY of `tag_ids`;
+/// empty for no tag filter. Ids are integers, safe to inline.
+pub(crate) fn tag_filter_sql(target: TagTarget, alias: &str, tag_ids: &[i64]) -> String {
+    if tag_ids.is_empty() {
+        return String::new();
+    }
+    let list: Vec<String> = tag_ids.iter().map(|id| id.to_string()).collect();
+    format!(
+        " AND EXISTS (SELECT 1 FROM {link} lt WHERE lt.{owner} = {alias}.id AND lt.tag_id IN ({list}))",
+        link = target.link_table(),
+        owner = target.owner_column(),
+        alias = alias,
+        list = list.join(","),
+    )
+}
+
+fn tag_names_of(
+    conn: &Connection,
+    target: TagTarget,
+    owner_id: i64,
+) -> Result<Vec<String>, String> {
+    let json: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM {} o WHERE o.id = ?1",
+                tags_json_sql(target, "o"),
+                target.owner_table()
+            ),
+            [owner_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    Ok(parse_tag_names(json))
+}
+
+/// Audited form of a tag list.
+fn tags_value(names: &[String]) -> Option<String> {
+    if names.is_empty() {
+        None
+    } else {
+        Some(names.join(", "))
+    }
+}
+
+/// Marks the rows as edited so sync carries their new tag list.
+fn touch_owners(conn: &Connection, target: TagTarget, owner_ids: &[i64]) -> Result<(), String> {
+    for id in owner_ids {
+        conn.execute(
+            &format!(
+                "UPDATE {} SET updated_at = datetime('now') WHERE id = ?1",
+                target.owner_table()
+            ),
+            [id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Adds or removes `tag_ids` on every owner row; returns the number of rows
+/// whose tags changed. Session changes are audited.
+fn change_links(
+    tx: &Transaction<'_>,
+    target: TagTarget,
+    owner_ids: &[i64],
+    tag_ids: &[i64],
+    add: bool,
+) -> Result<i64, String> {
+    for tag_id in tag_ids {
+        let exists = tx
+            .query_row("SELECT 1 FROM tags WHERE id = ?1", [tag_id], |_| Ok(()))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .is_some();
+        if !exists {
+            return Err(format!("Tag {} not found", tag_id));
+        }
+    }
+    let sql = if add {
+        format!(
+            "INSERT OR IGNORE INTO {} ({}, tag_id) VALUES (?1, ?2)",
+            target.link_table(),
+            target.owner_column()
+        )
+    } else {
+        format!(
+            "DELETE FROM {} WHERE {} = ?1 AND tag_id = ?2",
+            target.link_table(),
+            target.owner_column()
+        )
+    };
+
+    let mut changed_rows = 0;
+    for &owner_id in owner_ids {
+        let exists = tx
+            .query_row(
+                &format!("SELECT 1 FROM {} WHERE id = ?1", target.owner_table()),
+                [owner_id],
+                |_| Ok(()),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .is_some();
+        if !exists {
+            return Err(format!("Session {} not found", owner_id));
+        }
+        let old_names = tag_names_of(tx, target, owner_id)?;
+        let mut changed = 0;
+        for tag_id in tag_ids {
+            changed += tx
+                .execute(&sql, rusqlite::params![owner_id, tag_id])
+                .map_err(|e| e.to_string())?;
+        }
+        if changed == 0 {
+            continue;
+        }
+        changed_rows += 1;
+        touch_owners(tx, target, &[owner_id])?;
+        if target == TagTarget::Session {
+            let new_names = tag_names_of(tx, target, owner_id)?;
+            session_audit::record(
+                tx,
+                owner_id,
+                Change::new(
+                    "tag",
+                    "tags",
+                    tags_value(&old_names),
+                    tags_value(&new_names),
+                ),
+            )?;
+        }
+    }
+    Ok(changed_rows)
+}
+
+/// Ids of the local tags with these names (case-insensitive). Unknown names
+/// — e.g. a tag deleted here — are skipped.
+fn tag_ids_by_names(conn: &Connection, names: &[String]) -> Result<Vec<i64>, String> {
+    let mut ids = Vec::new();
+    for name in names {
+        let id: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM tags WHERE name = ?1",
+                [name.trim()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        ids.extend(id);
+    }
+    Ok(ids)
+}
+
+/// Sync / import: links the tags named `names` to the row. With `replace`
+/// the row's other tags are dropped first. Does not touch `updated_at` —
+/// the caller writes the peer's one.
+pub(crate) fn link_tag_names(
+    conn: &Connection,
+    target: TagTarget,
+    owner_id: i64,
+    names: &[String],
+    replace: bool,
+) -> Result<(), String> {
+    if replace {
+        conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE {} = ?1",
+                target.link_table(),
+                target.owner_column()
+            ),
+            [owner_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    for tag_id in tag_ids_by_names(conn, names)? {
+        conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO {} ({}, tag_id) VALUES (?1, ?2)",
+                target.link_table(),
+                target.owner_column()
+            ),
+            rusqlite::params![owner_id, tag_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Tag names of a session row, for archives and merges.
+pub(crate) fn session_tag_names(conn: &Connection, session_id: i64) -> Result<Vec<String>, String> {
+    tag_names_of(conn, TagTarget::Session, session_id)
+}
+
+/// Archive import: tags keyed by `name`, LWW by `updated_at`.
+pub(crate) fn import_tags(tx: &Transaction<'_>, rows: &[TagRow]) -> Result<(), String> {
+    for t in rows {
+        if t.name.trim().is_empty() {
+            continue;
+        }
+        let local_updated: Option<String> = tx
+            .query_row(
+                "SELECT updated_at FROM tags WHERE name = ?1",
+                [t.name.as_str()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        match local_updated {
+            Some(ref lu)
+                if super::delta_export::normalize_datetime_for_sqlite_pub(&t.updated_at)
+                    <= super::delta_export::normalize_datetime_for_sqlite_pub(lu) => {}
+            Some(_) => {
+                tx.execute(
+                    "UPDATE tags SET color = ?1, updated_at = ?2 WHERE name = ?3",
+                    rusqlite::params![t.color, t.updated_at, t.name],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO tags (name, color, created_at, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                    rusqlite::params![t.name, t.color, t.created_at, t.updated_at],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// All tags as archive rows.
+pub(crate) fn load_tag_rows(conn: &Connection) -> Result<Vec<TagRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name, color, created_at, updated_at FROM tags ORDER BY name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TagRow {
+                name: row.get(0)?,
+                color: row.get(1)?,
+                created_at: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read tag row: {}", e))
+}
+
+/// Time per tag over sessions (visible ones) and manual sessions in the range,
+/// most time first. Raw durations — no multipliers, no overlap dedup.
+pub(crate) fn tag_time_breakdown(
+    conn: &Connection,
+    date_range: &DateRange,
+) -> Result<Vec<TagTime>, String> {
+    let sql = format!(
+        "SELECT t.id, t.name, t.color, SUM(x.seconds) AS total, COUNT(*)
+         FROM (
+             SELECT st.tag_id, s.duration_seconds AS seconds
+             FROM session_tags st
+             JOIN sessions s ON s.id = st.session_id
+             WHERE s.date >= ?1 AND s.date <= ?2 AND {ACTIVE_SESSION_FILTER_S}
+             UNION ALL
+             SELECT mt.tag_id, ms.duration_seconds
+             FROM manual_session_tags mt
+             JOIN manual_sessions ms ON ms.id = mt.manual_session_id
+             WHERE ms.date >= ?1 AND ms.date <= ?2
+         ) x
+         JOIN tags t ON t.id = x.tag_id
+         GROUP BY t.id
+         ORDER BY total DESC, t.name COLLATE NOCASE"
+    );
+    let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![&date_range.start, &date_range.end],
+            |row| {
+                Ok(TagTime {
+                    tag_id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    seconds: row.get(3)?,
+                    session_count: row.get(4)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read tag time row: {}", e))
+}
+
+/// Time per tag over already loaded rows (tag names, seconds) — reports
+/// summarize exactly the sessions they list.
+pub(crate) fn summarize_tag_time(
+    conn: &Connection,
+    items: &[(Vec<String>, i64)],
+) -> Result<Vec<TagTime>, String> {
+    let mut totals: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+    for (names, seconds) in items {
+        let unique: BTreeSet<&String> = names.iter().collect();
+        for name in unique {
+            let entry = totals.entry(name.clone()).or_insert((0, 0));
+            entry.0 += seconds;
+            entry.1 += 1;
+        }
+    }
+    if totals.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut meta: HashMap<String, (i64, String)> = HashMap::new();
+    {
+        let mut stmt = conn
+            .prepare("SELECT id, name, color FROM tags")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let (id, name, color) = row.map_err(|e| e.to_string())?;
+            meta.insert(name, (id, color));
+        }
+    }
+    let mut out: Vec<TagTime> = totals
+        .into_iter()
+        .filter_map(|(name, (seconds, session_count))| {
+            let (tag_id, color) = meta.get(&name)?.clone();
+            Some(TagTime {
+                tag_id,
+                name,
+                color,
+                seconds,
+                session_count,
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.name.cmp(&b.name)));
+    Ok(out)
+}
+
+#[tauri::command]
+pub async fn get_tags(app: AppHandle) -> Result<Vec<Tag>, String> {
+    run_db_blocking(app, move |conn| {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM tags t ORDER BY t.name COLLATE NOCASE",
+                TAG_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], map_tag_row).map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read tag row: {}", e))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_tag_time_breakdown(
+    app: AppHandle,
+    date_range: DateRange,
+) -> Result<Vec<TagTime>, String> {
+    run_db_blocking(app, move |conn| tag_time_breakdown(conn, &date_range)).await
+}
+
+fn create_tag_sync(conn: &Connection, name: &str, color: Option<String>) -> Result<Tag, String> {
+    let name = normalize_tag_name(name)?;
+    let exists = conn
+        .query_row("SELECT 1 FROM tags WHERE name = ?1", [&name], |_| Ok(()))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if exists {
+        return Err("Tag already exists".to_string());
+    }
+    let color = color.unwrap_or_else(default_tag_color);
+    let now = chrono::Local::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO tags (name, color, created_at, updated_at) VALUES (?1, ?2, ?3, datetime('now'))",
+        rusqlite::params![name, color, now],
+    )
+    .map_err(|e| e.to_string())?;
+    load_tag(conn, conn.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn create_tag(
+    app: AppHandle,
+    name: String,
+    color: Option<String>,
+) -> Result<Tag, String> {
+    run_db_blocking(app, move |conn| create_tag_sync(conn, &name, color)).await
+}
+
+fn update_tag_sync(
+    conn: &mut Connection,
+    id: i64,
+    name: &str,
+    color: Option<String>,
+) -> Result<Tag, String> {
+    let name = normalize_tag_name(name)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let old_name: String = tx
+        .query_row("SELECT name FROM tags WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Tag not found".to_string())?;
+    let clash = tx
+        .query_row(
+            "SELECT 1 FROM tags WHERE name = ?1 AND id <> ?2",
+            rusqlite::params![name, id],
+            |_| Ok(()),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if clash {
+        return Err("Another tag already uses this name".to_string());
+    }
+    let color = color.unwrap_or_else(default_tag_color);
+    tx.execute(
+        "UPDATE tags SET name = ?1, color = ?2, updated_at = datetime('now') WHERE id = ?3",
+        rusqlite::params![name, color, id],
+    )
+    .map_err(|e| e.to_string())?;
+    if old_name != name {
+        // Sync keys tags by name: for peers a rename is "old name deleted +
+        // new name created", and the tagged rows must ship the new name.
+        tx.execute(
+            "INSERT INTO tombstones (table_name, record_id, sync_key) VALUES ('tags', ?1, ?2)",
+            rusqlite::params![id, old_name],
+        )
+        .map_err(|e| e.to_string())?;
+        for target in [TagTarget::Session, TagTarget::ManualSession] {
+            tx.execute(
+                &format!(
+                    "UPDATE {owner} SET updated_at = datetime('now')
+                     WHERE id IN (SELECT {col} FROM {link} WHERE tag_id = ?1)",
+                    owner = target.owner_table(),
+                    col = target.owner_column(),
+                    link = target.link_table(),
+                ),
+                [id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    let tag = load_tag(&tx, id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(tag)
+}
+
+#[tauri::command]
+pub async fn update_tag(
+    app: AppHandle,
+    id: i64,
+    name: String,
+    color: Option<String>,
+) -> Result<Tag, String> {
+    run_db_blocking(app, move |conn| update_tag_sync(conn, id, &name, color)).await
+}
+
+/// Deletes the tag and its links; the tombstone trigger propagates it.
+#[tauri::command]
+pub async fn delete_tag(app: AppHandle, id: i64) -> Result<(), String> {
+    run_db_blocking(app, move |conn| {
+        let deleted = conn
+            .execute("DELETE FROM tags WHERE id = ?1", [id])
+            .map_err(|e| e.to_string())?;
+        if deleted == 0 {
+            return Err("Tag not found".to_string());
+        }
+        Ok(())
+    })
+    .await
+}
+
+fn change_links_sync(
+    conn: &mut Connection,
+    target: TagTarget,
+    owner_ids: Vec<i64>,
+    tag_ids: Vec<i64>,
+    add: bool,
+) -> Result<i64, String> {
+    let owner_ids = sanitize_ids(owner_ids);
+    let tag_ids = sanitize_ids(tag_ids);
+    if owner_ids.is_empty() || tag_ids.is_empty() {
+        return Ok(0);
+    }
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let changed = change_links(&tx, target, &owner_ids, &tag_ids, add)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(changed)
+}
+
+/// Returns the number of sessions whose tags changed.
+#[tauri::command]
+pub async fn add_tags_to_sessions(
+    app: AppHandle,
+    session_ids: Vec<i64>,
+    tag_ids: Vec<i64>,
+) -> Result<i64, String> {
+    run_db_blocking(app, move |conn| {
+        change_links_sync(conn, TagTarget::Session, session_ids, tag_ids, true)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remove_tags_from_sessions(
+    app: AppHandle,
+    session_ids: Vec<i64>,
+    tag_ids: Vec<i64>,
+) -> Result<i64, String> {
+    run_db_blocking(app, move |conn| {
+        change_links_sync(conn, TagTarget::Session, session_ids, tag_ids, false)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn add_tags_to_manual_sessions(
+    app: AppHandle,
+    manual_session_ids: Vec<i64>,
+    tag_ids: Vec<i64>,
+) -> Result<i64, String> {
+    run_db_blocking(app, move |conn| {
+        change_links_sync(
+            conn,
+            TagTarget::ManualSession,
+            manual_session_ids,
+            tag_ids,
+            true,
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remove_tags_from_manual_sessions(
+    app: AppHandle,
+    manual_session_ids: Vec<i64>,
+    tag_ids: Vec<i64>,
+) -> Result<i64, String> {
+    run_db_blocking(app, move |conn| {
+        change_links_sync(
+            conn,
+            TagTarget::ManualSession,
+            manual_session_ids,
+            tag_ids,
+            false,
+        )
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        conn.execute_batch("PRAGMA foreign_keys = ON;")
+            .expect("pragma");
+        conn.execute_batch(include_str!("../../resources/sql/schema.sql"))
+            .expect("schema");
+        crate::db_migrations::run_migrations(&conn).expect("migrations");
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, color) VALUES (1, 'Alpha', '#fff');
+             INSERT INTO applications (id, executable_name, display_name) VALUES (1, 'code.exe', 'Code');
+             INSERT INTO sessions (id, app_id, project_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES (1, 1, 1, '2026-01-05T09:00:00', '2026-01-05T10:00:00', 3600, '2026-01-05', '2026-01-05 10:00:00'),
+                    (2, 1, 1, '2026-01-05T11:00:00', '2026-01-05T11:30:00', 1800, '2026-01-05', '2026-01-05 11:30:00');
+             INSERT INTO manual_sessions (id, title, session_type, project_id, start_time, end_time, duration_seconds, date)
+             VALUES (1, 'Call', 'meeting', 1, '2026-01-05T13:00:00', '2026-01-05T13:15:00', 900, '2026-01-05');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn bulk_tagging_links_audits_and_breaks_time_down() {
+        let mut conn = test_conn();
+        let review = create_tag_sync(&conn, "Review", None).unwrap();
+        let urgent = create_tag_sync(&conn, " urgent ", Some("#f00".to_string())).unwrap();
+        assert_eq!(urgent.name, "urgent");
+        assert!(create_tag_sync(&conn, "REVIEW", None).is_err());
+
+        let changed = change_links_sync(
+            &mut conn,
+            TagTarget::Session,
+            vec![1, 2, 2],
+            vec![review.id, urgent.id],
+            true,
+        )
+        .unwrap();
+        assert_eq!(changed, 2);
+        // Re-adding the same tags changes nothing.
+        let again = change_links_sync(
+            &mut conn,
+            TagTarget::Session,
+            vec![1],
+            vec![review.id],
+            true,
+        )
+        .unwrap();
+        assert_eq!(again, 0);
+        change_links_sync(
+            &mut conn,
+            TagTarget::ManualSession,
+            vec![1],
+            vec![review.id],
+            true,
+        )
+        .unwrap();
+        change_links_sync(
+            &mut conn,
+            TagTarget::Session,
+            vec![2],
+            vec![urgent.id],
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            session_tag_names(&conn, 1).unwrap(),
+            vec!["Review", "urgent"]
+        );
+        assert_eq!(session_tag_names(&conn, 2).unwrap(), vec!["Review"]);
+        let bumped: String = conn
+            .query_row("SELECT updated_at FROM sessions WHERE id = 2", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert!(bumped.as_str() > "2026-01-05 11:30:00");
+        let audited: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM session_audit_log WHERE action = 'tag' AND field = 'tags'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(audited, 3);
+
+        let range = DateRange {
+            start: "2026-01-05".to_string(),
+            end: "2026-01-05".to_string(),
+        };
+        let breakdown = tag_time_breakdown(&conn, &range).unwrap();
+        assert_eq!(breakdown.len(), 2);
+        assert_eq!(breakdown[0].name, "Review");
+        assert_eq!(breakdown[0].seconds, 3600 + 1800 + 900);
+        assert_eq!(breakdown[0].session_count, 3);
+        assert_eq!(breakdown[1].name, "urgent");
+        assert_eq!(breakdown[1].seconds, 3600);
+
+        let filter = tag_filter_sql(TagTarget::Session, "s", &[urgent.id]);
+        let tagged: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM sessions s WHERE 1=1{}", filter),
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(tagged, 1);
+    }
+
+    #[test]
+    fn rename_tombstones_old_name_and_delete_drops_links() {
+        let mut conn = test_conn();
+        let tag = create_tag_sync(&conn, "Draft", None).unwrap();
+        change_links_sync(&mut conn, TagTarget::Session, vec![1], vec![tag.id], true).unwrap();
+        conn.execute(
+            "UPDATE sessions SET updated_at = '2026-01-05 10:00:00' WHERE id = 1",
+            [],
+        )
+        .unwrap();
+
+        let renamed = update_tag_sync(&mut conn, tag.id, "Final", None).unwrap();
+        assert_eq!(renamed.session_count, 1);
+        let tombstone: String = conn
+            .query_row(
+                "SELECT sync_key FROM tombstones WHERE table_name = 'tags'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(tombstone, "Draft");
+        let bumped: String = conn
+            .query_row("SELECT updated_at FROM sessions WHERE id = 1", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert!(bumped.as_str() > "2026-01-05 10:00:00");
+
+        conn.execute("DELETE FROM tags WHERE id = ?1", [tag.id])
+            .unwrap();
+        assert!(session_tag_names(&conn, 1).unwrap().is_empty());
+        let tombstones: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM tombstones WHERE table_name = 'tags' AND sync_key = 'Final'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(tombstones, 1);
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! Architecture (plugin-ready):
 //!   * HOST  — `load_project_intervals` reads raw sessions from the DB and
-//!     produces pure [`IntervalInput`]s (no algorithm logic here).
+//!     produces pure [`IntervalInput`]s (no algorithm logic here), then
+//!     resolves manual/tracked overlaps per the user's overlap policy
+//!     (see `overlaps`) so every strategy sees the same inputs.
 //!   * STRATEGY — a [`TimeStrategy`] turns intervals into per-project seconds.
 //!     This is a pure function (no DB, no I/O) and is the exact contract a
 //!     future external/WASM plugin would implement.
@@ -26,7 +28,11 @@ use tauri::AppHandle;
 use super::analysis::{project_series_key, UNASSIGNED_PROJECT_SERIES_KEY};
 use super::datetime::parse_datetime_local;
 use super::helpers::{disambiguate_name, duplicate_name_counts, run_db_blocking};
-use super::sql_fragments::{ensure_session_project_cache, SESSION_PROJECT_CTE};
+use super::overlaps::{active_overlap_policy, apply_overlap_policy, OverlapPolicy};
+use super::sql_fragments::{
+    ensure_session_project_cache, SESSION_BACKGROUND_ONLY_SQL, SESSION_BILLABLE_SQL,
+    SESSION_PROJECT_CTE,
+};
 use super::types::{DateRange, StackedSeriesMeta, TopApp};
 
 const DEFAULT_UNASSIGNED_PROJECT_COLOR: &str = "#64748b";
@@ -39,7 +45,11 @@ const DEFAULT_ALGORITHM_ID: &str = "wall_clock";
 
 /// Available time strategies. Adding a strategy = one new line here.
 fn registry() -> Vec<Box<dyn TimeStrategy>> {
-    vec![Box::new(WallClockStrategy)]
+    vec![
+        Box::new(WallClockStrategy),
+        Box::new(ProportionalStrategy),
+        Box::new(ForegroundExclusiveStrategy),
+    ]
 }
 
 /// The active strategy id from settings, validated against the registry
@@ -84,6 +94,7 @@ pub struct TimeAlgorithmInfo {
 // ---------------------------------------------------------------------------
 
 /// One raw activity interval, free of any DB/I/O concern.
+#[derive(Clone)]
 pub(crate) struct IntervalInput {
     pub start: DateTime<Local>,
     pub end: DateTime<Local>,
@@ -91,6 +102,11 @@ pub(crate) struct IntervalInput {
     pub multiplier: f64,
     pub is_manual: bool,
     pub comment: Option<String>,
+    /// Effective billable flag (m34): the row's override, else the project's.
+    pub billable: bool,
+    /// Tracked session backed only by background-CPU activity (the daemon
+    /// records it as the `(background)` file while the app is not focused).
+    pub background: bool,
 }
 
 /// Bucketing window + granularity handed to a strategy.
@@ -104,6 +120,9 @@ pub(crate) struct ComputeRange {
 struct ActivityOutput {
     bucket_project_seconds: BucketDurations,
     total_by_project: ProjectTotals,
+    /// Part of `total_by_project` during which at least one billable interval
+    /// of the project was active — never more than the project's total.
+    billable_by_project: ProjectTotals,
     bucket_flags: BucketFlags,
     bucket_comments: BucketComments,
 }
@@ -203,6 +222,7 @@ struct BucketPiece {
     multiplier: f64,
     is_manual: bool,
     comment: Option<String>,
+    billable: bool,
 }
 
 type BucketDurations = BTreeMap<String, HashMap<String, f64>>;
@@ -383,6 +403,34 @@ pub(crate) fn compute_project_clock_totals_by_id(
         .collect())
 }
 
+/// Like [`compute_project_clock_totals_by_id`], with the billable part of
+/// each project's clock total: `project_id → (total, billable)` seconds.
+pub(crate) fn compute_project_billable_totals_by_id(
+    conn: &rusqlite::Connection,
+    date_range: &DateRange,
+    active_only: bool,
+    rollup_merged: bool,
+) -> Result<HashMap<i64, (f64, f64)>, String> {
+    let ((_, totals, series_meta_by_key, _, _), billable) = compute_project_activity_with_billable(
+        conn,
+        date_range,
+        false,
+        active_only,
+        None,
+        None,
+        rollup_merged,
+    )?;
+
+    Ok(totals
+        .into_iter()
+        .filter_map(|(series_key, seconds)| {
+            let project_id = series_meta_by_key.get(&series_key)?.project_id?;
+            let billable = billable.get(&series_key).copied().unwrap_or(0.0);
+            Some((project_id, (seconds, billable.min(seconds))))
+        })
+        .collect())
+}
+
 /// Host orchestration: load intervals from the DB, run the active strategy on
 /// them, then attach series metadata. The single dispatch point for the time
 /// algorithm — the strategy itself is pure and lives behind [`TimeStrategy`].
@@ -400,6 +448,29 @@ pub(crate) fn compute_project_activity_unique(
     min_session_duration: Option<i64>,
     rollup_merged: bool,
 ) -> Result<ProjectActivityUniqueResult, String> {
+    compute_project_activity_with_billable(
+        conn,
+        date_range,
+        hourly,
+        active_only,
+        project_id_filter,
+        min_session_duration,
+        rollup_merged,
+    )
+    .map(|(result, _)| result)
+}
+
+/// [`compute_project_activity_unique`] plus the billable part of every
+/// series total (m34), folded the same way as the totals.
+pub(crate) fn compute_project_activity_with_billable(
+    conn: &rusqlite::Connection,
+    date_range: &DateRange,
+    hourly: bool,
+    active_only: bool,
+    project_id_filter: Option<i64>,
+    min_session_duration: Option<i64>,
+    rollup_merged: bool,
+) -> Result<(ProjectActivityUniqueResult, ProjectTotals), String> {
     let (intervals, mut series_meta_by_key, range) = load_project_intervals(
         conn,
         date_range,
@@ -409,13 +480,35 @@ pub(crate) fn compute_project_activity_unique(
         min_session_duration,
         rollup_merged,
     )?;
+    // A project-filtered load must still yield to winning intervals of other
+    // projects, so the policy is resolved against the unfiltered set then.
+    let policy = active_overlap_policy(conn);
+    let reference = match (policy, project_id_filter) {
+        (OverlapPolicy::CountBoth, _) | (_, None) => None,
+        (_, Some(_)) => Some(
+            load_project_intervals(
+                conn,
+                date_range,
+                hourly,
+                active_only,
+                None,
+                min_session_duration,
+                rollup_merged,
+            )?
+            .0,
+        ),
+    };
+    let intervals = apply_overlap_policy(intervals, reference.as_deref(), policy);
 
     if intervals.is_empty() {
         return Ok((
-            BTreeMap::new(),
-            HashMap::new(),
-            series_meta_by_key,
-            HashMap::new(),
+            (
+                BTreeMap::new(),
+                HashMap::new(),
+                series_meta_by_key,
+                HashMap::new(),
+                HashMap::new(),
+            ),
             HashMap::new(),
         ));
     }
@@ -423,20 +516,25 @@ pub(crate) fn compute_project_activity_unique(
     let output = active_strategy(conn).compute(&intervals, &range);
     let mut bucket_project_seconds = output.bucket_project_seconds;
     let mut total_by_project = output.total_by_project;
+    let mut billable_by_project = output.billable_by_project;
     if rollup_merged {
         fold_merged_series(
             conn,
             &mut bucket_project_seconds,
             &mut total_by_project,
+            &mut billable_by_project,
             &mut series_meta_by_key,
         )?;
     }
     Ok((
-        bucket_project_seconds,
-        total_by_project,
-        series_meta_by_key,
-        output.bucket_flags,
-        output.bucket_comments,
+        (
+            bucket_project_seconds,
+            total_by_project,
+            series_meta_by_key,
+            output.bucket_flags,
+            output.bucket_comments,
+        ),
+        billable_by_project,
     ))
 }
 
@@ -448,6 +546,7 @@ fn fold_merged_series(
     conn: &rusqlite::Connection,
     bucket_project_seconds: &mut BucketDurations,
     total_by_project: &mut ProjectTotals,
+    billable_by_project: &mut ProjectTotals,
     series_meta_by_key: &mut ProjectSeriesMetaMap,
 ) -> Result<(), String> {
     let pairs: Vec<(i64, i64, String, String)> = {
@@ -473,8 +572,10 @@ fn fold_merged_series(
         let child_key = project_series_key(Some(child_id));
         let parent_key = project_series_key(Some(parent_id));
 
-        if let Some(child_seconds) = total_by_project.remove(&child_key) {
-            *total_by_project.entry(parent_key.clone()).or_insert(0.0) += child_seconds;
+        for totals in [&mut *total_by_project, &mut *billable_by_project] {
+            if let Some(child_seconds) = totals.remove(&child_key) {
+                *totals.entry(parent_key.clone()).or_insert(0.0) += child_seconds;
+            }
         }
         for bucket in bucket_project_seconds.values_mut() {
             if let Some(child_seconds) = bucket.remove(&child_key) {
@@ -549,7 +650,9 @@ fn load_project_intervals(
                 p.color as project_color,
                 sp.multiplier,
                 0 as is_manual,
-                sp.comment
+                sp.comment,
+                {SESSION_BILLABLE_SQL} as billable,
+                {SESSION_BACKGROUND_ONLY_SQL} as background
          FROM session_projects sp
          LEFT JOIN projects p ON p.id = sp.project_id AND (?3 = 0 OR p.excluded_at IS NULL)
          WHERE (?4 IS NULL OR sp.project_id = ?4
@@ -565,7 +668,9 @@ fn load_project_intervals(
                 p.color as project_color,
                 1.0 as multiplier,
                 1 as is_manual,
-                ms.title as comment
+                ms.title as comment,
+                COALESCE(ms.billable, p.billable, 1) as billable,
+                0 as background
          FROM manual_sessions ms
          JOIN projects p ON p.id = ms.project_id
          WHERE ms.date >= ?1 AND ms.date <= ?2
@@ -600,6 +705,8 @@ fn load_project_intervals(
                     row.get::<_, f64>("multiplier")?,
                     row.get::<_, i32>("is_manual")?,
                     row.get::<_, Option<String>>("comment")?,
+                    row.get::<_, i64>("billable")? != 0,
+                    row.get::<_, i64>("background")? != 0,
                 ))
             },
         )
@@ -629,6 +736,8 @@ fn load_project_intervals(
             multiplier: row.5,
             is_manual: row.6 != 0,
             comment: row.7,
+            billable: row.8,
+            background: row.9,
         });
     }
     finalize_project_series_labels(&mut series_meta_by_key);
@@ -645,182 +754,313 @@ fn load_project_intervals(
 }
 
 // ---------------------------------------------------------------------------
-// Built-in strategy: wall-clock with overlap deduplication
+// Shared sweep: bucket slicing + overlap resolution
 // ---------------------------------------------------------------------------
 
-struct WallClockStrategy;
-
-impl TimeStrategy for WallClockStrategy {
-    fn id(&self) -> &'static str {
-        "wall_clock"
-    }
-
-    fn name_key(&self) -> &'static str {
-        "settings_page.time_algorithm_wall_clock_name"
-    }
+/// How a stretch during which several projects are active is divided.
+#[derive(Clone, Copy)]
+enum OverlapShare {
+    /// Equally between the concurrently-active projects.
+    Equal,
+    /// In proportion to how many intervals (apps/sessions) of each project
+    /// are active at that moment.
+    ByIntervalCount,
+}
 
-    fn description_key(&self) -> &'static str {
-        "settings_page.time_algorithm_wall_clock_description"
-    }
+/// Slices intervals into buckets and sweeps every bucket, counting each
+/// moment once and dividing it between the projects active at that moment.
+fn sweep_intervals(
+    intervals: &[IntervalInput],
+    range: &ComputeRange,
+    share: OverlapShare,
+) -> ActivityOutput {
+    let mut bucket_pieces: BTreeMap<String, Vec<BucketPiece>> = BTreeMap::new();
+    for interval in intervals {
+        if interval.end <= range.start || interval.start >= range.end_exclusive {
+            continue;
+        }
+        let mut piece_start = if interval.start < range.start {
+            range.start
+        } else {
+            interval.start
+        };
+        let piece_end_limit = if interval.end > range.end_exclusive {
+            range.end_exclusive
+        } else {
+            interval.end
+        };
 
-    fn compute(&self, intervals: &[IntervalInput], range: &ComputeRange) -> ActivityOutput {
-        let mut bucket_pieces: BTreeMap<String, Vec<BucketPiece>> = BTreeMap::new();
-        for interval in intervals {
-            if interval.end <= range.start || interval.start >= range.end_exclusive {
-                continue;
-            }
-            let mut piece_start = if interval.start < range.start {
-                range.start
-            } else {
-                interval.start
+        while piece_start < piece_end_limit {
+            let Some(bucket_start) = bucket_floor(piece_start, range.bucket_kind) else {
+                break;
             };
-            let piece_end_limit = if interval.end > range.end_exclusive {
-                range.end_exclusive
+            let bucket_end = next_bucket(bucket_start, range.bucket_kind);
+            let piece_end = if bucket_end < piece_end_limit {
+                bucket_end
             } else {
-                interval.end
+                piece_end_limit
             };
-
-            while piece_start < piece_end_limit {
-                let Some(bucket_start) = bucket_floor(piece_start, range.bucket_kind) else {
-                    break;
-                };
-                let bucket_end = next_bucket(bucket_start, range.bucket_kind);
-                let piece_end = if bucket_end < piece_end_limit {
-                    bucket_end
-                } else {
-                    piece_end_limit
-                };
-                if piece_end <= piece_start {
-                    break;
-                }
-                bucket_pieces
-                    .entry(bucket_key(bucket_start, range.bucket_kind))
-                    .or_default()
-                    .push(BucketPiece {
-                        start_ms: piece_start.timestamp_millis(),
-                        end_ms: piece_end.timestamp_millis(),
-                        project_key: interval.project_key.clone(),
-                        multiplier: interval.multiplier,
-                        is_manual: interval.is_manual,
-                        comment: interval.comment.clone(),
-                    });
-                piece_start = piece_end;
+            if piece_end <= piece_start {
+                break;
             }
+            bucket_pieces
+                .entry(bucket_key(bucket_start, range.bucket_kind))
+                .or_default()
+                .push(BucketPiece {
+                    start_ms: piece_start.timestamp_millis(),
+                    end_ms: piece_end.timestamp_millis(),
+                    project_key: interval.project_key.clone(),
+                    multiplier: interval.multiplier,
+                    is_manual: interval.is_manual,
+                    comment: interval.comment.clone(),
+                    billable: interval.billable,
+                });
+            piece_start = piece_end;
         }
+    }
 
-        let mut bucket_project_seconds: BucketDurations = BTreeMap::new();
-        let mut total_by_project: ProjectTotals = HashMap::new();
-        let mut bucket_flags: BucketFlags = HashMap::new();
-        let mut bucket_comments: BucketComments = HashMap::new();
+    let mut bucket_project_seconds: BucketDurations = BTreeMap::new();
+    let mut total_by_project: ProjectTotals = HashMap::new();
+    let mut billable_by_project: ProjectTotals = HashMap::new();
+    let mut bucket_flags: BucketFlags = HashMap::new();
+    let mut bucket_comments: BucketComments = HashMap::new();
 
-        for (bucket, slices) in bucket_pieces {
-            if slices.is_empty() {
-                continue;
-            }
+    for (bucket, slices) in bucket_pieces {
+        if slices.is_empty() {
+            continue;
+        }
 
-            let mut has_boost = false;
-            let mut has_manual = false;
-            let mut comments = Vec::new();
-            for s in &slices {
-                if s.multiplier > 1.000_001 {
-                    has_boost = true;
-                }
-                if s.is_manual {
-                    has_manual = true;
-                }
-                if let Some(c) = &s.comment {
-                    if !c.trim().is_empty() {
-                        comments.push(c.clone());
-                    }
-                }
+        let mut has_boost = false;
+        let mut has_manual = false;
+        let mut comments = Vec::new();
+        for s in &slices {
+            if s.multiplier > 1.000_001 {
+                has_boost = true;
             }
-            bucket_flags.insert(bucket.clone(), (has_boost, has_manual));
-            if !comments.is_empty() {
-                comments.sort();
-                comments.dedup();
-                bucket_comments.insert(bucket.clone(), comments);
+            if s.is_manual {
+                has_manual = true;
             }
-
-            let mut events: Vec<(i64, i32, String)> = Vec::with_capacity(slices.len() * 2);
-            for slice in slices {
-                if slice.end_ms <= slice.start_ms {
-                    continue;
+            if let Some(c) = &s.comment {
+                if !c.trim().is_empty() {
+                    comments.push(c.clone());
                 }
-                events.push((slice.start_ms, 1, slice.project_key.clone()));
-                events.push((slice.end_ms, -1, slice.project_key));
             }
-            if events.is_empty() {
+        }
+        bucket_flags.insert(bucket.clone(), (has_boost, has_manual));
+        if !comments.is_empty() {
+            comments.sort();
+            comments.dedup();
+            bucket_comments.insert(bucket.clone(), comments);
+        }
+
+        let mut events: Vec<(i64, i32, String, bool)> = Vec::with_capacity(slices.len() * 2);
+        for slice in slices {
+            if slice.end_ms <= slice.start_ms {
                 continue;
             }
-            events.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
-
-            let mut active: HashMap<String, i32> = HashMap::new();
-            let mut i = 0usize;
-            let mut prev_ms = events[0].0;
-            let mut seconds_for_bucket: HashMap<String, f64> = HashMap::new();
-
-            while i < events.len() {
-                let current_ms = events[i].0;
-                if current_ms > prev_ms && !active.is_empty() {
-                    let delta_seconds = (current_ms - prev_ms) as f64 / 1000.0;
-                    let active_items: Vec<String> = active
-                        .iter()
-                        .filter_map(
-                            |(name, count)| {
-                                if *count > 0 {
-                                    Some(name.clone())
-                                } else {
-                                    None
-                                }
-                            },
-                        )
-                        .collect();
-
-                    if !active_items.is_empty() {
-                        let share = delta_seconds / active_items.len() as f64;
-                        for name in active_items {
-                            *seconds_for_bucket.entry(name.clone()).or_insert(0.0) += share;
-                            *total_by_project.entry(name).or_insert(0.0) += share;
+            events.push((slice.start_ms, 1, slice.project_key.clone(), slice.billable));
+            events.push((slice.end_ms, -1, slice.project_key, slice.billable));
+        }
+        if events.is_empty() {
+            continue;
+        }
+        events.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+
+        // Per project: (active intervals, active billable intervals).
+        let mut active: HashMap<String, (i32, i32)> = HashMap::new();
+        let mut i = 0usize;
+        let mut prev_ms = events[0].0;
+        let mut seconds_for_bucket: HashMap<String, f64> = HashMap::new();
+
+        while i < events.len() {
+            let current_ms = events[i].0;
+            if current_ms > prev_ms && !active.is_empty() {
+                let delta_seconds = (current_ms - prev_ms) as f64 / 1000.0;
+                let active_items: Vec<(String, i32, i32)> = active
+                    .iter()
+                    .filter(|(_, (count, _))| *count > 0)
+                    .map(|(name, (count, billable))| (name.clone(), *count, *billable))
+                    .collect();
+
+                if !active_items.is_empty() {
+                    let weight_total: i32 = match share {
+                        OverlapShare::Equal => active_items.len() as i32,
+                        OverlapShare::ByIntervalCount => {
+                            active_items.iter().map(|(_, count, _)| count).sum()
                         }
+                    };
+                    for (name, count, billable_count) in active_items {
+                        let (weight, billable_weight) = match share {
+                            OverlapShare::Equal => (1, i32::from(billable_count > 0)),
+                            OverlapShare::ByIntervalCount => (count, billable_count),
+                        };
+                        let part = delta_seconds * weight as f64 / weight_total as f64;
+                        if billable_weight > 0 {
+                            *billable_by_project.entry(name.clone()).or_insert(0.0) +=
+                                delta_seconds * billable_weight as f64 / weight_total as f64;
+                        }
+                        *seconds_for_bucket.entry(name.clone()).or_insert(0.0) += part;
+                        *total_by_project.entry(name).or_insert(0.0) += part;
                     }
                 }
-
-                while i < events.len() && events[i].0 == current_ms {
-                    let delta = events[i].1;
-                    let name = events[i].2.clone();
-                    let entry = active.entry(name.clone()).or_insert(0);
-                    *entry += delta;
-                    if *entry <= 0 {
-                        active.remove(&name);
-                    }
-                    i += 1;
-                }
-                prev_ms = current_ms;
             }
 
-            if !seconds_for_bucket.is_empty() {
-                bucket_project_seconds.insert(bucket, seconds_for_bucket);
+            while i < events.len() && events[i].0 == current_ms {
+                let delta = events[i].1;
+                let name = events[i].2.clone();
+                let entry = active.entry(name.clone()).or_insert((0, 0));
+                entry.0 += delta;
+                if events[i].3 {
+                    entry.1 += delta;
+                }
+                if entry.0 <= 0 {
+                    active.remove(&name);
+                }
+                i += 1;
             }
+            prev_ms = current_ms;
         }
 
-        ActivityOutput {
-            bucket_project_seconds,
-            total_by_project,
-            bucket_flags,
-            bucket_comments,
+        if !seconds_for_bucket.is_empty() {
+            bucket_project_seconds.insert(bucket, seconds_for_bucket);
         }
     }
+
+    ActivityOutput {
+        bucket_project_seconds,
+        total_by_project,
+        billable_by_project,
+        bucket_flags,
+        bucket_comments,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Built-in strategy: wall-clock with overlap deduplication
+// ---------------------------------------------------------------------------
+
+struct WallClockStrategy;
+
+impl TimeStrategy for WallClockStrategy {
+    fn id(&self) -> &'static str {
+        "wall_clock"
+    }
+
+    fn name_key(&self) -> &'static str {
+        "settings_page.time_algorithm_wall_clock_name"
+    }
+
+    fn description_key(&self) -> &'static str {
+        "settings_page.time_algorithm_wall_clock_description"
+    }
+
+    fn compute(&self, intervals: &[IntervalInput], range: &ComputeRange) -> ActivityOutput {
+        sweep_intervals(intervals, range, OverlapShare::Equal)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Built-in strategy: proportional split of overlapping time
+// ---------------------------------------------------------------------------
+
+/// Wall-clock time, but a shared stretch goes to each project in proportion
+/// to its concurrently-active intervals: two apps on A next to one on B give
+/// A two thirds instead of half.
+struct ProportionalStrategy;
+
+impl TimeStrategy for ProportionalStrategy {
+    fn id(&self) -> &'static str {
+        "proportional"
+    }
+
+    fn name_key(&self) -> &'static str {
+        "settings_page.time_algorithm_proportional_name"
+    }
+
+    fn description_key(&self) -> &'static str {
+        "settings_page.time_algorithm_proportional_description"
+    }
+
+    fn compute(&self, intervals: &[IntervalInput], range: &ComputeRange) -> ActivityOutput {
+        sweep_intervals(intervals, range, OverlapShare::ByIntervalCount)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Built-in strategy: foreground only
+// ---------------------------------------------------------------------------
+
+/// Wall-clock time without background-CPU-only sessions — only time the user
+/// had the app in front of them (plus manual sessions) counts.
+struct ForegroundExclusiveStrategy;
+
+impl TimeStrategy for ForegroundExclusiveStrategy {
+    fn id(&self) -> &'static str {
+        "foreground_exclusive"
+    }
+
+    fn name_key(&self) -> &'static str {
+        "settings_page.time_algorithm_foreground_exclusive_name"
+    }
+
+    fn description_key(&self) -> &'static str {
+        "settings_page.time_algorithm_foreground_exclusive_description"
+    }
+
+    fn compute(&self, intervals: &[IntervalInput], range: &ComputeRange) -> ActivityOutput {
+        let foreground: Vec<IntervalInput> = intervals
+            .iter()
+            .filter(|interval| !interval.background)
+            .cloned()
+            .collect();
+        sweep_intervals(&foreground, range, OverlapShare::Equal)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
         active_algorithm_id, compute_project_activity_unique, distribute_app_seconds,
-        finalize_project_series_labels, registry, ProjectSeriesMetaMap,
+        finalize_project_series_labels, registry, BucketKind, ComputeRange, IntervalInput,
+        ProjectSeriesMetaMap, ProjectTotals,
     };
     use crate::commands::types::{DateRange, StackedSeriesMeta, TopApp};
+    use chrono::{Local, TimeZone};
     use std::collections::HashMap;
 
+    fn interval(start_hour: u32, end_hour: u32, project: &str, background: bool) -> IntervalInput {
+        IntervalInput {
+            start: Local
+                .with_ymd_and_hms(2026, 3, 2, start_hour, 0, 0)
+                .unwrap(),
+            end: Local.with_ymd_and_hms(2026, 3, 2, end_hour, 0, 0).unwrap(),
+            project_key: project.to_string(),
+            multiplier: 1.0,
+            is_manual: false,
+            comment: None,
+            billable: true,
+            background,
+        }
+    }
+
+    fn run_strategy(id: &str, intervals: &[IntervalInput]) -> (ProjectTotals, ProjectTotals) {
+        let range = ComputeRange {
+            start: Local.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap(),
+            end_exclusive: Local.with_ymd_and_hms(2026, 3, 3, 0, 0, 0).unwrap(),
+            bucket_kind: BucketKind::Day,
+        };
+        let strategy = registry()
+            .into_iter()
+            .find(|s| s.id() == id)
+            .expect("strategy registered");
+        let output = strategy.compute(intervals, &range);
+        (output.total_by_project, output.billable_by_project)
+    }
+
+    fn seconds(totals: &ProjectTotals, key: &str) -> i64 {
+        totals.get(key).copied().unwrap_or_default().round() as i64
+    }
+
     fn setup_conn() -> rusqlite::Connection {
         let conn = rusqlite::Connection::open_in_memory().expect("in-memory db");
         conn.execute_batch(
@@ -829,7 +1069,8 @@ mod tests {
                 name TEXT NOT NULL,
                 color TEXT NOT NULL,
                 excluded_at TEXT,
-                merged_into TEXT
+                merged_into TEXT,
+                billable INTEGER NOT NULL DEFAULT 1
             );
             CREATE TABLE sessions (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -841,7 +1082,8 @@ mod tests {
                 project_id INTEGER,
                 is_hidden INTEGER DEFAULT 0,
                 rate_multiplier REAL NOT NULL DEFAULT 1.0,
-                comment TEXT
+                comment TEXT,
+                billable INTEGER
             );
             CREATE TABLE file_activities (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -860,7 +1102,8 @@ mod tests {
                 start_time TEXT NOT NULL,
                 end_time TEXT NOT NULL,
                 duration_seconds INTEGER NOT NULL,
-                date TEXT NOT NULL
+                date TEXT NOT NULL,
+                billable INTEGER
             );
             CREATE TABLE session_project_cache (
                 session_id INTEGER PRIMARY KEY,
@@ -891,6 +1134,87 @@ mod tests {
         assert_eq!(active_algorithm_id(&conn), "wall_clock");
     }
 
+    #[test]
+    fn proportional_strategy_weights_overlap_by_active_intervals() {
+        // Two apps on A and one on B for the same hour.
+        let mut intervals = vec![
+            interval(9, 10, "a", false),
+            interval(9, 10, "a", false),
+            interval(9, 10, "b", false),
+        ];
+        intervals[2].billable = false;
+
+        let (wall, _) = run_strategy("wall_clock", &intervals);
+        assert_eq!((seconds(&wall, "a"), seconds(&wall, "b")), (1800, 1800));
+
+        let (totals, billable) = run_strategy("proportional", &intervals);
+        assert_eq!((seconds(&totals, "a"), seconds(&totals, "b")), (2400, 1200));
+        assert_eq!(seconds(&billable, "a"), 2400);
+        assert!(!billable.contains_key("b"));
+    }
+
+    #[test]
+    fn foreground_exclusive_strategy_drops_background_only_intervals() {
+        let intervals = vec![interval(9, 10, "a", false), interval(9, 11, "b", true)];
+
+        let (wall, _) = run_strategy("wall_clock", &intervals);
+        assert_eq!((seconds(&wall, "a"), seconds(&wall, "b")), (1800, 5400));
+
+        let (totals, billable) = run_strategy("foreground_exclusive", &intervals);
+        assert_eq!(seconds(&totals, "a"), 3600);
+        assert!(!totals.contains_key("b"));
+        assert_eq!(seconds(&billable, "a"), 3600);
+    }
+
+    #[test]
+    fn selected_strategy_drives_totals_and_background_flag_comes_from_files() {
+        let conn = setup_conn();
+        conn.execute_batch(
+            "CREATE TABLE estimate_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT
+            );
+            INSERT INTO projects (id, name, color) VALUES (1, 'Editor', '#111111');
+            INSERT INTO projects (id, name, color) VALUES (2, 'Render', '#222222');
+            INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, project_id)
+            VALUES (10, '2026-03-01T09:00:00', '2026-03-01T10:00:00', 3600, '2026-03-01', 1);
+            INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, project_id)
+            VALUES (20, '2026-03-01T09:00:00', '2026-03-01T10:00:00', 3600, '2026-03-01', 2);
+            INSERT INTO file_activities (app_id, date, file_name, first_seen, last_seen)
+            VALUES (10, '2026-03-01', 'main.rs', '2026-03-01T09:00:00', '2026-03-01T10:00:00');
+            INSERT INTO file_activities (app_id, date, file_name, first_seen, last_seen)
+            VALUES (20, '2026-03-01', '(background)', '2026-03-01T09:00:00', '2026-03-01T10:00:00');",
+        )
+        .expect("seed");
+        let totals_for = |algorithm: &str| {
+            conn.execute(
+                "INSERT OR REPLACE INTO estimate_settings (key, value) VALUES ('time_algorithm', ?1)",
+                [algorithm],
+            )
+            .expect("set algorithm");
+            assert_eq!(active_algorithm_id(&conn), algorithm);
+            let (_, totals, _, _, _) = compute_project_activity_unique(
+                &conn,
+                &DateRange {
+                    start: "2026-03-01".to_string(),
+                    end: "2026-03-01".to_string(),
+                },
+                false,
+                false,
+                None,
+                None,
+                false,
+            )
+            .expect("compute activity");
+            (seconds(&totals, "project:1"), seconds(&totals, "project:2"))
+        };
+
+        assert_eq!(totals_for("wall_clock"), (1800, 1800));
+        assert_eq!(totals_for("proportional"), (1800, 1800));
+        assert_eq!(totals_for("foreground_exclusive"), (3600, 0));
+    }
+
     #[test]
     fn distribute_app_seconds_scales_down_to_clock_total() {
         let mut apps = vec![
@@ -961,6 +1285,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn overlap_policy_decides_who_keeps_manual_over_tracked_time() {
+        let conn = setup_conn();
+        conn.execute_batch(
+            "CREATE TABLE estimate_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT
+            );
+            INSERT INTO projects (id, name, color) VALUES (1, 'Tracked', '#111111');
+            INSERT INTO projects (id, name, color) VALUES (2, 'Meeting', '#222222');
+            INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, project_id)
+            VALUES (10, '2026-03-01T09:00:00', '2026-03-01T10:00:00', 3600, '2026-03-01', 1);
+            INSERT INTO manual_sessions (title, project_id, start_time, end_time, duration_seconds, date)
+            VALUES ('Call', 2, '2026-03-01T09:30:00', '2026-03-01T10:00:00', 1800, '2026-03-01');",
+        )
+        .expect("seed");
+        let range = DateRange {
+            start: "2026-03-01".to_string(),
+            end: "2026-03-01".to_string(),
+        };
+        let totals_for = |policy: &str, project_filter: Option<i64>| {
+            conn.execute(
+                "INSERT OR REPLACE INTO estimate_settings (key, value) VALUES ('overlap_policy', ?1)",
+                [policy],
+            )
+            .expect("set policy");
+            let (_, totals, _, _, _) = compute_project_activity_unique(
+                &conn,
+                &range,
+                false,
+                false,
+                project_filter,
+                None,
+                false,
+            )
+            .expect("compute activity");
+            (
+                totals.get("project:1").copied().unwrap_or_default().round() as i64,
+                totals.get("project:2").copied().unwrap_or_default().round() as i64,
+            )
+        };
+
+        assert_eq!(totals_for("count_both", None), (2700, 900));
+        assert_eq!(totals_for("manual_wins", None), (1800, 1800));
+        assert_eq!(totals_for("tracked_wins", None), (3600, 0));
+        assert_eq!(totals_for("manual_wins", Some(1)), (1800, 0));
+    }
+
     #[test]
     fn wall_clock_strategy_keeps_clock_time_separate_from_boost() {
         let conn = setup_conn();
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::{BTreeMap, HashMap};
 pub use timeflow_shared::monitored_app::MonitoredApp;
 
@@ -28,11 +28,34 @@ pub struct Project {
     pub created_at: String,
     pub excluded_at: Option<String>,
     pub frozen_at: Option<String>,
-    #[serde(default)]
-    pub merged_into: Option<String>,
-    #[serde(default)]
-    pub merged_at: Option<String>,
+    /// Nazwa projektu nadrzędnego (m23). Jak `client_name`: `Some(None)` =
+    /// jawnie rozłączony, `None` = archiwum bez klucza — import zostawia
+    /// lokalną wartość.
+    #[serde(
+        default,
+        deserialize_with = "present_field",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub merged_into: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "present_field",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub merged_at: Option<Option<String>>,
     pub assigned_folder_path: Option<String>,
+    /// Klient projektu (m24): `Some(None)` = odpięty od klienta, `None` =
+    /// archiwum sprzed m24.
+    #[serde(
+        default,
+        deserialize_with = "present_field",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub client_name: Option<Option<String>>,
+    /// Domyślna flaga billable (m34). `None` = archiwum sprzed m34 — import
+    /// zostawia lokalną wartość.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billable: Option<bool>,
     #[serde(default)]
     pub is_imported: i64,
     #[serde(default)]
@@ -54,6 +77,9 @@ pub struct ProjectWithStats {
     pub app_count: i64,
     pub last_activity: Option<String>,
     pub assigned_folder_path: Option<String>,
+    /// Selective sync: projekt nigdy nie opuszcza tej maszyny (m28).
+    #[serde(default)]
+    pub device_local: bool,
     /// Surowe sekundy (dedup) per kalendarzowy dzień — do zaokrąglania `per_day`.
     #[serde(default)]
     pub daily_seconds: Vec<i64>,
@@ -71,6 +97,8 @@ pub struct AppWithStats {
     pub project_name: Option<String>,
     pub project_color: Option<String>,
     pub color: Option<String>,
+    /// Selective sync: aplikacja (i jej sesje) nie opuszcza tej maszyny (m28).
+    pub device_local: bool,
     /// Surowe sekundy per kalendarzowy dzień — do zaokrąglania `per_day`.
     #[serde(default)]
     pub daily_seconds: Vec<i64>,
@@ -97,6 +125,10 @@ pub struct SessionWithApp {
     /// true when the most recent assignment_feedback for this session has source = 'auto_accept'
     pub ai_assigned: bool,
     pub comment: Option<String>,
+    /// Tag names, alphabetically (m33).
+    pub tags: Vec<String>,
+    /// Per-session override (m34); `None` inherits the project's default.
+    pub billable: Option<bool>,
 }
 
 #[derive(Serialize, Clone)]
@@ -124,6 +156,9 @@ pub struct DashboardStats {
     /// Łączny czas per kalendarzowy dzień (suma projektów w dniu) — do `per_day`.
     #[serde(default)]
     pub daily_seconds: Vec<i64>,
+    /// Czas per tag (sesje + sesje manualne), malejąco.
+    #[serde(default)]
+    pub top_tags: Vec<TagTime>,
 }
 
 #[derive(Serialize)]
@@ -184,6 +219,18 @@ pub struct ProjectReportData {
     pub estimate: f64,
     pub sessions: Vec<SessionWithApp>,
     pub manual_sessions: Vec<ManualSessionWithProject>,
+    pub tag_breakdown: Vec<TagTime>,
+    /// Billable / non-billable split of the project's time in the report range.
+    pub billable_seconds: i64,
+    pub non_billable_seconds: i64,
+    /// Overlap policy the totals were computed with, and the overlaps
+    /// touching this project in the report range.
+    pub overlap_policy: String,
+    pub overlaps: Vec<SessionOverlap>,
+    /// `project.total_seconds` and `estimate` after the user's rounding
+    /// settings; equal to the raw values when rounding is off.
+    pub rounded_total_seconds: i64,
+    pub rounded_estimate: f64,
 }
 
 #[derive(Serialize)]
@@ -223,6 +270,15 @@ pub struct EstimateProjectRow {
     pub project_color: String,
     pub seconds: i64,
     pub hours: f64,
+    /// Część `seconds` liczona do wyceny (m34); reszta w `non_billable_seconds`.
+    #[serde(default)]
+    pub billable_seconds: i64,
+    #[serde(default)]
+    pub non_billable_seconds: i64,
+    /// Domyślna flaga projektu — sesje mogą ją nadpisać.
+    #[serde(default = "default_billable")]
+    pub billable: bool,
+    /// Godziny do wyceny: billable + dodatek z mnożników (tylko sesji billable).
     pub weighted_hours: f64,
     pub project_hourly_rate: Option<f64>,
     pub effective_hourly_rate: f64,
@@ -239,15 +295,30 @@ pub struct EstimateProjectRow {
     /// Rozbicie czasu na dni z ETYKIETAMI DAT (YYYY-MM-DD), chronologicznie. Dla raportu
     /// estymacji w wariancie „plus" (projekt → dni z godzinami). Pomija dni z 0 s.
     pub days: Vec<EstimateDay>,
+    /// `seconds` / `estimated_value` po zaokrągleniu z ustawień użytkownika
+    /// (ta sama reguła co tabela na froncie). Równe surowym, gdy wyłączone.
+    #[serde(default)]
+    pub rounded_seconds: i64,
+    #[serde(default)]
+    pub rounded_value: f64,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct EstimateSummary {
     pub total_seconds: i64,
     pub total_hours: f64,
+    #[serde(default)]
+    pub billable_seconds: i64,
+    #[serde(default)]
+    pub non_billable_seconds: i64,
     pub total_value: f64,
     pub projects_count: i64,
     pub overrides_count: i64,
+    /// Suma zaokrąglonych wierszy (`rounded_seconds` / `rounded_value`).
+    #[serde(default)]
+    pub rounded_seconds: i64,
+    #[serde(default)]
+    pub rounded_value: f64,
 }
 
 #[derive(Serialize)]
@@ -283,6 +354,9 @@ pub struct SessionFilters {
     pub include_files: Option<bool>,
     #[serde(rename = "includeAiSuggestions")]
     pub include_ai_suggestions: Option<bool>,
+    /// Sesje z dowolnym z tych tagów.
+    #[serde(rename = "tagIds")]
+    pub tag_ids: Option<Vec<i64>>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
@@ -419,6 +493,18 @@ pub struct ManualSession {
     pub created_at: String,
     #[serde(default)]
     pub updated_at: String,
+    /// Nazwy tagów (m33). `None` = archiwum sprzed tagów — import zostawia
+    /// lokalne powiązania bez zmian.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// Nadpisanie billable (m34): `Some(None)` = dziedziczy po projekcie,
+    /// `None` = archiwum sprzed m34.
+    #[serde(
+        default,
+        deserialize_with = "present_field",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub billable: Option<Option<bool>>,
 }
 
 #[derive(Serialize)]
@@ -434,6 +520,9 @@ pub struct ManualSessionWithProject {
     pub end_time: String,
     pub duration_seconds: i64,
     pub date: String,
+    pub tags: Vec<String>,
+    /// Per-session override (m34); `None` inherits the project's default.
+    pub billable: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -452,6 +541,85 @@ pub struct ManualSessionFilters {
     pub date_range: Option<DateRange>,
     #[serde(rename = "projectId")]
     pub project_id: Option<i64>,
+    #[serde(rename = "tagIds")]
+    pub tag_ids: Option<Vec<i64>>,
+}
+
+// ==================== Untracked Gaps ====================
+
+/// Workday window in local time, `HH:MM` (same shape as the frontend setting).
+#[derive(Deserialize, Clone, Debug)]
+pub struct WorkingHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// A project proposed for filling a gap, taken from a neighbouring session.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GapFillCandidate {
+    pub project_id: i64,
+    pub project_name: String,
+    pub project_color: String,
+    /// "previous", "next" or "both" — which neighbour used this project.
+    pub source: String,
+}
+
+/// Untracked stretch of working hours. `start_time`/`end_time` are local
+/// `YYYY-MM-DDTHH:MM:SS`, ready to be passed back as a manual session.
+#[derive(Serialize, Clone, Debug)]
+pub struct UntrackedGap {
+    pub date: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub duration_seconds: i64,
+    /// "sleep", "daemon_off", "idle", "pause" or "unknown" (no tracker history).
+    pub kind: String,
+    pub suggested_project_id: Option<i64>,
+    pub candidates: Vec<GapFillCandidate>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct UntrackedGapReport {
+    pub gaps: Vec<UntrackedGap>,
+    pub total_seconds: i64,
+    pub sleep_seconds: i64,
+    pub daemon_off_seconds: i64,
+    pub idle_seconds: i64,
+    pub pause_seconds: i64,
+    pub unknown_seconds: i64,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct GapFillInput {
+    pub start_time: String,
+    pub end_time: String,
+    pub project_id: i64,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+// ==================== Overlaps ====================
+
+/// A stretch covered by two sessions at once. The first side is always a
+/// tracked session; the other is a manual session ("manual_tracked") or a
+/// tracked session of another app on the same project ("cross_app").
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SessionOverlap {
+    pub kind: String,
+    pub date: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub overlap_seconds: i64,
+    pub session_id: i64,
+    pub app_name: String,
+    pub project_id: Option<i64>,
+    pub project_name: Option<String>,
+    pub other_session_id: i64,
+    pub other_is_manual: bool,
+    /// Manual session title or the other app's name.
+    pub other_label: String,
+    pub other_project_id: Option<i64>,
+    pub other_project_name: Option<String>,
 }
 
 // ==================== Export/Import Archive Types ====================
@@ -473,6 +641,10 @@ pub struct ExportMetadata {
     pub project_name: Option<String>,
     pub total_sessions: i64,
     pub total_seconds: i64,
+    /// `total_seconds` zaokrąglone wg ustawień eksportującego (per sesja /
+    /// dzień / suma). Archiwa sprzed zaokrąglania: 0.
+    #[serde(default)]
+    pub rounded_total_seconds: i64,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -489,6 +661,16 @@ pub struct ExportData {
     pub assignment_auto_runs: Vec<AssignmentAutoRunRow>,
     #[serde(default)]
     pub file_activities: Vec<FileActivityExportRow>,
+    #[serde(default)]
+    pub clients: Vec<ClientRow>,
+    #[serde(default)]
+    pub estimate_settings: Vec<EstimateSettingRow>,
+    #[serde(default)]
+    pub tags: Vec<TagRow>,
+    /// Audit trail of the exported sessions (m31). For the record only —
+    /// import does not replay it, session ids are local to the source.
+    #[serde(default)]
+    pub session_audit_log: Vec<SessionAuditEntry>,
 }
 
 /// Wiersz `file_activities` w archiwum eksportu — wszystkie kolumny tabeli
@@ -521,6 +703,61 @@ fn default_activity_spans() -> String {
     "[]".to_string()
 }
 
+/// Wiersz `clients` w archiwum — bez lokalnego `id`; klucz synchronizacji to
+/// `name` (jak w projektach), rozstrzyganie LWW po `updated_at`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ClientRow {
+    pub name: String,
+    #[serde(default)]
+    pub contact: Option<String>,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub tax_id: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub default_hourly_rate: Option<f64>,
+    #[serde(default = "default_client_color")]
+    pub color: String,
+    #[serde(default)]
+    pub archived_at: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+fn default_client_color() -> String {
+    "#38bdf8".to_string()
+}
+
+/// Wiersz `tags` w archiwum — klucz synchronizacji to `name`, LWW po
+/// `updated_at`. Powiązania jadą jako lista nazw na wierszach sesji.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TagRow {
+    pub name: String,
+    #[serde(default = "default_tag_color")]
+    pub color: String,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+pub(crate) fn default_tag_color() -> String {
+    "#a78bfa".to_string()
+}
+
+/// Wiersz `estimate_settings` w archiwum (stawka globalna, algorytm czasu, ...).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EstimateSettingRow {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Tombstone {
     #[serde(default)]
@@ -567,12 +804,35 @@ pub struct SessionRow {
     /// zawiedzie (ghost names), albo przynajmniej zachować etykietę.
     #[serde(default)]
     pub project_name: Option<String>,
+    /// Nazwy tagów (m33); `None` w archiwach sprzed tagów.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// Nadpisanie billable (m34), jak w `ManualSession::billable`.
+    #[serde(
+        default,
+        deserialize_with = "present_field",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub billable: Option<Option<bool>>,
 }
 
 fn default_rate_multiplier() -> f64 {
     1.0
 }
 
+fn default_billable() -> bool {
+    true
+}
+
+/// Tells an explicit `null` (`Some(None)`) apart from a missing key (`None`).
+fn present_field<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 // ==================== Multi-Project Split Types ====================
 
 #[derive(Serialize)]
@@ -642,6 +902,27 @@ pub struct AssignmentFeedbackRow {
     pub created_at: String,
 }
 
+/// One changed field of a manual session edit (`session_audit_log`, m31).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SessionAuditEntry {
+    pub id: i64,
+    pub session_id: i64,
+    /// Project of the session after the change.
+    pub project_id: Option<i64>,
+    /// Project before the change — differs only for reassignments.
+    pub previous_project_id: Option<i64>,
+    /// "assign", "override", "rate_multiplier", "comment", "tag", "split",
+    /// "merge", "delete", "undo" or "redo".
+    pub action: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    /// Assignment source, e.g. "manual_session_assign".
+    pub source: Option<String>,
+    pub device_id: String,
+    pub created_at: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AssignmentAutoRunRow {
     pub id: i64,
@@ -653,6 +934,31 @@ pub struct AssignmentAutoRunRow {
     pub rolled_back_at: Option<String>,
 }
 
+// ==================== Tags ====================
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+    pub session_count: i64,
+    pub manual_session_count: i64,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+/// Time per tag. A session with several tags counts towards each of them.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TagTime {
+    pub tag_id: i64,
+    pub name: String,
+    pub color: String,
+    pub seconds: i64,
+    pub session_count: i64,
+}
+
 // ==================== Clients ====================
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -695,6 +1001,10 @@ pub struct ClientProjectSummary {
     pub status: String,
     pub seconds: i64,
     pub value: f64,
+    #[serde(default)]
+    pub billable_seconds: i64,
+    #[serde(default)]
+    pub non_billable_seconds: i64,
     /// Surowe sekundy per kalendarzowy dzień — do zaokrąglania `per_day`.
     #[serde(default)]
     pub daily_seconds: Vec<i64>,
@@ -708,6 +1018,8 @@ pub struct ClientSummary {
     pub project_count: i64,
     pub total_seconds: i64,
     pub total_value: f64,
+    pub billable_seconds: i64,
+    pub non_billable_seconds: i64,
     pub active_value: f64,
     pub done_value: f64,
     pub paid_value: f64,
@@ -18,6 +18,13 @@ fn read_object(base: &std::path::Path) -> Map<String, Value> {
     }
 }
 
+/// Pojedyncze ustawienie z magazynu (`None`, gdy plik lub klucz nie istnieje).
+/// Backend czyta stąd ustawienia UI, które wpływają na liczone wartości.
+pub(crate) fn read_user_setting(key: &str) -> Option<Value> {
+    let base = timeflow_data_dir().ok()?;
+    read_object(&base).remove(key)
+}
+
 /// Cały magazyn ustawień jako obiekt JSON klucz→wartość (pusty obiekt, gdy plik
 /// nie istnieje). Frontend hydratuje z tego swój lokalny cache przy starcie.
 #[tauri::command]
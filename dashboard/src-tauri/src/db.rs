@@ -403,6 +403,9 @@ fn initialize_database_file(path_str: &str) -> Result<(), String> {
     // Run migrations for existing databases
     run_migrations(&db).map_err(|e| format!("Migration error: {}", e))?;
     ensure_post_migration_indexes(&db).map_err(|e| format!("Index creation error: {}", e))?;
+    // HLC node = this device's sync id, also for a restored or copied DB.
+    timeflow_shared::sync_hlc::set_node(&db, &crate::commands::local_device_id())
+        .map_err(|e| format!("HLC node error: {}", e))?;
 
     Ok(())
 }
@@ -614,6 +617,7 @@ mod tests {
             "project_id",
             "project_name",
             "updated_at",
+            "hlc",
         ];
         let actual: std::collections::HashSet<String> = {
             let mut stmt = conn
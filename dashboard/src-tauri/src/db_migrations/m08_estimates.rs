@@ -7,9 +7,10 @@ pub fn run(db: &rusqlite::Connection) -> Result<(), rusqlite::Error> {
         )",
     )?;
 
-    // Seed default global hourly rate
+    // Seed default global hourly rate. Epoch updated_at: the seed must lose
+    // sync LWW against any rate the user actually set on another machine.
     db.execute(
-        "INSERT OR IGNORE INTO estimate_settings (key, value, updated_at) VALUES ('global_hourly_rate', '100', datetime('now'))",
+        "INSERT OR IGNORE INTO estimate_settings (key, value, updated_at) VALUES ('global_hourly_rate', '100', '1970-01-01 00:00:00')",
         [],
     )?;
 
@@ -3,14 +3,13 @@ use rusqlite::Connection;
 /// m24: clients entity + project↔client link + project status.
 ///
 /// - `clients`: full client entity. `name` is UNIQUE and is the stable key used
-///   for the project link (and for future cross-machine sync, mirroring how
-///   projects are identified by name). `updated_at` is carried for future LWW.
+///   for the project link (and for cross-machine sync, mirroring how
+///   projects are identified by name). `updated_at` drives LWW.
 /// - `projects.client_name`: links a project to a client by NAME (portable; an
 ///   integer id would differ per machine once sync is added).
 /// - `projects.status`: 'active' | 'done' | 'paid' ("zrealizowane" = paid).
 ///
-/// NOTE: clients do NOT participate in sync merge yet (see C1b) — they are local
-/// until the merge + tombstone wiring is added on both daemon/dashboard mirrors.
+/// Sync merge + tombstone wiring for clients is added by m25.
 ///
 /// ALTER statements are guarded by pragma_table_info checks (idempotent).
 pub fn run(tx: &Connection) -> Result<(), rusqlite::Error> {
@@ -0,0 +1,17 @@
+use rusqlite::Connection;
+
+use super::tombstone_triggers;
+
+/// m25: clients + estimate_settings join sync merge.
+///
+/// - `trg_clients_tombstone`: client deletions propagate like project ones
+///   (sync_key = client name).
+/// - Existing `estimate_settings` rows are left alone: a seeded default rate
+///   cannot be told apart from a rate the user deliberately set to the same
+///   value, so they take part in the normal LWW. Fresh installs seed the
+///   default with an epoch `updated_at` (m08) and lose to any real edit.
+pub fn run(tx: &Connection) -> Result<(), rusqlite::Error> {
+    tx.execute(tombstone_triggers::DROP_CLIENTS_TOMBSTONE_TRIGGER_SQL, [])?;
+    tx.execute(tombstone_triggers::CLIENTS_TOMBSTONE_TRIGGER_SQL, [])?;
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use rusqlite::Connection;
+
+/// m26: review state for the sync conflict queue (`sync_merge_log`).
+///
+/// - `reviewed_at` — the user acknowledged the automatic resolution.
+/// - `flipped_at` — the user reversed it; the losing values were written back
+///   as a regular edit (fresh `updated_at`) so they propagate on the next sync.
+///
+/// Conflict values themselves live in the existing `details` column (JSON
+/// `{"local": {...}, "incoming": {...}}`, written by the daemon merge).
+pub fn run(db: &Connection) -> Result<(), rusqlite::Error> {
+    for column in ["reviewed_at", "flipped_at"] {
+        let exists: bool = db
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('sync_merge_log') WHERE name = ?1",
+                [column],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|c| c > 0)
+            .unwrap_or(false);
+        if !exists {
+            db.execute_batch(&format!(
+                "ALTER TABLE sync_merge_log ADD COLUMN {} TEXT DEFAULT NULL;",
+                column
+            ))?;
+        }
+    }
+    db.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_sync_merge_log_reviewed ON sync_merge_log(reviewed_at);",
+    )?;
+    Ok(())
+}
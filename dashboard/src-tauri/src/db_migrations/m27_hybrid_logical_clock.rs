@@ -0,0 +1,14 @@
+use rusqlite::Connection;
+
+/// m27: hybrid logical clocks on every synced row.
+///
+/// Adds `hlc` to projects, applications, sessions, manual_sessions, clients,
+/// estimate_settings and tombstones, the shared `sync_clock` table and the
+/// stamping triggers. Existing rows are seeded from `updated_at`/`deleted_at`
+/// (counter 0, no node), so the first sync after the upgrade resolves exactly
+/// like before; from then on the daemon merge compares clocks instead of
+/// timestamp strings. The DDL is shared with the daemon's defensive guard
+/// (`timeflow_shared::sync_hlc::ensure_schema`).
+pub fn run(db: &Connection) -> Result<(), rusqlite::Error> {
+    timeflow_shared::sync_hlc::ensure_schema(db)
+}
@@ -0,0 +1,28 @@
+use rusqlite::Connection;
+
+/// m28: selective sync — `device_local` on projects and applications.
+///
+/// A device-local project (or application) never leaves this machine: the
+/// daemon export (`build_delta_for_pull`) skips the row itself, everything
+/// attributed to it (sessions, manual sessions, file activities, apps
+/// assigned to a device-local project) and its tombstones; the merge never
+/// applies a peer tombstone to such rows. The flag itself is not synced.
+pub fn run(db: &Connection) -> Result<(), rusqlite::Error> {
+    for table in ["projects", "applications"] {
+        let exists: bool = db
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = 'device_local'",
+                [table],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|c| c > 0)
+            .unwrap_or(false);
+        if !exists {
+            db.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN device_local INTEGER NOT NULL DEFAULT 0;",
+                table
+            ))?;
+        }
+    }
+    Ok(())
+}
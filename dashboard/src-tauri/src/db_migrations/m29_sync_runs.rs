@@ -0,0 +1,38 @@
+use rusqlite::Connection;
+
+/// m29: structured sync history — one row per LAN / online sync run.
+///
+/// Written by the daemon when a run finishes (`sync_history::record`, which
+/// also creates the table on databases the dashboard has not migrated yet —
+/// keep both definitions identical). Rows older than
+/// `LogSettings::sync_history_retention_days` are pruned by the daemon on
+/// every write; `clear_sync_history` prunes on demand.
+pub fn run(db: &Connection) -> Result<(), rusqlite::Error> {
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sync_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            role TEXT NOT NULL DEFAULT '',
+            peer_id TEXT NOT NULL DEFAULT '',
+            mode TEXT NOT NULL DEFAULT '',
+            dry_run INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL,
+            error TEXT,
+            step_reached INTEGER NOT NULL DEFAULT 0,
+            phase TEXT NOT NULL DEFAULT '',
+            started_at TEXT NOT NULL,
+            finished_at TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            bytes_sent INTEGER NOT NULL DEFAULT 0,
+            bytes_received INTEGER NOT NULL DEFAULT 0,
+            rows_inserted INTEGER NOT NULL DEFAULT 0,
+            rows_updated INTEGER NOT NULL DEFAULT 0,
+            rows_deleted INTEGER NOT NULL DEFAULT 0,
+            tombstones_added INTEGER NOT NULL DEFAULT 0,
+            conflicts INTEGER NOT NULL DEFAULT 0,
+            table_stats TEXT NOT NULL DEFAULT '{}'
+        );
+        CREATE INDEX IF NOT EXISTS idx_sync_runs_started_at ON sync_runs(started_at);
+        CREATE INDEX IF NOT EXISTS idx_sync_runs_peer ON sync_runs(peer_id, started_at);",
+    )
+}
@@ -0,0 +1,31 @@
+use rusqlite::Connection;
+
+/// m30: undo / redo journal for user actions (`commands::mutation_journal`).
+///
+/// One `mutation_journal` row per action; `mutation_journal_rows` keeps the
+/// before / after image (JSON, without `updated_at` / `hlc`) of every row the
+/// action changed — NULL before = row created, NULL after = row deleted.
+/// Device-local: never exported by sync.
+pub fn run(db: &Connection) -> Result<(), rusqlite::Error> {
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS mutation_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            action TEXT NOT NULL,
+            label TEXT NOT NULL DEFAULT '',
+            row_count INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            undone_at TEXT
+        );
+        CREATE TABLE IF NOT EXISTS mutation_journal_rows (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            journal_id INTEGER NOT NULL,
+            seq INTEGER NOT NULL,
+            table_name TEXT NOT NULL,
+            row_id INTEGER NOT NULL,
+            before_json TEXT,
+            after_json TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_mutation_journal_rows_journal
+        ON mutation_journal_rows(journal_id, seq);",
+    )
+}
@@ -0,0 +1,43 @@
+use rusqlite::Connection;
+
+/// m31: append-only audit trail of manual session edits
+/// (`commands::session_audit`).
+///
+/// One row per changed field: the value before and after, the project the
+/// session belonged to before and after, the assignment source, the device
+/// and the time. UPDATE / DELETE abort, so the trail can only grow — it is
+/// what a disputed invoice gets checked against. Exported with the archive,
+/// never synced.
+pub fn run(db: &Connection) -> Result<(), rusqlite::Error> {
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session_audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL,
+            project_id INTEGER,
+            previous_project_id INTEGER,
+            action TEXT NOT NULL,
+            field TEXT NOT NULL,
+            old_value TEXT,
+            new_value TEXT,
+            source TEXT,
+            device_id TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_audit_log_session
+        ON session_audit_log(session_id, id);
+        CREATE INDEX IF NOT EXISTS idx_session_audit_log_project
+        ON session_audit_log(project_id);
+        CREATE INDEX IF NOT EXISTS idx_session_audit_log_previous_project
+        ON session_audit_log(previous_project_id);
+        CREATE TRIGGER IF NOT EXISTS trg_session_audit_log_no_update
+        BEFORE UPDATE ON session_audit_log
+        BEGIN
+            SELECT RAISE(ABORT, 'session_audit_log is append-only');
+        END;
+        CREATE TRIGGER IF NOT EXISTS trg_session_audit_log_no_delete
+        BEFORE DELETE ON session_audit_log
+        BEGIN
+            SELECT RAISE(ABORT, 'session_audit_log is append-only');
+        END;",
+    )
+}
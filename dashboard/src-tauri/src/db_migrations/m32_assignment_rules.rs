@@ -0,0 +1,29 @@
+use rusqlite::Connection;
+
+/// m32: saved assignment rules (`commands::assignment_model::rules`).
+///
+/// "App X with a title / path matching regex Y in date range Z → project P".
+/// Every condition is optional except the target project. `keep_active`
+/// rules also run on new unassigned sessions, ahead of the AI model.
+/// Device-local, never synced.
+pub fn run(db: &Connection) -> Result<(), rusqlite::Error> {
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS assignment_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            app_id INTEGER,
+            pattern TEXT,
+            date_from TEXT,
+            date_to TEXT,
+            project_id INTEGER NOT NULL,
+            keep_active INTEGER NOT NULL DEFAULT 0,
+            last_applied_at TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (app_id) REFERENCES applications(id) ON DELETE CASCADE,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_assignment_rules_active
+        ON assignment_rules(keep_active);",
+    )
+}
@@ -0,0 +1,45 @@
+use rusqlite::Connection;
+
+use super::tombstone_triggers;
+
+/// m33: tags on sessions and manual sessions (`commands::tags`).
+///
+/// - `tags`: `name` is UNIQUE (case-insensitive) and is the sync key, the same
+///   way clients and projects are identified across machines.
+///   `updated_at` drives LWW.
+/// - `session_tags` / `manual_session_tags`: many-to-many links. Sync carries
+///   them as a list of tag names on each session row, so link edits bump the
+///   owning row's `updated_at`.
+/// - `trg_tags_tombstone`: tag deletions propagate (sync_key = tag name).
+pub fn run(tx: &Connection) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT NOT NULL DEFAULT '#a78bfa',
+            created_at TEXT,
+            updated_at TEXT NOT NULL DEFAULT '1970-01-01 00:00:00'
+        );
+        CREATE TABLE IF NOT EXISTS session_tags (
+            session_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (session_id, tag_id),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag_id);
+        CREATE TABLE IF NOT EXISTS manual_session_tags (
+            manual_session_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (manual_session_id, tag_id),
+            FOREIGN KEY (manual_session_id) REFERENCES manual_sessions(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_manual_session_tags_tag
+        ON manual_session_tags(tag_id);",
+    )?;
+
+    tx.execute(tombstone_triggers::DROP_TAGS_TOMBSTONE_TRIGGER_SQL, [])?;
+    tx.execute(tombstone_triggers::TAGS_TOMBSTONE_TRIGGER_SQL, [])?;
+    Ok(())
+}
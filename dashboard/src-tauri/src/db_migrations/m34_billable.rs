@@ -0,0 +1,30 @@
+use rusqlite::Connection;
+
+/// m34: billable vs non-billable time.
+///
+/// - `projects.billable`: project default, billable unless switched off.
+/// - `sessions.billable` / `manual_sessions.billable`: per-row override;
+///   NULL inherits the project default, 0/1 wins over it.
+///
+/// Estimates value only billable time; non-billable hours are reported
+/// separately. ALTERs are guarded by pragma_table_info checks (idempotent).
+pub fn run(tx: &Connection) -> Result<(), rusqlite::Error> {
+    for (table, definition) in [
+        ("projects", "billable INTEGER NOT NULL DEFAULT 1"),
+        ("sessions", "billable INTEGER"),
+        ("manual_sessions", "billable INTEGER"),
+    ] {
+        let exists: bool = tx
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = 'billable'",
+                [table],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|c| c > 0)
+            .unwrap_or(false);
+        if !exists {
+            tx.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {};", table, definition))?;
+        }
+    }
+    Ok(())
+}
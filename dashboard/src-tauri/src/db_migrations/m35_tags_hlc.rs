@@ -0,0 +1,11 @@
+use rusqlite::Connection;
+
+/// m35: hybrid logical clocks on tags.
+///
+/// Tags (m33) arrived after m27, so their rows resolved conflicts by
+/// `updated_at` strings. Re-running the shared guard adds `tags.hlc`, seeds it
+/// from `updated_at` and installs the stamping triggers; the other tables are
+/// already installed and left as they are.
+pub fn run(db: &Connection) -> Result<(), rusqlite::Error> {
+    timeflow_shared::sync_hlc::ensure_schema(db)
+}
@@ -23,8 +23,19 @@ mod m21_tombstone_session_sync_key;
 mod m22_updated_at_indexes;
 mod m23_project_merge;
 mod m24_clients;
-
-pub(crate) const LATEST_SCHEMA_VERSION: i64 = 24;
+mod m25_sync_clients;
+mod m26_sync_conflict_review;
+mod m27_hybrid_logical_clock;
+mod m28_device_local;
+mod m29_sync_runs;
+mod m30_mutation_journal;
+mod m31_session_audit_log;
+mod m32_assignment_rules;
+mod m33_tags;
+mod m34_billable;
+mod m35_tags_hlc;
+
+pub(crate) const LATEST_SCHEMA_VERSION: i64 = 35;
 
 pub fn run_migrations(db: &rusqlite::Connection) -> Result<(), rusqlite::Error> {
     db.execute_batch(
@@ -126,6 +137,40 @@ pub fn run_migrations(db: &rusqlite::Connection) -> Result<(), rusqlite::Error>
     if current_version < 24 {
         m24_clients::run(&tx)?;
     }
+    if current_version < 25 {
+        m25_sync_clients::run(&tx)?;
+    }
+    if current_version < 26 {
+        m26_sync_conflict_review::run(&tx)?;
+    }
+    if current_version < 27 {
+        m27_hybrid_logical_clock::run(&tx)?;
+    }
+    if current_version < 28 {
+        m28_device_local::run(&tx)?;
+    }
+
+    if current_version < 29 {
+        m29_sync_runs::run(&tx)?;
+    }
+    if current_version < 30 {
+        m30_mutation_journal::run(&tx)?;
+    }
+    if current_version < 31 {
+        m31_session_audit_log::run(&tx)?;
+    }
+    if current_version < 32 {
+        m32_assignment_rules::run(&tx)?;
+    }
+    if current_version < 33 {
+        m33_tags::run(&tx)?;
+    }
+    if current_version < 34 {
+        m34_billable::run(&tx)?;
+    }
+    if current_version < 35 {
+        m35_tags_hlc::run(&tx)?;
+    }
 
     tx.execute(
         "INSERT OR REPLACE INTO schema_version (rowid, version) VALUES (1, ?1)",
@@ -67,18 +67,49 @@ pub(crate) const MANUAL_SESSIONS_TOMBSTONE_TRIGGER_SQL: &str =
          VALUES ('manual_sessions', OLD.id, OLD.project_id || '|' || OLD.start_time || '|' || OLD.title);
      END;";
 
-/// All four production tombstone triggers — for code paths that must run
+pub(crate) const DROP_CLIENTS_TOMBSTONE_TRIGGER_SQL: &str =
+    "DROP TRIGGER IF EXISTS trg_clients_tombstone";
+
+/// Version from m25 — sync_key = client name (clients table comes from m24,
+/// so this trigger is not part of schema.sql).
+pub(crate) const CLIENTS_TOMBSTONE_TRIGGER_SQL: &str =
+    "CREATE TRIGGER IF NOT EXISTS trg_clients_tombstone
+     AFTER DELETE ON clients
+     FOR EACH ROW
+     BEGIN
+         INSERT INTO tombstones (table_name, record_id, sync_key)
+         VALUES ('clients', OLD.id, OLD.name);
+     END;";
+
+pub(crate) const DROP_TAGS_TOMBSTONE_TRIGGER_SQL: &str =
+    "DROP TRIGGER IF EXISTS trg_tags_tombstone";
+
+/// Version from m33 — sync_key = tag name (not part of schema.sql).
+pub(crate) const TAGS_TOMBSTONE_TRIGGER_SQL: &str =
+    "CREATE TRIGGER IF NOT EXISTS trg_tags_tombstone
+     AFTER DELETE ON tags
+     FOR EACH ROW
+     BEGIN
+         INSERT INTO tombstones (table_name, record_id, sync_key)
+         VALUES ('tags', OLD.id, OLD.name);
+     END;";
+
+/// All six production tombstone triggers — for code paths that must run
 /// technical (non-user-intent) DELETEs without minting tombstones.
-pub(crate) const DROP_ALL_TOMBSTONE_TRIGGERS_SQL: [&str; 4] = [
+pub(crate) const DROP_ALL_TOMBSTONE_TRIGGERS_SQL: [&str; 6] = [
     DROP_SESSIONS_TOMBSTONE_TRIGGER_SQL,
     DROP_APPLICATIONS_TOMBSTONE_TRIGGER_SQL,
     DROP_PROJECTS_TOMBSTONE_TRIGGER_SQL,
     DROP_MANUAL_SESSIONS_TOMBSTONE_TRIGGER_SQL,
+    DROP_CLIENTS_TOMBSTONE_TRIGGER_SQL,
+    DROP_TAGS_TOMBSTONE_TRIGGER_SQL,
 ];
 
-pub(crate) const CREATE_ALL_TOMBSTONE_TRIGGERS_SQL: [&str; 4] = [
+pub(crate) const CREATE_ALL_TOMBSTONE_TRIGGERS_SQL: [&str; 6] = [
     SESSIONS_TOMBSTONE_TRIGGER_SQL,
     APPLICATIONS_TOMBSTONE_TRIGGER_SQL,
     PROJECTS_TOMBSTONE_TRIGGER_SQL,
     MANUAL_SESSIONS_TOMBSTONE_TRIGGER_SQL,
+    CLIENTS_TOMBSTONE_TRIGGER_SQL,
+    TAGS_TOMBSTONE_TRIGGER_SQL,
 ];
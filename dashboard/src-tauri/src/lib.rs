@@ -165,6 +165,15 @@ pub fn run() {
             commands::projects_with_client,
             commands::clients_sync_from_pm,
             commands::get_clients_summary,
+            commands::get_tags,
+            commands::create_tag,
+            commands::update_tag,
+            commands::delete_tag,
+            commands::add_tags_to_sessions,
+            commands::remove_tags_from_sessions,
+            commands::add_tags_to_manual_sessions,
+            commands::remove_tags_from_manual_sessions,
+            commands::get_tag_time_breakdown,
             commands::webserver_status,
             commands::webserver_set_config,
             commands::webserver_generate_pairing_code,
@@ -188,6 +197,7 @@ pub fn run() {
             commands::blacklist_project_names,
             commands::freeze_project,
             commands::unfreeze_project,
+            commands::set_project_device_local,
             commands::get_project_extra_info,
             commands::get_project_report_data,
             commands::print_report,
@@ -212,6 +222,7 @@ pub fn run() {
             commands::set_time_algorithm,
             commands::update_global_hourly_rate,
             commands::update_project_hourly_rate,
+            commands::update_project_billable,
             commands::get_project_estimates,
             commands::get_estimates_summary,
             commands::get_applications,
@@ -257,6 +268,12 @@ pub fn run() {
             commands::update_manual_session,
             commands::delete_manual_session,
             commands::delete_manual_sessions,
+            commands::update_manual_sessions_billable,
+            commands::get_untracked_gaps,
+            commands::accept_gap_fills,
+            commands::get_overlap_policy,
+            commands::set_overlap_policy,
+            commands::get_session_overlaps,
             commands::export_data,
             commands::export_data_archive,
             commands::validate_import,
@@ -264,10 +281,12 @@ pub fn run() {
             commands::import_data_archive,
             commands::build_delta_archive,
             commands::update_app_color,
+            commands::set_app_device_local,
             commands::update_session_rate_multiplier,
             commands::update_session_rate_multipliers,
             commands::update_session_comment,
             commands::update_session_comments,
+            commands::update_sessions_billable,
             commands::rebuild_sessions,
             commands::get_assignment_model_status,
             commands::get_assignment_model_metrics,
@@ -283,6 +302,13 @@ pub fn run() {
             commands::rollback_last_auto_safe_run,
             commands::auto_run_if_needed,
             commands::apply_deterministic_assignment,
+            commands::get_assignment_rules,
+            commands::create_assignment_rule,
+            commands::update_assignment_rule,
+            commands::delete_assignment_rule,
+            commands::preview_assignment_rule,
+            commands::apply_assignment_rule,
+            commands::apply_active_assignment_rules,
             commands::get_session_score_breakdown,
             commands::set_feedback_weight,
             commands::scan_project_folders_for_ai,
@@ -311,8 +337,15 @@ pub fn run() {
             commands::analyze_session_projects,
             commands::analyze_sessions_splittable,
             commands::split_session_multi,
+            commands::merge_sessions,
+            commands::get_action_history,
+            commands::undo_last_action,
+            commands::redo_last_action,
+            commands::get_session_audit_log,
+            commands::get_project_audit_log,
             commands::get_lan_peers,
             commands::get_lan_sync_progress,
+            commands::get_sync_preview,
             commands::build_table_hashes_only,
             commands::ping_lan_peer,
             commands::scan_lan_subnet,
@@ -323,6 +356,12 @@ pub fn run() {
             commands::get_lan_server_status,
             commands::insert_sync_marker,
             commands::get_latest_sync_marker,
+            commands::get_sync_conflicts,
+            commands::mark_sync_conflicts_reviewed,
+            commands::flip_sync_conflict,
+            commands::get_sync_runs,
+            commands::get_sync_run_stats,
+            commands::clear_sync_history,
             commands::markers_match,
             commands::backup_before_sync,
             commands::upsert_lan_peer,
@@ -336,6 +375,8 @@ pub fn run() {
             commands::run_online_sync,
             commands::get_online_sync_progress,
             commands::cancel_online_sync,
+            commands::change_online_sync_passphrase,
+            commands::rotate_online_sync_key,
             commands::get_log_settings,
             commands::save_log_settings,
             commands::get_log_files_info,
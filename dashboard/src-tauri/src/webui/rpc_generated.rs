@@ -44,11 +44,16 @@ pub fn dispatch_generated(
     args: &Value,
 ) -> Option<Result<Value, String>> {
     match command {
+        "accept_gap_fills" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::accept_gap_fills(app.clone(), from_arg(args, "fills")?))?) })()),
         "add_monitored_app" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::add_monitored_app(app.clone(), from_arg(args, "exe_name")?, from_arg(args, "display_name")?, from_arg(args, "bundle_id")?, from_arg(args, "app_path")?))?) })()),
         "add_project_folder" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::add_project_folder(app.clone(), from_arg(args, "path")?))?) })()),
+        "add_tags_to_manual_sessions" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::add_tags_to_manual_sessions(app.clone(), from_arg(args, "manual_session_ids")?, from_arg(args, "tag_ids")?))?) })()),
+        "add_tags_to_sessions" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::add_tags_to_sessions(app.clone(), from_arg(args, "session_ids")?, from_arg(args, "tag_ids")?))?) })()),
         "analyze_session_projects" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::analyze_session_projects(app.clone(), from_arg(args, "session_id")?, from_arg(args, "tolerance_threshold")?, from_arg(args, "max_projects")?))?) })()),
         "analyze_sessions_splittable" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::analyze_sessions_splittable(app.clone(), from_arg(args, "session_ids")?, from_arg(args, "tolerance_threshold")?, from_arg(args, "max_projects")?))?) })()),
         "append_sync_log" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::append_sync_log(from_arg(args, "lines")?))?) })()),
+        "apply_active_assignment_rules" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::apply_active_assignment_rules(app.clone()))?) })()),
+        "apply_assignment_rule" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::apply_assignment_rule(app.clone(), from_arg(args, "rule_id")?, from_arg(args, "only_unassigned")?))?) })()),
         "apply_deterministic_assignment" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::apply_deterministic_assignment(app.clone(), from_arg(args, "min_history")?))?) })()),
         "assign_app_to_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::assign_app_to_project(app.clone(), from_arg(args, "app_id")?, from_arg(args, "project_id")?))?) })()),
         "assign_session_to_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::assign_session_to_project(app.clone(), from_arg(args, "session_id")?, from_arg(args, "project_id")?, from_arg(args, "source")?))?) })()),
@@ -62,10 +67,12 @@ pub fn dispatch_generated(
         "build_delta_archive" => Some((|| -> Result<Value, String> { ok(crate::commands::build_delta_archive(app.clone(), from_arg(args, "since")?)?) })()),
         "build_table_hashes_only" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::build_table_hashes_only(app.clone()))?) })()),
         "cancel_online_sync" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::cancel_online_sync())?) })()),
+        "change_online_sync_passphrase" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::change_online_sync_passphrase(from_arg(args, "old_passphrase")?, from_arg(args, "new_passphrase")?))?) })()),
         "cleanup_data_folder" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::cleanup_data_folder(app.clone()))?) })()),
         "clear_all_data" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::clear_all_data(app.clone()))?) })()),
         "clear_folder_scan_data" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::clear_folder_scan_data(app.clone()))?) })()),
         "clear_log_file" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::clear_log_file(from_arg(args, "key")?))?) })()),
+        "clear_sync_history" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::clear_sync_history(app.clone(), from_arg(args, "older_than_days")?))?) })()),
         "clients_archive" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::clients_archive(app.clone(), from_arg(args, "id")?, from_arg(args, "archived")?))?) })()),
         "clients_create" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::clients_create(app.clone(), from_arg(args, "name")?, from_arg(args, "contact")?, from_arg(args, "address")?, from_arg(args, "tax_id")?, from_arg(args, "currency")?, from_arg(args, "default_hourly_rate")?, from_arg(args, "color")?))?) })()),
         "clients_delete" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::clients_delete(app.clone(), from_arg(args, "id")?, from_arg(args, "name")?))?) })()),
@@ -73,28 +80,35 @@ pub fn dispatch_generated(
         "clients_sync_from_pm" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::clients_sync_from_pm(app.clone()))?) })()),
         "clients_update" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::clients_update(app.clone(), from_arg(args, "id")?, from_arg(args, "name")?, from_arg(args, "contact")?, from_arg(args, "address")?, from_arg(args, "tax_id")?, from_arg(args, "currency")?, from_arg(args, "default_hourly_rate")?, from_arg(args, "color")?))?) })()),
         "compact_project_data" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::compact_project_data(app.clone(), from_arg(args, "id")?))?) })()),
+        "create_assignment_rule" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::create_assignment_rule(app.clone(), from_arg(args, "rule")?))?) })()),
         "create_manual_session" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::create_manual_session(app.clone(), from_arg(args, "input")?))?) })()),
         "create_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::create_project(app.clone(), from_arg(args, "name")?, from_arg(args, "color")?, from_arg(args, "assigned_folder_path")?))?) })()),
         "create_project_from_folder" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::create_project_from_folder(app.clone(), from_arg(args, "folder_path")?))?) })()),
+        "create_tag" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::create_tag(app.clone(), from_arg(args, "name")?, from_arg(args, "color")?))?) })()),
         "delete_all_excluded_projects" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_all_excluded_projects(app.clone()))?) })()),
         "delete_app_and_data" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_app_and_data(app.clone(), from_arg(args, "app_id")?))?) })()),
         "delete_archive_file" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_archive_file(app.clone(), from_arg(args, "file_name")?))?) })()),
+        "delete_assignment_rule" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_assignment_rule(app.clone(), from_arg(args, "rule_id")?))?) })()),
         "delete_manual_session" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_manual_session(app.clone(), from_arg(args, "id")?))?) })()),
         "delete_manual_sessions" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_manual_sessions(app.clone(), from_arg(args, "ids")?))?) })()),
         "delete_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_project(app.clone(), from_arg(args, "id")?))?) })()),
         "delete_session" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_session(app.clone(), from_arg(args, "session_id")?))?) })()),
         "delete_sessions" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_sessions(app.clone(), from_arg(args, "session_ids")?))?) })()),
+        "delete_tag" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::delete_tag(app.clone(), from_arg(args, "id")?))?) })()),
         "exclude_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::exclude_project(app.clone(), from_arg(args, "id")?))?) })()),
         "export_data" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::export_data(app.clone(), from_arg(args, "project_id")?, from_arg(args, "date_start")?, from_arg(args, "date_end")?))?) })()),
         "export_data_archive" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::export_data_archive(app.clone(), from_arg(args, "project_id")?, from_arg(args, "date_start")?, from_arg(args, "date_end")?))?) })()),
+        "flip_sync_conflict" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::flip_sync_conflict(app.clone(), from_arg(args, "id")?))?) })()),
         "freeze_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::freeze_project(app.clone(), from_arg(args, "id")?))?) })()),
         "generate_pairing_code" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::generate_pairing_code())?) })()),
+        "get_action_history" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_action_history(app.clone(), from_arg(args, "limit")?))?) })()),
         "get_activity_date_span" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_activity_date_span(app.clone()))?) })()),
         "get_all_user_settings" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_all_user_settings())?) })()),
         "get_applications" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_applications(app.clone(), from_arg(args, "date_range")?))?) })()),
         "get_archive_files" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_archive_files(app.clone()))?) })()),
         "get_assignment_model_metrics" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_assignment_model_metrics(app.clone(), from_arg(args, "days")?))?) })()),
         "get_assignment_model_status" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_assignment_model_status(app.clone()))?) })()),
+        "get_assignment_rules" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_assignment_rules(app.clone()))?) })()),
         "get_autostart_enabled" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_autostart_enabled())?) })()),
         "get_background_diagnostics" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_background_diagnostics(app.clone()))?) })()),
         "get_backup_files" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_backup_files(app.clone()))?) })()),
@@ -129,9 +143,11 @@ pub fn dispatch_generated(
         "get_monitored_apps" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_monitored_apps(app.clone()))?) })()),
         "get_online_sync_progress" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_online_sync_progress())?) })()),
         "get_online_sync_settings" => Some((|| -> Result<Value, String> { ok(crate::commands::get_online_sync_settings()?) })()),
+        "get_overlap_policy" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_overlap_policy(app.clone()))?) })()),
         "get_paired_devices" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_paired_devices())?) })()),
         "get_persisted_language" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_persisted_language())?) })()),
         "get_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_project(app.clone(), from_arg(args, "id")?))?) })()),
+        "get_project_audit_log" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_project_audit_log(app.clone(), from_arg(args, "project_id")?, from_arg(args, "limit")?))?) })()),
         "get_project_estimates" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_project_estimates(app.clone(), from_arg(args, "date_range")?))?) })()),
         "get_project_extra_info" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_project_extra_info(app.clone(), from_arg(args, "id")?, from_arg(args, "date_range")?))?) })()),
         "get_project_folders" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_project_folders(app.clone()))?) })()),
@@ -139,21 +155,32 @@ pub fn dispatch_generated(
         "get_project_timeline" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_project_timeline(app.clone(), from_arg(args, "date_range")?, from_arg(args, "limit")?, from_arg(args, "granularity")?, from_arg(args, "id")?))?) })()),
         "get_projects" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_projects(app.clone(), from_arg(args, "date_range")?))?) })()),
         "get_secure_token" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_secure_token(app.clone()))?) })()),
+        "get_session_audit_log" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_session_audit_log(app.clone(), from_arg(args, "session_id")?))?) })()),
         "get_session_count" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_session_count(app.clone(), from_arg(args, "filters")?))?) })()),
+        "get_session_overlaps" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_session_overlaps(app.clone(), from_arg(args, "date_range")?, from_arg(args, "project_id")?))?) })()),
         "get_session_score_breakdown" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_session_score_breakdown(app.clone(), from_arg(args, "session_id")?))?) })()),
         "get_sessions" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_sessions(app.clone(), from_arg(args, "filters")?))?) })()),
+        "get_sync_conflicts" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_sync_conflicts(app.clone(), from_arg(args, "include_reviewed")?, from_arg(args, "limit")?))?) })()),
         "get_sync_log" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_sync_log(from_arg(args, "tail_lines")?))?) })()),
+        "get_sync_preview" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_sync_preview())?) })()),
+        "get_sync_run_stats" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_sync_run_stats(app.clone(), from_arg(args, "days")?))?) })()),
+        "get_sync_runs" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_sync_runs(app.clone(), from_arg(args, "kind")?, from_arg(args, "status")?, from_arg(args, "peer_id")?, from_arg(args, "limit")?))?) })()),
+        "get_tag_time_breakdown" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_tag_time_breakdown(app.clone(), from_arg(args, "date_range")?))?) })()),
+        "get_tags" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_tags(app.clone()))?) })()),
         "get_time_algorithm" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_time_algorithm(app.clone()))?) })()),
         "get_timeline" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_timeline(app.clone(), from_arg(args, "date_range")?))?) })()),
         "get_today_file_signature" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_today_file_signature(app.clone()))?) })()),
+        "get_untracked_gaps" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::get_untracked_gaps(app.clone(), from_arg(args, "date_range")?, from_arg(args, "working_hours")?, from_arg(args, "min_gap_minutes")?))?) })()),
         "import_data" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::import_data(app.clone(), from_arg(args, "archive_path")?))?) })()),
         "import_data_archive" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::import_data_archive(app.clone(), from_arg(args, "archive")?))?) })()),
         "import_json_files" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::import_json_files(app.clone(), from_arg(args, "file_paths")?))?) })()),
         "insert_sync_marker" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::insert_sync_marker(app.clone(), from_arg(args, "tables_hash")?, from_arg(args, "device_id")?, from_arg(args, "peer_id")?, from_arg(args, "full_sync")?))?) })()),
         "inspect_dropped_app" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::inspect_dropped_app(from_arg(args, "path")?))?) })()),
         "list_time_algorithms" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::list_time_algorithms(app.clone()))?) })()),
+        "mark_sync_conflicts_reviewed" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::mark_sync_conflicts_reviewed(app.clone(), from_arg(args, "ids")?))?) })()),
         "markers_match" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::markers_match(app.clone(), from_arg(args, "remote_marker_hash")?))?) })()),
         "merge_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::merge_project(app.clone(), from_arg(args, "source_id")?, from_arg(args, "target_id")?))?) })()),
+        "merge_sessions" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::merge_sessions(app.clone(), from_arg(args, "session_ids")?, from_arg(args, "max_gap_minutes")?))?) })()),
         "open_db_folder" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::open_db_folder(app.clone()))?) })()),
         "open_logs_folder" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::open_logs_folder())?) })()),
         "optimize_database" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::optimize_database(app.clone()))?) })()),
@@ -177,15 +204,19 @@ pub fn dispatch_generated(
         "pm_set_work_folder" => Some((|| -> Result<Value, String> { ok(crate::commands::pm_set_work_folder(from_arg(args, "path")?)?) })()),
         "pm_suggest_project_number" => Some((|| -> Result<Value, String> { ok(crate::commands::pm_suggest_project_number()?) })()),
         "pm_update_project" => Some((|| -> Result<Value, String> { ok(crate::commands::pm_update_project(from_arg(args, "index")?, from_arg(args, "project")?)?) })()),
+        "preview_assignment_rule" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::preview_assignment_rule(app.clone(), from_arg(args, "rule")?, from_arg(args, "only_unassigned")?))?) })()),
         "project_set_client" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::project_set_client(app.clone(), from_arg(args, "project_id")?, from_arg(args, "client_name")?))?) })()),
         "project_set_status" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::project_set_status(app.clone(), from_arg(args, "project_id")?, from_arg(args, "status")?))?) })()),
         "projects_with_client" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::projects_with_client(app.clone()))?) })()),
         "read_log_file" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::read_log_file(from_arg(args, "key")?, from_arg(args, "tail_lines")?))?) })()),
         "rebuild_sessions" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::rebuild_sessions(app.clone(), from_arg(args, "gap_fill_minutes")?))?) })()),
+        "redo_last_action" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::redo_last_action(app.clone()))?) })()),
         "refresh_missing_days" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::refresh_missing_days(app.clone()))?) })()),
         "refresh_today" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::refresh_today(app.clone()))?) })()),
         "remove_monitored_app" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::remove_monitored_app(app.clone(), from_arg(args, "exe_name")?))?) })()),
         "remove_project_folder" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::remove_project_folder(app.clone(), from_arg(args, "path")?))?) })()),
+        "remove_tags_from_manual_sessions" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::remove_tags_from_manual_sessions(app.clone(), from_arg(args, "manual_session_ids")?, from_arg(args, "tag_ids")?))?) })()),
+        "remove_tags_from_sessions" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::remove_tags_from_sessions(app.clone(), from_arg(args, "session_ids")?, from_arg(args, "tag_ids")?))?) })()),
         "rename_application" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::rename_application(app.clone(), from_arg(args, "app_id")?, from_arg(args, "display_name")?))?) })()),
         "rename_monitored_app" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::rename_monitored_app(app.clone(), from_arg(args, "exe_name")?, from_arg(args, "display_name")?))?) })()),
         "reset_app_time" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::reset_app_time(app.clone(), from_arg(args, "app_id")?))?) })()),
@@ -196,20 +227,24 @@ pub fn dispatch_generated(
         "restore_database_from_file" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::restore_database_from_file(app.clone(), from_arg(args, "path")?))?) })()),
         "restore_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::restore_project(app.clone(), from_arg(args, "id")?))?) })()),
         "rollback_last_auto_safe_run" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::rollback_last_auto_safe_run(app.clone()))?) })()),
+        "rotate_online_sync_key" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::rotate_online_sync_key())?) })()),
         "run_auto_safe_assignment" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::run_auto_safe_assignment(app.clone(), from_arg(args, "limit")?, from_arg(args, "date_range")?, from_arg(args, "min_duration")?))?) })()),
-        "run_lan_sync" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::run_lan_sync(app.clone(), from_arg(args, "peer_ip")?, from_arg(args, "peer_port")?, from_arg(args, "_since")?, from_arg(args, "force")?))?) })()),
-        "run_online_sync" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::run_online_sync())?) })()),
+        "run_lan_sync" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::run_lan_sync(app.clone(), from_arg(args, "peer_ip")?, from_arg(args, "peer_port")?, from_arg(args, "_since")?, from_arg(args, "force")?, from_arg(args, "dry_run")?))?) })()),
+        "run_online_sync" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::run_online_sync(from_arg(args, "dry_run")?))?) })()),
         "save_log_settings" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::save_log_settings(from_arg(args, "settings")?))?) })()),
         "save_online_sync_settings" => Some((|| -> Result<Value, String> { ok(crate::commands::save_online_sync_settings(from_arg(args, "settings")?)?) })()),
         "scan_lan_subnet" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::scan_lan_subnet())?) })()),
         "scan_project_folders_for_ai" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::scan_project_folders_for_ai(app.clone()))?) })()),
         "send_bug_report" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::send_bug_report(app.clone(), from_arg(args, "subject")?, from_arg(args, "message")?, from_arg(args, "version")?, from_arg(args, "attachments")?))?) })()),
+        "set_app_device_local" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_app_device_local(app.clone(), from_arg(args, "id")?, from_arg(args, "device_local")?))?) })()),
         "set_assignment_mode" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_assignment_mode(app.clone(), from_arg(args, "mode")?, from_arg(args, "suggest_conf")?, from_arg(args, "auto_conf")?, from_arg(args, "auto_ev")?))?) })()),
         "set_assignment_model_cooldown" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_assignment_model_cooldown(app.clone(), from_arg(args, "hours")?))?) })()),
         "set_autostart_enabled" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_autostart_enabled(from_arg(args, "enabled")?))?) })()),
         "set_decay_half_life_days" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_decay_half_life_days(app.clone(), from_arg(args, "days")?))?) })()),
         "set_demo_mode" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_demo_mode(app.clone(), from_arg(args, "enabled")?))?) })()),
         "set_feedback_weight" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_feedback_weight(app.clone(), from_arg(args, "weight")?))?) })()),
+        "set_overlap_policy" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_overlap_policy(app.clone(), from_arg(args, "policy")?))?) })()),
+        "set_project_device_local" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_project_device_local(app.clone(), from_arg(args, "id")?, from_arg(args, "device_local")?))?) })()),
         "set_secure_token" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_secure_token(app.clone(), from_arg(args, "token")?))?) })()),
         "set_time_algorithm" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_time_algorithm(app.clone(), from_arg(args, "algorithm")?))?) })()),
         "set_training_blacklists" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::set_training_blacklists(app.clone(), from_arg(args, "app_blacklist")?, from_arg(args, "folder_blacklist")?))?) })()),
@@ -225,20 +260,26 @@ pub fn dispatch_generated(
         "sync_monitored_apps_from_applications" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::sync_monitored_apps_from_applications(app.clone()))?) })()),
         "sync_projects_from_folders" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::sync_projects_from_folders(app.clone()))?) })()),
         "train_assignment_model" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::train_assignment_model(app.clone(), from_arg(args, "force")?, from_arg(args, "full_rebuild")?))?) })()),
+        "undo_last_action" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::undo_last_action(app.clone()))?) })()),
         "unfreeze_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::unfreeze_project(app.clone(), from_arg(args, "id")?))?) })()),
         "unmerge_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::unmerge_project(app.clone(), from_arg(args, "id")?))?) })()),
         "unpair_device" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::unpair_device(from_arg(args, "device_id")?))?) })()),
         "update_app_color" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_app_color(app.clone(), from_arg(args, "id")?, from_arg(args, "color")?))?) })()),
+        "update_assignment_rule" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_assignment_rule(app.clone(), from_arg(args, "rule_id")?, from_arg(args, "rule")?))?) })()),
         "update_database_settings" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_database_settings(app.clone(), from_arg(args, "vacuum_on_startup")?, from_arg(args, "backup_enabled")?, from_arg(args, "backup_path")?, from_arg(args, "backup_interval_days")?, from_arg(args, "auto_optimize_enabled")?, from_arg(args, "auto_optimize_interval_hours")?))?) })()),
         "update_global_hourly_rate" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_global_hourly_rate(app.clone(), from_arg(args, "rate")?))?) })()),
         "update_manual_session" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_manual_session(app.clone(), from_arg(args, "id")?, from_arg(args, "input")?))?) })()),
+        "update_manual_sessions_billable" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_manual_sessions_billable(app.clone(), from_arg(args, "ids")?, from_arg(args, "billable")?))?) })()),
         "update_project" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_project(app.clone(), from_arg(args, "id")?, from_arg(args, "color")?))?) })()),
+        "update_project_billable" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_project_billable(app.clone(), from_arg(args, "project_id")?, from_arg(args, "billable")?))?) })()),
         "update_project_folder_meta" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_project_folder_meta(app.clone(), from_arg(args, "path")?, from_arg(args, "color")?, from_arg(args, "category")?, from_arg(args, "badge")?))?) })()),
         "update_project_hourly_rate" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_project_hourly_rate(app.clone(), from_arg(args, "project_id")?, from_arg(args, "rate")?))?) })()),
         "update_session_comment" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_session_comment(app.clone(), from_arg(args, "session_id")?, from_arg(args, "comment")?))?) })()),
         "update_session_comments" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_session_comments(app.clone(), from_arg(args, "session_ids")?, from_arg(args, "comment")?))?) })()),
         "update_session_rate_multiplier" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_session_rate_multiplier(app.clone(), from_arg(args, "session_id")?, from_arg(args, "multiplier")?))?) })()),
         "update_session_rate_multipliers" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_session_rate_multipliers(app.clone(), from_arg(args, "session_ids")?, from_arg(args, "multiplier")?))?) })()),
+        "update_sessions_billable" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_sessions_billable(app.clone(), from_arg(args, "session_ids")?, from_arg(args, "billable")?))?) })()),
+        "update_tag" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::update_tag(app.clone(), from_arg(args, "id")?, from_arg(args, "name")?, from_arg(args, "color")?))?) })()),
         "upsert_lan_peer" => Some((|| -> Result<Value, String> { ok(crate::commands::upsert_lan_peer(from_arg(args, "peer")?)?) })()),
         "vacuum_database" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::vacuum_database(app.clone()))?) })()),
         "validate_import" => Some((|| -> Result<Value, String> { ok(tauri::async_runtime::block_on(crate::commands::validate_import(app.clone(), from_arg(args, "archive_path")?))?) })()),
@@ -88,6 +88,13 @@ export function DevSettingsCard() {
     logManagementApi.saveLogSettings(next).catch(() => {});
   }, [settings]);
 
+  const handleRetentionChange = useCallback((days: number) => {
+    if (!settings) return;
+    const next = { ...settings, sync_history_retention_days: days };
+    dispatch({ type: 'patch_settings', settings: next });
+    logManagementApi.saveLogSettings(next).catch(() => {});
+  }, [settings]);
+
   const handleClear = useCallback(
     (key: string) => {
       logManagementApi
@@ -168,6 +175,28 @@ export function DevSettingsCard() {
           </div>
         </div>
 
+        {/* Sync history retention */}
+        <div className="flex items-center justify-between rounded-md border border-border/70 bg-background/35 p-2.5">
+          <div>
+            <p className="text-sm font-medium">{t('dev_settings.sync_history_retention')}</p>
+            <p className="text-xs text-muted-foreground">{t('dev_settings.sync_history_retention_desc')}</p>
+          </div>
+          <div className="relative">
+            <select
+              className="h-7 w-24 appearance-none rounded-md border border-input bg-background pl-2 pr-7 text-xs shadow-sm focus-visible:outline-none focus-visible:ring-2 focus-visible:ring-ring/40"
+              value={settings.sync_history_retention_days ?? 90}
+              onChange={(e) => handleRetentionChange(Number(e.target.value))}
+            >
+              {[7, 30, 90, 180, 365].map((days) => (
+                <option key={days} value={days}>
+                  {t('dev_settings.sync_history_retention_days', { days })}
+                </option>
+              ))}
+            </select>
+            <ChevronDown className="absolute right-2 top-1/2 size-3 -translate-y-1/2 text-muted-foreground pointer-events-none" />
+          </div>
+        </div>
+
         {/* Log Files */}
         <div className="space-y-2">
           <div className="flex items-center justify-between">
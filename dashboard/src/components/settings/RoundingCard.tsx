@@ -2,8 +2,10 @@ import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { useTranslation } from 'react-i18next';
 import {
   FULL_HOUR_MINUTES,
+  ROUNDING_DIRECTIONS,
   ROUNDING_INTERVALS,
   ROUNDING_VARIANTS,
+  type RoundingDirection,
   type RoundingMode,
   type RoundingSettings,
 } from '@/lib/rounding';
@@ -14,13 +16,14 @@ interface RoundingCardProps {
 }
 
 /**
- * Karta ustawień zaokrąglania czasu. Kierunek jest stały (w górę) — użytkownik
- * wybiera tylko interwał oraz wariant. Warianty pochodzą z rejestru
- * `ROUNDING_VARIANTS`, więc dodanie kolejnego nie wymaga zmian w tym pliku.
+ * Karta ustawień zaokrąglania czasu — interwał, kierunek (domyślnie w górę) oraz
+ * wariant. Warianty pochodzą z rejestru `ROUNDING_VARIANTS`, więc dodanie
+ * kolejnego nie wymaga zmian w tym pliku.
  */
 export function RoundingCard({ settings, onChange }: RoundingCardProps) {
   const { t } = useTranslation();
   const { enabled, intervalMinutes, mode } = settings;
+  const direction = settings.direction ?? 'up';
   // Tryb dzienny wymusza pełną godzinę — interwał jest wtedy nieedytowalny.
   const intervalLocked = mode === 'per_day';
   const intervalDisabled = !enabled || intervalLocked;
@@ -95,6 +98,34 @@ export function RoundingCard({ settings, onChange }: RoundingCardProps) {
           </select>
         </div>
 
+        {/* Direction */}
+        <div
+          className={`grid gap-3 rounded-md border border-border/70 bg-background/35 p-3 sm:grid-cols-[1fr_auto] sm:items-center ${
+            enabled ? '' : 'opacity-50'
+          }`}
+        >
+          <div className="min-w-0">
+            <p className="text-sm font-medium">{t('rounding.direction')}</p>
+            <p className="text-xs leading-5 break-words text-muted-foreground">
+              {t('rounding.direction_hint')}
+            </p>
+          </div>
+          <select
+            value={direction}
+            disabled={!enabled}
+            onChange={(e) =>
+              onChange({ ...settings, direction: e.target.value as RoundingDirection })
+            }
+            className="rounded-md border border-input bg-background px-2 py-1 text-sm shadow-sm focus-visible:outline-none focus-visible:ring-2 focus-visible:ring-ring/40 text-foreground disabled:cursor-not-allowed"
+          >
+            {ROUNDING_DIRECTIONS.map((d) => (
+              <option key={d} value={d} className="bg-background text-foreground">
+                {t(`rounding.directions.${d}`)}
+              </option>
+            ))}
+          </select>
+        </div>
+
         {/* Variant (mode) */}
         <div className={`space-y-2 ${enabled ? '' : 'opacity-50'}`}>
           <p className="text-sm font-medium">{t('rounding.mode')}</p>
@@ -14,11 +14,13 @@ import {
 } from '@/components/sync/lan-peer-notification-state';
 
 /**
- * Strict version match — LAN sync ma sens tylko dla identycznych wersji
- * po obu stronach (schema bazy / format snapshotu mogą się rozjechać między
- * patchami). Backend `run_lan_sync` blokuje to dodatkowo serwerowo.
+ * Zgodność peera — backend wylicza `sync_compatible` z negocjacji protokołu
+ * (różne wersje TIMEFLOW synchronizują wspólny podzbiór danych). Bez tego pola
+ * (stary backend) zostaje strict version match. `run_lan_sync` i tak
+ * negocjuje ponownie po stronie serwera.
  */
 function peerVersionMatches(peer: LanPeer, localVersion: string): boolean {
+  if (peer.sync_compatible !== undefined) return peer.sync_compatible;
   const peerVersion = (peer.timeflow_version ?? '').trim();
   return localVersion !== '' && peerVersion !== '' && peerVersion === localVersion;
 }
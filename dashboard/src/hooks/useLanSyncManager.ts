@@ -340,6 +340,9 @@ export function useLanSyncManager() {
         dashboard_port: result.dashboard_port,
         last_seen: new Date().toISOString(),
         dashboard_running: true,
+        timeflow_version: result.version,
+        sync_protocol: result.sync_protocol,
+        min_sync_protocol: result.min_sync_protocol,
       };
       await lanSyncApi.upsertLanPeer(peer);
       setLanPeers((prev) => {
@@ -91,6 +91,7 @@ export async function runHeavyOperation<T>(
 
 export type AiAssignmentResult = {
   needsRefresh: boolean;
+  ruleAssigned: number;
   deterministicAssigned: number;
   aiAssigned: number;
 };
@@ -99,8 +100,17 @@ export async function runAutoAiAssignmentCycle(): Promise<AiAssignmentResult> {
   const result = await runHeavyOperation(
     AI_AND_SPLIT_OPERATION_KEY,
     async () => {
+      let ruleAssigned = 0;
       let deterministicAssigned = 0;
       let aiAssigned = 0;
+      // Saved rules kept active go first — they outrank the model.
+      try {
+        const rules = await aiApi.applyActiveAssignmentRules();
+        ruleAssigned = rules.sessions_assigned;
+      } catch (e) {
+        logger.warn('Assignment rules failed:', e);
+      }
+
       try {
         const det = await aiApi.applyDeterministicAssignment();
         deterministicAssigned = det.sessions_assigned;
@@ -117,12 +127,20 @@ export async function runAutoAiAssignmentCycle(): Promise<AiAssignmentResult> {
         logger.warn('AI auto-assignment failed:', e);
       }
 
-      const needsRefresh = deterministicAssigned > 0 || aiAssigned > 0;
-      return { needsRefresh, deterministicAssigned, aiAssigned };
+      const needsRefresh =
+        ruleAssigned > 0 || deterministicAssigned > 0 || aiAssigned > 0;
+      return { needsRefresh, ruleAssigned, deterministicAssigned, aiAssigned };
     },
   );
 
-  return result ?? { needsRefresh: false, deterministicAssigned: 0, aiAssigned: 0 };
+  return (
+    result ?? {
+      needsRefresh: false,
+      ruleAssigned: 0,
+      deterministicAssigned: 0,
+      aiAssigned: 0,
+    }
+  );
 }
 
 // === Event dispatch ===
@@ -138,7 +156,8 @@ export function dispatchLanSyncDone(peerName: string) {
 }
 
 export function dispatchAiAssignmentDone(result: AiAssignmentResult) {
-  const total = result.deterministicAssigned + result.aiAssigned;
+  const total =
+    result.ruleAssigned + result.deterministicAssigned + result.aiAssigned;
   if (total > 0) {
     window.dispatchEvent(
       new CustomEvent(AI_ASSIGNMENT_DONE_EVENT, { detail: total }),
@@ -10,6 +10,9 @@ export interface Project {
   merged_into?: string | null;       // parent project name (null = not merged)
   merged_at?: string | null;
   assigned_folder_path?: string | null;
+  client_name?: string | null;
+  /** Domyślna flaga billable; sesje mogą ją nadpisać. */
+  billable?: boolean;
   is_imported: number;
 }
 
@@ -68,6 +71,8 @@ export interface AppWithStats extends Application {
   project_name: string | null;
   project_color: string | null;
   color: string;
+  /** Selective sync: aplikacja i jej sesje nie opuszczają tej maszyny. */
+  device_local: boolean;
   /** Sekundy per kalendarzowy dzień — do zaokrąglania `per_day`. */
   daily_seconds: number[];
 }
@@ -86,6 +91,10 @@ export interface SessionWithApp extends Session {
   /** true when the most recent assignment for this session was made by AI auto-safe */
   ai_assigned?: boolean;
   comment?: string | null;
+  /** Tag names, sorted. */
+  tags: string[];
+  /** Per-session override; null inherits the project's default. */
+  billable?: boolean | null;
 }
 
 export type AssignmentMode = 'off' | 'suggest' | 'auto_safe';
@@ -172,6 +181,57 @@ export interface DeterministicResult {
   sessions_skipped: number;
 }
 
+/** Saved "app + title/path regex + date range → project" rule. */
+export interface AssignmentRuleInput {
+  name: string;
+  app_id: number | null;
+  /** Case-insensitive regex over file names, paths and window titles. */
+  pattern: string | null;
+  /** Inclusive YYYY-MM-DD bounds. */
+  date_from: string | null;
+  date_to: string | null;
+  project_id: number;
+  /** Also assign new unassigned sessions, ahead of the AI model. */
+  keep_active: boolean;
+}
+
+export interface AssignmentRule extends AssignmentRuleInput {
+  id: number;
+  app_name: string | null;
+  project_name: string | null;
+  last_applied_at: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface AssignmentRuleMatch {
+  session_id: number;
+  app_id: number;
+  app_name: string;
+  date: string;
+  start_time: string;
+  end_time: string;
+  duration_seconds: number;
+  project_id: number | null;
+  project_name: string | null;
+}
+
+export interface AssignmentRulePreview {
+  session_count: number;
+  total_seconds: number;
+  /** Matches currently assigned to another project. */
+  reassigned_count: number;
+  /** Capped list; the counts cover every match. */
+  sessions: AssignmentRuleMatch[];
+  truncated: boolean;
+}
+
+export interface AssignmentRuleApplyResult {
+  rules_applied: number;
+  sessions_assigned: number;
+  seconds_assigned: number;
+}
+
 export interface CandidateScore {
   project_id: number;
   project_name: string;
@@ -229,6 +289,7 @@ export interface DashboardStats {
   top_project: { name: string; seconds: number; color: string } | null;
   /** Łączny czas per kalendarzowy dzień — do zaokrąglania `per_day`. */
   daily_seconds: number[];
+  top_tags: TagTime[];
 }
 
 export interface DashboardData {
@@ -274,6 +335,11 @@ export interface EstimateProjectRow {
   project_color: string;
   seconds: number;
   hours: number;
+  /** Część `seconds` liczona do wyceny; reszta w `non_billable_seconds`. */
+  billable_seconds: number;
+  non_billable_seconds: number;
+  /** Domyślna flaga projektu. */
+  billable: boolean;
   weighted_hours: number;
   project_hourly_rate: number | null;
   effective_hourly_rate: number;
@@ -287,14 +353,22 @@ export interface EstimateProjectRow {
   client_name: string | null;
   /** Rozbicie czasu na dni z etykietami dat (YYYY-MM-DD), chronologicznie. */
   days: EstimateDay[];
+  /** `seconds` / `estimated_value` po zaokrągleniu po stronie backendu (= surowe, gdy wyłączone). */
+  rounded_seconds: number;
+  rounded_value: number;
 }
 
 export interface EstimateSummary {
   total_seconds: number;
   total_hours: number;
+  billable_seconds: number;
+  non_billable_seconds: number;
   total_value: number;
   projects_count: number;
   overrides_count: number;
+  /** Suma zaokrąglonych wierszy. */
+  rounded_seconds: number;
+  rounded_value: number;
 }
 
 export interface DateRange {
@@ -315,6 +389,8 @@ export interface ProjectWithStats extends Project {
   app_count: number;
   last_activity: string | null;
   assigned_folder_path?: string | null;
+  /** Selective sync: projekt i wszystko przypisane do niego nie opuszcza tej maszyny. */
+  device_local?: boolean;
   /** Sekundy per kalendarzowy dzień (all-time) — do zaokrąglania `per_day`. */
   daily_seconds: number[];
 }
@@ -341,6 +417,16 @@ export interface ProjectReportData {
   estimate: number;
   sessions: SessionWithApp[];
   manual_sessions: ManualSessionWithProject[];
+  tag_breakdown: TagTime[];
+  /** Billable / non-billable split of the project's time in the report range. */
+  billable_seconds: number;
+  non_billable_seconds: number;
+  /** Overlap policy the totals were computed with. */
+  overlap_policy: 'manual_wins' | 'tracked_wins' | 'count_both';
+  overlaps: SessionOverlap[];
+  /** Total and estimate after the backend applied the rounding settings. */
+  rounded_total_seconds: number;
+  rounded_estimate: number;
 }
 
 export interface ProjectFolder {
@@ -501,6 +587,65 @@ export interface ManualSessionWithProject {
   end_time: string;
   duration_seconds: number;
   date: string;
+  tags: string[];
+  /** Per-session override; null inherits the project's default. */
+  billable?: boolean | null;
+}
+
+/** Two sessions covering the same stretch; the first side is always tracked. */
+export interface SessionOverlap {
+  kind: 'manual_tracked' | 'cross_app';
+  date: string;
+  start_time: string;
+  end_time: string;
+  overlap_seconds: number;
+  session_id: number;
+  app_name: string;
+  project_id: number | null;
+  project_name: string | null;
+  other_session_id: number;
+  other_is_manual: boolean;
+  /** Manual session title or the other app's name. */
+  other_label: string;
+  other_project_id: number | null;
+  other_project_name: string | null;
+}
+
+export type UntrackedGapKind = 'sleep' | 'daemon_off' | 'idle' | 'pause' | 'unknown';
+
+export interface GapFillCandidate {
+  project_id: number;
+  project_name: string;
+  project_color: string;
+  source: 'previous' | 'next' | 'both';
+}
+
+export interface UntrackedGap {
+  date: string;
+  /** Local `YYYY-MM-DDTHH:MM:SS`, accepted as-is by `acceptGapFills`. */
+  start_time: string;
+  end_time: string;
+  duration_seconds: number;
+  kind: UntrackedGapKind;
+  suggested_project_id: number | null;
+  candidates: GapFillCandidate[];
+}
+
+export interface UntrackedGapReport {
+  gaps: UntrackedGap[];
+  total_seconds: number;
+  sleep_seconds: number;
+  daemon_off_seconds: number;
+  idle_seconds: number;
+  pause_seconds: number;
+  unknown_seconds: number;
+}
+
+export interface GapFillInput {
+  start_time: string;
+  end_time: string;
+  project_id: number;
+  title?: string | null;
 }
 
 export interface ExportArchive {
@@ -514,6 +659,8 @@ export interface ExportArchive {
     project_name?: string;
     total_sessions: number;
     total_seconds: number;
+    /** Zaokrąglony total (archiwa sprzed zaokrąglania: 0). */
+    rounded_total_seconds?: number;
   };
   data: {
     projects: Project[];
@@ -528,6 +675,7 @@ export interface ExportArchive {
       deleted_at: string;
       sync_key?: string | null;
     }>;
+    session_audit_log?: SessionAuditEntry[];
   };
 }
 
@@ -613,3 +761,63 @@ export interface SplitPart {
   project_id: number | null;
   ratio: number;
 }
+
+/** One undoable user action from the mutation journal. */
+export interface JournalEntry {
+  id: number;
+  /** Command that recorded it, e.g. "assign_sessions_to_project". */
+  action: string;
+  label: string;
+  row_count: number;
+  created_at: string;
+  /** Set while undone — the entry is then the next redo. */
+  undone_at: string | null;
+}
+
+/** One changed field of a manual session edit (append-only audit trail). */
+export interface SessionAuditEntry {
+  id: number;
+  session_id: number;
+  /** Project of the session after the change. */
+  project_id: number | null;
+  /** Project before the change — differs only for reassignments. */
+  previous_project_id: number | null;
+  action:
+    | 'assign'
+    | 'override'
+    | 'rate_multiplier'
+    | 'comment'
+    | 'split'
+    | 'merge'
+    | 'tag'
+    | 'billable'
+    | 'delete'
+    | 'undo'
+    | 'redo';
+  field: string;
+  old_value: string | null;
+  new_value: string | null;
+  /** Assignment source, e.g. "manual_session_assign". */
+  source: string | null;
+  device_id: string;
+  created_at: string;
+}
+
+export interface Tag {
+  id: number;
+  name: string;
+  color: string;
+  session_count: number;
+  manual_session_count: number;
+  created_at: string;
+  updated_at: string;
+}
+
+/** Time per tag — a session with several tags counts towards each of them. */
+export interface TagTime {
+  tag_id: number;
+  name: string;
+  color: string;
+  seconds: number;
+  session_count: number;
+}
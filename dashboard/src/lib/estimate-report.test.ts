@@ -16,6 +16,9 @@ function row(partial: Partial<EstimateProjectRow>): EstimateProjectRow {
     project_color: '#111',
     seconds: 3600,
     hours: 1,
+    billable_seconds: 3600,
+    non_billable_seconds: 0,
+    billable: true,
     weighted_hours: 1,
     project_hourly_rate: null,
     effective_hourly_rate: 100,
@@ -26,6 +29,8 @@ function row(partial: Partial<EstimateProjectRow>): EstimateProjectRow {
     daily_seconds: [3600],
     client_name: null,
     days: [{ date: '2026-01-01', seconds: 3600 }],
+    rounded_seconds: 3600,
+    rounded_value: 100,
     ...partial,
   };
 }
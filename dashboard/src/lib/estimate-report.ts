@@ -2,6 +2,7 @@ import {
   effectiveIntervalMinutes,
   roundDailyTotals,
   roundSeconds,
+  roundingDirection,
   scaleValueToRounded,
   type RoundingSettings,
 } from '@/lib/rounding';
@@ -74,6 +75,7 @@ export function buildEstimateReportModel(
   settings: RoundingSettings,
 ): EstimateReportModel {
   const interval = effectiveIntervalMinutes(settings);
+  const direction = roundingDirection(settings);
   const usePerDay = settings.mode === 'per_day';
 
   const projects: EstimateReportProject[] = rows.map((row) => {
@@ -82,14 +84,14 @@ export function buildEstimateReportModel(
     const displaySeconds = rounded
       ? usePerDay && dailySeconds.length > 0
         ? roundDailyTotals(dailySeconds, settings)
-        : roundSeconds(realTotal, interval)
+        : roundSeconds(realTotal, interval, direction)
       : realTotal;
     const displayValue = rounded
       ? scaleValueToRounded(row.estimated_value, realTotal, displaySeconds)
       : row.estimated_value;
 
     const days: EstimateReportDay[] = row.days.map((d) => {
-      const dayDisplaySeconds = rounded ? roundSeconds(d.seconds, interval) : d.seconds;
+      const dayDisplaySeconds = rounded ? roundSeconds(d.seconds, interval, direction) : d.seconds;
       const dayRawValue =
         realTotal > 0 ? row.estimated_value * (d.seconds / realTotal) : 0;
       const dayDisplayValue = rounded
@@ -134,6 +136,7 @@ export function roundedEstimatesSummary(
 ): RoundedEstimatesSummary | null {
   if (!settings.enabled) return null;
   const interval = effectiveIntervalMinutes(settings);
+  const direction = roundingDirection(settings);
   const usePerDay = settings.mode === 'per_day';
 
   let roundedSeconds = 0;
@@ -143,7 +146,7 @@ export function roundedEstimatesSummary(
     const rowRounded =
       usePerDay && r.daily_seconds.length > 0
         ? roundDailyTotals(r.daily_seconds, settings)
-        : roundSeconds(r.seconds, interval);
+        : roundSeconds(r.seconds, interval, direction);
     roundedSeconds += rowRounded;
     rawSeconds +=
       Number.isFinite(r.seconds) && r.seconds > 0 ? Math.floor(r.seconds) : 0;
@@ -16,6 +16,14 @@ export interface LanPeer {
   dashboard_running: boolean;
   /** Wersja TIMEFLOW peera (np. "0.1.5701"). Pusty string lub undefined = nieznana. */
   timeflow_version?: string;
+  /** Protokół sync ogłaszany przez peera; 0/undefined = peer sprzed negocjacji. */
+  sync_protocol?: number;
+  min_sync_protocol?: number;
+  /**
+   * Wyliczane przez `get_lan_peers`: zakresy protokołów się pokrywają (albo,
+   * dla peera sprzed negocjacji, wersje są identyczne). Brak = stary backend.
+   */
+  sync_compatible?: boolean;
 }
 
 export interface LanSyncState {
@@ -57,6 +65,24 @@ export interface SyncMarker {
   full_sync: boolean;
 }
 
+/** Entry of the sync conflict review queue (`sync_merge_log`). */
+export interface SyncConflict {
+  id: number;
+  sync_timestamp: string;
+  table_name: string;
+  record_key: string;
+  resolution: string;
+  local_updated_at: string | null;
+  remote_updated_at: string | null;
+  /** Side kept by last-writer-wins. */
+  winner: 'local' | 'remote';
+  local_values: Record<string, unknown> | null;
+  incoming_values: Record<string, unknown> | null;
+  reviewed_at: string | null;
+  flipped_at: string | null;
+  can_flip: boolean;
+}
+
 export interface SyncProgress {
   step: number;
   total_steps: number;
@@ -69,6 +95,79 @@ export interface SyncProgress {
   sync_type?: string;  // "lan" | "online" | ""
 }
 
+export interface SyncTableChanges {
+  inserted: number;
+  updated: number;
+  deleted: number;
+}
+
+export interface SyncProjectTotalChange {
+  project: string;
+  seconds_before: number;
+  seconds_after: number;
+}
+
+export interface SyncPreview {
+  tables: Record<string, SyncTableChanges>;
+  tombstones_added: number;
+  projects_changed: SyncProjectTotalChange[];
+  /** LWW conflicts logged during the merge (absent from older daemons). */
+  conflicts?: number;
+}
+
+/** Last sync dry-run; `finished_at === null` while it is still running. */
+export interface SyncPreviewReport {
+  sync_type: string;  // "lan" | "online"
+  peer: string;
+  started_at: string;
+  finished_at: string | null;
+  preview: SyncPreview | null;
+  error: string | null;
+}
+
+/** One recorded LAN / online sync run (`sync_runs`). */
+export interface SyncRunRecord {
+  id: number;
+  kind: 'lan' | 'online';
+  /** Empty for online runs that ended before a role was assigned. */
+  role: 'master' | 'slave' | '';
+  peer_id: string;
+  mode: string;  // "delta" | "full" | "none" | "async" | ""
+  dry_run: boolean;
+  status: 'ok' | 'error' | 'aborted';
+  error: string | null;
+  step_reached: number;
+  phase: string;
+  started_at: string;
+  finished_at: string;
+  duration_ms: number;
+  bytes_sent: number;
+  bytes_received: number;
+  rows_inserted: number;
+  rows_updated: number;
+  rows_deleted: number;
+  tombstones_added: number;
+  conflicts: number;
+  table_stats: Record<string, SyncTableChanges>;
+}
+
+/** Per-peer aggregate of sync runs over a time window (dry-runs excluded). */
+export interface SyncPeerStats {
+  kind: 'lan' | 'online';
+  peer_id: string;
+  runs: number;
+  ok: number;
+  failed: number;
+  aborted: number;
+  avg_duration_ms: number;
+  bytes_sent: number;
+  bytes_received: number;
+  conflicts: number;
+  last_run_at: string;
+  last_ok_at: string | null;
+  last_error: string | null;
+}
+
 export const LAN_SYNC_SETTINGS_KEY = 'timeflow.settings.lan-sync';
 export const LAN_SYNC_STATE_KEY = 'timeflow.state.lan-sync';
 export const LAN_SYNC_SETTINGS_CHANGED_EVENT = 'timeflow:lan-sync-settings-changed';
@@ -44,6 +44,10 @@ export interface DeltaData {
   }[];
   assignment_feedback?: unknown[];
   assignment_auto_runs?: unknown[];
+  clients?: unknown[];
+  estimate_settings?: unknown[];
+  tags?: unknown[];
+  file_activities?: unknown[];
 }
 
 export interface DeltaArchive {
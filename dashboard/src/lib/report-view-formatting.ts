@@ -3,6 +3,7 @@ import {
   effectiveIntervalMinutes,
   roundDailyTotals,
   roundSeconds,
+  roundingDirection,
   scaleValueToRounded,
   type RoundingSettings,
 } from '@/lib/rounding';
@@ -23,7 +24,7 @@ export function computeReportDisplayValues(
   const displayTotal = rounded
     ? usePerDay
       ? roundDailyTotals(dailySeconds, roundingSettings)
-      : roundSeconds(realTotal, interval)
+      : roundSeconds(realTotal, interval, roundingDirection(roundingSettings))
     : realTotal;
   const displayValue = rounded
     ? scaleValueToRounded(report.estimate, realTotal, displayTotal)
@@ -49,7 +50,7 @@ export function createReportDurationFormatter(
       rounded
         ? usePerDay && daily && daily.length > 0
           ? roundDailyTotals(daily, roundingSettings)
-          : roundSeconds(seconds, interval)
+          : roundSeconds(seconds, interval, roundingDirection(roundingSettings))
         : seconds,
     );
 }
@@ -35,6 +35,13 @@ describe('roundSeconds', () => {
     expect(roundSeconds(3600, 60)).toBe(3600);
   });
 
+  it('rounds to the nearest or down when the direction says so', () => {
+    expect(roundSeconds(1349, 15, 'nearest')).toBe(900);
+    expect(roundSeconds(1350, 15, 'nearest')).toBe(1800);
+    expect(roundSeconds(1799, 15, 'down')).toBe(900);
+    expect(roundSeconds(899, 15, 'down')).toBe(0);
+  });
+
   it('respects other intervals (6 min = 1/10 h)', () => {
     expect(roundSeconds(1, 6)).toBe(360);
     expect(roundSeconds(361, 6)).toBe(720);
@@ -153,7 +160,12 @@ describe('normalizeRoundingSettings', () => {
   });
   it('keeps valid values', () => {
     const n = normalizeRoundingSettings({ enabled: false, intervalMinutes: 30, mode: 'per_session' });
-    expect(n).toEqual({ enabled: false, intervalMinutes: 30, mode: 'per_session' });
+    expect(n).toEqual({
+      enabled: false,
+      intervalMinutes: 30,
+      mode: 'per_session',
+      direction: 'up',
+    });
   });
   it('accepts the per_day mode', () => {
     const n = normalizeRoundingSettings({ enabled: true, intervalMinutes: 15, mode: 'per_day' });
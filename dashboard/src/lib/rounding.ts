@@ -4,9 +4,12 @@
  * TIMEFLOW trzyma surowy czas (daemon/baza). Zaokrąglanie nakładamy WYŁĄCZNIE przy
  * wyświetlaniu/raportowaniu — nigdy nie modyfikujemy danych źródłowych.
  *
- * Kierunek jest stały: zawsze w GÓRĘ (ceil). Konfigurowalny jest interwał oraz
- * WARIANT (tryb) zaokrąglania. Warianty są w rejestrze `ROUNDING_VARIANTS`, więc
- * dodanie kolejnego w przyszłości = wpis w rejestrze + gałąź w `roundDurations`.
+ * Konfigurowalny jest interwał, WARIANT (tryb) i kierunek zaokrąglania (domyślnie
+ * w GÓRĘ). Warianty są w rejestrze `ROUNDING_VARIANTS`, więc dodanie kolejnego
+ * w przyszłości = wpis w rejestrze + gałąź w `roundDurations`.
+ *
+ * Backend ma lustrzaną implementację (`src-tauri/src/commands/rounding.rs`) dla
+ * estymacji, raportów i eksportu — każda zmiana reguł musi trafić do obu miejsc.
  */
 
 /** Tryb (wariant) zaokrąglania. Rozszerzalny — dodaj nowy id tutaj i obsłuż w `roundDurations`. */
@@ -42,6 +45,11 @@ export const ROUNDING_VARIANTS: readonly RoundingVariant[] = [
   },
 ] as const;
 
+/** Kierunek zaokrąglania do wielokrotności interwału. */
+export type RoundingDirection = 'up' | 'nearest' | 'down';
+
+export const ROUNDING_DIRECTIONS: readonly RoundingDirection[] = ['up', 'nearest', 'down'] as const;
+
 /** Dozwolone interwały zaokrąglania w minutach (6 min = 1/10 h). */
 export const ROUNDING_INTERVALS = [1, 5, 6, 10, 15, 30, 60] as const;
 
@@ -49,22 +57,38 @@ export interface RoundingSettings {
   enabled: boolean;
   intervalMinutes: number;
   mode: RoundingMode;
+  /** Brak = 'up' (ustawienia sprzed wyboru kierunku). */
+  direction?: RoundingDirection;
 }
 
 export const DEFAULT_ROUNDING_SETTINGS: RoundingSettings = {
   enabled: false,
   intervalMinutes: 15,
   mode: 'per_total',
+  direction: 'up',
 };
 
 /**
- * Zaokrągla pojedynczą wartość w sekundach W GÓRĘ do najbliższej wielokrotności
- * interwału. 0 (lub wartość niepoprawna/ujemna) → 0 (nie nadbijamy pustego czasu).
+ * Zaokrągla pojedynczą wartość w sekundach do wielokrotności interwału w danym
+ * kierunku (domyślnie W GÓRĘ). 0 (lub wartość niepoprawna/ujemna) → 0 (nie
+ * nadbijamy pustego czasu).
  */
-export function roundSeconds(seconds: number, intervalMinutes: number): number {
+export function roundSeconds(
+  seconds: number,
+  intervalMinutes: number,
+  direction: RoundingDirection = 'up',
+): number {
   if (!Number.isFinite(seconds) || seconds <= 0) return 0;
   const step = Math.max(1, Math.round(intervalMinutes)) * 60;
-  return Math.ceil(seconds / step) * step;
+  const steps = seconds / step;
+  if (direction === 'nearest') return Math.round(steps) * step;
+  if (direction === 'down') return Math.floor(steps) * step;
+  return Math.ceil(steps) * step;
+}
+
+/** Kierunek z ustawień (brak → w górę). */
+export function roundingDirection(settings: RoundingSettings): RoundingDirection {
+  return settings.direction ?? 'up';
 }
 
 /**
@@ -95,11 +119,16 @@ export function roundDurations(
   if (!settings.enabled) return rawTotal;
   if (settings.mode === 'per_session') {
     return perSessionSeconds.reduce(
-      (acc, s) => acc + roundSeconds(s, settings.intervalMinutes),
+      (acc, s) =>
+        acc + roundSeconds(s, settings.intervalMinutes, roundingDirection(settings)),
       0,
     );
   }
-  return roundSeconds(rawTotal, effectiveIntervalMinutes(settings));
+  return roundSeconds(
+    rawTotal,
+    effectiveIntervalMinutes(settings),
+    roundingDirection(settings),
+  );
 }
 
 /**
@@ -116,7 +145,8 @@ export function roundDailyTotals(
 ): number {
   if (settings.enabled && settings.mode === 'per_day') {
     return perDaySeconds.reduce(
-      (acc, s) => acc + roundSeconds(s, FULL_HOUR_MINUTES),
+      (acc, s) =>
+        acc + roundSeconds(s, FULL_HOUR_MINUTES, roundingDirection(settings)),
       0,
     );
   }
@@ -133,7 +163,11 @@ export function roundAggregate(
   settings: RoundingSettings,
 ): number {
   if (!settings.enabled) return totalSeconds;
-  return roundSeconds(totalSeconds, effectiveIntervalMinutes(settings));
+  return roundSeconds(
+    totalSeconds,
+    effectiveIntervalMinutes(settings),
+    roundingDirection(settings),
+  );
 }
 
 /**
@@ -163,5 +197,8 @@ export function normalizeRoundingSettings(
   const mode = ROUNDING_VARIANTS.some((v) => v.id === parsed.mode)
     ? (parsed.mode as RoundingMode)
     : DEFAULT_ROUNDING_SETTINGS.mode;
-  return { enabled: !!parsed.enabled, intervalMinutes, mode };
+  const direction = ROUNDING_DIRECTIONS.includes(parsed.direction as RoundingDirection)
+    ? (parsed.direction as RoundingDirection)
+    : 'up';
+  return { enabled: !!parsed.enabled, intervalMinutes, mode, direction };
 }
@@ -20,6 +20,7 @@ function buildSession(overrides: Partial<SessionWithApp> = {}): SessionWithApp {
     project_name: null,
     project_color: null,
     files: [],
+    tags: [],
     ...overrides,
   };
 }
@@ -14,4 +14,6 @@ export * from './tauri/online-sync';
 export * from './tauri/log-management';
 export * from './tauri/pm';
 export * from './tauri/clients';
+export * from './tauri/tags';
+export * from './tauri/gaps';
 export * from './tauri/webserver';
@@ -4,6 +4,10 @@ import type {
   AssignmentMode,
   AssignmentModelMetrics,
   AssignmentModelStatus,
+  AssignmentRule,
+  AssignmentRuleApplyResult,
+  AssignmentRuleInput,
+  AssignmentRulePreview,
   AutoSafeRollbackResult,
   AutoSafeRunResult,
   DateRange,
@@ -93,6 +97,43 @@ export const applyDeterministicAssignment = (minHistory?: number) =>
     notify: (result) => result.sessions_assigned > 0,
   });
 
+export const getAssignmentRules = () =>
+  invoke<AssignmentRule[]>('get_assignment_rules');
+
+export const createAssignmentRule = (rule: AssignmentRuleInput) =>
+  invokeMutation<AssignmentRule>('create_assignment_rule', { rule });
+
+export const updateAssignmentRule = (
+  ruleId: number,
+  rule: AssignmentRuleInput,
+) =>
+  invokeMutation<AssignmentRule>('update_assignment_rule', { ruleId, rule });
+
+export const deleteAssignmentRule = (ruleId: number) =>
+  invokeMutation<void>('delete_assignment_rule', { ruleId });
+
+export const previewAssignmentRule = (
+  rule: AssignmentRuleInput,
+  onlyUnassigned = false,
+) =>
+  invoke<AssignmentRulePreview>('preview_assignment_rule', {
+    rule,
+    onlyUnassigned,
+  });
+
+export const applyAssignmentRule = (ruleId: number, onlyUnassigned = false) =>
+  invokeMutation<AssignmentRuleApplyResult>('apply_assignment_rule', {
+    ruleId,
+    onlyUnassigned,
+  });
+
+export const applyActiveAssignmentRules = () =>
+  invokeMutation<AssignmentRuleApplyResult>(
+    'apply_active_assignment_rules',
+    undefined,
+    { notify: (result) => result.sessions_assigned > 0 },
+  );
+
 export const setFeedbackWeight = (weight: number) =>
   invokeMutation<void>('set_feedback_weight', { weight });
 
@@ -120,6 +161,13 @@ export const aiApi = {
   rollbackLastAutoSafeRun,
   autoRunIfNeeded,
   applyDeterministicAssignment,
+  getAssignmentRules,
+  createAssignmentRule,
+  updateAssignmentRule,
+  deleteAssignmentRule,
+  previewAssignmentRule,
+  applyAssignmentRule,
+  applyActiveAssignmentRules,
   setFeedbackWeight,
   scanProjectFoldersForAi,
   getFolderScanStatus,
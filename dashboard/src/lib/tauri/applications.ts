@@ -11,6 +11,9 @@ export const getApplications = (dateRange?: DateRange) =>
 export const updateAppColor = (id: number, color: string) =>
   invokeMutation<void>('update_app_color', { id, color });
 
+export const setAppDeviceLocal = (id: number, deviceLocal: boolean) =>
+  invokeMutation<void>('set_app_device_local', { id, deviceLocal });
+
 export const resetAppTime = (appId: number) =>
   invokeMutation<void>('reset_app_time', { appId });
 
@@ -23,6 +26,7 @@ export const deleteAppAndData = (appId: number) =>
 export const applicationsApi = {
   getApplications,
   updateAppColor,
+  setAppDeviceLocal,
   resetAppTime,
   renameApplication,
   deleteAppAndData,
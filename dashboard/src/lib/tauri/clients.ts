@@ -26,6 +26,8 @@ export interface ClientProjectSummary {
   status: ProjectStatus;
   seconds: number;
   value: number;
+  billable_seconds: number;
+  non_billable_seconds: number;
   /** Sekundy per kalendarzowy dzień — do zaokrąglania `per_day`. */
   daily_seconds: number[];
 }
@@ -37,6 +39,8 @@ export interface ClientSummary {
   project_count: number;
   total_seconds: number;
   total_value: number;
+  billable_seconds: number;
+  non_billable_seconds: number;
   active_value: number;
   done_value: number;
   paid_value: number;
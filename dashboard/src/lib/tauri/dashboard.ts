@@ -44,6 +44,9 @@ export const updateProjectHourlyRate = (
   rate: number | null,
 ) => invokeMutation<void>('update_project_hourly_rate', { projectId, rate });
 
+export const updateProjectBillable = (projectId: number, billable: boolean) =>
+  invokeMutation<void>('update_project_billable', { projectId, billable });
+
 export const getProjectEstimates = (dateRange: DateRange) =>
   invoke<EstimateProjectRow[]>('get_project_estimates', { dateRange });
 
@@ -71,6 +74,7 @@ export const dashboardApi = {
   getEstimateSettings,
   updateGlobalHourlyRate,
   updateProjectHourlyRate,
+  updateProjectBillable,
   getProjectEstimates,
   getEstimatesSummary,
   getProjectTimeline,
@@ -0,0 +1,30 @@
+// @public-api — Tauri command bindings; knip cannot detect dynamic invoke() usage
+import { invoke, invokeMutation } from './core';
+import type {
+  DateRange,
+  GapFillInput,
+  ManualSession,
+  UntrackedGapReport,
+} from '../db-types';
+import type { WorkingHoursSettings } from '../user-settings';
+
+/** Gaps inside working hours not covered by tracked or manual sessions. */
+export const getUntrackedGaps = (
+  dateRange: DateRange,
+  workingHours: Pick<WorkingHoursSettings, 'start' | 'end'>,
+  minGapMinutes?: number,
+) =>
+  invoke<UntrackedGapReport>('get_untracked_gaps', {
+    dateRange,
+    workingHours: { start: workingHours.start, end: workingHours.end },
+    minGapMinutes: minGapMinutes ?? null,
+  });
+
+/** Creates one manual session per fill in a single transaction. */
+export const acceptGapFills = (fills: GapFillInput[]) =>
+  invokeMutation<ManualSession[]>('accept_gap_fills', { fills });
+
+export const gapsApi = {
+  getUntrackedGaps,
+  acceptGapFills,
+} as const;
@@ -4,8 +4,12 @@ import type {
   LanPeer,
   LanSyncResult,
   LanServerStatus,
+  SyncConflict,
   SyncMarker,
   SyncProgress,
+  SyncPreviewReport,
+  SyncPeerStats,
+  SyncRunRecord,
   PairingCodeInfo,
   PairedDeviceInfo,
 } from '../lan-sync-types';
@@ -24,6 +28,8 @@ export interface PingLanPeerResult {
   dashboard_port: number;
   role: string;
   version: string;
+  sync_protocol?: number;
+  min_sync_protocol?: number;
 }
 
 export const pingLanPeer = (ip: string, port: number) =>
@@ -38,6 +44,13 @@ export const buildTableHashesOnly = () =>
 export const runLanSync = (peerIp: string, peerPort: number, since: string, force?: boolean) =>
   invokeMutation<LanSyncResult>('run_lan_sync', { peerIp, peerPort, since, force: force ?? false });
 
+/** Dry-run: the daemon merges in a rolled-back transaction — read the result via getSyncPreview. */
+export const previewLanSync = (peerIp: string, peerPort: number, since: string) =>
+  invoke<LanSyncResult>('run_lan_sync', { peerIp, peerPort, since, force: false, dryRun: true });
+
+export const getSyncPreview = () =>
+  invoke<SyncPreviewReport | null>('get_sync_preview');
+
 export const startLanServer = (port?: number) =>
   invoke<void>('start_lan_server', { port });
 
@@ -53,6 +66,28 @@ export const insertSyncMarker = (tablesHash: string, deviceId: string, peerId?:
 export const getLatestSyncMarker = () =>
   invoke<SyncMarker | null>('get_latest_sync_marker');
 
+export const getSyncConflicts = (includeReviewed?: boolean, limit?: number) =>
+  invoke<SyncConflict[]>('get_sync_conflicts', { includeReviewed, limit });
+
+export const markSyncConflictsReviewed = (ids: number[]) =>
+  invokeMutation<number>('mark_sync_conflicts_reviewed', { ids });
+
+export const flipSyncConflict = (id: number) =>
+  invokeMutation<SyncConflict>('flip_sync_conflict', { id });
+
+export const getSyncRuns = (filter?: {
+  kind?: 'lan' | 'online';
+  status?: 'ok' | 'error' | 'aborted';
+  peerId?: string;
+  limit?: number;
+}) => invoke<SyncRunRecord[]>('get_sync_runs', { ...filter });
+
+export const getSyncRunStats = (days?: number) =>
+  invoke<SyncPeerStats[]>('get_sync_run_stats', { days });
+
+export const clearSyncHistory = (olderThanDays?: number) =>
+  invokeMutation<number>('clear_sync_history', { olderThanDays });
+
 export const markersMatch = (remoteMarkerHash?: string | null) =>
   invoke<boolean>('markers_match', { remoteMarkerHash });
 
@@ -89,11 +124,19 @@ export const lanSyncApi = {
   getLanSyncLog,
   buildTableHashesOnly,
   runLanSync,
+  previewLanSync,
+  getSyncPreview,
   startLanServer,
   stopLanServer,
   getLanServerStatus,
   insertSyncMarker,
   getLatestSyncMarker,
+  getSyncConflicts,
+  markSyncConflictsReviewed,
+  flipSyncConflict,
+  getSyncRuns,
+  getSyncRunStats,
+  clearSyncHistory,
   markersMatch,
   backupBeforeSync,
   getLanSyncProgress,
@@ -7,6 +7,7 @@ export interface LogSettings {
   online_sync_level: string;
   dashboard_level: string;
   max_log_size_kb: number;
+  sync_history_retention_days: number;
 }
 
 export interface LogFileInfo {
@@ -14,6 +14,7 @@ export const createManualSession = (input: {
 export const getManualSessions = (filters: {
   dateRange?: DateRange;
   projectId?: number;
+  tagIds?: number[];
 }) => invoke<ManualSessionWithProject[]>('get_manual_sessions', { filters });
 
 export const updateManualSession = (
@@ -34,10 +35,15 @@ export const deleteManualSession = (id: number) =>
 export const deleteManualSessionsBatch = (ids: number[]) =>
   invokeMutation<void>('delete_manual_sessions', { ids });
 
+/** `null` clears the override — the sessions inherit the project's default. */
+export const updateManualSessionsBillable = (ids: number[], billable: boolean | null) =>
+  invokeMutation<void>('update_manual_sessions_billable', { ids, billable });
+
 export const manualSessionsApi = {
   createManualSession,
   getManualSessions,
   updateManualSession,
   deleteManualSession,
   deleteManualSessionsBatch,
+  updateManualSessionsBillable,
 } as const;
@@ -10,6 +10,27 @@ export interface DaemonOnlineSyncSettings {
   encryption_key: string;
   sync_interval_minutes: number;
   auto_sync_on_startup: boolean;
+  /** Omitted on save = keep the backend already configured. */
+  storage?: DaemonOnlineStorageSettings;
+  /**
+   * End-to-end passphrase (keychain only). Omit to keep it, '' turns it off.
+   * To change an existing one use changeDaemonOnlineSyncPassphrase.
+   */
+  passphrase?: string;
+}
+
+/** Where transfer blobs go; the sync server still coordinates. */
+export interface DaemonOnlineStorageSettings {
+  backend: 'server' | 'local' | 'webdav' | 's3';
+  local_path?: string;
+  url?: string;
+  bucket?: string;
+  region?: string;
+  path_style?: boolean;
+  prefix?: string;
+  username?: string;
+  /** WebDAV password / S3 secret key — stored in the OS keychain. */
+  password?: string;
 }
 
 export const getDaemonOnlineSyncSettings = () =>
@@ -21,16 +42,47 @@ export const saveDaemonOnlineSyncSettings = (settings: DaemonOnlineSyncSettings)
 export const triggerDaemonOnlineSync = () =>
   invokeMutation<string>('run_online_sync');
 
+/** Dry-run: nothing is written — read the result via getSyncPreview. */
+export const previewDaemonOnlineSync = () =>
+  invoke<string>('run_online_sync', { dryRun: true });
+
 export const getDaemonOnlineSyncProgress = () =>
   invoke<SyncProgress>('get_online_sync_progress');
 
 export const cancelDaemonOnlineSync = () =>
   invokeMutation<void>('cancel_online_sync');
 
+/** Re-wraps the daemon's sync key under the new passphrase; history stays readable. */
+export const changeDaemonOnlineSyncPassphrase = (
+  oldPassphrase: string,
+  newPassphrase: string,
+) =>
+  invokeMutation<void>('change_online_sync_passphrase', {
+    oldPassphrase,
+    newPassphrase,
+  });
+
+/** Outcome of rotateDaemonOnlineSyncKey — which devices already got the new key. */
+export interface DaemonKeyRotationReport {
+  key_id: string;
+  lan_delivered: string[];
+  /** [device_id, error] */
+  lan_failed: [string, string][];
+  package_id: string | null;
+  package_error: string | null;
+}
+
+/** New encryption_key for all paired devices; the old one keeps opening in-flight packages. */
+export const rotateDaemonOnlineSyncKey = () =>
+  invokeMutation<DaemonKeyRotationReport>('rotate_online_sync_key');
+
 export const daemonOnlineSyncApi = {
   getDaemonOnlineSyncSettings,
   saveDaemonOnlineSyncSettings,
   triggerDaemonOnlineSync,
+  previewDaemonOnlineSync,
   getDaemonOnlineSyncProgress,
   cancelDaemonOnlineSync,
+  changeDaemonOnlineSyncPassphrase,
+  rotateDaemonOnlineSyncKey,
 } as const;
@@ -52,6 +52,9 @@ export const freezeProject = (id: number) =>
 export const unfreezeProject = (id: number) =>
   invokeMutation<void>('unfreeze_project', { id });
 
+export const setProjectDeviceLocal = (id: number, deviceLocal: boolean) =>
+  invokeMutation<void>('set_project_device_local', { id, deviceLocal });
+
 export const mergeProject = (sourceId: number, targetId: number) =>
   invokeMutation<void>('merge_project', { sourceId, targetId });
 
@@ -185,6 +188,7 @@ export const projectsApi = {
   deleteProject,
   freezeProject,
   unfreezeProject,
+  setProjectDeviceLocal,
   mergeProject,
   unmergeProject,
   autoFreezeProjects,
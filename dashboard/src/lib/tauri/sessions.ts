@@ -2,8 +2,11 @@
 import { invoke, invokeMutation } from './core';
 import type {
   DateRange,
+  JournalEntry,
   MultiProjectAnalysis,
   ScoreBreakdown,
+  SessionAuditEntry,
+  SessionOverlap,
   SessionSplittableFlag,
   SessionWithApp,
   SplitPart,
@@ -15,6 +18,8 @@ export const getSessions = (filters: {
   projectId?: number;
   unassigned?: boolean;
   minDuration?: number;
+  /** Sessions carrying at least one of these tags. */
+  tagIds?: number[];
   includeFiles?: boolean;
   includeAiSuggestions?: boolean;
   limit?: number;
@@ -27,6 +32,8 @@ export const getSessionCount = (filters: {
   projectId?: number;
   unassigned?: boolean;
   minDuration?: number;
+  /** Sessions carrying at least one of these tags. */
+  tagIds?: number[];
 }) => invoke<number>('get_session_count', { filters });
 
 export const rebuildSessions = (gapFillMinutes: number) =>
@@ -68,6 +75,12 @@ export const updateSessionCommentsBatch = (
   comment: string | null,
 ) => invokeMutation<void>('update_session_comments', { sessionIds, comment });
 
+/** `null` clears the override — the sessions inherit the project's default. */
+export const updateSessionsBillable = (
+  sessionIds: number[],
+  billable: boolean | null,
+) => invokeMutation<void>('update_sessions_billable', { sessionIds, billable });
+
 export const analyzeSessionProjects = (
   sessionId: number,
   toleranceThreshold: number,
@@ -101,9 +114,46 @@ export const splitSessionMulti = (
     notModifiedSince: notModifiedSince ?? null,
   });
 
+/** Joins adjacent sessions of one app; resolves to the merged session id. */
+export const mergeSessions = (sessionIds: number[], maxGapMinutes?: number) =>
+  invokeMutation<number>('merge_sessions', {
+    sessionIds,
+    maxGapMinutes: maxGapMinutes ?? null,
+  });
+
 export const getSessionScoreBreakdown = (sessionId: number) =>
   invoke<ScoreBreakdown>('get_session_score_breakdown', { sessionId });
 
+/** Newest first, including undone entries (the redo stack). */
+export const getActionHistory = (limit?: number) =>
+  invoke<JournalEntry[]>('get_action_history', { limit: limit ?? null });
+
+/** Resolves to null when there is nothing to undo. */
+export const undoLastAction = () =>
+  invokeMutation<JournalEntry | null>('undo_last_action');
+
+/** Resolves to null when there is nothing to redo. */
+export const redoLastAction = () =>
+  invokeMutation<JournalEntry | null>('redo_last_action');
+
+/** Audit trail of one session, oldest first. */
+export const getSessionAuditLog = (sessionId: number) =>
+  invoke<SessionAuditEntry[]>('get_session_audit_log', { sessionId });
+
+/** Audit entries of sessions in or moved out of a project, newest first. */
+export const getProjectAuditLog = (projectId: number, limit?: number) =>
+  invoke<SessionAuditEntry[]>('get_project_audit_log', {
+    projectId,
+    limit: limit ?? null,
+  });
+
+/** Manual↔tracked and cross-app overlaps, optionally for one project. */
+export const getSessionOverlaps = (dateRange: DateRange, projectId?: number) =>
+  invoke<SessionOverlap[]>('get_session_overlaps', {
+    dateRange,
+    projectId: projectId ?? null,
+  });
+
 export const sessionsApi = {
   getSessions,
   getSessionCount,
@@ -114,8 +164,16 @@ export const sessionsApi = {
   updateSessionRateMultipliersBatch,
   updateSessionComment,
   updateSessionCommentsBatch,
+  updateSessionsBillable,
   analyzeSessionProjects,
   analyzeSessionsSplittable,
   splitSessionMulti,
+  mergeSessions,
   getSessionScoreBreakdown,
+  getActionHistory,
+  undoLastAction,
+  redoLastAction,
+  getSessionAuditLog,
+  getProjectAuditLog,
+  getSessionOverlaps,
 } as const;
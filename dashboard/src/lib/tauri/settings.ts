@@ -27,6 +27,14 @@ export const getTimeAlgorithm = () => invoke<string>('get_time_algorithm');
 export const setTimeAlgorithm = (algorithm: string) =>
   invokeMutation<void>('set_time_algorithm', { algorithm });
 
+/** How manual and tracked sessions covering the same time are counted. */
+export type OverlapPolicy = 'manual_wins' | 'tracked_wins' | 'count_both';
+
+export const getOverlapPolicy = () => invoke<OverlapPolicy>('get_overlap_policy');
+
+export const setOverlapPolicy = (policy: OverlapPolicy) =>
+  invokeMutation<void>('set_overlap_policy', { policy });
+
 export const getSecureToken = () => invoke<string>('get_secure_token');
 
 export const setSecureToken = (token: string) =>
@@ -78,6 +86,8 @@ export const settingsApi = {
   listTimeAlgorithms,
   getTimeAlgorithm,
   setTimeAlgorithm,
+  getOverlapPolicy,
+  setOverlapPolicy,
   getSecureToken,
   setSecureToken,
   getAllUserSettings,
@@ -0,0 +1,42 @@
+// @public-api — Tauri command bindings; knip cannot detect dynamic invoke() usage
+import { invoke, invokeMutation } from './core';
+import type { DateRange, Tag, TagTime } from '../db-types';
+
+export const getTags = () => invoke<Tag[]>('get_tags');
+
+export const getTagTimeBreakdown = (dateRange: DateRange) =>
+  invoke<TagTime[]>('get_tag_time_breakdown', { dateRange });
+
+export const createTag = (name: string, color?: string | null) =>
+  invokeMutation<Tag>('create_tag', { name, color: color ?? null });
+
+export const updateTag = (id: number, name: string, color?: string | null) =>
+  invokeMutation<Tag>('update_tag', { id, name, color: color ?? null });
+
+export const deleteTag = (id: number) =>
+  invokeMutation<void>('delete_tag', { id });
+
+/** Returns the number of links actually added. */
+export const addTagsToSessions = (sessionIds: number[], tagIds: number[]) =>
+  invokeMutation<number>('add_tags_to_sessions', { sessionIds, tagIds });
+
+export const removeTagsFromSessions = (sessionIds: number[], tagIds: number[]) =>
+  invokeMutation<number>('remove_tags_from_sessions', { sessionIds, tagIds });
+
+export const addTagsToManualSessions = (
+  manualSessionIds: number[],
+  tagIds: number[],
+) =>
+  invokeMutation<number>('add_tags_to_manual_sessions', {
+    manualSessionIds,
+    tagIds,
+  });
+
+export const removeTagsFromManualSessions = (
+  manualSessionIds: number[],
+  tagIds: number[],
+) =>
+  invokeMutation<number>('remove_tags_from_manual_sessions', {
+    manualSessionIds,
+    tagIds,
+  });
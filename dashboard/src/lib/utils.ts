@@ -5,6 +5,7 @@ import { twMerge } from "tailwind-merge";
 import { formatMultiplierLabel } from '@/lib/rate-utils';
 import {
   roundSeconds,
+  roundingDirection,
   roundDailyTotals,
   effectiveIntervalMinutes,
 } from '@/lib/rounding';
@@ -41,7 +42,7 @@ export function getDurationParts(totalSeconds: number) {
 export function roundedAlternativeSeconds(seconds: number): number | null {
   const r = loadRoundingSettings();
   if (!r.enabled) return null;
-  const rounded = roundSeconds(seconds, effectiveIntervalMinutes(r));
+  const rounded = roundSeconds(seconds, effectiveIntervalMinutes(r), roundingDirection(r));
   const raw = Number.isFinite(seconds) && seconds > 0 ? Math.floor(seconds) : 0;
   return rounded === raw ? null : rounded;
 }
@@ -62,7 +63,7 @@ export function roundedAlternativeFromDaily(
   const rounded =
     r.mode === 'per_day' && dailySeconds.length > 0
       ? roundDailyTotals(dailySeconds, r)
-      : roundSeconds(totalSeconds, effectiveIntervalMinutes(r));
+      : roundSeconds(totalSeconds, effectiveIntervalMinutes(r), roundingDirection(r));
   const raw =
     Number.isFinite(totalSeconds) && totalSeconds > 0
       ? Math.floor(totalSeconds)
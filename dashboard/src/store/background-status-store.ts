@@ -106,8 +106,10 @@ interface BackgroundStatusState {
   lanPeerPaired: boolean;
   lanIsSlave: boolean;
   /**
-   * `false` only when peer is online AND local + peer `timeflow_version`
-   * are both known AND differ. Unknown versions default to `true` so we
+   * `false` only when peer is online AND backend reports it protocol
+   * incompatible (`sync_compatible`), or — for older backends without that
+   * field — local + peer `timeflow_version` are both known AND differ.
+   * Unknown versions default to `true` so we
    * don't flash a false "mismatch" warning during startup. Backend still
    * rejects sync on real mismatch (412), this is purely a UI gate.
    */
@@ -242,7 +244,10 @@ export const useBackgroundStatusStore = create<BackgroundStatusState>(
         const localVersion = (get().daemonStatus?.dashboard_version ?? '').trim();
         const peerVersion = (online?.timeflow_version ?? '').trim();
         const versionOk =
-          !online || localVersion === '' || peerVersion === '' || localVersion === peerVersion;
+          !online ||
+          (online.sync_compatible !== undefined
+            ? online.sync_compatible
+            : localVersion === '' || peerVersion === '' || localVersion === peerVersion);
         if (
           get().lanPeer !== online ||
           get().lanPeerPaired !== isPaired ||
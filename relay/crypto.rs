@@ -0,0 +1,142 @@
+//! Credential envelope and random identifiers.
+//!
+//! `encrypt_credentials` is the counterpart of the daemon's
+//! `sync_encryption::decrypt_credentials`: the key is derived per session /
+//! package id from the account's encryption key, so a leaked envelope is
+//! useless for any other transfer. `keyId` names the account key, so devices
+//! holding several keys (after a rotation) pick the right one.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+type AesNonce =
+    aes_gcm::aead::generic_array::GenericArray<u8, aes_gcm::aead::generic_array::typenum::U12>;
+
+const GCM_TAG_LEN: usize = 16;
+
+/// prk = HMAC-SHA256(master_key, id), okm = HMAC-SHA256(prk, purpose)
+fn derive_key(master_key: &str, id: &str, purpose: &str) -> [u8; 32] {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(master_key.as_bytes())
+        .expect("HMAC accepts any key length");
+    mac.update(id.as_bytes());
+    let prk = mac.finalize().into_bytes();
+
+    let mut mac2 = <HmacSha256 as Mac>::new_from_slice(&prk).expect("HMAC accepts any key length");
+    mac2.update(purpose.as_bytes());
+    mac2.finalize().into_bytes().into()
+}
+
+/// First 8 bytes of SHA-256(key) as hex — the daemon's `sync_encryption::key_id`.
+pub fn key_id(master_key: &str) -> String {
+    to_hex(&Sha256::digest(master_key.as_bytes())[..8])
+}
+
+/// Encrypts the storage credentials JSON for one session / package.
+/// Returns the `{encryptedPayload, iv, tag, keyId}` object the client expects.
+pub fn encrypt_credentials(
+    plaintext: &str,
+    master_key: &str,
+    id: &str,
+) -> Result<serde_json::Value, String> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let key_bytes = derive_key(master_key, id, "credential-encryption");
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key_bytes));
+
+    let iv = random_bytes::<12>()?;
+    let mut sealed = cipher
+        .encrypt(&AesNonce::clone_from_slice(&iv), plaintext.as_bytes())
+        .map_err(|e| format!("Credential encryption failed: {}", e))?;
+    // aes-gcm appends the tag; the wire format carries it separately.
+    let tag = sealed.split_off(sealed.len() - GCM_TAG_LEN);
+
+    Ok(serde_json::json!({
+        "encryptedPayload": engine.encode(&sealed),
+        "iv": engine.encode(iv),
+        "tag": engine.encode(tag),
+        "keyId": key_id(master_key),
+    }))
+}
+
+pub fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut buf = [0u8; N];
+    getrandom::getrandom(&mut buf).map_err(|e| format!("getrandom failed: {}", e))?;
+    Ok(buf)
+}
+
+/// 128-bit random id as 32 hex chars (session / package ids, grant tokens).
+pub fn random_id() -> Result<String, String> {
+    Ok(to_hex(&random_bytes::<16>()?))
+}
+
+/// Fresh AES-256 file key, base64 — what `fileEncryptionKey` carries.
+pub fn random_file_key() -> Result<String, String> {
+    Ok(base64::engine::general_purpose::STANDARD.encode(random_bytes::<32>()?))
+}
+
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mirror of the daemon's `decrypt_credentials`.
+    fn decrypt(envelope: &serde_json::Value, master_key: &str, id: &str) -> Result<String, String> {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let field = |name: &str| {
+            engine
+                .decode(envelope[name].as_str().unwrap_or_default())
+                .map_err(|e| e.to_string())
+        };
+        let mut combined = field("encryptedPayload")?;
+        combined.extend_from_slice(&field("tag")?);
+        let iv = field("iv")?;
+        let key_bytes = derive_key(master_key, id, "credential-encryption");
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key_bytes));
+        let plain = cipher
+            .decrypt(&AesNonce::clone_from_slice(&iv), combined.as_ref())
+            .map_err(|e| e.to_string())?;
+        String::from_utf8(plain).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn credentials_decrypt_only_with_the_same_key_and_id() {
+        let envelope = encrypt_credentials(r#"{"host":"h"}"#, "master", "session-1").unwrap();
+        assert_eq!(
+            decrypt(&envelope, "master", "session-1").unwrap(),
+            r#"{"host":"h"}"#
+        );
+        assert!(decrypt(&envelope, "master", "session-2").is_err());
+        assert!(decrypt(&envelope, "other", "session-1").is_err());
+        assert_eq!(envelope["keyId"], key_id("master"));
+        assert_eq!(key_id("master").len(), 16);
+    }
+
+    #[test]
+    fn ids_and_keys_have_the_expected_shape() {
+        let id = random_id().unwrap();
+        assert_eq!(id.len(), 32);
+        assert_ne!(id, random_id().unwrap());
+        let key = base64::engine::general_purpose::STANDARD
+            .decode(random_file_key().unwrap())
+            .unwrap();
+        assert_eq!(key.len(), 32);
+        assert!(constant_time_eq("abc", "abc"));
+        assert!(!constant_time_eq("abc", "abd"));
+        assert!(!constant_time_eq("abc", "ab"));
+    }
+}
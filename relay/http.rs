@@ -0,0 +1,216 @@
+//! Minimal HTTP/1.1 request parsing and responses — one request per
+//! connection, `Connection: close`, same shape as the daemon's LAN server.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+
+const MAX_HEADERS: usize = 100;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    /// Value of `Authorization: Bearer …`, empty when absent.
+    pub bearer: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn json(&self) -> Result<serde_json::Value, Response> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| Response::error(400, &format!("invalid JSON body: {}", e)))
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, value: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    pub fn ok(value: serde_json::Value) -> Self {
+        Self::json(200, value)
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::json(status, serde_json::json!({ "ok": false, "error": message }))
+    }
+
+    pub fn bytes(data: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/octet-stream",
+            body: data,
+        }
+    }
+}
+
+/// Reads one request. Bodies over `max_body` are answered with 413 by the
+/// caller — returned as `Err(Response)` together with malformed requests.
+pub fn read_request(stream: &TcpStream, max_body: usize) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let bad = |msg: &str| Response::error(400, msg);
+
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|e| bad(&e.to_string()))?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(m), Some(t)) => (m.to_string(), t.to_string()),
+        _ => return Err(bad("invalid request line")),
+    };
+
+    let mut content_length = 0usize;
+    let mut bearer = String::new();
+    for count in 0.. {
+        if count > MAX_HEADERS {
+            return Err(bad("too many headers"));
+        }
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|e| bad(&e.to_string()))?;
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                content_length = value.parse().map_err(|_| bad("invalid Content-Length"))?
+            }
+            "authorization" => {
+                if let Some(token) = value.strip_prefix("Bearer ") {
+                    bearer = token.trim().to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if content_length > max_body {
+        return Err(Response::error(
+            413,
+            &format!(
+                "payload too large ({} bytes, max {})",
+                content_length, max_body
+            ),
+        ));
+    }
+    let mut body = vec![0u8; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| bad(&e.to_string()))?;
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), parse_query(query)),
+        None => (target, Vec::new()),
+    };
+    Ok(Request {
+        method,
+        path,
+        query,
+        bearer,
+        body,
+    })
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(k), percent_decode(v))
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                match (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                    (Some(hi), Some(lo)) => {
+                        out.push(hi << 4 | lo);
+                        i += 3;
+                        continue;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            b'+' => out.push(b' '),
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+pub fn write_response(stream: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        status_text(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+fn status_text(code: u16) -> &'static str {
+    match code {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_is_split_and_percent_decoded() {
+        let q = parse_query("deviceId=dev%2D1&x=a+b&flag&bad=%zz");
+        assert_eq!(q[0], ("deviceId".to_string(), "dev-1".to_string()));
+        assert_eq!(q[1], ("x".to_string(), "a b".to_string()));
+        assert_eq!(q[2], ("flag".to_string(), String::new()));
+        assert_eq!(q[3], ("bad".to_string(), "%zz".to_string()));
+    }
+}
@@ -0,0 +1,633 @@
+//! TIMEFLOW sync relay — a self-hostable stand-in for the online sync server.
+//!
+//! Implements the API the daemon's `online_sync` talks to:
+//!
+//! - session sync: `POST /api/sync/session/create`,
+//!   `GET /api/sync/session/<id>/status?deviceId=…`,
+//!   `POST /api/sync/session/<id>/{report,heartbeat,cancel}`
+//! - async delta: `POST /api/sync/async/{push,pending,credentials,ack,reject}`
+//!
+//! Transfers go to the relay itself instead of SFTP: the storage credentials
+//! carry `protocol: "http"`, `host: <public_url>` and a grant token, and the
+//! daemon `PUT`s / `GET`s `/api/storage/<path>`. Blobs are encrypted by the
+//! devices with `fileEncryptionKey` before upload.
+//!
+//! Accounts come from the config file; a device authenticates with the
+//! account's `token` (the daemon's `auth_token`), and credentials are sealed
+//! with the account's `encryption_key` (the daemon's `encryption_key`).
+//!
+//! ```json
+//! {
+//!   "data_dir": "/var/lib/timeflow-relay",
+//!   "public_url": "https://sync.example.com",
+//!   "accounts": [{ "name": "home", "token": "…", "encryption_key": "…" }]
+//! }
+//! ```
+//!
+//! Session state lives in memory (a restart cancels running sessions);
+//! async packages are persisted in `<data_dir>/packages.json`.
+
+mod crypto;
+mod http;
+mod packages;
+mod sessions;
+mod storage;
+
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use http::{Request, Response};
+use packages::{Package, PackageStore};
+use sessions::{Participant, SessionStorage, SessionStore};
+use storage::BlobStore;
+
+const MAX_CONNECTIONS: usize = 64;
+const MAX_JSON_BODY: usize = 1024 * 1024; // 1 MB
+/// Matches the daemon's download limit.
+const MAX_BLOB_SIZE: usize = 50 * 1024 * 1024;
+const SESSION_GRANT_TTL: Duration = Duration::from_secs(24 * 3600);
+const PACKAGE_GRANT_TTL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Account {
+    pub name: String,
+    pub token: String,
+    pub encryption_key: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RelayConfig {
+    /// Base URL devices use to reach the relay's storage endpoints. Defaults
+    /// to `http://<bound address>`.
+    #[serde(default)]
+    pub public_url: Option<String>,
+    pub data_dir: PathBuf,
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+    /// A session expires when a device has not polled for this long.
+    #[serde(default = "default_session_timeout_secs")]
+    pub session_timeout_secs: u64,
+    /// Undelivered async packages are dropped after this many days (0 = never).
+    #[serde(default = "default_package_retention_days")]
+    pub package_retention_days: u32,
+}
+
+fn default_session_timeout_secs() -> u64 {
+    120
+}
+
+fn default_package_retention_days() -> u32 {
+    30
+}
+
+impl RelayConfig {
+    pub fn new(data_dir: PathBuf) -> Self {
+        Self {
+            public_url: None,
+            data_dir,
+            accounts: Vec::new(),
+            session_timeout_secs: default_session_timeout_secs(),
+            package_retention_days: default_package_retention_days(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        serde_json::from_str(&raw).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.accounts.is_empty() {
+            return Err("No accounts configured".to_string());
+        }
+        for account in &self.accounts {
+            if account.token.is_empty() || account.encryption_key.is_empty() {
+                return Err(format!(
+                    "Account '{}' needs both token and encryption_key",
+                    account.name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Relay {
+    config: RelayConfig,
+    public_url: String,
+    sessions: Mutex<SessionStore>,
+    packages: Mutex<PackageStore>,
+    blobs: BlobStore,
+}
+
+/// Serves the relay API on `listener` until `stop` is set.
+pub fn serve(
+    listener: TcpListener,
+    config: RelayConfig,
+    stop: Arc<AtomicBool>,
+) -> Result<(), String> {
+    config.validate()?;
+    std::fs::create_dir_all(&config.data_dir).map_err(|e| e.to_string())?;
+    let public_url = match &config.public_url {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None => format!(
+            "http://{}",
+            listener.local_addr().map_err(|e| e.to_string())?
+        ),
+    };
+    let relay = Arc::new(Relay {
+        sessions: Mutex::new(SessionStore::new(Duration::from_secs(
+            config.session_timeout_secs,
+        ))),
+        packages: Mutex::new(PackageStore::open(config.data_dir.join("packages.json"))?),
+        blobs: BlobStore::new(config.data_dir.join("blobs")),
+        public_url,
+        config,
+    });
+    log::info!(
+        "Relay: listening, public URL {} ({} account(s))",
+        relay.public_url,
+        relay.config.accounts.len()
+    );
+
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let active = Arc::new(AtomicUsize::new(0));
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, addr)) => {
+                if active.load(Ordering::Relaxed) >= MAX_CONNECTIONS {
+                    log::warn!("Relay: max connections reached, dropping {}", addr);
+                    continue;
+                }
+                active.fetch_add(1, Ordering::Relaxed);
+                let (relay, active) = (relay.clone(), active.clone());
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, &relay) {
+                        log::debug!("Relay: connection error from {}: {}", addr, e);
+                    }
+                    active.fetch_sub(1, Ordering::Relaxed);
+                });
+            }
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(20));
+            }
+            Err(e) => log::warn!("Relay: accept error: {}", e),
+        }
+    }
+    log::info!("Relay: stopped");
+    Ok(())
+}
+
+fn handle_connection(mut stream: std::net::TcpStream, relay: &Relay) -> Result<(), String> {
+    // Accepted streams inherit non-blocking mode on some platforms.
+    stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(60)))
+        .map_err(|e| e.to_string())?;
+    stream
+        .set_write_timeout(Some(Duration::from_secs(60)))
+        .map_err(|e| e.to_string())?;
+    let response = match http::read_request(&stream, MAX_BLOB_SIZE) {
+        Ok(request) => route(relay, &request),
+        Err(response) => response,
+    };
+    http::write_response(&mut stream, &response).map_err(|e| e.to_string())
+}
+
+fn route(relay: &Relay, req: &Request) -> Response {
+    if let Some(path) = req.path.strip_prefix("/api/storage/") {
+        return handle_storage(relay, req, path);
+    }
+    if req.method == "GET" && req.path == "/health" {
+        return Response::ok(json!({ "ok": true, "service": "timeflow-relay" }));
+    }
+
+    let Some(account) = relay.account(&req.bearer) else {
+        return Response::error(401, "unauthorized");
+    };
+    if req.body.len() > MAX_JSON_BODY {
+        return Response::error(413, "payload too large");
+    }
+    let segments: Vec<&str> = req.path.trim_matches('/').split('/').collect();
+    let result = match (req.method.as_str(), segments.as_slice()) {
+        ("POST", ["api", "sync", "session", "create"]) => relay.create_session(account, req),
+        ("GET", ["api", "sync", "session", id, "status"]) => relay.session_status(account, id, req),
+        ("POST", ["api", "sync", "session", id, action]) => {
+            relay.session_action(account, id, action, req)
+        }
+        ("POST", ["api", "sync", "async", action]) => relay.async_action(account, action, req),
+        _ => Err(Response::error(404, "not found")),
+    };
+    result.unwrap_or_else(|response| response)
+}
+
+fn handle_storage(relay: &Relay, req: &Request, path: &str) -> Response {
+    let result = match req.method.as_str() {
+        "PUT" => relay
+            .blobs
+            .put(&req.bearer, path, &req.body)
+            .map(|_| Response::ok(json!({ "ok": true }))),
+        "GET" => relay.blobs.get(&req.bearer, path).map(Response::bytes),
+        _ => Err((405, "method not allowed".to_string())),
+    };
+    result.unwrap_or_else(|(status, message)| Response::error(status, &message))
+}
+
+fn required_str(body: &Value, field: &str) -> Result<String, Response> {
+    match body.get(field).and_then(Value::as_str) {
+        Some(value) if !value.is_empty() => Ok(value.to_string()),
+        _ => Err(Response::error(400, &format!("missing {}", field))),
+    }
+}
+
+fn optional_str(body: &Value, field: &str) -> Option<String> {
+    body.get(field)
+        .and_then(Value::as_str)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+fn internal(e: String) -> Response {
+    log::error!("Relay: {}", e);
+    Response::error(500, &e)
+}
+
+/// The announcement as the client sent it, or `None` for clients that do
+/// not announce a protocol (the peer then falls back to full archives).
+fn announced_protocol(body: &Value) -> Option<Value> {
+    body.get("syncProtocol").filter(|v| v.is_u64())?;
+    let mut protocol = serde_json::Map::new();
+    for field in ["syncProtocol", "minSyncProtocol", "capabilities"] {
+        if let Some(value) = body.get(field).filter(|v| !v.is_null()) {
+            protocol.insert(field.to_string(), value.clone());
+        }
+    }
+    Some(Value::Object(protocol))
+}
+
+fn session_prefix(id: &str) -> String {
+    format!("sessions/{}/", id)
+}
+
+fn package_prefix(id: &str) -> String {
+    format!("packages/{}/", id)
+}
+
+impl Relay {
+    fn account(&self, token: &str) -> Option<&Account> {
+        if token.is_empty() {
+            return None;
+        }
+        self.config
+            .accounts
+            .iter()
+            .find(|a| crypto::constant_time_eq(&a.token, token))
+    }
+
+    fn lock_sessions(&self) -> MutexGuard<'_, SessionStore> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        for id in sessions.sweep() {
+            log::info!("Relay: session {} expired", id);
+            self.blobs.remove_prefix(&session_prefix(&id));
+        }
+        sessions
+    }
+
+    fn lock_packages(&self) -> Result<MutexGuard<'_, PackageStore>, Response> {
+        let mut packages = self.packages.lock().unwrap_or_else(|e| e.into_inner());
+        for id in packages
+            .prune(self.config.package_retention_days)
+            .map_err(internal)?
+        {
+            log::info!("Relay: package {} dropped after retention", id);
+            self.blobs.remove_prefix(&package_prefix(&id));
+        }
+        Ok(packages)
+    }
+
+    /// `{encrypted: {encryptedPayload, iv, tag}}` for the daemon's
+    /// `decrypt_credentials(…, id, encryption_key)`.
+    fn storage_credentials(
+        &self,
+        account: &Account,
+        id: &str,
+        upload_path: &str,
+        download_path: &str,
+        token: &str,
+        file_key: &str,
+    ) -> Result<Value, Response> {
+        let plain = json!({
+            "host": self.public_url,
+            "port": 0,
+            "protocol": "http",
+            "username": id,
+            "password": token,
+            "uploadPath": upload_path,
+            "downloadPath": download_path,
+            "fileEncryptionKey": file_key,
+        });
+        let encrypted =
+            crypto::encrypt_credentials(&plain.to_string(), &account.encryption_key, id)
+                .map_err(internal)?;
+        Ok(json!({ "encrypted": encrypted }))
+    }
+
+    // ── Session sync ──
+
+    fn create_session(&self, account: &Account, req: &Request) -> Result<Response, Response> {
+        let body = req.json()?;
+        let device_id = required_str(&body, "deviceId")?;
+        let participant = Participant::new(
+            &device_id,
+            optional_str(&body, "markerHash"),
+            body.pointer("/tableHashes/combined")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            body.get("forceFullSync")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            announced_protocol(&body),
+        );
+        let new_id = crypto::random_id().map_err(internal)?;
+
+        let mut sessions = self.lock_sessions();
+        let (id, cancelled) = sessions.create(&account.name, participant, new_id);
+        for old in cancelled {
+            log::info!("Relay: session {} superseded", old);
+            self.blobs.remove_prefix(&session_prefix(&old));
+        }
+        let session = sessions
+            .get_mut(&id, &account.name, &device_id)
+            .ok_or_else(|| internal("session vanished after create".to_string()))?;
+        if session.status == "active" && session.storage.is_none() {
+            session.storage = Some(SessionStorage {
+                token: self
+                    .blobs
+                    .grant(&session_prefix(&id), SESSION_GRANT_TTL)
+                    .map_err(internal)?,
+                file_key: crypto::random_file_key().map_err(internal)?,
+            });
+        }
+        let role = session.role_of(&device_id).unwrap_or("master");
+        log::info!(
+            "Relay: session {} [{}] {} as {} → {}",
+            id,
+            account.name,
+            device_id,
+            role,
+            session.status
+        );
+        Ok(Response::ok(json!({
+            "ok": true,
+            "sessionId": id,
+            "role": role,
+            "status": session.status,
+            "syncMode": session.sync_mode,
+            "peerProtocol": session.peer_of(&device_id).and_then(|p| p.protocol.clone()),
+        })))
+    }
+
+    fn session_status(
+        &self,
+        account: &Account,
+        id: &str,
+        req: &Request,
+    ) -> Result<Response, Response> {
+        let device_id = req
+            .query_param("deviceId")
+            .filter(|d| !d.is_empty())
+            .ok_or_else(|| Response::error(400, "missing deviceId"))?
+            .to_string();
+        let mut sessions = self.lock_sessions();
+        let session = sessions
+            .get_mut(id, &account.name, &device_id)
+            .ok_or_else(|| Response::error(404, "session not found"))?;
+        session.touch(&device_id);
+        let credentials = match (&session.storage, session.status.as_str()) {
+            (Some(storage), "active") => Some(self.storage_credentials(
+                account,
+                id,
+                &format!("{}up/", session_prefix(id)),
+                &format!("{}down/", session_prefix(id)),
+                &storage.token,
+                &storage.file_key,
+            )?),
+            _ => None,
+        };
+        Ok(Response::ok(json!({
+            "status": session.status,
+            "currentStep": session.current_step(),
+            "syncMode": session.sync_mode,
+            "storageCredentials": credentials,
+            "peerProtocol": session.peer_of(&device_id).and_then(|p| p.protocol.clone()),
+            "reason": session.reason,
+        })))
+    }
+
+    fn session_action(
+        &self,
+        account: &Account,
+        id: &str,
+        action: &str,
+        req: &Request,
+    ) -> Result<Response, Response> {
+        let body = req.json()?;
+        let device_id = required_str(&body, "deviceId")?;
+        let mut sessions = self.lock_sessions();
+        let session = sessions
+            .get_mut(id, &account.name, &device_id)
+            .ok_or_else(|| Response::error(404, "session not found"))?;
+        let ended = match action {
+            "heartbeat" => {
+                session.touch(&device_id);
+                false
+            }
+            "report" => {
+                let step = body.get("step").and_then(Value::as_u64).unwrap_or(0) as u32;
+                let failed = body.get("status").and_then(Value::as_str) == Some("error");
+                if session.is_finished() {
+                    return Err(Response::error(409, &format!("session {}", session.status)));
+                }
+                session.report(&device_id, step, failed)
+            }
+            "cancel" => {
+                let reason = optional_str(&body, "reason").unwrap_or_else(|| "cancelled".into());
+                session.cancel(&format!("{}: {}", device_id, reason))
+            }
+            _ => return Err(Response::error(404, "not found")),
+        };
+        if ended {
+            log::info!("Relay: session {} {}", id, session.status);
+            self.blobs.remove_prefix(&session_prefix(id));
+        }
+        Ok(Response::ok(json!({ "ok": true })))
+    }
+
+    // ── Async delta ──
+
+    fn async_action(
+        &self,
+        account: &Account,
+        action: &str,
+        req: &Request,
+    ) -> Result<Response, Response> {
+        let body = req.json()?;
+        let device_id = required_str(&body, "deviceId")?;
+        let mut packages = self.lock_packages()?;
+        match action {
+            "push" => {
+                let group_id = required_str(&body, "groupId")?;
+                packages
+                    .register_member(&account.name, &group_id, &device_id)
+                    .map_err(internal)?;
+                let package = Package {
+                    id: crypto::random_id().map_err(internal)?,
+                    account: account.name.clone(),
+                    group_id,
+                    from_device_id: device_id,
+                    base_marker_hash: optional_str(&body, "baseMarkerHash"),
+                    new_marker_hash: optional_str(&body, "newMarkerHash").unwrap_or_default(),
+                    size_bytes: body
+                        .get("fileSizeBytes")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(0),
+                    file_key: crypto::random_file_key().map_err(internal)?,
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                    external_storage: body
+                        .get("storageBackend")
+                        .and_then(Value::as_str)
+                        .is_some_and(|b| b != "server"),
+                    handled_by: Default::default(),
+                };
+                let credentials = self.package_credentials(account, &package)?;
+                log::info!(
+                    "Relay: package {} [{}/{}] from {}",
+                    package.id,
+                    account.name,
+                    package.group_id,
+                    package.from_device_id
+                );
+                let id = package.id.clone();
+                packages.push(package).map_err(internal)?;
+                Ok(Response::ok(
+                    json!({ "packageId": id, "storageCredentials": credentials }),
+                ))
+            }
+            "pending" => {
+                let group_id = required_str(&body, "groupId")?;
+                packages
+                    .register_member(&account.name, &group_id, &device_id)
+                    .map_err(internal)?;
+                let list: Vec<Value> = packages
+                    .pending(&account.name, &group_id, &device_id, |p| {
+                        p.external_storage
+                            || self
+                                .blobs
+                                .exists(&format!("{}delta.enc", package_prefix(&p.id)))
+                    })
+                    .into_iter()
+                    .map(|p| {
+                        json!({
+                            "id": p.id,
+                            "fromDeviceId": p.from_device_id,
+                            "baseMarkerHash": p.base_marker_hash,
+                            "newMarkerHash": p.new_marker_hash,
+                            "sizeBytes": p.size_bytes,
+                            "createdAt": p.created_at,
+                        })
+                    })
+                    .collect();
+                Ok(Response::ok(json!({ "packages": list })))
+            }
+            "credentials" => {
+                let package_id = required_str(&body, "packageId")?;
+                let package = packages
+                    .get(&account.name, &package_id)
+                    .ok_or_else(|| Response::error(404, "package not found"))?;
+                let credentials = self.package_credentials(account, package)?;
+                Ok(Response::ok(json!({ "storageCredentials": credentials })))
+            }
+            "ack" | "reject" => {
+                let package_id = required_str(&body, "packageId")?;
+                let outcome = match action {
+                    "ack" => "acked".to_string(),
+                    _ => format!(
+                        "rejected: {}",
+                        optional_str(&body, "reason").unwrap_or_default()
+                    ),
+                };
+                let delivered = packages
+                    .mark_handled(&account.name, &package_id, &device_id, &outcome)
+                    .map_err(internal)?
+                    .ok_or_else(|| Response::error(404, "package not found"))?;
+                if delivered {
+                    log::info!("Relay: package {} delivered to the whole group", package_id);
+                    self.blobs.remove_prefix(&package_prefix(&package_id));
+                }
+                Ok(Response::ok(json!({ "ok": true })))
+            }
+            _ => Err(Response::error(404, "not found")),
+        }
+    }
+
+    fn package_credentials(&self, account: &Account, package: &Package) -> Result<Value, Response> {
+        let prefix = package_prefix(&package.id);
+        let token = self
+            .blobs
+            .grant(&prefix, PACKAGE_GRANT_TTL)
+            .map_err(internal)?;
+        self.storage_credentials(
+            account,
+            &package.id,
+            &prefix,
+            &prefix,
+            &token,
+            &package.file_key,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_defaults_and_validation() {
+        let config: RelayConfig = serde_json::from_str(
+            r#"{"data_dir": "/tmp/relay", "accounts": [{"name": "home", "token": "t", "encryption_key": "k"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.session_timeout_secs, 120);
+        assert_eq!(config.package_retention_days, 30);
+        assert!(config.public_url.is_none());
+        assert!(config.validate().is_ok());
+
+        assert!(RelayConfig::new("/tmp/relay".into()).validate().is_err());
+        let mut no_key = config.clone();
+        no_key.accounts[0].encryption_key.clear();
+        assert!(no_key.validate().is_err());
+    }
+
+    #[test]
+    fn protocol_announcement_is_relayed_only_when_present() {
+        assert!(announced_protocol(&json!({"deviceId": "a"})).is_none());
+        let announced = announced_protocol(&json!({
+            "syncProtocol": 3,
+            "minSyncProtocol": null,
+            "capabilities": ["tombstones"],
+        }))
+        .unwrap();
+        assert_eq!(
+            announced,
+            json!({"syncProtocol": 3, "capabilities": ["tombstones"]})
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! timeflow-relay — self-hosted TIMEFLOW online sync server.
+//!
+//! Usage:
+//!   timeflow-relay --config relay.json [--bind 0.0.0.0:8787]
+//!   timeflow-relay --data-dir ./relay-data --token <auth token> --key <encryption key>
+//!       [--bind 127.0.0.1:8787] [--public-url https://sync.example.com]
+//!
+//! Point the daemon's online sync at `public_url` with the same token and
+//! encryption key. See lib.rs for the config file format.
+
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use timeflow_relay::{Account, RelayConfig};
+
+const DEFAULT_BIND: &str = "127.0.0.1:8787";
+
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "{} [{}] {}",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                record.level(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn parse_args() -> Result<(RelayConfig, String), String> {
+    let mut args = std::env::args().skip(1);
+    let mut config_path = None;
+    let mut bind = DEFAULT_BIND.to_string();
+    let mut data_dir = None;
+    let mut public_url = None;
+    let mut token = None;
+    let mut key = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--config" => config_path = Some(PathBuf::from(value()?)),
+            "--bind" => bind = value()?,
+            "--data-dir" => data_dir = Some(PathBuf::from(value()?)),
+            "--public-url" => public_url = Some(value()?),
+            "--token" => token = Some(value()?),
+            "--key" => key = Some(value()?),
+            "-h" | "--help" => return Err(
+                "usage: timeflow-relay --config <file> | --data-dir <dir> --token <t> --key <k> \
+                     [--bind <addr>] [--public-url <url>]"
+                    .to_string(),
+            ),
+            other => return Err(format!("unknown argument: {}", other)),
+        }
+    }
+
+    let mut config = match (config_path, &data_dir) {
+        (Some(path), _) => RelayConfig::load(&path)?,
+        (None, Some(dir)) => RelayConfig::new(dir.clone()),
+        (None, None) => return Err("either --config or --data-dir is required".to_string()),
+    };
+    if let Some(dir) = data_dir {
+        config.data_dir = dir;
+    }
+    if public_url.is_some() {
+        config.public_url = public_url;
+    }
+    match (token, key) {
+        (Some(token), Some(encryption_key)) => config.accounts.push(Account {
+            name: "default".to_string(),
+            token,
+            encryption_key,
+        }),
+        (None, None) => {}
+        _ => return Err("--token and --key must be given together".to_string()),
+    }
+    Ok((config, bind))
+}
+
+fn main() {
+    let _ = log::set_boxed_logger(Box::new(StderrLogger))
+        .map(|()| log::set_max_level(log::LevelFilter::Info));
+
+    let (config, bind) = match parse_args() {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let listener = match TcpListener::bind(&bind) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Cannot bind {}: {}", bind, e);
+            std::process::exit(1);
+        }
+    };
+    if config.public_url.is_none() && listener.local_addr().is_ok_and(|a| a.ip().is_unspecified()) {
+        log::warn!(
+            "Bound to a wildcard address without --public-url — devices will not reach storage"
+        );
+    }
+    if let Err(e) = timeflow_relay::serve(listener, config, Arc::new(AtomicBool::new(false))) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
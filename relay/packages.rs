@@ -0,0 +1,263 @@
+//! Async delta packages (store-and-forward).
+//!
+//! A device registers a package with `push` and uploads `delta.enc` to the
+//! granted prefix; the other members of its group list it through `pending`
+//! once the blob is in place (or right away when it lives in the device's own
+//! storage backend), and `ack` / `reject` it. A package is dropped when every
+//! other group member has handled it, or after `package_retention_days`. The
+//! index survives restarts in `<data_dir>/packages.json`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Package {
+    pub id: String,
+    pub account: String,
+    pub group_id: String,
+    pub from_device_id: String,
+    pub base_marker_hash: Option<String>,
+    pub new_marker_hash: String,
+    pub size_bytes: u64,
+    pub file_key: String,
+    pub created_at: String,
+    /// The pushing device keeps the blob in its own storage backend
+    /// (`storageBackend` other than "server"), so the relay never sees it.
+    #[serde(default)]
+    pub external_storage: bool,
+    /// device id → "acked" | "rejected: <reason>"
+    #[serde(default)]
+    pub handled_by: BTreeMap<String, String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct PackageIndex {
+    packages: Vec<Package>,
+    /// "<account>/<group>" → device ids seen in that group
+    members: BTreeMap<String, BTreeSet<String>>,
+}
+
+pub struct PackageStore {
+    path: PathBuf,
+    index: PackageIndex,
+}
+
+fn group_key(account: &str, group_id: &str) -> String {
+    format!("{}/{}", account, group_id)
+}
+
+impl PackageStore {
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let index = match std::fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw)
+                .map_err(|e| format!("Invalid {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => PackageIndex::default(),
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(Self { path, index })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let raw = serde_json::to_vec_pretty(&self.index).map_err(|e| e.to_string())?;
+        crate::storage::write_atomic(&self.path, &raw)
+    }
+
+    pub fn register_member(
+        &mut self,
+        account: &str,
+        group_id: &str,
+        device_id: &str,
+    ) -> Result<(), String> {
+        let added = self
+            .index
+            .members
+            .entry(group_key(account, group_id))
+            .or_default()
+            .insert(device_id.to_string());
+        if added {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn push(&mut self, package: Package) -> Result<(), String> {
+        self.index.packages.push(package);
+        self.save()
+    }
+
+    pub fn get(&self, account: &str, id: &str) -> Option<&Package> {
+        self.index
+            .packages
+            .iter()
+            .find(|p| p.id == id && p.account == account)
+    }
+
+    /// Packages from other devices of the group that `device_id` has not
+    /// handled yet, oldest first. `uploaded` filters out packages whose blob
+    /// is not in storage yet.
+    pub fn pending(
+        &self,
+        account: &str,
+        group_id: &str,
+        device_id: &str,
+        uploaded: impl Fn(&Package) -> bool,
+    ) -> Vec<&Package> {
+        self.index
+            .packages
+            .iter()
+            .filter(|p| {
+                p.account == account
+                    && p.group_id == group_id
+                    && p.from_device_id != device_id
+                    && !p.handled_by.contains_key(device_id)
+                    && uploaded(p)
+            })
+            .collect()
+    }
+
+    /// Records an ack / reject. `Ok(None)` for an unknown package, otherwise
+    /// whether the package was delivered to the whole group and dropped.
+    pub fn mark_handled(
+        &mut self,
+        account: &str,
+        id: &str,
+        device_id: &str,
+        outcome: &str,
+    ) -> Result<Option<bool>, String> {
+        let Some(pos) = self
+            .index
+            .packages
+            .iter()
+            .position(|p| p.id == id && p.account == account)
+        else {
+            return Ok(None);
+        };
+        let package = &mut self.index.packages[pos];
+        package
+            .handled_by
+            .insert(device_id.to_string(), outcome.to_string());
+        let members = self
+            .index
+            .members
+            .get(&group_key(account, &package.group_id));
+        let delivered = members.is_none_or(|m| {
+            m.iter()
+                .filter(|d| **d != package.from_device_id)
+                .all(|d| package.handled_by.contains_key(d))
+        });
+        if delivered {
+            self.index.packages.remove(pos);
+        }
+        self.save()?;
+        Ok(Some(delivered))
+    }
+
+    /// Drops packages older than `retention_days` (0 keeps them forever) and
+    /// returns their ids.
+    pub fn prune(&mut self, retention_days: u32) -> Result<Vec<String>, String> {
+        if retention_days == 0 {
+            return Ok(Vec::new());
+        }
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(i64::from(retention_days));
+        let (old, keep): (Vec<Package>, Vec<Package>) = std::mem::take(&mut self.index.packages)
+            .into_iter()
+            .partition(|p| {
+                chrono::DateTime::parse_from_rfc3339(&p.created_at).is_ok_and(|at| at < cutoff)
+            });
+        self.index.packages = keep;
+        if !old.is_empty() {
+            self.save()?;
+        }
+        Ok(old.into_iter().map(|p| p.id).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_index(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "timeflow-relay-packages-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("packages.json")
+    }
+
+    fn package(id: &str, from: &str, created_at: &str) -> Package {
+        Package {
+            id: id.to_string(),
+            account: "acc".to_string(),
+            group_id: "g".to_string(),
+            from_device_id: from.to_string(),
+            base_marker_hash: None,
+            new_marker_hash: format!("marker-{}", id),
+            size_bytes: 10,
+            file_key: "key".to_string(),
+            created_at: created_at.to_string(),
+            external_storage: false,
+            handled_by: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn package_is_pending_for_other_members_until_all_handled_it() {
+        let path = temp_index("pending");
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut store = PackageStore::open(path.clone()).unwrap();
+        for device in ["a", "b", "c"] {
+            store.register_member("acc", "g", device).unwrap();
+        }
+        store.push(package("p1", "a", &now)).unwrap();
+        store.push(package("p2", "a", &now)).unwrap();
+
+        assert!(store.pending("acc", "g", "a", |_| true).is_empty());
+        let ids = |v: Vec<&Package>| v.iter().map(|p| p.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(store.pending("acc", "g", "b", |_| true)), ["p1", "p2"]);
+        assert_eq!(
+            ids(store.pending("acc", "g", "b", |p| p.id == "p2")),
+            ["p2"]
+        );
+        assert!(store.pending("acc", "other", "b", |_| true).is_empty());
+
+        assert_eq!(
+            store.mark_handled("acc", "p1", "b", "acked").unwrap(),
+            Some(false)
+        );
+        assert_eq!(ids(store.pending("acc", "g", "b", |_| true)), ["p2"]);
+        assert_eq!(
+            store
+                .mark_handled("acc", "p1", "c", "rejected: base_marker_mismatch")
+                .unwrap(),
+            Some(true)
+        );
+        assert!(store.get("acc", "p1").is_none());
+        assert_eq!(store.mark_handled("acc", "p1", "c", "acked").unwrap(), None);
+
+        // The index is persisted.
+        let reopened = PackageStore::open(path.clone()).unwrap();
+        assert_eq!(ids(reopened.pending("acc", "g", "c", |_| true)), ["p2"]);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn prune_drops_packages_past_retention() {
+        let path = temp_index("prune");
+        let mut store = PackageStore::open(path.clone()).unwrap();
+        let old = (chrono::Utc::now() - chrono::Duration::days(40)).to_rfc3339();
+        store.push(package("old", "a", &old)).unwrap();
+        store
+            .push(package("new", "a", &chrono::Utc::now().to_rfc3339()))
+            .unwrap();
+
+        assert!(store.prune(0).unwrap().is_empty());
+        assert_eq!(store.prune(30).unwrap(), ["old"]);
+        assert!(store.get("acc", "new").is_some());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
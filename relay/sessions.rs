@@ -0,0 +1,370 @@
+//! Session sync coordination (the 13-step flow).
+//!
+//! The first device to call `create` becomes master and waits in
+//! `awaiting_peer`; the next device of the same account joins as slave, which
+//! activates the session and fixes the sync mode. Steps reported by either
+//! device advance `currentStep` (the highest step seen), and the session
+//! completes once both devices report step 13. A device that stops polling
+//! for `session_timeout_secs` expires the session.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Finished sessions stay queryable this long, so the slower device still
+/// reads the final status.
+const FINISHED_RETENTION: Duration = Duration::from_secs(600);
+const FINAL_STEP: u32 = 13;
+
+pub struct Participant {
+    pub device_id: String,
+    pub marker_hash: Option<String>,
+    pub tables_hash: Option<String>,
+    pub force_full: bool,
+    /// `{syncProtocol, minSyncProtocol, capabilities}` as announced, relayed
+    /// to the peer as `peerProtocol`.
+    pub protocol: Option<serde_json::Value>,
+    last_seen: Instant,
+    step: u32,
+}
+
+impl Participant {
+    pub fn new(
+        device_id: &str,
+        marker_hash: Option<String>,
+        tables_hash: Option<String>,
+        force_full: bool,
+        protocol: Option<serde_json::Value>,
+    ) -> Self {
+        Self {
+            device_id: device_id.to_string(),
+            marker_hash,
+            tables_hash,
+            force_full,
+            protocol,
+            last_seen: Instant::now(),
+            step: 0,
+        }
+    }
+}
+
+/// Storage handed to both devices once the session is active.
+pub struct SessionStorage {
+    pub token: String,
+    pub file_key: String,
+}
+
+pub struct Session {
+    pub id: String,
+    pub account: String,
+    /// "awaiting_peer" | "active" | "completed" | "failed" | "cancelled" | "expired"
+    pub status: String,
+    pub sync_mode: Option<String>,
+    pub master: Participant,
+    pub slave: Option<Participant>,
+    pub storage: Option<SessionStorage>,
+    pub reason: Option<String>,
+    finished_at: Option<Instant>,
+}
+
+impl Session {
+    pub fn is_finished(&self) -> bool {
+        self.finished_at.is_some()
+    }
+
+    pub fn current_step(&self) -> u32 {
+        self.participants().map(|p| p.step).max().unwrap_or(0)
+    }
+
+    pub fn role_of(&self, device_id: &str) -> Option<&'static str> {
+        if self.master.device_id == device_id {
+            Some("master")
+        } else if self
+            .slave
+            .as_ref()
+            .is_some_and(|s| s.device_id == device_id)
+        {
+            Some("slave")
+        } else {
+            None
+        }
+    }
+
+    pub fn peer_of(&self, device_id: &str) -> Option<&Participant> {
+        self.participants().find(|p| p.device_id != device_id)
+    }
+
+    fn participants(&self) -> impl Iterator<Item = &Participant> {
+        std::iter::once(&self.master).chain(self.slave.as_ref())
+    }
+
+    fn participant_mut(&mut self, device_id: &str) -> Option<&mut Participant> {
+        if self.master.device_id == device_id {
+            Some(&mut self.master)
+        } else {
+            self.slave.as_mut().filter(|s| s.device_id == device_id)
+        }
+    }
+
+    fn finish(&mut self, status: &str, reason: Option<&str>) {
+        if !self.is_finished() {
+            self.status = status.to_string();
+            self.reason = reason.map(str::to_string);
+            self.finished_at = Some(Instant::now());
+        }
+    }
+
+    /// Records a step report. Returns true when this report ended the session.
+    pub fn report(&mut self, device_id: &str, step: u32, failed: bool) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        if failed {
+            self.finish(
+                "failed",
+                Some(&format!("{} reported an error at step {}", device_id, step)),
+            );
+            return true;
+        }
+        if let Some(p) = self.participant_mut(device_id) {
+            p.step = p.step.max(step);
+            p.last_seen = Instant::now();
+        }
+        let both_done = self.slave.is_some() && self.participants().all(|p| p.step >= FINAL_STEP);
+        if both_done {
+            self.finish("completed", None);
+        }
+        both_done
+    }
+
+    pub fn touch(&mut self, device_id: &str) {
+        if let Some(p) = self.participant_mut(device_id) {
+            p.last_seen = Instant::now();
+        }
+    }
+
+    /// Returns true when this call ended the session.
+    pub fn cancel(&mut self, reason: &str) -> bool {
+        let was_open = !self.is_finished();
+        self.finish("cancelled", Some(reason));
+        was_open
+    }
+}
+
+/// Mode for a master/slave pair: identical data needs no transfer, a shared
+/// marker allows a delta, anything else (or a forced run) is a full sync.
+pub fn choose_mode(master: &Participant, slave: &Participant) -> &'static str {
+    if master.force_full || slave.force_full {
+        return "full";
+    }
+    match (&master.marker_hash, &slave.marker_hash) {
+        (Some(a), Some(b)) if a == b => {
+            if master.tables_hash.is_some() && master.tables_hash == slave.tables_hash {
+                "none"
+            } else {
+                "delta"
+            }
+        }
+        _ => "full",
+    }
+}
+
+pub struct SessionStore {
+    sessions: HashMap<String, Session>,
+    timeout: Duration,
+}
+
+impl SessionStore {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            sessions: HashMap::new(),
+            timeout,
+        }
+    }
+
+    /// Expires sessions with a silent participant and forgets long-finished
+    /// ones. Returns the ids that ended now (their storage can go).
+    pub fn sweep(&mut self) -> Vec<String> {
+        let now = Instant::now();
+        let timeout = self.timeout;
+        let mut ended = Vec::new();
+        for session in self.sessions.values_mut() {
+            if !session.is_finished()
+                && session
+                    .participants()
+                    .any(|p| now.duration_since(p.last_seen) > timeout)
+            {
+                session.finish("expired", Some("peer stopped responding"));
+                ended.push(session.id.clone());
+            }
+        }
+        self.sessions.retain(|_, s| {
+            s.finished_at
+                .is_none_or(|at| now.duration_since(at) < FINISHED_RETENTION)
+        });
+        ended
+    }
+
+    /// Creates a session or joins a waiting one of the same account.
+    /// A device runs one sync at a time, so its own open sessions are
+    /// cancelled first; their ids are returned with the session id.
+    pub fn create(
+        &mut self,
+        account: &str,
+        participant: Participant,
+        new_id: String,
+    ) -> (String, Vec<String>) {
+        let mut cancelled = Vec::new();
+        for session in self.sessions.values_mut() {
+            if session.account == account
+                && session.role_of(&participant.device_id).is_some()
+                && session.cancel("superseded by a new session")
+            {
+                cancelled.push(session.id.clone());
+            }
+        }
+
+        let waiting = self
+            .sessions
+            .values_mut()
+            .filter(|s| s.account == account && s.status == "awaiting_peer")
+            .min_by_key(|s| s.master.last_seen);
+        if let Some(session) = waiting {
+            let mode = choose_mode(&session.master, &participant);
+            session.sync_mode = Some(mode.to_string());
+            session.slave = Some(participant);
+            if mode == "none" {
+                session.finish("completed", None);
+            } else {
+                session.status = "active".to_string();
+            }
+            return (session.id.clone(), cancelled);
+        }
+
+        self.sessions.insert(
+            new_id.clone(),
+            Session {
+                id: new_id.clone(),
+                account: account.to_string(),
+                status: "awaiting_peer".to_string(),
+                sync_mode: None,
+                master: participant,
+                slave: None,
+                storage: None,
+                reason: None,
+                finished_at: None,
+            },
+        );
+        (new_id, cancelled)
+    }
+
+    /// The session, if it belongs to `account` and `device_id` takes part in it.
+    pub fn get_mut(&mut self, id: &str, account: &str, device_id: &str) -> Option<&mut Session> {
+        self.sessions
+            .get_mut(id)
+            .filter(|s| s.account == account && s.role_of(device_id).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, marker: Option<&str>, tables: Option<&str>) -> Participant {
+        Participant::new(
+            id,
+            marker.map(str::to_string),
+            tables.map(str::to_string),
+            false,
+            None,
+        )
+    }
+
+    #[test]
+    fn mode_follows_markers_table_hashes_and_force() {
+        let a = device("a", Some("m1"), Some("t1"));
+        assert_eq!(
+            choose_mode(&a, &device("b", Some("m1"), Some("t1"))),
+            "none"
+        );
+        assert_eq!(
+            choose_mode(&a, &device("b", Some("m1"), Some("t2"))),
+            "delta"
+        );
+        assert_eq!(
+            choose_mode(&a, &device("b", Some("m2"), Some("t1"))),
+            "full"
+        );
+        assert_eq!(choose_mode(&a, &device("b", None, None)), "full");
+        assert_eq!(
+            choose_mode(&device("a", None, None), &device("b", None, None)),
+            "full"
+        );
+        let mut forced = device("b", Some("m1"), Some("t1"));
+        forced.force_full = true;
+        assert_eq!(choose_mode(&a, &forced), "full");
+    }
+
+    #[test]
+    fn second_device_joins_and_both_final_reports_complete_the_session() {
+        let mut store = SessionStore::new(Duration::from_secs(60));
+        let (id, _) = store.create("acc", device("a", None, None), "s1".into());
+        // Another account never joins someone else's session.
+        let (other, _) = store.create("acc2", device("x", None, None), "s2".into());
+        assert_ne!(other, id);
+
+        let (joined, _) = store.create("acc", device("b", None, None), "s3".into());
+        assert_eq!(joined, id);
+        let session = store.get_mut(&id, "acc", "b").unwrap();
+        assert_eq!(session.status, "active");
+        assert_eq!(session.role_of("b"), Some("slave"));
+        assert_eq!(session.sync_mode.as_deref(), Some("full"));
+        assert!(store.get_mut(&id, "acc", "x").is_none());
+        assert!(store.get_mut(&id, "acc2", "a").is_none());
+
+        let session = store.get_mut(&id, "acc", "a").unwrap();
+        assert!(!session.report("b", 6, false));
+        assert!(!session.report("a", 5, false));
+        assert_eq!(session.current_step(), 6);
+        assert!(!session.report("b", 13, false));
+        assert!(session.report("a", 13, false));
+        assert_eq!(session.status, "completed");
+        assert!(
+            !session.report("a", 13, false),
+            "a finished session ends only once"
+        );
+    }
+
+    #[test]
+    fn error_report_fails_and_new_create_cancels_the_devices_old_session() {
+        let mut store = SessionStore::new(Duration::from_secs(60));
+        let (first, _) = store.create("acc", device("a", None, None), "s1".into());
+        let (second, cancelled) = store.create("acc", device("a", None, None), "s2".into());
+        assert_ne!(first, second);
+        assert_eq!(cancelled, vec![first.clone()]);
+        assert_eq!(
+            store.get_mut(&first, "acc", "a").unwrap().status,
+            "cancelled"
+        );
+
+        store.create("acc", device("b", None, None), "s3".into());
+        let session = store.get_mut(&second, "acc", "b").unwrap();
+        assert!(session.report("b", 6, true));
+        assert_eq!(session.status, "failed");
+    }
+
+    #[test]
+    fn identical_databases_complete_on_join_and_silent_sessions_expire() {
+        let mut store = SessionStore::new(Duration::from_secs(60));
+        let (id, _) = store.create("acc", device("a", Some("m"), Some("t")), "s1".into());
+        store.create("acc", device("b", Some("m"), Some("t")), "s2".into());
+        let session = store.get_mut(&id, "acc", "b").unwrap();
+        assert_eq!(session.status, "completed");
+        assert_eq!(session.sync_mode.as_deref(), Some("none"));
+
+        let mut store = SessionStore::new(Duration::ZERO);
+        let (id, _) = store.create("acc", device("a", None, None), "s1".into());
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(store.sweep(), vec![id.clone()]);
+        assert_eq!(store.get_mut(&id, "acc", "a").unwrap().status, "expired");
+    }
+}
@@ -0,0 +1,204 @@
+//! Local blob storage — what SFTP is for the hosted server.
+//!
+//! Blobs live under `<data_dir>/blobs/<path>`. Every transfer gets a grant: a
+//! random token bound to one path prefix (`sessions/<id>/`, `packages/<id>/`)
+//! that the client presents as `Authorization: Bearer` on
+//! `PUT/GET /api/storage/<path>`. The token travels inside the encrypted
+//! credentials (`password`), so only the devices holding the account's
+//! encryption key can use it.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::crypto;
+
+struct Grant {
+    prefix: String,
+    expires: Instant,
+}
+
+pub struct BlobStore {
+    root: PathBuf,
+    grants: Mutex<HashMap<String, Grant>>,
+}
+
+impl BlobStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            grants: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Issues a token for reading and writing below `prefix`.
+    pub fn grant(&self, prefix: &str, ttl: Duration) -> Result<String, String> {
+        let token = crypto::random_id()?;
+        let mut grants = self.grants.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        grants.retain(|_, g| g.expires > now);
+        grants.insert(
+            token.clone(),
+            Grant {
+                prefix: prefix.to_string(),
+                expires: now + ttl,
+            },
+        );
+        Ok(token)
+    }
+
+    /// Drops the grants for `prefix` and deletes everything stored under it.
+    pub fn remove_prefix(&self, prefix: &str) {
+        self.grants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, g| g.prefix != prefix);
+        if validate_path(prefix.trim_end_matches('/')).is_ok() {
+            let dir = self.root.join(prefix.trim_end_matches('/'));
+            if dir.exists() {
+                if let Err(e) = std::fs::remove_dir_all(&dir) {
+                    log::warn!("Could not remove {}: {}", dir.display(), e);
+                }
+            }
+        }
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        validate_path(path).is_ok() && self.root.join(path).is_file()
+    }
+
+    /// Resolves `path` for a request carrying `token`; `Err((status, message))`.
+    fn authorize(&self, token: &str, path: &str) -> Result<PathBuf, (u16, String)> {
+        validate_path(path).map_err(|e| (400, e))?;
+        let grants = self.grants.lock().unwrap_or_else(|e| e.into_inner());
+        let allowed = grants.iter().any(|(t, g)| {
+            crypto::constant_time_eq(t, token)
+                && g.expires > Instant::now()
+                && path.starts_with(&g.prefix)
+        });
+        if !allowed {
+            return Err((
+                403,
+                "storage grant missing, expired or for another path".into(),
+            ));
+        }
+        Ok(self.root.join(path))
+    }
+
+    pub fn put(&self, token: &str, path: &str, data: &[u8]) -> Result<(), (u16, String)> {
+        let target = self.authorize(token, path)?;
+        write_atomic(&target, data).map_err(|e| (500, e))
+    }
+
+    pub fn get(&self, token: &str, path: &str) -> Result<Vec<u8>, (u16, String)> {
+        let target = self.authorize(token, path)?;
+        std::fs::read(&target).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => (404, format!("{} not found", path)),
+            _ => (500, e.to_string()),
+        })
+    }
+}
+
+/// Relative path of plain segments — no `..`, absolute paths or odd characters.
+pub fn validate_path(path: &str) -> Result<(), String> {
+    if path.is_empty() || path.len() > 512 {
+        return Err("invalid storage path".into());
+    }
+    for segment in path.split('/') {
+        let plain = segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if segment.is_empty() || segment == "." || segment == ".." || !plain {
+            return Err(format!("invalid storage path: {}", path));
+        }
+    }
+    Ok(())
+}
+
+/// Temp file + rename, so a half-written upload is never visible to readers.
+pub fn write_atomic(target: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let tmp = target.with_extension("part");
+    std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, target).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> BlobStore {
+        let dir = std::env::temp_dir().join(format!(
+            "timeflow-relay-storage-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        BlobStore::new(dir)
+    }
+
+    #[test]
+    fn paths_with_traversal_or_odd_segments_are_rejected() {
+        assert!(validate_path("sessions/abc/up/data.enc").is_ok());
+        for bad in [
+            "",
+            "/etc/passwd",
+            "sessions/../secret",
+            "sessions//x",
+            "sessions/./x",
+            "a\\b",
+            "a b",
+        ] {
+            assert!(validate_path(bad).is_err(), "{:?} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn grants_are_scoped_to_their_prefix_and_removed_with_it() {
+        let store = temp_store("grants");
+        let token = store
+            .grant("sessions/s1/", Duration::from_secs(60))
+            .unwrap();
+
+        store
+            .put(&token, "sessions/s1/up/data.enc", b"blob")
+            .unwrap();
+        assert_eq!(
+            store.get(&token, "sessions/s1/up/data.enc").unwrap(),
+            b"blob"
+        );
+        assert!(store.exists("sessions/s1/up/data.enc"));
+        assert_eq!(
+            store.get(&token, "sessions/s2/up/data.enc").unwrap_err().0,
+            403
+        );
+        assert_eq!(
+            store.get("wrong", "sessions/s1/up/data.enc").unwrap_err().0,
+            403
+        );
+        assert_eq!(
+            store
+                .get(&token, "sessions/s1/down/data.enc")
+                .unwrap_err()
+                .0,
+            404
+        );
+
+        store.remove_prefix("sessions/s1/");
+        assert!(!store.exists("sessions/s1/up/data.enc"));
+        assert_eq!(
+            store.get(&token, "sessions/s1/up/data.enc").unwrap_err().0,
+            403
+        );
+
+        let expired = store.grant("sessions/s3/", Duration::ZERO).unwrap();
+        assert_eq!(
+            store.put(&expired, "sessions/s3/x", b"x").unwrap_err().0,
+            403
+        );
+        let _ = std::fs::remove_dir_all(&store.root);
+    }
+}
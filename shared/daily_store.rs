@@ -8,20 +8,28 @@
 /// reads snapshots via [`read`] for display and analysis. Legacy JSON files are migrated
 /// on first access via [`legacy`].
 ///
+/// **Tracker spans:** The daemon also records when it was running, idle or asleep
+/// ([`tracker_spans`]) so the dashboard can explain gaps in a workday.
+///
 /// **Key types:** See [`types`] for `DaySnapshot`, `AppActivityRecord`, etc.
 mod legacy;
 mod read;
 mod schema;
+mod tracker_spans;
 mod types;
 mod write;
 
 pub use legacy::{load_legacy_json_file, migrate_legacy_json_files};
 pub use read::{get_day_signature, load_day_snapshot, load_range_snapshots};
 pub use schema::{ensure_schema, open_store, store_db_path};
+pub use tracker_spans::{
+    load_tracker_spans, record_tracker_span, TrackerSpan, TRACKER_SPAN_IDLE, TRACKER_SPAN_RUNNING,
+    TRACKER_SPAN_SLEEP,
+};
 pub(crate) use types::{dedupe_files_preserving_last, detected_path_key};
 pub use types::{
-    extend_activity_spans, DaySignature, StoredAppDailyData, StoredDailyData, StoredFileEntry,
-    StoredSession,
+    extend_activity_spans, merge_activity_spans, merge_activity_spans_json, DaySignature,
+    StoredAppDailyData, StoredDailyData, StoredFileEntry, StoredSession,
 };
 pub use write::replace_day_snapshot;
 #[cfg(test)]
@@ -595,4 +603,95 @@ mod tests {
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].detected_path, None);
     }
+
+    #[test]
+    fn merge_activity_spans_unions_adjacent_spans_and_is_idempotent() {
+        let local = vec![(
+            "2026-03-12T10:00:00+00:00".to_string(),
+            "2026-03-12T10:10:00+00:00".to_string(),
+        )];
+        let incoming = vec![
+            (
+                "2026-03-12T10:10:20+00:00".to_string(),
+                "2026-03-12T10:20:00+00:00".to_string(),
+            ),
+            (
+                "2026-03-12T12:00:00+00:00".to_string(),
+                "2026-03-12T12:05:00+00:00".to_string(),
+            ),
+        ];
+
+        let merged = merge_activity_spans(&local, &incoming);
+        assert_eq!(
+            merged,
+            vec![
+                (
+                    "2026-03-12T10:00:00+00:00".to_string(),
+                    "2026-03-12T10:20:00+00:00".to_string(),
+                ),
+                (
+                    "2026-03-12T12:00:00+00:00".to_string(),
+                    "2026-03-12T12:05:00+00:00".to_string(),
+                ),
+            ]
+        );
+        assert_eq!(merge_activity_spans(&merged, &incoming), merged);
+        assert_eq!(merge_activity_spans(&merged, &local), merged);
+
+        let json = merge_activity_spans_json("[]", "not json");
+        assert_eq!(json, "[]");
+    }
+
+    #[test]
+    fn tracker_spans_extend_touching_intervals_per_kind() {
+        let conn = Connection::open_in_memory().expect("in-memory sqlite");
+        ensure_schema(&conn).expect("schema");
+
+        let running = [
+            ("2026-03-12T09:00:00+00:00", "2026-03-12T09:00:30+00:00"),
+            ("2026-03-12T09:00:30+00:00", "2026-03-12T09:01:00+00:00"),
+            ("2026-03-12T09:00:40+00:00", "2026-03-12T09:00:50+00:00"),
+            ("2026-03-12T11:00:00+00:00", "2026-03-12T11:00:30+00:00"),
+        ];
+        for (start, end) in running {
+            record_tracker_span(&conn, TRACKER_SPAN_RUNNING, start, end).expect("record");
+        }
+        record_tracker_span(
+            &conn,
+            TRACKER_SPAN_SLEEP,
+            "2026-03-12T09:01:00+00:00",
+            "2026-03-12T11:00:00+00:00",
+        )
+        .expect("record sleep");
+        assert!(record_tracker_span(&conn, TRACKER_SPAN_IDLE, "bad", "bad").is_err());
+
+        let spans = load_tracker_spans(&conn, "2026-03-12", "2026-03-12").expect("load");
+        let summary: Vec<(&str, &str, &str)> = spans
+            .iter()
+            .map(|s| (s.kind.as_str(), s.start.as_str(), s.end.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    TRACKER_SPAN_RUNNING,
+                    "2026-03-12T09:00:00+00:00",
+                    "2026-03-12T09:01:00+00:00"
+                ),
+                (
+                    TRACKER_SPAN_SLEEP,
+                    "2026-03-12T09:01:00+00:00",
+                    "2026-03-12T11:00:00+00:00"
+                ),
+                (
+                    TRACKER_SPAN_RUNNING,
+                    "2026-03-12T11:00:00+00:00",
+                    "2026-03-12T11:00:30+00:00"
+                ),
+            ]
+        );
+        assert!(load_tracker_spans(&conn, "2026-03-13", "2026-03-14")
+            .expect("load empty")
+            .is_empty());
+    }
 }
@@ -75,11 +75,20 @@ pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
              PRIMARY KEY (date, exe_name, file_name, detected_path),
              FOREIGN KEY (date, exe_name) REFERENCES daily_apps(date, exe_name) ON DELETE CASCADE
          );
+         CREATE TABLE IF NOT EXISTS tracker_spans (
+             date TEXT NOT NULL,
+             kind TEXT NOT NULL,
+             start_time TEXT NOT NULL,
+             end_time TEXT NOT NULL,
+             PRIMARY KEY (date, kind, start_time)
+         );
          DROP INDEX IF EXISTS idx_daily_snapshots_date;
          CREATE INDEX IF NOT EXISTS idx_daily_sessions_date_exe
              ON daily_sessions(date, exe_name, session_index);
          CREATE INDEX IF NOT EXISTS idx_daily_files_date_exe
-             ON daily_files(date, exe_name, ordinal);",
+             ON daily_files(date, exe_name, ordinal);
+         CREATE INDEX IF NOT EXISTS idx_tracker_spans_kind_end
+             ON tracker_spans(kind, end_time);",
     )
     .map_err(|e| format!("Failed to initialize daily store schema: {}", e))?;
     migrate_daily_files_schema(conn)
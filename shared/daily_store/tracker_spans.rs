@@ -0,0 +1,109 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Daemon alive (coalesced heartbeats).
+pub const TRACKER_SPAN_RUNNING: &str = "running";
+/// No keyboard/mouse input for the idle threshold.
+pub const TRACKER_SPAN_IDLE: &str = "idle";
+/// System suspended (wall clock advanced while uptime did not).
+pub const TRACKER_SPAN_SLEEP: &str = "sleep";
+
+/// A new interval starting at most this long after the newest span of the
+/// same kind extends it instead of opening a new row.
+const TRACKER_SPAN_MERGE_GAP_SECS: i64 = 90;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackerSpan {
+    pub kind: String,
+    pub start: String,
+    pub end: String,
+}
+
+fn parse_rfc3339(s: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    chrono::DateTime::parse_from_rfc3339(s).ok()
+}
+
+/// Records `start..end` (RFC3339) for `kind`, extending the newest span of
+/// that kind when the two touch. Spans are keyed by the date of their start.
+pub fn record_tracker_span(
+    conn: &Connection,
+    kind: &str,
+    start: &str,
+    end: &str,
+) -> Result<(), String> {
+    let (Some(start_dt), Some(end_dt)) = (parse_rfc3339(start), parse_rfc3339(end)) else {
+        return Err(format!("Invalid tracker span {}..{}", start, end));
+    };
+    if end_dt <= start_dt {
+        return Ok(());
+    }
+    let newest = conn
+        .query_row(
+            "SELECT date, start_time, end_time FROM tracker_spans
+             WHERE kind = ?1 ORDER BY end_time DESC LIMIT 1",
+            [kind],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read newest tracker span: {}", e))?;
+
+    if let Some((date, newest_start, newest_end)) = newest {
+        let newest_range = parse_rfc3339(&newest_start).zip(parse_rfc3339(&newest_end));
+        if let Some((newest_start_dt, newest_end_dt)) = newest_range {
+            if start_dt >= newest_start_dt && end_dt <= newest_end_dt {
+                return Ok(());
+            }
+            let gap = start_dt.signed_duration_since(newest_end_dt).num_seconds();
+            if start_dt >= newest_start_dt && gap <= TRACKER_SPAN_MERGE_GAP_SECS {
+                conn.execute(
+                    "UPDATE tracker_spans SET end_time = ?4
+                     WHERE date = ?1 AND kind = ?2 AND start_time = ?3",
+                    params![date, kind, newest_start, end],
+                )
+                .map_err(|e| format!("Failed to extend tracker span: {}", e))?;
+                return Ok(());
+            }
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO tracker_spans (date, kind, start_time, end_time)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(date, kind, start_time) DO UPDATE SET
+             end_time = MAX(tracker_spans.end_time, excluded.end_time)",
+        params![start_dt.format("%Y-%m-%d").to_string(), kind, start, end],
+    )
+    .map_err(|e| format!("Failed to insert tracker span: {}", e))?;
+    Ok(())
+}
+
+/// Spans overlapping the dates `start..=end` (YYYY-MM-DD), oldest first.
+pub fn load_tracker_spans(
+    conn: &Connection,
+    start: &str,
+    end: &str,
+) -> Result<Vec<TrackerSpan>, String> {
+    let mut stmt = conn
+        .prepare_cached(
+            "SELECT kind, start_time, end_time FROM tracker_spans
+             WHERE substr(end_time, 1, 10) >= ?1 AND date <= ?2
+             ORDER BY start_time ASC",
+        )
+        .map_err(|e| format!("Failed to prepare tracker span query: {}", e))?;
+    let rows = stmt
+        .query_map(params![start, end], |row| {
+            Ok(TrackerSpan {
+                kind: row.get(0)?,
+                start: row.get(1)?,
+                end: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query tracker spans: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to map tracker span row: {}", e))
+}
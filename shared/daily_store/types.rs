@@ -135,3 +135,26 @@ pub fn extend_activity_spans(
 
     merged
 }
+
+/// Merge two span lists (e.g. local + synced from a peer) by feeding every
+/// incoming span through [`extend_activity_spans`]. Idempotent — merging the
+/// same spans twice yields the same result.
+pub fn merge_activity_spans(
+    local: &[(String, String)],
+    incoming: &[(String, String)],
+) -> Vec<(String, String)> {
+    incoming
+        .iter()
+        .fold(local.to_vec(), |acc, (start, end)| {
+            extend_activity_spans(&acc, start, end)
+        })
+}
+
+/// JSON variant of [`merge_activity_spans`] for the `file_activities.activity_spans`
+/// column. Unparseable input is treated as an empty list.
+pub fn merge_activity_spans_json(local: &str, incoming: &str) -> String {
+    let local: Vec<(String, String)> = serde_json::from_str(local).unwrap_or_default();
+    let incoming: Vec<(String, String)> = serde_json::from_str(incoming).unwrap_or_default();
+    serde_json::to_string(&merge_activity_spans(&local, &incoming))
+        .unwrap_or_else(|_| "[]".to_string())
+}
@@ -2,9 +2,13 @@ pub mod activity_classification;
 pub mod title_parser;
 pub mod daily_store;
 pub mod monitored_app;
+pub mod peer_addr;
 pub mod process_utils;
 pub mod secret_store;
 pub mod session_settings;
+pub mod sync_hlc;
+pub mod sync_keyring;
+pub mod sync_protocol;
 pub mod timeflow_paths;
 pub mod version_compat;
 pub mod webui_host;
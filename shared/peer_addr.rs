@@ -0,0 +1,147 @@
+//! Adresy peerów LAN — IPv4 albo IPv6, dla link-local ze strefą.
+//!
+//! Peer jest zapisywany (`PeerInfo.ip`, lan_peers.json) jako goły adres; adres
+//! IPv6 link-local dostaje dopisek `%<indeks interfejsu>` (np. `fe80::1%3`),
+//! bo bez strefy nie da się go osiągnąć. W URL-ach adres IPv6 jest w nawiasach,
+//! a `%` strefy kodowane wg RFC 6874: `http://[fe80::1%253]:47891/...`; przy
+//! parsowaniu akceptowany jest też surowy zapis `[fe80::1%3]`.
+
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+/// Parsuje `1.2.3.4`, `fd00::5` albo `fe80::1%3`.
+/// Zwraca adres i indeks strefy (0 = brak).
+pub fn parse_peer_ip(s: &str) -> Result<(IpAddr, u32), String> {
+    let s = s.trim();
+    let (addr, zone) = match s.split_once('%') {
+        Some((addr, zone)) => (addr, Some(zone)),
+        None => (s, None),
+    };
+    let ip: IpAddr = addr
+        .parse()
+        .map_err(|_| format!("Invalid peer IP: {}", s))?;
+    let scope_id = match zone {
+        None => 0,
+        Some(_) if ip.is_ipv4() => return Err(format!("Invalid peer IP: {} (zone on IPv4)", s)),
+        Some(zone) => zone
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid peer IP: {} (zone must be an interface index)", s))?,
+    };
+    Ok((ip, scope_id))
+}
+
+/// Adres gniazda dla peera zapisanego jako `parse_peer_ip`.
+pub fn socket_addr(ip: &str, port: u16) -> Result<SocketAddr, String> {
+    Ok(match parse_peer_ip(ip)? {
+        (IpAddr::V4(v4), _) => SocketAddr::new(IpAddr::V4(v4), port),
+        (IpAddr::V6(v6), scope_id) => SocketAddr::V6(SocketAddrV6::new(v6, port, 0, scope_id)),
+    })
+}
+
+/// Odwrotność `parse_peer_ip` dla adresu nadawcy pakietu/połączenia. Strefa jest
+/// zachowywana tylko dla link-local — dla innych adresów nic nie znaczy.
+pub fn format_peer_ip(addr: &SocketAddr) -> String {
+    match addr {
+        SocketAddr::V6(v6) if v6.scope_id() != 0 && is_ipv6_link_local(v6.ip()) => {
+            format!("{}%{}", v6.ip(), v6.scope_id())
+        }
+        SocketAddr::V6(v6) => match v6.ip().to_ipv4_mapped() {
+            Some(v4) => v4.to_string(),
+            None => v6.ip().to_string(),
+        },
+        SocketAddr::V4(v4) => v4.ip().to_string(),
+    }
+}
+
+/// `host:port` do URL-a: `1.2.3.4:47891` albo `[fe80::1%253]:47891`.
+pub fn host_port(ip: &str, port: u16) -> String {
+    if ip.contains(':') {
+        format!("[{}]:{}", ip.replace('%', "%25"), port)
+    } else {
+        format!("{}:{}", ip, port)
+    }
+}
+
+/// Parsuje część `host:port` URL-a (`1.2.3.4:80`, `[fe80::1%253]:80`, `[fe80::1%3]:80`).
+pub fn parse_host_port(s: &str) -> Result<SocketAddr, String> {
+    let invalid = || format!("Invalid address {}", s);
+    let (host, port) = match s.strip_prefix('[') {
+        Some(rest) => {
+            let (host, tail) = rest.split_once(']').ok_or_else(invalid)?;
+            let port = tail.strip_prefix(':').ok_or_else(invalid)?;
+            // `%25` + niepusta strefa to zakodowany `%`; inaczej zapis surowy.
+            match host.split_once("%25") {
+                Some((addr, zone)) if !zone.is_empty() => (format!("{}%{}", addr, zone), port),
+                _ => (host.to_string(), port),
+            }
+        }
+        None => {
+            let (host, port) = s.rsplit_once(':').ok_or_else(invalid)?;
+            (host.to_string(), port)
+        }
+    };
+    let port: u16 = port.parse().map_err(|_| invalid())?;
+    socket_addr(&host, port)
+}
+
+/// fe80::/10
+pub fn is_ipv6_link_local(ip: &Ipv6Addr) -> bool {
+    (ip.segments()[0] & 0xffc0) == 0xfe80
+}
+
+/// fc00::/7 (unique local) — IPv6-owy odpowiednik 10/8, 172.16/12, 192.168/16.
+pub fn is_ipv6_unique_local(ip: &Ipv6Addr) -> bool {
+    (ip.segments()[0] & 0xfe00) == 0xfc00
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_scoped_addresses() {
+        assert_eq!(
+            parse_peer_ip("192.168.1.5").unwrap(),
+            ("192.168.1.5".parse().unwrap(), 0)
+        );
+        assert_eq!(
+            parse_peer_ip("fe80::1%3").unwrap(),
+            ("fe80::1".parse().unwrap(), 3)
+        );
+        assert_eq!(
+            parse_peer_ip("fe80::1%25").unwrap(),
+            ("fe80::1".parse().unwrap(), 25)
+        );
+        assert!(parse_peer_ip("10.0.0.1%3").is_err());
+        assert!(parse_peer_ip("fe80::1%eth0").is_err());
+        assert!(parse_peer_ip("not-an-ip").is_err());
+    }
+
+    #[test]
+    fn host_port_roundtrips_both_families() {
+        for (ip, port) in [
+            ("10.0.0.7", 47891),
+            ("::1", 80),
+            ("fe80::abcd%7", 47891),
+            ("fe80::abcd%251", 47891),
+        ] {
+            let hp = host_port(ip, port);
+            let addr = parse_host_port(&hp).unwrap();
+            assert_eq!(addr.port(), port);
+            assert_eq!(format_peer_ip(&addr), ip, "{}", hp);
+        }
+        assert_eq!(host_port("fe80::1%3", 47891), "[fe80::1%253]:47891");
+        let raw = parse_host_port("[fe80::1%3]:47891").unwrap();
+        assert_eq!(format_peer_ip(&raw), "fe80::1%3");
+        assert!(parse_host_port("[::1]").is_err());
+        assert!(parse_host_port("10.0.0.1").is_err());
+    }
+
+    #[test]
+    fn scope_is_kept_only_for_link_local() {
+        let global: SocketAddr =
+            SocketAddr::V6(SocketAddrV6::new("fd00::5".parse().unwrap(), 1, 0, 4));
+        assert_eq!(format_peer_ip(&global), "fd00::5");
+        let mapped: SocketAddr = "[::ffff:192.168.1.9]:1".parse().unwrap();
+        assert_eq!(format_peer_ip(&mapped), "192.168.1.9");
+    }
+}
@@ -0,0 +1,441 @@
+//! Hybrid logical clock (HLC) carried by every synced row.
+//!
+//! An HLC is `wall_ms-counter-node`: the wall clock in epoch milliseconds, a
+//! logical counter that breaks ties within one millisecond, and the id of the
+//! database that produced it. Unlike raw `updated_at` strings, HLCs never go
+//! backwards after a device has seen a peer's clock — an edit made after a
+//! sync always outranks whatever was received, even if the peer's wall clock
+//! was ahead.
+//!
+//! The clock lives in the `sync_clock` table so both processes writing to the
+//! same database (dashboard and daemon) share it. Its node is the device's
+//! sync id (`device_id.txt`), pinned by [`set_node`] whenever either process
+//! opens the database — a restored or copied database takes the node of the
+//! device it now lives on, so tie-breaks stay unique per device. Local writes stamp rows via
+//! the `trg_<table>_hlc_*` triggers; sync merge writes the peer's HLC
+//! explicitly, which makes those triggers skip.
+
+use std::fmt;
+
+use rusqlite::Connection;
+
+/// Synced tables and the timestamp column an HLC is seeded from.
+pub const SYNCED_TABLES: [(&str, &str); 8] = [
+    ("projects", "updated_at"),
+    ("applications", "updated_at"),
+    ("sessions", "updated_at"),
+    ("manual_sessions", "updated_at"),
+    ("clients", "updated_at"),
+    ("estimate_settings", "updated_at"),
+    ("tags", "updated_at"),
+    ("tombstones", "deleted_at"),
+];
+
+/// Current wall time in epoch ms, evaluated inside SQLite.
+const NOW_MS_SQL: &str = "CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)";
+
+const CLOCK_HLC_SQL: &str =
+    "SELECT printf('%013d-%04d-%s', wall_ms, counter, node_id) FROM sync_clock WHERE id = 1";
+
+/// Updated-at triggers that fire on ANY column change. Writing only `hlc`
+/// (seeding, merge) must not count as a local edit, so they get an extra
+/// `NEW.hlc IS OLD.hlc` guard.
+const UPDATED_AT_TRIGGERS: [(&str, &str); 2] = [
+    (
+        "trg_projects_updated_at",
+        "CREATE TRIGGER IF NOT EXISTS trg_projects_updated_at
+         AFTER UPDATE ON projects
+         FOR EACH ROW
+         WHEN NEW.updated_at IS OLD.updated_at AND NEW.hlc IS OLD.hlc
+         BEGIN
+             UPDATE projects SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+         END",
+    ),
+    (
+        "trg_manual_sessions_updated_at",
+        "CREATE TRIGGER IF NOT EXISTS trg_manual_sessions_updated_at
+         AFTER UPDATE ON manual_sessions
+         FOR EACH ROW
+         WHEN NEW.updated_at IS OLD.updated_at AND NEW.hlc IS OLD.hlc
+         BEGIN
+             UPDATE manual_sessions SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+         END",
+    ),
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Hlc {
+    pub wall_ms: i64,
+    pub counter: i64,
+    pub node: String,
+}
+
+impl Hlc {
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().splitn(3, '-');
+        let wall_ms = parts.next()?.parse().ok()?;
+        let counter = parts.next()?.parse().ok()?;
+        let node = parts.next()?.to_string();
+        Some(Self {
+            wall_ms,
+            counter,
+            node,
+        })
+    }
+
+    /// HLC for a row that predates clocks (or comes from an older peer):
+    /// the timestamp's wall time with counter 0 and no node. Unparseable
+    /// timestamps map to the epoch, so any real edit outranks them.
+    pub fn from_timestamp(ts: &str) -> Self {
+        Self {
+            wall_ms: parse_timestamp_ms(ts).unwrap_or(0),
+            counter: 0,
+            node: String::new(),
+        }
+    }
+
+    /// Stored `hlc` column when present and valid, otherwise seeded from `ts`.
+    pub fn from_parts(hlc: Option<&str>, ts: &str) -> Self {
+        hlc.and_then(Self::parse)
+            .unwrap_or_else(|| Self::from_timestamp(ts))
+    }
+}
+
+impl fmt::Display for Hlc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:013}-{:04}-{}", self.wall_ms, self.counter, self.node)
+    }
+}
+
+fn parse_timestamp_ms(ts: &str) -> Option<i64> {
+    let ts = ts.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
+        return Some(dt.timestamp_millis());
+    }
+    if let Ok(dt) = chrono::DateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f%z") {
+        return Some(dt.timestamp_millis());
+    }
+    // Naive timestamps (SQLite CURRENT_TIMESTAMP / datetime('now')) are UTC.
+    chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+        .map(|dt| dt.and_utc().timestamp_millis())
+}
+
+fn table_exists(conn: &Connection, table: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|c| c > 0)
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|c| c > 0)
+}
+
+fn trigger_sql(conn: &Connection, name: &str) -> rusqlite::Result<Option<String>> {
+    let mut stmt =
+        conn.prepare("SELECT sql FROM sqlite_master WHERE type = 'trigger' AND name = ?1")?;
+    let mut rows = stmt.query([name])?;
+    match rows.next()? {
+        Some(row) => row.get(0),
+        None => Ok(None),
+    }
+}
+
+fn hlc_triggers_sql(table: &str) -> String {
+    let tick = format!(
+        "UPDATE sync_clock
+             SET counter = CASE WHEN {now} > wall_ms THEN 0 ELSE counter + 1 END,
+                 wall_ms = MAX(wall_ms, {now})
+             WHERE id = 1;
+         UPDATE {table} SET hlc = ({clock}) WHERE rowid = NEW.rowid;",
+        now = NOW_MS_SQL,
+        clock = CLOCK_HLC_SQL,
+        table = table,
+    );
+    let mut sql = format!(
+        "CREATE TRIGGER IF NOT EXISTS trg_{table}_hlc_insert
+         AFTER INSERT ON {table}
+         FOR EACH ROW WHEN NEW.hlc IS NULL
+         BEGIN
+         {tick}
+         END;",
+        table = table,
+        tick = tick,
+    );
+    // Tombstones are never updated — deleted_at is fixed at insert time.
+    if table != "tombstones" {
+        sql.push_str(&format!(
+            "CREATE TRIGGER IF NOT EXISTS trg_{table}_hlc_update
+             AFTER UPDATE OF updated_at ON {table}
+             FOR EACH ROW WHEN NEW.hlc IS OLD.hlc
+             BEGIN
+             {tick}
+             END;",
+            table = table,
+            tick = tick,
+        ));
+    }
+    sql
+}
+
+/// True when the clock table, every `hlc` column and every trigger exist —
+/// lets callers run [`ensure_schema`] on each sync without issuing DDL.
+pub fn is_installed(conn: &Connection) -> rusqlite::Result<bool> {
+    if !table_exists(conn, "sync_clock")? {
+        return Ok(false);
+    }
+    for (table, _) in SYNCED_TABLES {
+        if !table_exists(conn, table)? {
+            continue;
+        }
+        if !column_exists(conn, table, "hlc")?
+            || trigger_sql(conn, &format!("trg_{}_hlc_insert", table))?.is_none()
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Idempotently installs HLCs: the shared clock, an `hlc` column on every
+/// synced table (seeded from its timestamp), and the stamping triggers.
+/// Tables that don't exist yet are skipped.
+pub fn ensure_schema(conn: &Connection) -> rusqlite::Result<()> {
+    if is_installed(conn)? {
+        return Ok(());
+    }
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sync_clock (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            wall_ms INTEGER NOT NULL,
+            counter INTEGER NOT NULL,
+            node_id TEXT NOT NULL
+        );
+        INSERT OR IGNORE INTO sync_clock (id, wall_ms, counter, node_id)
+        VALUES (1, 0, 0, lower(hex(randomblob(8))));",
+    )?;
+
+    let mut tables: Vec<(&str, &str)> = Vec::new();
+    for (table, ts_col) in SYNCED_TABLES {
+        if !table_exists(conn, table)? {
+            continue;
+        }
+        if !column_exists(conn, table, "hlc")? {
+            conn.execute(&format!("ALTER TABLE {} ADD COLUMN hlc TEXT", table), [])?;
+        }
+        tables.push((table, ts_col));
+    }
+
+    for (name, create_sql) in UPDATED_AT_TRIGGERS {
+        if let Some(existing) = trigger_sql(conn, name)? {
+            if !existing.contains("hlc") {
+                conn.execute(&format!("DROP TRIGGER IF EXISTS {}", name), [])?;
+                conn.execute(create_sql, [])?;
+            }
+        }
+    }
+
+    // Seed before the stamping triggers exist, so seeding isn't a local edit.
+    let mut newest = Hlc::default();
+    for (table, ts_col) in &tables {
+        let seeds: Vec<(i64, Hlc)> = {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT rowid, {} FROM {} WHERE hlc IS NULL",
+                ts_col, table
+            ))?;
+            let rows = stmt.query_map([], |row| {
+                let ts: Option<String> = row.get(1)?;
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    Hlc::from_timestamp(ts.as_deref().unwrap_or("")),
+                ))
+            })?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        if !seeds.is_empty() {
+            let mut update =
+                conn.prepare(&format!("UPDATE {} SET hlc = ?1 WHERE rowid = ?2", table))?;
+            for (rowid, hlc) in seeds {
+                update.execute(rusqlite::params![hlc.to_string(), rowid])?;
+                if hlc > newest {
+                    newest = hlc;
+                }
+            }
+        }
+    }
+    // Rows stamped in the future (skewed writer) must not be outranked by the
+    // next local edit — the clock starts at the newest seeded value.
+    observe(conn, &newest)?;
+
+    for (table, _) in &tables {
+        conn.execute_batch(&hlc_triggers_sql(table))?;
+    }
+    Ok(())
+}
+
+/// Pins the clock's node to `device_id`. [`ensure_schema`] starts with a
+/// random placeholder, which a database copied to another device would
+/// otherwise share with the original.
+pub fn set_node(conn: &Connection, device_id: &str) -> rusqlite::Result<()> {
+    let device_id = device_id.trim();
+    if device_id.is_empty() {
+        return Ok(());
+    }
+    conn.execute(
+        "UPDATE sync_clock SET node_id = ?1 WHERE id = 1 AND node_id IS NOT ?1",
+        [device_id],
+    )?;
+    Ok(())
+}
+
+/// HLC receive rule: after seeing a peer's clock, the local clock is at least
+/// as far ahead, so the next local edit outranks everything received so far.
+pub fn observe(conn: &Connection, remote: &Hlc) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE sync_clock
+         SET counter = CASE
+                 WHEN ?1 > wall_ms THEN ?2
+                 WHEN ?1 = wall_ms THEN MAX(counter, ?2)
+                 ELSE counter
+             END,
+             wall_ms = MAX(wall_ms, ?1)
+         WHERE id = 1",
+        rusqlite::params![remote.wall_ms, remote.counter],
+    )?;
+    Ok(())
+}
+
+/// Current clock value, e.g. for diagnostics and tests.
+pub fn current(conn: &Connection) -> rusqlite::Result<Option<Hlc>> {
+    let mut stmt = conn.prepare(CLOCK_HLC_SQL)?;
+    let mut rows = stmt.query([])?;
+    match rows.next()? {
+        Some(row) => Ok(Hlc::parse(&row.get::<_, String>(0)?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conn_with_projects() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE projects (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                color TEXT,
+                updated_at TEXT NOT NULL
+            );
+            CREATE TRIGGER trg_projects_updated_at
+            AFTER UPDATE ON projects
+            FOR EACH ROW
+            WHEN NEW.updated_at IS OLD.updated_at
+            BEGIN
+                UPDATE projects SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+            END;
+            INSERT INTO projects (name, color, updated_at)
+            VALUES ('Alpha', '#fff', '2026-01-01 10:00:00');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn parse_roundtrip_and_ordering() {
+        let a = Hlc::parse("1767261600000-0002-aa").unwrap();
+        assert_eq!(a.to_string(), "1767261600000-0002-aa");
+        assert!(Hlc::parse("1767261600000-0003-aa").unwrap() > a);
+        assert!(Hlc::parse("1767261600000-0002-bb").unwrap() > a);
+        assert!(Hlc::parse("1767261600001-0000-").unwrap() > a);
+        assert!(Hlc::parse("not-a-clock").is_none());
+        assert_eq!(
+            Hlc::from_parts(None, "2026-01-01 10:00:00"),
+            Hlc::from_timestamp("2026-01-01T10:00:00Z")
+        );
+        assert_eq!(Hlc::from_timestamp("garbage").wall_ms, 0);
+    }
+
+    #[test]
+    fn ensure_schema_seeds_from_timestamps_without_bumping_updated_at() {
+        let conn = conn_with_projects();
+        ensure_schema(&conn).unwrap();
+        ensure_schema(&conn).unwrap();
+
+        let (hlc, updated_at): (String, String) = conn
+            .query_row("SELECT hlc, updated_at FROM projects", [], |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(updated_at, "2026-01-01 10:00:00");
+        assert_eq!(Hlc::parse(&hlc), Some(Hlc::from_timestamp(&updated_at)));
+    }
+
+    #[test]
+    fn local_edits_carry_the_pinned_device_node() {
+        let conn = conn_with_projects();
+        ensure_schema(&conn).unwrap();
+        set_node(&conn, "laptop-18f2a").unwrap();
+        conn.execute(
+            "UPDATE projects SET color = '#000', updated_at = '2026-01-02 10:00:00' WHERE name = 'Alpha'",
+            [],
+        )
+        .unwrap();
+        let hlc: String = conn
+            .query_row("SELECT hlc FROM projects", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(Hlc::parse(&hlc).unwrap().node, "laptop-18f2a");
+
+        // The same database opened on another device takes its node.
+        set_node(&conn, "desktop-9c01").unwrap();
+        set_node(&conn, "  ").unwrap();
+        assert_eq!(current(&conn).unwrap().unwrap().node, "desktop-9c01");
+    }
+
+    #[test]
+    fn local_edit_after_observing_future_peer_clock_outranks_it() {
+        let conn = conn_with_projects();
+        ensure_schema(&conn).unwrap();
+
+        let peer = Hlc {
+            wall_ms: Hlc::from_timestamp("2099-01-01 00:00:00").wall_ms,
+            counter: 7,
+            node: "peer".to_string(),
+        };
+        observe(&conn, &peer).unwrap();
+
+        conn.execute(
+            "UPDATE projects SET color = '#000' WHERE name = 'Alpha'",
+            [],
+        )
+        .unwrap();
+        let hlc: String = conn
+            .query_row("SELECT hlc FROM projects", [], |r| r.get(0))
+            .unwrap();
+        let local = Hlc::parse(&hlc).unwrap();
+        assert!(local > peer, "{} should outrank {}", local, peer);
+        assert_eq!(Some(local), current(&conn).unwrap());
+
+        // Writing an explicit HLC (merge path) leaves it untouched.
+        conn.execute(
+            "UPDATE projects SET hlc = ?1, updated_at = '2026-02-01 00:00:00'",
+            [peer.to_string()],
+        )
+        .unwrap();
+        let hlc: String = conn
+            .query_row("SELECT hlc FROM projects", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(hlc, peer.to_string());
+    }
+}
@@ -0,0 +1,127 @@
+//! Poprzednie klucze online sync (`encryption_key`) po rotacji.
+//!
+//! Aktualny klucz siedzi w keychainie pod "online.encryption_key"; wycofane
+//! trafiają tutaj (lista JSON, najnowszy pierwszy), żeby paczki i poświadczenia
+//! zaszyfrowane starym kluczem dało się jeszcze odczytać. Dashboard i demon
+//! czytają ten sam wpis.
+//!
+//! Relay szyfruje poświadczenia kluczem z własnej konfiguracji konta, więc
+//! rotacja go nie zmienia. Klucz, który ostatnio je otworzył, jest zapamiętany
+//! pod "online.encryption_key.relay" i przycinanie listy go nie usuwa.
+
+use crate::secret_store;
+
+const ACCOUNT_CURRENT: &str = "online.encryption_key";
+const ACCOUNT_PREVIOUS: &str = "online.encryption_key.previous";
+const ACCOUNT_RELAY: &str = "online.encryption_key.relay";
+
+/// Ile wycofanych kluczy trzymamy — starsze paczki i tak wygasają.
+pub const MAX_PREVIOUS_KEYS: usize = 4;
+
+/// Wycofane klucze, najnowszy pierwszy.
+pub fn previous_keys() -> Vec<String> {
+    secret_store::get_secret(ACCOUNT_PREVIOUS)
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Klucz, który ostatnio otworzył poświadczenia z relay (pusty, gdy brak).
+pub fn relay_key() -> String {
+    secret_store::get_secret(ACCOUNT_RELAY).unwrap_or_default()
+}
+
+/// Zapamiętuje klucz, którym dało się otworzyć poświadczenia z relay. Zapis
+/// tylko przy zmianie — wywoływane przy każdej synchronizacji.
+pub fn note_relay_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || relay_key() == key {
+        return Ok(());
+    }
+    secret_store::set_secret(ACCOUNT_RELAY, key)
+}
+
+/// Lista po wycofaniu `old`: na początek, bez duplikatów i bez `current`,
+/// przycięta do `MAX_PREVIOUS_KEYS`. `protected` (klucz relay) zostaje na
+/// liście nawet ponad limit.
+pub fn with_retired(
+    previous: Vec<String>,
+    old: &str,
+    current: &str,
+    protected: &str,
+) -> Vec<String> {
+    let mut keys = Vec::with_capacity(previous.len() + 1);
+    for key in std::iter::once(old.to_string()).chain(previous) {
+        if !key.is_empty() && key != current && !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    let keep_protected = keys
+        .iter()
+        .position(|k| k == protected)
+        .is_some_and(|i| i >= MAX_PREVIOUS_KEYS);
+    keys.truncate(MAX_PREVIOUS_KEYS);
+    if keep_protected {
+        keys.push(protected.to_string());
+    }
+    keys
+}
+
+/// Zapisuje `old` jako wycofany, gdy aktualnym kluczem staje się `current`.
+pub fn retire_key(old: &str, current: &str) -> Result<(), String> {
+    let keys = with_retired(previous_keys(), old, current, &relay_key());
+    let json = serde_json::to_string(&keys).map_err(|e| e.to_string())?;
+    secret_store::set_secret(ACCOUNT_PREVIOUS, &json)
+}
+
+/// Ustawia `new` jako aktualny klucz, a dotychczasowy wycofuje. `false`, gdy
+/// `new` już jest aktualny (np. ta sama rotacja przyszła LAN-em i paczką).
+pub fn install_key(new: &str) -> Result<bool, String> {
+    let old = secret_store::get_secret(ACCOUNT_CURRENT).unwrap_or_default();
+    if old == new {
+        return Ok(false);
+    }
+    // Najpierw wycofanie: gdy zapis nowego klucza się nie uda, stary nadal
+    // jest aktualny, a jego kopia na liście niczemu nie szkodzi.
+    retire_key(&old, new)?;
+    secret_store::set_secret(ACCOUNT_CURRENT, new)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retired_keys_are_newest_first_unique_and_capped() {
+        let keys = with_retired(vec!["k1".into(), "k0".into()], "k2", "k3", "");
+        assert_eq!(keys, ["k2", "k1", "k0"]);
+        // Re-retiring a key moves it to the front; the current key never lingers.
+        assert_eq!(
+            with_retired(keys.clone(), "k0", "k3", ""),
+            ["k0", "k2", "k1"]
+        );
+        assert_eq!(with_retired(keys.clone(), "k1", "k2", ""), ["k1", "k0"]);
+        assert_eq!(with_retired(keys, "", "k3", ""), ["k2", "k1", "k0"]);
+
+        let many: Vec<String> = (0..10).map(|i| format!("k{}", i)).collect();
+        assert_eq!(
+            with_retired(many, "new", "cur", "").len(),
+            MAX_PREVIOUS_KEYS
+        );
+    }
+
+    #[test]
+    fn relay_key_survives_any_number_of_rotations() {
+        // The relay seals with the key it was configured with; rotations
+        // must not push that key out of the keyring.
+        let mut current = "relay".to_string();
+        let mut previous = Vec::new();
+        for i in 0..10 {
+            let next = format!("k{}", i);
+            previous = with_retired(previous, &current, &next, "relay");
+            current = next;
+        }
+        assert_eq!(previous.len(), MAX_PREVIOUS_KEYS + 1);
+        assert_eq!(previous[..MAX_PREVIOUS_KEYS], ["k8", "k7", "k6", "k5"]);
+        assert_eq!(previous.last().map(String::as_str), Some("relay"));
+    }
+}
@@ -0,0 +1,244 @@
+//! Sync protocol version and capability negotiation.
+//!
+//! Peers announce `sync_protocol`, `min_sync_protocol` and `capabilities` in
+//! `/lan/ping`, `/lan/preflight`, UDP beacons and the online session create
+//! call. Two peers may sync when each one's protocol is within the other's
+//! supported range; the archive they exchange is then limited to the
+//! capabilities both sides list. Peers from before negotiation announce
+//! nothing — for them the old rule (identical TIMEFLOW version) still applies.
+//!
+//! Bump `SYNC_PROTOCOL_VERSION` only for changes an older peer cannot ignore
+//! (step order, endpoint semantics). New tables/columns become capabilities.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+pub const SYNC_PROTOCOL_VERSION: u32 = 2;
+/// Oldest protocol this build still syncs with. Version 1 is the implicit
+/// protocol of peers that predate negotiation.
+pub const MIN_SYNC_PROTOCOL_VERSION: u32 = 2;
+
+/// `projects.merged_into` / `projects.merged_at`.
+pub const CAP_PROJECT_MERGE: &str = "project_merge";
+/// `projects.client_name`.
+pub const CAP_PROJECT_CLIENT: &str = "project_client";
+/// `data.clients`.
+pub const CAP_CLIENTS: &str = "clients";
+/// `data.estimate_settings`.
+pub const CAP_ESTIMATE_SETTINGS: &str = "estimate_settings";
+/// `data.file_activities`.
+pub const CAP_FILE_ACTIVITIES: &str = "file_activities";
+/// Per-row `hlc` (hybrid logical clock) values.
+pub const CAP_HLC: &str = "hlc";
+/// `data.tags` and the `tags` name list on sessions / manual sessions.
+pub const CAP_TAGS: &str = "tags";
+/// `billable` on projects, sessions and manual sessions.
+pub const CAP_BILLABLE: &str = "billable";
+
+pub const LOCAL_CAPABILITIES: &[&str] = &[
+    CAP_PROJECT_MERGE,
+    CAP_PROJECT_CLIENT,
+    CAP_CLIENTS,
+    CAP_ESTIMATE_SETTINGS,
+    CAP_FILE_ACTIVITIES,
+    CAP_HLC,
+    CAP_TAGS,
+    CAP_BILLABLE,
+];
+
+/// What a peer announces. Flattened into ping/preflight responses; the online
+/// server API uses the camelCase spelling.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolInfo {
+    #[serde(alias = "syncProtocol")]
+    pub sync_protocol: u32,
+    #[serde(default, alias = "minSyncProtocol")]
+    pub min_sync_protocol: u32,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+impl ProtocolInfo {
+    pub fn local() -> Self {
+        Self {
+            sync_protocol: SYNC_PROTOCOL_VERSION,
+            min_sync_protocol: MIN_SYNC_PROTOCOL_VERSION,
+            capabilities: LOCAL_CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    /// Reads the announcement from a ping/preflight/session JSON object.
+    /// `None` for peers that predate negotiation.
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let info: Self = serde_json::from_value(value.clone()).ok()?;
+        (info.sync_protocol > 0).then_some(info)
+    }
+}
+
+/// Capabilities both sides support — what the exchanged archive may contain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncFeatures(BTreeSet<String>);
+
+impl SyncFeatures {
+    /// Everything this build supports (peer on the same build).
+    pub fn all() -> Self {
+        Self::from_names(LOCAL_CAPABILITIES.iter().copied())
+    }
+
+    /// Local capabilities limited to `names` (unknown names are dropped).
+    pub fn from_names<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let wanted: BTreeSet<String> = names.into_iter().map(|n| n.as_ref().to_string()).collect();
+        Self(
+            LOCAL_CAPABILITIES
+                .iter()
+                .filter(|c| wanted.contains(**c))
+                .map(|c| c.to_string())
+                .collect(),
+        )
+    }
+
+    pub fn supports(&self, capability: &str) -> bool {
+        self.0.contains(capability)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().cloned().collect()
+    }
+
+    /// Local capabilities the peer lacks — skipped for this sync.
+    pub fn missing(&self) -> Vec<&'static str> {
+        LOCAL_CAPABILITIES
+            .iter()
+            .copied()
+            .filter(|c| !self.supports(c))
+            .collect()
+    }
+}
+
+/// Replaces the strict version-equality gate. `peer` is the peer's
+/// announcement (`None` = peer predates negotiation).
+pub fn negotiate(
+    local_version: &str,
+    peer_version: Option<&str>,
+    peer: Option<&ProtocolInfo>,
+) -> Result<SyncFeatures, String> {
+    match peer {
+        Some(peer) => {
+            if peer.sync_protocol < MIN_SYNC_PROTOCOL_VERSION {
+                return Err(format!(
+                    "protocol_mismatch: peer speaks sync protocol v{}, this build needs v{}+ — update the peer",
+                    peer.sync_protocol, MIN_SYNC_PROTOCOL_VERSION
+                ));
+            }
+            if peer.min_sync_protocol > SYNC_PROTOCOL_VERSION {
+                return Err(format!(
+                    "protocol_mismatch: peer needs sync protocol v{}+, this build speaks v{} — update this machine",
+                    peer.min_sync_protocol, SYNC_PROTOCOL_VERSION
+                ));
+            }
+            Ok(SyncFeatures::from_names(&peer.capabilities))
+        }
+        None => {
+            let peer_version = peer_version.unwrap_or("unknown");
+            if peer_version == local_version {
+                Ok(SyncFeatures::all())
+            } else {
+                Err(format!(
+                    "version_mismatch: local v{}, peer v{} (peer predates protocol negotiation) — update the peer",
+                    local_version, peer_version
+                ))
+            }
+        }
+    }
+}
+
+/// UI-level check from what discovery knows about a peer (protocol range
+/// from beacons, no capability list). `sync_protocol == 0` = not announced.
+pub fn peer_compatible(
+    local_version: &str,
+    peer_version: &str,
+    sync_protocol: u32,
+    min_sync_protocol: u32,
+) -> bool {
+    let info = ProtocolInfo {
+        sync_protocol,
+        min_sync_protocol,
+        capabilities: Vec::new(),
+    };
+    let peer = (sync_protocol > 0).then_some(&info);
+    negotiate(local_version, Some(peer_version), peer).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_protocol_different_versions_sync_on_common_subset() {
+        let peer = ProtocolInfo {
+            sync_protocol: SYNC_PROTOCOL_VERSION,
+            min_sync_protocol: MIN_SYNC_PROTOCOL_VERSION,
+            capabilities: vec![CAP_HLC.to_string(), "from_the_future".to_string()],
+        };
+        let features = negotiate("0.1.5800", Some("0.1.5790"), Some(&peer)).unwrap();
+        assert!(features.supports(CAP_HLC));
+        assert!(!features.supports(CAP_PROJECT_MERGE));
+        assert!(!features.supports("from_the_future"));
+        assert!(features.missing().contains(&CAP_PROJECT_MERGE));
+    }
+
+    #[test]
+    fn protocol_ranges_must_overlap() {
+        let too_old = ProtocolInfo {
+            sync_protocol: MIN_SYNC_PROTOCOL_VERSION - 1,
+            ..ProtocolInfo::default()
+        };
+        let err = negotiate("a", Some("b"), Some(&too_old)).unwrap_err();
+        assert!(err.contains("protocol_mismatch"));
+
+        let too_new = ProtocolInfo {
+            sync_protocol: SYNC_PROTOCOL_VERSION + 5,
+            min_sync_protocol: SYNC_PROTOCOL_VERSION + 1,
+            capabilities: Vec::new(),
+        };
+        let err = negotiate("a", Some("b"), Some(&too_new)).unwrap_err();
+        assert!(err.contains("update this machine"));
+    }
+
+    #[test]
+    fn legacy_peer_needs_identical_version() {
+        assert_eq!(
+            negotiate("0.1.5", Some("0.1.5"), None).unwrap(),
+            SyncFeatures::all()
+        );
+        assert!(negotiate("0.1.5", Some("0.1.4"), None)
+            .unwrap_err()
+            .contains("version_mismatch"));
+        assert!(negotiate("0.1.5", None, None).is_err());
+        assert!(!peer_compatible("0.1.5", "0.1.4", 0, 0));
+        assert!(peer_compatible(
+            "0.1.5",
+            "0.1.4",
+            SYNC_PROTOCOL_VERSION,
+            MIN_SYNC_PROTOCOL_VERSION
+        ));
+    }
+
+    #[test]
+    fn announcement_roundtrips_through_ping_json() {
+        let mut ping = serde_json::json!({ "ok": true, "version": "0.1.5" });
+        let local = serde_json::to_value(ProtocolInfo::local()).unwrap();
+        ping.as_object_mut()
+            .unwrap()
+            .extend(local.as_object().unwrap().clone());
+        assert_eq!(ProtocolInfo::from_json(&ping), Some(ProtocolInfo::local()));
+        assert_eq!(
+            ProtocolInfo::from_json(&serde_json::json!({ "ok": true })),
+            None
+        );
+    }
+}
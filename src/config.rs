@@ -66,11 +66,30 @@ pub fn ensure_app_dirs() -> Result<()> {
     Ok(())
 }
 
+thread_local! {
+    /// Katalog danych tego wątku zamiast `<user_data_root>/TimeFlow`. Ustawiany
+    /// tylko przez testy (kilka węzłów sync w jednym procesie); serwer LAN
+    /// przenosi go do wątków połączeń.
+    static DATA_DIR_OVERRIDE: std::cell::RefCell<Option<PathBuf>> =
+        const { std::cell::RefCell::new(None) };
+}
+
 /// Zwraca ścieżkę do katalogu konfiguracji (`<user_data_root>/TimeFlow`).
 pub fn config_dir() -> Result<PathBuf> {
+    if let Some(dir) = data_dir_override() {
+        return Ok(dir);
+    }
     timeflow_paths::timeflow_data_dir().context("Failed to resolve TIMEFLOW data directory")
 }
 
+pub(crate) fn data_dir_override() -> Option<PathBuf> {
+    DATA_DIR_OVERRIDE.with(|dir| dir.borrow().clone())
+}
+
+pub(crate) fn set_data_dir_override(dir: Option<PathBuf>) {
+    DATA_DIR_OVERRIDE.with(|slot| *slot.borrow_mut() = dir);
+}
+
 /// Ścieżka do pliku konfiguracyjnego
 fn config_path() -> Result<PathBuf> {
     Ok(config_dir()?.join("monitored_apps.json"))
@@ -173,6 +192,69 @@ pub struct OnlineSyncSettings {
     /// Group ID for license-based features (async delta, etc.)
     #[serde(default)]
     pub group_id: String,
+    /// Where transfer blobs are stored — see `remote_storage`.
+    #[serde(default)]
+    pub storage: OnlineStorageSettings,
+    /// End-to-end passphrase (keychain "online.passphrase"); when set, blobs
+    /// are encrypted with a key the server never sees — see `sync_passphrase`.
+    #[serde(default)]
+    pub passphrase: String,
+    /// Keys retired by rotation (keychain, `timeflow_shared::sync_keyring`),
+    /// newest first — still accepted for data sealed before the rotation.
+    #[serde(default, skip_serializing)]
+    pub previous_encryption_keys: Vec<String>,
+}
+
+/// Storage backend for online sync transfers. The server still coordinates
+/// and issues per-transfer paths and file keys; only the blobs move.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct OnlineStorageSettings {
+    /// "server" (SFTP / relay HTTP from the credentials), "local", "webdav", "s3"
+    #[serde(default = "default_storage_backend")]
+    pub backend: String,
+    /// Folder for the "local" backend (shared/synced directory, NAS mount).
+    #[serde(default)]
+    pub local_path: String,
+    /// WebDAV collection URL or S3 endpoint.
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub bucket: String,
+    #[serde(default)]
+    pub region: String,
+    /// Path-style S3 addressing (`{url}/{bucket}/key`) — MinIO and most
+    /// self-hosted servers; false for virtual-host style.
+    #[serde(default = "default_enabled")]
+    pub path_style: bool,
+    /// Key prefix inside the folder / collection / bucket.
+    #[serde(default)]
+    pub prefix: String,
+    /// WebDAV user or S3 access key id.
+    #[serde(default)]
+    pub username: String,
+    /// WebDAV password or S3 secret key — kept in the keychain, not the file.
+    #[serde(default)]
+    pub password: String,
+}
+
+fn default_storage_backend() -> String {
+    "server".to_string()
+}
+
+impl Default for OnlineStorageSettings {
+    fn default() -> Self {
+        Self {
+            backend: default_storage_backend(),
+            local_path: String::new(),
+            url: String::new(),
+            bucket: String::new(),
+            region: String::new(),
+            path_style: true,
+            prefix: String::new(),
+            username: String::new(),
+            password: String::new(),
+        }
+    }
 }
 
 fn default_sync_mode() -> String {
@@ -188,6 +270,15 @@ impl OnlineSyncSettings {
             self.device_id.clone()
         }
     }
+
+    /// Current `encryption_key` first, then the retired ones.
+    pub fn keyring(&self) -> Vec<String> {
+        std::iter::once(&self.encryption_key)
+            .chain(&self.previous_encryption_keys)
+            .filter(|k| !k.is_empty())
+            .cloned()
+            .collect()
+    }
 }
 
 impl Default for OnlineSyncSettings {
@@ -202,14 +293,18 @@ impl Default for OnlineSyncSettings {
             auto_sync_on_startup: false,
             sync_mode: "session".to_string(),
             group_id: String::new(),
+            storage: OnlineStorageSettings::default(),
+            passphrase: String::new(),
+            previous_encryption_keys: Vec::new(),
         }
     }
 }
 
 /// Read online sync settings from the shared file (written by dashboard).
-/// Secrets (auth_token, encryption_key) are NOT in the file — they live in the
-/// OS keychain (timeflow_shared::secret_store, service "TIMEFLOW", same accounts
-/// the dashboard writes). Hydrate them here when the file fields are empty.
+/// Secrets (auth_token, encryption_key, storage.password, passphrase) are NOT in
+/// the file — they live in the OS keychain (timeflow_shared::secret_store,
+/// service "TIMEFLOW", same accounts the dashboard writes). Hydrate them here
+/// when the file fields are empty, along with the retired encryption keys.
 pub fn load_online_sync_settings() -> OnlineSyncSettings {
     let path = match config_dir() {
         Ok(d) => d.join("online_sync_settings.json"),
@@ -235,6 +330,17 @@ pub fn load_online_sync_settings() -> OnlineSyncSettings {
             settings.encryption_key = v;
         }
     }
+    if settings.storage.password.is_empty() {
+        if let Some(v) = timeflow_shared::secret_store::get_secret("online.storage_password") {
+            settings.storage.password = v;
+        }
+    }
+    if settings.passphrase.is_empty() {
+        if let Some(v) = timeflow_shared::secret_store::get_secret("online.passphrase") {
+            settings.passphrase = v;
+        }
+    }
+    settings.previous_encryption_keys = timeflow_shared::sync_keyring::previous_keys();
     settings
 }
 
@@ -622,10 +728,14 @@ pub struct LogSettings {
     /// Max size per log file in KB (default 1024 = 1 MB)
     #[serde(default = "default_max_log_size_kb")]
     pub max_log_size_kb: u32,
+    /// Days of structured sync history (`sync_runs`) kept (default 90).
+    #[serde(default = "default_sync_history_retention_days")]
+    pub sync_history_retention_days: u32,
 }
 
 fn default_log_level() -> String { "info".to_string() }
 fn default_max_log_size_kb() -> u32 { 1024 }
+fn default_sync_history_retention_days() -> u32 { 90 }
 
 impl Default for LogSettings {
     fn default() -> Self {
@@ -635,6 +745,7 @@ impl Default for LogSettings {
             online_sync_level: default_log_level(),
             dashboard_level: default_log_level(),
             max_log_size_kb: default_max_log_size_kb(),
+            sync_history_retention_days: default_sync_history_retention_days(),
         }
     }
 }
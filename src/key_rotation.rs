@@ -0,0 +1,191 @@
+//! Rotation of the online sync `encryption_key`.
+//!
+//! The new key travels as `{"keyRotation": {"keyId", "wrapped"}}`: the key in
+//! the keyed envelope under a key derived from the previous `encryption_key`,
+//! so only devices that already hold that key can install the new one. The
+//! same message goes to paired LAN peers (`/lan/receive-key`, authenticated
+//! with the pairing secret) and, for devices out of reach, as an async
+//! package. The old key stays in the keyring (`timeflow_shared::sync_keyring`)
+//! so credentials and packages sealed before the rotation still open.
+
+use crate::config;
+use crate::lan_common::sync_log;
+use crate::lan_discovery;
+use crate::lan_sync_orchestrator;
+use crate::online_sync;
+use crate::sync_encryption;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+const WRAP_PURPOSE: &str = "key-rotation";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyRotation {
+    /// `sync_encryption::key_id` of the new key.
+    pub key_id: String,
+    /// base64 keyed envelope with the new key.
+    pub wrapped: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Message {
+    key_rotation: KeyRotation,
+}
+
+/// Outcome of `rotate`, returned to the dashboard.
+#[derive(Serialize, Debug, Default)]
+pub struct RotationReport {
+    pub key_id: String,
+    pub lan_delivered: Vec<String>,
+    /// (device_id, error)
+    pub lan_failed: Vec<(String, String)>,
+    pub package_id: Option<String>,
+    pub package_error: Option<String>,
+}
+
+/// Fresh 32-byte key, base64 — the `encryption_key` format.
+pub fn generate_key() -> Result<String, String> {
+    let mut key = [0u8; 32];
+    getrandom::getrandom(&mut key).map_err(|e| format!("Failed to generate key: {}", e))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(key))
+}
+
+/// The rotation message for `new_key`, readable only with `old_key`.
+pub fn wrap(new_key: &str, old_key: &str) -> Result<String, String> {
+    let wrapping_key = sync_encryption::derive_key_base64(old_key, WRAP_PURPOSE);
+    let envelope = sync_encryption::encrypt_file_data_keyed(new_key.as_bytes(), &wrapping_key)?;
+    let message = Message {
+        key_rotation: KeyRotation {
+            key_id: sync_encryption::key_id(new_key),
+            wrapped: base64::engine::general_purpose::STANDARD.encode(envelope),
+        },
+    };
+    serde_json::to_string(&message).map_err(|e| e.to_string())
+}
+
+/// `Some` when `payload` is a rotation message rather than a delta archive.
+pub fn parse(payload: &str) -> Option<KeyRotation> {
+    // Cheap check first — deltas can be large.
+    if !payload.trim_start().starts_with(r#"{"keyRotation""#) {
+        return None;
+    }
+    serde_json::from_str::<Message>(payload)
+        .ok()
+        .map(|m| m.key_rotation)
+}
+
+/// The new key, unwrapped with whichever keyring key wrapped it.
+pub fn unwrap(rotation: &KeyRotation, keyring: &[String]) -> Result<String, String> {
+    let envelope = base64::engine::general_purpose::STANDARD
+        .decode(&rotation.wrapped)
+        .map_err(|e| format!("Wrapped key decode: {}", e))?;
+    let wrapping_keys: Vec<String> = keyring
+        .iter()
+        .map(|k| sync_encryption::derive_key_base64(k, WRAP_PURPOSE))
+        .collect();
+    let plain = sync_encryption::decrypt_file_data_keyring(&envelope, &wrapping_keys)
+        .map_err(|e| format!("Rotated key was wrapped with an unknown key: {}", e))?;
+    let key = String::from_utf8(plain).map_err(|e| format!("Invalid rotated key: {}", e))?;
+    if sync_encryption::key_id(&key) != rotation.key_id {
+        return Err("Rotated key does not match its key id".to_string());
+    }
+    Ok(key)
+}
+
+/// Unwraps and installs the new key as current. `false` when it already was.
+pub fn install(rotation: &KeyRotation, keyring: &[String]) -> Result<bool, String> {
+    let key = unwrap(rotation, keyring)?;
+    let changed = timeflow_shared::sync_keyring::install_key(&key)?;
+    if changed {
+        sync_log(&format!(
+            "[key-rotation] Zainstalowano klucz {}",
+            rotation.key_id
+        ));
+    }
+    Ok(changed)
+}
+
+/// Generates a new key, hands it to paired LAN peers and — with async sync
+/// configured — publishes a key package, then makes it current locally.
+/// Delivery failures are reported, not fatal: the old key stays in every
+/// keyring, so a device can still be updated later.
+pub fn rotate(settings: &config::OnlineSyncSettings) -> Result<RotationReport, String> {
+    if settings.encryption_key.is_empty() {
+        return Err("encryption_key not configured in online sync settings".to_string());
+    }
+    let new_key = generate_key()?;
+    let message = wrap(&new_key, &settings.encryption_key)?;
+    let mut report = RotationReport {
+        key_id: sync_encryption::key_id(&new_key),
+        ..RotationReport::default()
+    };
+    sync_log(&format!(
+        "[key-rotation] Rotacja klucza -> {}",
+        report.key_id
+    ));
+
+    for peer in lan_discovery::active_peers() {
+        match lan_sync_orchestrator::send_key_rotation(&peer, &message) {
+            Ok(()) => report.lan_delivered.push(peer.device_id),
+            Err(e) => {
+                sync_log(&format!(
+                    "[key-rotation] Peer {} nie przyjął klucza: {}",
+                    peer.device_id, e
+                ));
+                report.lan_failed.push((peer.device_id, e));
+            }
+        }
+    }
+
+    if settings.enabled && !settings.server_url.is_empty() && !settings.group_id.is_empty() {
+        match online_sync::push_key_package(settings, &settings.group_id, &message) {
+            Ok(id) => report.package_id = Some(id),
+            Err(e) => {
+                sync_log(&format!(
+                    "[key-rotation] Paczka z kluczem nie wysłana: {}",
+                    e
+                ));
+                report.package_error = Some(e);
+            }
+        }
+    }
+
+    timeflow_shared::sync_keyring::install_key(&new_key)?;
+    sync_log(&format!(
+        "[key-rotation] Klucz {} jest teraz aktualny",
+        report.key_id
+    ));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_message_opens_only_with_a_key_that_wrapped_it() {
+        let (old, new, stranger) = (
+            generate_key().unwrap(),
+            generate_key().unwrap(),
+            generate_key().unwrap(),
+        );
+        let message = wrap(&new, &old).unwrap();
+        let rotation = parse(&message).expect("rotation message");
+        assert_eq!(rotation.key_id, sync_encryption::key_id(&new));
+
+        // A device that already rotated once still holds `old` as a retired key.
+        assert_eq!(
+            unwrap(&rotation, &[stranger.clone(), old.clone()]).unwrap(),
+            new
+        );
+        assert!(unwrap(&rotation, &[stranger]).is_err());
+
+        let mut forged = rotation.clone();
+        forged.key_id = sync_encryption::key_id(&old);
+        assert!(unwrap(&forged, &[old]).is_err());
+
+        assert!(parse(r#"{"data":{"projects":[]}}"#).is_none());
+    }
+}
@@ -201,39 +201,78 @@ pub fn open_dashboard_db_readonly() -> Result<rusqlite::Connection, String> {
 
 /// Compute a hash for a table's content using DefaultHasher.
 pub fn compute_table_hash(conn: &rusqlite::Connection, table: &str) -> String {
+    table_hash(conn, table, &crate::sync_common::DeviceLocalScope::load(conn))
+}
+
+/// Device-local rows (selective sync) are left out, like in every export —
+/// peers never see them, so they must not make two synced devices differ.
+fn table_hash(
+    conn: &rusqlite::Connection,
+    table: &str,
+    local: &crate::sync_common::DeviceLocalScope,
+) -> String {
+    let (local_projects, local_apps) = (&local.project_ids, &local.app_ids);
     let sql = match table {
-        "projects" => {
+        "projects" => format!(
             "SELECT COALESCE(group_concat(name || '|' || updated_at, ';'), '') \
-             FROM (SELECT name, updated_at FROM projects ORDER BY name)"
-        }
-        "applications" => {
+             FROM (SELECT name, updated_at FROM projects WHERE id NOT IN ({}) ORDER BY name)",
+            local_projects
+        ),
+        "applications" => format!(
             "SELECT COALESCE(group_concat(executable_name || '|' || updated_at, ';'), '') \
-             FROM (SELECT executable_name, updated_at FROM applications ORDER BY executable_name)"
-        }
-        "sessions" => {
+             FROM (SELECT executable_name, updated_at FROM applications \
+                   WHERE id NOT IN ({}) ORDER BY executable_name)",
+            local_apps
+        ),
+        "sessions" => format!(
             "SELECT COALESCE(group_concat(app_name || '|' || start_time || '|' || updated_at, ';'), '') \
              FROM (SELECT a.executable_name AS app_name, s.start_time, s.updated_at \
                    FROM sessions s JOIN applications a ON s.app_id = a.id \
-                   ORDER BY a.executable_name, s.start_time)"
-        }
-        "manual_sessions" => {
+                   WHERE s.app_id NOT IN ({}) \
+                     AND (s.project_id IS NULL OR s.project_id NOT IN ({})) \
+                   ORDER BY a.executable_name, s.start_time)",
+            local_apps, local_projects
+        ),
+        "manual_sessions" => format!(
             "SELECT COALESCE(group_concat(title || '|' || start_time || '|' || updated_at, ';'), '') \
-             FROM (SELECT title, start_time, updated_at FROM manual_sessions ORDER BY title, start_time)"
-        }
+             FROM (SELECT title, start_time, updated_at FROM manual_sessions \
+                   WHERE (project_id IS NULL OR project_id NOT IN ({})) \
+                     AND (app_id IS NULL OR app_id NOT IN ({})) \
+                   ORDER BY title, start_time)",
+            local_projects, local_apps
+        ),
+        "clients" => "SELECT COALESCE(group_concat(name || '|' || updated_at, ';'), '') \
+             FROM (SELECT name, updated_at FROM clients ORDER BY name)"
+            .to_string(),
+        "estimate_settings" => "SELECT COALESCE(group_concat(key || '|' || updated_at, ';'), '') \
+             FROM (SELECT key, updated_at FROM estimate_settings ORDER BY key)"
+            .to_string(),
+        "tags" => "SELECT COALESCE(group_concat(name || '|' || updated_at, ';'), '') \
+             FROM (SELECT name, updated_at FROM tags ORDER BY name)"
+            .to_string(),
         _ => return String::new(),
     };
     let concat: String = conn
-        .query_row(sql, [], |row| row.get(0))
+        .query_row(&sql, [], |row| row.get(0))
         .unwrap_or_else(|_| String::new());
     format!("{:032x}", hash_128(concat.as_bytes()))
 }
 
-/// Compute hashes for all 4 sync tables, concatenated.
+/// Compute hashes for all synced tables, concatenated.
 pub fn compute_tables_hash_string(conn: &rusqlite::Connection) -> String {
-    let tables = ["projects", "applications", "sessions", "manual_sessions"];
+    let tables = [
+        "projects",
+        "applications",
+        "sessions",
+        "manual_sessions",
+        "clients",
+        "estimate_settings",
+        "tags",
+    ];
+    let local = crate::sync_common::DeviceLocalScope::load(conn);
     let mut combined = String::new();
     for table in &tables {
-        combined.push_str(&compute_table_hash(conn, table));
+        combined.push_str(&table_hash(conn, table, &local));
     }
     combined
 }
@@ -253,6 +292,25 @@ pub fn primary_local_ip() -> Option<String> {
     if ip.is_loopback() { None } else { Some(ip.to_string()) }
 }
 
+/// Gniazdo IPv6 z IPV6_V6ONLY — działa obok osobnego gniazda IPv4 na tym samym
+/// porcie (Linux domyślnie robi z `[::]` dual-stack i bind 0.0.0.0 by się nie udał).
+pub fn bind_ipv6_only(
+    addr: std::net::SocketAddr,
+    ty: socket2::Type,
+) -> std::io::Result<socket2::Socket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+    let protocol = if ty == Type::STREAM { Protocol::TCP } else { Protocol::UDP };
+    let socket = Socket::new(Domain::IPV6, ty, Some(protocol))?;
+    socket.set_only_v6(true)?;
+    // Jak std::net::TcpListener::bind na unixie — szybki restart bez TIME_WAIT.
+    #[cfg(unix)]
+    if ty == Type::STREAM {
+        socket.set_reuse_address(true)?;
+    }
+    socket.bind(&addr.into())?;
+    Ok(socket)
+}
+
 #[cfg(test)]
 mod tests {
     use super::get_machine_name;
@@ -1,11 +1,12 @@
-// LAN Discovery — UDP broadcast peer discovery for TIMEFLOW LAN Sync.
+// LAN Discovery — UDP broadcast + DNS-SD (mDNS) peer discovery for TIMEFLOW LAN Sync.
 // Runs in a dedicated thread, controlled by Arc<AtomicBool> stop signal.
+// HTTP subnet scan is the last resort, used only while no peer is known.
 // Writes discovered peers to %APPDATA%/TimeFlow/lan_peers.json.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::net::UdpSocket;
+use std::net::{Ipv6Addr, SocketAddr, SocketAddrV6, UdpSocket};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
@@ -16,8 +17,11 @@ use timeflow_shared::process_utils::no_console;
 
 use crate::config;
 use crate::lan_common;
+use crate::lan_mdns;
 use crate::lan_server::LanSyncState;
 use crate::lan_sync_orchestrator;
+use timeflow_shared::peer_addr;
+use timeflow_shared::sync_protocol::{self, ProtocolInfo};
 
 /// Cached ipconfig output to avoid spawning the process on every beacon (every 30s).
 /// TTL: 120 seconds.
@@ -57,6 +61,12 @@ const BEACON_INTERVAL: Duration = Duration::from_secs(30);
 const PEER_EXPIRY: Duration = Duration::from_secs(120);
 const RECV_TIMEOUT: Duration = Duration::from_secs(1);
 const PROTOCOL_VERSION: u32 = 1;
+/// How long mDNS and broadcast get to find peers before the first HTTP subnet scan.
+const SUBNET_SCAN_GRACE: Duration = Duration::from_secs(15);
+/// Link-local multicast group for beacons on IPv6 (IPv6 has no broadcast).
+/// ff02::114 is the IANA "any private experiment" link-scope address.
+const DISCOVERY_GROUP_V6: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0x114);
+
 // ── Beacon / Discovery packets ──
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -79,6 +89,11 @@ struct BeaconPacket {
     /// Capped at MAX_UPTIME_SECS to limit spoofing impact.
     #[serde(default)]
     uptime_secs: u64,
+    /// Sync protocol range (timeflow_shared::sync_protocol); 0 = peer predates negotiation.
+    #[serde(default)]
+    sync_protocol: u32,
+    #[serde(default)]
+    min_sync_protocol: u32,
 }
 
 /// Cap uptime at 30 days — any value beyond is likely spoofed or a bug.
@@ -88,6 +103,73 @@ fn default_role() -> String {
     "undecided".to_string()
 }
 
+fn beacon_packet(
+    device_id: &str,
+    machine_name: &str,
+    dashboard_running: bool,
+    version: &str,
+    role: &str,
+    uptime_secs: u64,
+) -> BeaconPacket {
+    BeaconPacket {
+        packet_type: "timeflow_beacon".to_string(),
+        version: PROTOCOL_VERSION,
+        device_id: device_id.to_string(),
+        machine_name: machine_name.to_string(),
+        dashboard_port: DASHBOARD_PORT_DEFAULT,
+        dashboard_running,
+        timeflow_version: version.to_string(),
+        role: role.to_string(),
+        sync_marker_hash: None,
+        sync_ready: true,
+        uptime_secs,
+        sync_protocol: sync_protocol::SYNC_PROTOCOL_VERSION,
+        min_sync_protocol: sync_protocol::MIN_SYNC_PROTOCOL_VERSION,
+    }
+}
+
+/// DNS-SD TXT record for a beacon — same fields, values as strings.
+fn beacon_txt(packet: &BeaconPacket) -> HashMap<String, String> {
+    let mut txt = HashMap::from([
+        ("version".to_string(), packet.version.to_string()),
+        ("device_id".to_string(), packet.device_id.clone()),
+        ("machine_name".to_string(), packet.machine_name.clone()),
+        ("dashboard_port".to_string(), packet.dashboard_port.to_string()),
+        ("dashboard_running".to_string(), packet.dashboard_running.to_string()),
+        ("timeflow_version".to_string(), packet.timeflow_version.clone()),
+        ("role".to_string(), packet.role.clone()),
+        ("sync_ready".to_string(), packet.sync_ready.to_string()),
+        ("uptime_secs".to_string(), packet.uptime_secs.to_string()),
+        ("sync_protocol".to_string(), packet.sync_protocol.to_string()),
+        ("min_sync_protocol".to_string(), packet.min_sync_protocol.to_string()),
+    ]);
+    if let Some(ref hash) = packet.sync_marker_hash {
+        txt.insert("sync_marker_hash".to_string(), hash.clone());
+    }
+    txt
+}
+
+/// Inverse of `beacon_txt`. Missing optional fields take the same defaults
+/// as a JSON beacon; records without device_id/port/version are ignored.
+fn beacon_from_txt(txt: &HashMap<String, String>) -> Option<BeaconPacket> {
+    let get = |key: &str| txt.get(key).map(String::as_str);
+    Some(BeaconPacket {
+        packet_type: "timeflow_beacon".to_string(),
+        version: get("version")?.parse().ok()?,
+        device_id: get("device_id").filter(|id| !id.is_empty())?.to_string(),
+        machine_name: get("machine_name").unwrap_or_default().to_string(),
+        dashboard_port: get("dashboard_port")?.parse().ok()?,
+        dashboard_running: get("dashboard_running") == Some("true"),
+        timeflow_version: get("timeflow_version").unwrap_or_default().to_string(),
+        role: get("role").map(str::to_string).unwrap_or_else(default_role),
+        sync_marker_hash: get("sync_marker_hash").map(str::to_string),
+        sync_ready: get("sync_ready") == Some("true"),
+        uptime_secs: get("uptime_secs").and_then(|v| v.parse().ok()).unwrap_or(0),
+        sync_protocol: get("sync_protocol").and_then(|v| v.parse().ok()).unwrap_or(0),
+        min_sync_protocol: get("min_sync_protocol").and_then(|v| v.parse().ok()).unwrap_or(0),
+    })
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct DiscoverPacket {
     #[serde(rename = "type")]
@@ -112,6 +194,8 @@ enum InboundPacket {
 pub struct PeerInfo {
     pub device_id: String,
     pub machine_name: String,
+    /// IPv4 albo IPv6; link-local IPv6 ze strefą (`fe80::1%3`) — patrz
+    /// `timeflow_shared::peer_addr`.
     pub ip: String,
     pub dashboard_port: u16,
     pub last_seen: String,
@@ -125,6 +209,12 @@ pub struct PeerInfo {
     /// version-match w LAN sync).
     #[serde(default)]
     pub timeflow_version: String,
+    /// Zakres protokołu sync peera (timeflow_shared::sync_protocol). 0 = peer
+    /// sprzed negocjacji — wtedy sync wymaga identycznej wersji.
+    #[serde(default)]
+    pub sync_protocol: u32,
+    #[serde(default)]
+    pub min_sync_protocol: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -223,7 +313,7 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
         device_id, machine_name, DISCOVERY_PORT
     );
 
-    let socket = {
+    let v4_socket = {
         let mut attempts = 0;
         loop {
             match UdpSocket::bind(format!("0.0.0.0:{}", DISCOVERY_PORT)) {
@@ -243,15 +333,17 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
             }
         }
     };
-    if let Err(e) = socket.set_broadcast(true) {
+    if let Err(e) = v4_socket.set_broadcast(true) {
         log::error!("LAN discovery: failed to set broadcast: {}", e);
         return;
     }
-    if let Err(e) = socket.set_read_timeout(Some(RECV_TIMEOUT)) {
+    if let Err(e) = v4_socket.set_read_timeout(Some(RECV_TIMEOUT)) {
         log::error!("LAN discovery: failed to set read timeout: {}", e);
         return;
     }
 
+    let sockets = DiscoverySockets::open(v4_socket);
+
     // Log broadcast addresses and unicast scan ranges at startup
     let bcast_addrs = get_subnet_broadcast_addresses();
     let local_ifaces = get_local_interfaces();
@@ -272,6 +364,23 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
         log::warn!("LAN discovery: NO real LAN interfaces found for unicast scan");
     }
 
+    // DNS-SD alongside the UDP beacons — multicast DNS gets through networks
+    // that drop broadcast. Optional: discovery keeps working without it.
+    let mdns = match lan_mdns::MdnsDiscovery::start(
+        &device_id,
+        DASHBOARD_PORT_DEFAULT,
+        beacon_txt(&beacon_packet(&device_id, &machine_name, is_dashboard_running(), &version_str, "undecided", 0)),
+    ) {
+        Ok(m) => {
+            log::info!("LAN discovery: mDNS advertising + browsing {}", lan_mdns::SERVICE_TYPE);
+            Some(m)
+        }
+        Err(e) => {
+            log::warn!("LAN discovery: mDNS unavailable ({}) — UDP beacons and HTTP scan only", e);
+            None
+        }
+    };
+
     let mut peers: HashMap<String, PeerInfo> = HashMap::new();
     let mut peers_dirty = false;
     let mut last_peers_write = Instant::now();
@@ -290,8 +399,9 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
             device_id: device_id.clone(),
         }).unwrap_or_default();
         for ip in &previous_ips {
-            let target = format!("{}:{}", ip, DISCOVERY_PORT);
-            let _ = socket.send_to(probe_packet.as_bytes(), &target);
+            if let Ok(target) = peer_addr::socket_addr(ip, DISCOVERY_PORT) {
+                let _ = sockets.send_to(probe_packet.as_bytes(), target);
+            }
         }
     }
     // Clear file after reading — will be repopulated as peers respond
@@ -328,15 +438,15 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
             }
             // Send discover bursts at 0s, 1s, 2s
             if bursts_sent < 3 && election_start.elapsed() >= burst_interval.saturating_mul(bursts_sent) {
-                send_discover(&socket, &device_id);
+                send_discover(&sockets, &device_id);
                 // Also send beacon as undecided so existing master sees us
-                send_beacon(&socket, &device_id, &machine_name, is_dashboard_running(),
+                send_beacon(&sockets, &device_id, &machine_name, is_dashboard_running(),
                     &version_str, "undecided", 0);
                 bursts_sent += 1;
                 log::info!("LAN discovery: election burst {}/3 sent", bursts_sent);
             }
             // Listen for responses
-            match socket.recv_from(&mut election_buf) {
+            match sockets.recv_from(&mut election_buf) {
                 Ok((len, src_addr)) => {
                     if let Ok(text) = std::str::from_utf8(&election_buf[..len]) {
                         if let Ok(InboundPacket::Beacon(beacon)) = serde_json::from_str::<InboundPacket>(text) {
@@ -344,13 +454,15 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
                                 let peer = PeerInfo {
                                     device_id: beacon.device_id.clone(),
                                     machine_name: beacon.machine_name,
-                                    ip: src_addr.ip().to_string(),
+                                    ip: peer_addr::format_peer_ip(&src_addr),
                                     dashboard_port: beacon.dashboard_port,
                                     last_seen: Utc::now().to_rfc3339(),
                                     dashboard_running: beacon.dashboard_running,
                                     role: beacon.role.clone(),
                                     uptime_secs: beacon.uptime_secs,
                                     timeflow_version: beacon.timeflow_version.clone(),
+                                    sync_protocol: beacon.sync_protocol,
+                                    min_sync_protocol: beacon.min_sync_protocol,
                                 };
                                 peers.insert(beacon.device_id.clone(), peer);
                                 peers_dirty = true;
@@ -359,7 +471,7 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
                                     found_master = true;
                                     log::info!(
                                         "LAN discovery: found existing MASTER {} at {} (uptime {}s) — becoming SLAVE",
-                                        beacon.device_id, src_addr.ip(), beacon.uptime_secs
+                                        beacon.device_id, peer_addr::format_peer_ip(&src_addr), beacon.uptime_secs
                                     );
                                     if let Some(ref state) = sync_state {
                                         state.set_role("slave");
@@ -368,7 +480,7 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
                                 } else {
                                     log::info!(
                                         "LAN discovery: found peer {} at {} (role={}, uptime {}s)",
-                                        beacon.device_id, src_addr.ip(), beacon.role, beacon.uptime_secs
+                                        beacon.device_id, peer_addr::format_peer_ip(&src_addr), beacon.role, beacon.uptime_secs
                                     );
                                 }
                             }
@@ -415,7 +527,7 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
         // Announce our role immediately after election
         if let Some(ref state) = sync_state {
             let role = state.get_role();
-            send_beacon(&socket, &device_id, &machine_name, is_dashboard_running(),
+            send_beacon(&sockets, &device_id, &machine_name, is_dashboard_running(),
                 &version_str, &role, started_at.elapsed().as_secs());
             log::info!("LAN discovery: election complete — role={}, peers={}", role, peers.len());
             log::logger().flush();
@@ -425,13 +537,12 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
     // Announce role immediately (forced or elected)
     if let Some(ref state) = sync_state {
         let role = state.get_role();
-        send_beacon(&socket, &device_id, &machine_name, is_dashboard_running(),
+        send_beacon(&sockets, &device_id, &machine_name, is_dashboard_running(),
             &version_str, &role, started_at.elapsed().as_secs());
     }
 
     let mut last_beacon = Instant::now();
     let mut last_http_scan = Instant::now();
-    let mut last_full_scan = Instant::now();
 
     let mut buf = [0u8; 2048];
     let mut sync_handle: Option<JoinHandle<()>> = None;
@@ -543,7 +654,7 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
                 .map(|s| s.get_role())
                 .unwrap_or_else(|| "undecided".to_string());
             send_beacon(
-                &socket,
+                &sockets,
                 &device_id,
                 &machine_name,
                 dashboard_up,
@@ -551,20 +662,44 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
                 &current_role,
                 started_at.elapsed().as_secs(),
             );
+            if let Some(ref m) = mdns {
+                let packet = beacon_packet(&device_id, &machine_name, dashboard_up,
+                    &version_str, &current_role, started_at.elapsed().as_secs());
+                if let Err(e) = m.announce(beacon_txt(&packet)) {
+                    log::warn!("LAN discovery: mDNS re-announce failed: {}", e);
+                }
+            }
             last_beacon = Instant::now();
         }
 
-        // HTTP-based peer scan: health-check known peers (30s) or full subnet scan (300s).
-        // When peers are known, most scans only ping them (1-2 probes instead of 253).
-        let use_health_check = !peers.is_empty() && last_full_scan.elapsed() < Duration::from_secs(300);
-        let http_scan_interval = if peers.is_empty() {
-            Duration::from_secs(30)
-        } else if use_health_check {
-            Duration::from_secs(30)  // health checks are cheap
-        } else {
-            Duration::from_secs(120)
-        };
-        if discovery_active && (first_run || last_http_scan.elapsed() >= http_scan_interval) {
+        // mDNS-resolved peers go through the same path as UDP beacons.
+        if discovery_active {
+            if let Some(ref m) = mdns {
+                for found in m.poll() {
+                    if let Some(beacon) = beacon_from_txt(&found.txt) {
+                        handle_beacon(
+                            beacon,
+                            &found.ip,
+                            &device_id,
+                            &mut peers,
+                            &mut peers_dirty,
+                            &sync_state,
+                            started_at.elapsed().as_secs(),
+                            role_is_forced,
+                        );
+                    }
+                }
+            }
+        }
+
+        // HTTP-based peer scan: health-check known peers every 30s. The full
+        // subnet scan (253 probes) is the last resort — only while no peer is
+        // known, and only after mDNS/broadcast had SUBNET_SCAN_GRACE to answer.
+        let use_health_check = !peers.is_empty();
+        let http_scan_interval = Duration::from_secs(30);
+        let full_scan_allowed = mdns.is_none() || started_at.elapsed() >= SUBNET_SCAN_GRACE;
+        let scan_due = first_run || last_http_scan.elapsed() >= http_scan_interval;
+        if discovery_active && scan_due && (use_health_check || full_scan_allowed) {
             last_http_scan = Instant::now();
 
             let found = if use_health_check {
@@ -573,8 +708,7 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
                 log::debug!("LAN discovery: health-check {} known peer(s)", known_ips.len());
                 http_ping_known_peers(&device_id, &known_ips)
             } else {
-                // Full subnet scan
-                last_full_scan = Instant::now();
+                // Full subnet scan — nothing else found a peer
                 http_scan_subnet(&device_id)
             };
 
@@ -604,16 +738,16 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
         }
 
         // Receive packets (non-blocking with 1s timeout)
-        match socket.recv_from(&mut buf) {
+        match sockets.recv_from(&mut buf) {
             Ok((len, src_addr)) => {
                 if let Ok(text) = std::str::from_utf8(&buf[..len]) {
                     handle_packet(
                         text,
-                        &src_addr.ip().to_string(),
+                        &peer_addr::format_peer_ip(&src_addr),
                         &device_id,
                         &machine_name,
                         &version_str,
-                        &socket,
+                        &sockets,
                         &mut peers,
                         &mut peers_dirty,
                         &sync_state,
@@ -685,6 +819,7 @@ fn run_discovery_loop(stop_signal: Arc<AtomicBool>, sync_state: Option<Arc<LanSy
 /// Compute subnet broadcast addresses from local network interfaces.
 /// Uses `ipconfig` on Windows to get real IP + subnet mask, then calculates
 /// the correct broadcast address (IP | ~mask). Falls back to /24 heuristic.
+/// IPv4 only — IPv6 peers are reached via DISCOVERY_GROUP_V6 multicast instead.
 fn get_subnet_broadcast_addresses() -> Vec<String> {
     let mut addrs = Vec::new();
 
@@ -857,6 +992,107 @@ fn get_local_interfaces() -> Vec<LocalInterface> {
     interfaces
 }
 
+/// The IPv4 broadcast socket plus, where available, a v6-only socket on the
+/// same port joined to DISCOVERY_GROUP_V6 on every IPv6 interface.
+struct DiscoverySockets {
+    v4: UdpSocket,
+    v6: Option<UdpSocket>,
+    /// Interface indexes the v6 socket joined; beacons go out on each of them.
+    v6_interfaces: Vec<u32>,
+}
+
+impl DiscoverySockets {
+    /// IPv6 is optional — without it discovery behaves exactly as before.
+    fn open(v4: UdpSocket) -> Self {
+        let interfaces = ipv6_interfaces();
+        let v6 = match open_ipv6_socket(&interfaces) {
+            Ok(socket) => {
+                log::info!(
+                    "LAN discovery: IPv6 multicast [{}]:{} on interface(s) {:?}",
+                    DISCOVERY_GROUP_V6, DISCOVERY_PORT, interfaces
+                );
+                Some(socket)
+            }
+            Err(e) => {
+                log::warn!("LAN discovery: IPv6 discovery unavailable: {}", e);
+                None
+            }
+        };
+        Self { v4, v6, v6_interfaces: interfaces }
+    }
+
+    fn broadcast(&self, data: &[u8]) {
+        broadcast_to_all(&self.v4, data);
+        if let Some(ref v6) = self.v6 {
+            for &index in &self.v6_interfaces {
+                let target = SocketAddrV6::new(DISCOVERY_GROUP_V6, DISCOVERY_PORT, 0, index);
+                if let Err(e) = v6.send_to(data, target) {
+                    log::warn!("LAN discovery: failed to send to {}: {}", target, e);
+                }
+            }
+        }
+    }
+
+    /// Unicast on the socket matching the target's family.
+    fn send_to(&self, data: &[u8], target: SocketAddr) -> std::io::Result<usize> {
+        match (target, &self.v6) {
+            (SocketAddr::V4(_), _) => self.v4.send_to(data, target),
+            (SocketAddr::V6(_), Some(v6)) => v6.send_to(data, target),
+            (SocketAddr::V6(_), None) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "IPv6 discovery socket not available",
+            )),
+        }
+    }
+
+    /// Drains the (non-blocking) IPv6 socket first, then waits on IPv4 up to RECV_TIMEOUT.
+    fn recv_from(&self, buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)> {
+        if let Some(ref v6) = self.v6 {
+            match v6.recv_from(buf) {
+                Ok(r) => return Ok(r),
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(e) => log::debug!("LAN discovery: IPv6 recv error: {}", e),
+            }
+        }
+        self.v4.recv_from(buf)
+    }
+}
+
+/// Indexes of non-loopback interfaces with an IPv6 address.
+fn ipv6_interfaces() -> Vec<u32> {
+    let mut indexes: Vec<u32> = if_addrs::get_if_addrs()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|iface| !iface.is_loopback() && iface.ip().is_ipv6())
+        .filter_map(|iface| iface.index)
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes
+}
+
+fn open_ipv6_socket(interfaces: &[u32]) -> Result<UdpSocket, String> {
+    if interfaces.is_empty() {
+        return Err("no IPv6 interfaces".to_string());
+    }
+    let bind = SocketAddr::from((Ipv6Addr::UNSPECIFIED, DISCOVERY_PORT));
+    let socket: UdpSocket = lan_common::bind_ipv6_only(bind, socket2::Type::DGRAM)
+        .map_err(|e| format!("bind {}: {}", bind, e))?
+        .into();
+    let mut joined = 0;
+    for &index in interfaces {
+        match socket.join_multicast_v6(&DISCOVERY_GROUP_V6, index) {
+            Ok(()) => joined += 1,
+            Err(e) => log::warn!("LAN discovery: join {} on interface {} failed: {}", DISCOVERY_GROUP_V6, index, e),
+        }
+    }
+    if joined == 0 {
+        return Err(format!("could not join {} on any interface", DISCOVERY_GROUP_V6));
+    }
+    socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+    Ok(socket)
+}
+
 /// Send data to broadcast addresses + unicast scan of local subnets.
 /// Unicast scan is the primary mechanism — broadcast is unreliable on Windows
 /// with multiple network interfaces (Hyper-V, WSL, VPN adapters).
@@ -908,7 +1144,7 @@ fn broadcast_to_all(socket: &UdpSocket, data: &[u8]) {
 }
 
 fn send_beacon(
-    socket: &UdpSocket,
+    sockets: &DiscoverySockets,
     device_id: &str,
     machine_name: &str,
     dashboard_running: bool,
@@ -916,27 +1152,15 @@ fn send_beacon(
     role: &str,
     uptime_secs: u64,
 ) {
-    let packet = BeaconPacket {
-        packet_type: "timeflow_beacon".to_string(),
-        version: PROTOCOL_VERSION,
-        device_id: device_id.to_string(),
-        machine_name: machine_name.to_string(),
-        dashboard_port: DASHBOARD_PORT_DEFAULT,
-        dashboard_running,
-        timeflow_version: version.to_string(),
-        role: role.to_string(),
-        sync_marker_hash: None,
-        sync_ready: true,
-        uptime_secs,
-    };
+    let packet = beacon_packet(device_id, machine_name, dashboard_running, version, role, uptime_secs);
     if let Ok(json) = serde_json::to_string(&packet) {
-        broadcast_to_all(socket, json.as_bytes());
+        sockets.broadcast(json.as_bytes());
     }
 }
 
 /// Send beacon directly to a specific IP (unicast) — used for discover responses.
 fn send_beacon_to(
-    socket: &UdpSocket,
+    sockets: &DiscoverySockets,
     device_id: &str,
     machine_name: &str,
     dashboard_running: bool,
@@ -945,35 +1169,29 @@ fn send_beacon_to(
     uptime_secs: u64,
     target_ip: &str,
 ) {
-    let packet = BeaconPacket {
-        packet_type: "timeflow_beacon".to_string(),
-        version: PROTOCOL_VERSION,
-        device_id: device_id.to_string(),
-        machine_name: machine_name.to_string(),
-        dashboard_port: DASHBOARD_PORT_DEFAULT,
-        dashboard_running,
-        timeflow_version: version.to_string(),
-        role: role.to_string(),
-        sync_marker_hash: None,
-        sync_ready: true,
-        uptime_secs,
-    };
+    let packet = beacon_packet(device_id, machine_name, dashboard_running, version, role, uptime_secs);
     if let Ok(json) = serde_json::to_string(&packet) {
-        let target = format!("{}:{}", target_ip, DISCOVERY_PORT);
-        if let Err(e) = socket.send_to(json.as_bytes(), &target) {
+        let target = match peer_addr::socket_addr(target_ip, DISCOVERY_PORT) {
+            Ok(t) => t,
+            Err(e) => {
+                log::warn!("LAN discovery: cannot reply to {}: {}", target_ip, e);
+                return;
+            }
+        };
+        if let Err(e) = sockets.send_to(json.as_bytes(), target) {
             log::warn!("LAN discovery: failed to send beacon to {}: {}", target, e);
         }
     }
 }
 
-fn send_discover(socket: &UdpSocket, device_id: &str) {
+fn send_discover(sockets: &DiscoverySockets, device_id: &str) {
     let packet = DiscoverPacket {
         packet_type: "timeflow_discover".to_string(),
         version: PROTOCOL_VERSION,
         device_id: device_id.to_string(),
     };
     if let Ok(json) = serde_json::to_string(&packet) {
-        broadcast_to_all(socket, json.as_bytes());
+        sockets.broadcast(json.as_bytes());
     }
 }
 
@@ -983,7 +1201,7 @@ fn handle_packet(
     my_device_id: &str,
     my_machine_name: &str,
     my_version: &str,
-    socket: &UdpSocket,
+    sockets: &DiscoverySockets,
     peers: &mut HashMap<String, PeerInfo>,
     dirty: &mut bool,
     sync_state: &Option<Arc<LanSyncState>>,
@@ -997,83 +1215,16 @@ fn handle_packet(
 
     match packet {
         InboundPacket::Beacon(beacon) => {
-            if beacon.device_id == my_device_id {
-                return;
-            }
-            let is_new = !peers.contains_key(&beacon.device_id);
-            let peer = PeerInfo {
-                device_id: beacon.device_id.clone(),
-                machine_name: beacon.machine_name,
-                ip: src_ip.to_string(),
-                dashboard_port: beacon.dashboard_port,
-                last_seen: Utc::now().to_rfc3339(),
-                dashboard_running: beacon.dashboard_running,
-                role: beacon.role.clone(),
-                uptime_secs: beacon.uptime_secs,
-                timeflow_version: beacon.timeflow_version.clone(),
-            };
-            peers.insert(beacon.device_id.clone(), peer);
-            *dirty = true;
-
-            // Role assignment logic (including master-master conflict resolution)
-            // Skipped when role is forced by user in settings.
-            if role_is_forced {
-                // Forced role — no automatic changes
-            } else if let Some(ref state) = sync_state {
-                let my_role = state.get_role();
-                // Cap both uptimes to prevent spoofing from dominating election
-                let my_up = my_uptime_secs.min(MAX_UPTIME_SECS);
-                let peer_up = beacon.uptime_secs.min(MAX_UPTIME_SECS);
-                let i_win_election = my_up > peer_up
-                    || (my_up == peer_up && my_device_id < beacon.device_id.as_str());
-
-                match (my_role.as_str(), beacon.role.as_str()) {
-                    ("undecided", "master") => {
-                        state.set_role("slave");
-                        log::info!(
-                            "LAN discovery: peer {} is MASTER (uptime {}s) — assuming SLAVE role",
-                            beacon.device_id, beacon.uptime_secs
-                        );
-                    }
-                    ("undecided", "undecided") | ("undecided", "slave") => {
-                        if i_win_election {
-                            state.set_role("master");
-                            log::info!(
-                                "LAN discovery: election with {} — assuming MASTER (my uptime {}s > peer {}s)",
-                                beacon.device_id, my_uptime_secs, beacon.uptime_secs
-                            );
-                        } else {
-                            state.set_role("slave");
-                            log::info!(
-                                "LAN discovery: election with {} — assuming SLAVE (my uptime {}s <= peer {}s)",
-                                beacon.device_id, my_uptime_secs, beacon.uptime_secs
-                            );
-                        }
-                    }
-                    ("master", "master") => {
-                        // Conflict! Two masters — longer uptime keeps master
-                        if i_win_election {
-                            log::info!(
-                                "LAN discovery: MASTER-MASTER conflict with {} — keeping MASTER (my uptime {}s > peer {}s)",
-                                beacon.device_id, my_uptime_secs, beacon.uptime_secs
-                            );
-                        } else {
-                            state.set_role("slave");
-                            log::info!(
-                                "LAN discovery: MASTER-MASTER conflict with {} — yielding to SLAVE (my uptime {}s <= peer {}s)",
-                                beacon.device_id, my_uptime_secs, beacon.uptime_secs
-                            );
-                        }
-                    }
-                    _ => {}
-                }
-            }
-
-            if is_new {
-                log::info!("LAN discovery: new peer found at {} (role={})", src_ip, beacon.role);
-                write_peers_file(peers);
-                *dirty = false;
-            }
+            handle_beacon(
+                beacon,
+                src_ip,
+                my_device_id,
+                peers,
+                dirty,
+                sync_state,
+                my_uptime_secs,
+                role_is_forced,
+            );
         }
         InboundPacket::Discover(discover) => {
             if discover.device_id == my_device_id {
@@ -1084,7 +1235,7 @@ fn handle_packet(
                 .unwrap_or_else(|| "undecided".to_string());
             // Respond directly to the requester's IP (unicast) — broadcast may be blocked
             send_beacon_to(
-                socket,
+                sockets,
                 my_device_id,
                 my_machine_name,
                 is_dashboard_running(),
@@ -1097,11 +1248,105 @@ fn handle_packet(
     }
 }
 
+/// Merge a peer announcement (UDP beacon or mDNS TXT record) into the peer map
+/// and run role election against it.
+#[allow(clippy::too_many_arguments)]
+fn handle_beacon(
+    beacon: BeaconPacket,
+    src_ip: &str,
+    my_device_id: &str,
+    peers: &mut HashMap<String, PeerInfo>,
+    dirty: &mut bool,
+    sync_state: &Option<Arc<LanSyncState>>,
+    my_uptime_secs: u64,
+    role_is_forced: bool,
+) {
+    if beacon.device_id == my_device_id {
+        return;
+    }
+    let is_new = !peers.contains_key(&beacon.device_id);
+    let peer = PeerInfo {
+        device_id: beacon.device_id.clone(),
+        machine_name: beacon.machine_name,
+        ip: src_ip.to_string(),
+        dashboard_port: beacon.dashboard_port,
+        last_seen: Utc::now().to_rfc3339(),
+        dashboard_running: beacon.dashboard_running,
+        role: beacon.role.clone(),
+        uptime_secs: beacon.uptime_secs,
+        timeflow_version: beacon.timeflow_version.clone(),
+        sync_protocol: beacon.sync_protocol,
+        min_sync_protocol: beacon.min_sync_protocol,
+    };
+    peers.insert(beacon.device_id.clone(), peer);
+    *dirty = true;
+
+    // Role assignment logic (including master-master conflict resolution)
+    // Skipped when role is forced by user in settings.
+    if role_is_forced {
+        // Forced role — no automatic changes
+    } else if let Some(ref state) = sync_state {
+        let my_role = state.get_role();
+        // Cap both uptimes to prevent spoofing from dominating election
+        let my_up = my_uptime_secs.min(MAX_UPTIME_SECS);
+        let peer_up = beacon.uptime_secs.min(MAX_UPTIME_SECS);
+        let i_win_election = my_up > peer_up
+            || (my_up == peer_up && my_device_id < beacon.device_id.as_str());
+
+        match (my_role.as_str(), beacon.role.as_str()) {
+            ("undecided", "master") => {
+                state.set_role("slave");
+                log::info!(
+                    "LAN discovery: peer {} is MASTER (uptime {}s) — assuming SLAVE role",
+                    beacon.device_id, beacon.uptime_secs
+                );
+            }
+            ("undecided", "undecided") | ("undecided", "slave") => {
+                if i_win_election {
+                    state.set_role("master");
+                    log::info!(
+                        "LAN discovery: election with {} — assuming MASTER (my uptime {}s > peer {}s)",
+                        beacon.device_id, my_uptime_secs, beacon.uptime_secs
+                    );
+                } else {
+                    state.set_role("slave");
+                    log::info!(
+                        "LAN discovery: election with {} — assuming SLAVE (my uptime {}s <= peer {}s)",
+                        beacon.device_id, my_uptime_secs, beacon.uptime_secs
+                    );
+                }
+            }
+            ("master", "master") => {
+                // Conflict! Two masters — longer uptime keeps master
+                if i_win_election {
+                    log::info!(
+                        "LAN discovery: MASTER-MASTER conflict with {} — keeping MASTER (my uptime {}s > peer {}s)",
+                        beacon.device_id, my_uptime_secs, beacon.uptime_secs
+                    );
+                } else {
+                    state.set_role("slave");
+                    log::info!(
+                        "LAN discovery: MASTER-MASTER conflict with {} — yielding to SLAVE (my uptime {}s <= peer {}s)",
+                        beacon.device_id, my_uptime_secs, beacon.uptime_secs
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if is_new {
+        log::info!("LAN discovery: new peer found at {} (role={})", src_ip, beacon.role);
+        write_peers_file(peers);
+        *dirty = false;
+    }
+}
+
 /// Ping a single IP on the LAN server port. Returns (device_id, PeerInfo) if a peer responds.
 fn http_ping_one(ip: String, my_device_id: &str) -> Option<(String, PeerInfo)> {
-    let addr = format!("{}:{}", ip, DASHBOARD_PORT_DEFAULT);
+    let addr = peer_addr::host_port(&ip, DASHBOARD_PORT_DEFAULT);
     let stream = std::net::TcpStream::connect_timeout(
-        &addr.parse().ok()?,
+        &peer_addr::socket_addr(&ip, DASHBOARD_PORT_DEFAULT).ok()?,
         Duration::from_millis(800),
     ).ok()?;
     let _ = stream.set_read_timeout(Some(Duration::from_millis(800)));
@@ -1130,6 +1375,7 @@ fn http_ping_one(ip: String, my_device_id: &str) -> Option<(String, PeerInfo)> {
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
+    let protocol = ProtocolInfo::from_json(&parsed).unwrap_or_default();
 
     Some((
         device_id.clone(),
@@ -1143,6 +1389,8 @@ fn http_ping_one(ip: String, my_device_id: &str) -> Option<(String, PeerInfo)> {
             role,
             uptime_secs: 0,
             timeflow_version,
+            sync_protocol: protocol.sync_protocol,
+            min_sync_protocol: protocol.min_sync_protocol,
         },
     ))
 }
@@ -1242,19 +1490,52 @@ fn http_scan_subnet(my_device_id: &str) -> HashMap<String, PeerInfo> {
 
 /// Read lan_peers.json and return the first active peer with dashboard running.
 pub fn find_first_peer() -> Option<lan_sync_orchestrator::PeerTarget> {
-    let path = peers_file_path()?;
-    let content = std::fs::read_to_string(&path).ok()?;
-    let file: PeersFile = serde_json::from_str(&content).ok()?;
+    active_peers().into_iter().next()
+}
 
-    for p in file.peers {
-        if !p.dashboard_running {
-            continue;
-        }
-        return Some(lan_sync_orchestrator::PeerTarget {
+/// Every peer from lan_peers.json with dashboard running.
+pub fn active_peers() -> Vec<lan_sync_orchestrator::PeerTarget> {
+    let file: Option<PeersFile> = peers_file_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok());
+    file.map(|f| f.peers)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| p.dashboard_running)
+        .map(|p| lan_sync_orchestrator::PeerTarget {
             ip: p.ip,
             port: p.dashboard_port,
             device_id: p.device_id,
-        });
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beacon_survives_txt_roundtrip() {
+        let mut packet = beacon_packet("dev-1", "Studio", true, "0.2.0", "master", 4200);
+        packet.sync_marker_hash = Some("abc123".to_string());
+        let parsed = beacon_from_txt(&beacon_txt(&packet)).expect("valid TXT record");
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&packet).unwrap()
+        );
+    }
+
+    #[test]
+    fn txt_without_identity_is_ignored_and_optional_fields_default() {
+        let mut txt = beacon_txt(&beacon_packet("dev-1", "Studio", false, "0.2.0", "slave", 1));
+        for key in ["role", "uptime_secs", "machine_name"] {
+            txt.remove(key);
+        }
+        let parsed = beacon_from_txt(&txt).expect("optional fields may be missing");
+        assert_eq!(parsed.role, "undecided");
+        assert_eq!(parsed.uptime_secs, 0);
+
+        txt.remove("device_id");
+        assert!(beacon_from_txt(&txt).is_none());
     }
-    None
 }
@@ -0,0 +1,105 @@
+// LAN mDNS — DNS-SD advertisement and browsing of the `_timeflow._tcp` service.
+// Runs next to the UDP beacons in lan_discovery: managed Wi-Fi often drops
+// broadcast but still forwards multicast DNS. The TXT record carries the same
+// fields as a beacon (see lan_discovery::beacon_txt).
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use timeflow_shared::peer_addr;
+
+pub const SERVICE_TYPE: &str = "_timeflow._tcp.local.";
+
+/// A resolved `_timeflow._tcp` instance: the address to reach it on and its TXT record.
+pub struct MdnsAnnouncement {
+    pub ip: String,
+    pub txt: HashMap<String, String>,
+}
+
+pub struct MdnsDiscovery {
+    daemon: ServiceDaemon,
+    browse: mdns_sd::Receiver<ServiceEvent>,
+    instance: String,
+    host: String,
+    port: u16,
+}
+
+impl MdnsDiscovery {
+    /// Start the mDNS responder, register our instance and browse for peers.
+    pub fn start(instance: &str, port: u16, txt: HashMap<String, String>) -> Result<Self, String> {
+        let daemon = ServiceDaemon::new().map_err(|e| format!("mDNS daemon: {}", e))?;
+        let browse = daemon
+            .browse(SERVICE_TYPE)
+            .map_err(|e| format!("mDNS browse: {}", e))?;
+        let short: String = instance
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .take(12)
+            .collect();
+        let discovery = Self {
+            daemon,
+            browse,
+            instance: instance.to_string(),
+            host: format!("timeflow-{}.local.", short.to_ascii_lowercase()),
+            port,
+        };
+        discovery.announce(txt)?;
+        Ok(discovery)
+    }
+
+    /// (Re-)register our instance with a fresh TXT record. Same fullname, so
+    /// browsers on the network see it as an update of the existing instance.
+    pub fn announce(&self, txt: HashMap<String, String>) -> Result<(), String> {
+        let info = ServiceInfo::new(SERVICE_TYPE, &self.instance, &self.host, "", self.port, txt)
+            .map_err(|e| format!("mDNS service info: {}", e))?
+            .enable_addr_auto();
+        self.daemon
+            .register(info)
+            .map_err(|e| format!("mDNS register: {}", e))
+    }
+
+    /// Drain pending browse events without blocking; returns resolved instances.
+    pub fn poll(&self) -> Vec<MdnsAnnouncement> {
+        let mut found = Vec::new();
+        while let Ok(event) = self.browse.try_recv() {
+            if let ServiceEvent::ServiceResolved(info) = event {
+                let Some(ip) = pick_address(info.get_addresses().iter()) else {
+                    continue;
+                };
+                found.push(MdnsAnnouncement {
+                    ip: ip.to_string(),
+                    txt: info.get_properties().clone().into_property_map_str(),
+                });
+            }
+        }
+        found
+    }
+
+    fn fullname(&self) -> String {
+        format!("{}.{}", self.instance, SERVICE_TYPE)
+    }
+}
+
+impl Drop for MdnsDiscovery {
+    fn drop(&mut self) {
+        // Goodbye packets let peers drop us right away instead of waiting for the TTL.
+        let _ = self.daemon.unregister(&self.fullname());
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Prefer a routable IPv4 address; on IPv6-only networks fall back to a
+/// non-link-local IPv6 one (mDNS does not tell us the interface, so a bare
+/// `fe80::` address would be unusable without its zone).
+fn pick_address<'a>(addrs: impl Iterator<Item = &'a IpAddr>) -> Option<IpAddr> {
+    let mut usable: Vec<IpAddr> = addrs
+        .filter(|ip| match ip {
+            IpAddr::V4(v4) => !v4.is_loopback() && !v4.is_link_local(),
+            IpAddr::V6(v6) => !v6.is_loopback() && !peer_addr::is_ipv6_link_local(v6),
+        })
+        .copied()
+        .collect();
+    // IPv4 sorts before IPv6.
+    usable.sort();
+    usable.into_iter().next()
+}
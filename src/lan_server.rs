@@ -5,14 +5,16 @@
 
 use crate::config;
 use crate::lan_common::{self, sync_log};
+use crate::sync_history::RunStatus;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::io::{BufRead, BufReader, Read, Write};
-use std::net::{IpAddr, Ipv4Addr, TcpListener};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, OnceLock};
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use timeflow_shared::sync_protocol::{ProtocolInfo, SyncFeatures};
 
 const DEFAULT_LAN_PORT: u16 = 47891;
 const MAX_REQUEST_BODY: usize = 50 * 1024 * 1024; // 50MB
@@ -55,6 +57,8 @@ struct PingResponse {
     machine_name: String,
     role: String,
     sync_marker_hash: Option<String>,
+    #[serde(flatten)]
+    protocol: ProtocolInfo,
 }
 
 #[derive(Serialize)]
@@ -152,6 +156,10 @@ pub struct LanSyncState {
     /// unikalny per sync (hash z tables_hash + sekunda UTC + device_id), więc stary
     /// wpis nigdy nie pasuje do nowego db-ready.
     pub last_db_ready: std::sync::Mutex<Option<(String, String)>>,
+    /// Wynik ostatniego dry-run (LAN lub online) — odczytywany przez `/lan/sync-preview`.
+    pub last_sync_preview: std::sync::Mutex<Option<crate::sync_preview::SyncPreviewReport>>,
+    /// Trwający przebieg synchronizacji — zapisywany do `sync_runs` po zakończeniu.
+    pub current_run: std::sync::Mutex<Option<crate::sync_history::SyncRun>>,
 }
 
 /// Guard that resets sync_in_progress to false on drop (panic-safe).
@@ -184,6 +192,8 @@ impl LanSyncState {
             consecutive_sync_failures: AtomicU32::new(0),
             sync_backoff_until: AtomicU64::new(0),
             last_db_ready: std::sync::Mutex::new(None),
+            last_sync_preview: std::sync::Mutex::new(None),
+            current_run: std::sync::Mutex::new(None),
         }
     }
 
@@ -203,6 +213,68 @@ impl LanSyncState {
         *guard = Some((master_marker.to_string(), own_marker.to_string()));
     }
 
+    /// Mark a dry-run as started — replaces the previous report.
+    pub fn begin_sync_preview(&self, sync_type: &str, peer: &str) {
+        let mut guard = self.last_sync_preview.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = Some(crate::sync_preview::SyncPreviewReport::running(sync_type, peer));
+    }
+
+    /// Store the dry-run outcome in the report opened by `begin_sync_preview`.
+    pub fn finish_sync_preview(&self, result: Result<crate::sync_preview::SyncPreview, String>) {
+        let mut guard = self.last_sync_preview.lock().unwrap_or_else(|e| e.into_inner());
+        guard.get_or_insert_with(Default::default).finish(result);
+    }
+
+    pub fn sync_preview(&self) -> Option<crate::sync_preview::SyncPreviewReport> {
+        self.last_sync_preview.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Open a sync run record. A run still open at this point never finished
+    /// cleanly — it is recorded as aborted first.
+    pub fn begin_sync_run(&self, kind: &str, role: &str, peer_id: &str, dry_run: bool) {
+        let previous = {
+            let mut guard = self.current_run.lock().unwrap_or_else(|e| e.into_inner());
+            guard.replace(crate::sync_history::SyncRun::new(kind, role, peer_id, dry_run))
+        };
+        if let Some(run) = previous {
+            crate::sync_history::record(
+                &run,
+                RunStatus::Aborted,
+                Some("superseded by a new sync run"),
+            );
+        }
+    }
+
+    /// Update the open run (no-op when none is open).
+    pub fn note_sync_run(&self, update: impl FnOnce(&mut crate::sync_history::SyncRun)) {
+        let mut guard = self.current_run.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(run) = guard.as_mut() {
+            update(run);
+        }
+    }
+
+    /// Close the open run and persist it to `sync_runs`.
+    pub fn finish_sync_run(&self, status: RunStatus, error: Option<&str>) {
+        let run = self.current_run.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(run) = run {
+            crate::sync_history::record(&run, status, error);
+        }
+    }
+
+    /// Slave side: close the run at unfreeze. A failed db-ready may still be
+    /// retried by the master, so its error only decides the status here.
+    pub fn finish_slave_sync_run(&self) {
+        let run = self.current_run.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let Some(run) = run else { return };
+        if run.imported || run.dry_run {
+            crate::sync_history::record(&run, RunStatus::Ok, None);
+        } else if let Some(error) = run.error.clone() {
+            crate::sync_history::record(&run, RunStatus::Error, Some(&error));
+        } else {
+            crate::sync_history::record(&run, RunStatus::Aborted, Some("unfrozen before import"));
+        }
+    }
+
     /// Record that a sync just completed (for cooldown logic).
     pub fn mark_sync_completed(&self) {
         let now = SystemTime::now()
@@ -278,6 +350,11 @@ impl LanSyncState {
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_millis() as u64)
             .unwrap_or(0);
+        drop(guard);
+        self.note_sync_run(|run| {
+            run.step_reached = run.step_reached.max(step);
+            run.phase = phase.to_string();
+        });
     }
 
     /// Update transfer byte counters (called during upload/download).
@@ -339,6 +416,10 @@ impl LanSyncState {
             *guard = None;
             drop(guard);
 
+            self.finish_sync_run(
+                RunStatus::Aborted,
+                Some("auto-unfreeze after timeout"),
+            );
             if can_clear_sync_lock {
                 self.sync_in_progress.store(false, Ordering::SeqCst);
                 self.reset_progress();
@@ -379,24 +460,52 @@ pub fn start(stop_signal: Arc<AtomicBool>, sync_state: Arc<LanSyncState>) -> Joi
     })
 }
 
-// LAN server listens on 0.0.0.0. Mutating endpoints require X-Auth-Secret header
+// LAN server listens on 0.0.0.0 and [::]. Mutating endpoints require X-Auth-Secret header
 // matching the shared secret from lan_secret.txt. Read-only + pairing endpoints are open.
 fn run_server(stop_signal: Arc<AtomicBool>, sync_state: Arc<LanSyncState>) {
-    let listener = match TcpListener::bind(format!("0.0.0.0:{}", DEFAULT_LAN_PORT)) {
-        Ok(s) => {
-            log::info!("LAN server: listening on port {}", DEFAULT_LAN_PORT);
-            s
+    let mut listeners = Vec::new();
+    for addr in [
+        SocketAddr::from((Ipv4Addr::UNSPECIFIED, DEFAULT_LAN_PORT)),
+        SocketAddr::from((Ipv6Addr::UNSPECIFIED, DEFAULT_LAN_PORT)),
+    ] {
+        match bind_listener(addr) {
+            Ok(l) => {
+                log::info!("LAN server: listening on {}", addr);
+                listeners.push(l);
+            }
+            Err(e) => log::warn!("LAN server: failed to bind {}: {}", addr, e),
         }
-        Err(e) => {
-            log::error!("LAN server: failed to bind port {}: {}", DEFAULT_LAN_PORT, e);
-            return;
+    }
+    if listeners.is_empty() {
+        log::error!("LAN server: failed to bind port {} on any address family", DEFAULT_LAN_PORT);
+        return;
+    }
+    serve(listeners, stop_signal, sync_state);
+}
+
+/// IPv6 gets its own v6-only socket, so it never collides with the IPv4 one.
+pub(crate) fn bind_listener(addr: SocketAddr) -> std::io::Result<TcpListener> {
+    match addr {
+        SocketAddr::V4(_) => TcpListener::bind(addr),
+        SocketAddr::V6(_) => {
+            let socket = lan_common::bind_ipv6_only(addr, socket2::Type::STREAM)?;
+            socket.listen(128)?;
+            Ok(socket.into())
         }
-    };
+    }
+}
 
+pub(crate) fn serve(
+    listeners: Vec<TcpListener>,
+    stop_signal: Arc<AtomicBool>,
+    sync_state: Arc<LanSyncState>,
+) {
     // Non-blocking accept with short sleep to check stop_signal periodically.
-    listener
-        .set_nonblocking(true)
-        .unwrap_or_else(|e| log::warn!("LAN server: set_nonblocking failed: {}", e));
+    for listener in &listeners {
+        listener
+            .set_nonblocking(true)
+            .unwrap_or_else(|e| log::warn!("LAN server: set_nonblocking failed: {}", e));
+    }
 
     let active_connections = Arc::new(AtomicUsize::new(0));
     let mut last_unfreeze_check = std::time::Instant::now();
@@ -412,36 +521,42 @@ fn run_server(stop_signal: Arc<AtomicBool>, sync_state: Arc<LanSyncState>) {
             last_unfreeze_check = std::time::Instant::now();
         }
 
-        match listener.accept() {
-            Ok((stream, addr)) => {
-                let conn_count = active_connections.clone();
-                if conn_count.load(Ordering::Relaxed) >= MAX_CONNECTIONS {
-                    log::warn!(
-                        "LAN server: max connections ({}) reached, dropping {}",
-                        MAX_CONNECTIONS,
-                        addr
-                    );
-                    drop(stream);
-                    continue;
-                }
-                conn_count.fetch_add(1, Ordering::Relaxed);
-                let state = sync_state.clone();
-                let stop = stop_signal.clone();
-                thread::spawn(move || {
-                    let _decrement = ConnectionGuard(conn_count);
-                    if let Err(e) = handle_connection(stream, state, stop) {
-                        log::debug!("LAN server: connection error from {}: {}", addr, e);
+        let mut accepted = false;
+        for listener in &listeners {
+            match listener.accept() {
+                Ok((stream, addr)) => {
+                    accepted = true;
+                    let conn_count = active_connections.clone();
+                    if conn_count.load(Ordering::Relaxed) >= MAX_CONNECTIONS {
+                        log::warn!(
+                            "LAN server: max connections ({}) reached, dropping {}",
+                            MAX_CONNECTIONS,
+                            addr
+                        );
+                        drop(stream);
+                        continue;
                     }
-                });
-            }
-            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                thread::sleep(Duration::from_millis(100));
-            }
-            Err(e) => {
-                log::warn!("LAN server: accept error: {}", e);
-                thread::sleep(Duration::from_millis(100));
+                    conn_count.fetch_add(1, Ordering::Relaxed);
+                    let state = sync_state.clone();
+                    let stop = stop_signal.clone();
+                    let data_dir = config::data_dir_override();
+                    thread::spawn(move || {
+                        config::set_data_dir_override(data_dir);
+                        let _decrement = ConnectionGuard(conn_count);
+                        if let Err(e) = handle_connection(stream, state, stop) {
+                            log::debug!("LAN server: connection error from {}: {}", addr, e);
+                        }
+                    });
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(e) => {
+                    log::warn!("LAN server: accept error: {}", e);
+                }
             }
         }
+        if !accepted {
+            thread::sleep(Duration::from_millis(100));
+        }
     }
 
     log::info!("LAN server: stopped");
@@ -514,6 +629,7 @@ fn handle_connection(
         | "/lan/store-paired-device" | "/lan/remove-paired-device"
         | "/lan/local-identity" | "/lan/initiate-pair"
         | "/lan/trigger-sync" | "/online/trigger-sync" | "/online/cancel-sync"
+        | "/online/change-passphrase" | "/online/rotate-key" | "/lan/sync-preview"
     );
     if requires_auth {
         let expected = get_or_create_lan_secret();
@@ -566,7 +682,7 @@ fn handle_connection(
         ("POST", "/lan/upload-db") => handle_upload_db(&state, &body),
         ("POST", "/lan/upload-ack") => (200, json_ok()),
         ("POST", "/lan/db-ready") => handle_db_ready(&state, &body),
-        ("POST", "/lan/unfreeze") => handle_unfreeze(&state),
+        ("POST", "/lan/unfreeze") => handle_unfreeze(&state, &body),
         ("POST", "/lan/pair") => handle_pair(&body, client_ip),
         ("POST", "/lan/initiate-pair") => handle_initiate_pair(&body, client_ip),
         ("POST", "/lan/generate-pairing-code") => handle_generate_pairing_code(),
@@ -575,9 +691,13 @@ fn handle_connection(
         ("GET", "/lan/paired-devices") => handle_get_paired_devices(),
         ("GET", "/lan/local-identity") => handle_local_identity(),
         ("POST", "/lan/trigger-sync") => handle_trigger_sync(&state, &stop_signal, &body, client_ip),
+        ("GET", "/lan/sync-preview") => handle_sync_preview(&state, client_ip),
         // Online sync endpoints
-        ("POST", "/online/trigger-sync") => handle_online_trigger_sync(&state, &stop_signal, client_ip),
+        ("POST", "/online/trigger-sync") => handle_online_trigger_sync(&state, &stop_signal, &body, client_ip),
         ("POST", "/online/cancel-sync") => handle_online_cancel_sync(&state, client_ip),
+        ("POST", "/online/change-passphrase") => handle_online_change_passphrase(&state, &body, client_ip),
+        ("POST", "/online/rotate-key") => handle_online_rotate_key(&state, client_ip),
+        ("POST", "/lan/receive-key") => handle_receive_key(&body),
         ("GET", "/online/sync-progress") => handle_sync_progress(&state),
         // Legacy endpoints — /lan/pull used by 13-step protocol (step 6, master fetches from slave)
         ("POST", "/lan/pull") => handle_pull(&state, &body),
@@ -752,6 +872,16 @@ fn handle_sync_progress(state: &LanSyncState) -> (u16, String) {
     (200, json)
 }
 
+/// Last dry-run report (LAN or online). Auth-exempt like the trigger endpoints
+/// it pairs with, so loopback-only — it lists local project names.
+fn handle_sync_preview(state: &LanSyncState, client_ip: IpAddr) -> (u16, String) {
+    if !is_loopback(client_ip) {
+        return (403, json_error("loopback_only"));
+    }
+    let report = state.sync_preview();
+    (200, serde_json::to_string(&report).unwrap_or_else(|_| "null".to_string()))
+}
+
 fn handle_ping(state: &LanSyncState) -> (u16, String) {
     // Ping is unauthenticated (used for discovery), so minimize exposed data.
     // sync_marker_hash and machine_name are omitted to reduce info leakage.
@@ -762,6 +892,7 @@ fn handle_ping(state: &LanSyncState) -> (u16, String) {
         machine_name: String::new(),
         role: state.get_role(),
         sync_marker_hash: None,
+        protocol: ProtocolInfo::local(),
     };
     (200, serde_json::to_string(&resp).unwrap_or_default())
 }
@@ -772,11 +903,15 @@ fn handle_preflight(state: &LanSyncState) -> (u16, String) {
     let device_id = lan_common::get_device_id();
     let version = crate::VERSION.trim();
 
+    let protocol = ProtocolInfo::local();
     let resp = serde_json::json!({
         "ok": true,
         "auth": "valid",
         "device_id": device_id,
         "version": version,
+        "sync_protocol": protocol.sync_protocol,
+        "min_sync_protocol": protocol.min_sync_protocol,
+        "capabilities": protocol.capabilities,
         "sync_in_progress": in_sync,
         "db_frozen": frozen,
     });
@@ -827,6 +962,8 @@ fn handle_negotiate(state: &LanSyncState, body: &str) -> (u16, String) {
 
     // Accept slave role when master negotiates
     state.set_role("slave");
+    state.begin_sync_run("lan", "slave", &req.master_device_id, false);
+    state.note_sync_run(|run| run.mode = mode.to_string());
     state.set_progress(3, "negotiating", "local");
     sync_log(&format!("[SLAVE] Master {} rozpoczyna sync — tryb: {}", req.master_device_id, mode));
 
@@ -857,6 +994,7 @@ fn handle_upload_db(state: &LanSyncState, body: &str) -> (u16, String) {
     }
 
     state.set_progress(9, "merging", "download");
+    state.note_sync_run(|run| run.bytes_received = body.len() as u64);
     let kb = body.len() as f64 / 1024.0;
     sync_log(&format!("[SLAVE] Odebrano {:.1} KB danych od mastera", kb));
 
@@ -963,13 +1101,17 @@ fn handle_db_ready(state: &LanSyncState, body: &str) -> (u16, String) {
 
     // Merge incoming data
     sync_log("[SLAVE] Scalanie danych...");
-    if let Err(e) = crate::sync_common::merge_incoming_data(&mut conn, &merged_data) {
-        sync_log(&format!("[SLAVE] BLAD scalania: {} — przywracam backup", e));
-        if let Err(re) = crate::sync_common::restore_database_backup_typed(&mut conn, "lan") {
-            sync_log(&format!("[SLAVE] BLAD przywracania backupu: {}", re));
+    let merge_stats = match crate::sync_common::merge_incoming_data_with_stats(&mut conn, &merged_data) {
+        Ok(stats) => stats,
+        Err(e) => {
+            sync_log(&format!("[SLAVE] BLAD scalania: {} — przywracam backup", e));
+            if let Err(re) = crate::sync_common::restore_database_backup_typed(&mut conn, "lan") {
+                sync_log(&format!("[SLAVE] BLAD przywracania backupu: {}", re));
+            }
+            state.note_sync_run(|run| run.error = Some(format!("Merge failed: {}", e)));
+            return (500, json_error(&format!("Merge failed: {}", e)));
         }
-        return (500, json_error(&format!("Merge failed: {}", e)));
-    }
+    };
 
     // Verify integrity
     sync_log("[SLAVE] Weryfikacja integralnosci...");
@@ -978,6 +1120,7 @@ fn handle_db_ready(state: &LanSyncState, body: &str) -> (u16, String) {
         if let Err(re) = crate::sync_common::restore_database_backup_typed(&mut conn, "lan") {
             sync_log(&format!("[SLAVE] BLAD przywracania backupu: {}", re));
         }
+        state.note_sync_run(|run| run.error = Some(format!("Verify failed: {}", e)));
         return (500, json_error(&format!("Verify failed: {}", e)));
     }
 
@@ -1017,6 +1160,11 @@ fn handle_db_ready(state: &LanSyncState, body: &str) -> (u16, String) {
     }
 
     state.set_progress(12, "slave_import_done", "local");
+    state.note_sync_run(|run| {
+        run.mode = req.transfer_mode.clone();
+        run.add_merge(&merge_stats);
+        run.imported = true;
+    });
     sync_log("[SLAVE] Import zakonczony — dane scalone i zweryfikowane");
 
     if !req.marker_hash.is_empty() {
@@ -1031,7 +1179,16 @@ fn handle_db_ready(state: &LanSyncState, body: &str) -> (u16, String) {
     (200, serde_json::to_string(&resp).unwrap_or_default())
 }
 
-fn handle_unfreeze(state: &LanSyncState) -> (u16, String) {
+fn handle_unfreeze(state: &LanSyncState, body: &str) -> (u16, String) {
+    // Master dry-run releases the slave without an import — still a clean run.
+    let dry_run = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v.get("dry_run").and_then(|d| d.as_bool()))
+        .unwrap_or(false);
+    if dry_run {
+        state.note_sync_run(|run| run.dry_run = true);
+    }
+    state.finish_slave_sync_run();
     state.unfreeze();
     state.set_role("undecided");
     state.mark_sync_completed();
@@ -1063,6 +1220,10 @@ fn handle_pull(state: &LanSyncState, body: &str) -> (u16, String) {
         /// snapshots include tombstones so deletions converge too.
         #[serde(default)]
         full_sync: bool,
+        /// Capabilities negotiated by the master; absent from pre-negotiation
+        /// masters, which only ever sync with the same build.
+        #[serde(default)]
+        capabilities: Option<Vec<String>>,
     }
     let req: PullRequest = match serde_json::from_str(body) {
         Ok(r) => r,
@@ -1078,14 +1239,20 @@ fn handle_pull(state: &LanSyncState, body: &str) -> (u16, String) {
         Err(e) => return (500, json_error(&e)),
     };
 
+    let features = req
+        .capabilities
+        .as_ref()
+        .map_or_else(SyncFeatures::all, SyncFeatures::from_names);
     let result = if req.full_sync {
         build_full_snapshot_public(&conn)
     } else {
         build_delta_for_pull(&conn, &req.since, true)
-    };
+    }
+    .and_then(|json| crate::sync_common::limit_archive_to_features(json, &features));
 
     match result {
         Ok(json) => {
+            state.note_sync_run(|run| run.bytes_sent = json.len() as u64);
             let kb = json.len() as f64 / 1024.0;
             sync_log(&format!("[SLAVE] Wyslano {:.1} KB danych do mastera", kb));
             (200, json)
@@ -1118,13 +1285,15 @@ fn handle_trigger_sync(
         peer_device_id: String,
         #[serde(default)]
         force: bool,
+        #[serde(default)]
+        dry_run: bool,
     }
     let req: TriggerReq = match serde_json::from_str(body) {
         Ok(r) => r,
         Err(e) => return (400, json_error(&format!("Invalid request: {}", e))),
     };
 
-    if !req.force && state.secs_since_last_sync() < SYNC_COOLDOWN_SECS {
+    if !req.force && !req.dry_run && state.secs_since_last_sync() < SYNC_COOLDOWN_SECS {
         return (429, json_error("Sync completed recently, wait before retrying"));
     }
 
@@ -1162,7 +1331,8 @@ fn handle_trigger_sync(
         return (409, json_error("Sync already in progress"));
     }
 
-    log::info!("LAN trigger-sync: dashboard requested sync with {}:{}{}", req.peer_ip, req.peer_port, if req.force { " [FORCE]" } else { "" });
+    log::info!("LAN trigger-sync: dashboard requested sync with {}:{}{}{}", req.peer_ip, req.peer_port,
+        if req.force { " [FORCE]" } else { "" }, if req.dry_run { " [DRY-RUN]" } else { "" });
 
     let peer = crate::lan_sync_orchestrator::PeerTarget {
         ip: req.peer_ip,
@@ -1171,7 +1341,7 @@ fn handle_trigger_sync(
     };
 
     state.set_role("master");
-    crate::lan_sync_orchestrator::run_sync_as_master_with_options(peer, state.clone(), stop_signal.clone(), req.force);
+    crate::lan_sync_orchestrator::run_sync_as_master_with_options(peer, state.clone(), stop_signal.clone(), req.force, req.dry_run);
 
     (200, r#"{"ok":true,"message":"sync started"}"#.to_string())
 }
@@ -1179,12 +1349,27 @@ fn handle_trigger_sync(
 fn handle_online_trigger_sync(
     state: &Arc<LanSyncState>,
     stop_signal: &Arc<AtomicBool>,
+    body: &str,
     client_ip: IpAddr,
 ) -> (u16, String) {
     if !is_loopback(client_ip) {
         log::warn!("Online trigger-sync rejected from non-loopback {}", client_ip);
         return (403, json_error("loopback_only"));
     }
+    // Body is optional — older dashboards POST without one.
+    #[derive(Deserialize, Default)]
+    struct OnlineTriggerReq {
+        #[serde(default)]
+        dry_run: bool,
+    }
+    let req: OnlineTriggerReq = if body.trim().is_empty() {
+        OnlineTriggerReq::default()
+    } else {
+        match serde_json::from_str(body) {
+            Ok(r) => r,
+            Err(e) => return (400, json_error(&format!("Invalid request: {}", e))),
+        }
+    };
     if state.sync_in_progress.compare_exchange(
         false, true, Ordering::SeqCst, Ordering::SeqCst
     ).is_err() {
@@ -1198,13 +1383,17 @@ fn handle_online_trigger_sync(
         return (400, json_error("Online sync not configured (missing server_url or auth_token)"));
     }
 
-    log::info!("Online trigger-sync: dashboard requested online sync");
+    log::info!("Online trigger-sync: dashboard requested online sync{}", if req.dry_run { " [DRY-RUN]" } else { "" });
 
     let state_clone = state.clone();
     let stop_clone = stop_signal.clone();
     std::thread::spawn(move || {
         log::info!("Online sync thread started");
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            if req.dry_run {
+                crate::online_sync::run_online_sync_dry_run(settings, state_clone.clone(), stop_clone);
+                return;
+            }
             match settings.sync_mode.as_str() {
                 "async" if !settings.group_id.is_empty() => {
                     let group_id = settings.group_id.clone();
@@ -1241,6 +1430,87 @@ fn handle_online_cancel_sync(state: &Arc<LanSyncState>, client_ip: IpAddr) -> (u
     (200, r#"{"ok":true,"message":"online sync cancellation requested"}"#.to_string())
 }
 
+const KC_PASSPHRASE: &str = "online.passphrase";
+
+/// Re-wraps the end-to-end sync key under a new passphrase (loopback only —
+/// the current passphrase must still be supplied).
+fn handle_online_change_passphrase(
+    state: &Arc<LanSyncState>,
+    body: &str,
+    client_ip: IpAddr,
+) -> (u16, String) {
+    if !is_loopback(client_ip) {
+        log::warn!("Online change-passphrase rejected from non-loopback {}", client_ip);
+        return (403, json_error("loopback_only"));
+    }
+    #[derive(Deserialize)]
+    struct ChangePassphraseReq {
+        old_passphrase: String,
+        new_passphrase: String,
+    }
+    let req: ChangePassphraseReq = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(e) => return (400, json_error(&format!("Invalid request: {}", e))),
+    };
+    if req.new_passphrase.is_empty() {
+        return (400, json_error("New passphrase must not be empty"));
+    }
+    if state.sync_in_progress.load(Ordering::SeqCst) {
+        return (409, json_error("Sync in progress"));
+    }
+
+    // Keychain first: if it fails, nothing has changed yet. A failed re-wrap
+    // (wrong current passphrase) puts the previous keychain entry back.
+    let previous = timeflow_shared::secret_store::get_secret(KC_PASSPHRASE).unwrap_or_default();
+    if let Err(e) = timeflow_shared::secret_store::set_secret(KC_PASSPHRASE, &req.new_passphrase) {
+        return (500, json_error(&format!("Keychain update failed: {}", e)));
+    }
+    let key_id = match crate::sync_passphrase::change_passphrase(&req.old_passphrase, &req.new_passphrase) {
+        Ok(id) => id,
+        Err(e) => {
+            let _ = timeflow_shared::secret_store::set_secret(KC_PASSPHRASE, &previous);
+            return (400, json_error(&e));
+        }
+    };
+    log::info!("Online change-passphrase: sync key re-wrapped");
+    (200, serde_json::json!({ "ok": true, "key_id": key_id }).to_string())
+}
+
+/// Dashboard-initiated `encryption_key` rotation — see `key_rotation`.
+fn handle_online_rotate_key(state: &Arc<LanSyncState>, client_ip: IpAddr) -> (u16, String) {
+    if !is_loopback(client_ip) {
+        log::warn!("Online rotate-key rejected from non-loopback {}", client_ip);
+        return (403, json_error("loopback_only"));
+    }
+    if state.sync_in_progress.load(Ordering::SeqCst) {
+        return (409, json_error("Sync in progress"));
+    }
+    let settings = config::load_online_sync_settings();
+    match crate::key_rotation::rotate(&settings) {
+        Ok(report) => {
+            log::info!("Online rotate-key: key {} installed", report.key_id);
+            (200, serde_json::json!({ "ok": true, "report": report }).to_string())
+        }
+        Err(e) => (400, json_error(&e)),
+    }
+}
+
+/// Rotated key from a paired peer (authenticated by the pairing secret). It
+/// only installs when wrapped with a key this device already holds.
+fn handle_receive_key(body: &str) -> (u16, String) {
+    let Some(rotation) = crate::key_rotation::parse(body) else {
+        return (400, json_error("Invalid key rotation message"));
+    };
+    let settings = config::load_online_sync_settings();
+    match crate::key_rotation::install(&rotation, &settings.keyring()) {
+        Ok(_) => (200, json_ok()),
+        Err(e) => {
+            log::warn!("LAN receive-key rejected: {}", e);
+            (400, json_error(&e))
+        }
+    }
+}
+
 fn pair_throttle() -> &'static crate::lan_pair_throttle::PairThrottle {
     static PAIR_THROTTLE: OnceLock<crate::lan_pair_throttle::PairThrottle> = OnceLock::new();
     PAIR_THROTTLE.get_or_init(crate::lan_pair_throttle::PairThrottle::new)
@@ -1338,7 +1608,16 @@ fn handle_initiate_pair(body: &str, client_ip: IpAddr) -> (u16, String) {
         return (500, json_error("local_identity_unavailable"));
     }
 
-    let pair_url = format!("http://{}:{}/lan/pair", req.peer_ip, req.peer_port);
+    let peer_addr = match timeflow_shared::peer_addr::socket_addr(&req.peer_ip, req.peer_port) {
+        Ok(a) => a,
+        Err(e) => return (400, json_error(&e)),
+    };
+    // URL-e ureq nie przenoszą strefy IPv6 (`fe80::1%3`) — stały host,
+    // rozwiązywany bezpośrednio na adres peera.
+    let agent = ureq::AgentBuilder::new()
+        .resolver(move |_: &str| Ok(vec![peer_addr]))
+        .build();
+    let pair_url = format!("http://timeflow-peer:{}/lan/pair", req.peer_port);
     let pair_body = serde_json::json!({
         "code": req.code,
         "slave_device_id": local_device_id,
@@ -1347,7 +1626,7 @@ fn handle_initiate_pair(body: &str, client_ip: IpAddr) -> (u16, String) {
     })
     .to_string();
 
-    let resp = match ureq::post(&pair_url)
+    let resp = match agent.post(&pair_url)
         .set("Content-Type", "application/json")
         .timeout(std::time::Duration::from_secs(15))
         .send_string(&pair_body)
@@ -1517,34 +1796,103 @@ fn build_delta_for_pull(
     // Defensive: the merged_* columns come from a dashboard migration (m23);
     // make sure they exist before SELECT-ing them (no-op when already migrated).
     crate::sync_common::ensure_project_merge_columns(conn);
+    crate::sync_common::ensure_client_sync_schema(conn);
+    crate::sync_common::ensure_tag_sync_schema(conn);
+    crate::sync_common::ensure_billable_sync_schema(conn);
 
     // Normalize ISO timestamp for SQLite comparison
     let since_norm = since.replace('T', " ");
     let since_ref = if since_norm.len() > 19 { &since_norm[..19] } else { &since_norm };
 
+    // Selective sync: device-local projects/apps and everything attributed to
+    // them never leave this machine (see sync_common::DeviceLocalScope).
+    let local = crate::sync_common::DeviceLocalScope::load(conn);
+    let (local_projects, local_apps) = (&local.project_ids, &local.app_ids);
+
     // Fetch projects (always full — small table, needed for ID resolution)
-    let projects = fetch_all_rows(conn, "SELECT id, name, color, hourly_rate, created_at, excluded_at, frozen_at, assigned_folder_path, merged_into, merged_at, updated_at FROM projects ORDER BY name")?;
+    // client_name is omitted (not null) when the column is missing — an absent
+    // key tells the receiver to keep its local link.
+    let project_client_col = if crate::sync_common::column_exists(conn, "projects", "client_name") {
+        ", client_name"
+    } else {
+        ""
+    };
+    // Rows carry their hybrid logical clock once the DB has one; peers without
+    // it fall back to updated_at/deleted_at on the receiving side.
+    let hlc_col = |table: &str, prefix: &str| {
+        if crate::sync_common::column_exists(conn, table, "hlc") {
+            format!(", {}hlc", prefix)
+        } else {
+            String::new()
+        }
+    };
+    // Billable flags (m34): omitted when the column is missing, like client_name.
+    let billable_col = |table: &str, prefix: &str| {
+        if crate::sync_common::column_exists(conn, table, "billable") {
+            format!(", {}billable", prefix)
+        } else {
+            String::new()
+        }
+    };
+    // Tag links travel as a JSON list of tag names per session row (m33).
+    let has_tags = crate::sync_common::table_exists(conn, "tags");
+    let tags_col = |link: &str, owner: &str, alias: &str| {
+        if has_tags {
+            format!(
+                ", (SELECT json_group_array(name) FROM (SELECT t.name FROM {link} lt \
+                 JOIN tags t ON t.id = lt.tag_id WHERE lt.{owner} = {alias}.id \
+                 ORDER BY t.name COLLATE NOCASE)) AS tags",
+                link = link,
+                owner = owner,
+                alias = alias
+            )
+        } else {
+            String::new()
+        }
+    };
+    let projects = fetch_all_rows(conn, &format!(
+        "SELECT id, name, color, hourly_rate, created_at, excluded_at, frozen_at, assigned_folder_path, merged_into, merged_at{}{}, updated_at{} FROM projects WHERE id NOT IN ({}) ORDER BY name",
+        project_client_col, billable_col("projects", ""), hlc_col("projects", ""), local_projects
+    ))?;
 
     // Fetch applications (always full)
-    let apps = fetch_all_rows(conn, "SELECT id, executable_name, display_name, project_id, updated_at FROM applications ORDER BY executable_name")?;
+    let apps = fetch_all_rows(conn, &format!(
+        "SELECT id, executable_name, display_name, project_id, updated_at{} FROM applications \
+         WHERE id NOT IN ({}) ORDER BY executable_name",
+        hlc_col("applications", ""), local_apps
+    ))?;
 
     // Fetch sessions since timestamp (parameterized — no SQL injection).
     // project_name carries the peer's project label so receiving peers can preserve
     // the assignment even when the project row is absent in their local DB.
-    let sessions = fetch_all_rows_params(conn,
-        "SELECT s.id, s.app_id, s.project_id, s.project_name, s.start_time, s.end_time, s.duration_seconds, \
-         s.date, s.rate_multiplier, s.comment, s.is_hidden, s.updated_at \
-         FROM sessions s WHERE s.updated_at >= ?1 ORDER BY s.start_time",
+    let mut sessions = fetch_all_rows_params(conn,
+        &format!(
+            "SELECT s.id, s.app_id, s.project_id, s.project_name, s.start_time, s.end_time, s.duration_seconds, \
+             s.date, s.rate_multiplier, s.comment, s.is_hidden, s.updated_at{}{}{} \
+             FROM sessions s WHERE s.updated_at >= ?1 \
+             AND s.app_id NOT IN ({}) AND (s.project_id IS NULL OR s.project_id NOT IN ({})) \
+             ORDER BY s.start_time",
+            hlc_col("sessions", "s."), tags_col("session_tags", "session_id", "s"),
+            billable_col("sessions", "s."), local_apps, local_projects
+        ),
         &[&since_ref as &dyn rusqlite::types::ToSql],
     )?;
+    expand_tag_lists(&mut sessions);
 
     // Fetch manual_sessions since timestamp (parameterized)
-    let manual = fetch_all_rows_params(conn,
-        "SELECT id, title, session_type, project_id, project_name, app_id, start_time, end_time, \
-         duration_seconds, date, created_at, updated_at \
-         FROM manual_sessions WHERE updated_at >= ?1 ORDER BY start_time",
+    let mut manual = fetch_all_rows_params(conn,
+        &format!(
+            "SELECT ms.id, ms.title, ms.session_type, ms.project_id, ms.project_name, ms.app_id, ms.start_time, \
+             ms.end_time, ms.duration_seconds, ms.date, ms.created_at, ms.updated_at{}{}{} \
+             FROM manual_sessions ms WHERE ms.updated_at >= ?1 \
+             AND (ms.project_id IS NULL OR ms.project_id NOT IN ({})) AND (ms.app_id IS NULL OR ms.app_id NOT IN ({})) \
+             ORDER BY ms.start_time",
+            hlc_col("manual_sessions", "ms."), tags_col("manual_session_tags", "manual_session_id", "ms"),
+            billable_col("manual_sessions", "ms."), local_projects, local_apps
+        ),
         &[&since_ref as &dyn rusqlite::types::ToSql],
     )?;
+    expand_tag_lists(&mut manual);
 
     // Tombstones are delta-only events. In full snapshots we'd be replaying the
     // entire deletion history against the peer's live records — guaranteed to
@@ -1552,8 +1900,22 @@ fn build_delta_for_pull(
     // with a stale `updated_at` (the skip_tombstone guard fails on old rows).
     let tombstones: Vec<serde_json::Value> = if include_tombstones {
         fetch_all_rows_params(conn,
-            "SELECT id, table_name, record_id, record_uuid, deleted_at, sync_key \
-             FROM tombstones WHERE deleted_at >= ?1 ORDER BY deleted_at",
+            &format!(
+                "SELECT id, table_name, record_id, record_uuid, deleted_at, sync_key{} \
+                 FROM tombstones WHERE deleted_at >= ?1 \
+                 AND NOT (table_name = 'projects' AND sync_key IN \
+                     (SELECT name FROM projects WHERE id IN ({projects}))) \
+                 AND NOT (table_name = 'applications' AND sync_key IN \
+                     (SELECT executable_name FROM applications WHERE id IN ({apps}))) \
+                 AND NOT (table_name = 'sessions' AND substr(sync_key, 1, instr(sync_key, '|') - 1) IN \
+                     (SELECT executable_name FROM applications WHERE id IN ({apps}))) \
+                 AND NOT (table_name = 'manual_sessions' AND substr(sync_key, 1, instr(sync_key, '|') - 1) IN \
+                     (SELECT CAST(id AS TEXT) FROM projects WHERE id IN ({projects}))) \
+                 ORDER BY deleted_at",
+                hlc_col("tombstones", ""),
+                projects = local_projects,
+                apps = local_apps
+            ),
             &[&since_ref as &dyn rusqlite::types::ToSql],
         )?
     } else {
@@ -1562,7 +1924,7 @@ fn build_delta_for_pull(
 
     let table_hashes = build_table_hashes(conn);
 
-    let archive = serde_json::json!({
+    let mut archive = serde_json::json!({
         "table_hashes": table_hashes,
         "exported_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
         "device_id": lan_common::get_device_id(),
@@ -1575,14 +1937,64 @@ fn build_delta_for_pull(
         }
     });
 
+    // Clients and estimate settings (always full — tiny tables). Skipped on a
+    // not-yet-migrated read-only DB instead of failing the whole pull.
+    if crate::sync_common::table_exists(conn, "clients") {
+        archive["data"]["clients"] = serde_json::Value::Array(fetch_all_rows(conn, &format!(
+            "SELECT name, contact, address, tax_id, currency, default_hourly_rate, color, \
+             archived_at, created_at, updated_at{} FROM clients ORDER BY name",
+            hlc_col("clients", "")
+        ))?);
+    }
+    if crate::sync_common::table_exists(conn, "estimate_settings") {
+        archive["data"]["estimate_settings"] = serde_json::Value::Array(fetch_all_rows(conn, &format!(
+            "SELECT key, value, updated_at{} FROM estimate_settings ORDER BY key",
+            hlc_col("estimate_settings", "")
+        ))?);
+    }
+    if has_tags {
+        archive["data"]["tags"] = serde_json::Value::Array(fetch_all_rows(conn, &format!(
+            "SELECT name, color, created_at, updated_at{} FROM tags ORDER BY name COLLATE NOCASE",
+            hlc_col("tags", "")
+        ))?);
+    }
+
+    // File activities carry no updated_at — a row keeps growing until its day
+    // ends, so every row dated on/after the `since` day is re-sent. app_id and
+    // project_id are resolved by the receiver through the applications/projects
+    // lists above, like sessions.
+    if crate::sync_common::column_exists(conn, "file_activities", "activity_spans") {
+        archive["data"]["file_activities"] = serde_json::Value::Array(fetch_all_rows_params(conn,
+            &format!(
+                "SELECT app_id, project_id, date, file_name, file_path, total_seconds, first_seen, \
+                 last_seen, window_title, detected_path, title_history, activity_type, activity_spans \
+                 FROM file_activities WHERE date >= substr(?1, 1, 10) \
+                 AND app_id NOT IN ({}) AND (project_id IS NULL OR project_id NOT IN ({})) \
+                 ORDER BY date, app_id, file_path",
+                local_apps, local_projects
+            ),
+            &[&since_ref as &dyn rusqlite::types::ToSql],
+        )?);
+    }
+
     serde_json::to_string(&archive).map_err(|e| e.to_string())
 }
 
+/// The `tags` column comes out of SQLite as a JSON string — ship it as an array.
+fn expand_tag_lists(rows: &mut [serde_json::Value]) {
+    for row in rows.iter_mut().filter_map(|r| r.as_object_mut()) {
+        if let Some(serde_json::Value::String(json)) = row.get("tags") {
+            let names = serde_json::from_str(json).unwrap_or_else(|_| serde_json::json!([]));
+            row.insert("tags".to_string(), names);
+        }
+    }
+}
+
 fn fetch_all_rows(conn: &rusqlite::Connection, sql: &str) -> Result<Vec<serde_json::Value>, String> {
     fetch_all_rows_params(conn, sql, &[])
 }
 
-fn fetch_all_rows_params(
+pub(crate) fn fetch_all_rows_params(
     conn: &rusqlite::Connection,
     sql: &str,
     params: &[&dyn rusqlite::types::ToSql],
@@ -1749,7 +2161,7 @@ mod tests {
         let state = Arc::new(LanSyncState::new());
         let stop = Arc::new(AtomicBool::new(false));
         let (status, resp) = handle_online_trigger_sync(
-            &state, &stop,
+            &state, &stop, "",
             IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7)),
         );
         assert_eq!(status, 403);
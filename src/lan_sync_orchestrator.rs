@@ -5,11 +5,15 @@ use crate::lan_common;
 use crate::lan_common::sync_log;
 use crate::lan_server::LanSyncState;
 use crate::sync_common;
+use crate::sync_history::RunStatus;
+use crate::sync_preview::SyncPreview;
 use serde::Deserialize;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
+use timeflow_shared::peer_addr;
+use timeflow_shared::sync_protocol::{self, ProtocolInfo, SyncFeatures};
 
 const SYNC_TIMEOUT: Duration = Duration::from_secs(300); // 5 min max
 const EPOCH: &str = "1970-01-01 00:00:00";
@@ -101,26 +105,12 @@ fn http_post_with_progress(
 }
 
 fn url_to_addr(url: &str) -> Result<std::net::SocketAddr, String> {
-    // Parse "http://1.2.3.4:47891/path" or "http://[::1]:47891/path" → SocketAddr
+    // Parse "http://1.2.3.4:47891/path" or "http://[fe80::1%253]:47891/path" → SocketAddr
     let without_scheme = url
         .strip_prefix("http://")
         .unwrap_or(url);
-    // For IPv6, bracket notation [::1]:port — find the closing ']' first
-    let host_port = if without_scheme.starts_with('[') {
-        // IPv6: find ']:port' then skip anything after the next '/'
-        let end = without_scheme.find("]/")
-            .map(|i| i + 1) // include the ']'
-            .unwrap_or(without_scheme.find(']').map(|i| i + 1).unwrap_or(without_scheme.len()));
-        // Include :port after ']'
-        let rest = &without_scheme[end..];
-        let port_end = rest.find('/').unwrap_or(rest.len());
-        &without_scheme[..end + port_end]
-    } else {
-        without_scheme.split('/').next().unwrap_or(without_scheme)
-    };
-    host_port
-        .parse()
-        .map_err(|e| format!("Invalid address {}: {}", host_port, e))
+    let host_port = without_scheme.split('/').next().unwrap_or(without_scheme);
+    timeflow_shared::peer_addr::parse_host_port(host_port)
 }
 
 fn url_path(url: &str) -> &str {
@@ -315,7 +305,7 @@ pub fn run_sync_as_master(
     sync_state: Arc<LanSyncState>,
     stop_signal: Arc<AtomicBool>,
 ) -> JoinHandle<()> {
-    run_sync_as_master_with_options(peer, sync_state, stop_signal, false)
+    run_sync_as_master_with_options(peer, sync_state, stop_signal, false, false)
 }
 
 pub fn run_sync_as_master_with_options(
@@ -323,13 +313,15 @@ pub fn run_sync_as_master_with_options(
     sync_state: Arc<LanSyncState>,
     stop_signal: Arc<AtomicBool>,
     force: bool,
+    dry_run: bool,
 ) -> JoinHandle<()> {
     thread::spawn(move || {
         // Circuit breaker: after repeated full-cycle failures the breaker opens an
         // exponential backoff window. Skip auto-sync while it's active so a persistent
-        // conflict stops hammering both machines. A forced (manual) sync bypasses it.
+        // conflict stops hammering both machines. A forced (manual) sync bypasses it,
+        // and so does a dry-run — it writes nothing.
         let backoff_left = sync_state.sync_backoff_remaining_secs();
-        if !force && backoff_left > 0 {
+        if !force && !dry_run && backoff_left > 0 {
             sync_log(&format!(
                 "=== SYNC POMINIETY — circuit breaker aktywny po {} kolejnych bledach, nastepna proba za {}s (wymus reczna sync, aby pominac) ===",
                 sync_state.consecutive_sync_failures.load(Ordering::Relaxed),
@@ -342,13 +334,17 @@ pub fn run_sync_as_master_with_options(
             return;
         }
 
-        sync_log(&format!("=== START SYNC z {}:{} {} ===",
-            peer.ip, peer.port, if force { "[FORCE]" } else { "" }));
+        sync_log(&format!("=== START SYNC z {}:{} {}{} ===",
+            peer.ip, peer.port, if force { "[FORCE]" } else { "" }, if dry_run { "[DRY-RUN]" } else { "" }));
+        if dry_run {
+            sync_state.begin_sync_preview("lan", &peer.device_id);
+        }
         sync_state.set_sync_type("lan");
         sync_state.set_progress(1, "starting", "local");
         let _start = Instant::now();
 
         let mut last_err = String::new();
+        let mut preview = None;
         for attempt in 1..=MAX_RETRIES {
             if stop_signal.load(Ordering::Relaxed) {
                 sync_log("[!] Stop signal — przerywam sync");
@@ -357,8 +353,9 @@ pub fn run_sync_as_master_with_options(
             if attempt > 1 {
                 sync_log(&format!("[!] Ponowna proba {}/{}", attempt, MAX_RETRIES));
             }
-            match execute_master_sync(&peer, &sync_state, &stop_signal, force) {
-                Ok(()) => {
+            match execute_master_sync(&peer, &sync_state, &stop_signal, force, dry_run) {
+                Ok(p) => {
+                    preview = p;
                     last_err.clear();
                     // Sync completed end-to-end — any prior auth-error badge
                     // for this peer is stale, clear it so the UI recovers.
@@ -374,7 +371,7 @@ pub fn run_sync_as_master_with_options(
                     // Skip the unfreeze call when peer is not paired (no secret available) —
                     // without a matching secret it would just loop on 401.
                     if let Some(retry_secret) = resolve_peer_secret(&peer.device_id) {
-                        let slave_unfreeze_url = format!("http://{}:{}/lan/unfreeze", peer.ip, peer.port);
+                        let slave_unfreeze_url = format!("http://{}/lan/unfreeze", peer_addr::host_port(&peer.ip, peer.port));
                         if let Err(ue) = http_post(&slave_unfreeze_url, "{}", &retry_secret) {
                             sync_log(&format!("[!] Nie udalo sie odmrozic slave: {}", ue));
                         }
@@ -405,10 +402,18 @@ pub fn run_sync_as_master_with_options(
             sync_log(&format!("=== SYNC NIEUDANY po {} probach: {} ===", MAX_RETRIES, last_err));
         }
 
+        if dry_run {
+            sync_state.finish_sync_preview(preview.ok_or_else(|| {
+                if last_err.is_empty() { "stopped".to_string() } else { last_err.clone() }
+            }));
+        }
+
         // Feed this cycle's outcome to the circuit breaker. A stop-signal abort is
         // neither success nor failure — leave the breaker untouched in that case.
+        // A dry-run says nothing about sync health and is not counted either.
         if let Some(success) =
             breaker_outcome(stop_signal.load(Ordering::Relaxed), last_err.is_empty())
+                .filter(|_| !dry_run)
         {
             sync_state.note_sync_outcome(success);
         }
@@ -417,7 +422,7 @@ pub fn run_sync_as_master_with_options(
         // This prevents the "stuck in syncing" state if any step panics or errors
         // without proper cleanup.
         sync_state.unfreeze();
-        if last_err.is_empty() {
+        if last_err.is_empty() && !dry_run {
             sync_state.mark_sync_completed();
         }
         // Small delay so UI can see "completed" phase before reset
@@ -427,29 +432,44 @@ pub fn run_sync_as_master_with_options(
     })
 }
 
-/// Version gate for the 13-step protocol: peers must run the same TIMEFLOW
-/// version. The dashboard bridge enforces this for UI-triggered syncs
-/// (dashboard commands/lan_sync.rs), but auto-sync paths (discovery, tray)
-/// reach execute_master_sync directly — this is their only gate.
-fn version_compat_error(local: &str, peer: Option<&str>) -> Option<String> {
-    let peer = peer.unwrap_or("unknown");
-    if peer == local {
-        None
-    } else {
-        Some(format!(
-            "version_mismatch: local v{}, peer v{} — update both machines",
-            local, peer
-        ))
+/// Protocol gate for the 13-step flow: the peer's preflight announces its sync
+/// protocol range and capabilities, and the sync runs on the features both
+/// sides support. Peers that predate negotiation must run the same version.
+/// The dashboard bridge checks the same for UI-triggered syncs (dashboard
+/// commands/lan_sync.rs), but auto-sync paths (discovery, tray) reach
+/// execute_master_sync directly — this is their only gate.
+fn negotiate_features(local: &str, preflight: &serde_json::Value) -> Result<SyncFeatures, String> {
+    sync_protocol::negotiate(
+        local,
+        preflight.get("version").and_then(|v| v.as_str()),
+        ProtocolInfo::from_json(preflight).as_ref(),
+    )
+}
+
+pub(crate) fn execute_master_sync(
+    peer: &PeerTarget,
+    sync_state: &LanSyncState,
+    stop_signal: &AtomicBool,
+    force: bool,
+    dry_run: bool,
+) -> Result<Option<SyncPreview>, String> {
+    sync_state.begin_sync_run("lan", "master", &peer.device_id, dry_run);
+    let result = run_master_sync_steps(peer, sync_state, stop_signal, force, dry_run);
+    match &result {
+        Ok(_) => sync_state.finish_sync_run(RunStatus::Ok, None),
+        Err(e) => sync_state.finish_sync_run(RunStatus::Error, Some(e)),
     }
+    result
 }
 
-fn execute_master_sync(
+fn run_master_sync_steps(
     peer: &PeerTarget,
     sync_state: &LanSyncState,
     stop_signal: &AtomicBool,
     force: bool,
-) -> Result<(), String> {
-    let base_url = format!("http://{}:{}", peer.ip, peer.port);
+    dry_run: bool,
+) -> Result<Option<SyncPreview>, String> {
+    let base_url = format!("http://{}", peer_addr::host_port(&peer.ip, peer.port));
     let secret = match resolve_peer_secret(&peer.device_id) {
         Some(s) => s,
         None => {
@@ -469,6 +489,7 @@ fn execute_master_sync(
     // Step 2: Preflight check — verify connectivity + auth before committing
     sync_state.set_progress(2, "preflight", "local");
     sync_log(&format!("[2/13] Preflight check z peerem {}:{} ...", peer.ip, peer.port));
+    let mut features = SyncFeatures::all();
     let preflight_resp = http_post(
         &format!("{}/lan/preflight", base_url),
         "{}",
@@ -485,10 +506,19 @@ fn execute_master_sync(
                     sync_log("[2/13] Peer ma zamrozona baze — przerywam");
                     return Err("Peer database frozen".to_string());
                 }
-                let peer_version = resp.get("version").and_then(|v| v.as_str());
-                if let Some(err) = version_compat_error(crate::VERSION.trim(), peer_version) {
-                    sync_log(&format!("[2/13] PRZERWANE — {}", err));
-                    return Err(err);
+                features = match negotiate_features(crate::VERSION.trim(), &resp) {
+                    Ok(f) => f,
+                    Err(err) => {
+                        sync_log(&format!("[2/13] PRZERWANE — {}", err));
+                        return Err(err);
+                    }
+                };
+                let missing = features.missing();
+                if !missing.is_empty() {
+                    sync_log(&format!(
+                        "[2/13] Peer nie obsluguje: {} — pomijam te dane",
+                        missing.join(", ")
+                    ));
                 }
                 sync_log("[2/13] Preflight OK — peer gotowy");
             }
@@ -554,6 +584,7 @@ fn execute_master_sync(
         neg.mode.clone()
     };
     sync_state.set_progress(4, "negotiated", "local");
+    sync_state.note_sync_run(|run| run.mode = transfer_mode.clone());
     sync_log(&format!("[4/13] Tryb: {} | marker local={:?} remote={:?}",
         transfer_mode, local_marker, neg.slave_marker_hash));
 
@@ -590,6 +621,8 @@ fn execute_master_sync(
         // Full sync asks for the slave's whole convergence snapshot, including
         // tombstones. Tombstones are merged before live rows.
         "full_sync": transfer_mode == "full",
+        // The slave leaves out what this build cannot take.
+        "capabilities": features.names(),
     });
 
     let slave_data = http_post_with_progress(
@@ -602,9 +635,26 @@ fn execute_master_sync(
     ).map_err(|e| { sync_log(&format!("[6/13] BLAD pobierania: {}", e)); e })?;
 
     sync_state.set_progress(7, "received_from_slave", "local");
+    sync_state.note_sync_run(|run| run.bytes_received = slave_data.len() as u64);
     let slave_kb = slave_data.len() as f64 / 1024.0;
     sync_log(&format!("[7/13] Odebrano {:.1} KB danych z peera", slave_kb));
 
+    if dry_run {
+        // Dry-run ends here: release both databases first — the preview merge
+        // is rolled back, so neither side has anything to wait for.
+        sync_state.unfreeze();
+        http_post(&format!("{}/lan/unfreeze", base_url), r#"{"dry_run":true}"#, &secret).ok();
+        sync_state.set_progress(9, "previewing", "local");
+        sync_log("[9/13] DRY-RUN — podglad scalania bez zapisu (bez backupu, markera i wysylki do peera)");
+        let preview = sync_common::preview_incoming_data(&mut conn, &slave_data)
+            .map_err(|e| { sync_log(&format!("[9/13] BLAD podgladu: {}", e)); e })?;
+        sync_state.note_sync_run(|run| run.add_merge(&preview));
+        sync_log(&format!("=== DRY-RUN ZAKONCZONY w {:.1}s (tryb: {}) ===",
+            sync_start.elapsed().as_secs_f64(), transfer_mode));
+        sync_state.set_progress(13, "completed", "local");
+        return Ok(Some(preview));
+    }
+
     // Step 8: Backup
     sync_state.set_progress(8, "backing_up", "local");
     sync_log("[8/13] Tworzenie kopii zapasowej bazy...");
@@ -629,7 +679,7 @@ fn execute_master_sync(
     // NOTE: slave_data is passed directly to merge_incoming_data in memory.
     // No need to write it to a file first (data already available).
 
-    sync_common::merge_incoming_data(&mut conn, &slave_data)
+    let merge_stats = sync_common::merge_incoming_data_with_stats(&mut conn, &slave_data)
         .map_err(|e| {
             sync_log(&format!("[9/13] BLAD scalania: {} — przywracam backup", e));
             if let Err(re) = sync_common::restore_database_backup_typed(&mut conn, "lan") {
@@ -637,6 +687,7 @@ fn execute_master_sync(
             }
             e
         })?;
+    sync_state.note_sync_run(|run| run.add_merge(&merge_stats));
     sync_log("[9/13] Scalanie zakonczone");
 
     // Step 10: Verify
@@ -672,7 +723,9 @@ fn execute_master_sync(
         transfer_mode
     ));
     let merged_export = sync_common::build_full_export(&conn)
+        .and_then(|json| sync_common::limit_archive_to_features(json, &features))
         .map_err(|e| { sync_log(&format!("[11/13] BLAD budowania eksportu: {}", e)); e })?;
+    sync_state.note_sync_run(|run| run.bytes_sent = merged_export.len() as u64);
     let export_kb = merged_export.len() as f64 / 1024.0;
     sync_log(&format!("[11/13] Wysylanie {:.1} KB do peera...", export_kb));
 
@@ -789,7 +842,7 @@ fn execute_master_sync(
     // Set completed AFTER unfreeze — stays visible for UI polling
     sync_state.set_progress(13, "completed", "local");
 
-    Ok(())
+    Ok(None)
 }
 
 // ── DB helper functions (local only) ──
@@ -841,6 +894,21 @@ fn resolve_pull_since(
         .unwrap_or_else(|| EPOCH.to_string())
 }
 
+/// Hands a key-rotation message (see `key_rotation`) to a paired peer over the
+/// authenticated LAN channel. Unpaired peers are refused like a sync would be.
+pub fn send_key_rotation(peer: &PeerTarget, message: &str) -> Result<(), String> {
+    let secret = resolve_peer_secret(&peer.device_id).ok_or_else(|| "not_paired".to_string())?;
+    let url = format!("http://{}/lan/receive-key", peer_addr::host_port(&peer.ip, peer.port));
+    let resp = http_post(&url, message, &secret)?;
+    let parsed: serde_json::Value =
+        serde_json::from_str(&resp).map_err(|e| format!("Invalid receive-key response: {}", e))?;
+    if parsed["ok"].as_bool() == Some(true) {
+        Ok(())
+    } else {
+        Err(parsed["error"].as_str().unwrap_or("receive-key failed").to_string())
+    }
+}
+
 /// Find the created_at timestamp for a specific marker hash.
 /// Used to determine the correct `since` for delta sync — we need
 /// the date of the marker matching the remote peer's hash, not our latest.
@@ -855,9 +923,10 @@ fn get_marker_created_at_by_hash(conn: &rusqlite::Connection, hash: &str) -> Opt
 
 #[cfg(test)]
 mod tests {
-    use super::version_compat_error;
+    use super::negotiate_features;
     use super::resolve_pull_since;
     use super::breaker_outcome;
+    use super::{http_request, url_to_addr};
 
     #[test]
     fn pull_since_prefers_slave_clock() {
@@ -888,16 +957,24 @@ mod tests {
         );
     }
 
+    fn legacy_preflight(version: Option<&str>) -> serde_json::Value {
+        match version {
+            Some(v) => serde_json::json!({ "ok": true, "version": v }),
+            None => serde_json::json!({ "ok": true }),
+        }
+    }
+
     #[test]
     fn version_match_is_ok() {
-        assert!(version_compat_error("0.1.5704", Some("0.1.5704")).is_none());
+        assert!(negotiate_features("0.1.5704", &legacy_preflight(Some("0.1.5704"))).is_ok());
         // dev builds on both sides (VERSION fallback) must also pass
-        assert!(version_compat_error("dev", Some("dev")).is_none());
+        assert!(negotiate_features("dev", &legacy_preflight(Some("dev"))).is_ok());
     }
 
     #[test]
     fn version_mismatch_is_rejected() {
-        let err = version_compat_error("0.1.5704", Some("0.1.5700")).expect("must reject");
+        let err = negotiate_features("0.1.5704", &legacy_preflight(Some("0.1.5700")))
+            .expect_err("must reject");
         assert!(err.contains("version_mismatch"));
         assert!(err.contains("0.1.5704") && err.contains("0.1.5700"));
     }
@@ -905,7 +982,20 @@ mod tests {
     #[test]
     fn missing_peer_version_is_rejected() {
         // Very old peers don't return `version` in preflight — treat as mismatch.
-        assert!(version_compat_error("0.1.5704", None).is_some());
+        assert!(negotiate_features("0.1.5704", &legacy_preflight(None)).is_err());
+    }
+
+    #[test]
+    fn negotiating_peer_on_other_version_syncs_common_features() {
+        let preflight = serde_json::json!({
+            "ok": true,
+            "version": "0.1.5700",
+            "sync_protocol": timeflow_shared::sync_protocol::SYNC_PROTOCOL_VERSION,
+            "min_sync_protocol": timeflow_shared::sync_protocol::MIN_SYNC_PROTOCOL_VERSION,
+            "capabilities": ["hlc", "clients"],
+        });
+        let features = negotiate_features("0.1.5704", &preflight).expect("compatible protocol");
+        assert_eq!(features.names(), vec!["clients".to_string(), "hlc".to_string()]);
     }
 
     #[test]
@@ -923,4 +1013,53 @@ mod tests {
         assert_eq!(breaker_outcome(true, false), None);
         assert_eq!(breaker_outcome(true, true), None);
     }
+
+    #[test]
+    fn url_to_addr_accepts_scoped_ipv6() {
+        let addr = url_to_addr("http://[fe80::1%3]:47891/lan/ping").unwrap();
+        assert_eq!(addr.to_string(), "[fe80::1%3]:47891");
+        let rfc6874 = url_to_addr("http://[fe80::1%253]:47891/lan/ping").unwrap();
+        assert_eq!(rfc6874, addr);
+        assert_eq!(url_to_addr("http://10.0.0.2:47891/lan/pull").unwrap().to_string(), "10.0.0.2:47891");
+        assert!(url_to_addr("http://[fe80::1%3]/lan/ping").is_err());
+    }
+
+    #[test]
+    fn http_client_reaches_lan_server_over_both_loopbacks() {
+        use crate::lan_server::{bind_listener, serve, LanSyncState};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        // Hosts without IPv6 loopback (some CI containers) still test IPv4;
+        // where ::1 exists, failing to serve on it is a real failure.
+        let ipv6_loopback = std::net::TcpListener::bind("[::1]:0").is_ok();
+        let ips: &[&str] = if ipv6_loopback {
+            &["127.0.0.1", "::1"]
+        } else {
+            &["127.0.0.1"]
+        };
+        let mut listeners = Vec::new();
+        let mut urls = Vec::new();
+        for &ip in ips {
+            let bind = timeflow_shared::peer_addr::socket_addr(ip, 0).unwrap();
+            let listener = bind_listener(bind).unwrap_or_else(|e| panic!("bind {}: {}", ip, e));
+            let port = listener.local_addr().unwrap().port();
+            urls.push(format!("http://{}/lan/ping", super::peer_addr::host_port(ip, port)));
+            listeners.push(listener);
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = {
+            let stop = stop.clone();
+            std::thread::spawn(move || serve(listeners, stop, Arc::new(LanSyncState::new())))
+        };
+        for url in &urls {
+            let stream = std::net::TcpStream::connect(url_to_addr(url).unwrap()).unwrap();
+            let body = http_request(stream, "GET", url, None, None, "").unwrap();
+            let ping: serde_json::Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(ping["ok"], true, "{}", url);
+        }
+        stop.store(true, Ordering::Relaxed);
+        server.join().unwrap();
+    }
 }
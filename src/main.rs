@@ -12,8 +12,10 @@ use lan_server::SyncGuard;
 mod activity;
 mod config;
 mod i18n;
+mod key_rotation;
 mod lan_common;
 mod lan_discovery;
+mod lan_mdns;
 mod lan_pair_throttle;
 mod lan_pairing;
 mod lan_server;
@@ -23,13 +25,21 @@ mod monitor;
 #[cfg(target_os = "macos")]
 #[path = "monitor_macos.rs"]
 mod monitor;
+#[cfg(test)]
+mod online_harness;
 mod online_sync;
 mod platform;
+mod remote_storage;
 mod sftp_client;
 mod storage;
 mod title_parser;
 mod sync_common;
 mod sync_encryption;
+#[cfg(test)]
+mod sync_harness;
+mod sync_history;
+mod sync_passphrase;
+mod sync_preview;
 mod tombstone_triggers;
 #[cfg(target_os = "macos")]
 mod sync_trigger;
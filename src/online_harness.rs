@@ -0,0 +1,272 @@
+//! In-process online sync harness: two [`Node`]s from the LAN harness sync
+//! through a `timeflow-relay` on a loopback port — session sync (master /
+//! slave via the relay's 13-step coordination) and async delta packages,
+//! with the relay's HTTP storage standing in for SFTP, or a shared folder as
+//! the devices' own storage backend.
+#![cfg(test)]
+
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::{self, OnlineStorageSettings, OnlineSyncSettings};
+use crate::online_sync;
+use crate::sync_harness::{assert_converged, harness_lock, Node};
+
+const AUTH_TOKEN: &str = "relay-test-token";
+const ENCRYPTION_KEY: &str = "relay-test-encryption-key";
+
+struct Relay {
+    url: String,
+    dir: PathBuf,
+    stop: Arc<AtomicBool>,
+    server: Option<JoinHandle<()>>,
+    /// Storage backend both devices are configured with.
+    storage: OnlineStorageSettings,
+    /// End-to-end passphrase both devices are configured with.
+    passphrase: String,
+}
+
+impl Relay {
+    fn start() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "timeflow-online-harness-relay-{}-{}",
+            std::process::id(),
+            nanos
+        ));
+        let mut relay_config = timeflow_relay::RelayConfig::new(dir.clone());
+        relay_config.accounts.push(timeflow_relay::Account {
+            name: "harness".to_string(),
+            token: AUTH_TOKEN.to_string(),
+            encryption_key: ENCRYPTION_KEY.to_string(),
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind loopback");
+        let url = format!("http://{}", listener.local_addr().expect("relay addr"));
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                timeflow_relay::serve(listener, relay_config, stop).expect("relay serve");
+            })
+        };
+        Self {
+            url,
+            dir,
+            stop,
+            server: Some(server),
+            storage: OnlineStorageSettings::default(),
+            passphrase: String::new(),
+        }
+    }
+
+    fn settings(&self, node: &Node, sync_mode: &str) -> OnlineSyncSettings {
+        OnlineSyncSettings {
+            enabled: true,
+            server_url: self.url.clone(),
+            auth_token: AUTH_TOKEN.to_string(),
+            device_id: node.device_id(),
+            encryption_key: ENCRYPTION_KEY.to_string(),
+            sync_mode: sync_mode.to_string(),
+            group_id: "harness-group".to_string(),
+            storage: self.storage.clone(),
+            passphrase: self.passphrase.clone(),
+            ..OnlineSyncSettings::default()
+        }
+    }
+
+    /// Entries left in the relay's blob storage under `kind` ("sessions" /
+    /// "packages").
+    fn stored(&self, kind: &str) -> usize {
+        std::fs::read_dir(self.dir.join("blobs").join(kind))
+            .map(|entries| entries.count())
+            .unwrap_or(0)
+    }
+}
+
+impl Drop for Relay {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(server) = self.server.take() {
+            let _ = server.join();
+        }
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Runs `run_online_sync` on both nodes at once; `first` opens the session
+/// and becomes master, `second` joins as slave.
+fn session_sync(relay: &Relay, first: &Node, second: &Node) {
+    std::thread::scope(|scope| {
+        for (node, delay) in [(first, 0), (second, 300)] {
+            let settings = relay.settings(node, "session");
+            let (dir, state) = (node.dir.clone(), node.state.clone());
+            scope.spawn(move || {
+                config::set_data_dir_override(Some(dir));
+                std::thread::sleep(Duration::from_millis(delay));
+                online_sync::run_online_sync(settings, state, Arc::new(AtomicBool::new(false)));
+            });
+        }
+    });
+}
+
+fn async_sync(relay: &Relay, node: &Node) {
+    let settings = relay.settings(node, "async");
+    let _dir = node.enter();
+    online_sync::run_async_delta_sync(
+        settings.clone(),
+        node.state.clone(),
+        &settings.group_id,
+        Arc::new(AtomicBool::new(false)),
+    );
+}
+
+#[test]
+fn session_sync_through_relay_converges_both_devices() {
+    let _lock = harness_lock();
+    let relay = Relay::start();
+    let a = Node::start("a", "A");
+    let b = Node::start("b", "B");
+
+    session_sync(&relay, &a, &b);
+
+    assert_converged(&a, &b);
+    let union = b.snapshot();
+    assert!(union.iter().any(|row| row.starts_with("P|A-proj-1|")));
+    assert!(union.iter().any(|row| row.starts_with("P|B-proj-1|")));
+    for (node, role) in [(&a, "master"), (&b, "slave")] {
+        assert!(
+            !node.state.db_frozen.load(Ordering::SeqCst),
+            "{} left frozen",
+            role
+        );
+        assert_eq!(
+            node.count(
+                "SELECT COUNT(*) FROM sync_runs
+                 WHERE kind = 'online' AND status = 'ok' AND mode = 'full' AND role = ?1",
+                role
+            ),
+            1,
+            "{} run not recorded as ok",
+            role
+        );
+    }
+    assert_eq!(
+        relay.stored("sessions"),
+        0,
+        "completed session storage not cleaned up"
+    );
+}
+
+#[test]
+fn async_delta_package_reaches_the_other_device() {
+    let _lock = harness_lock();
+    let relay = Relay::start();
+    let a = Node::start("a", "A");
+    let b = Node::start("b", "B");
+
+    // A has nothing pending and pushes its changes; B pulls and merges them,
+    // then pushes its own.
+    async_sync(&relay, &a);
+    assert_eq!(relay.stored("packages"), 1);
+    async_sync(&relay, &b);
+
+    let merged = b.snapshot();
+    assert!(merged.iter().any(|row| row.starts_with("P|A-proj-1|")));
+    assert!(merged.iter().any(|row| row.starts_with("P|B-proj-1|")));
+    assert!(!a
+        .snapshot()
+        .iter()
+        .any(|row| row.starts_with("P|B-proj-1|")));
+    assert_eq!(
+        b.count(
+            "SELECT COUNT(*) FROM sync_runs
+             WHERE kind = 'online' AND status = 'ok' AND rows_inserted > 0 AND mode = ?1",
+            "async"
+        ),
+        1
+    );
+    // A's package was delivered to the whole group and dropped; B's waits for A.
+    assert_eq!(relay.stored("packages"), 1);
+}
+
+#[test]
+fn shared_folder_backend_carries_async_and_session_transfers() {
+    let _lock = harness_lock();
+    let mut relay = Relay::start();
+    let shared = relay.dir.with_extension("shared");
+    relay.storage = OnlineStorageSettings {
+        backend: "local".to_string(),
+        local_path: shared.to_string_lossy().to_string(),
+        prefix: "team".to_string(),
+        ..OnlineStorageSettings::default()
+    };
+    let a = Node::start("a", "A");
+    let b = Node::start("b", "B");
+
+    // The relay only coordinates — A's package lands in the shared folder and
+    // is listed for B without ever reaching the relay's blob storage.
+    async_sync(&relay, &a);
+    assert_eq!(relay.stored("packages"), 0);
+    assert_eq!(
+        std::fs::read_dir(shared.join("team/packages"))
+            .map(|entries| entries.count())
+            .unwrap_or(0),
+        1
+    );
+    async_sync(&relay, &b);
+    assert!(b
+        .snapshot()
+        .iter()
+        .any(|row| row.starts_with("P|A-proj-1|")));
+
+    session_sync(&relay, &a, &b);
+    assert_converged(&a, &b);
+    assert!(
+        !shared.join("team/sessions").exists(),
+        "session blobs left in the shared folder"
+    );
+    let _ = std::fs::remove_dir_all(&shared);
+}
+
+#[test]
+fn passphrase_devices_sync_with_blobs_the_relay_cannot_read() {
+    let _lock = harness_lock();
+    let mut relay = Relay::start();
+    relay.passphrase = "correct horse battery staple".to_string();
+    let a = Node::start("a", "A");
+    let b = Node::start("b", "B");
+
+    async_sync(&relay, &a);
+    let package_dir = std::fs::read_dir(relay.dir.join("blobs/packages"))
+        .expect("package stored")
+        .next()
+        .expect("one package")
+        .expect("package entry")
+        .path();
+    let blob = std::fs::read(package_dir.join("delta.enc")).expect("package blob");
+    assert!(crate::sync_passphrase::is_sealed(&blob));
+
+    // B has no key yet and adopts A's from the package header.
+    async_sync(&relay, &b);
+    assert!(b
+        .snapshot()
+        .iter()
+        .any(|row| row.starts_with("P|A-proj-1|")));
+    let key_file = |node: &Node| {
+        std::fs::read_to_string(node.dir.join("sync_passphrase_key.json")).expect("key file")
+    };
+    let key_id =
+        |raw: String| serde_json::from_str::<serde_json::Value>(&raw).unwrap()["active"].clone();
+    assert_eq!(key_id(key_file(&a)), key_id(key_file(&b)));
+
+    session_sync(&relay, &a, &b);
+    assert_converged(&a, &b);
+}
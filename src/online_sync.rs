@@ -1,22 +1,34 @@
 //! Online Sync Orchestrator — 13-step state machine using server coordination + SFTP transfer.
 
 use crate::config;
+use crate::key_rotation;
 use crate::lan_common;
 use crate::lan_common::sync_log;
 use crate::lan_server::LanSyncState;
-use crate::sftp_client::SftpClient;
+use crate::remote_storage::{self, RemoteStorage};
 use crate::sync_common;
 use crate::sync_encryption;
+use crate::sync_history::RunStatus;
+use crate::sync_passphrase;
+use crate::sync_preview::{self, SyncPreview};
 use serde::Deserialize;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
+use timeflow_shared::sync_protocol::{self, ProtocolInfo, SyncFeatures};
 
+#[cfg(not(test))]
 const POLL_INTERVAL: Duration = Duration::from_secs(3);
+// The harness runs both devices in-process against timeflow-relay.
+#[cfg(test)]
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
 const SYNC_TIMEOUT: Duration = Duration::from_secs(1800); // 30 min
 const MAX_POLL_ATTEMPTS: u32 = 200; // ~10 min at 3s intervals
+#[cfg(not(test))]
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+#[cfg(test)]
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(200);
 const MAX_RETRIES: u32 = 3;
 // Exponential backoff: 5s × 3^attempt → 5s, 15s, 45s (max total ~65s with jitter)
 const RETRY_BASE_DELAY: Duration = Duration::from_secs(5);
@@ -33,6 +45,10 @@ struct SessionCreateResponse {
     status: String,
     #[serde(rename = "syncMode")]
     sync_mode: Option<String>,
+    /// The other device's protocol announcement, relayed by the server.
+    /// Absent while no peer has joined and on servers that don't relay it.
+    #[serde(rename = "peerProtocol", default)]
+    peer_protocol: Option<ProtocolInfo>,
 }
 
 #[derive(Deserialize)]
@@ -44,6 +60,8 @@ struct SessionStatusResponse {
     sync_mode: Option<String>,
     #[serde(rename = "storageCredentials")]
     storage_credentials: Option<StorageCredentialsWrapper>,
+    #[serde(rename = "peerProtocol", default)]
+    peer_protocol: Option<ProtocolInfo>,
 }
 
 #[derive(Deserialize)]
@@ -176,11 +194,15 @@ fn create_session(
     table_hashes: Option<&str>,
     force_full: bool,
 ) -> Result<SessionCreateResponse, String> {
+    let protocol = ProtocolInfo::local();
     let body = serde_json::json!({
         "deviceId": device_id,
         "markerHash": marker_hash,
         "tableHashes": table_hashes.map(|h| serde_json::json!({"combined": h})),
         "forceFullSync": force_full,
+        "syncProtocol": protocol.sync_protocol,
+        "minSyncProtocol": protocol.min_sync_protocol,
+        "capabilities": protocol.capabilities,
     });
     let resp = server_post(
         server_url,
@@ -280,7 +302,7 @@ fn wait_for_peer(
     _sync_state: &LanSyncState,
     stop_signal: &AtomicBool,
     sync_start: Instant,
-) -> Result<(String, Option<StorageCredentialsWrapper>), String> {
+) -> Result<(String, Option<StorageCredentialsWrapper>, Option<ProtocolInfo>), String> {
     for _ in 0..MAX_POLL_ATTEMPTS {
         check_timeout_and_stop(sync_start, stop_signal)?;
         thread::sleep(POLL_INTERVAL);
@@ -289,11 +311,17 @@ fn wait_for_peer(
         }
 
         let status = poll_status(server_url, token, session_id, device_id)?;
+        if matches!(status.status.as_str(), "failed" | "cancelled" | "expired") {
+            return Err(format!("Session ended: {}", status.status));
+        }
         if status.status != "awaiting_peer" {
-            let mode = status
-                .sync_mode
-                .unwrap_or_else(|| "full".to_string());
-            return Ok((mode, status.storage_credentials));
+            // A peer with identical data completes the session right away.
+            let mode = if status.status == "completed" {
+                "none".to_string()
+            } else {
+                status.sync_mode.unwrap_or_else(|| "full".to_string())
+            };
+            return Ok((mode, status.storage_credentials, status.peer_protocol));
         }
     }
     Err("Timeout waiting for peer".to_string())
@@ -385,6 +413,7 @@ fn async_push(
     base_marker_hash: Option<&str>,
     new_marker_hash: &str,
     file_size_bytes: usize,
+    storage_backend: &str,
 ) -> Result<AsyncPushResponse, String> {
     let body = serde_json::json!({
         "deviceId": device_id,
@@ -392,6 +421,7 @@ fn async_push(
         "baseMarkerHash": base_marker_hash,
         "newMarkerHash": new_marker_hash,
         "fileSizeBytes": file_size_bytes,
+        "storageBackend": storage_backend,
     });
     let resp = server_post(server_url, "/api/sync/async/push", token, &body.to_string())?;
     serde_json::from_str(&resp).map_err(|e| format!("Parse async push response: {}", e))
@@ -461,8 +491,59 @@ fn async_reject(
     Ok(())
 }
 
+/// Opens server-sealed storage credentials with the keyring and remembers the
+/// key that did it, so key rotations never prune it (`sync_keyring`).
+fn open_credentials(
+    encrypted: &sync_encryption::EncryptedCredentials,
+    session_id: &str,
+    settings: &config::OnlineSyncSettings,
+) -> Result<sync_encryption::SftpCredentials, String> {
+    let keyring = settings.keyring();
+    let (creds, key) = sync_encryption::open_credentials_keyring(encrypted, session_id, &keyring)?;
+    if let Err(e) = timeflow_shared::sync_keyring::note_relay_key(key) {
+        sync_log(&format!("[sync] Nie zapisano klucza relay: {}", e));
+    }
+    Ok(creds)
+}
+
 // ── Async delta orchestrator ──
 
+/// With a passphrase the end-to-end layer replaces the server-issued file key
+/// (which the server can read); otherwise the file key is used as before,
+/// inside the keyed envelope so a mismatched key is reported by id.
+fn encrypt_blob(passphrase: &str, data: &[u8], file_key: &str) -> Result<Vec<u8>, String> {
+    if passphrase.is_empty() {
+        sync_encryption::encrypt_file_data_keyed(data, file_key)
+    } else {
+        sync_passphrase::seal(data, passphrase)
+    }
+}
+
+/// Both sides must agree: a passphrase device refuses blobs the server could
+/// have written, and a device without one cannot read sealed blobs.
+fn decrypt_blob(passphrase: &str, blob: &[u8], file_key: &str) -> Result<Vec<u8>, String> {
+    match (passphrase.is_empty(), sync_passphrase::is_sealed(blob)) {
+        (true, false) => sync_encryption::decrypt_file_data(blob, file_key),
+        (false, true) => sync_passphrase::open(blob, passphrase),
+        (true, true) => Err(
+            "Peer encrypts sync data with a passphrase — set the same passphrase on this device"
+                .to_string(),
+        ),
+        (false, false) => Err(
+            "Peer sent data without passphrase encryption — set the same passphrase on every device"
+                .to_string(),
+        ),
+    }
+}
+
+/// Deletes a session blob once its reader has it. Only custom storage
+/// backends do anything here; a failure just leaves the file behind.
+fn discard_blob(storage: &dyn RemoteStorage, remote_path: &str) {
+    if let Err(e) = storage.remove(remote_path) {
+        sync_log(&format!("Nie udało się usunąć {} z magazynu: {}", remote_path, e));
+    }
+}
+
 /// Execute async delta push: export local changes, encrypt, upload to storage.
 fn execute_async_push(
     settings: &config::OnlineSyncSettings,
@@ -509,27 +590,25 @@ fn execute_async_push(
         local_marker.as_deref(),
         &new_marker,
         delta_size,
+        &settings.storage.backend,
     )?;
 
     // Decrypt storage credentials and upload
     if let Some(creds_wrapper) = &push_resp.storage_credentials {
-        let creds = sync_encryption::decrypt_credentials(
-            &creds_wrapper.encrypted,
-            &push_resp.package_id,
-            &settings.encryption_key,
-        )?;
+        let creds = open_credentials(&creds_wrapper.encrypted, &push_resp.package_id, settings)?;
 
         // Encrypt delta data
         let file_key = &creds.file_encryption_key;
-        let encrypted_delta = sync_encryption::encrypt_file_data(delta_json.as_bytes(), file_key)?;
+        let encrypted_delta = encrypt_blob(&settings.passphrase, delta_json.as_bytes(), file_key)?;
+        sync_state.note_sync_run(|run| run.bytes_sent += encrypted_delta.len() as u64);
 
         // Upload via SFTP
         sync_state.set_progress(2, "async_push_uploading", "upload");
         sync_log(&format!("[async-push] Uploading {} bytes to storage...", encrypted_delta.len()));
-        let sftp = SftpClient::new(&creds.host, creds.port, &creds.username, &creds.password);
+        let sftp = remote_storage::connect(&settings.storage, &creds)?;
         let remote_path = format!("{}delta.enc", creds.upload_path);
         with_retry("SFTP upload (async delta)", || {
-            sftp.upload_data(&encrypted_delta, &remote_path, |sent, total| {
+            sftp.upload_data(&encrypted_delta, &remote_path, &|sent, total| {
                 sync_state.update_transfer_bytes(sent, total);
             })
         })?;
@@ -553,6 +632,72 @@ fn execute_async_push(
     Ok(())
 }
 
+/// Publish a key-rotation message as an async package. It has no base marker
+/// (it applies to any database state) and inserts no local marker — receivers
+/// install the key instead of merging, see `key_rotation`.
+pub fn push_key_package(
+    settings: &config::OnlineSyncSettings,
+    group_id: &str,
+    message: &str,
+) -> Result<String, String> {
+    let device_id = settings.effective_device_id();
+    let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let marker = sync_common::generate_marker_hash_simple("key-rotation", &timestamp, &device_id);
+    let push_resp = async_push(
+        &settings.server_url,
+        &settings.auth_token,
+        &device_id,
+        group_id,
+        None,
+        &marker,
+        message.len(),
+        &settings.storage.backend,
+    )?;
+    let creds_wrapper = push_resp
+        .storage_credentials
+        .ok_or_else(|| "No storage credentials returned for key package".to_string())?;
+    let creds = open_credentials(&creds_wrapper.encrypted, &push_resp.package_id, settings)?;
+    let blob = encrypt_blob(&settings.passphrase, message.as_bytes(), &creds.file_encryption_key)?;
+    let storage = remote_storage::connect(&settings.storage, &creds)?;
+    let remote_path = format!("{}delta.enc", creds.upload_path);
+    with_retry("Upload (key package)", || {
+        storage.upload_data(&blob, &remote_path, &|_, _| {})
+    })?;
+    sync_log(&format!("[key-rotation] Key package {} registered", &push_resp.package_id[..8]));
+    Ok(push_resp.package_id)
+}
+
+/// Fetch storage credentials for a pending package, download and decrypt its delta.
+fn download_async_package(
+    settings: &config::OnlineSyncSettings,
+    sync_state: &LanSyncState,
+    device_id: &str,
+    package_id: &str,
+) -> Result<String, String> {
+    // Request credentials for this package via server
+    sync_state.set_progress(3, "async_pull_downloading", "download");
+    sync_log("[async-pull] Requesting storage credentials...");
+
+    let creds_resp = async_get_credentials(&settings.server_url, &settings.auth_token, device_id, package_id)?;
+    let creds = match creds_resp.storage_credentials {
+        Some(cw) => open_credentials(&cw.encrypted, package_id, settings)?,
+        None => return Err("No storage credentials for async pull".to_string()),
+    };
+
+    sync_log("[async-pull] Downloading delta from storage...");
+    let sftp = remote_storage::connect(&settings.storage, &creds)?;
+    let remote_path = format!("{}delta.enc", creds.upload_path);
+    let encrypted = with_retry("SFTP download (async delta)", || {
+        sftp.download_data(&remote_path, &|sent, total| {
+            sync_state.update_transfer_bytes(sent, total);
+        })
+    })?;
+    sync_state.note_sync_run(|run| run.bytes_received += encrypted.len() as u64);
+    let file_key = &creds.file_encryption_key;
+    let delta_data = decrypt_blob(&settings.passphrase, &encrypted, file_key)?;
+    String::from_utf8(delta_data).map_err(|e| format!("Invalid UTF-8 in delta: {}", e))
+}
+
 /// Execute async delta pull: check for pending packages, download, decrypt, merge.
 fn execute_async_pull(
     settings: &config::OnlineSyncSettings,
@@ -602,42 +747,35 @@ fn execute_async_pull(
             }
         }
 
-        // Request credentials for this package via server
-        sync_state.set_progress(3, "async_pull_downloading", "download");
-        sync_log("[async-pull] Requesting storage credentials...");
-
-        let creds_resp = async_get_credentials(server_url, token, &device_id, &pkg.id)?;
-        let creds = match creds_resp.storage_credentials {
-            Some(cw) => sync_encryption::decrypt_credentials(
-                &cw.encrypted,
-                &pkg.id,
-                &settings.encryption_key,
-            )?,
-            None => return Err("No storage credentials for async pull".to_string()),
-        };
+        let delta_str = download_async_package(settings, sync_state, &device_id, &pkg.id)?;
 
-        sync_log("[async-pull] Downloading delta from storage...");
-        let sftp = SftpClient::new(&creds.host, creds.port, &creds.username, &creds.password);
-        let remote_path = format!("{}delta.enc", creds.upload_path);
-        let encrypted = with_retry("SFTP download (async delta)", || {
-            sftp.download_data(&remote_path, |sent, total| {
-                sync_state.update_transfer_bytes(sent, total);
-            })
-        })?;
-        let file_key = &creds.file_encryption_key;
-        let delta_data = sync_encryption::decrypt_file_data(&encrypted, file_key)?;
+        // Key packages carry a rotated encryption_key, not data
+        if let Some(rotation) = key_rotation::parse(&delta_str) {
+            match key_rotation::install(&rotation, &settings.keyring()) {
+                Ok(_) => {
+                    async_ack(server_url, token, &device_id, &pkg.id)?;
+                    sync_log(&format!("[async-pull] Key package {} installed", &pkg.id[..8]));
+                }
+                Err(e) => {
+                    sync_log(&format!("[async-pull] Key package {} rejected: {}", &pkg.id[..8], e));
+                    async_reject(server_url, token, &device_id, &pkg.id, &format!("key_rotation_failed: {}", e))?;
+                }
+            }
+            continue;
+        }
 
         // Merge delta
         sync_state.set_progress(4, "async_pull_merging", "local");
         sync_log("[async-pull] Merging delta data...");
-        let delta_str = String::from_utf8(delta_data)
-            .map_err(|e| format!("Invalid UTF-8 in delta: {}", e))?;
 
-        if let Err(e) = sync_common::merge_incoming_data(&mut conn, &delta_str) {
-            sync_log(&format!("[async-pull] Merge failed: {} — restoring backup", e));
-            sync_common::restore_database_backup_typed(&mut conn, "online")?;
-            async_reject(server_url, token, &device_id, &pkg.id, &format!("merge_failed: {}", e))?;
-            return Err(format!("Async merge failed: {}", e));
+        match sync_common::merge_incoming_data_with_stats(&mut conn, &delta_str) {
+            Ok(stats) => sync_state.note_sync_run(|run| run.add_merge(&stats)),
+            Err(e) => {
+                sync_log(&format!("[async-pull] Merge failed: {} — restoring backup", e));
+                sync_common::restore_database_backup_typed(&mut conn, "online")?;
+                async_reject(server_url, token, &device_id, &pkg.id, &format!("merge_failed: {}", e))?;
+                return Err(format!("Async merge failed: {}", e));
+            }
         }
 
         // Verify integrity
@@ -670,6 +808,53 @@ fn execute_async_pull(
     Ok(true)
 }
 
+/// Dry-run of `execute_async_pull`: downloads the pending packages and previews
+/// them as a single merge. Nothing is merged, acknowledged or rejected.
+fn preview_async_pull(
+    settings: &config::OnlineSyncSettings,
+    sync_state: &LanSyncState,
+    group_id: &str,
+) -> Result<SyncPreview, String> {
+    let device_id = settings.effective_device_id();
+
+    sync_state.set_progress(1, "async_pull_checking", "download");
+    sync_log("[async-pull] DRY-RUN — sprawdzanie oczekujacych paczek...");
+    let pending = async_pending(&settings.server_url, &settings.auth_token, &device_id, group_id)?;
+    if pending.packages.is_empty() {
+        sync_log("[async-pull] DRY-RUN — brak oczekujacych paczek");
+        return Ok(SyncPreview::default());
+    }
+
+    let mut conn = lan_common::open_dashboard_db()?;
+    let local_marker: Option<String> = conn
+        .query_row(
+            "SELECT marker_hash FROM sync_markers ORDER BY created_at DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .ok();
+
+    let mut deltas = Vec::with_capacity(pending.packages.len());
+    for pkg in &pending.packages {
+        if let Some(ref base) = pkg.base_marker_hash {
+            if local_marker.as_deref() != Some(base) {
+                return Err("base_marker_mismatch — a real sync would fall back to session sync".to_string());
+            }
+        }
+        let delta = download_async_package(settings, sync_state, &device_id, &pkg.id)?;
+        if key_rotation::parse(&delta).is_none() {
+            deltas.push(delta);
+        }
+    }
+
+    sync_state.set_progress(4, "previewing", "local");
+    sync_log(&format!("[async-pull] DRY-RUN — podglad scalania {} paczek...", deltas.len()));
+    let combined = sync_preview::combine_archives(&deltas)?;
+    let preview = sync_common::preview_incoming_data(&mut conn, &combined)?;
+    sync_state.note_sync_run(|run| run.add_merge(&preview));
+    Ok(preview)
+}
+
 /// Run async delta sync: pull pending packages first, then push local changes.
 pub fn run_async_delta_sync(
     settings: config::OnlineSyncSettings,
@@ -685,6 +870,8 @@ pub fn run_async_delta_sync(
         !settings.encryption_key.is_empty(),
     ));
     sync_state.set_sync_type("online");
+    sync_state.begin_sync_run("online", "", &settings.server_url, false);
+    sync_state.note_sync_run(|run| run.mode = "async".to_string());
     sync_state.set_progress(1, "async_delta", "local");
 
     match execute_async_pull(&settings, &sync_state, group_id) {
@@ -695,12 +882,14 @@ pub fn run_async_delta_sync(
         }
         Err(e) if e.contains("base_marker_mismatch") => {
             sync_log("[async] Base marker mismatch — falling back to session sync");
+            sync_state.finish_sync_run(RunStatus::Error, Some(&e));
             sync_state.reset_progress();
             run_online_sync(settings, sync_state, stop_signal);
             return;
         }
         Err(e) => {
             sync_log(&format!("[async] Pull error: {}", e));
+            sync_state.finish_sync_run(RunStatus::Error, Some(&e));
             sync_state.unfreeze();
             sync_state.reset_progress();
             return;
@@ -710,9 +899,11 @@ pub fn run_async_delta_sync(
     match execute_async_push(&settings, &sync_state, group_id) {
         Ok(()) => {
             sync_log("=== ASYNC DELTA SYNC ZAKOŃCZONY ===");
+            sync_state.finish_sync_run(RunStatus::Ok, None);
         }
         Err(e) => {
             sync_log(&format!("[async] Push error: {}", e));
+            sync_state.finish_sync_run(RunStatus::Error, Some(&e));
         }
     }
 
@@ -739,6 +930,7 @@ pub fn run_online_sync(
         settings.sync_mode,
     ));
     sync_state.set_sync_type("online");
+    sync_state.begin_sync_run("online", "", &settings.server_url, false);
     sync_state.set_progress(1, "creating_session", "local");
 
     let server_url = settings.server_url.clone();
@@ -754,12 +946,15 @@ pub fn run_online_sync(
         &stop_signal,
         &mut session_id_for_cleanup,
         false,
+        false,
     ) {
-        Ok(()) => {
+        Ok(_) => {
             sync_log("=== ONLINE SYNC ZAKOŃCZONY ===");
+            sync_state.finish_sync_run(RunStatus::Ok, None);
         }
         Err(e) => {
             sync_log(&format!("=== ONLINE SYNC BŁĄD: {} ===", e));
+            sync_state.finish_sync_run(RunStatus::Error, Some(&e));
             sync_state.unfreeze();
             sync_state.reset_progress();
             // Try to cancel session on server (best-effort)
@@ -794,6 +989,7 @@ pub fn run_online_sync_forced(
         settings.sync_mode,
     ));
     sync_state.set_sync_type("online");
+    sync_state.begin_sync_run("online", "", &settings.server_url, false);
     sync_state.set_progress(1, "creating_session", "local");
 
     let server_url = settings.server_url.clone();
@@ -808,12 +1004,15 @@ pub fn run_online_sync_forced(
         &stop_signal,
         &mut session_id_for_cleanup,
         force_full,
+        false,
     ) {
-        Ok(()) => {
+        Ok(_) => {
             sync_log("=== ONLINE SYNC ZAKOŃCZONY ===");
+            sync_state.finish_sync_run(RunStatus::Ok, None);
         }
         Err(e) => {
             sync_log(&format!("=== ONLINE SYNC BŁĄD: {} ===", e));
+            sync_state.finish_sync_run(RunStatus::Error, Some(&e));
             sync_state.unfreeze();
             sync_state.reset_progress();
             if let Some(sid) = &session_id_for_cleanup {
@@ -826,13 +1025,69 @@ pub fn run_online_sync_forced(
     sync_state.set_role("undecided");
 }
 
+/// Dry-run of online sync. Async mode previews the pending delta packages;
+/// session mode runs the session up to the master's download of the peer data,
+/// previews the merge and cancels the session. A dry-run that lands in the
+/// slave role stops before uploading anything — only the master merges.
+pub fn run_online_sync_dry_run(
+    settings: config::OnlineSyncSettings,
+    sync_state: Arc<LanSyncState>,
+    stop_signal: Arc<AtomicBool>,
+) {
+    ONLINE_SYNC_CANCEL_REQUESTED.store(false, Ordering::SeqCst);
+    sync_log(&format!("=== START ONLINE SYNC [DRY-RUN] (sync_mode={}) ===", settings.sync_mode));
+    sync_state.set_sync_type("online");
+    sync_state.set_progress(1, "creating_session", "local");
+    sync_state.begin_sync_preview("online", &settings.server_url);
+    sync_state.begin_sync_run("online", "", &settings.server_url, true);
+    if settings.sync_mode == "async" {
+        sync_state.note_sync_run(|run| run.mode = "async".to_string());
+    }
+
+    let server_url = settings.server_url.clone();
+    let token = settings.auth_token.clone();
+    let device_id = settings.effective_device_id();
+
+    let result = if settings.sync_mode == "async" && !settings.group_id.is_empty() {
+        preview_async_pull(&settings, &sync_state, &settings.group_id)
+    } else {
+        let mut session_id: Option<String> = None;
+        let result = execute_online_sync(&settings, &sync_state, &stop_signal, &mut session_id, false, true)
+            .and_then(|preview| preview.ok_or_else(|| "dry-run preview not collected".to_string()));
+        // The session never completes in a dry-run — always release it.
+        if let Some(sid) = &session_id {
+            let reason = result.as_ref().err().map(String::as_str).unwrap_or("dry_run");
+            cancel_session(&server_url, &token, sid, &device_id, reason).ok();
+        }
+        result
+    };
+
+    match &result {
+        Ok(preview) => sync_log(&format!(
+            "=== ONLINE DRY-RUN ZAKOŃCZONY: {} zmian ===",
+            preview.total_changes()
+        )),
+        Err(e) => sync_log(&format!("=== ONLINE DRY-RUN BŁĄD: {} ===", e)),
+    }
+    match &result {
+        Ok(_) => sync_state.finish_sync_run(RunStatus::Ok, None),
+        Err(e) => sync_state.finish_sync_run(RunStatus::Error, Some(e)),
+    }
+    sync_state.finish_sync_preview(result);
+    sync_state.unfreeze();
+    sync_state.reset_progress();
+    sync_state.sync_in_progress.store(false, Ordering::SeqCst);
+    sync_state.set_role("undecided");
+}
+
 fn execute_online_sync(
     settings: &config::OnlineSyncSettings,
     sync_state: &LanSyncState,
     stop_signal: &AtomicBool,
     session_id_out: &mut Option<String>,
     force_full: bool,
-) -> Result<(), String> {
+    dry_run: bool,
+) -> Result<Option<SyncPreview>, String> {
     let server_url = &settings.server_url;
     let token = &settings.auth_token;
     let device_id = settings.effective_device_id();
@@ -873,9 +1128,10 @@ fn execute_online_sync(
         || create_resp.sync_mode.as_deref() == Some("none")
     {
         sync_log("[1/13] Sync niepotrzebna — bazy identyczne");
+        sync_state.note_sync_run(|run| run.mode = "none".to_string());
         sync_state.set_progress(13, "not_needed", "local");
         sync_state.sync_in_progress.store(false, Ordering::SeqCst);
-        return Ok(());
+        return Ok(dry_run.then(SyncPreview::default));
     }
 
     let session_id = create_resp.session_id;
@@ -888,10 +1144,17 @@ fn execute_online_sync(
         my_role
     ));
     sync_state.set_role(&my_role);
+    sync_state.note_sync_run(|run| run.role = my_role.clone());
+    if dry_run && my_role != "master" {
+        // The slave uploads its data for the master to merge — a dry-run must
+        // not hand anything over, and it has nothing to preview on its own.
+        sync_log("[1/13] DRY-RUN — rola slave, przerywam przed wysylka danych");
+        return Err("dry_run_requires_master: another device already opened a sync session".to_string());
+    }
 
     // Step 2: Wait for peer if master, or proceed if slave
     sync_state.set_progress(2, "awaiting_peer", "local");
-    let (sync_mode, storage_creds) = if create_resp.status == "awaiting_peer" {
+    let (sync_mode, storage_creds, peer_protocol) = if create_resp.status == "awaiting_peer" {
         // We're master, wait for slave to join
         sync_log("[2/13] Oczekiwanie na drugiego klienta...");
         wait_for_peer(
@@ -916,9 +1179,35 @@ fn execute_online_sync(
                 .sync_mode
                 .unwrap_or_else(|| "full".to_string()),
             creds,
+            create_resp.peer_protocol.or(status.peer_protocol),
         )
     };
 
+    if sync_mode == "none" && !force_full {
+        sync_log("[2/13] Sync niepotrzebna — bazy identyczne");
+        sync_state.note_sync_run(|run| run.mode = "none".to_string());
+        sync_state.set_progress(13, "not_needed", "local");
+        return Ok(dry_run.then(SyncPreview::default));
+    }
+
+    // Online sync never had a version gate: without a relayed announcement
+    // (older server or peer) both sides keep exchanging the full archive.
+    let features = match peer_protocol {
+        Some(ref peer) => sync_protocol::negotiate(crate::VERSION.trim(), None, Some(peer))
+            .map_err(|e| {
+                sync_log(&format!("[2/13] PRZERWANE — {}", e));
+                e
+            })?,
+        None => SyncFeatures::all(),
+    };
+    let missing = features.missing();
+    if !missing.is_empty() {
+        sync_log(&format!(
+            "[2/13] Peer nie obsługuje: {} — pomijam te dane",
+            missing.join(", ")
+        ));
+    }
+
     // Override sync mode if force_full requested
     let sync_mode = if force_full {
         sync_log("[2/13] Force full override — wymuszam tryb 'full'");
@@ -926,6 +1215,7 @@ fn execute_online_sync(
     } else {
         sync_mode
     };
+    sync_state.note_sync_run(|run| run.mode = sync_mode.clone());
 
     // Step 3-4: Negotiate — get SFTP credentials
     sync_state.set_progress(3, "negotiating", "local");
@@ -952,20 +1242,17 @@ fn execute_online_sync(
     if settings.encryption_key.is_empty() {
         return Err("encryption_key not configured in online sync settings".to_string());
     }
-    let decrypted =
-        sync_encryption::decrypt_credentials(&sftp_creds.encrypted, &session_id, &settings.encryption_key)?;
+    let decrypted = open_credentials(&sftp_creds.encrypted, &session_id, settings)?;
     sync_log(&format!(
         "[4/13] Kredencjały SFTP odszyfrowane | host: {}",
         decrypted.host
     ));
     sync_state.set_progress(4, "negotiated", "local");
 
-    let sftp = SftpClient::new(
-        &decrypted.host,
-        decrypted.port,
-        &decrypted.username,
-        &decrypted.password,
-    );
+    let sftp = remote_storage::connect(&settings.storage, &decrypted)?;
+    if settings.storage.backend != "server" {
+        sync_log(&format!("[4/13] Magazyn transferu: {}", settings.storage.backend));
+    }
 
     // Report step 4
     report_step(
@@ -1012,17 +1299,24 @@ fn execute_online_sync(
         &device_id,
         &my_role,
         &sync_mode,
-        &sftp,
+        sftp.as_ref(),
         &decrypted,
+        &settings.passphrase,
         &mut conn,
         sync_state,
         stop_signal,
         sync_start,
+        &features,
+        dry_run,
     );
 
     // Step 13: Unfreeze
     sync_log("[13/13] Odmrażanie bazy...");
     sync_state.unfreeze();
+    if dry_run {
+        // Nothing was written — the caller cancels the session instead of completing it.
+        return result;
+    }
     if result.is_ok() {
         report_step(
             server_url,
@@ -1061,13 +1355,16 @@ fn execute_sync_steps(
     device_id: &str,
     my_role: &str,
     sync_mode: &str,
-    sftp: &SftpClient,
+    sftp: &dyn RemoteStorage,
     creds: &sync_encryption::SftpCredentials,
+    passphrase: &str,
     conn: &mut rusqlite::Connection,
     sync_state: &LanSyncState,
     stop_signal: &AtomicBool,
     sync_start: Instant,
-) -> Result<(), String> {
+    features: &SyncFeatures,
+    dry_run: bool,
+) -> Result<Option<SyncPreview>, String> {
     let file_key = &creds.file_encryption_key;
 
     if my_role == "slave" {
@@ -1076,10 +1373,12 @@ fn execute_sync_steps(
         // Step 6: SLAVE uploads its database
         sync_state.set_progress(6, "uploading_to_storage", "upload");
         sync_log("[6/13] Budowanie eksportu...");
-        let export = sync_common::build_full_export(conn)?;
+        let export = sync_common::build_full_export(conn)
+            .and_then(|json| sync_common::limit_archive_to_features(json, features))?;
         let encrypted =
-            sync_encryption::encrypt_file_data(export.as_bytes(), file_key)?;
+            encrypt_blob(passphrase, export.as_bytes(), file_key)?;
         let remote_file = format!("{}data.enc", creds.upload_path);
+        sync_state.note_sync_run(|run| run.bytes_sent = encrypted.len() as u64);
         sync_log(&format!(
             "[6/13] Wysyłanie {} KB na SFTP...",
             encrypted.len() / 1024
@@ -1089,7 +1388,7 @@ fn execute_sync_steps(
             let remote_ref = &remote_file;
             let encrypted_ref = &encrypted;
             with_retry("SFTP upload (slave)", || {
-                sftp_ref.upload_data(encrypted_ref, remote_ref, |sent, total| {
+                sftp_ref.upload_data(encrypted_ref, remote_ref, &|sent, total| {
                     sync_state.update_transfer_bytes(sent, total);
                 })
             })?;
@@ -1128,26 +1427,31 @@ fn execute_sync_steps(
             let sftp_ref = sftp;
             let remote_ref = &remote_merged;
             with_retry("SFTP download (slave)", || {
-                sftp_ref.download_data(remote_ref, |recv, total| {
+                sftp_ref.download_data(remote_ref, &|recv, total| {
                     sync_state.update_transfer_bytes(recv, total);
                 })
             })?
         };
+        sync_state.note_sync_run(|run| run.bytes_received = merged_encrypted.len() as u64);
+        discard_blob(sftp, &remote_merged);
         let merged_data =
-            sync_encryption::decrypt_file_data(&merged_encrypted, file_key)?;
+            decrypt_blob(passphrase, &merged_encrypted, file_key)?;
         let merged_str = String::from_utf8(merged_data).map_err(|e| e.to_string())?;
 
         // Import merged data with backup restore on error
         sync_log("[12/13] Importowanie scalonych danych...");
         sync_common::backup_database_typed(conn, "online")?;
-        if let Err(e) = sync_common::merge_incoming_data(conn, &merged_str)
-            .and_then(|_| sync_common::verify_merge_integrity(conn))
+        match sync_common::merge_incoming_data_with_stats(conn, &merged_str)
+            .and_then(|stats| sync_common::verify_merge_integrity(conn).map(|_| stats))
         {
-            sync_log(&format!("[12/13] Merge/verify failed: {} — restoring backup", e));
-            sync_common::restore_database_backup_typed(conn, "online").map_err(|re| {
-                format!("Merge failed: {} AND backup restore failed: {}", e, re)
-            })?;
-            return Err(format!("Merge failed (backup restored): {}", e));
+            Ok(stats) => sync_state.note_sync_run(|run| run.add_merge(&stats)),
+            Err(e) => {
+                sync_log(&format!("[12/13] Merge/verify failed: {} — restoring backup", e));
+                sync_common::restore_database_backup_typed(conn, "online").map_err(|re| {
+                    format!("Merge failed: {} AND backup restore failed: {}", e, re)
+                })?;
+                return Err(format!("Merge failed (backup restored): {}", e));
+            }
         }
 
         // Generate new marker
@@ -1208,13 +1512,15 @@ fn execute_sync_steps(
             let sftp_ref = sftp;
             let remote_ref = &remote_file;
             with_retry("SFTP download (master)", || {
-                sftp_ref.download_data(remote_ref, |recv, total| {
+                sftp_ref.download_data(remote_ref, &|recv, total| {
                     sync_state.update_transfer_bytes(recv, total);
                 })
             })?
         };
+        sync_state.note_sync_run(|run| run.bytes_received = slave_encrypted.len() as u64);
+        discard_blob(sftp, &remote_file);
         let slave_data =
-            sync_encryption::decrypt_file_data(&slave_encrypted, file_key)?;
+            decrypt_blob(passphrase, &slave_encrypted, file_key)?;
         let slave_str = String::from_utf8(slave_data).map_err(|e| e.to_string())?;
         report_step(
             server_url,
@@ -1232,6 +1538,14 @@ fn execute_sync_steps(
         ));
         check_timeout_and_stop(sync_start, stop_signal)?;
 
+        if dry_run {
+            sync_state.set_progress(9, "previewing", "local");
+            sync_log("[9/13] DRY-RUN — podglad scalania bez zapisu (bez backupu, markera i wysylki)");
+            let preview = sync_common::preview_incoming_data(conn, &slave_str)?;
+            sync_state.note_sync_run(|run| run.add_merge(&preview));
+            return Ok(Some(preview));
+        }
+
         // Step 8: Backup
         sync_state.set_progress(8, "backing_up", "local");
         sync_log("[8/13] Kopia zapasowa...");
@@ -1250,12 +1564,15 @@ fn execute_sync_steps(
         // Step 9: Merge with backup restore on error
         sync_state.set_progress(9, "merging", "local");
         sync_log("[9/13] Scalanie danych...");
-        if let Err(e) = sync_common::merge_incoming_data(conn, &slave_str) {
-            sync_log(&format!("[9/13] Merge failed: {} — restoring backup", e));
-            sync_common::restore_database_backup_typed(conn, "online").map_err(|re| {
-                format!("Merge failed: {} AND backup restore failed: {}", e, re)
-            })?;
-            return Err(format!("Merge failed (backup restored): {}", e));
+        match sync_common::merge_incoming_data_with_stats(conn, &slave_str) {
+            Ok(stats) => sync_state.note_sync_run(|run| run.add_merge(&stats)),
+            Err(e) => {
+                sync_log(&format!("[9/13] Merge failed: {} — restoring backup", e));
+                sync_common::restore_database_backup_typed(conn, "online").map_err(|re| {
+                    format!("Merge failed: {} AND backup restore failed: {}", e, re)
+                })?;
+                return Err(format!("Merge failed (backup restored): {}", e));
+            }
         }
         report_step(
             server_url,
@@ -1316,16 +1633,18 @@ fn execute_sync_steps(
         // Step 11: Upload merged result for slave
         sync_state.set_progress(11, "uploading_merged", "upload");
         sync_log("[11/13] Budowanie i wysyłanie scalonych danych...");
-        let merged_export = sync_common::build_full_export(conn)?;
+        let merged_export = sync_common::build_full_export(conn)
+            .and_then(|json| sync_common::limit_archive_to_features(json, features))?;
         let merged_encrypted =
-            sync_encryption::encrypt_file_data(merged_export.as_bytes(), file_key)?;
+            encrypt_blob(passphrase, merged_export.as_bytes(), file_key)?;
+        sync_state.note_sync_run(|run| run.bytes_sent = merged_encrypted.len() as u64);
         let remote_merged = format!("{}data.enc", creds.download_path);
         {
             let sftp_ref = sftp;
             let remote_ref = &remote_merged;
             let encrypted_ref = &merged_encrypted;
             with_retry("SFTP upload (master merged)", || {
-                sftp_ref.upload_data(encrypted_ref, remote_ref, |sent, total| {
+                sftp_ref.upload_data(encrypted_ref, remote_ref, &|sent, total| {
                     sync_state.update_transfer_bytes(sent, total);
                 })
             })?;
@@ -1356,5 +1675,5 @@ fn execute_sync_steps(
         )?;
     }
 
-    Ok(())
+    Ok(None)
 }
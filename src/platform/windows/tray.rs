@@ -475,7 +475,7 @@ impl TrayState {
                         state.set_role("master");
                         let stop = Arc::new(AtomicBool::new(false));
                         crate::lan_sync_orchestrator::run_sync_as_master_with_options(
-                            peer, state, stop, force,
+                            peer, state, stop, force, false,
                         );
                     }
                     None => {
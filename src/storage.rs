@@ -2,7 +2,7 @@
 // Legacy JSON pozostaje tylko jako źródło migracji/fallback dla starszych instalacji.
 
 use anyhow::Result;
-use chrono::{Local, NaiveDate};
+use chrono::{DateTime, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -240,6 +240,22 @@ impl DailyStore {
             .map(|_| ())
             .map_err(anyhow::Error::msg)
     }
+
+    /// Zapisuje przedział pracy/bezczynności/uśpienia demona (patrz `tracker_spans`).
+    pub fn record_span(
+        &mut self,
+        kind: &str,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> Result<()> {
+        crate::daily_store::record_tracker_span(
+            &self.conn,
+            kind,
+            &start.to_rfc3339(),
+            &end.to_rfc3339(),
+        )
+        .map_err(anyhow::Error::msg)
+    }
 }
 
 /// Tworzy pustą strukturę dzienną
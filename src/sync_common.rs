@@ -3,9 +3,13 @@
 use crate::config;
 use crate::lan_common;
 use crate::lan_server;
+use crate::sync_preview::{self, SyncPreview};
 
 use std::sync::Mutex;
 
+use timeflow_shared::sync_hlc::{self, Hlc};
+use timeflow_shared::sync_protocol::{self, SyncFeatures};
+
 pub(crate) static MERGE_MUTEX: Mutex<()> = Mutex::new(());
 
 fn diag_logging_enabled() -> bool {
@@ -47,6 +51,62 @@ pub fn build_full_export(conn: &rusqlite::Connection) -> Result<String, String>
     lan_server::build_full_snapshot_public(conn)
 }
 
+/// Drops from an export archive whatever the negotiated peer cannot take —
+/// tables, columns and row clocks outside the common capability set. The
+/// receiving merge treats absent keys as "keep local", so nothing is lost.
+pub fn limit_archive_to_features(archive_json: String, features: &SyncFeatures) -> Result<String, String> {
+    if features.missing().is_empty() {
+        return Ok(archive_json);
+    }
+    let mut archive: serde_json::Value =
+        serde_json::from_str(&archive_json).map_err(|e| format!("Failed to parse export: {}", e))?;
+    let Some(data) = archive.get_mut("data").and_then(|d| d.as_object_mut()) else {
+        return Ok(archive_json);
+    };
+    for (capability, table) in [
+        (sync_protocol::CAP_CLIENTS, "clients"),
+        (sync_protocol::CAP_ESTIMATE_SETTINGS, "estimate_settings"),
+        (sync_protocol::CAP_FILE_ACTIVITIES, "file_activities"),
+        (sync_protocol::CAP_TAGS, "tags"),
+    ] {
+        if !features.supports(capability) {
+            data.remove(table);
+        }
+    }
+    let mut dropped_columns: Vec<(&str, &str)> = Vec::new();
+    if !features.supports(sync_protocol::CAP_PROJECT_MERGE) {
+        dropped_columns.extend([("projects", "merged_into"), ("projects", "merged_at")]);
+    }
+    if !features.supports(sync_protocol::CAP_PROJECT_CLIENT) {
+        dropped_columns.push(("projects", "client_name"));
+    }
+    if !features.supports(sync_protocol::CAP_TAGS) {
+        dropped_columns.extend([("sessions", "tags"), ("manual_sessions", "tags")]);
+    }
+    if !features.supports(sync_protocol::CAP_BILLABLE) {
+        dropped_columns.extend([
+            ("projects", "billable"),
+            ("sessions", "billable"),
+            ("manual_sessions", "billable"),
+        ]);
+    }
+    let drop_hlc = !features.supports(sync_protocol::CAP_HLC);
+    for (table, rows) in data.iter_mut() {
+        let Some(rows) = rows.as_array_mut() else {
+            continue;
+        };
+        for row in rows.iter_mut().filter_map(|r| r.as_object_mut()) {
+            for (_, column) in dropped_columns.iter().filter(|(t, _)| t == table) {
+                row.remove(*column);
+            }
+            if drop_hlc {
+                row.remove("hlc");
+            }
+        }
+    }
+    serde_json::to_string(&archive).map_err(|e| e.to_string())
+}
+
 // ── Backup ──
 
 pub fn backup_database_typed(conn: &rusqlite::Connection, sync_type: &str) -> Result<(), String> {
@@ -246,6 +306,8 @@ fn normalize_ts(ts: &str) -> String {
 
 // ── Merge conflict logging ──
 
+/// `details` carries both sides of the conflict (see [`conflict_details`]) so the
+/// dashboard review queue can show them and flip the resolution later.
 fn log_merge_conflict(
     tx: &rusqlite::Transaction,
     table_name: &str,
@@ -253,55 +315,106 @@ fn log_merge_conflict(
     local_updated_at: &str,
     remote_updated_at: &str,
     winner: &str,
+    details: Option<String>,
 ) {
     let _ = tx.execute(
-        "INSERT INTO sync_merge_log (table_name, record_key, resolution, local_updated_at, remote_updated_at, winner) \
-         VALUES (?1, ?2, 'last_writer_wins', ?3, ?4, ?5)",
-        rusqlite::params![table_name, record_key, local_updated_at, remote_updated_at, winner],
+        "INSERT INTO sync_merge_log (table_name, record_key, resolution, local_updated_at, remote_updated_at, winner, details) \
+         VALUES (?1, ?2, 'last_writer_wins', ?3, ?4, ?5, ?6)",
+        rusqlite::params![table_name, record_key, local_updated_at, remote_updated_at, winner, details],
     );
 }
 
+/// `{"local": <local row, all columns>, "incoming": <peer record>}` — the local
+/// row must be read BEFORE the merge overwrites it.
+fn conflict_details(
+    tx: &rusqlite::Transaction,
+    local_row_sql: &str,
+    params: &[&dyn rusqlite::types::ToSql],
+    incoming: &serde_json::Value,
+) -> Option<String> {
+    let local = crate::lan_server::fetch_all_rows_params(tx, local_row_sql, params)
+        .ok()?
+        .into_iter()
+        .next()?;
+    Some(serde_json::json!({ "local": local, "incoming": incoming }).to_string())
+}
+
+/// HLC of a peer record: its `hlc` field, or — for peers that predate
+/// clocks — one derived from `ts_key` exactly like the local seeding.
+fn record_hlc(record: &serde_json::Value, ts_key: &str) -> Hlc {
+    Hlc::from_parts(record.get("hlc").and_then(|v| v.as_str()), json_str(record, ts_key))
+}
+
+/// Newest HLC of all rows matched by `sql` (columns: hlc, timestamp).
+fn newest_local_hlc(
+    tx: &rusqlite::Transaction,
+    sql: &str,
+    params: &[&dyn rusqlite::types::ToSql],
+) -> Option<Hlc> {
+    let mut stmt = tx.prepare(sql).ok()?;
+    let rows = stmt
+        .query_map(params, |row| {
+            let hlc: Option<String> = row.get(0)?;
+            let ts: Option<String> = row.get(1)?;
+            Ok(Hlc::from_parts(hlc.as_deref(), ts.as_deref().unwrap_or("")))
+        })
+        .ok()?;
+    rows.flatten().max()
+}
+
 fn local_tombstone_covers(
     tx: &rusqlite::Transaction,
     table_name: &str,
     sync_key: &str,
-    record_updated_at: &str,
+    record_hlc: &Hlc,
 ) -> bool {
-    let deleted_at: Option<String> = tx
-        .query_row(
-            "SELECT MAX(deleted_at) FROM tombstones WHERE table_name = ?1 AND sync_key = ?2",
-            rusqlite::params![table_name, sync_key],
-            |row| row.get(0),
-        )
-        .ok()
-        .flatten();
-
-    deleted_at
-        .as_deref()
-        .map(|deleted| normalize_ts(deleted) >= normalize_ts(record_updated_at))
-        .unwrap_or(false)
+    newest_local_hlc(
+        tx,
+        "SELECT hlc, deleted_at FROM tombstones WHERE table_name = ?1 AND sync_key = ?2",
+        &[&table_name, &sync_key],
+    )
+    .map(|deleted| deleted >= *record_hlc)
+    .unwrap_or(false)
 }
 
 fn local_manual_tombstone_covers(
     tx: &rusqlite::Transaction,
     start_time: &str,
     title: &str,
-    record_updated_at: &str,
+    record_hlc: &Hlc,
 ) -> bool {
     let pattern = format!("%|{}|{}", start_time, title);
-    let deleted_at: Option<String> = tx
-        .query_row(
-            "SELECT MAX(deleted_at) FROM tombstones WHERE table_name = 'manual_sessions' AND sync_key LIKE ?1",
-            rusqlite::params![pattern],
-            |row| row.get(0),
-        )
-        .ok()
-        .flatten();
+    newest_local_hlc(
+        tx,
+        "SELECT hlc, deleted_at FROM tombstones WHERE table_name = 'manual_sessions' AND sync_key LIKE ?1",
+        &[&pattern],
+    )
+    .map(|deleted| deleted >= *record_hlc)
+    .unwrap_or(false)
+}
 
-    deleted_at
-        .as_deref()
-        .map(|deleted| normalize_ts(deleted) >= normalize_ts(record_updated_at))
-        .unwrap_or(false)
+/// Newest HLC carried by the payload — fed to the HLC receive rule so local
+/// edits made after this merge outrank everything it brought in.
+fn newest_incoming_hlc(archive: &serde_json::Value) -> Hlc {
+    let mut newest = Hlc::default();
+    for (path, ts_key) in [
+        ("/data/projects", "updated_at"),
+        ("/data/applications", "updated_at"),
+        ("/data/sessions", "updated_at"),
+        ("/data/manual_sessions", "updated_at"),
+        ("/data/clients", "updated_at"),
+        ("/data/estimate_settings", "updated_at"),
+        ("/data/tags", "updated_at"),
+        ("/data/tombstones", "deleted_at"),
+    ] {
+        for record in archive.pointer(path).and_then(|v| v.as_array()).into_iter().flatten() {
+            let hlc = record_hlc(record, ts_key);
+            if hlc > newest {
+                newest = hlc;
+            }
+        }
+    }
+    newest
 }
 
 /// Daemon-side defensive schema guard: the dashboard owns migrations (m23),
@@ -321,13 +434,283 @@ pub(crate) fn ensure_project_merge_columns(conn: &rusqlite::Connection) {
     }
 }
 
+pub(crate) fn table_exists(conn: &rusqlite::Connection, table: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
+        [table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|c| c > 0)
+    .unwrap_or(false)
+}
+
+pub(crate) fn column_exists(conn: &rusqlite::Connection, table: &str, column: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name=?2",
+        [table, column],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|c| c > 0)
+    .unwrap_or(false)
+}
+
+/// Daemon-side guard for the synced parts of m24 (clients, projects.client_name)
+/// and m08 (estimate_settings) — same DDL as the dashboard. DDL runs only when
+/// something is missing, so read-only pull connections stay quiet.
+pub(crate) fn ensure_client_sync_schema(conn: &rusqlite::Connection) {
+    let mut stmts: Vec<&str> = Vec::new();
+    if !table_exists(conn, "clients") {
+        stmts.push(
+            "CREATE TABLE IF NOT EXISTS clients (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                contact TEXT,
+                address TEXT,
+                tax_id TEXT,
+                currency TEXT,
+                default_hourly_rate REAL,
+                color TEXT NOT NULL DEFAULT '#38bdf8',
+                archived_at TEXT,
+                created_at TEXT,
+                updated_at TEXT NOT NULL DEFAULT '1970-01-01 00:00:00'
+            )",
+        );
+    }
+    if !column_exists(conn, "projects", "client_name") {
+        stmts.push("ALTER TABLE projects ADD COLUMN client_name TEXT");
+    }
+    if !table_exists(conn, "estimate_settings") {
+        stmts.push(
+            "CREATE TABLE IF NOT EXISTS estimate_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+        );
+    }
+    for sql in stmts {
+        if let Err(e) = conn.execute(sql, []) {
+            lan_common::sync_log(&format!("ensure_client_sync_schema: {}", e));
+        }
+    }
+}
+
+/// Tags (dashboard m33) travel as `data.tags` plus a name list on every
+/// session / manual session. The daemon may open a DB the dashboard has not
+/// migrated yet — create the tables so the merge and the tombstone trigger
+/// have somewhere to land.
+pub(crate) fn ensure_tag_sync_schema(conn: &rusqlite::Connection) {
+    if table_exists(conn, "tags") {
+        return;
+    }
+    if let Err(e) = conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT NOT NULL DEFAULT '#a78bfa',
+            created_at TEXT,
+            updated_at TEXT NOT NULL DEFAULT '1970-01-01 00:00:00'
+        );
+        CREATE TABLE IF NOT EXISTS session_tags (
+            session_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (session_id, tag_id),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag_id);
+        CREATE TABLE IF NOT EXISTS manual_session_tags (
+            manual_session_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (manual_session_id, tag_id),
+            FOREIGN KEY (manual_session_id) REFERENCES manual_sessions(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_manual_session_tags_tag
+        ON manual_session_tags(tag_id);",
+    ) {
+        lan_common::sync_log(&format!("ensure_tag_sync_schema: {}", e));
+    }
+}
+
+/// Billable flags (dashboard m34): the project default and the per-session
+/// override (NULL = inherit). Same guarded ALTERs as the dashboard.
+pub(crate) fn ensure_billable_sync_schema(conn: &rusqlite::Connection) {
+    for (table, ddl) in [
+        ("projects", "billable INTEGER NOT NULL DEFAULT 1"),
+        ("sessions", "billable INTEGER"),
+        ("manual_sessions", "billable INTEGER"),
+    ] {
+        if !table_exists(conn, table) || column_exists(conn, table, "billable") {
+            continue;
+        }
+        if let Err(e) = conn.execute(&format!("ALTER TABLE {} ADD COLUMN {}", table, ddl), []) {
+            lan_common::sync_log(&format!("ensure_billable_sync_schema: {}", e));
+        }
+    }
+}
+
+/// A peer's `billable` value as `(present, value)`, bound into the same
+/// INSERT/UPDATE that writes `updated_at`/`hlc` (a separate UPDATE would fire
+/// the updated_at/HLC triggers and restamp the row as a local edit).
+/// An absent key (peer without the flag) is not present and keeps the local
+/// value; for projects (`nullable = false`) a NULL counts as absent too.
+fn remote_billable(row: &serde_json::Value, nullable: bool) -> (bool, Option<i64>) {
+    let Some(value) = row.get("billable") else {
+        return (false, None);
+    };
+    let billable = value.as_bool().map(i64::from).or_else(|| value.as_i64());
+    (nullable || billable.is_some(), billable)
+}
+
+/// Replaces the tag links of one session / manual session with `names`
+/// (sync payload: the peer's full list). Unknown names get a fresh tag row —
+/// `data.tags` normally brings it first, this only covers older payloads.
+fn replace_tag_links(
+    tx: &rusqlite::Transaction,
+    link_table: &str,
+    owner_column: &str,
+    owner_id: i64,
+    names: &[serde_json::Value],
+) -> Result<(), String> {
+    tx.execute(
+        &format!("DELETE FROM {} WHERE {} = ?1", link_table, owner_column),
+        [owner_id],
+    )
+    .map_err(|e| e.to_string())?;
+    for name in names.iter().filter_map(|v| v.as_str()) {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        tx.execute(
+            "INSERT OR IGNORE INTO tags (name, created_at, updated_at) \
+             VALUES (?1, datetime('now'), '1970-01-01 00:00:00')",
+            [name],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            &format!(
+                "INSERT OR IGNORE INTO {} ({}, tag_id) \
+                 SELECT ?1, id FROM tags WHERE name = ?2",
+                link_table, owner_column
+            ),
+            rusqlite::params![owner_id, name],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Selective sync (dashboard m28): SQL subqueries selecting the ids of
+/// device-local projects and applications. An application assigned to a
+/// device-local project is device-local too — its sessions carry that
+/// project's time. Without the column (not-yet-migrated DB) nothing is local.
+pub(crate) struct DeviceLocalScope {
+    pub project_ids: String,
+    pub app_ids: String,
+}
+
+impl DeviceLocalScope {
+    const NONE: &'static str = "SELECT NULL WHERE 0";
+
+    pub(crate) fn load(conn: &rusqlite::Connection) -> Self {
+        let project_ids = if column_exists(conn, "projects", "device_local") {
+            "SELECT id FROM projects WHERE device_local = 1".to_string()
+        } else {
+            Self::NONE.to_string()
+        };
+        let app_ids = if column_exists(conn, "applications", "device_local") {
+            format!(
+                "SELECT id FROM applications WHERE device_local = 1 OR project_id IN ({})",
+                project_ids
+            )
+        } else {
+            format!("SELECT id FROM applications WHERE project_id IN ({})", project_ids)
+        };
+        Self { project_ids, app_ids }
+    }
+
+    /// Whether a peer tombstone would delete (or cascade into) device-local
+    /// rows. Such rows were never sent, so the peer cannot have deleted them.
+    fn covers_tombstone(&self, conn: &rusqlite::Connection, table_name: &str, sync_key: &str) -> bool {
+        let exists = |sql: String, params: &[&dyn rusqlite::types::ToSql]| {
+            conn.query_row(&sql, params, |_| Ok(())).is_ok()
+        };
+        match table_name {
+            "projects" => exists(
+                format!("SELECT 1 FROM projects WHERE name = ?1 AND id IN ({})", self.project_ids),
+                &[&sync_key],
+            ),
+            "applications" => exists(
+                format!(
+                    "SELECT 1 FROM applications a
+                     WHERE a.executable_name = ?1
+                       AND (a.id IN ({apps})
+                            OR EXISTS (SELECT 1 FROM sessions s
+                                       WHERE s.app_id = a.id AND s.project_id IN ({projects})))",
+                    apps = self.app_ids,
+                    projects = self.project_ids
+                ),
+                &[&sync_key],
+            ),
+            "sessions" => match sync_key.split_once('|') {
+                Some((app_key, start_time)) => exists(
+                    format!(
+                        "SELECT 1 FROM sessions s
+                         LEFT JOIN applications a ON a.id = s.app_id
+                         WHERE (a.executable_name = ?1 OR s.app_id = CAST(?1 AS INTEGER))
+                           AND s.start_time = ?2
+                           AND (s.app_id IN ({apps}) OR s.project_id IN ({projects}))",
+                        apps = self.app_ids,
+                        projects = self.project_ids
+                    ),
+                    &[&app_key, &start_time],
+                ),
+                None => false,
+            },
+            "manual_sessions" => {
+                let parts: Vec<&str> = sync_key.splitn(3, '|').collect();
+                parts.len() == 3
+                    && exists(
+                        format!(
+                            "SELECT 1 FROM manual_sessions
+                             WHERE start_time = ?1 AND title = ?2
+                               AND (project_id IN ({projects}) OR app_id IN ({apps}))",
+                            apps = self.app_ids,
+                            projects = self.project_ids
+                        ),
+                        &[&parts[1], &parts[2]],
+                    )
+            }
+            _ => false,
+        }
+    }
+}
+
 // ── Merge ──
 
 pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) -> Result<(), String> {
+    merge_incoming(conn, slave_data, false).map(|_| ())
+}
+
+/// [`merge_incoming_data`] that also reports what the merge changed, for the
+/// sync run history (`projects_changed` stays empty — totals are not diffed).
+pub fn merge_incoming_data_with_stats(conn: &mut rusqlite::Connection, slave_data: &str) -> Result<SyncPreview, String> {
+    merge_incoming(conn, slave_data, false)
+}
+
+/// Dry-run of [`merge_incoming_data`]: runs the same merge (plus the
+/// post-merge integrity pass) and rolls the transaction back, returning what
+/// would have changed.
+pub fn preview_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) -> Result<SyncPreview, String> {
+    merge_incoming(conn, slave_data, true)
+}
+
+fn merge_incoming(conn: &mut rusqlite::Connection, slave_data: &str, dry_run: bool) -> Result<SyncPreview, String> {
     let _merge_guard = MERGE_MUTEX
         .lock()
         .map_err(|_| "merge mutex poisoned".to_string())?;
-    ensure_project_merge_columns(conn);
     const MAX_PAYLOAD_SIZE: usize = 200 * 1024 * 1024; // 200 MB
     if slave_data.len() > MAX_PAYLOAD_SIZE {
         return Err(format!(
@@ -349,12 +732,32 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
 
     // Log counts for visibility
     let count = |path: &str| archive.pointer(path).and_then(|v| v.as_array()).map(|a| a.len()).unwrap_or(0);
-    lan_common::sync_log(&format!("  Dane peera: {} projektow, {} aplikacji, {} sesji, {} sesji manualnych, {} tombstones",
+    lan_common::sync_log(&format!("  Dane peera: {} projektow, {} aplikacji, {} sesji, {} sesji manualnych, {} klientow, {} ustawien wycen, {} tagow, {} aktywnosci plikow, {} tombstones",
         count("/data/projects"), count("/data/applications"), count("/data/sessions"),
-        count("/data/manual_sessions"), count("/data/tombstones")));
+        count("/data/manual_sessions"), count("/data/clients"), count("/data/estimate_settings"),
+        count("/data/tags"), count("/data/file_activities"), count("/data/tombstones")));
 
     let tx = conn.transaction().map_err(|e| e.to_string())?;
 
+    // Schema upgrades run inside the transaction, so a dry-run rolls them
+    // back with everything else and leaves the database untouched.
+    ensure_project_merge_columns(&tx);
+    ensure_client_sync_schema(&tx);
+    ensure_tag_sync_schema(&tx);
+    ensure_billable_sync_schema(&tx);
+    // LWW decisions below read and write `hlc` — without it the merge can't run.
+    sync_hlc::ensure_schema(&tx).map_err(|e| format!("HLC schema: {}", e))?;
+    sync_hlc::set_node(&tx, &lan_common::get_device_id()).map_err(|e| format!("HLC node: {}", e))?;
+
+    // Probes run on real merges too — their counts feed the sync run history.
+    sync_preview::install_change_probes(&tx)?;
+    let totals_before = if dry_run {
+        Some(sync_preview::project_totals(&tx)?)
+    } else {
+        None
+    };
+    let conflicts_before = merge_log_count(&tx);
+
     // Suppress tombstone triggers for the whole merge transaction: every
     // DELETE below replays a peer tombstone that is recorded explicitly with
     // its original deleted_at. Trigger-minted copies (deleted_at = now) would
@@ -373,6 +776,7 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
     // historical state). Applying deletions first means the subsequent
     // INSERT/UPDATE re-introduces the record exactly as the peer last saw it,
     // and a no-op when the peer also lost it.
+    let device_local = DeviceLocalScope::load(&tx);
     if let Some(tombstones) = archive.pointer("/data/tombstones").and_then(|v| v.as_array()) {
         for ts in tombstones {
             let table_name = ts.get("table_name").and_then(|v| v.as_str()).unwrap_or("");
@@ -389,87 +793,98 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                 )
                 .is_ok();
 
+            if !exists && device_local.covers_tombstone(&tx, table_name, sync_key) {
+                lan_common::sync_log(&format!(
+                    "  SKIP tombstone {} '{}' — rekord lokalny (nie synchronizowany)",
+                    table_name, sync_key
+                ));
+                continue;
+            }
+
             if !exists {
                 let deleted_at_str = ts.get("deleted_at").and_then(|v| v.as_str()).unwrap_or("");
                 let deleted_at_norm = normalize_ts(deleted_at_str);
+                let tombstone_hlc = record_hlc(ts, "deleted_at");
+                let newer_than_tombstone = |local: Option<Hlc>| local.map(|l| l > tombstone_hlc).unwrap_or(false);
 
                 // Guard: don't delete a record that was re-created/updated AFTER the tombstone
                 // Applied to ALL tables, not just projects (5.7 fix)
                 let skip_tombstone = match table_name {
-                    "projects" => {
-                        let local_updated: Option<String> = tx
-                            .query_row("SELECT updated_at FROM projects WHERE name = ?1", [sync_key], |row| row.get(0))
-                            .ok();
-                        local_updated.as_deref().map(|lu| normalize_ts(lu) > normalize_ts(deleted_at_str)).unwrap_or(false)
-                    }
+                    "projects" => newer_than_tombstone(newest_local_hlc(
+                        &tx,
+                        "SELECT hlc, updated_at FROM projects WHERE name = ?1",
+                        &[&sync_key],
+                    )),
                     "applications" => {
-                        let local_updated: Option<String> = tx
-                            .query_row("SELECT updated_at FROM applications WHERE executable_name = ?1", [sync_key], |row| row.get(0))
-                            .ok();
-                        let app_newer = local_updated.as_deref().map(|lu| normalize_ts(lu) > normalize_ts(deleted_at_str)).unwrap_or(false);
+                        let app_newer = newer_than_tombstone(newest_local_hlc(
+                            &tx,
+                            "SELECT hlc, updated_at FROM applications WHERE executable_name = ?1",
+                            &[&sync_key],
+                        ));
                         // This tombstone's cascade deletes ALL sessions of the
                         // application. If any session is fresher than the
                         // tombstone, the deletion is stale — skip it entirely.
-                        let newest_session: Option<String> = tx
-                            .query_row(
-                                "SELECT MAX(s.updated_at) FROM sessions s
-                                 JOIN applications a ON a.id = s.app_id
-                                 WHERE a.executable_name = ?1",
-                                [sync_key],
-                                |row| row.get::<_, Option<String>>(0),
-                            )
-                            .ok()
-                            .flatten();
-                        let sessions_newer = newest_session.as_deref().map(|su| normalize_ts(su) > normalize_ts(deleted_at_str)).unwrap_or(false);
+                        let sessions_newer = newer_than_tombstone(newest_local_hlc(
+                            &tx,
+                            "SELECT s.hlc, s.updated_at FROM sessions s
+                             JOIN applications a ON a.id = s.app_id
+                             WHERE a.executable_name = ?1",
+                            &[&sync_key],
+                        ));
                         app_newer || sessions_newer
                     }
                     "sessions" => {
                         // sync_key = "executable_name|start_time" (legacy: "app_id|start_time")
                         if let Some((app_key, start_time)) = sync_key.split_once('|') {
-                            let local_updated: Option<String> = tx
-                                .query_row(
-                                    "SELECT s.updated_at
-                                     FROM sessions s
-                                     JOIN applications a ON a.id = s.app_id
-                                     WHERE a.executable_name = ?1 AND s.start_time = ?2",
-                                    rusqlite::params![app_key, start_time],
-                                    |row| row.get(0),
+                            let local = newest_local_hlc(
+                                &tx,
+                                "SELECT s.hlc, s.updated_at
+                                 FROM sessions s
+                                 JOIN applications a ON a.id = s.app_id
+                                 WHERE a.executable_name = ?1 AND s.start_time = ?2",
+                                &[&app_key, &start_time],
+                            )
+                            .or_else(|| {
+                                newest_local_hlc(
+                                    &tx,
+                                    "SELECT hlc, updated_at
+                                     FROM sessions
+                                     WHERE app_id = CAST(?1 AS INTEGER) AND start_time = ?2",
+                                    &[&app_key, &start_time],
                                 )
-                                .or_else(|_| {
-                                    tx.query_row(
-                                        "SELECT updated_at
-                                         FROM sessions
-                                         WHERE app_id = CAST(?1 AS INTEGER) AND start_time = ?2",
-                                        rusqlite::params![app_key, start_time],
-                                        |row| row.get(0),
-                                    )
-                                })
-                                .ok();
-                            local_updated.as_deref().map(|lu| normalize_ts(lu) > normalize_ts(deleted_at_str)).unwrap_or(false)
+                            });
+                            newer_than_tombstone(local)
                         } else { false }
                     }
                     "manual_sessions" => {
                         // sync_key = "project_id|start_time|title"
                         let parts: Vec<&str> = sync_key.splitn(3, '|').collect();
                         if parts.len() == 3 {
-                            let local_updated: Option<String> = tx
-                                .query_row(
-                                    "SELECT updated_at FROM manual_sessions WHERE start_time = ?1 AND title = ?2",
-                                    rusqlite::params![parts[1], parts[2]],
-                                    |row| row.get(0),
-                                )
-                                .ok();
-                            local_updated.as_deref().map(|lu| normalize_ts(lu) > normalize_ts(deleted_at_str)).unwrap_or(false)
+                            newer_than_tombstone(newest_local_hlc(
+                                &tx,
+                                "SELECT hlc, updated_at FROM manual_sessions WHERE start_time = ?1 AND title = ?2",
+                                &[&parts[1], &parts[2]],
+                            ))
                         } else { false }
                     }
+                    "clients" => newer_than_tombstone(newest_local_hlc(
+                        &tx,
+                        "SELECT hlc, updated_at FROM clients WHERE name = ?1",
+                        &[&sync_key],
+                    )),
+                    "tags" => newer_than_tombstone(newest_local_hlc(
+                        &tx,
+                        "SELECT hlc, updated_at FROM tags WHERE name = ?1",
+                        &[&sync_key],
+                    )),
                     _ => false,
                 };
                 if skip_tombstone {
                     // Record was updated after tombstone — skip deletion, just record the tombstone
                     tx.execute(
-                        "INSERT OR IGNORE INTO tombstones (table_name, record_id, deleted_at, sync_key) \
-                         VALUES (?1, ?2, ?3, ?4)",
-                        rusqlite::params![table_name, json_i64(ts, "record_id"), deleted_at_norm, sync_key],
+                        "INSERT OR IGNORE INTO tombstones (table_name, record_id, deleted_at, sync_key, hlc) \
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        rusqlite::params![table_name, json_i64(ts, "record_id"), deleted_at_norm, sync_key, tombstone_hlc.to_string()],
                     ).map_err(|e| e.to_string())?;
                     continue;
                 }
@@ -547,17 +962,37 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                             let _ = tx.execute("DELETE FROM manual_sessions WHERE id = CAST(?1 AS INTEGER)", [sync_key]);
                         }
                     }
+                    "clients" => {
+                        // Project links are cleared by verify_merge_integrity, AFTER
+                        // project rows from this payload are merged — unlinking here
+                        // would bump projects.updated_at and outvote the peer's rows.
+                        let _ = tx.execute("DELETE FROM clients WHERE name = ?1", [sync_key]);
+                    }
+                    "tags" => {
+                        // foreign_keys=OFF here — drop the links explicitly.
+                        for link in ["session_tags", "manual_session_tags"] {
+                            let _ = tx.execute(
+                                &format!(
+                                    "DELETE FROM {} WHERE tag_id IN (SELECT id FROM tags WHERE name = ?1)",
+                                    link
+                                ),
+                                [sync_key],
+                            );
+                        }
+                        let _ = tx.execute("DELETE FROM tags WHERE name = ?1", [sync_key]);
+                    }
                     _ => { log::warn!("Tombstone for unknown table: {}", table_name); }
                 }
 
                 tx.execute(
-                    "INSERT OR IGNORE INTO tombstones (table_name, record_id, deleted_at, sync_key) \
-                     VALUES (?1, ?2, ?3, ?4)",
+                    "INSERT OR IGNORE INTO tombstones (table_name, record_id, deleted_at, sync_key, hlc) \
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
                     rusqlite::params![
                         table_name,
                         json_i64(ts, "record_id"),
                         deleted_at_norm,
                         sync_key,
+                        tombstone_hlc.to_string(),
                     ],
                 ).map_err(|e| e.to_string())?;
             }
@@ -590,7 +1025,8 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
             if name.is_empty() {
                 continue;
             }
-            if local_tombstone_covers(&tx, "projects", name, updated_at) {
+            let remote_hlc = record_hlc(proj, "updated_at");
+            if local_tombstone_covers(&tx, "projects", name, &remote_hlc) {
                 lan_common::sync_log(&format!(
                     "  SKIP projekt '{}' — lokalny tombstone jest nowszy niz rekord peera",
                     name
@@ -598,24 +1034,31 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                 continue;
             }
 
-            let existing: Option<(String, Option<String>, Option<String>)> = tx
+            let existing: Option<(String, Option<String>, Option<String>, Option<String>, Hlc)> = tx
                 .query_row(
-                    "SELECT updated_at, merged_into, merged_at FROM projects WHERE name = ?1",
+                    "SELECT updated_at, merged_into, merged_at, client_name, hlc FROM projects WHERE name = ?1",
                     [name],
-                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                    |row| {
+                        let updated_at: String = row.get(0)?;
+                        let hlc: Option<String> = row.get(4)?;
+                        let local_hlc = Hlc::from_parts(hlc.as_deref(), &updated_at);
+                        Ok((updated_at, row.get(1)?, row.get(2)?, row.get(3)?, local_hlc))
+                    },
                 )
                 .ok();
 
             match existing {
-                Some((local_ts, _, _)) if normalize_ts(&local_ts) >= normalize_ts(updated_at) => {
-                    // Local wins — log only if timestamps differ (actual conflict)
-                    if normalize_ts(&local_ts) != normalize_ts(updated_at) {
-                        log_merge_conflict(&tx, "projects", name, &local_ts, updated_at, "local");
+                Some((local_ts, _, _, _, ref local_hlc)) if *local_hlc >= remote_hlc => {
+                    // Local wins — log only if clocks differ (actual conflict)
+                    if *local_hlc != remote_hlc {
+                        log_merge_conflict(&tx, "projects", name, &local_ts, updated_at, "local",
+                            conflict_details(&tx, "SELECT * FROM projects WHERE name = ?1", &[&name], proj));
                     }
                     diag_proj_local_wins += 1;
                 }
-                Some((ref local_ts, ref local_merged_into, ref local_merged_at)) => {
-                    log_merge_conflict(&tx, "projects", name, local_ts, updated_at, "remote");
+                Some((ref local_ts, ref local_merged_into, ref local_merged_at, ref local_client_name, _)) => {
+                    log_merge_conflict(&tx, "projects", name, local_ts, updated_at, "remote",
+                        conflict_details(&tx, "SELECT * FROM projects WHERE name = ?1", &[&name], proj));
                     // Old peers don't know merged_* keys — absent key means
                     // "preserve local value", explicit null means "cleared by unmerge".
                     let merged_into: Option<String> = match proj.get("merged_into") {
@@ -626,6 +1069,10 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                         None => local_merged_at.clone(),
                         Some(v) => v.as_str().map(|s| s.to_string()),
                     };
+                    let client_name: Option<String> = match proj.get("client_name") {
+                        None => local_client_name.clone(),
+                        Some(v) => v.as_str().map(|s| s.to_string()),
+                    };
                     // Peer says this project is active → clear any stale local blacklist row
                     // so the BEFORE UPDATE trigger doesn't abort the merge.
                     if has_blacklist_table && json_str_opt(proj, "excluded_at").is_none() {
@@ -635,9 +1082,12 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                         ).map_err(|e| e.to_string())?;
                     }
                     // Note: assigned_folder_path is machine-specific — never overwrite from remote
+                    let (has_billable, billable) = remote_billable(proj, false);
                     tx.execute(
                         "UPDATE projects SET color = ?1, hourly_rate = ?2, excluded_at = ?3, \
-                         frozen_at = ?4, merged_into = ?5, merged_at = ?6, updated_at = ?7 WHERE name = ?8",
+                         frozen_at = ?4, merged_into = ?5, merged_at = ?6, client_name = ?7, \
+                         billable = CASE WHEN ?11 THEN ?12 ELSE billable END, \
+                         updated_at = ?8, hlc = ?9 WHERE name = ?10",
                         rusqlite::params![
                             json_str(proj, "color"),
                             json_f64_opt(proj, "hourly_rate"),
@@ -645,8 +1095,12 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                             json_str_opt(proj, "frozen_at"),
                             merged_into,
                             merged_at,
+                            client_name,
                             updated_at,
+                            remote_hlc.to_string(),
                             name,
+                            has_billable,
+                            billable,
                         ],
                     ).map_err(|e| e.to_string())?;
                     diag_proj_updated.push(name.to_string());
@@ -662,8 +1116,9 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                     }
                     tx.execute(
                         "INSERT INTO projects (name, color, hourly_rate, created_at, excluded_at, \
-                         frozen_at, assigned_folder_path, merged_into, merged_at, is_imported, updated_at) \
-                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 1, ?10)",
+                         frozen_at, assigned_folder_path, merged_into, merged_at, client_name, is_imported, updated_at, hlc, \
+                         billable) \
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 1, ?11, ?12, COALESCE(?13, 1))",
                         rusqlite::params![
                             name,
                             json_str(proj, "color"),
@@ -674,7 +1129,10 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                             json_str_opt(proj, "assigned_folder_path"),
                             json_str_opt(proj, "merged_into"),
                             json_str_opt(proj, "merged_at"),
+                            json_str_opt(proj, "client_name"),
                             updated_at,
+                            remote_hlc.to_string(),
+                            remote_billable(proj, false).1,
                         ],
                     ).map_err(|e| e.to_string())?;
                     diag_proj_new.push(name.to_string());
@@ -691,6 +1149,168 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
         ));
     }
 
+    // Merge clients (sync key = name, LWW on updated_at). The project↔client
+    // link travels with the project row (projects.client_name).
+    if let Some(clients) = archive.pointer("/data/clients").and_then(|v| v.as_array()) {
+        for client in clients {
+            let name = json_str(client, "name");
+            let updated_at = json_str(client, "updated_at");
+            if name.is_empty() {
+                continue;
+            }
+            let remote_hlc = record_hlc(client, "updated_at");
+            if local_tombstone_covers(&tx, "clients", name, &remote_hlc) {
+                lan_common::sync_log(&format!(
+                    "  SKIP klient '{}' — lokalny tombstone jest nowszy niz rekord peera",
+                    name
+                ));
+                continue;
+            }
+
+            let local: Option<(String, Option<String>)> = tx
+                .query_row("SELECT updated_at, hlc FROM clients WHERE name = ?1", [name], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .ok();
+            let local = local.map(|(ts, hlc)| {
+                let local_hlc = Hlc::from_parts(hlc.as_deref(), &ts);
+                (ts, local_hlc)
+            });
+            match local {
+                Some((local_ts, local_hlc)) if local_hlc >= remote_hlc => {
+                    if local_hlc != remote_hlc {
+                        log_merge_conflict(&tx, "clients", name, &local_ts, updated_at, "local",
+                            conflict_details(&tx, "SELECT * FROM clients WHERE name = ?1", &[&name], client));
+                    }
+                }
+                Some((local_ts, _)) => {
+                    log_merge_conflict(&tx, "clients", name, &local_ts, updated_at, "remote",
+                        conflict_details(&tx, "SELECT * FROM clients WHERE name = ?1", &[&name], client));
+                    tx.execute(
+                        "UPDATE clients SET contact = ?1, address = ?2, tax_id = ?3, currency = ?4, \
+                         default_hourly_rate = ?5, color = ?6, archived_at = ?7, updated_at = ?8, \
+                         hlc = ?9 WHERE name = ?10",
+                        rusqlite::params![
+                            json_str_opt(client, "contact"),
+                            json_str_opt(client, "address"),
+                            json_str_opt(client, "tax_id"),
+                            json_str_opt(client, "currency"),
+                            json_f64_opt(client, "default_hourly_rate"),
+                            json_str_opt(client, "color").unwrap_or_else(|| "#38bdf8".to_string()),
+                            json_str_opt(client, "archived_at"),
+                            updated_at,
+                            remote_hlc.to_string(),
+                            name,
+                        ],
+                    ).map_err(|e| e.to_string())?;
+                }
+                None => {
+                    tx.execute(
+                        "INSERT INTO clients (name, contact, address, tax_id, currency, \
+                         default_hourly_rate, color, archived_at, created_at, updated_at, hlc) \
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                        rusqlite::params![
+                            name,
+                            json_str_opt(client, "contact"),
+                            json_str_opt(client, "address"),
+                            json_str_opt(client, "tax_id"),
+                            json_str_opt(client, "currency"),
+                            json_f64_opt(client, "default_hourly_rate"),
+                            json_str_opt(client, "color").unwrap_or_else(|| "#38bdf8".to_string()),
+                            json_str_opt(client, "archived_at"),
+                            json_str_opt(client, "created_at"),
+                            updated_at,
+                            remote_hlc.to_string(),
+                        ],
+                    ).map_err(|e| e.to_string())?;
+                }
+            }
+        }
+    }
+
+    // Merge estimate_settings (global hourly rate, time algorithm, ...) — per key LWW.
+    if let Some(settings) = archive.pointer("/data/estimate_settings").and_then(|v| v.as_array()) {
+        for setting in settings {
+            let key = json_str(setting, "key");
+            let updated_at = json_str(setting, "updated_at");
+            let Some(value) = json_str_opt(setting, "value") else {
+                continue;
+            };
+            if key.is_empty() {
+                continue;
+            }
+            let remote_hlc = record_hlc(setting, "updated_at");
+            let local: Option<(String, Option<String>)> = tx
+                .query_row("SELECT updated_at, hlc FROM estimate_settings WHERE key = ?1", [key], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .ok();
+            if let Some((ref local_ts, ref local_hlc)) = local {
+                let local_hlc = Hlc::from_parts(local_hlc.as_deref(), local_ts);
+                if local_hlc >= remote_hlc {
+                    if local_hlc != remote_hlc {
+                        log_merge_conflict(&tx, "estimate_settings", key, local_ts, updated_at, "local",
+                            conflict_details(&tx, "SELECT * FROM estimate_settings WHERE key = ?1", &[&key], setting));
+                    }
+                    continue;
+                }
+                log_merge_conflict(&tx, "estimate_settings", key, local_ts, updated_at, "remote",
+                    conflict_details(&tx, "SELECT * FROM estimate_settings WHERE key = ?1", &[&key], setting));
+            }
+            tx.execute(
+                "INSERT INTO estimate_settings (key, value, updated_at, hlc) VALUES (?1, ?2, ?3, ?4) \
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at, \
+                 hlc = excluded.hlc",
+                rusqlite::params![key, value, updated_at, remote_hlc.to_string()],
+            ).map_err(|e| e.to_string())?;
+        }
+    }
+
+    // Merge tags (sync key = name, LWW on updated_at — tags carry no hlc).
+    // Session links travel as a name list on each session row, merged below.
+    if let Some(tags) = archive.pointer("/data/tags").and_then(|v| v.as_array()) {
+        for tag in tags {
+            let name = json_str(tag, "name").trim();
+            let updated_at = json_str(tag, "updated_at");
+            if name.is_empty() {
+                continue;
+            }
+            let remote_hlc = record_hlc(tag, "updated_at");
+            if local_tombstone_covers(&tx, "tags", name, &remote_hlc) {
+                continue;
+            }
+            let local: Option<(String, Option<String>)> = tx
+                .query_row("SELECT updated_at, hlc FROM tags WHERE name = ?1", [name], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .ok();
+            if let Some((ref local_ts, ref local_hlc)) = local {
+                let local_hlc = Hlc::from_parts(local_hlc.as_deref(), local_ts);
+                if local_hlc >= remote_hlc {
+                    if local_hlc != remote_hlc {
+                        log_merge_conflict(&tx, "tags", name, local_ts, updated_at, "local",
+                            conflict_details(&tx, "SELECT * FROM tags WHERE name = ?1", &[&name], tag));
+                    }
+                    continue;
+                }
+                log_merge_conflict(&tx, "tags", name, local_ts, updated_at, "remote",
+                    conflict_details(&tx, "SELECT * FROM tags WHERE name = ?1", &[&name], tag));
+            }
+            tx.execute(
+                "INSERT INTO tags (name, color, created_at, updated_at, hlc) VALUES (?1, ?2, ?3, ?4, ?5) \
+                 ON CONFLICT(name) DO UPDATE SET name = excluded.name, color = excluded.color, \
+                 updated_at = excluded.updated_at, hlc = excluded.hlc",
+                rusqlite::params![
+                    name,
+                    json_str_opt(tag, "color").unwrap_or_else(|| "#a78bfa".to_string()),
+                    json_str_opt(tag, "created_at"),
+                    updated_at,
+                    remote_hlc.to_string(),
+                ],
+            ).map_err(|e| e.to_string())?;
+        }
+    }
+
     // Build ID maps once: remote ID → name, local name → ID
     // These are used by applications, sessions, and manual_sessions merge.
     // Built AFTER project/app merge so local IDs reflect newly-inserted records.
@@ -749,7 +1369,8 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
             if exe_name.is_empty() {
                 continue;
             }
-            if local_tombstone_covers(&tx, "applications", exe_name, updated_at) {
+            let remote_hlc = record_hlc(app, "updated_at");
+            if local_tombstone_covers(&tx, "applications", exe_name, &remote_hlc) {
                 lan_common::sync_log(&format!(
                     "  SKIP aplikacja '{}' — lokalny tombstone jest nowszy niz rekord peera",
                     exe_name
@@ -764,28 +1385,30 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                 .and_then(|name| project_name_to_local_id.get(name))
                 .copied();
 
-            let existing: Option<(i64, Option<String>)> = tx
+            let existing: Option<(i64, Option<String>, Option<String>)> = tx
                 .query_row(
-                    "SELECT id, updated_at FROM applications WHERE executable_name = ?1",
+                    "SELECT id, updated_at, hlc FROM applications WHERE executable_name = ?1",
                     [exe_name],
-                    |row| Ok((row.get(0)?, row.get(1)?)),
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
                 )
                 .ok();
 
             match existing {
-                Some((_id, local_ts)) => {
+                Some((_id, local_ts, local_hlc)) => {
                     let local = local_ts.as_deref().unwrap_or("");
-                    if normalize_ts(updated_at) > normalize_ts(local) {
-                        log_merge_conflict(&tx, "applications", exe_name, local, updated_at, "remote");
+                    if remote_hlc > Hlc::from_parts(local_hlc.as_deref(), local) {
+                        log_merge_conflict(&tx, "applications", exe_name, local, updated_at, "remote",
+                            conflict_details(&tx, "SELECT * FROM applications WHERE executable_name = ?1", &[&exe_name], app));
                         // Sync project_id: prefer remote if set, else keep local
                         tx.execute(
                             "UPDATE applications SET display_name = ?1, \
                              project_id = COALESCE(?2, project_id), \
-                             updated_at = ?3 WHERE executable_name = ?4",
+                             updated_at = ?3, hlc = ?4 WHERE executable_name = ?5",
                             rusqlite::params![
                                 json_str_opt(app, "display_name"),
                                 local_project_id,
                                 updated_at,
+                                remote_hlc.to_string(),
                                 exe_name,
                             ],
                         ).map_err(|e| e.to_string())?;
@@ -793,9 +1416,9 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                 }
                 None => {
                     tx.execute(
-                        "INSERT INTO applications (executable_name, display_name, project_id, is_imported, updated_at) \
-                         VALUES (?1, ?2, ?3, 1, ?4)",
-                        rusqlite::params![exe_name, json_str_opt(app, "display_name"), local_project_id, updated_at],
+                        "INSERT INTO applications (executable_name, display_name, project_id, is_imported, updated_at, hlc) \
+                         VALUES (?1, ?2, ?3, 1, ?4, ?5)",
+                        rusqlite::params![exe_name, json_str_opt(app, "display_name"), local_project_id, updated_at, remote_hlc.to_string()],
                     ).map_err(|e| e.to_string())?;
                     // Update app_name_to_local_id for newly-inserted apps
                     if let Ok(new_id) = tx.query_row(
@@ -840,7 +1463,8 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                 }
             };
             let session_sync_key = format!("{}|{}", remote_app_name, start_time);
-            if local_tombstone_covers(&tx, "sessions", &session_sync_key, updated_at) {
+            let remote_hlc = record_hlc(sess, "updated_at");
+            if local_tombstone_covers(&tx, "sessions", &session_sync_key, &remote_hlc) {
                 lan_common::sync_log(&format!(
                     "  SKIP sesja '{}' — lokalny tombstone jest nowszy niz rekord peera",
                     session_sync_key
@@ -884,30 +1508,33 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                 }
             }
 
-            let existing: Option<(i64, Option<String>)> = tx
+            let existing: Option<(i64, Option<String>, Option<String>)> = tx
                 .query_row(
-                    "SELECT id, updated_at FROM sessions WHERE app_id = ?1 AND start_time = ?2",
+                    "SELECT id, updated_at, hlc FROM sessions WHERE app_id = ?1 AND start_time = ?2",
                     rusqlite::params![local_app_id, start_time],
-                    |row| Ok((row.get(0)?, row.get(1)?)),
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
                 )
                 .ok();
 
             match existing {
-                Some((id, local_ts)) => {
+                Some((id, local_ts, local_hlc)) => {
                     let local = local_ts.as_deref().unwrap_or("");
-                    if normalize_ts(updated_at) > normalize_ts(local) {
+                    if remote_hlc > Hlc::from_parts(local_hlc.as_deref(), local) {
                         let key = format!("app_id={}|start_time={}", local_app_id, start_time);
-                        log_merge_conflict(&tx, "sessions", &key, local, updated_at, "remote");
+                        log_merge_conflict(&tx, "sessions", &key, local, updated_at, "remote",
+                            conflict_details(&tx, "SELECT * FROM sessions WHERE id = ?1", &[&id], sess));
                         // COALESCE for project_id: prefer remote-resolved if set, else keep local.
                         // project_name: prefer remote (peer's label), fallback to local — this
                         // ensures that even when the project isn't present locally, the label
                         // persists and the session is not rendered as "Unassigned".
+                        let (has_billable, billable) = remote_billable(sess, true);
                         tx.execute(
                             "UPDATE sessions SET end_time = ?1, duration_seconds = ?2, \
                              rate_multiplier = ?3, comment = ?4, is_hidden = ?5, \
                              project_id = COALESCE(?6, project_id), \
                              project_name = COALESCE(?7, project_name), \
-                             updated_at = ?8 WHERE id = ?9",
+                             billable = CASE WHEN ?11 THEN ?12 ELSE billable END, \
+                             updated_at = ?8, hlc = ?9 WHERE id = ?10",
                             rusqlite::params![
                                 json_str_opt(sess, "end_time"),
                                 json_i64(sess, "duration_seconds"),
@@ -917,16 +1544,23 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                                 local_project_id,
                                 remote_project_name,
                                 updated_at,
+                                remote_hlc.to_string(),
                                 id,
+                                has_billable,
+                                billable,
                             ],
                         ).map_err(|e| e.to_string())?;
+                        // Absent list (peer without tags) keeps the local links.
+                        if let Some(names) = sess.get("tags").and_then(|v| v.as_array()) {
+                            replace_tag_links(&tx, "session_tags", "session_id", id, names)?;
+                        }
                     }
                 }
                 None => {
-                    tx.execute(
+                    let inserted = tx.execute(
                         "INSERT OR IGNORE INTO sessions (app_id, project_id, project_name, start_time, end_time, \
-                         duration_seconds, date, rate_multiplier, comment, is_hidden, updated_at) \
-                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                         duration_seconds, date, rate_multiplier, comment, is_hidden, updated_at, hlc, billable) \
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
                         rusqlite::params![
                             local_app_id,
                             local_project_id,
@@ -939,8 +1573,16 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                             json_str_opt(sess, "comment"),
                             json_i64(sess, "is_hidden"),
                             updated_at,
+                            remote_hlc.to_string(),
+                            remote_billable(sess, true).1,
                         ],
                     ).map_err(|e| e.to_string())?;
+                    if inserted == 1 {
+                        let id = tx.last_insert_rowid();
+                        if let Some(names) = sess.get("tags").and_then(|v| v.as_array()) {
+                            replace_tag_links(&tx, "session_tags", "session_id", id, names)?;
+                        }
+                    }
                 }
             }
         }
@@ -964,7 +1606,8 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
             if title.is_empty() || start_time.is_empty() {
                 continue;
             }
-            if local_manual_tombstone_covers(&tx, start_time, title, updated_at) {
+            let remote_hlc = record_hlc(ms, "updated_at");
+            if local_manual_tombstone_covers(&tx, start_time, title, &remote_hlc) {
                 lan_common::sync_log(&format!(
                     "  SKIP sesja manualna '{}|{}' — lokalny tombstone jest nowszy niz rekord peera",
                     start_time, title
@@ -986,24 +1629,27 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                 .and_then(|name| app_name_to_local_id.get(name))
                 .copied();
 
-            let existing: Option<(i64, Option<String>)> = tx
+            let existing: Option<(i64, Option<String>, Option<String>)> = tx
                 .query_row(
-                    "SELECT id, updated_at FROM manual_sessions WHERE title = ?1 AND start_time = ?2",
+                    "SELECT id, updated_at, hlc FROM manual_sessions WHERE title = ?1 AND start_time = ?2",
                     rusqlite::params![title, start_time],
-                    |row| Ok((row.get(0)?, row.get(1)?)),
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
                 )
                 .ok();
 
             match existing {
-                Some((id, local_ts)) => {
+                Some((id, local_ts, local_hlc)) => {
                     let local = local_ts.as_deref().unwrap_or("");
-                    if normalize_ts(updated_at) > normalize_ts(local) {
+                    if remote_hlc > Hlc::from_parts(local_hlc.as_deref(), local) {
                         let key = format!("title={}|start_time={}", title, start_time);
-                        log_merge_conflict(&tx, "manual_sessions", &key, local, updated_at, "remote");
+                        log_merge_conflict(&tx, "manual_sessions", &key, local, updated_at, "remote",
+                            conflict_details(&tx, "SELECT * FROM manual_sessions WHERE id = ?1", &[&id], ms));
+                        let (has_billable, billable) = remote_billable(ms, true);
                         tx.execute(
                             "UPDATE manual_sessions SET session_type = ?1, project_id = ?2, \
                              app_id = ?3, end_time = ?4, duration_seconds = ?5, \
-                             date = ?6, updated_at = ?7 WHERE id = ?8",
+                             date = ?6, billable = CASE WHEN ?10 THEN ?11 ELSE billable END, \
+                             updated_at = ?7, hlc = ?8 WHERE id = ?9",
                             rusqlite::params![
                                 json_str_opt(ms, "session_type"),
                                 local_project_id,
@@ -1012,16 +1658,22 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                                 json_i64(ms, "duration_seconds"),
                                 json_str_opt(ms, "date"),
                                 updated_at,
+                                remote_hlc.to_string(),
                                 id,
+                                has_billable,
+                                billable,
                             ],
                         ).map_err(|e| e.to_string())?;
+                        if let Some(names) = ms.get("tags").and_then(|v| v.as_array()) {
+                            replace_tag_links(&tx, "manual_session_tags", "manual_session_id", id, names)?;
+                        }
                     }
                 }
                 None => {
                     tx.execute(
                         "INSERT INTO manual_sessions (title, session_type, project_id, app_id, \
-                         start_time, end_time, duration_seconds, date, created_at, updated_at) \
-                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                         start_time, end_time, duration_seconds, date, created_at, updated_at, hlc, billable) \
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
                         rusqlite::params![
                             title,
                             json_str_opt(ms, "session_type"),
@@ -1033,10 +1685,109 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
                             json_str_opt(ms, "date"),
                             json_str_opt(ms, "created_at"),
                             updated_at,
+                            remote_hlc.to_string(),
+                            remote_billable(ms, true).1,
                         ],
                     ).map_err(|e| e.to_string())?;
+                    let id = tx.last_insert_rowid();
+                    if let Some(names) = ms.get("tags").and_then(|v| v.as_array()) {
+                        replace_tag_links(&tx, "manual_session_tags", "manual_session_id", id, names)?;
+                    }
+                }
+            }
+        }
+    }
+
+    // Merge file_activities — dedupe on (app, date, file_path). Rows have no
+    // updated_at, so instead of LWW both sides are unioned: MAX total_seconds,
+    // MIN first_seen, MAX last_seen, activity_spans merged with
+    // extend_activity_spans. Descriptive fields (project, title, path) come from
+    // the side seen most recently, falling back to whichever is set.
+    // The union is idempotent, so re-sent rows converge instead of growing.
+    if let Some(file_activities) = archive.pointer("/data/file_activities").and_then(|v| v.as_array()) {
+        if column_exists(&tx, "file_activities", "activity_spans") {
+            let mut merged_fa: u32 = 0;
+            let mut skipped_fa: u32 = 0;
+            for fa in file_activities {
+                let date = json_str(fa, "date");
+                let file_path = json_str(fa, "file_path");
+                let first_seen = json_str(fa, "first_seen");
+                let last_seen = json_str(fa, "last_seen");
+                if date.is_empty() || file_path.is_empty() || first_seen.is_empty() || last_seen.is_empty() {
+                    continue;
                 }
+                let Some(local_app_id) = fa.get("app_id").and_then(|v| v.as_i64())
+                    .and_then(|rid| remote_app_id_to_name.get(&rid))
+                    .and_then(|name| app_name_to_local_id.get(name))
+                    .copied()
+                else {
+                    skipped_fa += 1;
+                    continue;
+                };
+                let local_project_id: Option<i64> = fa.get("project_id").and_then(|v| v.as_i64())
+                    .and_then(|rid| remote_project_id_to_name.get(&rid))
+                    .and_then(|name| project_name_to_local_id.get(name))
+                    .copied();
+
+                let local_spans: String = tx
+                    .query_row(
+                        "SELECT activity_spans FROM file_activities WHERE app_id = ?1 AND date = ?2 AND file_path = ?3",
+                        rusqlite::params![local_app_id, date, file_path],
+                        |row| row.get(0),
+                    )
+                    .unwrap_or_else(|_| "[]".to_string());
+                let spans = crate::daily_store::merge_activity_spans_json(
+                    &local_spans,
+                    fa.get("activity_spans").and_then(|v| v.as_str()).unwrap_or("[]"),
+                );
+
+                tx.execute(
+                    "INSERT INTO file_activities (app_id, date, file_name, file_path, total_seconds, \
+                     first_seen, last_seen, project_id, window_title, detected_path, title_history, \
+                     activity_type, activity_spans) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13) \
+                     ON CONFLICT(app_id, date, file_path) DO UPDATE SET \
+                     total_seconds = MAX(file_activities.total_seconds, excluded.total_seconds), \
+                     first_seen = MIN(file_activities.first_seen, excluded.first_seen), \
+                     last_seen = MAX(file_activities.last_seen, excluded.last_seen), \
+                     project_id = CASE WHEN excluded.last_seen > file_activities.last_seen \
+                         THEN COALESCE(excluded.project_id, file_activities.project_id) \
+                         ELSE COALESCE(file_activities.project_id, excluded.project_id) END, \
+                     window_title = CASE WHEN excluded.last_seen > file_activities.last_seen \
+                         THEN COALESCE(excluded.window_title, file_activities.window_title) \
+                         ELSE COALESCE(file_activities.window_title, excluded.window_title) END, \
+                     detected_path = CASE WHEN excluded.last_seen > file_activities.last_seen \
+                         THEN COALESCE(excluded.detected_path, file_activities.detected_path) \
+                         ELSE COALESCE(file_activities.detected_path, excluded.detected_path) END, \
+                     title_history = CASE WHEN excluded.last_seen > file_activities.last_seen \
+                         THEN COALESCE(excluded.title_history, file_activities.title_history) \
+                         ELSE COALESCE(file_activities.title_history, excluded.title_history) END, \
+                     activity_type = CASE WHEN excluded.last_seen > file_activities.last_seen \
+                         THEN COALESCE(excluded.activity_type, file_activities.activity_type) \
+                         ELSE COALESCE(file_activities.activity_type, excluded.activity_type) END, \
+                     activity_spans = excluded.activity_spans",
+                    rusqlite::params![
+                        local_app_id,
+                        date,
+                        json_str(fa, "file_name"),
+                        file_path,
+                        json_i64(fa, "total_seconds"),
+                        first_seen,
+                        last_seen,
+                        local_project_id,
+                        json_str_opt(fa, "window_title"),
+                        json_str_opt(fa, "detected_path"),
+                        json_str_opt(fa, "title_history"),
+                        json_str_opt(fa, "activity_type"),
+                        spans,
+                    ],
+                ).map_err(|e| e.to_string())?;
+                merged_fa += 1;
             }
+            lan_common::sync_log(&format!(
+                "  Aktywnosci plikow: scalono {}, pominieto {} (nieznana aplikacja)",
+                merged_fa, skipped_fa
+            ));
         }
     }
 
@@ -1050,12 +1801,38 @@ pub fn merge_incoming_data(conn: &mut rusqlite::Connection, slave_data: &str) ->
         tx.execute(sql, []).map_err(|e| e.to_string())?;
     }
 
+    if let Some(totals_before) = totals_before {
+        // The real flow runs verify_merge_integrity right after the merge —
+        // its cleanup belongs in the preview too.
+        verify_merge_integrity(&tx)?;
+        let mut preview = sync_preview::collect(&tx, &totals_before)?;
+        preview.conflicts = merge_log_count(&tx) - conflicts_before;
+        tx.rollback().map_err(|e| e.to_string())?;
+        lan_common::sync_log(&format!(
+            "  Podglad scalania (dry-run): {} zmian, {} tombstones, {} projektow ze zmiana sumy — transakcja wycofana",
+            preview.total_changes(), preview.tombstones_added, preview.projects_changed.len()
+        ));
+        return Ok(preview);
+    }
+
+    let mut stats = sync_preview::collect_changes(&tx)?;
+    stats.conflicts = merge_log_count(&tx) - conflicts_before;
+    sync_preview::remove_change_probes(&tx)?;
+
+    sync_hlc::observe(&tx, &newest_incoming_hlc(&archive)).map_err(|e| e.to_string())?;
+
     tx.commit().map_err(|e| {
         log::error!("Transaction commit failed: {}", e);
         e.to_string()
     })?;
     lan_common::sync_log("  Scalanie zakonczone — commit transakcji");
-    Ok(())
+    Ok(stats)
+}
+
+/// Rows in `sync_merge_log`; 0 when the table does not exist (bare test DBs).
+fn merge_log_count(tx: &rusqlite::Transaction) -> i64 {
+    tx.query_row("SELECT COUNT(*) FROM sync_merge_log", [], |row| row.get(0))
+        .unwrap_or(0)
 }
 
 // ── Tombstone garbage collection ──
@@ -1230,6 +2007,41 @@ pub fn verify_merge_integrity(conn: &rusqlite::Connection) -> Result<(), String>
         );
     }
 
+    // Unlink projects from clients deleted on a peer. Only names backed by a
+    // clients tombstone are cleared — a client_name without a local clients row
+    // is legitimate (PM-sourced clients live outside the table).
+    match conn.execute(
+        "UPDATE projects SET client_name = NULL \
+         WHERE client_name IS NOT NULL \
+           AND client_name NOT IN (SELECT name FROM clients) \
+           AND client_name IN (SELECT sync_key FROM tombstones WHERE table_name = 'clients')",
+        [],
+    ) {
+        Ok(n) if n > 0 => log::warn!("Sync verify: unlinked {} projects from deleted clients", n),
+        Ok(_) => {}
+        // Pre-m24 DB without clients — nothing to unlink.
+        Err(e) => log::warn!("Sync verify: client link check skipped: {}", e),
+    }
+
+    // Tag links whose session or tag is gone (foreign_keys=OFF — no cascade).
+    for (link, owner_col, owner_table) in [
+        ("session_tags", "session_id", "sessions"),
+        ("manual_session_tags", "manual_session_id", "manual_sessions"),
+    ] {
+        match conn.execute(
+            &format!(
+                "DELETE FROM {link} WHERE {owner_col} NOT IN (SELECT id FROM {owner_table}) \
+                 OR tag_id NOT IN (SELECT id FROM tags)"
+            ),
+            [],
+        ) {
+            Ok(n) if n > 0 => log::warn!("Sync verify: removed {} orphan {} rows", n, link),
+            Ok(_) => {}
+            // Pre-m33 DB without tags — nothing to clean.
+            Err(e) => log::warn!("Sync verify: {} check skipped: {}", link, e),
+        }
+    }
+
     // Check FK integrity
     let fk_errors: Vec<String> = {
         let mut stmt = conn.prepare("PRAGMA foreign_key_check")
@@ -1304,7 +2116,7 @@ fn json_f64_opt(v: &serde_json::Value, key: &str) -> Option<f64> {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     #[test]
@@ -1626,7 +2438,7 @@ mod tests {
     // Simulates two daemons sharing data via the real sync funnel
     // (build_delta_for_pull → merge_incoming_data, both directions).
     // Run with:  cargo test --release roundtrip -- --ignored --nocapture
-    fn open_test_db() -> rusqlite::Connection {
+    pub(crate) fn open_test_db() -> rusqlite::Connection {
         let conn = rusqlite::Connection::open_in_memory().expect("in-memory db");
         conn.execute_batch(
             "CREATE TABLE projects (
@@ -1640,9 +2452,28 @@ mod tests {
                 frozen_at TEXT,
                 merged_into TEXT,
                 merged_at TEXT,
+                client_name TEXT,
                 is_imported INTEGER DEFAULT 0,
                 updated_at TEXT NOT NULL DEFAULT '1970-01-01 00:00:00'
             );
+            CREATE TABLE clients (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                contact TEXT,
+                address TEXT,
+                tax_id TEXT,
+                currency TEXT,
+                default_hourly_rate REAL,
+                color TEXT NOT NULL DEFAULT '#38bdf8',
+                archived_at TEXT,
+                created_at TEXT,
+                updated_at TEXT NOT NULL DEFAULT '1970-01-01 00:00:00'
+            );
+            CREATE TABLE estimate_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
             CREATE TABLE applications (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 executable_name TEXT NOT NULL UNIQUE,
@@ -1682,6 +2513,23 @@ mod tests {
                 updated_at TEXT,
                 UNIQUE(project_id, start_time, title)
             );
+            CREATE TABLE file_activities (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                app_id INTEGER NOT NULL,
+                date TEXT NOT NULL,
+                file_name TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                total_seconds INTEGER NOT NULL,
+                first_seen TEXT NOT NULL,
+                last_seen TEXT NOT NULL,
+                project_id INTEGER,
+                window_title TEXT,
+                detected_path TEXT,
+                title_history TEXT,
+                activity_type TEXT,
+                activity_spans TEXT NOT NULL DEFAULT '[]',
+                UNIQUE(app_id, date, file_path)
+            );
             CREATE TABLE tombstones (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 table_name TEXT NOT NULL,
@@ -1716,6 +2564,8 @@ mod tests {
             );",
         )
         .expect("schema");
+        ensure_tag_sync_schema(&conn);
+        ensure_billable_sync_schema(&conn);
         conn
     }
 
@@ -1749,18 +2599,27 @@ mod tests {
             .collect()
     }
 
-    fn user_data_snapshot(conn: &rusqlite::Connection) -> Vec<String> {
+    pub(crate) fn user_data_snapshot(conn: &rusqlite::Connection) -> Vec<String> {
         let mut rows = Vec::new();
         rows.extend(query_snapshot_rows(
             conn,
-            "SELECT 'P|' || name || '|' || COALESCE(color, '') || '|' || COALESCE(hourly_rate, '') || '|' || COALESCE(excluded_at, '') || '|' || COALESCE(frozen_at, '') || '|' || COALESCE(merged_into, '')
+            "SELECT 'P|' || name || '|' || COALESCE(color, '') || '|' || COALESCE(hourly_rate, '') || '|' || COALESCE(excluded_at, '') || '|' || COALESCE(frozen_at, '') || '|' || COALESCE(merged_into, '') || '|' || COALESCE(client_name, '')
              FROM projects ORDER BY name",
         ));
         rows.extend(query_snapshot_rows(
             conn,
-            "SELECT 'A|' || a.executable_name || '|' || COALESCE(a.display_name, '') || '|' || COALESCE(p.name, '')
-             FROM applications a LEFT JOIN projects p ON p.id = a.project_id
-             ORDER BY a.executable_name",
+            "SELECT 'C|' || name || '|' || COALESCE(contact, '') || '|' || COALESCE(default_hourly_rate, '') || '|' || color || '|' || COALESCE(archived_at, '')
+             FROM clients ORDER BY name",
+        ));
+        rows.extend(query_snapshot_rows(
+            conn,
+            "SELECT 'E|' || key || '|' || value FROM estimate_settings ORDER BY key",
+        ));
+        rows.extend(query_snapshot_rows(
+            conn,
+            "SELECT 'A|' || a.executable_name || '|' || COALESCE(a.display_name, '') || '|' || COALESCE(p.name, '')
+             FROM applications a LEFT JOIN projects p ON p.id = a.project_id
+             ORDER BY a.executable_name",
         ));
         rows.extend(query_snapshot_rows(
             conn,
@@ -1778,12 +2637,20 @@ mod tests {
              LEFT JOIN applications a ON a.id = manual_sessions.app_id
              ORDER BY title, start_time",
         ));
+        rows.extend(query_snapshot_rows(
+            conn,
+            "SELECT 'F|' || a.executable_name || '|' || fa.date || '|' || fa.file_path || '|' || fa.total_seconds || '|' || fa.first_seen || '|' || fa.last_seen || '|' || COALESCE(p.name, '') || '|' || COALESCE(fa.window_title, '') || '|' || fa.activity_spans
+             FROM file_activities fa
+             JOIN applications a ON a.id = fa.app_id
+             LEFT JOIN projects p ON p.id = fa.project_id
+             ORDER BY a.executable_name, fa.date, fa.file_path",
+        ));
         rows
     }
 
     /// Seed disjoint data so master + slave should reach a perfect union.
     /// Prefix is a single character so unique keys never collide across peers.
-    fn seed(conn: &rusqlite::Connection, prefix: &str) {
+    pub(crate) fn seed(conn: &rusqlite::Connection, prefix: &str) {
         let ts = "2026-04-20 10:00:00";
 
         for i in 1..=3 {
@@ -2004,12 +2871,14 @@ mod tests {
         assert_eq!(ma, None, "explicit null from a new peer must clear merged_at (LWW)");
 
         // 3) Restore the marker locally, then merge an OLD-peer archive
-        //    (merged_* keys ABSENT) with an even newer updated_at.
+        //    (merged_* keys ABSENT) with an even newer updated_at. The local
+        //    edit is back-dated explicitly, clock included (a plain UPDATE is
+        //    stamped "now" and would outrank the peer).
         conn.execute(
             "UPDATE projects SET merged_into = 'final', merged_at = '2026-06-03 10:00:00', \
-             excluded_at = '2026-06-03 10:00:00', updated_at = '2026-06-03 10:00:00' \
+             excluded_at = '2026-06-03 10:00:00', updated_at = '2026-06-03 10:00:00', hlc = ?1 \
              WHERE name = 'stage1'",
-            [],
+            [Hlc::from_timestamp("2026-06-03 10:00:00").to_string()],
         )
         .unwrap();
         let old_peer_archive = serde_json::json!({
@@ -2634,6 +3503,10 @@ mod tests {
                  start_time TEXT,
                  title TEXT
              );
+             CREATE TABLE clients (
+                 id INTEGER PRIMARY KEY,
+                 name TEXT NOT NULL UNIQUE
+             );
              CREATE TABLE tombstones (
                  id INTEGER PRIMARY KEY AUTOINCREMENT,
                  table_name TEXT NOT NULL,
@@ -2643,6 +3516,7 @@ mod tests {
              );",
         )
         .expect("schema");
+        ensure_tag_sync_schema(&conn);
         for sql in crate::tombstone_triggers::CREATE_ALL_TOMBSTONE_TRIGGERS_SQL {
             conn.execute(sql, []).expect("trigger");
         }
@@ -2784,4 +3658,703 @@ mod tests {
             .expect("tombstone zapisany");
         assert_eq!(stored, "2026-04-20 08:00:00", "RFC3339 +02:00 → kanoniczny UTC");
     }
+
+    #[test]
+    fn lan_sync_simulator_converges_clients_and_estimate_settings() {
+        for mode in [SimulatorPullMode::Delta, SimulatorPullMode::Full] {
+            let mut sim = LanSyncSimulator::new();
+            sim.master
+                .execute_batch(
+                    "INSERT INTO clients (name, contact, updated_at) VALUES ('Acme', 'old@acme', '2026-04-20 10:00:00');
+                     INSERT INTO clients (name, updated_at) VALUES ('Globex', '2026-04-20 10:00:00');
+                     INSERT INTO estimate_settings (key, value, updated_at) VALUES ('global_hourly_rate', '100', '1970-01-01 00:00:00');
+                     INSERT INTO estimate_settings (key, value, updated_at) VALUES ('time_algorithm', 'max', '2026-04-22 08:00:00');",
+                )
+                .unwrap();
+            sim.slave
+                .execute_batch(
+                    "INSERT INTO clients (name, contact, default_hourly_rate, updated_at) VALUES ('Acme', 'new@acme', 180, '2026-04-20 11:00:00');
+                     INSERT INTO projects (name, hourly_rate, client_name, updated_at) VALUES ('Website', 120, 'Acme', '2026-04-20 11:00:00');
+                     INSERT INTO estimate_settings (key, value, updated_at) VALUES ('global_hourly_rate', '150', '2026-04-21 09:00:00');
+                     INSERT INTO estimate_settings (key, value, updated_at) VALUES ('time_algorithm', 'sum', '2026-04-21 09:00:00');",
+                )
+                .unwrap();
+
+            sim.run_master_cycle(mode, "1970-01-01 00:00:00")
+                .expect("simulated LAN sync");
+            sim.assert_converged();
+
+            for conn in [&sim.master, &sim.slave] {
+                assert_eq!(query_string(conn, "SELECT contact FROM clients WHERE name = 'Acme'"), "new@acme");
+                assert_eq!(query_string(conn, "SELECT name FROM clients WHERE name = 'Globex'"), "Globex");
+                assert_eq!(query_string(conn, "SELECT client_name FROM projects WHERE name = 'Website'"), "Acme");
+                assert_eq!(
+                    query_string(conn, "SELECT CAST(hourly_rate AS TEXT) FROM projects WHERE name = 'Website'"),
+                    "120.0"
+                );
+                assert_eq!(
+                    query_string(conn, "SELECT value FROM estimate_settings WHERE key = 'global_hourly_rate'"),
+                    "150"
+                );
+                assert_eq!(
+                    query_string(conn, "SELECT value FROM estimate_settings WHERE key = 'time_algorithm'"),
+                    "max"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lan_sync_simulator_converges_tags_and_session_links() {
+        let tag_lists = |conn: &rusqlite::Connection| {
+            query_snapshot_rows(
+                conn,
+                "SELECT t.name || '|' || t.color || '|' || COALESCE(
+                     (SELECT group_concat(s.start_time, ',') FROM session_tags st
+                      JOIN sessions s ON s.id = st.session_id WHERE st.tag_id = t.id), '')
+                 FROM tags t ORDER BY t.name",
+            )
+        };
+        for mode in [SimulatorPullMode::Delta, SimulatorPullMode::Full] {
+            let mut sim = LanSyncSimulator::new();
+            sim.master
+                .execute_batch(
+                    "INSERT INTO tags (name, color, updated_at) VALUES ('urgent', '#ef4444', '2026-04-20 10:00:00');",
+                )
+                .unwrap();
+            sim.slave
+                .execute_batch(
+                    "INSERT INTO tags (name, color, updated_at) VALUES ('urgent', '#f59e0b', '2026-04-20 11:00:00');
+                     INSERT INTO tags (name, updated_at) VALUES ('review', '2026-04-20 11:00:00');
+                     INSERT INTO applications (executable_name, display_name, updated_at) VALUES ('code.exe', 'Code', '2026-04-20 11:00:00');
+                     INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+                     VALUES (1, '2026-04-20 09:00:00', '2026-04-20 10:00:00', 3600, '2026-04-20', '2026-04-20 11:00:00');
+                     INSERT INTO session_tags (session_id, tag_id) VALUES (1, 1), (1, 2);",
+                )
+                .unwrap();
+
+            sim.run_master_cycle(mode, "1970-01-01 00:00:00")
+                .expect("simulated LAN sync");
+            sim.assert_converged();
+            for conn in [&sim.master, &sim.slave] {
+                assert_eq!(
+                    tag_lists(conn),
+                    vec![
+                        "review|#a78bfa|2026-04-20 09:00:00".to_string(),
+                        "urgent|#f59e0b|2026-04-20 09:00:00".to_string(),
+                    ]
+                );
+            }
+        }
+
+        // A peer's tag tombstone drops the tag and its links; a tag edited
+        // after the deletion survives.
+        let mut conn = open_test_db();
+        conn.execute_batch(
+            "INSERT INTO tags (name, updated_at) VALUES ('old', '2026-04-20 10:00:00');
+             INSERT INTO tags (name, updated_at) VALUES ('fresh', '2026-04-25 10:00:00');
+             INSERT INTO applications (executable_name, display_name, updated_at) VALUES ('code.exe', 'Code', '2026-04-20 10:00:00');
+             INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES (1, '2026-04-20 09:00:00', '2026-04-20 10:00:00', 3600, '2026-04-20', '2026-04-20 10:00:00');
+             INSERT INTO session_tags (session_id, tag_id) VALUES (1, 1), (1, 2);",
+        )
+        .unwrap();
+        let peer = serde_json::json!({
+            "data": {
+                "tombstones": [
+                    {"table_name": "tags", "record_id": 3, "deleted_at": "2026-04-21 10:00:00", "sync_key": "old"},
+                    {"table_name": "tags", "record_id": 4, "deleted_at": "2026-04-21 10:00:00", "sync_key": "fresh"}
+                ]
+            }
+        });
+        merge_incoming_data(&mut conn, &peer.to_string()).expect("merge");
+        assert_eq!(query_snapshot_rows(&conn, "SELECT name FROM tags"), vec!["fresh".to_string()]);
+        assert_eq!(
+            query_snapshot_rows(&conn, "SELECT CAST(tag_id AS TEXT) FROM session_tags"),
+            vec!["2".to_string()]
+        );
+    }
+
+    #[test]
+    fn lan_sync_simulator_merges_file_activities_and_spans() {
+        for mode in [SimulatorPullMode::Delta, SimulatorPullMode::Full] {
+            let mut sim = LanSyncSimulator::new();
+            sim.master
+                .execute_batch(
+                    "INSERT INTO projects (name, updated_at) VALUES ('Website', '2026-04-20 10:00:00');
+                     INSERT INTO applications (executable_name, display_name, updated_at) VALUES ('code.exe', 'Code', '2026-04-20 10:00:00');
+                     INSERT INTO file_activities (app_id, date, file_name, file_path, total_seconds, first_seen, last_seen, window_title, activity_spans)
+                     VALUES (1, '2026-04-20', 'a.rs', '/w/a.rs', 600, '2026-04-20T10:00:00+00:00', '2026-04-20T10:10:00+00:00', 'local title',
+                             '[[\"2026-04-20T10:00:00+00:00\",\"2026-04-20T10:10:00+00:00\"]]');",
+                )
+                .unwrap();
+            sim.slave
+                .execute_batch(
+                    "INSERT INTO projects (name, updated_at) VALUES ('Website', '2026-04-20 10:00:00');
+                     INSERT INTO applications (executable_name, display_name, updated_at) VALUES ('code.exe', 'Code', '2026-04-20 10:00:00');
+                     INSERT INTO file_activities (app_id, date, file_name, file_path, total_seconds, first_seen, last_seen, project_id, window_title, activity_spans)
+                     VALUES (1, '2026-04-20', 'a.rs', '/w/a.rs', 900, '2026-04-20T10:10:20+00:00', '2026-04-20T12:05:00+00:00', 1, 'remote title',
+                             '[[\"2026-04-20T10:10:20+00:00\",\"2026-04-20T10:20:00+00:00\"],[\"2026-04-20T12:00:00+00:00\",\"2026-04-20T12:05:00+00:00\"]]');
+                     INSERT INTO file_activities (app_id, date, file_name, file_path, total_seconds, first_seen, last_seen, activity_spans)
+                     VALUES (1, '2026-04-21', 'b.rs', '/w/b.rs', 60, '2026-04-21T09:00:00+00:00', '2026-04-21T09:01:00+00:00', '[]');",
+                )
+                .unwrap();
+
+            sim.run_master_cycle(mode, "1970-01-01 00:00:00")
+                .expect("simulated LAN sync");
+            sim.assert_converged();
+            let first_pass = user_data_snapshot(&sim.master);
+
+            // Re-sending the same rows must not change anything (union is idempotent).
+            sim.run_master_cycle(mode, "1970-01-01 00:00:00")
+                .expect("second simulated LAN sync");
+            sim.assert_converged();
+            assert_eq!(user_data_snapshot(&sim.master), first_pass);
+
+            for conn in [&sim.master, &sim.slave] {
+                let count: i64 = conn
+                    .query_row("SELECT COUNT(*) FROM file_activities", [], |row| row.get(0))
+                    .unwrap();
+                assert_eq!(count, 2, "dedupe on (app, date, file_path)");
+                let (total, first, last): (i64, String, String) = conn
+                    .query_row(
+                        "SELECT total_seconds, first_seen, last_seen FROM file_activities WHERE file_path = '/w/a.rs'",
+                        [],
+                        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                    )
+                    .unwrap();
+                assert_eq!(total, 900);
+                assert_eq!(first, "2026-04-20T10:00:00+00:00");
+                assert_eq!(last, "2026-04-20T12:05:00+00:00");
+                assert_eq!(
+                    query_string(conn, "SELECT activity_spans FROM file_activities WHERE file_path = '/w/a.rs'"),
+                    r#"[["2026-04-20T10:00:00+00:00","2026-04-20T10:20:00+00:00"],["2026-04-20T12:00:00+00:00","2026-04-20T12:05:00+00:00"]]"#
+                );
+                assert_eq!(
+                    query_string(conn, "SELECT window_title FROM file_activities WHERE file_path = '/w/a.rs'"),
+                    "remote title",
+                    "descriptive fields follow the most recent last_seen"
+                );
+                assert_eq!(
+                    query_string(conn, "SELECT p.name FROM file_activities fa JOIN projects p ON p.id = fa.project_id WHERE fa.file_path = '/w/a.rs'"),
+                    "Website"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn merge_conflict_log_records_local_and_incoming_values() {
+        let mut conn = open_test_db();
+        conn.execute_batch(
+            "INSERT INTO projects (name, color, hourly_rate, updated_at)
+             VALUES ('Website', '#00ff00', 120, '2026-04-20 10:00:00');",
+        )
+        .unwrap();
+
+        let payload = serde_json::json!({
+            "data": {
+                "projects": [{
+                    "id": 3, "name": "Website", "color": "#ff0000", "hourly_rate": 200.0,
+                    "created_at": "2026-04-01 00:00:00", "updated_at": "2026-04-21 10:00:00"
+                }]
+            }
+        });
+        merge_incoming_data(&mut conn, &payload.to_string()).expect("merge");
+
+        let (winner, details): (String, String) = conn
+            .query_row(
+                "SELECT winner, details FROM sync_merge_log WHERE table_name = 'projects' AND record_key = 'Website'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("conflict logged");
+        assert_eq!(winner, "remote");
+        let details: serde_json::Value = serde_json::from_str(&details).expect("details json");
+        // Local row is captured before the merge overwrites it.
+        assert_eq!(details["local"]["color"], "#00ff00");
+        assert_eq!(details["local"]["id"], 1);
+        assert_eq!(details["incoming"]["color"], "#ff0000");
+        assert_eq!(query_string(&conn, "SELECT color FROM projects WHERE name = 'Website'"), "#ff0000");
+    }
+
+    #[test]
+    fn merge_project_without_client_key_keeps_local_link() {
+        let mut conn = open_test_db();
+        conn.execute_batch(
+            "INSERT INTO projects (name, hourly_rate, client_name, updated_at)
+             VALUES ('Website', 90, 'Acme', '2026-04-20 10:00:00');",
+        )
+        .unwrap();
+
+        // Old peer: no client_name key at all → local link survives; a cleared
+        // hourly_rate still propagates.
+        let old_peer = serde_json::json!({
+            "data": {
+                "projects": [{
+                    "id": 1, "name": "Website", "color": "#38bdf8", "hourly_rate": null,
+                    "created_at": "2026-04-01 00:00:00", "updated_at": "2026-04-21 10:00:00"
+                }]
+            }
+        });
+        merge_incoming_data(&mut conn, &old_peer.to_string()).expect("merge old peer");
+        assert_eq!(query_string(&conn, "SELECT client_name FROM projects WHERE name = 'Website'"), "Acme");
+        let rate: Option<f64> = conn
+            .query_row("SELECT hourly_rate FROM projects WHERE name = 'Website'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rate, None);
+
+        // New peer with an explicit null unlinks the project.
+        let new_peer = serde_json::json!({
+            "data": {
+                "projects": [{
+                    "id": 1, "name": "Website", "color": "#38bdf8", "client_name": null,
+                    "created_at": "2026-04-01 00:00:00", "updated_at": "2026-04-22 10:00:00"
+                }]
+            }
+        });
+        merge_incoming_data(&mut conn, &new_peer.to_string()).expect("merge new peer");
+        let client: Option<String> = conn
+            .query_row("SELECT client_name FROM projects WHERE name = 'Website'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(client, None);
+    }
+
+    #[test]
+    fn merge_billable_flags_keep_local_when_key_is_absent() {
+        let mut conn = open_test_db();
+        conn.execute_batch(
+            "INSERT INTO projects (name, billable, updated_at) VALUES ('Website', 0, '2026-04-20 10:00:00');
+             INSERT INTO applications (executable_name, display_name) VALUES ('code.exe', 'Code');
+             INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, billable, updated_at)
+             VALUES (1, '2026-04-20T09:00:00', '2026-04-20T10:00:00', 3600, '2026-04-20', 1, '2026-04-20 10:00:00');",
+        )
+        .unwrap();
+        let project_billable = |conn: &rusqlite::Connection| -> i64 {
+            conn.query_row("SELECT billable FROM projects WHERE name = 'Website'", [], |row| row.get(0))
+                .unwrap()
+        };
+        let session_billable = |conn: &rusqlite::Connection| -> Option<i64> {
+            conn.query_row("SELECT billable FROM sessions", [], |row| row.get(0)).unwrap()
+        };
+        let payload = |updated_at: &str, billable: Option<serde_json::Value>| {
+            let mut project = serde_json::json!({
+                "id": 1, "name": "Website", "color": "#38bdf8",
+                "created_at": "2026-04-01 00:00:00", "updated_at": updated_at
+            });
+            let mut session = serde_json::json!({
+                "id": 1, "app_id": 1, "start_time": "2026-04-20T09:00:00",
+                "end_time": "2026-04-20T10:00:00", "duration_seconds": 3600, "date": "2026-04-20",
+                "rate_multiplier": 1.0, "is_hidden": 0, "updated_at": updated_at
+            });
+            if let Some(value) = billable {
+                project["billable"] = serde_json::json!(1);
+                session["billable"] = value;
+            }
+            serde_json::json!({
+                "data": {
+                    "projects": [project],
+                    "applications": [{"id": 1, "executable_name": "code.exe", "display_name": "Code", "updated_at": updated_at}],
+                    "sessions": [session]
+                }
+            })
+            .to_string()
+        };
+
+        // Old peer: no billable keys → both local flags survive a newer row.
+        merge_incoming_data(&mut conn, &payload("2026-04-21 10:00:00", None)).expect("merge old peer");
+        assert_eq!(project_billable(&conn), 0);
+        assert_eq!(session_billable(&conn), Some(1));
+
+        // New peer: explicit null clears the session override back to inherit.
+        merge_incoming_data(&mut conn, &payload("2026-04-22 10:00:00", Some(serde_json::Value::Null)))
+            .expect("merge new peer");
+        assert_eq!(project_billable(&conn), 1);
+        assert_eq!(session_billable(&conn), None);
+    }
+
+    #[test]
+    fn merge_billable_keeps_peer_timestamps_under_updated_at_triggers() {
+        let mut conn = open_test_db();
+        // schema.sql triggers; merge upgrades them and adds the HLC stamping ones.
+        conn.execute_batch(
+            "CREATE TRIGGER trg_projects_updated_at AFTER UPDATE ON projects
+             FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+             BEGIN UPDATE projects SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id; END;
+             CREATE TRIGGER trg_manual_sessions_updated_at AFTER UPDATE ON manual_sessions
+             FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+             BEGIN UPDATE manual_sessions SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id; END;
+             INSERT INTO projects (name, updated_at) VALUES ('Website', '2026-04-20 10:00:00');",
+        )
+        .unwrap();
+
+        let peer_hlc = "1776765600000-0000-peerpeerpeerpeer";
+        let peer = serde_json::json!({
+            "data": {
+                "projects": [
+                    {"id": 1, "name": "Website", "color": "#38bdf8", "billable": 0,
+                     "created_at": "2026-04-01 00:00:00", "updated_at": "2026-04-21 10:00:00", "hlc": peer_hlc},
+                    {"id": 2, "name": "Shop", "color": "#38bdf8", "billable": 0,
+                     "created_at": "2026-04-01 00:00:00", "updated_at": "2026-04-21 10:00:00", "hlc": peer_hlc}
+                ],
+                "manual_sessions": [{
+                    "id": 1, "title": "Call", "session_type": "meeting", "project_id": 1,
+                    "start_time": "2026-04-20T09:00:00", "end_time": "2026-04-20T10:00:00",
+                    "duration_seconds": 3600, "date": "2026-04-20", "billable": 0,
+                    "created_at": "2026-04-20 10:00:00", "updated_at": "2026-04-21 10:00:00", "hlc": peer_hlc
+                }]
+            }
+        });
+        merge_incoming_data(&mut conn, &peer.to_string()).expect("merge");
+
+        for (sql, rows) in [
+            ("SELECT billable, updated_at, hlc FROM projects ORDER BY name", 2),
+            ("SELECT billable, updated_at, hlc FROM manual_sessions", 1),
+        ] {
+            let mut stmt = conn.prepare(sql).unwrap();
+            let got: Vec<(i64, String, String)> = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .unwrap()
+                .map(|r| r.unwrap())
+                .collect();
+            assert_eq!(got.len(), rows, "{}", sql);
+            for row in got {
+                assert_eq!(row, (0, "2026-04-21 10:00:00".to_string(), peer_hlc.to_string()), "{}", sql);
+            }
+        }
+    }
+
+    #[test]
+    fn client_tombstone_deletes_client_and_unlinks_projects() {
+        let mut conn = open_test_db();
+        conn.execute_batch(
+            "INSERT INTO clients (name, updated_at) VALUES ('Acme', '2026-04-20 10:00:00');
+             INSERT INTO clients (name, updated_at) VALUES ('Fresh', '2026-04-25 10:00:00');
+             INSERT INTO projects (name, client_name, updated_at) VALUES ('Website', 'Acme', '2026-04-20 10:00:00');
+             INSERT INTO projects (name, client_name, updated_at) VALUES ('Shop', 'Fresh', '2026-04-20 10:00:00');",
+        )
+        .unwrap();
+        for sql in crate::tombstone_triggers::CREATE_ALL_TOMBSTONE_TRIGGERS_SQL {
+            conn.execute(sql, []).unwrap();
+        }
+
+        let peer = serde_json::json!({
+            "data": {
+                "tombstones": [
+                    {"table_name": "clients", "record_id": 7, "deleted_at": "2026-04-21 10:00:00", "sync_key": "Acme"},
+                    {"table_name": "clients", "record_id": 8, "deleted_at": "2026-04-21 10:00:00", "sync_key": "Fresh"}
+                ],
+                // Stale peer copy of the client deleted above must not resurrect it.
+                "clients": [{"name": "Acme", "color": "#38bdf8", "updated_at": "2026-04-19 10:00:00"}]
+            }
+        });
+        merge_incoming_data(&mut conn, &peer.to_string()).expect("merge");
+        verify_merge_integrity(&conn).expect("verify");
+
+        let names = query_snapshot_rows(&conn, "SELECT name FROM clients ORDER BY name");
+        assert_eq!(names, vec!["Fresh".to_string()], "client edited after the tombstone survives");
+        let website_client: Option<String> = conn
+            .query_row("SELECT client_name FROM projects WHERE name = 'Website'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(website_client, None);
+        assert_eq!(query_string(&conn, "SELECT client_name FROM projects WHERE name = 'Shop'"), "Fresh");
+        let minted: i64 = conn
+            .query_row("SELECT COUNT(*) FROM tombstones WHERE table_name = 'clients'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(minted, 2, "only the peer tombstones are recorded");
+    }
+
+    #[test]
+    fn local_edit_after_skewed_peer_sync_wins_over_resent_record() {
+        let mut conn = open_test_db();
+        conn.execute_batch(
+            "INSERT INTO projects (name, color, updated_at) VALUES ('Alpha', '#111111', '2026-05-01 10:00:00');
+             INSERT INTO projects (name, color, updated_at) VALUES ('Beta', '#111111', '2026-05-01 10:00:00');",
+        )
+        .unwrap();
+
+        // Peer's wall clock runs decades ahead — its edits carry a future updated_at.
+        let skewed_peer = serde_json::json!({
+            "data": {
+                "projects": [
+                    { "id": 1, "name": "Alpha", "color": "#222222", "updated_at": "2099-01-01 00:00:00" },
+                    { "id": 2, "name": "Beta", "color": "#222222", "updated_at": "2099-01-01 00:00:00" }
+                ],
+                "tags": [{ "name": "Urgent", "color": "#222222", "updated_at": "2099-01-01 00:00:00" }]
+            }
+        })
+        .to_string();
+        merge_incoming_data(&mut conn, &skewed_peer).expect("first merge");
+        assert_eq!(query_string(&conn, "SELECT color FROM projects WHERE name = 'Alpha'"), "#222222");
+
+        // Local edits made after that sync: an update and a delete (with the
+        // tombstone a dashboard trigger would mint), both stamped "now".
+        conn.execute_batch(
+            "UPDATE projects SET color = '#333333', updated_at = datetime('now') WHERE name = 'Alpha';
+             UPDATE tags SET color = '#333333', updated_at = datetime('now') WHERE name = 'Urgent';
+             DELETE FROM projects WHERE name = 'Beta';
+             INSERT INTO tombstones (table_name, record_id, deleted_at, sync_key)
+             VALUES ('projects', 2, datetime('now'), 'Beta');",
+        )
+        .unwrap();
+
+        // The peer re-sends its stale rows. updated_at (2099) is still "newer",
+        // but the local clocks already passed the peer's — local edits win.
+        merge_incoming_data(&mut conn, &skewed_peer).expect("second merge");
+        assert_eq!(query_string(&conn, "SELECT color FROM projects WHERE name = 'Alpha'"), "#333333");
+        assert_eq!(query_string(&conn, "SELECT color FROM tags WHERE name = 'Urgent'"), "#333333");
+        let beta: i64 = conn
+            .query_row("SELECT COUNT(*) FROM projects WHERE name = 'Beta'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(beta, 0, "local tombstone outranks the re-sent row");
+
+        let alpha_hlc = Hlc::parse(&query_string(&conn, "SELECT hlc FROM projects WHERE name = 'Alpha'"))
+            .expect("stamped hlc");
+        assert!(alpha_hlc > Hlc::from_timestamp("2099-01-01 00:00:00"));
+    }
+
+    #[test]
+    fn preview_reports_changes_and_leaves_database_untouched() {
+        let peer = open_test_db();
+        peer.execute_batch(
+            "INSERT INTO projects (name, color, updated_at) VALUES ('Alpha', '#222222', '2026-06-01 10:00:00');
+             INSERT INTO projects (name, color, updated_at) VALUES ('Gamma', '#333333', '2026-06-01 10:00:00');
+             INSERT INTO applications (executable_name, display_name, project_id, updated_at)
+             VALUES ('gamma.exe', 'Gamma', 2, '2026-06-01 10:00:00');
+             INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES (1, '2026-06-01 09:00:00', '2026-06-01 09:05:00', 300, '2026-06-01', '2026-06-01 10:00:00');
+             INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES (1, '2026-06-01 11:00:00', '2026-06-01 11:05:00', 300, '2026-06-01', '2026-06-01 10:00:00');
+             INSERT INTO tombstones (table_name, record_id, deleted_at, sync_key)
+             VALUES ('projects', 9, '2026-06-01 10:00:00', 'Beta');",
+        )
+        .unwrap();
+        let peer_export = crate::lan_server::build_delta_for_pull_public(&peer, "1970-01-01 00:00:00")
+            .expect("peer export");
+
+        let mut local = open_test_db();
+        local
+            .execute_batch(
+                "INSERT INTO projects (name, color, updated_at) VALUES ('Alpha', '#111111', '2026-05-01 10:00:00');
+                 INSERT INTO projects (name, color, updated_at) VALUES ('Beta', '#111111', '2026-05-01 10:00:00');",
+            )
+            .unwrap();
+        let schema = |conn: &rusqlite::Connection| -> String {
+            conn.query_row(
+                "SELECT group_concat(type || ':' || name || ':' || COALESCE(sql, ''), char(10))
+                 FROM (SELECT * FROM sqlite_master ORDER BY type, name)",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap()
+        };
+        let schema_before = schema(&local);
+        let before = crate::lan_server::build_delta_for_pull_public(&local, "1970-01-01 00:00:00").unwrap();
+
+        let preview = preview_incoming_data(&mut local, &peer_export).expect("preview");
+
+        let projects = &preview.tables["projects"];
+        assert_eq!((projects.inserted, projects.updated, projects.deleted), (1, 1, 1));
+        assert_eq!(preview.tables["applications"].inserted, 1);
+        assert_eq!(preview.tables["sessions"].inserted, 2);
+        assert_eq!(preview.tombstones_added, 1);
+        assert_eq!(
+            preview.projects_changed,
+            vec![crate::sync_preview::ProjectTotalChange {
+                project: "Gamma".to_string(),
+                seconds_before: 0,
+                seconds_after: 600,
+            }]
+        );
+
+        let after = crate::lan_server::build_delta_for_pull_public(&local, "1970-01-01 00:00:00").unwrap();
+        assert_eq!(before, after, "dry-run must not change the database");
+        assert_eq!(schema_before, schema(&local), "dry-run must not upgrade the schema");
+        assert!(!column_exists(&local, "projects", "hlc"));
+        let temp_objects: i64 = local
+            .query_row("SELECT COUNT(*) FROM sqlite_temp_master WHERE name LIKE 'sync_preview%'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(temp_objects, 0, "probes are rolled back with the transaction");
+
+        // The real merge applies exactly what the preview announced.
+        merge_incoming_data(&mut local, &peer_export).expect("merge");
+        assert_eq!(query_string(&local, "SELECT color FROM projects WHERE name = 'Alpha'"), "#222222");
+        assert_eq!(counts(&local).sessions, 2);
+    }
+
+    #[test]
+    fn archive_limited_to_common_features_keeps_peer_columns_intact() {
+        let master = open_test_db();
+        ensure_project_merge_columns(&master);
+        master
+            .execute_batch(
+                "INSERT INTO projects (name, color, merged_into, merged_at, updated_at)
+                 VALUES ('Alpha', '#222222', 'Omega', '2026-06-01 09:00:00', '2026-06-01 10:00:00');",
+            )
+            .unwrap();
+        let full = crate::lan_server::build_delta_for_pull_public(&master, "1970-01-01 00:00:00").unwrap();
+        assert_eq!(limit_archive_to_features(full.clone(), &SyncFeatures::all()).unwrap(), full);
+
+        // Peer on a build without project merge / file activities / clocks.
+        let features = SyncFeatures::from_names([sync_protocol::CAP_CLIENTS, sync_protocol::CAP_PROJECT_CLIENT]);
+        let limited = limit_archive_to_features(full, &features).unwrap();
+        let archive: serde_json::Value = serde_json::from_str(&limited).unwrap();
+        let project = &archive["data"]["projects"][0];
+        assert!(project.get("merged_into").is_none() && project.get("merged_at").is_none());
+        assert!(project.get("hlc").is_none());
+        assert!(archive["data"].get("file_activities").is_none());
+        assert!(archive["data"].get("tags").is_none());
+        assert!(archive["data"].get("clients").is_some());
+
+        // The receiver keeps its own merged_* values for absent keys.
+        let mut slave = open_test_db();
+        ensure_project_merge_columns(&slave);
+        slave
+            .execute_batch(
+                "INSERT INTO projects (name, color, merged_into, merged_at, updated_at)
+                 VALUES ('Alpha', '#111111', 'Local', '2026-05-01 09:00:00', '2026-05-01 10:00:00');",
+            )
+            .unwrap();
+        merge_incoming_data(&mut slave, &limited).expect("merge limited archive");
+        assert_eq!(query_string(&slave, "SELECT color FROM projects WHERE name = 'Alpha'"), "#222222");
+        assert_eq!(query_string(&slave, "SELECT merged_into FROM projects WHERE name = 'Alpha'"), "Local");
+    }
+
+    fn seed_device_local(conn: &rusqlite::Connection) {
+        conn.execute_batch(
+            "ALTER TABLE projects ADD COLUMN device_local INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE applications ADD COLUMN device_local INTEGER NOT NULL DEFAULT 0;
+             INSERT INTO projects (id, name, updated_at) VALUES (1, 'Work', '2026-06-01 10:00:00');
+             INSERT INTO projects (id, name, device_local, updated_at) VALUES (2, 'Diary', 1, '2026-06-01 10:00:00');
+             INSERT INTO applications (id, executable_name, display_name, project_id, updated_at)
+             VALUES (1, 'code.exe', 'Code', 1, '2026-06-01 10:00:00');
+             INSERT INTO applications (id, executable_name, display_name, project_id, updated_at)
+             VALUES (2, 'journal.exe', 'Journal', 2, '2026-06-01 10:00:00');
+             INSERT INTO applications (id, executable_name, display_name, device_local, updated_at)
+             VALUES (3, 'secret.exe', 'Secret', 1, '2026-06-01 10:00:00');
+             INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES (1, '2026-06-01 08:00:00', '2026-06-01 08:05:00', 300, '2026-06-01', '2026-06-01 10:00:00');
+             INSERT INTO sessions (app_id, project_id, project_name, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES (1, 2, 'Diary', '2026-06-01 09:00:00', '2026-06-01 09:05:00', 300, '2026-06-01', '2026-06-01 10:00:00');
+             INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES (2, '2026-06-01 10:00:00', '2026-06-01 10:05:00', 300, '2026-06-01', '2026-06-01 10:00:00');
+             INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES (3, '2026-06-01 11:00:00', '2026-06-01 11:05:00', 300, '2026-06-01', '2026-06-01 10:00:00');
+             INSERT INTO manual_sessions (title, session_type, project_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES ('Meeting', 'meeting', 1, '2026-06-02 08:00:00', '2026-06-02 09:00:00', 3600, '2026-06-02', '2026-06-02 10:00:00');
+             INSERT INTO manual_sessions (title, session_type, project_id, start_time, end_time, duration_seconds, date, updated_at)
+             VALUES ('Therapy', 'other', 2, '2026-06-02 12:00:00', '2026-06-02 13:00:00', 3600, '2026-06-02', '2026-06-02 10:00:00');
+             INSERT INTO file_activities (app_id, project_id, date, file_name, file_path, total_seconds, first_seen, last_seen)
+             VALUES (1, 1, '2026-06-01', 'main.rs', '/work/main.rs', 60, '2026-06-01 08:00:00', '2026-06-01 08:01:00');
+             INSERT INTO file_activities (app_id, project_id, date, file_name, file_path, total_seconds, first_seen, last_seen)
+             VALUES (2, 2, '2026-06-01', 'today.md', '/diary/today.md', 60, '2026-06-01 10:00:00', '2026-06-01 10:01:00');
+             INSERT INTO tombstones (table_name, record_id, deleted_at, sync_key)
+             VALUES ('projects', 7, '2026-06-01 10:00:00', 'Gone');
+             INSERT INTO tombstones (table_name, record_id, deleted_at, sync_key)
+             VALUES ('sessions', 8, '2026-06-01 10:00:00', 'secret.exe|2026-05-01 08:00:00');
+             INSERT INTO tombstones (table_name, record_id, deleted_at, sync_key)
+             VALUES ('manual_sessions', 9, '2026-06-01 10:00:00', '2|2026-05-01 08:00:00|Old therapy');",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn device_local_rows_never_appear_in_exports() {
+        let conn = open_test_db();
+        seed_device_local(&conn);
+
+        let exports = [
+            ("full", build_full_export(&conn).unwrap()),
+            ("delta", build_delta_export(&conn, None).unwrap().0),
+            ("pull", crate::lan_server::build_delta_for_pull_public(&conn, "1970-01-01 00:00:00").unwrap()),
+        ];
+        for (kind, export) in exports {
+            for private in ["Diary", "journal.exe", "secret.exe", "Therapy", "today.md"] {
+                assert!(!export.contains(private), "{} export leaks '{}'", kind, private);
+            }
+            let archive: serde_json::Value = serde_json::from_str(&export).unwrap();
+            let len = |table: &str| archive["data"][table].as_array().map(|a| a.len()).unwrap_or(0);
+            assert_eq!(len("projects"), 1, "{}", kind);
+            assert_eq!(len("applications"), 1, "{}", kind);
+            assert_eq!(len("sessions"), 1, "{}: the Diary-assigned code.exe session stays home", kind);
+            assert_eq!(len("manual_sessions"), 1, "{}", kind);
+            assert_eq!(len("file_activities"), 1, "{}", kind);
+            assert_eq!(len("tombstones"), 1, "{}", kind);
+            assert_eq!(archive["data"]["tombstones"][0]["sync_key"], "Gone");
+        }
+    }
+
+    #[test]
+    fn device_local_rows_do_not_change_table_hashes() {
+        let with_private = open_test_db();
+        seed_device_local(&with_private);
+
+        // Same shared rows, different private ones.
+        let other = open_test_db();
+        other
+            .execute_batch(
+                "ALTER TABLE projects ADD COLUMN device_local INTEGER NOT NULL DEFAULT 0;
+                 ALTER TABLE applications ADD COLUMN device_local INTEGER NOT NULL DEFAULT 0;
+                 INSERT INTO projects (id, name, updated_at) VALUES (1, 'Work', '2026-06-01 10:00:00');
+                 INSERT INTO projects (id, name, device_local, updated_at) VALUES (5, 'Hobby', 1, '2026-06-03 10:00:00');
+                 INSERT INTO applications (id, executable_name, display_name, project_id, updated_at)
+                 VALUES (1, 'code.exe', 'Code', 1, '2026-06-01 10:00:00');
+                 INSERT INTO applications (id, executable_name, display_name, device_local, updated_at)
+                 VALUES (4, 'game.exe', 'Game', 1, '2026-06-03 10:00:00');
+                 INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+                 VALUES (1, '2026-06-01 08:00:00', '2026-06-01 08:05:00', 300, '2026-06-01', '2026-06-01 10:00:00');
+                 INSERT INTO sessions (app_id, project_id, project_name, start_time, end_time, duration_seconds, date, updated_at)
+                 VALUES (1, 5, 'Hobby', '2026-06-03 09:00:00', '2026-06-03 09:05:00', 300, '2026-06-03', '2026-06-03 10:00:00');
+                 INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+                 VALUES (4, '2026-06-03 11:00:00', '2026-06-03 11:05:00', 300, '2026-06-03', '2026-06-03 10:00:00');
+                 INSERT INTO manual_sessions (title, session_type, project_id, start_time, end_time, duration_seconds, date, updated_at)
+                 VALUES ('Meeting', 'meeting', 1, '2026-06-02 08:00:00', '2026-06-02 09:00:00', 3600, '2026-06-02', '2026-06-02 10:00:00');
+                 INSERT INTO manual_sessions (title, session_type, project_id, start_time, end_time, duration_seconds, date, updated_at)
+                 VALUES ('Practice', 'other', 5, '2026-06-03 12:00:00', '2026-06-03 13:00:00', 3600, '2026-06-03', '2026-06-03 10:00:00');",
+            )
+            .unwrap();
+
+        assert_eq!(
+            compute_tables_hash_string_conn(&with_private),
+            compute_tables_hash_string_conn(&other)
+        );
+        other
+            .execute("UPDATE projects SET updated_at = '2026-06-04 10:00:00' WHERE name = 'Work'", [])
+            .unwrap();
+        assert_ne!(
+            compute_tables_hash_string_conn(&with_private),
+            compute_tables_hash_string_conn(&other),
+            "shared rows still count"
+        );
+    }
+
+    #[test]
+    fn merge_never_applies_peer_tombstones_to_device_local_rows() {
+        let mut conn = open_test_db();
+        seed_device_local(&conn);
+        let tombstone = |table: &str, key: &str| {
+            serde_json::json!({
+                "table_name": table, "record_id": 1, "deleted_at": "2030-01-01 00:00:00", "sync_key": key,
+            })
+        };
+        let peer = serde_json::json!({ "data": { "tombstones": [
+            tombstone("projects", "Diary"),
+            tombstone("applications", "secret.exe"),
+            // code.exe is shared, but one of its sessions belongs to Diary.
+            tombstone("applications", "code.exe"),
+            tombstone("sessions", "journal.exe|2026-06-01 10:00:00"),
+            tombstone("manual_sessions", "2|2026-06-02 12:00:00|Therapy"),
+            tombstone("manual_sessions", "1|2026-06-02 08:00:00|Meeting"),
+        ] } })
+        .to_string();
+        merge_incoming_data(&mut conn, &peer).expect("merge");
+
+        assert_eq!(query_string(&conn, "SELECT name FROM projects WHERE id = 2"), "Diary");
+        let count = |sql: &str| conn.query_row(sql, [], |r| r.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM applications"), 3);
+        assert_eq!(count("SELECT COUNT(*) FROM sessions"), 4);
+        assert_eq!(count("SELECT COUNT(*) FROM manual_sessions WHERE title = 'Therapy'"), 1);
+        // Tombstones for rows that did sync still apply.
+        assert_eq!(count("SELECT COUNT(*) FROM manual_sessions WHERE title = 'Meeting'"), 0);
+    }
 }
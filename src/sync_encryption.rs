@@ -2,7 +2,7 @@ use aes_gcm::aead::{Aead, KeyInit};
 use aes_gcm::{Aes256Gcm, Key};
 use hmac::{Hmac, Mac};
 use serde::Deserialize;
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -19,6 +19,9 @@ pub struct EncryptedCredentials {
     pub encrypted_payload: String, // base64
     pub iv: String,                // base64
     pub tag: String,               // base64
+    /// `key_id` of the encryption_key that sealed it (absent from older servers).
+    #[serde(default)]
+    pub key_id: Option<String>,
 }
 
 /// Decrypted SFTP credentials.
@@ -27,7 +30,7 @@ pub struct EncryptedCredentials {
 pub struct SftpCredentials {
     pub host: String,
     pub port: u16,
-    #[allow(dead_code)] // Present in server JSON but not read directly
+    /// "sftp" or "http" (timeflow-relay) — selects the `RemoteStorage` backend.
     pub protocol: String,
     pub username: String,
     pub password: String,
@@ -64,6 +67,13 @@ fn derive_session_key(master_key: &str, session_id: &str, purpose: &str) -> [u8;
     okm.into()
 }
 
+/// Base64 AES key derived from `master_key` for a local `purpose` (e.g. the
+/// key that wraps a rotated `encryption_key`), usable with `encrypt_file_data`.
+pub fn derive_key_base64(master_key: &str, purpose: &str) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(derive_session_key(master_key, "local", purpose))
+}
+
 fn make_nonce(bytes: &[u8]) -> AesNonce {
     AesNonce::clone_from_slice(bytes)
 }
@@ -106,6 +116,103 @@ pub fn decrypt_credentials(
     serde_json::from_str(&json_str).map_err(|e| format!("JSON parse: {}", e))
 }
 
+/// Short public identifier of a key: first 8 bytes of SHA-256 as hex.
+pub fn key_id(key: &str) -> String {
+    Sha256::digest(key.as_bytes())[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// `decrypt_credentials` over a keyring (current key first, then retired
+/// ones): the key named by the envelope's `keyId`, or each in turn when the
+/// server does not send one.
+pub fn decrypt_credentials_keyring(
+    encrypted: &EncryptedCredentials,
+    session_id: &str,
+    keys: &[String],
+) -> Result<SftpCredentials, String> {
+    open_credentials_keyring(encrypted, session_id, keys).map(|(creds, _)| creds)
+}
+
+/// `decrypt_credentials_keyring` that also returns the key that opened the
+/// envelope — the key the relay seals with.
+pub fn open_credentials_keyring<'k>(
+    encrypted: &EncryptedCredentials,
+    session_id: &str,
+    keys: &'k [String],
+) -> Result<(SftpCredentials, &'k str), String> {
+    let candidates: Vec<&String> = match &encrypted.key_id {
+        Some(id) => keys.iter().filter(|k| key_id(k) == *id).collect(),
+        None => keys.iter().filter(|k| !k.is_empty()).collect(),
+    };
+    if candidates.is_empty() {
+        return Err(match &encrypted.key_id {
+            Some(id) => format!(
+                "Credentials sealed with key {} which is not in the local keyring — update encryption_key",
+                id
+            ),
+            None => "encryption_key not configured".to_string(),
+        });
+    }
+    let mut last_err = String::new();
+    for key in candidates {
+        match decrypt_credentials(encrypted, session_id, key) {
+            Ok(creds) => return Ok((creds, key)),
+            Err(e) => last_err = e,
+        }
+    }
+    Err(last_err)
+}
+
+/// Keyed envelope: `TFK1` + 16-char key id + `encrypt_file_data` output, for
+/// data encrypted under a keyring key — the reader picks the key by id.
+const KEYED_MAGIC: &[u8; 4] = b"TFK1";
+const KEY_ID_LEN: usize = 16;
+
+pub fn encrypt_file_data_keyed(data: &[u8], key_base64: &str) -> Result<Vec<u8>, String> {
+    let body = encrypt_file_data(data, key_base64)?;
+    let mut result = Vec::with_capacity(KEYED_MAGIC.len() + KEY_ID_LEN + body.len());
+    result.extend_from_slice(KEYED_MAGIC);
+    result.extend_from_slice(key_id(key_base64).as_bytes());
+    result.extend_from_slice(&body);
+    Ok(result)
+}
+
+/// Splits a keyed envelope into (key id, `encrypt_file_data` body); `None`
+/// for plain envelopes.
+fn split_keyed(data: &[u8]) -> Result<Option<(&str, &[u8])>, String> {
+    if !data.starts_with(KEYED_MAGIC) {
+        return Ok(None);
+    }
+    let header = KEYED_MAGIC.len() + KEY_ID_LEN;
+    if data.len() < header {
+        return Err("Keyed envelope too short".to_string());
+    }
+    let id = std::str::from_utf8(&data[KEYED_MAGIC.len()..header])
+        .map_err(|_| "Invalid key id in envelope".to_string())?;
+    Ok(Some((id, &data[header..])))
+}
+
+/// Decrypts with the keyring entry matching the envelope's key id, or — for
+/// plain envelopes — tries each key in turn.
+pub fn decrypt_file_data_keyring(data: &[u8], keys: &[String]) -> Result<Vec<u8>, String> {
+    if let Some((id, _)) = split_keyed(data)? {
+        let key = keys.iter().find(|k| key_id(k) == id).ok_or_else(|| {
+            format!("Data encrypted with key {} which is not in the local keyring", id)
+        })?;
+        return decrypt_file_data(data, key);
+    }
+    let mut last_err = "No keys to decrypt with".to_string();
+    for key in keys {
+        match decrypt_file_data(data, key) {
+            Ok(plain) => return Ok(plain),
+            Err(e) => last_err = e,
+        }
+    }
+    Err(last_err)
+}
+
 /// Encrypt file data: gzip compress then AES-256-GCM encrypt.
 /// Output format: [12 bytes IV][ciphertext + 16-byte GCM tag]
 pub fn encrypt_file_data(data: &[u8], key_base64: &str) -> Result<Vec<u8>, String> {
@@ -158,7 +265,8 @@ pub fn encrypt_file_data(data: &[u8], key_base64: &str) -> Result<Vec<u8>, Strin
 }
 
 /// Decrypt file data: AES-256-GCM decrypt then gzip decompress.
-/// Expects input format: [12 bytes IV][ciphertext + 16-byte GCM tag]
+/// Expects input format: [12 bytes IV][ciphertext + 16-byte GCM tag],
+/// optionally inside a keyed envelope whose id must match `key_base64`.
 pub fn decrypt_file_data(data: &[u8], key_base64: &str) -> Result<Vec<u8>, String> {
     use base64::Engine;
     use flate2::read::GzDecoder;
@@ -166,6 +274,12 @@ pub fn decrypt_file_data(data: &[u8], key_base64: &str) -> Result<Vec<u8>, Strin
 
     let engine = base64::engine::general_purpose::STANDARD;
 
+    let data = match split_keyed(data)? {
+        Some((id, body)) if id == key_id(key_base64) => body,
+        Some((id, _)) => return Err(format!("Data encrypted with a different key ({})", id)),
+        None => data,
+    };
+
     if data.len() < 12 {
         return Err("Data too short for IV".to_string());
     }
@@ -214,3 +328,57 @@ pub fn decrypt_file_data(data: &[u8], key_base64: &str) -> Result<Vec<u8>, Strin
 
     Ok(decompressed)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+
+    fn random_key() -> String {
+        let mut key = [0u8; 32];
+        getrandom::getrandom(&mut key).unwrap();
+        base64::engine::general_purpose::STANDARD.encode(key)
+    }
+
+    /// Server side of `decrypt_credentials` (what timeflow-relay does).
+    fn seal_credentials(json: &str, master_key: &str, id: &str, with_id: bool) -> EncryptedCredentials {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let key = derive_session_key(master_key, id, "credential-encryption");
+        let iv = [3u8; 12];
+        let mut sealed = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .encrypt(&make_nonce(&iv), json.as_bytes())
+            .unwrap();
+        let tag = sealed.split_off(sealed.len() - 16);
+        EncryptedCredentials {
+            encrypted_payload: engine.encode(sealed),
+            iv: engine.encode(iv),
+            tag: engine.encode(tag),
+            key_id: with_id.then(|| key_id(master_key)),
+        }
+    }
+
+    #[test]
+    fn keyring_opens_data_and_credentials_sealed_with_a_retired_key() {
+        let (old, new) = (random_key(), random_key());
+        let keyring = vec![new.clone(), old.clone()];
+
+        let envelope = encrypt_file_data_keyed(b"rotated", &old).unwrap();
+        assert_eq!(decrypt_file_data_keyring(&envelope, &keyring).unwrap(), b"rotated");
+        assert_eq!(decrypt_file_data(&envelope, &old).unwrap(), b"rotated");
+        assert!(decrypt_file_data(&envelope, &new).unwrap_err().contains("different key"));
+        let err = decrypt_file_data_keyring(&envelope, &[new.clone()]).unwrap_err();
+        assert!(err.contains(&key_id(&old)), "{}", err);
+        // Plain envelopes still work — each key is tried.
+        let plain = encrypt_file_data(b"legacy", &old).unwrap();
+        assert_eq!(decrypt_file_data_keyring(&plain, &keyring).unwrap(), b"legacy");
+
+        let creds = r#"{"host":"h","port":22,"protocol":"sftp","username":"u","password":"p",
+            "uploadPath":"/up/","downloadPath":"/down/","fileEncryptionKey":"k"}"#;
+        for with_id in [true, false] {
+            let sealed = seal_credentials(creds, &old, "pkg-1", with_id);
+            let opened = decrypt_credentials_keyring(&sealed, "pkg-1", &keyring).unwrap();
+            assert_eq!(opened.upload_path, "/up/");
+            assert!(decrypt_credentials_keyring(&sealed, "pkg-1", &[new.clone()]).is_err());
+        }
+    }
+}
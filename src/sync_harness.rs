@@ -0,0 +1,441 @@
+//! In-process two-node LAN sync harness.
+//!
+//! Each [`Node`] is one daemon inside the test process: its own temp data dir
+//! (device id, LAN secret, paired devices, dashboard DB and backups — all of
+//! it resolved through `config::config_dir()`) and its own LAN server on a
+//! loopback port. Nodes pair through the real `/lan/generate-pairing-code` +
+//! `/lan/initiate-pair` handshake and sync through the master's 13-step flow
+//! against the peer's HTTP endpoints, which covers what the merge-level
+//! simulator in `sync_common` cannot: negotiation, markers, delta windows,
+//! freeze/unfreeze and the slave-side import.
+//!
+//! Projects have no rename path (the name is their sync key), so renames are
+//! exercised on applications, the way `rename_application` does them.
+#![cfg(test)]
+
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::config;
+use crate::lan_common;
+use crate::lan_server::{self, LanSyncState};
+use crate::lan_sync_orchestrator::{self, PeerTarget};
+use crate::sync_common::tests::{open_test_db, seed, user_data_snapshot};
+use crate::tombstone_triggers::CREATE_ALL_TOMBSTONE_TRIGGERS_SQL;
+
+/// The pairing code and the pair throttle are process-wide, so harness tests
+/// run one at a time.
+static HARNESS_LOCK: Mutex<()> = Mutex::new(());
+
+pub(crate) fn harness_lock() -> MutexGuard<'static, ()> {
+    HARNESS_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) struct Node {
+    pub(crate) dir: PathBuf,
+    port: u16,
+    pub(crate) state: Arc<LanSyncState>,
+    stop: Arc<AtomicBool>,
+    server: Option<JoinHandle<()>>,
+}
+
+/// Restores the thread's previous data dir on drop.
+pub(crate) struct DataDirGuard(Option<PathBuf>);
+
+impl Drop for DataDirGuard {
+    fn drop(&mut self) {
+        config::set_data_dir_override(self.0.take());
+    }
+}
+
+impl Node {
+    /// Starts a node whose DB is seeded with `seed_prefix` rows.
+    pub(crate) fn start(name: &str, seed_prefix: &str) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "timeflow-sync-harness-{}-{}-{}",
+            std::process::id(),
+            name,
+            nanos
+        ));
+        std::fs::create_dir_all(dir.join("logs")).expect("node data dir");
+        create_dashboard_db(&dir.join("timeflow_dashboard.db"), seed_prefix);
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind loopback");
+        let port = listener.local_addr().expect("listener addr").port();
+        let state = Arc::new(LanSyncState::new());
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = {
+            let (dir, state, stop) = (dir.clone(), state.clone(), stop.clone());
+            std::thread::spawn(move || {
+                config::set_data_dir_override(Some(dir));
+                lan_server::serve(vec![listener], stop, state);
+            })
+        };
+        Self {
+            dir,
+            port,
+            state,
+            stop,
+            server: Some(server),
+        }
+    }
+
+    /// Makes this node's data dir the current thread's `config_dir()`.
+    pub(crate) fn enter(&self) -> DataDirGuard {
+        let previous = config::data_dir_override();
+        config::set_data_dir_override(Some(self.dir.clone()));
+        DataDirGuard(previous)
+    }
+
+    pub(crate) fn db(&self) -> rusqlite::Connection {
+        rusqlite::Connection::open(self.dir.join("timeflow_dashboard.db")).expect("open node db")
+    }
+
+    pub(crate) fn device_id(&self) -> String {
+        let _dir = self.enter();
+        lan_common::get_device_id()
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<String> {
+        user_data_snapshot(&self.db())
+    }
+
+    fn post(&self, path: &str, body: serde_json::Value) -> serde_json::Value {
+        let resp = ureq::post(&format!("http://127.0.0.1:{}{}", self.port, path))
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+            .unwrap_or_else(|e| panic!("POST {} failed: {}", path, e));
+        let text = resp.into_string().expect("response body");
+        serde_json::from_str(&text).expect("json response")
+    }
+
+    pub(crate) fn count(&self, sql: &str, param: &str) -> i64 {
+        self.db()
+            .query_row(sql, [param], |row| row.get(0))
+            .expect("count query")
+    }
+
+    fn last_sync_was_full(&self) -> bool {
+        self.db()
+            .query_row(
+                "SELECT full_sync FROM sync_markers ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .expect("sync marker")
+            == 1
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(server) = self.server.take() {
+            let _ = server.join();
+        }
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Test schema plus what the dashboard migrations add on top: tombstone
+/// triggers (which rely on the `deleted_at` default), the persistent
+/// `project_name` labels from m20 and HLC columns.
+fn create_dashboard_db(path: &Path, seed_prefix: &str) {
+    let conn = open_test_db();
+    conn.execute_batch(
+        "DROP TABLE tombstones;
+         CREATE TABLE tombstones (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             table_name TEXT NOT NULL,
+             record_id INTEGER,
+             record_uuid TEXT,
+             deleted_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+             sync_key TEXT
+         );",
+    )
+    .expect("tombstones table");
+    for sql in CREATE_ALL_TOMBSTONE_TRIGGERS_SQL {
+        conn.execute_batch(sql).expect("tombstone trigger");
+    }
+    seed(&conn, seed_prefix);
+    conn.execute_batch(
+        "UPDATE sessions SET project_name = (SELECT name FROM projects WHERE id = sessions.project_id);
+         UPDATE manual_sessions
+             SET project_name = (SELECT name FROM projects WHERE id = manual_sessions.project_id);",
+    )
+    .expect("project_name labels");
+    timeflow_shared::sync_hlc::ensure_schema(&conn).expect("hlc schema");
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])
+        .expect("write node db");
+}
+
+/// Pairs the nodes the way the dashboards do: `slave` shows a code, `master`
+/// submits it through its own daemon.
+fn pair(master: &Node, slave: &Node) {
+    let code = slave.post("/lan/generate-pairing-code", json!({}))["code"]
+        .as_str()
+        .expect("pairing code")
+        .to_string();
+    let resp = master.post(
+        "/lan/initiate-pair",
+        json!({ "peer_ip": "127.0.0.1", "peer_port": slave.port, "code": code }),
+    );
+    assert_eq!(resp["ok"], true, "pairing failed: {}", resp);
+    assert_eq!(resp["device_id"], slave.device_id());
+}
+
+fn try_sync(master: &Node, slave: &Node, force: bool) -> Result<(), String> {
+    let peer = PeerTarget {
+        ip: "127.0.0.1".to_string(),
+        port: slave.port,
+        device_id: slave.device_id(),
+    };
+    let _dir = master.enter();
+    let stop = AtomicBool::new(false);
+    lan_sync_orchestrator::execute_master_sync(&peer, &master.state, &stop, force, false)
+        .map(|_| ())
+}
+
+fn sync(master: &Node, slave: &Node, force: bool) {
+    try_sync(master, slave, force).unwrap_or_else(|e| panic!("sync failed: {}", e));
+    assert!(
+        !master.state.db_frozen.load(Ordering::SeqCst),
+        "master left frozen"
+    );
+    assert!(
+        !slave.state.db_frozen.load(Ordering::SeqCst),
+        "slave left frozen"
+    );
+}
+
+pub(crate) fn assert_converged(a: &Node, b: &Node) {
+    let snapshot = a.snapshot();
+    assert!(!snapshot.is_empty());
+    assert_eq!(snapshot, b.snapshot(), "nodes diverged after sync");
+}
+
+/// Paired nodes after their first sync.
+fn synced_pair() -> (Node, Node) {
+    let a = Node::start("a", "A");
+    let b = Node::start("b", "B");
+    pair(&a, &b);
+    sync(&a, &b, false);
+    assert_converged(&a, &b);
+    (a, b)
+}
+
+#[test]
+fn paired_nodes_converge_through_full_delta_and_forced_syncs() {
+    let _lock = harness_lock();
+    let a = Node::start("a", "A");
+    let b = Node::start("b", "B");
+
+    let err = try_sync(&a, &b, false).unwrap_err();
+    assert!(err.starts_with("not_paired"), "unexpected error: {}", err);
+
+    pair(&a, &b);
+    sync(&a, &b, false);
+    assert!(a.last_sync_was_full(), "first sync has no common marker");
+    assert_converged(&a, &b);
+    let union = a.snapshot();
+    assert!(union.iter().any(|row| row.starts_with("P|A-proj-1|")));
+    assert!(union.iter().any(|row| row.starts_with("P|B-proj-1|")));
+
+    b.db()
+        .execute(
+            "INSERT INTO sessions (app_id, start_time, end_time, duration_seconds, date, updated_at)
+             SELECT id, '2026-04-22 08:00:00 B', '2026-04-22 08:30:00 B', 1800, '2026-04-22', datetime('now')
+             FROM applications WHERE executable_name = 'B-app-1.exe'",
+            [],
+        )
+        .unwrap();
+    sync(&a, &b, false);
+    assert!(!a.last_sync_was_full(), "shared marker allows a delta sync");
+    assert_converged(&a, &b);
+    assert_eq!(
+        a.count(
+            "SELECT COUNT(*) FROM sessions WHERE start_time = ?1",
+            "2026-04-22 08:00:00 B"
+        ),
+        1
+    );
+    // Both sides record every run — the refused unpaired attempt included.
+    assert_eq!(
+        a.count(
+            "SELECT COUNT(*) FROM sync_runs WHERE role = 'master' AND status = ?1",
+            "error"
+        ),
+        1
+    );
+    assert_eq!(
+        a.count(
+            "SELECT COUNT(*) FROM sync_runs
+             WHERE role = 'master' AND status = 'ok' AND rows_inserted > 0 AND mode = ?1",
+            "delta"
+        ),
+        1
+    );
+    assert_eq!(
+        b.count(
+            "SELECT COUNT(*) FROM sync_runs WHERE role = 'slave' AND status = ?1",
+            "ok"
+        ),
+        2
+    );
+
+    // Roles swap: pairing is mutual, and force skips the marker negotiation.
+    a.db()
+        .execute(
+            "INSERT INTO manual_sessions (title, session_type, project_id, start_time, end_time,
+                 duration_seconds, date, created_at, updated_at)
+             SELECT 'A-call', 'meeting', id, '2026-04-23 10:00:00', '2026-04-23 11:00:00',
+                 3600, '2026-04-23', datetime('now'), datetime('now')
+             FROM projects WHERE name = 'A-proj-1'",
+            [],
+        )
+        .unwrap();
+    sync(&b, &a, true);
+    assert!(b.last_sync_was_full());
+    assert_converged(&a, &b);
+    assert_eq!(
+        b.count(
+            "SELECT COUNT(*) FROM manual_sessions WHERE title = ?1",
+            "A-call"
+        ),
+        1
+    );
+}
+
+#[test]
+fn deletes_renames_and_merges_converge_and_stay_deleted() {
+    let _lock = harness_lock();
+    let (a, b) = synced_pair();
+
+    // A deletes a project (as delete_project_in_conn does).
+    a.db()
+        .execute_batch(
+            "UPDATE applications SET project_id = NULL
+                 WHERE project_id = (SELECT id FROM projects WHERE name = 'A-proj-3');
+             UPDATE sessions SET project_id = NULL
+                 WHERE project_id = (SELECT id FROM projects WHERE name = 'A-proj-3');
+             DELETE FROM projects WHERE name = 'A-proj-3';",
+        )
+        .unwrap();
+    assert_eq!(
+        a.count(
+            "SELECT COUNT(*) FROM tombstones WHERE table_name = 'projects' AND sync_key = ?1",
+            "A-proj-3"
+        ),
+        1
+    );
+    // B renames a peer's application and merges two projects
+    // (as merge_project_in_conn does).
+    b.db()
+        .execute_batch(
+            "UPDATE applications SET display_name = 'Renamed A App', updated_at = datetime('now')
+                 WHERE executable_name = 'A-app-1.exe';
+             UPDATE projects
+                 SET merged_into = 'B-proj-1', merged_at = datetime('now'),
+                     excluded_at = COALESCE(excluded_at, datetime('now')), updated_at = datetime('now')
+                 WHERE name = 'B-proj-2';",
+        )
+        .unwrap();
+    // Markers are second-resolution: sync in a later second than the delete,
+    // so tombstone GC sees the deletion as delivered.
+    std::thread::sleep(Duration::from_millis(1100));
+
+    sync(&a, &b, false);
+    assert_converged(&a, &b);
+    for node in [&a, &b] {
+        assert_eq!(
+            node.count("SELECT COUNT(*) FROM projects WHERE name = ?1", "A-proj-3"),
+            0
+        );
+        // Both sides have synced past the deletion, so GC dropped the tombstone.
+        assert_eq!(
+            node.count(
+                "SELECT COUNT(*) FROM tombstones WHERE table_name = 'projects' AND sync_key = ?1",
+                "A-proj-3"
+            ),
+            0
+        );
+        assert_eq!(
+            node.count(
+                "SELECT COUNT(*) FROM applications WHERE display_name = ?1",
+                "Renamed A App"
+            ),
+            1
+        );
+        assert_eq!(
+            node.count(
+                "SELECT COUNT(*) FROM projects WHERE name = 'B-proj-2' AND merged_into = ?1",
+                "B-proj-1"
+            ),
+            1
+        );
+    }
+
+    // A session deleted on the slave side travels in its delta pull.
+    b.db()
+        .execute(
+            "DELETE FROM sessions WHERE start_time = ?1",
+            ["2026-04-20 12:00:00 B"],
+        )
+        .unwrap();
+    sync(&a, &b, false);
+    assert_converged(&a, &b);
+    assert_eq!(
+        a.count(
+            "SELECT COUNT(*) FROM sessions WHERE start_time = ?1",
+            "2026-04-20 12:00:00 B"
+        ),
+        0
+    );
+
+    // A forced full snapshot must not resurrect anything already deleted.
+    let before = a.snapshot();
+    sync(&b, &a, true);
+    assert_converged(&a, &b);
+    assert_eq!(a.snapshot(), before);
+}
+
+#[test]
+fn edit_after_peer_delete_outlives_the_tombstone() {
+    let _lock = harness_lock();
+    let (a, b) = synced_pair();
+    let start = "2026-04-20 11:00:00 A";
+
+    a.db()
+        .execute("DELETE FROM sessions WHERE start_time = ?1", [start])
+        .unwrap();
+    // HLCs order the edit after the delete even within the same second.
+    std::thread::sleep(Duration::from_millis(20));
+    b.db()
+        .execute(
+            "UPDATE sessions SET comment = 'kept', updated_at = datetime('now') WHERE start_time = ?1",
+            [start],
+        )
+        .unwrap();
+
+    sync(&a, &b, false);
+    assert_converged(&a, &b);
+    for node in [&a, &b] {
+        assert_eq!(
+            node.count(
+                "SELECT COUNT(*) FROM sessions WHERE start_time = ?1 AND comment = 'kept'",
+                start
+            ),
+            1
+        );
+    }
+}
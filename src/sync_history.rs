@@ -0,0 +1,280 @@
+// sync_history.rs — structured record of every LAN / online sync run.
+//
+// A run is opened when a sync starts (LAN master/slave, online session or
+// async delta), accumulates its mode, transfer sizes and merge statistics in
+// `LanSyncState`, and is written to `sync_runs` in the dashboard DB when it
+// finishes. Rows older than `LogSettings::sync_history_retention_days` are
+// pruned on every write. The dashboard reads the table (m29_sync_runs).
+
+use std::time::Instant;
+
+use crate::sync_preview::SyncPreview;
+
+/// Keep in sync with the dashboard migration m29_sync_runs.
+pub(crate) const SYNC_RUNS_SCHEMA_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS sync_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        kind TEXT NOT NULL,
+        role TEXT NOT NULL DEFAULT '',
+        peer_id TEXT NOT NULL DEFAULT '',
+        mode TEXT NOT NULL DEFAULT '',
+        dry_run INTEGER NOT NULL DEFAULT 0,
+        status TEXT NOT NULL,
+        error TEXT,
+        step_reached INTEGER NOT NULL DEFAULT 0,
+        phase TEXT NOT NULL DEFAULT '',
+        started_at TEXT NOT NULL,
+        finished_at TEXT NOT NULL,
+        duration_ms INTEGER NOT NULL DEFAULT 0,
+        bytes_sent INTEGER NOT NULL DEFAULT 0,
+        bytes_received INTEGER NOT NULL DEFAULT 0,
+        rows_inserted INTEGER NOT NULL DEFAULT 0,
+        rows_updated INTEGER NOT NULL DEFAULT 0,
+        rows_deleted INTEGER NOT NULL DEFAULT 0,
+        tombstones_added INTEGER NOT NULL DEFAULT 0,
+        conflicts INTEGER NOT NULL DEFAULT 0,
+        table_stats TEXT NOT NULL DEFAULT '{}'
+    );
+    CREATE INDEX IF NOT EXISTS idx_sync_runs_started_at ON sync_runs(started_at);
+    CREATE INDEX IF NOT EXISTS idx_sync_runs_peer ON sync_runs(peer_id, started_at);
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Ok,
+    Error,
+    /// Cut short without an error of its own — peer vanished, auto-unfreeze,
+    /// superseded by a newer run.
+    Aborted,
+}
+
+impl RunStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunStatus::Ok => "ok",
+            RunStatus::Error => "error",
+            RunStatus::Aborted => "aborted",
+        }
+    }
+}
+
+/// A sync run in progress. `kind`: "lan" | "online"; `role`: "master" |
+/// "slave" | "" (not decided yet); `mode`: "delta" | "full" | "none" | "async".
+#[derive(Debug, Clone)]
+pub struct SyncRun {
+    pub kind: String,
+    pub role: String,
+    pub peer_id: String,
+    pub mode: String,
+    pub dry_run: bool,
+    pub step_reached: u32,
+    pub phase: String,
+    pub started_at: String,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Summed merge statistics (an async pull may merge several packages).
+    pub merge: SyncPreview,
+    /// Slave side: the peer's data was merged into the local DB.
+    pub imported: bool,
+    /// Last step failure that did not end the run (a retry may still succeed).
+    pub error: Option<String>,
+    started: Instant,
+}
+
+impl SyncRun {
+    pub fn new(kind: &str, role: &str, peer_id: &str, dry_run: bool) -> Self {
+        Self {
+            kind: kind.to_string(),
+            role: role.to_string(),
+            peer_id: peer_id.to_string(),
+            mode: String::new(),
+            dry_run,
+            step_reached: 0,
+            phase: String::new(),
+            started_at: now_string(),
+            bytes_sent: 0,
+            bytes_received: 0,
+            merge: SyncPreview::default(),
+            imported: false,
+            error: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Adds the statistics of one merge (or dry-run preview) to the run.
+    pub fn add_merge(&mut self, stats: &SyncPreview) {
+        for (table, changes) in &stats.tables {
+            let total = self.merge.tables.entry(table.clone()).or_default();
+            total.inserted += changes.inserted;
+            total.updated += changes.updated;
+            total.deleted += changes.deleted;
+        }
+        self.merge.tombstones_added += stats.tombstones_added;
+        self.merge.conflicts += stats.conflicts;
+    }
+}
+
+/// Writes the finished run and prunes rows older than `retention_days`
+/// (0 keeps everything).
+pub(crate) fn persist(
+    conn: &rusqlite::Connection,
+    run: &SyncRun,
+    status: RunStatus,
+    error: Option<&str>,
+    retention_days: u32,
+) -> Result<(), String> {
+    conn.execute_batch(SYNC_RUNS_SCHEMA_SQL)
+        .map_err(|e| e.to_string())?;
+    let sum = |f: fn(&crate::sync_preview::TableChanges) -> i64| -> i64 {
+        run.merge.tables.values().map(f).sum()
+    };
+    let table_stats = serde_json::to_string(&run.merge.tables).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO sync_runs (kind, role, peer_id, mode, dry_run, status, error, step_reached, phase,
+                                started_at, finished_at, duration_ms, bytes_sent, bytes_received,
+                                rows_inserted, rows_updated, rows_deleted, tombstones_added, conflicts, table_stats)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
+        rusqlite::params![
+            run.kind,
+            run.role,
+            run.peer_id,
+            run.mode,
+            run.dry_run,
+            status.as_str(),
+            error,
+            run.step_reached,
+            run.phase,
+            run.started_at,
+            now_string(),
+            run.started.elapsed().as_millis() as i64,
+            run.bytes_sent as i64,
+            run.bytes_received as i64,
+            sum(|t| t.inserted),
+            sum(|t| t.updated),
+            sum(|t| t.deleted),
+            run.merge.tombstones_added,
+            run.merge.conflicts,
+            table_stats,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    if retention_days > 0 {
+        conn.execute(
+            "DELETE FROM sync_runs WHERE started_at < datetime('now', ?1)",
+            [format!("-{} days", retention_days)],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Persists the run into the dashboard DB. Failures are logged, never
+/// propagated — history must not fail a sync.
+pub(crate) fn record(run: &SyncRun, status: RunStatus, error: Option<&str>) {
+    let retention_days = crate::config::load_log_settings().sync_history_retention_days;
+    let result = crate::lan_common::open_dashboard_db()
+        .and_then(|conn| persist(&conn, run, status, error, retention_days));
+    if let Err(e) = result {
+        log::warn!("Nie udalo sie zapisac historii synchronizacji: {}", e);
+    }
+}
+
+fn now_string() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync_preview::TableChanges;
+
+    #[test]
+    fn persist_writes_summed_stats_and_prunes_old_runs() {
+        let conn = rusqlite::Connection::open_in_memory().expect("open");
+        conn.execute_batch(SYNC_RUNS_SCHEMA_SQL).expect("schema");
+        conn.execute(
+            "INSERT INTO sync_runs (kind, status, started_at, finished_at)
+             VALUES ('lan', 'ok', datetime('now', '-40 days'), datetime('now', '-40 days')),
+                    ('lan', 'ok', datetime('now', '-5 days'), datetime('now', '-5 days'))",
+            [],
+        )
+        .expect("old runs");
+
+        let mut run = SyncRun::new("lan", "master", "peer-1", false);
+        run.mode = "delta".into();
+        run.bytes_received = 2048;
+        let mut first = SyncPreview::default();
+        first.tables.insert(
+            "sessions".into(),
+            TableChanges {
+                inserted: 3,
+                updated: 1,
+                deleted: 0,
+            },
+        );
+        first.conflicts = 1;
+        let mut second = SyncPreview::default();
+        second.tables.insert(
+            "sessions".into(),
+            TableChanges {
+                inserted: 2,
+                updated: 0,
+                deleted: 1,
+            },
+        );
+        second.tombstones_added = 1;
+        run.add_merge(&first);
+        run.add_merge(&second);
+
+        persist(&conn, &run, RunStatus::Ok, None, 30).expect("persist");
+
+        let total: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sync_runs", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(
+            total, 2,
+            "the 40-day-old run is pruned, the 5-day-old one kept"
+        );
+        let (peer, mode, status, inserted, updated, deleted, tombstones, conflicts, bytes, tables): (
+            String,
+            String,
+            String,
+            i64,
+            i64,
+            i64,
+            i64,
+            i64,
+            i64,
+            String,
+        ) = conn
+            .query_row(
+                "SELECT peer_id, mode, status, rows_inserted, rows_updated, rows_deleted,
+                        tombstones_added, conflicts, bytes_received, table_stats
+                 FROM sync_runs WHERE peer_id = 'peer-1'",
+                [],
+                |r| {
+                    Ok((
+                        r.get(0)?,
+                        r.get(1)?,
+                        r.get(2)?,
+                        r.get(3)?,
+                        r.get(4)?,
+                        r.get(5)?,
+                        r.get(6)?,
+                        r.get(7)?,
+                        r.get(8)?,
+                        r.get(9)?,
+                    ))
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            (peer.as_str(), mode.as_str(), status.as_str()),
+            ("peer-1", "delta", "ok")
+        );
+        assert_eq!((inserted, updated, deleted), (5, 1, 1));
+        assert_eq!((tombstones, conflicts, bytes), (1, 1, 2048));
+        let tables: serde_json::Value = serde_json::from_str(&tables).unwrap();
+        assert_eq!(tables["sessions"]["inserted"], 5);
+    }
+}
@@ -0,0 +1,475 @@
+//! End-to-end encryption of online sync data keyed by a user passphrase.
+//!
+//! Without a passphrase, blobs are encrypted with the server-issued
+//! `fileEncryptionKey` — which the server (or relay operator) can read. With
+//! `OnlineSyncSettings.passphrase` set, the server key is not used for content:
+//!
+//! - a random 32-byte *content key* encrypts the data (one per group, shared
+//!   by every device that knows the passphrase);
+//! - the content key is wrapped with a key derived from the passphrase by
+//!   Argon2id (random salt, parameters stored with it);
+//! - every blob carries that wrapped key in its header, so a new device only
+//!   needs the passphrase to join, and the device keeps its own copy in
+//!   `sync_passphrase_key.json` in the config dir.
+//!
+//! Devices that seal before they first pull each create a key. Every key a
+//! device sees is kept in its keyring, and all of them seal with the lowest
+//! key id seen, so the group converges on one content key.
+//!
+//! Changing the passphrase re-wraps every key in the keyring (new salt) —
+//! uploaded blobs stay valid, since devices match them to their local copy
+//! by key id.
+//!
+//! Blob format: `TFP1` | header length (u32 LE) | header JSON |
+//! `encrypt_file_data` output under a per-blob key derived from the content
+//! key and the blob's random `fileSalt`.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config;
+use crate::sync_encryption;
+
+const MAGIC: &[u8; 4] = b"TFP1";
+const KEY_FILE: &str = "sync_passphrase_key.json";
+const MAX_HEADER_LEN: usize = 16 * 1024;
+
+// Argon2id cost for new wraps (OWASP: ≥ 19 MiB, t=2). Tests use a cheap
+// setting so debug builds stay fast; the values travel with every wrap.
+#[cfg(not(test))]
+const KDF_MEMORY_KIB: u32 = 64 * 1024;
+#[cfg(test)]
+const KDF_MEMORY_KIB: u32 = 1024;
+const KDF_ITERATIONS: u32 = 3;
+const KDF_PARALLELISM: u32 = 1;
+
+// Upper bounds for parameters read from a received blob — a forged header
+// must not make the daemon allocate gigabytes.
+const MAX_KDF_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_KDF_ITERATIONS: u32 = 16;
+const MAX_KDF_PARALLELISM: u32 = 8;
+
+/// Content key wrapped under a passphrase-derived key.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WrappedKey {
+    /// First 16 hex chars of SHA-256(content key) — identifies the key
+    /// without revealing it.
+    pub key_id: String,
+    pub kdf: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub wrapped_key: String,
+}
+
+/// Every content key this device has sealed or opened with, each wrapped
+/// under the current passphrase.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyRing {
+    /// Key id new blobs are sealed with — the lowest id seen.
+    active: String,
+    keys: Vec<WrappedKey>,
+}
+
+impl KeyRing {
+    fn get(&self, key_id: &str) -> Option<&WrappedKey> {
+        self.keys.iter().find(|k| k.key_id == key_id)
+    }
+
+    fn active_key(&self) -> Option<&WrappedKey> {
+        self.get(&self.active)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlobHeader {
+    v: u32,
+    file_salt: String,
+    key: WrappedKey,
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+fn random<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Random generation failed: {}", e))?;
+    Ok(bytes)
+}
+
+fn key_id(content_key: &[u8; 32]) -> String {
+    Sha256::digest(content_key)[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Argon2id(passphrase, salt). Memoized per (passphrase, salt, params): one
+/// sync run unwraps the same key several times, and each derivation costs
+/// tens of MB and a noticeable fraction of a second.
+fn derive_wrapping_key(passphrase: &str, key: &WrappedKey) -> Result<[u8; 32], String> {
+    if key.kdf != "argon2id" {
+        return Err(format!("Unsupported passphrase KDF: {}", key.kdf));
+    }
+    if key.memory_kib > MAX_KDF_MEMORY_KIB
+        || key.iterations > MAX_KDF_ITERATIONS
+        || key.parallelism > MAX_KDF_PARALLELISM
+    {
+        return Err("Passphrase KDF parameters out of range".to_string());
+    }
+    let salt = b64()
+        .decode(&key.salt)
+        .map_err(|e| format!("Salt decode: {}", e))?;
+
+    static CACHE: OnceLock<Mutex<HashMap<[u8; 32], [u8; 32]>>> = OnceLock::new();
+    let mut fingerprint = Sha256::new();
+    for part in [
+        passphrase.as_bytes(),
+        &salt,
+        &key.memory_kib.to_le_bytes(),
+        &key.iterations.to_le_bytes(),
+        &key.parallelism.to_le_bytes(),
+    ] {
+        fingerprint.update((part.len() as u32).to_le_bytes());
+        fingerprint.update(part);
+    }
+    let fingerprint: [u8; 32] = fingerprint.finalize().into();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(derived) = cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&fingerprint)
+    {
+        return Ok(*derived);
+    }
+
+    let params = Params::new(key.memory_kib, key.iterations, key.parallelism, Some(32))
+        .map_err(|e| format!("Argon2 params: {}", e))?;
+    let mut derived = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut derived)
+        .map_err(|e| format!("Argon2 derivation failed: {}", e))?;
+    cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(fingerprint, derived);
+    Ok(derived)
+}
+
+/// Wraps `content_key` under `passphrase` with a fresh salt.
+pub fn wrap_key(content_key: &[u8; 32], passphrase: &str) -> Result<WrappedKey, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase must not be empty".to_string());
+    }
+    let nonce = random::<12>()?;
+    let mut key = WrappedKey {
+        key_id: key_id(content_key),
+        kdf: "argon2id".to_string(),
+        memory_kib: KDF_MEMORY_KIB,
+        iterations: KDF_ITERATIONS,
+        parallelism: KDF_PARALLELISM,
+        salt: b64().encode(random::<16>()?),
+        nonce: b64().encode(nonce),
+        wrapped_key: String::new(),
+    };
+    let wrapping_key = derive_wrapping_key(passphrase, &key)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&wrapping_key));
+    let wrapped = cipher
+        .encrypt(
+            aes_gcm::Nonce::from_slice(&nonce),
+            Payload {
+                msg: content_key,
+                aad: key.key_id.as_bytes(),
+            },
+        )
+        .map_err(|e| format!("Key wrap failed: {}", e))?;
+    key.wrapped_key = b64().encode(wrapped);
+    Ok(key)
+}
+
+/// Unwraps the content key; fails for a wrong passphrase or a tampered wrap.
+pub fn unwrap_key(key: &WrappedKey, passphrase: &str) -> Result<[u8; 32], String> {
+    let wrapping_key = derive_wrapping_key(passphrase, key)?;
+    let nonce = b64()
+        .decode(&key.nonce)
+        .map_err(|e| format!("Nonce decode: {}", e))?;
+    let wrapped = b64()
+        .decode(&key.wrapped_key)
+        .map_err(|e| format!("Wrapped key decode: {}", e))?;
+    if nonce.len() != 12 {
+        return Err("Invalid wrapped key nonce".to_string());
+    }
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&wrapping_key));
+    let plain = cipher
+        .decrypt(
+            aes_gcm::Nonce::from_slice(&nonce),
+            Payload {
+                msg: &wrapped,
+                aad: key.key_id.as_bytes(),
+            },
+        )
+        .map_err(|_| "Passphrase does not unlock the sync key".to_string())?;
+    let content_key: [u8; 32] = plain
+        .try_into()
+        .map_err(|_| "Invalid content key length".to_string())?;
+    if key_id(&content_key) != key.key_id {
+        return Err("Sync key id mismatch".to_string());
+    }
+    Ok(content_key)
+}
+
+fn key_file() -> Result<std::path::PathBuf, String> {
+    Ok(config::config_dir()
+        .map_err(|e| e.to_string())?
+        .join(KEY_FILE))
+}
+
+fn load_keyring() -> Result<KeyRing, String> {
+    let path = key_file()?;
+    match std::fs::read_to_string(&path) {
+        Ok(raw) => serde_json::from_str(&raw).map_err(|e| format!("Invalid {}: {}", KEY_FILE, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(KeyRing::default()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn save_keyring(ring: &KeyRing) -> Result<(), String> {
+    let path = key_file()?;
+    let json = serde_json::to_string_pretty(ring).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+/// The device's active content key, created on first use.
+fn local_content_key(passphrase: &str) -> Result<(WrappedKey, [u8; 32]), String> {
+    let mut ring = load_keyring()?;
+    if let Some(key) = ring.active_key() {
+        let content_key = unwrap_key(key, passphrase).map_err(|e| {
+            format!(
+                "{} — if the passphrase was changed on purpose, use the passphrase change so the key is re-wrapped",
+                e
+            )
+        })?;
+        return Ok((key.clone(), content_key));
+    }
+    let content_key = random::<32>()?;
+    let key = wrap_key(&content_key, passphrase)?;
+    ring.active = key.key_id.clone();
+    ring.keys.push(key.clone());
+    save_keyring(&ring)?;
+    log::info!("Passphrase sync key {} created", key.key_id);
+    Ok((key, content_key))
+}
+
+fn file_key(content_key: &[u8; 32], file_salt: &[u8]) -> Result<String, String> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(content_key).map_err(|e| e.to_string())?;
+    mac.update(file_salt);
+    mac.update(b"file-encryption");
+    Ok(b64().encode(mac.finalize().into_bytes()))
+}
+
+pub fn is_sealed(blob: &[u8]) -> bool {
+    blob.starts_with(MAGIC)
+}
+
+/// Encrypts `data` for the group sharing `passphrase`.
+pub fn seal(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let (key, content_key) = local_content_key(passphrase)?;
+    let file_salt = random::<16>()?;
+    let header = serde_json::to_vec(&BlobHeader {
+        v: 1,
+        file_salt: b64().encode(file_salt),
+        key,
+    })
+    .map_err(|e| e.to_string())?;
+    let body = sync_encryption::encrypt_file_data(data, &file_key(&content_key, &file_salt)?)?;
+
+    let mut blob = Vec::with_capacity(MAGIC.len() + 4 + header.len() + body.len());
+    blob.extend_from_slice(MAGIC);
+    blob.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    blob.extend_from_slice(&header);
+    blob.extend_from_slice(&body);
+    Ok(blob)
+}
+
+/// Decrypts a blob from `seal`. A blob under a key id in the keyring is opened
+/// with the local copy (works across passphrase changes); any other key is
+/// unwrapped from the header and added to the keyring, becoming the active
+/// key when its id is lower.
+pub fn open(blob: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    if !is_sealed(blob) || blob.len() < MAGIC.len() + 4 {
+        return Err("Not a passphrase-encrypted blob".to_string());
+    }
+    let header_len = u32::from_le_bytes(blob[4..8].try_into().expect("4-byte slice")) as usize;
+    if header_len > MAX_HEADER_LEN || blob.len() < 8 + header_len {
+        return Err("Invalid passphrase blob header".to_string());
+    }
+    let header: BlobHeader = serde_json::from_slice(&blob[8..8 + header_len])
+        .map_err(|e| format!("Passphrase blob header: {}", e))?;
+    if header.v != 1 {
+        return Err(format!("Unsupported passphrase blob version {}", header.v));
+    }
+
+    let mut ring = load_keyring()?;
+    let content_key = match ring.get(&header.key.key_id) {
+        Some(known) => unwrap_key(known, passphrase)?,
+        None => {
+            let content_key = unwrap_key(&header.key, passphrase).map_err(|e| {
+                format!(
+                    "{} (key {}; was it created before a passphrase change?)",
+                    e, header.key.key_id
+                )
+            })?;
+            if ring
+                .active_key()
+                .map_or(true, |active| header.key.key_id < active.key_id)
+            {
+                ring.active = header.key.key_id.clone();
+            }
+            ring.keys.push(header.key.clone());
+            save_keyring(&ring)?;
+            log::info!(
+                "Passphrase sync key {} adopted from peer (active {})",
+                header.key.key_id,
+                ring.active
+            );
+            content_key
+        }
+    };
+
+    let file_salt = b64()
+        .decode(&header.file_salt)
+        .map_err(|e| format!("File salt decode: {}", e))?;
+    sync_encryption::decrypt_file_data(
+        &blob[8 + header_len..],
+        &file_key(&content_key, &file_salt)?,
+    )
+}
+
+/// Re-wraps every key in the keyring under `new_passphrase`. Nothing is
+/// re-encrypted or re-uploaded. Returns the active key id.
+pub fn change_passphrase(old_passphrase: &str, new_passphrase: &str) -> Result<String, String> {
+    let mut ring = load_keyring()?;
+    // An empty keyring is fine: the next sync creates a key under the new passphrase.
+    for key in ring.keys.iter_mut() {
+        let content_key = unwrap_key(key, old_passphrase)
+            .map_err(|_| "Current passphrase is incorrect".to_string())?;
+        *key = wrap_key(&content_key, new_passphrase)?;
+    }
+    save_keyring(&ring)?;
+    log::info!(
+        "Passphrase sync keys re-wrapped ({} key(s), active {})",
+        ring.keys.len(),
+        ring.active
+    );
+    Ok(ring.active)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points this test thread's config dir at a fresh temp directory.
+    fn enter_device(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "timeflow-passphrase-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        config::set_data_dir_override(Some(dir.clone()));
+        dir
+    }
+
+    #[test]
+    fn wrapped_key_opens_only_with_its_passphrase() {
+        let content_key = [7u8; 32];
+        let wrapped = wrap_key(&content_key, "correct horse").unwrap();
+        assert_eq!(unwrap_key(&wrapped, "correct horse").unwrap(), content_key);
+        assert!(unwrap_key(&wrapped, "wrong horse").is_err());
+
+        let mut tampered = wrapped.clone();
+        tampered.key_id = key_id(&[8u8; 32]);
+        assert!(unwrap_key(&tampered, "correct horse").is_err());
+        let mut expensive = wrapped;
+        expensive.memory_kib = MAX_KDF_MEMORY_KIB + 1;
+        assert!(unwrap_key(&expensive, "correct horse").is_err());
+    }
+
+    #[test]
+    fn peer_adopts_the_key_and_history_survives_a_passphrase_change() {
+        let a_dir = enter_device("a");
+        let old_blob = seal(b"delta from before", "old secret").unwrap();
+        assert!(is_sealed(&old_blob));
+        assert!(!old_blob
+            .windows(b"delta from before".len())
+            .any(|w| w == b"delta from before"));
+        let key_id = change_passphrase("old secret", "new secret").unwrap();
+        assert!(change_passphrase("old secret", "newer").is_err());
+        let new_blob = seal(b"delta after", "new secret").unwrap();
+        assert!(seal(b"x", "old secret").is_err());
+
+        // A fresh device knowing only the new passphrase joins through a new
+        // blob, then still reads the package sealed before the change.
+        let b_dir = enter_device("b");
+        assert!(open(&old_blob, "new secret").is_err());
+        assert_eq!(open(&new_blob, "new secret").unwrap(), b"delta after");
+        assert_eq!(load_keyring().unwrap().active, key_id);
+        assert_eq!(open(&old_blob, "new secret").unwrap(), b"delta from before");
+        assert!(open(&new_blob, "guess").is_err());
+        config::set_data_dir_override(None);
+
+        let _ = std::fs::remove_dir_all(a_dir);
+        let _ = std::fs::remove_dir_all(b_dir);
+    }
+
+    #[test]
+    fn devices_that_sealed_independently_keep_history_across_a_passphrase_change() {
+        // Both devices push before their first pull — two content keys.
+        let a_dir = enter_device("indep-a");
+        let from_a = seal(b"from a", "old secret").unwrap();
+        let b_dir = enter_device("indep-b");
+        let from_b = seal(b"from b", "old secret").unwrap();
+        assert_eq!(open(&from_a, "old secret").unwrap(), b"from a");
+        config::set_data_dir_override(Some(a_dir.clone()));
+        assert_eq!(open(&from_b, "old secret").unwrap(), b"from b");
+
+        // Both converge on the lower key id.
+        let active_a = load_keyring().unwrap().active;
+        config::set_data_dir_override(Some(b_dir.clone()));
+        assert_eq!(load_keyring().unwrap().active, active_a);
+
+        for dir in [&a_dir, &b_dir] {
+            config::set_data_dir_override(Some(dir.clone()));
+            assert_eq!(
+                change_passphrase("old secret", "new secret").unwrap(),
+                active_a
+            );
+        }
+
+        // Blobs sealed before the change by the other device still open.
+        assert_eq!(open(&from_a, "new secret").unwrap(), b"from a");
+        config::set_data_dir_override(Some(a_dir.clone()));
+        assert_eq!(open(&from_b, "new secret").unwrap(), b"from b");
+        config::set_data_dir_override(None);
+
+        let _ = std::fs::remove_dir_all(a_dir);
+        let _ = std::fs::remove_dir_all(b_dir);
+    }
+}
@@ -0,0 +1,278 @@
+// sync_preview.rs — dry-run sync: what a merge would change, without keeping it.
+//
+// The merge runs in its normal transaction; before it starts we install TEMP
+// triggers that record every touched row into a TEMP table. After the merge
+// (and the post-merge integrity pass) the recorded changes and project totals
+// are read back and the transaction is rolled back — the temp objects vanish
+// with it. Real merges use the same probes for the per-run statistics in
+// `sync_runs`; they drop them before committing.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sync_common::column_exists;
+
+/// Tables whose row changes are reported per table.
+const PREVIEW_TABLES: [&str; 7] = [
+    "projects",
+    "applications",
+    "sessions",
+    "manual_sessions",
+    "clients",
+    "estimate_settings",
+    "file_activities",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableChanges {
+    pub inserted: i64,
+    pub updated: i64,
+    pub deleted: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectTotalChange {
+    pub project: String,
+    pub seconds_before: i64,
+    pub seconds_after: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncPreview {
+    pub tables: BTreeMap<String, TableChanges>,
+    pub tombstones_added: i64,
+    pub projects_changed: Vec<ProjectTotalChange>,
+    /// LWW conflicts logged to `sync_merge_log` during the merge.
+    #[serde(default)]
+    pub conflicts: i64,
+}
+
+impl SyncPreview {
+    pub fn total_changes(&self) -> i64 {
+        self.tables
+            .values()
+            .map(|t| t.inserted + t.updated + t.deleted)
+            .sum::<i64>()
+            + self.tombstones_added
+    }
+}
+
+/// Installs the change probes. Must run inside the merge transaction; a
+/// rollback removes them, a commit must be preceded by [`remove_change_probes`].
+pub(crate) fn install_change_probes(tx: &rusqlite::Connection) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE TEMP TABLE sync_preview_changes (
+             table_name TEXT NOT NULL,
+             op TEXT NOT NULL,
+             row_id INTEGER NOT NULL,
+             UNIQUE(table_name, op, row_id)
+         );",
+    )
+    .map_err(|e| e.to_string())?;
+
+    for (table, ops) in probed_triggers() {
+        for op in ops {
+            let row = if *op == "DELETE" { "OLD" } else { "NEW" };
+            let sql = format!(
+                "CREATE TEMP TRIGGER sync_preview_{table}_{op_lc} AFTER {op} ON main.{table}
+                 BEGIN
+                     INSERT OR IGNORE INTO sync_preview_changes (table_name, op, row_id)
+                     VALUES ('{table}', '{op_code}', {row}.rowid);
+                 END;",
+                op_lc = op.to_ascii_lowercase(),
+                op_code = &op[..1],
+            );
+            tx.execute_batch(&sql).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Drops the probes so a committing merge leaves no TEMP objects behind on
+/// the (possibly reused) connection.
+pub(crate) fn remove_change_probes(tx: &rusqlite::Connection) -> Result<(), String> {
+    for (table, ops) in probed_triggers() {
+        for op in ops {
+            let sql = format!(
+                "DROP TRIGGER IF EXISTS temp.sync_preview_{table}_{op_lc};",
+                op_lc = op.to_ascii_lowercase()
+            );
+            tx.execute_batch(&sql).map_err(|e| e.to_string())?;
+        }
+    }
+    tx.execute_batch("DROP TABLE IF EXISTS temp.sync_preview_changes;")
+        .map_err(|e| e.to_string())
+}
+
+fn probed_triggers() -> Vec<(&'static str, &'static [&'static str])> {
+    let mut probed: Vec<(&str, &[&str])> = PREVIEW_TABLES
+        .iter()
+        .map(|t| (*t, &["INSERT", "UPDATE", "DELETE"][..]))
+        .collect();
+    probed.push(("tombstones", &["INSERT"][..]));
+    probed
+}
+
+/// Tracked seconds per project name (automatic + manual sessions).
+pub(crate) fn project_totals(conn: &rusqlite::Connection) -> Result<BTreeMap<String, i64>, String> {
+    let hidden_filter = if column_exists(conn, "sessions", "is_hidden") {
+        "AND COALESCE(s.is_hidden, 0) = 0"
+    } else {
+        ""
+    };
+    let sql = format!(
+        "SELECT p.name, SUM(t.secs) FROM (
+             SELECT COALESCE(s.project_id, a.project_id) AS pid, s.duration_seconds AS secs
+             FROM sessions s LEFT JOIN applications a ON a.id = s.app_id
+             WHERE 1 = 1 {hidden_filter}
+             UNION ALL
+             SELECT project_id, duration_seconds FROM manual_sessions
+         ) t
+         JOIN projects p ON p.id = t.pid
+         GROUP BY p.name"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<i64>>(1)?.unwrap_or(0),
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<BTreeMap<_, _>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Reads what the probes recorded and diffs project totals against `before`.
+pub(crate) fn collect(
+    tx: &rusqlite::Connection,
+    totals_before: &BTreeMap<String, i64>,
+) -> Result<SyncPreview, String> {
+    let mut preview = collect_changes(tx)?;
+    let totals_after = project_totals(tx)?;
+    let mut names: Vec<&String> = totals_before.keys().chain(totals_after.keys()).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        let before = totals_before.get(name).copied().unwrap_or(0);
+        let after = totals_after.get(name).copied().unwrap_or(0);
+        if before != after {
+            preview.projects_changed.push(ProjectTotalChange {
+                project: name.clone(),
+                seconds_before: before,
+                seconds_after: after,
+            });
+        }
+    }
+    Ok(preview)
+}
+
+/// Per-table row changes and added tombstones recorded by the probes.
+pub(crate) fn collect_changes(tx: &rusqlite::Connection) -> Result<SyncPreview, String> {
+    let count = |table: &str, sql: &str| -> Result<i64, String> {
+        tx.query_row(sql, [table], |row| row.get(0))
+            .map_err(|e| e.to_string())
+    };
+    // A row inserted by this merge counts once as an insert, never as an update.
+    let mut preview = SyncPreview::default();
+    for table in PREVIEW_TABLES {
+        let changes = TableChanges {
+            inserted: count(
+                table,
+                "SELECT COUNT(*) FROM temp.sync_preview_changes i
+                 WHERE i.table_name = ?1 AND i.op = 'I'
+                   AND NOT EXISTS (SELECT 1 FROM temp.sync_preview_changes d
+                                   WHERE d.table_name = i.table_name AND d.op = 'D'
+                                     AND d.row_id = i.row_id)",
+            )?,
+            updated: count(
+                table,
+                "SELECT COUNT(*) FROM temp.sync_preview_changes u
+                 WHERE u.table_name = ?1 AND u.op = 'U'
+                   AND NOT EXISTS (SELECT 1 FROM temp.sync_preview_changes o
+                                   WHERE o.table_name = u.table_name AND o.op IN ('I', 'D')
+                                     AND o.row_id = u.row_id)",
+            )?,
+            deleted: count(
+                table,
+                "SELECT COUNT(*) FROM temp.sync_preview_changes d
+                 WHERE d.table_name = ?1 AND d.op = 'D'
+                   AND NOT EXISTS (SELECT 1 FROM temp.sync_preview_changes i
+                                   WHERE i.table_name = d.table_name AND i.op = 'I'
+                                     AND i.row_id = d.row_id)",
+            )?,
+        };
+        if changes != TableChanges::default() {
+            preview.tables.insert(table.to_string(), changes);
+        }
+    }
+    preview.tombstones_added = count(
+        "tombstones",
+        "SELECT COUNT(*) FROM temp.sync_preview_changes WHERE table_name = ?1 AND op = 'I'",
+    )?;
+    Ok(preview)
+}
+
+/// Concatenates the `data.*` arrays of several archives, in order, so a batch
+/// of delta packages can be previewed as one merge. Per-record LWW makes that
+/// equivalent to merging the packages one after another.
+pub(crate) fn combine_archives(archives: &[String]) -> Result<String, String> {
+    let mut data = serde_json::Map::new();
+    for raw in archives {
+        let archive: serde_json::Value =
+            serde_json::from_str(raw).map_err(|e| format!("Failed to parse package: {}", e))?;
+        let Some(tables) = archive.get("data").and_then(|d| d.as_object()) else {
+            continue;
+        };
+        for (table, rows) in tables {
+            let Some(rows) = rows.as_array() else {
+                continue;
+            };
+            if let Some(combined) = data
+                .entry(table.clone())
+                .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+                .as_array_mut()
+            {
+                combined.extend(rows.iter().cloned());
+            }
+        }
+    }
+    Ok(serde_json::json!({ "data": data }).to_string())
+}
+
+/// Last dry-run outcome, kept in `LanSyncState` for the dashboard to fetch.
+/// `finished_at == None` means the dry-run is still running.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncPreviewReport {
+    pub sync_type: String,
+    pub peer: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub preview: Option<SyncPreview>,
+    pub error: Option<String>,
+}
+
+impl SyncPreviewReport {
+    pub fn running(sync_type: &str, peer: &str) -> Self {
+        Self {
+            sync_type: sync_type.to_string(),
+            peer: peer.to_string(),
+            started_at: now_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn finish(&mut self, result: Result<SyncPreview, String>) {
+        self.finished_at = Some(now_string());
+        match result {
+            Ok(preview) => self.preview = Some(preview),
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+fn now_string() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
@@ -68,7 +68,7 @@ pub fn trigger_sync(sync_state: Arc<LanSyncState>, force: bool) {
                     state.set_role("master");
                     let stop = Arc::new(AtomicBool::new(false));
                     let handle = crate::lan_sync_orchestrator::run_sync_as_master_with_options(
-                        peer, state, stop, force,
+                        peer, state, stop, force, false,
                     );
                     let _ = handle.join();
                 }
@@ -14,11 +14,13 @@
 //! recorded tombstones, and trigger-minted copies (deleted_at = now) would
 //! propagate onward and defeat updated_at guards on other devices.
 
-pub(crate) const DROP_ALL_TOMBSTONE_TRIGGERS_SQL: [&str; 4] = [
+pub(crate) const DROP_ALL_TOMBSTONE_TRIGGERS_SQL: [&str; 6] = [
     "DROP TRIGGER IF EXISTS trg_sessions_tombstone",
     "DROP TRIGGER IF EXISTS trg_applications_tombstone",
     "DROP TRIGGER IF EXISTS trg_projects_tombstone",
     "DROP TRIGGER IF EXISTS trg_manual_sessions_tombstone",
+    "DROP TRIGGER IF EXISTS trg_clients_tombstone",
+    "DROP TRIGGER IF EXISTS trg_tags_tombstone",
 ];
 
 /// Current version (from m21) — sync_key = executable_name|start_time.
@@ -66,9 +68,31 @@ pub(crate) const MANUAL_SESSIONS_TOMBSTONE_TRIGGER_SQL: &str =
          VALUES ('manual_sessions', OLD.id, OLD.project_id || '|' || OLD.start_time || '|' || OLD.title);
      END;";
 
-pub(crate) const CREATE_ALL_TOMBSTONE_TRIGGERS_SQL: [&str; 4] = [
+/// Version from m25 — sync_key = client name.
+pub(crate) const CLIENTS_TOMBSTONE_TRIGGER_SQL: &str =
+    "CREATE TRIGGER IF NOT EXISTS trg_clients_tombstone
+     AFTER DELETE ON clients
+     FOR EACH ROW
+     BEGIN
+         INSERT INTO tombstones (table_name, record_id, sync_key)
+         VALUES ('clients', OLD.id, OLD.name);
+     END;";
+
+/// Version from m33 — sync_key = tag name.
+pub(crate) const TAGS_TOMBSTONE_TRIGGER_SQL: &str =
+    "CREATE TRIGGER IF NOT EXISTS trg_tags_tombstone
+     AFTER DELETE ON tags
+     FOR EACH ROW
+     BEGIN
+         INSERT INTO tombstones (table_name, record_id, sync_key)
+         VALUES ('tags', OLD.id, OLD.name);
+     END;";
+
+pub(crate) const CREATE_ALL_TOMBSTONE_TRIGGERS_SQL: [&str; 6] = [
     SESSIONS_TOMBSTONE_TRIGGER_SQL,
     APPLICATIONS_TOMBSTONE_TRIGGER_SQL,
     PROJECTS_TOMBSTONE_TRIGGER_SQL,
     MANUAL_SESSIONS_TOMBSTONE_TRIGGER_SQL,
+    CLIENTS_TOMBSTONE_TRIGGER_SQL,
+    TAGS_TOMBSTONE_TRIGGER_SQL,
 ];
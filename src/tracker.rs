@@ -192,6 +192,27 @@ fn save_daily_if_unfrozen(
     true
 }
 
+/// Records a tracker span unless the database is frozen for LAN sync.
+/// Returns `true` when the span was persisted.
+fn record_span_if_unfrozen(
+    store: &mut storage::DailyStore,
+    sync_state: Option<&Arc<crate::lan_server::LanSyncState>>,
+    kind: &str,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> bool {
+    if is_db_frozen(sync_state) {
+        return false;
+    }
+    match store.record_span(kind, start, end) {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("Failed to record {} tracker span: {}", kind, e);
+            false
+        }
+    }
+}
+
 fn should_flush_skipped_save(
     sync_state: Option<&Arc<crate::lan_server::LanSyncState>>,
     save_skipped_while_frozen: bool,
@@ -546,6 +567,9 @@ fn run_loop(stop_signal: Arc<AtomicBool>, foreground_signal: Option<Arc<Foregrou
     // the OS suspended us and no activity should be credited for that gap.
     let mut last_tracking_tick_wall = SystemTime::now();
     write_heartbeat();
+    // Start of the not-yet-persisted "running" span; advanced on every
+    // successful heartbeat so the dashboard can tell daemon-off gaps apart.
+    let mut running_span_start = Local::now();
 
     // Active session state per application
     let mut active_sessions: HashMap<String, Instant> = HashMap::new();
@@ -644,6 +668,17 @@ fn run_loop(stop_signal: Arc<AtomicBool>, foreground_signal: Option<Arc<Foregrou
             if let Err(e) = daily_store.reopen() {
                 log::warn!("DailyStore reopen after sleep failed: {}", e);
             }
+            let woke_at = Local::now();
+            let slept_from = woke_at
+                - chrono::Duration::from_std(sleep_gap).unwrap_or_else(|_| chrono::Duration::zero());
+            record_span_if_unfrozen(
+                &mut daily_store,
+                sync_state.as_ref(),
+                crate::daily_store::TRACKER_SPAN_SLEEP,
+                slept_from,
+                woke_at,
+            );
+            running_span_start = woke_at;
             active_sessions.clear();
             was_idle = true;
             last_tracking_tick = now;
@@ -866,6 +901,26 @@ fn run_loop(stop_signal: Arc<AtomicBool>, foreground_signal: Option<Arc<Foregrou
         if last_heartbeat.elapsed() >= heartbeat_interval {
             write_heartbeat();
             last_heartbeat = Instant::now();
+            let beat_at = Local::now();
+            if record_span_if_unfrozen(
+                &mut daily_store,
+                sync_state.as_ref(),
+                crate::daily_store::TRACKER_SPAN_RUNNING,
+                running_span_start,
+                beat_at,
+            ) {
+                running_span_start = beat_at;
+            }
+            if is_idle {
+                let idle_from = beat_at - chrono::Duration::milliseconds(idle_ms as i64);
+                record_span_if_unfrozen(
+                    &mut daily_store,
+                    sync_state.as_ref(),
+                    crate::daily_store::TRACKER_SPAN_IDLE,
+                    idle_from,
+                    beat_at,
+                );
+            }
         }
 
         // Periodic save (skip while database is frozen for LAN sync)